@@ -0,0 +1,130 @@
+//! Fixtures shared by the benchmark suites
+//!
+//! Every shape has the same number of cells, so a wide and a tall run of
+//! the same benchmark can be compared directly: a slowdown that only shows
+//! up on one of them points at per-row or per-cell overhead respectively.
+
+// Each bench target compiles this module and uses only part of it
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use excelstream::types::{CellStyle, CellValue, StyledCell};
+use excelstream::{ExcelWriter, StringMode};
+
+/// Dimensions of a generated sheet, header row excluded
+#[derive(Debug, Clone, Copy)]
+pub struct Shape {
+    pub name: &'static str,
+    pub cols: usize,
+    pub rows: usize,
+}
+
+impl Shape {
+    pub fn cells(&self) -> u64 {
+        (self.cols * self.rows) as u64
+    }
+}
+
+/// Many columns, few rows
+pub const WIDE: Shape = Shape {
+    name: "wide",
+    cols: 200,
+    rows: 500,
+};
+
+/// Few columns, many rows
+pub const TALL: Shape = Shape {
+    name: "tall",
+    cols: 5,
+    rows: 20_000,
+};
+
+pub const SHAPES: [Shape; 2] = [WIDE, TALL];
+
+/// How many distinct strings a small shared string table holds
+const SMALL_SST_VALUES: usize = 16;
+
+/// Header row `col_0`, `col_1`, ...
+pub fn header(shape: Shape) -> Vec<String> {
+    (0..shape.cols).map(|col| format!("col_{}", col)).collect()
+}
+
+/// Text of the cell at `row`, `col`
+///
+/// With `unique` every cell differs, otherwise values come from a small
+/// pool, as they would in a status or category column.
+pub fn text(row: usize, col: usize, unique: bool) -> String {
+    if unique {
+        format!("value_{}_{}", row, col)
+    } else {
+        format!("category_{}", (row + col) % SMALL_SST_VALUES)
+    }
+}
+
+/// Row of distinct strings
+pub fn string_row(shape: Shape, row: usize) -> Vec<String> {
+    (0..shape.cols).map(|col| text(row, col, true)).collect()
+}
+
+/// Row cycling through integers, floats, strings, booleans and dates
+pub fn typed_row(shape: Shape, row: usize) -> Vec<CellValue> {
+    (0..shape.cols)
+        .map(|col| match col % 5 {
+            0 => CellValue::Int((row * shape.cols + col) as i64),
+            1 => CellValue::Float(row as f64 * 1.25 + col as f64),
+            2 => CellValue::String(text(row, col, false)),
+            3 => CellValue::Bool((row + col).is_multiple_of(2)),
+            _ => CellValue::DateTime(45_000.0 + (row % 3650) as f64),
+        })
+        .collect()
+}
+
+/// [`typed_row`] with a number or date format on every non-text column
+pub fn styled_row(shape: Shape, row: usize) -> Vec<StyledCell> {
+    typed_row(shape, row)
+        .into_iter()
+        .map(|value| {
+            let style = match value {
+                CellValue::Int(_) => CellStyle::NumberInteger,
+                CellValue::Float(_) => CellStyle::NumberCurrency,
+                CellValue::DateTime(_) => CellStyle::DateDefault,
+                _ => CellStyle::Default,
+            };
+            StyledCell::new(value, style)
+        })
+        .collect()
+}
+
+/// Write a workbook of `shape` with a header and string cells kept in the
+/// shared string table
+///
+/// With `unique_strings` the table holds one entry per cell, otherwise a
+/// handful.
+pub fn write_xlsx(path: &Path, shape: Shape, unique_strings: bool) {
+    let mut writer = ExcelWriter::new(path).unwrap();
+    writer.set_string_mode(StringMode::SharedDeduped).unwrap();
+    writer.write_header(header(shape)).unwrap();
+    for row in 0..shape.rows {
+        writer
+            .write_row((0..shape.cols).map(|col| text(row, col, unique_strings)))
+            .unwrap();
+    }
+    writer.save().unwrap();
+}
+
+/// Write a CSV file of `shape` with a header and mixed numeric and text
+/// columns
+pub fn write_csv(path: &Path, shape: Shape) {
+    let mut csv = header(shape).join(",");
+    csv.push('\n');
+    for row in 0..shape.rows {
+        let fields: Vec<String> = typed_row(shape, row)
+            .iter()
+            .map(CellValue::as_string)
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    std::fs::write(path, csv).unwrap();
+}
@@ -0,0 +1,40 @@
+//! CSV to XLSX and XLSX to CSV conversion throughput
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use excelstream::{CsvToXlsxConverter, XlsxToCsvConverter};
+use tempfile::TempDir;
+
+use common::SHAPES;
+
+fn benchmark_conversion(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+
+    for shape in SHAPES {
+        let csv = dir.path().join(format!("{}.csv", shape.name));
+        let xlsx = dir.path().join(format!("{}.xlsx", shape.name));
+        common::write_csv(&csv, shape);
+        common::write_xlsx(&xlsx, shape, true);
+        let out_xlsx = dir.path().join("out.xlsx");
+        let out_csv = dir.path().join("out.csv");
+
+        let mut group = c.benchmark_group(format!("convert/{}", shape.name));
+        group.sample_size(10);
+        group.throughput(Throughput::Elements(shape.cells()));
+
+        group.bench_function(BenchmarkId::from_parameter("csv_to_xlsx"), |b| {
+            let converter = CsvToXlsxConverter::new();
+            b.iter(|| converter.convert(&csv, &out_xlsx).unwrap())
+        });
+        group.bench_function(BenchmarkId::from_parameter("xlsx_to_csv"), |b| {
+            let converter = XlsxToCsvConverter::new();
+            b.iter(|| converter.convert(&xlsx, &out_csv).unwrap())
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, benchmark_conversion);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+//! Parquet to XLSX and XLSX to Parquet conversion throughput
+//!
+//! Run with `cargo bench --features parquet-support --bench parquet_conversion`.
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use excelstream::parquet::{ExcelToParquetConverter, ParquetToExcelConverter};
+use tempfile::TempDir;
+
+use common::SHAPES;
+
+fn benchmark_parquet(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+
+    for shape in SHAPES {
+        let csv = dir.path().join(format!("{}.csv", shape.name));
+        let xlsx = dir.path().join(format!("{}.xlsx", shape.name));
+        let parquet = dir.path().join(format!("{}.parquet", shape.name));
+        // Going through CSV gives the XLSX typed columns to infer a schema from
+        common::write_csv(&csv, shape);
+        excelstream::CsvToXlsxConverter::new()
+            .convert(&csv, &xlsx)
+            .unwrap();
+        ExcelToParquetConverter::new(&xlsx)
+            .unwrap()
+            .convert_to_parquet(&parquet)
+            .unwrap();
+        let out_xlsx = dir.path().join("out.xlsx");
+        let out_parquet = dir.path().join("out.parquet");
+
+        let mut group = c.benchmark_group(format!("parquet/{}", shape.name));
+        group.sample_size(10);
+        group.throughput(Throughput::Elements(shape.cells()));
+
+        group.bench_function(BenchmarkId::from_parameter("xlsx_to_parquet"), |b| {
+            let converter = ExcelToParquetConverter::new(&xlsx).unwrap();
+            b.iter(|| converter.convert_to_parquet(&out_parquet).unwrap())
+        });
+        group.bench_function(BenchmarkId::from_parameter("parquet_to_xlsx"), |b| {
+            let converter = ParquetToExcelConverter::new(&parquet).unwrap();
+            b.iter(|| converter.convert_to_excel(&out_xlsx).unwrap())
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, benchmark_parquet);
+criterion_main!(benches);
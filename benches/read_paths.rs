@@ -0,0 +1,49 @@
+//! Read throughput with a small and a large shared string table
+//!
+//! The small table holds a handful of repeated values; in the large one
+//! every cell is its own entry, so loading the table dominates.
+
+mod common;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use excelstream::ExcelReader;
+use tempfile::TempDir;
+
+use common::SHAPES;
+
+fn benchmark_read(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+
+    for shape in SHAPES {
+        let mut group = c.benchmark_group(format!("read/{}", shape.name));
+        group.sample_size(10);
+        group.throughput(Throughput::Elements(shape.cells()));
+
+        for (sst, unique) in [("small_sst", false), ("large_sst", true)] {
+            let path = dir.path().join(format!("{}_{}.xlsx", shape.name, sst));
+            common::write_xlsx(&path, shape, unique);
+
+            group.bench_function(BenchmarkId::new("rows", sst), |b| {
+                b.iter(|| {
+                    let mut reader = ExcelReader::open(&path).unwrap();
+                    for row in reader.rows_by_index(0).unwrap() {
+                        black_box(row.unwrap());
+                    }
+                })
+            });
+            group.bench_function(BenchmarkId::new("stream_rows", sst), |b| {
+                b.iter(|| {
+                    let mut reader = ExcelReader::open(&path).unwrap();
+                    for row in reader.stream_rows("Sheet1").unwrap() {
+                        black_box(row.unwrap());
+                    }
+                })
+            });
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, benchmark_read);
+criterion_main!(benches);
@@ -0,0 +1,246 @@
+//! Write throughput of each writer for string, typed and styled rows
+//!
+//! Rows are generated before timing, so only the writer is measured. The
+//! `columns` runs write the typed data from column vectors through
+//! `write_columns`, against the `typed` runs which get it as rows.
+//! `ExcelWriter` and `UltraLowMemoryWorkbook` write a complete file;
+//! `FastWorksheet` writes bare sheet XML to a sink, which isolates cell
+//! encoding from compression.
+
+mod common;
+
+use std::io;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use excelstream::fast_writer::shared_strings::SharedStrings;
+use excelstream::fast_writer::{FastWorksheet, UltraLowMemoryWorkbook};
+use excelstream::types::{CellStyle, CellValue, ColumnSlice, StyledCell};
+use excelstream::ExcelWriter;
+use tempfile::TempDir;
+
+use common::{Shape, SHAPES};
+
+struct Fixture {
+    shape: Shape,
+    header: Vec<String>,
+    strings: Vec<Vec<String>>,
+    typed: Vec<Vec<CellValue>>,
+    styled: Vec<Vec<StyledCell>>,
+    // ExcelWriter::write_row_styled takes pairs
+    styled_pairs: Vec<Vec<(CellValue, CellStyle)>>,
+    // The typed rows as columns
+    columns: Vec<Column>,
+}
+
+/// Owned data behind a [`ColumnSlice`]
+enum Column {
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+    String(Vec<String>),
+    Bool(Vec<bool>),
+    DateTime(Vec<f64>),
+}
+
+impl Column {
+    /// Column `col` of the typed rows
+    fn from_rows(rows: &[Vec<CellValue>], col: usize) -> Self {
+        let mut column = match rows.first().map(|row| &row[col]) {
+            Some(CellValue::Int(_)) => Column::Int(Vec::new()),
+            Some(CellValue::Float(_)) => Column::Float(Vec::new()),
+            Some(CellValue::Bool(_)) => Column::Bool(Vec::new()),
+            Some(CellValue::DateTime(_)) => Column::DateTime(Vec::new()),
+            _ => Column::String(Vec::new()),
+        };
+        for row in rows {
+            match (&mut column, &row[col]) {
+                (Column::Int(values), CellValue::Int(v)) => values.push(*v),
+                (Column::Float(values), CellValue::Float(v)) => values.push(*v),
+                (Column::Bool(values), CellValue::Bool(v)) => values.push(*v),
+                (Column::DateTime(values), CellValue::DateTime(v)) => values.push(*v),
+                (Column::String(values), v) => values.push(v.as_string()),
+                _ => unreachable!("typed rows keep one type per column"),
+            }
+        }
+        column
+    }
+
+    fn slice(&self) -> ColumnSlice<'_> {
+        match self {
+            Column::Int(values) => ColumnSlice::Int(values),
+            Column::Float(values) => ColumnSlice::Float(values),
+            Column::String(values) => ColumnSlice::String(values),
+            Column::Bool(values) => ColumnSlice::Bool(values),
+            Column::DateTime(values) => ColumnSlice::DateTime(values),
+        }
+    }
+}
+
+impl Fixture {
+    fn new(shape: Shape) -> Self {
+        let styled: Vec<Vec<StyledCell>> = (0..shape.rows)
+            .map(|row| common::styled_row(shape, row))
+            .collect();
+        let typed: Vec<Vec<CellValue>> = (0..shape.rows)
+            .map(|row| common::typed_row(shape, row))
+            .collect();
+        Fixture {
+            shape,
+            header: common::header(shape),
+            strings: (0..shape.rows)
+                .map(|row| common::string_row(shape, row))
+                .collect(),
+            columns: (0..shape.cols)
+                .map(|col| Column::from_rows(&typed, col))
+                .collect(),
+            typed,
+            styled_pairs: styled
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| (cell.value.clone(), cell.style))
+                        .collect()
+                })
+                .collect(),
+            styled,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    String,
+    Typed,
+    Styled,
+}
+
+const KINDS: [(Kind, &str); 3] = [
+    (Kind::String, "string"),
+    (Kind::Typed, "typed"),
+    (Kind::Styled, "styled"),
+];
+
+fn write_excel_writer(path: &std::path::Path, fixture: &Fixture, kind: Kind) {
+    let mut writer = ExcelWriter::new(path).unwrap();
+    writer.write_header_bold(&fixture.header).unwrap();
+    match kind {
+        Kind::String => {
+            for row in &fixture.strings {
+                writer.write_row(black_box(row)).unwrap();
+            }
+        }
+        Kind::Typed => {
+            for row in &fixture.typed {
+                writer.write_row_typed(black_box(row)).unwrap();
+            }
+        }
+        Kind::Styled => {
+            for row in &fixture.styled_pairs {
+                writer.write_row_styled(black_box(row)).unwrap();
+            }
+        }
+    }
+    writer.save().unwrap();
+}
+
+fn write_ultra_low_memory(path: &std::path::Path, fixture: &Fixture, kind: Kind) {
+    let mut workbook = UltraLowMemoryWorkbook::new(path).unwrap();
+    workbook.add_worksheet("Sheet1").unwrap();
+    workbook.write_row(&fixture.header).unwrap();
+    match kind {
+        Kind::String => {
+            for row in &fixture.strings {
+                workbook.write_row(black_box(row)).unwrap();
+            }
+        }
+        Kind::Typed => {
+            for row in &fixture.typed {
+                workbook.write_row_typed(black_box(row)).unwrap();
+            }
+        }
+        Kind::Styled => {
+            for row in &fixture.styled {
+                workbook.write_row_styled(black_box(row)).unwrap();
+            }
+        }
+    }
+    workbook.close().unwrap();
+}
+
+fn write_excel_writer_columns(path: &std::path::Path, fixture: &Fixture) {
+    let columns: Vec<ColumnSlice> = fixture.columns.iter().map(Column::slice).collect();
+    let mut writer = ExcelWriter::new(path).unwrap();
+    writer.write_header_bold(&fixture.header).unwrap();
+    writer.write_columns(black_box(&columns)).unwrap();
+    writer.save().unwrap();
+}
+
+fn write_ultra_low_memory_columns(path: &std::path::Path, fixture: &Fixture) {
+    let columns: Vec<ColumnSlice> = fixture.columns.iter().map(Column::slice).collect();
+    let mut workbook = UltraLowMemoryWorkbook::new(path).unwrap();
+    workbook.add_worksheet("Sheet1").unwrap();
+    workbook.write_row(&fixture.header).unwrap();
+    workbook.write_columns(black_box(&columns)).unwrap();
+    workbook.close().unwrap();
+}
+
+fn write_fast_worksheet(fixture: &Fixture, kind: Kind) {
+    let mut sheet = FastWorksheet::new(io::sink(), SharedStrings::new()).unwrap();
+    let header: Vec<&str> = fixture.header.iter().map(String::as_str).collect();
+    sheet.write_row(&header).unwrap();
+    match kind {
+        Kind::String => {
+            let mut cells: Vec<&str> = Vec::with_capacity(fixture.shape.cols);
+            for row in &fixture.strings {
+                cells.clear();
+                cells.extend(row.iter().map(String::as_str));
+                sheet.write_row(black_box(&cells)).unwrap();
+            }
+        }
+        Kind::Typed => {
+            for row in &fixture.typed {
+                sheet.write_row_typed(black_box(row)).unwrap();
+            }
+        }
+        Kind::Styled => {
+            for row in &fixture.styled {
+                sheet.write_row_styled(black_box(row)).unwrap();
+            }
+        }
+    }
+    black_box(sheet.finish().unwrap());
+}
+
+fn benchmark_writers(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("out.xlsx");
+
+    for shape in SHAPES {
+        let fixture = Fixture::new(shape);
+        let mut group = c.benchmark_group(format!("write/{}", shape.name));
+        group.sample_size(10);
+        group.throughput(Throughput::Elements(shape.cells()));
+
+        for (kind, kind_name) in KINDS {
+            group.bench_function(BenchmarkId::new("excel_writer", kind_name), |b| {
+                b.iter(|| write_excel_writer(&path, &fixture, kind))
+            });
+            group.bench_function(BenchmarkId::new("ultra_low_memory", kind_name), |b| {
+                b.iter(|| write_ultra_low_memory(&path, &fixture, kind))
+            });
+            group.bench_function(BenchmarkId::new("fast_worksheet", kind_name), |b| {
+                b.iter(|| write_fast_worksheet(&fixture, kind))
+            });
+        }
+        group.bench_function(BenchmarkId::new("excel_writer", "columns"), |b| {
+            b.iter(|| write_excel_writer_columns(&path, &fixture))
+        });
+        group.bench_function(BenchmarkId::new("ultra_low_memory", "columns"), |b| {
+            b.iter(|| write_ultra_low_memory_columns(&path, &fixture))
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, benchmark_writers);
+criterion_main!(benches);
@@ -0,0 +1,31 @@
+//! Compare StreamingReader with calamine on a workbook
+//!
+//! Prints every cell the two readers disagree on. Attach the output when
+//! reporting a reading issue.
+//!
+//! Run with:
+//! ```bash
+//! cargo run --example calamine_compat --features calamine-compat -- data.xlsx [Sheet1]
+//! ```
+
+use excelstream::compat::CalamineCompat;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let path = args
+        .next()
+        .ok_or("usage: calamine_compat <file.xlsx> [sheet]...")?;
+
+    let mut compat = CalamineCompat::new(&path);
+    for sheet in args {
+        compat = compat.sheet(sheet);
+    }
+
+    let report = compat.run()?;
+    print!("{}", report);
+
+    if !report.is_identical() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
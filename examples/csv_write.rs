@@ -7,6 +7,9 @@
 //! - Writing with typed values
 //! - Handling edge cases (quotes, commas, newlines)
 
+#[path = "scenarios/mod.rs"]
+mod scenarios;
+
 use excelstream::types::CellValue;
 use excelstream::{CompressionMethod, CsvWriter};
 use std::error::Error;
@@ -30,25 +33,13 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Example 2: Zstd compressed CSV (large file)
     println!("\n2. Writing Zstd compressed CSV (100K rows)...");
     {
-        let mut writer = CsvWriter::with_compression(
+        let rows = scenarios::csv::write_products(
             "examples/large.csv.zst",
             CompressionMethod::Zstd,
             3, // Level 3 for balanced compression
+            100_000,
         )?;
-
-        writer.write_row(["ID", "Product", "Price", "Quantity"])?;
-
-        for i in 0..100_000 {
-            writer.write_row([
-                &i.to_string(),
-                &format!("Product_{}", i % 1000),
-                &format!("{:.2}", (i as f64 * 0.99) % 100.0),
-                &((i % 50) + 1).to_string(),
-            ])?;
-        }
-
-        println!("   Rows written: {}", writer.row_count());
-        writer.save()?;
+        println!("   Rows written: {}", rows);
         println!("   ✓ Created examples/large.csv.zst");
 
         // Show file size
@@ -107,30 +98,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Example 5: Edge cases (quotes, commas, newlines)
     println!("\n5. Writing edge cases...");
     {
-        let mut writer = CsvWriter::new("examples/edge_cases.csv")?;
-        writer.write_row(["Field Type", "Value", "Description"])?;
-
-        // Comma in field
-        writer.write_row(["Comma", "a,b,c", "Contains commas"])?;
-
-        // Quotes in field
-        writer.write_row(["Quotes", r#"Say "Hello""#, "Contains quotes"])?;
-
-        // Newline in field
-        writer.write_row(["Multiline", "Line 1\nLine 2\nLine 3", "Contains newlines"])?;
-
-        // Mixed special characters
-        writer.write_row([
-            "Complex",
-            r#"Name: "John, Jr.", Age: 30"#,
-            "Quotes and commas",
-        ])?;
-
-        // Empty fields
-        writer.write_row(["Empty", "", "Empty middle field"])?;
-
-        println!("   Rows written: {}", writer.row_count());
-        writer.save()?;
+        let rows = scenarios::csv::write_edge_cases("examples/edge_cases.csv")?;
+        println!("   Rows written: {}", rows);
         println!("   ✓ Created examples/edge_cases.csv");
     }
 
@@ -1,14 +1,13 @@
 //! Example: Stream Excel file directly to Google Cloud Storage (TRUE STREAMING - NO TEMP FILES!)
 //!
 //! This example demonstrates how to generate Excel files and upload them
-//! directly to GCS using s-zip's cloud support - NO temporary files needed!
+//! directly to GCS as a resumable upload - NO temporary files needed!
 //!
 //! Benefits:
 //! - ✅ ZERO disk usage (perfect for Cloud Run/Cloud Functions)
 //! - ✅ Works in read-only filesystems
-//! - ✅ Constant ~4 KB memory usage for buffering
-//! - ✅ TRUE streaming using s-zip's GCSZipWriter
-//! - ✅ Automatic multipart upload
+//! - ✅ Constant memory: one 8 MB chunk is buffered at a time
+//! - ✅ TRUE streaming with a GCS resumable upload
 //!
 //! Prerequisites:
 //! 1. GCP credentials configured (via gcloud or environment variables)
@@ -28,6 +27,10 @@
 //! cargo run --example gcs_streaming --features cloud-gcs
 //! ```
 
+#[cfg(feature = "cloud-gcs")]
+#[path = "scenarios/mod.rs"]
+mod scenarios;
+
 #[cfg(feature = "cloud-gcs")]
 use excelstream::cloud::GCSExcelWriter;
 
@@ -52,35 +55,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build()
         .await?;
 
-    println!("✅ GCS writer initialized (using s-zip's GCSZipWriter)\n");
-
-    // Write header
-    println!("📝 Writing header row...");
-    writer
-        .write_header_bold(["Month", "Product", "Sales", "Profit"])
-        .await?;
+    println!("✅ GCS writer initialized (resumable upload session started)\n");
 
-    // Generate sample data
+    // Write the report
     println!("📊 Writing sales data...");
-    let months = ["January", "February", "March", "April", "May", "June"];
-    let products = ["Laptop", "Phone", "Tablet", "Monitor", "Keyboard"];
-
-    let mut row_count = 0;
-    for month in &months {
-        for product in &products {
-            let sales = (row_count * 1000 + 5000) as f64;
-            let profit = sales * 0.25;
-
-            let sales_str = format!("{:.2}", sales);
-            let profit_str = format!("{:.2}", profit);
-
-            writer
-                .write_row([*month, *product, &sales_str, &profit_str])
-                .await?;
-
-            row_count += 1;
-        }
-    }
+    let row_count = scenarios::sales::FULL_REPORT_ROWS;
+    scenarios::sales::write_to_gcs(&mut writer, row_count).await?;
 
     println!("✅ Wrote {} rows\n", row_count);
 
@@ -14,6 +14,9 @@
 //! curl -o large.xlsx http://localhost:3000/download/large-dataset
 //! ```
 
+#[path = "scenarios/mod.rs"]
+mod scenarios;
+
 use axum::{
     extract::Path,
     http::header,
@@ -125,20 +128,12 @@ async fn generate_large_dataset() -> Response {
     let mut writer = HttpExcelWriter::with_compression(6);
 
     // Write header
-    writer
-        .write_header_bold(["ID", "Name", "Email", "Score", "Status"])
-        .unwrap();
+    writer.write_header_bold(scenarios::users::HEADER).unwrap();
 
     // Generate 10,000 rows
     for i in 1..=10_000 {
         writer
-            .write_row_typed(&[
-                CellValue::Int(i),
-                CellValue::String(format!("User_{}", i)),
-                CellValue::String(format!("user{}@example.com", i)),
-                CellValue::Float(50.0 + (i % 50) as f64),
-                CellValue::String(if i % 3 == 0 { "Active" } else { "Inactive" }.to_string()),
-            ])
+            .write_row_typed(&scenarios::users::user_row(i))
             .unwrap();
 
         if i % 1000 == 0 {
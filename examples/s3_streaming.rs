@@ -23,6 +23,10 @@
 //! cargo run --example s3_streaming --features cloud-s3
 //! ```
 
+#[cfg(feature = "cloud-s3")]
+#[path = "scenarios/mod.rs"]
+mod scenarios;
+
 #[cfg(feature = "cloud-s3")]
 use excelstream::cloud::S3ExcelWriter;
 
@@ -51,33 +55,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("✅ S3 writer initialized (using s-zip's S3ZipWriter)\n");
 
-    // Write header
-    println!("📝 Writing header row...");
-    writer
-        .write_header_bold(["Month", "Product", "Sales", "Profit"])
-        .await?;
-
-    // Generate sample data
+    // Write the report
     println!("📊 Writing sales data...");
-    let months = ["January", "February", "March", "April", "May", "June"];
-    let products = ["Laptop", "Phone", "Tablet", "Monitor", "Keyboard"];
-
-    let mut row_count = 0;
-    for month in &months {
-        for product in &products {
-            let sales = (row_count * 1000 + 5000) as f64;
-            let profit = sales * 0.25;
-
-            let sales_str = format!("{:.2}", sales);
-            let profit_str = format!("{:.2}", profit);
-
-            writer
-                .write_row([*month, *product, &sales_str, &profit_str])
-                .await?;
-
-            row_count += 1;
-        }
-    }
+    let row_count = scenarios::sales::FULL_REPORT_ROWS;
+    scenarios::sales::write_to_s3(&mut writer, row_count).await?;
 
     println!("✅ Wrote {} rows\n", row_count);
 
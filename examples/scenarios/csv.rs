@@ -0,0 +1,57 @@
+//! CSV files used by the `csv_write` example
+
+use excelstream::{CompressionMethod, CsvWriter, Result};
+use std::path::Path;
+
+/// Write a product table with `rows` data rows plus a header
+///
+/// Returns the number of rows written, header included.
+pub fn write_products<P: AsRef<Path>>(
+    path: P,
+    method: CompressionMethod,
+    level: u32,
+    rows: usize,
+) -> Result<u64> {
+    let mut writer = CsvWriter::with_compression(path, method, level)?;
+
+    writer.write_row(["ID", "Product", "Price", "Quantity"])?;
+
+    for i in 0..rows {
+        writer.write_row([
+            &i.to_string(),
+            &format!("Product_{}", i % 1000),
+            &format!("{:.2}", (i as f64 * 0.99) % 100.0),
+            &((i % 50) + 1).to_string(),
+        ])?;
+    }
+
+    let count = writer.row_count();
+    writer.save()?;
+    Ok(count)
+}
+
+/// Fields that need quoting or escaping, as `[field type, value, description]`
+pub const EDGE_CASES: [[&str; 3]; 5] = [
+    ["Comma", "a,b,c", "Contains commas"],
+    ["Quotes", r#"Say "Hello""#, "Contains quotes"],
+    ["Multiline", "Line 1\nLine 2\nLine 3", "Contains newlines"],
+    [
+        "Complex",
+        r#"Name: "John, Jr.", Age: 30"#,
+        "Quotes and commas",
+    ],
+    ["Empty", "", "Empty middle field"],
+];
+
+/// Write [`EDGE_CASES`] below a header row
+pub fn write_edge_cases<P: AsRef<Path>>(path: P) -> Result<u64> {
+    let mut writer = CsvWriter::new(path)?;
+    writer.write_row(["Field Type", "Value", "Description"])?;
+    for row in EDGE_CASES {
+        writer.write_row(row)?;
+    }
+
+    let count = writer.row_count();
+    writer.save()?;
+    Ok(count)
+}
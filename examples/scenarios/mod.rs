@@ -0,0 +1,19 @@
+//! Workloads shared by the examples and `tests/examples_test.rs`
+//!
+//! Each example's `main` only handles configuration and console output and
+//! calls one of these functions to produce the actual file. The integration
+//! tests call the same functions with a small row count, so a change that
+//! breaks an example fails `cargo test` instead of going unnoticed.
+//!
+//! This directory has no `main.rs`, so Cargo does not build it as an example
+//! on its own; users include it with `#[path = "scenarios/mod.rs"] mod scenarios;`.
+
+// Every includer only uses a subset of the scenarios
+#![allow(dead_code)]
+
+pub mod csv;
+pub mod formatting;
+pub mod multi_sheet;
+pub mod protection;
+pub mod sales;
+pub mod users;
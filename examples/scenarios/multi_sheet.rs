@@ -0,0 +1,35 @@
+//! Workbook used by the `multi_sheet` example
+
+use excelstream::writer::ExcelWriterBuilder;
+use excelstream::Result;
+use std::path::Path;
+
+/// Sheet names written by [`write_multi_sheet`], in order
+pub const SHEETS: [&str; 3] = ["Sales", "Employees", "Products"];
+
+/// Write a Sales, Employees and Products sheet with a header and three rows each
+pub fn write_multi_sheet<P: AsRef<Path>>(path: P) -> Result<()> {
+    // Create writer with custom sheet name
+    let mut writer = ExcelWriterBuilder::new(path)
+        .with_sheet_name(SHEETS[0])
+        .build()?;
+
+    writer.write_header(["Month", "Revenue", "Costs", "Profit"])?;
+    writer.write_row(["January", "50000", "30000", "20000"])?;
+    writer.write_row(["February", "55000", "32000", "23000"])?;
+    writer.write_row(["March", "60000", "35000", "25000"])?;
+
+    writer.add_sheet(SHEETS[1])?;
+    writer.write_header(["ID", "Name", "Department", "Salary"])?;
+    writer.write_row(["1", "Alice", "Engineering", "75000"])?;
+    writer.write_row(["2", "Bob", "Sales", "65000"])?;
+    writer.write_row(["3", "Carol", "Marketing", "70000"])?;
+
+    writer.add_sheet(SHEETS[2])?;
+    writer.write_header(["SKU", "Name", "Price", "Stock"])?;
+    writer.write_row(["P001", "Widget A", "19.99", "100"])?;
+    writer.write_row(["P002", "Widget B", "29.99", "50"])?;
+    writer.write_row(["P003", "Widget C", "39.99", "75"])?;
+
+    writer.save()
+}
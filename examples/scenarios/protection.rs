@@ -0,0 +1,114 @@
+//! Workbook used by the `worksheet_protection` example
+
+use excelstream::{CellStyle, CellValue, ExcelWriter, ProtectionOptions, Result};
+use std::path::Path;
+
+/// Sheet names written by [`write_protection_demo`], in order
+pub const SHEETS: [&str; 5] = [
+    "Sheet1",
+    "Read Only",
+    "Selective Permissions",
+    "Data Entry",
+    "No Protection",
+];
+
+/// Write five sheets, each with a different protection setup
+pub fn write_protection_demo<P: AsRef<Path>>(path: P) -> Result<()> {
+    let mut writer = ExcelWriter::new(path)?;
+
+    // ===== Sheet 1: Basic Protection with Password =====
+    let protection = ProtectionOptions::new()
+        .with_password("secret123")
+        .allow_select_locked_cells(true)
+        .allow_select_unlocked_cells(true);
+
+    writer.protect_sheet(protection)?;
+
+    writer.write_row_styled(&[(
+        CellValue::String("Protected Sheet".to_string()),
+        CellStyle::HeaderBold,
+    )])?;
+    writer.write_row([""])?;
+    writer.write_row(["This sheet is protected with password: secret123"])?;
+    writer.write_row(["Users can view but cannot edit without password"])?;
+
+    // ===== Sheet 2: Read-Only Protection (No Password) =====
+    writer.add_sheet(SHEETS[1])?;
+
+    let protection = ProtectionOptions::new(); // No password
+    writer.protect_sheet(protection)?;
+
+    writer.write_row_styled(&[(
+        CellValue::String("Read-Only Sheet".to_string()),
+        CellStyle::HeaderBold,
+    )])?;
+    writer.write_row([""])?;
+    writer.write_row(["This sheet is protected without password"])?;
+    writer.write_row(["Users cannot edit, but can unprotect from Excel menu"])?;
+
+    // ===== Sheet 3: Selective Permissions =====
+    writer.add_sheet(SHEETS[2])?;
+
+    let protection = ProtectionOptions::new()
+        .with_password("format123")
+        .allow_select_locked_cells(true)
+        .allow_select_unlocked_cells(true)
+        .allow_format_cells(true) // Allow formatting
+        .allow_format_columns(true) // Allow column formatting
+        .allow_format_rows(true); // Allow row formatting
+
+    writer.protect_sheet(protection)?;
+
+    writer.write_row_styled(&[(
+        CellValue::String("Selective Permissions".to_string()),
+        CellStyle::HeaderBold,
+    )])?;
+    writer.write_row([""])?;
+    writer.write_row(["Password: format123"])?;
+    writer.write_row(["Users CAN: Format cells, columns, rows"])?;
+    writer.write_row(["Users CANNOT: Insert/delete rows/columns, edit cell values"])?;
+
+    // ===== Sheet 4: Data Entry Sheet =====
+    writer.add_sheet(SHEETS[3])?;
+
+    let protection = ProtectionOptions::new()
+        .with_password("data456")
+        .allow_select_locked_cells(true)
+        .allow_select_unlocked_cells(true)
+        .allow_insert_rows(true) // Allow inserting rows
+        .allow_delete_rows(true) // Allow deleting rows
+        .allow_sort(true); // Allow sorting
+
+    writer.protect_sheet(protection)?;
+
+    writer.write_row_styled(&[
+        (CellValue::String("Name".to_string()), CellStyle::HeaderBold),
+        (
+            CellValue::String("Email".to_string()),
+            CellStyle::HeaderBold,
+        ),
+        (
+            CellValue::String("Phone".to_string()),
+            CellStyle::HeaderBold,
+        ),
+    ])?;
+    writer.write_row(["Alice", "alice@example.com", "555-0001"])?;
+    writer.write_row(["Bob", "bob@example.com", "555-0002"])?;
+    writer.write_row([""])?;
+    writer.write_row(["Password: data456"])?;
+    writer.write_row(["Users CAN: Insert/delete rows, sort data"])?;
+    writer.write_row(["Users CANNOT: Edit header, insert/delete columns"])?;
+
+    // ===== Sheet 5: No Protection (For Comparison) =====
+    writer.add_sheet(SHEETS[4])?;
+
+    writer.write_row_styled(&[(
+        CellValue::String("Unprotected Sheet".to_string()),
+        CellStyle::HeaderBold,
+    )])?;
+    writer.write_row([""])?;
+    writer.write_row(["This sheet has NO protection"])?;
+    writer.write_row(["Users can edit anything"])?;
+
+    writer.save()
+}
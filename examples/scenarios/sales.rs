@@ -0,0 +1,55 @@
+//! Monthly sales report streamed by the `s3_streaming` and `gcs_streaming` examples
+
+#[cfg(feature = "cloud-gcs")]
+use excelstream::cloud::GCSExcelWriter;
+#[cfg(feature = "cloud-s3")]
+use excelstream::cloud::S3ExcelWriter;
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+use excelstream::Result;
+
+/// Header row of the report
+pub const HEADER: [&str; 4] = ["Month", "Product", "Sales", "Profit"];
+
+const MONTHS: [&str; 6] = ["January", "February", "March", "April", "May", "June"];
+const PRODUCTS: [&str; 5] = ["Laptop", "Phone", "Tablet", "Monitor", "Keyboard"];
+
+/// Rows in the full report: every product for every month
+pub const FULL_REPORT_ROWS: usize = MONTHS.len() * PRODUCTS.len();
+
+/// Data row `index` of the report as `[month, product, sales, profit]`
+///
+/// Indexes past [`FULL_REPORT_ROWS`] wrap around to January with growing
+/// sales figures, so callers can ask for any number of rows.
+pub fn sales_row(index: usize) -> [String; 4] {
+    let month = MONTHS[(index / PRODUCTS.len()) % MONTHS.len()];
+    let product = PRODUCTS[index % PRODUCTS.len()];
+    let sales = (index * 1000 + 5000) as f64;
+    let profit = sales * 0.25;
+
+    [
+        month.to_string(),
+        product.to_string(),
+        format!("{:.2}", sales),
+        format!("{:.2}", profit),
+    ]
+}
+
+/// Write the header and `rows` data rows to an S3 writer (without saving)
+#[cfg(feature = "cloud-s3")]
+pub async fn write_to_s3(writer: &mut S3ExcelWriter, rows: usize) -> Result<()> {
+    writer.write_header_bold(HEADER).await?;
+    for index in 0..rows {
+        writer.write_row(sales_row(index)).await?;
+    }
+    Ok(())
+}
+
+/// Write the header and `rows` data rows to a GCS writer (without saving)
+#[cfg(feature = "cloud-gcs")]
+pub async fn write_to_gcs(writer: &mut GCSExcelWriter, rows: usize) -> Result<()> {
+    writer.write_header_bold(HEADER).await?;
+    for index in 0..rows {
+        writer.write_row(sales_row(index)).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,17 @@
+//! User table served by the `http_streaming` example's large-dataset endpoint
+
+use excelstream::CellValue;
+
+/// Header row of the user table
+pub const HEADER: [&str; 5] = ["ID", "Name", "Email", "Score", "Status"];
+
+/// Data row for user `id` (1-based)
+pub fn user_row(id: i64) -> [CellValue; 5] {
+    [
+        CellValue::Int(id),
+        CellValue::String(format!("User_{}", id)),
+        CellValue::String(format!("user{}@example.com", id)),
+        CellValue::Float(50.0 + (id % 50) as f64),
+        CellValue::String(if id % 3 == 0 { "Active" } else { "Inactive" }.to_string()),
+    ]
+}
@@ -0,0 +1,360 @@
+//! Python bindings for excelstream
+//!
+//! ```python
+//! import excelstream
+//!
+//! reader = excelstream.StreamingReader("orders.xlsx")
+//! for row in reader.rows("Orders"):
+//!     print(row)  # tuple of str / int / float / bool / None
+//!
+//! with excelstream.FastWorkbook("out.xlsx") as wb:
+//!     wb.add_worksheet("Data")
+//!     wb.write_row(["id", "amount"])
+//!     wb.write_rows((i, i * 1.5) for i in range(1_000_000))
+//!
+//! excelstream.xlsx_to_csv("orders.xlsx", "orders.csv.gz", sheet="Orders")
+//! ```
+//!
+//! Rows are read on a worker thread and handed over in batches, so the GIL
+//! is only held while a batch is turned into tuples.
+
+use excelstream::convert::{CsvToXlsxConverter, XlsxToCsvConverter};
+use excelstream::fast_writer::UltraLowMemoryWorkbook;
+use excelstream::streaming_reader::StreamingReader as Reader;
+use excelstream::types::{CellValue, Row};
+use excelstream::ExcelError;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyFileNotFoundError, PyIOError, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyFloat, PyInt, PyString, PyTuple};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::thread;
+
+create_exception!(excelstream, ExcelStreamError, PyException);
+
+/// Rows sent from the reader thread at a time
+const BATCH_ROWS: usize = 1024;
+/// Batches waiting for Python before the reader thread blocks
+const QUEUE_DEPTH: usize = 4;
+
+fn to_py_err(error: ExcelError) -> PyErr {
+    match error {
+        ExcelError::FileNotFound(path) => PyFileNotFoundError::new_err(path),
+        ExcelError::IoError(e) => PyIOError::new_err(e.to_string()),
+        other => ExcelStreamError::new_err(other.to_string()),
+    }
+}
+
+fn cell_to_py(py: Python<'_>, cell: CellValue) -> PyResult<PyObject> {
+    let object = match cell {
+        CellValue::Empty => py.None(),
+        CellValue::String(s) | CellValue::BigInt(s) | CellValue::Error(s) => {
+            PyString::new(py, &s).into_any().unbind()
+        }
+        CellValue::Hyperlink { text, .. } => PyString::new(py, &text).into_any().unbind(),
+        CellValue::Int(i) => i.into_pyobject(py)?.into_any().unbind(),
+        CellValue::Float(f) | CellValue::DateTime(f) => PyFloat::new(py, f).into_any().unbind(),
+        CellValue::Bool(b) => PyBool::new(py, b).to_owned().into_any().unbind(),
+        CellValue::Formula { cached, .. } => match cached {
+            Some(value) => cell_to_py(py, *value)?,
+            None => py.None(),
+        },
+        #[allow(unreachable_patterns)]
+        other => PyString::new(py, &other.as_string()).into_any().unbind(),
+    };
+    Ok(object)
+}
+
+fn cell_from_py(value: &Bound<'_, PyAny>) -> PyResult<CellValue> {
+    if value.is_none() {
+        Ok(CellValue::Empty)
+    } else if let Ok(b) = value.downcast::<PyBool>() {
+        // Checked before int: bool is a subclass of int in Python
+        Ok(CellValue::Bool(b.is_true()))
+    } else if value.is_instance_of::<PyInt>() {
+        Ok(CellValue::from(value.extract::<i128>()?))
+    } else if value.is_instance_of::<PyFloat>() {
+        Ok(CellValue::Float(value.extract()?))
+    } else if value.is_instance_of::<PyString>() {
+        Ok(CellValue::String(value.extract()?))
+    } else {
+        Ok(CellValue::String(value.str()?.extract()?))
+    }
+}
+
+/// Streaming XLSX reader
+#[pyclass(module = "excelstream")]
+struct StreamingReader {
+    path: String,
+    sheet_names: Vec<String>,
+}
+
+#[pymethods]
+impl StreamingReader {
+    #[new]
+    fn new(path: String) -> PyResult<Self> {
+        let reader = Reader::open(&path).map_err(to_py_err)?;
+        Ok(StreamingReader {
+            sheet_names: reader.sheet_names(),
+            path,
+        })
+    }
+
+    fn sheet_names(&self) -> Vec<String> {
+        self.sheet_names.clone()
+    }
+
+    /// Iterate over the rows of `sheet` (default: the first sheet) as tuples
+    #[pyo3(signature = (sheet=None))]
+    fn rows(&self, sheet: Option<String>) -> PyResult<RowIterator> {
+        let sheet = match sheet {
+            Some(name) if !self.sheet_names.contains(&name) => {
+                return Err(to_py_err(ExcelError::SheetNotFound {
+                    sheet: name,
+                    available: self.sheet_names.join(", "),
+                }))
+            }
+            Some(name) => name,
+            None => self
+                .sheet_names
+                .first()
+                .cloned()
+                .ok_or_else(|| ExcelStreamError::new_err("workbook has no sheets"))?,
+        };
+
+        let (sender, receiver) = mpsc::sync_channel(QUEUE_DEPTH);
+        let path = self.path.clone();
+        thread::Builder::new()
+            .name("excelstream-read".to_string())
+            .spawn(move || {
+                let result = (|| -> excelstream::Result<()> {
+                    let mut reader = Reader::open(&path)?;
+                    let mut batch = Vec::with_capacity(BATCH_ROWS);
+                    for row in reader.rows(&sheet)? {
+                        batch.push(row?);
+                        if batch.len() == BATCH_ROWS {
+                            let full =
+                                std::mem::replace(&mut batch, Vec::with_capacity(BATCH_ROWS));
+                            if sender.send(Ok(full)).is_err() {
+                                // Iterator dropped by Python
+                                return Ok(());
+                            }
+                        }
+                    }
+                    if !batch.is_empty() {
+                        let _ = sender.send(Ok(batch));
+                    }
+                    Ok(())
+                })();
+                if let Err(e) = result {
+                    let _ = sender.send(Err(e));
+                }
+            })
+            .map_err(|e| PyIOError::new_err(e.to_string()))?;
+
+        Ok(RowIterator {
+            receiver: Mutex::new(receiver),
+            pending: Vec::new().into_iter(),
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("StreamingReader({:?})", self.path)
+    }
+}
+
+type Batch = excelstream::Result<Vec<Row>>;
+
+#[pyclass(module = "excelstream")]
+struct RowIterator {
+    receiver: Mutex<Receiver<Batch>>,
+    pending: std::vec::IntoIter<Row>,
+}
+
+#[pymethods]
+impl RowIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<Py<PyTuple>>> {
+        let row = match self.pending.next() {
+            Some(row) => row,
+            None => {
+                let receiver = &self.receiver;
+                let batch = py.allow_threads(|| match receiver.lock() {
+                    Ok(receiver) => receiver.recv().ok(),
+                    Err(_) => None,
+                });
+                match batch {
+                    Some(batch) => {
+                        self.pending = batch.map_err(to_py_err)?.into_iter();
+                        match self.pending.next() {
+                            Some(row) => row,
+                            None => return Ok(None),
+                        }
+                    }
+                    // Reader thread finished
+                    None => return Ok(None),
+                }
+            }
+        };
+        let cells = row
+            .cells
+            .into_iter()
+            .map(|cell| cell_to_py(py, cell))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Some(PyTuple::new(py, cells)?.unbind()))
+    }
+}
+
+/// Constant-memory XLSX writer
+#[pyclass(module = "excelstream")]
+struct FastWorkbook {
+    inner: Mutex<Option<UltraLowMemoryWorkbook>>,
+}
+
+impl FastWorkbook {
+    fn with<T>(
+        &self,
+        f: impl FnOnce(&mut UltraLowMemoryWorkbook) -> excelstream::Result<T>,
+    ) -> PyResult<T> {
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|_| ExcelStreamError::new_err("workbook lock poisoned"))?;
+        let workbook = inner
+            .as_mut()
+            .ok_or_else(|| ExcelStreamError::new_err("workbook is already closed"))?;
+        f(workbook).map_err(to_py_err)
+    }
+}
+
+#[pymethods]
+impl FastWorkbook {
+    #[new]
+    #[pyo3(signature = (path, compression_level=6))]
+    fn new(path: String, compression_level: u32) -> PyResult<Self> {
+        let workbook = UltraLowMemoryWorkbook::with_compression(&path, compression_level)
+            .map_err(to_py_err)?;
+        Ok(FastWorkbook {
+            inner: Mutex::new(Some(workbook)),
+        })
+    }
+
+    fn add_worksheet(&self, name: &str) -> PyResult<()> {
+        self.with(|wb| wb.add_worksheet(name))
+    }
+
+    /// Write one row; values may be str, int, float, bool or None
+    fn write_row(&self, values: &Bound<'_, PyAny>) -> PyResult<()> {
+        let cells = values
+            .try_iter()?
+            .map(|value| cell_from_py(&value?))
+            .collect::<PyResult<Vec<_>>>()?;
+        self.with(|wb| wb.write_row_typed(&cells))
+    }
+
+    /// Write every row of an iterable of rows
+    fn write_rows(&self, rows: &Bound<'_, PyAny>) -> PyResult<u64> {
+        let mut written = 0;
+        for row in rows.try_iter()? {
+            self.write_row(&row?)?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// Finish the file; the workbook can't be written to afterwards
+    fn close(&self, py: Python<'_>) -> PyResult<()> {
+        let workbook = self
+            .inner
+            .lock()
+            .map_err(|_| ExcelStreamError::new_err("workbook lock poisoned"))?
+            .take();
+        match workbook {
+            Some(workbook) => py.allow_threads(|| workbook.close()).map_err(to_py_err),
+            None => Ok(()),
+        }
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (exc_type=None, _exc_value=None, _traceback=None))]
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        if exc_type.is_some_and(|t| !t.is_none()) {
+            // Leave the partial file alone and let the exception propagate
+            self.inner.lock().ok().and_then(|mut inner| inner.take());
+            return Ok(false);
+        }
+        self.close(py)?;
+        Ok(false)
+    }
+}
+
+/// Stream one sheet of an XLSX file into CSV (compressed for .gz / .zst)
+#[pyfunction]
+#[pyo3(signature = (input, output, sheet=None, delimiter=","))]
+fn xlsx_to_csv(
+    py: Python<'_>,
+    input: String,
+    output: String,
+    sheet: Option<String>,
+    delimiter: &str,
+) -> PyResult<u64> {
+    let mut converter = XlsxToCsvConverter::new().delimiter(delimiter_byte(delimiter)?);
+    if let Some(sheet) = &sheet {
+        converter = converter.sheet(sheet);
+    }
+    py.allow_threads(|| converter.convert(&input, &output))
+        .map_err(to_py_err)
+}
+
+/// Convert a CSV file into a single-sheet XLSX file
+#[pyfunction]
+#[pyo3(signature = (input, output, sheet_name="Sheet1", delimiter=",", has_header=true))]
+fn csv_to_xlsx(
+    py: Python<'_>,
+    input: String,
+    output: String,
+    sheet_name: &str,
+    delimiter: &str,
+    has_header: bool,
+) -> PyResult<u64> {
+    let converter = CsvToXlsxConverter::new()
+        .sheet_name(sheet_name)
+        .delimiter(delimiter_byte(delimiter)?)
+        .has_header(has_header);
+    py.allow_threads(|| converter.convert(&input, &output))
+        .map_err(to_py_err)
+}
+
+fn delimiter_byte(delimiter: &str) -> PyResult<u8> {
+    match delimiter.as_bytes() {
+        [byte] => Ok(*byte),
+        _ => Err(PyTypeError::new_err(
+            "delimiter must be a single ASCII character",
+        )),
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "excelstream")]
+fn excelstream_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("ExcelStreamError", m.py().get_type::<ExcelStreamError>())?;
+    m.add_class::<StreamingReader>()?;
+    m.add_class::<RowIterator>()?;
+    m.add_class::<FastWorkbook>()?;
+    m.add_function(wrap_pyfunction!(xlsx_to_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(csv_to_xlsx, m)?)?;
+    Ok(())
+}
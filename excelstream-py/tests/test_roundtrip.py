@@ -0,0 +1,94 @@
+"""Round trips through the Python bindings
+
+Run with ``maturin develop`` and then ``pytest`` from ``excelstream-py``.
+"""
+
+import pytest
+
+import excelstream
+
+ROWS = [
+    ("id", "name", "amount", "active", "note"),
+    (1, "apple", 1.5, True, None),
+    (2, "pear, ripe", -0.25, False, "two\nlines"),
+    (3, "", 2.75, True, "last"),
+]
+
+
+def write(path, rows, sheet="Data"):
+    with excelstream.FastWorkbook(str(path)) as wb:
+        wb.add_worksheet(sheet)
+        wb.write_rows(rows)
+
+
+def test_write_then_read(tmp_path):
+    path = tmp_path / "out.xlsx"
+    write(path, ROWS)
+
+    reader = excelstream.StreamingReader(str(path))
+    assert reader.sheet_names() == ["Data"]
+    rows = list(reader.rows("Data"))
+    assert rows[0] == ROWS[0]
+    assert rows[1] == ROWS[1]
+    assert rows[2] == ROWS[2]
+    assert rows[3] == ROWS[3]
+    assert isinstance(rows[1][0], int)
+    assert isinstance(rows[1][3], bool)
+
+
+def test_rows_default_to_the_first_sheet(tmp_path):
+    path = tmp_path / "out.xlsx"
+    with excelstream.FastWorkbook(str(path), compression_level=1) as wb:
+        wb.add_worksheet("First")
+        wb.write_row(["a"])
+        wb.add_worksheet("Second")
+        wb.write_row(["b"])
+
+    reader = excelstream.StreamingReader(str(path))
+    assert list(reader.rows()) == [("a",)]
+    assert list(reader.rows("Second")) == [("b",)]
+
+
+def test_many_rows_cross_reader_batches(tmp_path):
+    path = tmp_path / "big.xlsx"
+    count = 5000
+    write(path, ((i, f"item {i}") for i in range(count)))
+
+    rows = list(excelstream.StreamingReader(str(path)).rows())
+    assert len(rows) == count
+    assert rows[-1] == (count - 1, f"item {count - 1}")
+
+
+def test_csv_round_trip(tmp_path):
+    xlsx = tmp_path / "data.xlsx"
+    csv = tmp_path / "data.csv"
+    back = tmp_path / "back.xlsx"
+    write(xlsx, ROWS[:3])
+
+    assert excelstream.xlsx_to_csv(str(xlsx), str(csv), sheet="Data") == 3
+    assert excelstream.csv_to_xlsx(str(csv), str(back), sheet_name="Copy") == 3
+
+    reader = excelstream.StreamingReader(str(back))
+    assert reader.sheet_names() == ["Copy"]
+    rows = [tuple(map(str, row)) for row in reader.rows("Copy")]
+    assert rows[0] == ROWS[0]
+    assert rows[2][1:3] == ("pear, ripe", "-0.25")
+
+
+def test_errors(tmp_path):
+    with pytest.raises(FileNotFoundError):
+        excelstream.StreamingReader(str(tmp_path / "missing.xlsx"))
+
+    path = tmp_path / "out.xlsx"
+    write(path, ROWS)
+    with pytest.raises(excelstream.ExcelStreamError):
+        excelstream.StreamingReader(str(path)).rows("Nope")
+
+    wb = excelstream.FastWorkbook(str(tmp_path / "closed.xlsx"))
+    wb.add_worksheet("Data")
+    wb.close()
+    with pytest.raises(excelstream.ExcelStreamError):
+        wb.write_row([1])
+
+    with pytest.raises(TypeError):
+        excelstream.xlsx_to_csv(str(path), str(tmp_path / "x.csv"), delimiter=";;")
@@ -0,0 +1,13 @@
+//! Valid zip archives holding mutated workbook, relationship, shared
+//! string and sheet parts
+#![no_main]
+
+use excelstream_fuzz::Package;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|package: Package| {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    if package.write(file.path()).is_ok() {
+        excelstream_fuzz::read_all(file.path());
+    }
+});
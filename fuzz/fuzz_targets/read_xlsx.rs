@@ -0,0 +1,12 @@
+//! Arbitrary bytes opened as an XLSX file
+#![no_main]
+
+use std::io::Write;
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(data).unwrap();
+    excelstream_fuzz::read_all(file.path());
+});
@@ -0,0 +1,9 @@
+//! sharedStrings.xml parsing
+#![no_main]
+
+use excelstream::streaming_reader::{fuzzing, ReadLimits};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = fuzzing::shared_strings(data, &ReadLimits::default());
+});
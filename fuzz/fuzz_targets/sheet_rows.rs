@@ -0,0 +1,40 @@
+//! Worksheet row parsing, with formulas, column selection and both read
+//! modes
+#![no_main]
+
+use arbitrary::Arbitrary;
+use excelstream::streaming_reader::fuzzing;
+use excelstream::ReadOptions;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    sheet_xml: Vec<u8>,
+    shared_strings: Vec<String>,
+    evaluate_formulas: bool,
+    columns: Option<Vec<u16>>,
+    lenient: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let options = if input.lenient {
+        ReadOptions::lenient()
+    } else {
+        ReadOptions::default()
+    };
+    // Column numbers a caller could ask for
+    let columns: Option<Vec<usize>> = input.columns.map(|columns| {
+        columns
+            .into_iter()
+            .take(16)
+            .map(|col| usize::from(col) % 16_384)
+            .collect()
+    });
+    fuzzing::rows(
+        &input.sheet_xml,
+        &input.shared_strings,
+        input.evaluate_formulas,
+        columns.as_deref(),
+        options,
+    );
+});
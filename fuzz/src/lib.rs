@@ -0,0 +1,121 @@
+//! Helpers shared by the fuzz targets
+
+use std::path::Path;
+
+use arbitrary::Arbitrary;
+use excelstream::fast_writer::StreamingZipWriter;
+use excelstream::streaming_reader::StreamingReader;
+use excelstream::ReadOptions;
+
+/// Read everything `StreamingReader` can read from the file at `path`, in
+/// strict and lenient mode, with and without formula evaluation
+///
+/// Errors are expected; only panics, hangs and runaway allocations are
+/// failures.
+pub fn read_all(path: &Path) {
+    for options in [ReadOptions::default(), ReadOptions::lenient()] {
+        let Ok(mut reader) = StreamingReader::open_with_options(path, options) else {
+            return;
+        };
+        let _ = reader.sheet_metadata();
+        let defined_names = reader.defined_names().unwrap_or_default();
+
+        for evaluate in [false, true] {
+            reader.evaluate_formulas(evaluate);
+            for name in reader.sheet_names() {
+                let _ = reader.dimensions(&name);
+                if let Ok(rows) = reader.stream_rows(&name) {
+                    rows.for_each(drop);
+                }
+            }
+        }
+        for defined in &defined_names {
+            if let Ok(rows) = reader.rows_for_named_range(&defined.name) {
+                rows.for_each(drop);
+            }
+        }
+        reader.take_warnings();
+    }
+}
+
+/// Content of one package part
+#[derive(Debug, Arbitrary)]
+pub enum Part {
+    /// Left out of the archive
+    Missing,
+    /// The whole part
+    Raw(Vec<u8>),
+    /// Spliced into a valid part, so mutations start past the boilerplate
+    Fragment(String),
+}
+
+/// An XLSX package built around fuzzed parts
+#[derive(Debug, Arbitrary)]
+pub struct Package {
+    /// Goes after the first `<sheet>` of workbook.xml
+    pub workbook: Part,
+    /// Goes after the first relationship of workbook.xml.rels
+    pub relationships: Part,
+    /// Goes after the first `<si>` of sharedStrings.xml
+    pub shared_strings: Part,
+    /// The `<sheetData>` of sheet1.xml
+    pub sheet: Part,
+    /// More entries, by name
+    pub extra: Vec<(String, Vec<u8>)>,
+}
+
+const MAIN_NS: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const REL_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const PACKAGE_REL_NS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+
+impl Package {
+    /// Write the package as a zip archive to `path`
+    pub fn write(&self, path: &Path) -> excelstream::Result<()> {
+        let workbook = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><workbook xmlns="{}" xmlns:r="{}"><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/>{{}}</sheets></workbook>"#,
+            MAIN_NS, REL_NS
+        );
+        let relationships = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="{}"><Relationship Id="rId1" Type="{}/worksheet" Target="worksheets/sheet1.xml"/>{{}}</Relationships>"#,
+            PACKAGE_REL_NS, REL_NS
+        );
+        let shared_strings = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><sst xmlns="{}"><si><t>text</t></si>{{}}</sst>"#,
+            MAIN_NS
+        );
+        let sheet = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><worksheet xmlns="{}"><sheetData>{{}}</sheetData></worksheet>"#,
+            MAIN_NS
+        );
+
+        let mut zip = StreamingZipWriter::new(path)?;
+        for (name, part, template) in [
+            ("xl/workbook.xml", &self.workbook, &workbook),
+            (
+                "xl/_rels/workbook.xml.rels",
+                &self.relationships,
+                &relationships,
+            ),
+            (
+                "xl/sharedStrings.xml",
+                &self.shared_strings,
+                &shared_strings,
+            ),
+            ("xl/worksheets/sheet1.xml", &self.sheet, &sheet),
+        ] {
+            let data = match part {
+                Part::Missing => continue,
+                Part::Raw(data) => data.clone(),
+                Part::Fragment(fragment) => template.replacen("{}", fragment, 1).into_bytes(),
+            };
+            zip.start_entry(name)?;
+            zip.write_data(&data)?;
+        }
+        for (name, data) in self.extra.iter().take(4) {
+            zip.start_entry(name)?;
+            zip.write_data(data)?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+}
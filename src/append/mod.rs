@@ -169,10 +169,14 @@ impl AppendableExcelWriter {
             .map(|cell| match cell {
                 CellValue::String(s) => s.clone(),
                 CellValue::Int(i) => i.to_string(),
+                CellValue::BigInt(s) => s.clone(),
+                CellValue::Hyperlink { text, .. } => text.clone(),
                 CellValue::Float(f) => f.to_string(),
                 CellValue::Bool(b) => b.to_string(),
                 CellValue::Empty => String::new(),
-                CellValue::Formula(f) => f.clone(),
+                CellValue::Formula { expr, .. } => expr.clone(),
+                #[cfg(feature = "decimal")]
+                CellValue::Decimal(d) => d.to_string(),
                 _ => String::new(),
             })
             .collect();
@@ -213,7 +217,12 @@ impl AppendableExcelWriter {
         // Format: <sheet name="SheetName" sheetId="1" r:id="rId1"/>
 
         for line in workbook_xml.lines() {
-            if line.contains("<sheet ") && line.contains(&format!("name=\"{}\"", sheet_name)) {
+            if line.contains("<sheet ")
+                && line.contains(&format!(
+                    "name=\"{}\"",
+                    crate::fast_writer::xml_writer::escape(sheet_name)
+                ))
+            {
                 // Extract sheetId
                 if let Some(start) = line.find("sheetId=\"") {
                     let start = start + 9;
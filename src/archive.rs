@@ -0,0 +1,431 @@
+//! ZIP packages read from a file, a buffer in memory or a remote object
+//!
+//! Files go through s-zip's [`StreamingZipReader`]. Buffers (an upload held
+//! in memory, a browser `ArrayBuffer` under WASM) are read in place: the
+//! central directory is parsed once and entries are inflated straight from
+//! the slice. Cloud objects are read the same way with ranged GETs: the
+//! central directory comes from the end of the object, then each entry is
+//! fetched only when it is read. Either way the parts are handed to the
+//! same XML parsers.
+
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+use crate::cloud::ranged::{BlockingRangedSource, RangedSource};
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::{StreamingZipReader, ZipEntry};
+use flate2::read::DeflateDecoder;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+const LOCAL_FILE_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_DIRECTORY_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR: u32 = 0x0706_4b50;
+/// Extra field holding the 64-bit sizes and offset of an entry
+const ZIP64_EXTRA_FIELD: u16 = 0x0001;
+
+/// Fixed part of the end of central directory record
+const EOCD_SIZE: usize = 22;
+/// Longest archive comment, which may follow the EOCD record
+const MAX_COMMENT: usize = u16::MAX as usize;
+
+/// An opened package
+pub(crate) enum Archive {
+    File(StreamingZipReader),
+    Memory(MemoryArchive),
+    #[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+    Ranged(RangedArchive),
+}
+
+impl Archive {
+    pub(crate) fn open(path: &Path) -> Result<Archive> {
+        Ok(Archive::File(StreamingZipReader::open(path)?))
+    }
+
+    pub(crate) fn from_bytes(data: Vec<u8>) -> Result<Archive> {
+        Ok(Archive::Memory(MemoryArchive::new(data)?))
+    }
+
+    pub(crate) fn entries(&self) -> &[ZipEntry] {
+        match self {
+            Archive::File(zip) => zip.entries(),
+            Archive::Memory(zip) => &zip.entries,
+            #[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+            Archive::Ranged(zip) => &zip.entries,
+        }
+    }
+
+    /// Decompressing reader over `entry`
+    pub(crate) fn read_entry_streaming(&mut self, entry: &ZipEntry) -> Result<Box<dyn Read + '_>> {
+        match self {
+            Archive::File(zip) => Ok(zip.read_entry_streaming(entry)?),
+            Archive::Memory(zip) => zip.read_entry_streaming(entry),
+            #[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+            Archive::Ranged(zip) => zip.read_entry_streaming(entry),
+        }
+    }
+
+    /// Another handle on the same package, for reading on another thread;
+    /// a file is opened again from `path`, a buffer or a remote object is
+    /// shared
+    pub(crate) fn reopen(&self, path: &Path) -> Result<Archive> {
+        match self {
+            Archive::File(_) => Archive::open(path),
+            Archive::Memory(zip) => Ok(Archive::Memory(zip.clone())),
+            #[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+            Archive::Ranged(zip) => Ok(Archive::Ranged(zip.clone())),
+        }
+    }
+}
+
+/// Package held in memory
+#[derive(Clone)]
+pub(crate) struct MemoryArchive {
+    data: Arc<Vec<u8>>,
+    entries: Vec<ZipEntry>,
+}
+
+impl MemoryArchive {
+    fn new(data: Vec<u8>) -> Result<Self> {
+        let entries = match read_directory(&data, 0)? {
+            Directory::Entries(entries) => entries,
+            // Only asked for before the start of `data`
+            Directory::StartsAt(_) => return Err(truncated()),
+        };
+
+        Ok(MemoryArchive {
+            data: Arc::new(data),
+            entries,
+        })
+    }
+
+    fn read_entry_streaming(&self, entry: &ZipEntry) -> Result<Box<dyn Read + '_>> {
+        let header = offset(entry.offset)?;
+        let start = header + local_data_start(slice(&self.data, header, 30)?)?;
+        let compressed = slice(&self.data, start, offset(entry.compressed_size)?)?;
+        inflate(compressed, entry)
+    }
+}
+
+/// Package read from a remote object with ranged GETs
+///
+/// Only the central directory is fetched up front; an entry's bytes are
+/// fetched chunk by chunk as it is read.
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+#[derive(Clone)]
+pub(crate) struct RangedArchive {
+    source: Arc<dyn BlockingRangedSource>,
+    entries: Vec<ZipEntry>,
+    chunk_size: u64,
+}
+
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+impl RangedArchive {
+    /// Fetch the end of the object and read the central directory from it,
+    /// fetching further back when the directory does not fit
+    pub(crate) async fn open<S>(source: S, chunk_size: u64) -> Result<Self>
+    where
+        S: RangedSource + Sync + 'static,
+    {
+        let size = source.size();
+        let mut base = size.saturating_sub(DIRECTORY_GUESS);
+        let mut tail = source.read_range(base, size).await?;
+        let entries = loop {
+            match read_directory(&tail, base)? {
+                Directory::Entries(entries) => break entries,
+                Directory::StartsAt(start) => {
+                    let mut head = source.read_range(start, base).await?;
+                    head.extend_from_slice(&tail);
+                    tail = head;
+                    base = start;
+                }
+            }
+        };
+
+        Ok(RangedArchive {
+            source: Arc::new(source),
+            entries,
+            chunk_size: chunk_size.max(1),
+        })
+    }
+
+    fn read_entry_streaming(&self, entry: &ZipEntry) -> Result<Box<dyn Read + '_>> {
+        let header = self
+            .source
+            .read_range_blocking(entry.offset, entry.offset + 30)?;
+        let start = entry.offset + local_data_start(&header)? as u64;
+        let compressed = RangedEntry {
+            source: self.source.as_ref(),
+            pos: start,
+            end: start + entry.compressed_size,
+            chunk_size: self.chunk_size,
+            chunk: Vec::new(),
+            read: 0,
+        };
+        inflate(compressed, entry)
+    }
+}
+
+/// Bytes fetched from the end of a remote object to find its central
+/// directory; a workbook's directory is usually a few KB
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+const DIRECTORY_GUESS: u64 = 16 * 1024;
+
+/// Compressed bytes of a remote entry, one ranged GET at a time
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+struct RangedEntry<'a> {
+    source: &'a dyn BlockingRangedSource,
+    /// Next byte to fetch, and the end of the entry
+    pos: u64,
+    end: u64,
+    chunk_size: u64,
+    chunk: Vec<u8>,
+    read: usize,
+}
+
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+impl Read for RangedEntry<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.read == self.chunk.len() {
+            if self.pos >= self.end {
+                return Ok(0);
+            }
+            let end = (self.pos + self.chunk_size).min(self.end);
+            self.chunk = self
+                .source
+                .read_range_blocking(self.pos, end)
+                .map_err(std::io::Error::other)?;
+            if self.chunk.is_empty() {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            self.pos += self.chunk.len() as u64;
+            self.read = 0;
+        }
+        let n = buf.len().min(self.chunk.len() - self.read);
+        buf[..n].copy_from_slice(&self.chunk[self.read..self.read + n]);
+        self.read += n;
+        Ok(n)
+    }
+}
+
+/// Decompressing reader over an entry's compressed bytes
+fn inflate<'a, R: Read + 'a>(compressed: R, entry: &ZipEntry) -> Result<Box<dyn Read + 'a>> {
+    match entry.compression_method {
+        0 => Ok(Box::new(compressed)),
+        8 => Ok(Box::new(DeflateDecoder::new(compressed))),
+        method => Err(s_zip::SZipError::UnsupportedCompression(method).into()),
+    }
+}
+
+/// Central directory read from the end of a package
+enum Directory {
+    Entries(Vec<ZipEntry>),
+    /// The directory starts before the bytes at hand; more of the package
+    /// is needed, from this offset on; only asked for by ranged reads
+    #[cfg_attr(
+        not(any(feature = "cloud-s3", feature = "cloud-gcs")),
+        allow(dead_code)
+    )]
+    StartsAt(u64),
+}
+
+/// Read the central directory from `tail`, the bytes of the package from
+/// offset `base` to its end
+fn read_directory(tail: &[u8], base: u64) -> Result<Directory> {
+    let eocd = match find_eocd(tail) {
+        Ok(eocd) => eocd,
+        // The record may sit behind a comment longer than the tail
+        Err(_) if base > 0 && tail.len() < EOCD_SIZE + MAX_COMMENT => {
+            let missing = (EOCD_SIZE + MAX_COMMENT - tail.len()) as u64;
+            return Ok(Directory::StartsAt(base.saturating_sub(missing)));
+        }
+        Err(e) => return Err(e),
+    };
+    let mut count = u64::from(u16_at(tail, eocd + 10)?);
+    let mut cd_offset = u64::from(u32_at(tail, eocd + 16)?);
+
+    if count == 0xFFFF || cd_offset == 0xFFFF_FFFF {
+        // The ZIP64 locator sits right before the EOCD record
+        let Some(locator) = eocd.checked_sub(20) else {
+            return match base {
+                0 => Err(truncated()),
+                _ => Ok(Directory::StartsAt(base.saturating_sub(20))),
+            };
+        };
+        if u32_at(tail, locator)? != ZIP64_LOCATOR {
+            return Err(invalid("missing ZIP64 end of central directory locator"));
+        }
+        let record_offset = u64_at(tail, locator + 8)?;
+        let Some(record) = record_offset.checked_sub(base) else {
+            return Ok(Directory::StartsAt(record_offset));
+        };
+        let record = offset(record)?;
+        if u32_at(tail, record)? != ZIP64_END_OF_CENTRAL_DIRECTORY {
+            return Err(invalid("invalid ZIP64 end of central directory signature"));
+        }
+        count = u64_at(tail, record + 32)?;
+        cd_offset = u64_at(tail, record + 48)?;
+    }
+
+    let Some(start) = cd_offset.checked_sub(base) else {
+        return Ok(Directory::StartsAt(cd_offset));
+    };
+
+    // The count comes from the file, so nothing is reserved up front
+    let mut entries = Vec::new();
+    let mut pos = offset(start)?;
+    for _ in 0..count {
+        if u32_at(tail, pos)? != CENTRAL_DIRECTORY_HEADER {
+            return Err(invalid("invalid central directory header signature"));
+        }
+        let compression_method = u16_at(tail, pos + 10)?;
+        let mut compressed_size = u64::from(u32_at(tail, pos + 20)?);
+        let mut uncompressed_size = u64::from(u32_at(tail, pos + 24)?);
+        let name_len = usize::from(u16_at(tail, pos + 28)?);
+        let extra_len = usize::from(u16_at(tail, pos + 30)?);
+        let comment_len = usize::from(u16_at(tail, pos + 32)?);
+        let mut local_offset = u64::from(u32_at(tail, pos + 42)?);
+
+        let name = String::from_utf8_lossy(slice(tail, pos + 46, name_len)?).into_owned();
+        let extra = slice(tail, pos + 46 + name_len, extra_len)?;
+        // The ZIP64 field holds only the values saturated above, in this
+        // order
+        if let Some(mut field) = zip64_extra(extra)? {
+            for value in [
+                &mut uncompressed_size,
+                &mut compressed_size,
+                &mut local_offset,
+            ] {
+                if *value == 0xFFFF_FFFF {
+                    *value = u64_at(field, 0)?;
+                    field = &field[8..];
+                }
+            }
+        }
+
+        entries.push(ZipEntry {
+            name,
+            compressed_size,
+            uncompressed_size,
+            compression_method,
+            offset: local_offset,
+        });
+        pos += 46 + name_len + extra_len + comment_len;
+    }
+    Ok(Directory::Entries(entries))
+}
+
+/// Data offset of the entry whose local file header starts `header`
+fn local_data_start(header: &[u8]) -> Result<usize> {
+    if u32_at(header, 0)? != LOCAL_FILE_HEADER {
+        return Err(invalid("invalid local file header signature"));
+    }
+    // The local header has its own name and extra field lengths
+    Ok(30 + usize::from(u16_at(header, 26)?) + usize::from(u16_at(header, 28)?))
+}
+
+/// Position of the end of central directory record, searched backwards
+/// past an archive comment
+fn find_eocd(data: &[u8]) -> Result<usize> {
+    let last = data.len().checked_sub(EOCD_SIZE).ok_or_else(truncated)?;
+    let first = last.saturating_sub(MAX_COMMENT);
+    (first..=last)
+        .rev()
+        .find(|&pos| data[pos..pos + 4] == END_OF_CENTRAL_DIRECTORY.to_le_bytes())
+        .ok_or_else(|| invalid("end of central directory not found"))
+}
+
+/// Data of the ZIP64 extended information field, if present
+fn zip64_extra(mut extra: &[u8]) -> Result<Option<&[u8]>> {
+    while extra.len() >= 4 {
+        let id = u16_at(extra, 0)?;
+        let len = usize::from(u16_at(extra, 2)?);
+        let data = slice(extra, 4, len)?;
+        if id == ZIP64_EXTRA_FIELD {
+            return Ok(Some(data));
+        }
+        extra = &extra[4 + len..];
+    }
+    Ok(None)
+}
+
+fn slice(data: &[u8], pos: usize, len: usize) -> Result<&[u8]> {
+    pos.checked_add(len)
+        .and_then(|end| data.get(pos..end))
+        .ok_or_else(truncated)
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Result<u16> {
+    let bytes = slice(data, pos, 2)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Result<u32> {
+    let bytes = slice(data, pos, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into().expect("4 bytes")))
+}
+
+fn u64_at(data: &[u8], pos: usize) -> Result<u64> {
+    let bytes = slice(data, pos, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+}
+
+fn offset(value: u64) -> Result<usize> {
+    usize::try_from(value).map_err(|_| truncated())
+}
+
+fn invalid(message: &str) -> ExcelError {
+    ExcelError::InvalidFormat(format!("ZIP: {}", message))
+}
+
+fn truncated() -> ExcelError {
+    invalid("archive is truncated")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fast_writer::StreamingZipWriter;
+
+    fn package() -> Vec<u8> {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        let mut zip = StreamingZipWriter::with_compression(temp.path(), 6).unwrap();
+        for (name, text) in [("a.xml", "<a/>"), ("dir/b.xml", "<b>text</b>")] {
+            zip.start_entry(name).unwrap();
+            zip.write_data(text.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        std::fs::read(temp.path()).unwrap()
+    }
+
+    #[test]
+    fn test_reads_entries_like_the_file_reader() {
+        let data = package();
+        let mut archive = Archive::from_bytes(data).unwrap();
+        let names: Vec<_> = archive.entries().iter().map(|e| e.name.clone()).collect();
+        assert_eq!(names, vec!["a.xml", "dir/b.xml"]);
+
+        let entry = archive.entries()[1].clone();
+        let mut text = String::new();
+        archive
+            .read_entry_streaming(&entry)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "<b>text</b>");
+    }
+
+    #[test]
+    fn test_rejects_truncated_archives() {
+        let data = package();
+        for len in [0, 10, data.len() / 2] {
+            assert!(Archive::from_bytes(data[..len].to_vec()).is_err());
+        }
+        // Central directory cut off after the EOCD record was found
+        let mut data = package();
+        let eocd = find_eocd(&data).unwrap();
+        data[eocd + 16..eocd + 20].copy_from_slice(&u32::MAX.to_le_bytes()[..]);
+        assert!(Archive::from_bytes(data).is_err());
+    }
+}
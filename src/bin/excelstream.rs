@@ -0,0 +1,318 @@
+//! `excelstream` command line tool
+//!
+//! Thin wrapper over the library's converters and readers so that XLSX, CSV
+//! and Parquet files can be handled in shell pipelines:
+//!
+//! ```text
+//! excelstream convert data.parquet out.xlsx
+//! excelstream convert big.xlsx out.csv.zst --sheet Report
+//! excelstream head file.xlsx --rows 20
+//! excelstream sheets file.xlsx
+//! excelstream validate file.xlsx
+//! excelstream diff old.xlsx new.xlsx --tolerance 1e-9
+//! ```
+//!
+//! Built with `cargo install excelstream --features cli` (add
+//! `parquet-support` for Parquet input and output).
+
+use clap::{Parser, Subcommand};
+use excelstream::convert::{CsvToXlsxConverter, XlsxToCsvConverter};
+use excelstream::csv::CsvEncoder;
+use excelstream::csv_reader::CsvReader;
+use excelstream::diff::{compare, DiffOptions, DEFAULT_MAX_CHANGES};
+use excelstream::streaming_reader::StreamingReader;
+use excelstream::validate::validate_package;
+use excelstream::{ExcelError, Result};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "excelstream",
+    version,
+    about = "Stream XLSX, CSV and Parquet files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Convert between formats, picked from the file extensions
+    ///
+    /// Supported: xlsx -> csv (.csv, .csv.gz, .csv.zst), csv -> xlsx,
+    /// parquet -> xlsx and xlsx -> parquet.
+    Convert {
+        input: String,
+        output: String,
+        /// Worksheet to read (default: the first one)
+        #[arg(long)]
+        sheet: Option<String>,
+        /// CSV field delimiter
+        #[arg(long, default_value = ",", value_parser = parse_delimiter)]
+        delimiter: u8,
+        /// Treat the first CSV line as data instead of a header
+        #[arg(long)]
+        no_header: bool,
+    },
+    /// Print the first rows of a sheet or CSV file as CSV
+    Head {
+        file: String,
+        /// Number of rows to print
+        #[arg(short = 'n', long, default_value_t = 10)]
+        rows: usize,
+        /// Worksheet to read (default: the first one)
+        #[arg(long)]
+        sheet: Option<String>,
+        /// Field delimiter of CSV input and of the output
+        #[arg(long, default_value = ",", value_parser = parse_delimiter)]
+        delimiter: u8,
+    },
+    /// List the worksheets of an XLSX file
+    Sheets { file: String },
+    /// Check the package structure of an XLSX file
+    Validate { file: String },
+    /// Compare two XLSX files cell by cell; exits with 1 if they differ
+    Diff {
+        first: String,
+        second: String,
+        /// Numbers differing by at most this much are equal
+        #[arg(long, default_value_t = 0.0)]
+        tolerance: f64,
+        /// Numbers differing by at most this fraction of the larger are equal
+        #[arg(long, default_value_t = 0.0)]
+        relative_tolerance: f64,
+        /// Compare only this worksheet (repeatable)
+        #[arg(long)]
+        sheet: Vec<String>,
+        /// Changes listed per sheet
+        #[arg(long, default_value_t = DEFAULT_MAX_CHANGES)]
+        max_changes: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Xlsx,
+    Csv,
+    Parquet,
+}
+
+impl Format {
+    fn of(path: &str) -> Result<Format> {
+        let lower = path.to_ascii_lowercase();
+        let format = if lower.ends_with(".xlsx") || lower.ends_with(".xlsm") {
+            Format::Xlsx
+        } else if [".csv", ".csv.gz", ".csv.zst", ".csv.zip"]
+            .iter()
+            .any(|ext| lower.ends_with(ext))
+        {
+            Format::Csv
+        } else if lower.ends_with(".parquet") {
+            Format::Parquet
+        } else {
+            return Err(ExcelError::InvalidFormat(format!(
+                "Can't tell the format of '{}' from its extension",
+                path
+            )));
+        };
+        Ok(format)
+    }
+}
+
+#[cfg(not(feature = "parquet-support"))]
+fn parquet_disabled() -> ExcelError {
+    ExcelError::InvalidFormat(
+        "Parquet needs excelstream built with the parquet-support feature".to_string(),
+    )
+}
+
+fn parse_delimiter(value: &str) -> std::result::Result<u8, String> {
+    match value {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ if value.len() == 1 => Ok(value.as_bytes()[0]),
+        _ => Err("delimiter must be a single ASCII character".to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Convert {
+            input,
+            output,
+            sheet,
+            delimiter,
+            no_header,
+        } => convert(&input, &output, sheet, delimiter, !no_header),
+        Command::Head {
+            file,
+            rows,
+            sheet,
+            delimiter,
+        } => head(&file, rows, sheet, delimiter),
+        Command::Sheets { file } => sheets(&file),
+        Command::Validate { file } => validate(&file),
+        Command::Diff {
+            first,
+            second,
+            tolerance,
+            relative_tolerance,
+            sheet,
+            max_changes,
+        } => {
+            let options = sheet.iter().fold(
+                DiffOptions::new()
+                    .tolerance(tolerance)
+                    .relative_tolerance(relative_tolerance)
+                    .max_changes_per_sheet(max_changes),
+                |options, name| options.sheet(name),
+            );
+            diff(&first, &second, options)
+        }
+    };
+    match result {
+        Ok(code) => code,
+        // `excelstream head big.xlsx | head -3` closes stdout early
+        Err(ExcelError::IoError(e)) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("excelstream: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn convert(
+    input: &str,
+    output: &str,
+    sheet: Option<String>,
+    delimiter: u8,
+    has_header: bool,
+) -> Result<ExitCode> {
+    let rows = match (Format::of(input)?, Format::of(output)?) {
+        (Format::Xlsx, Format::Csv) => {
+            let mut converter = XlsxToCsvConverter::new().delimiter(delimiter);
+            if let Some(sheet) = &sheet {
+                converter = converter.sheet(sheet);
+            }
+            converter.convert(input, output)? as usize
+        }
+        (Format::Csv, Format::Xlsx) => {
+            let mut converter = CsvToXlsxConverter::new()
+                .delimiter(delimiter)
+                .has_header(has_header);
+            if let Some(sheet) = &sheet {
+                converter = converter.sheet_name(sheet);
+            }
+            converter.convert(input, output)? as usize
+        }
+        #[cfg(feature = "parquet-support")]
+        (Format::Parquet, Format::Xlsx) => {
+            excelstream::parquet::ParquetToExcelConverter::new(input)?.convert_to_excel(output)?
+        }
+        #[cfg(feature = "parquet-support")]
+        (Format::Xlsx, Format::Parquet) => {
+            let mut converter = excelstream::parquet::ExcelToParquetConverter::new(input)?;
+            if let Some(sheet) = &sheet {
+                converter = converter.sheet(sheet);
+            }
+            converter.convert_to_parquet(output)?
+        }
+        #[cfg(not(feature = "parquet-support"))]
+        (Format::Parquet, _) | (_, Format::Parquet) => return Err(parquet_disabled()),
+        (from, to) => {
+            return Err(ExcelError::InvalidFormat(format!(
+                "Converting {:?} to {:?} is not supported",
+                from, to
+            )))
+        }
+    };
+    eprintln!("{} rows written to {}", rows, output);
+    Ok(ExitCode::SUCCESS)
+}
+
+fn head(file: &str, limit: usize, sheet: Option<String>, delimiter: u8) -> Result<ExitCode> {
+    let encoder = CsvEncoder::new(delimiter, b'"');
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut line = Vec::new();
+    let mut emit = |fields: &[String]| -> Result<()> {
+        line.clear();
+        encoder.encode_fields(fields, &mut line);
+        line.push(b'\n');
+        out.write_all(&line)?;
+        Ok(())
+    };
+
+    match Format::of(file)? {
+        Format::Xlsx => {
+            let mut reader = StreamingReader::open(file)?;
+            let rows = match &sheet {
+                Some(name) => reader.rows(name)?,
+                None => reader.rows_by_index(0)?,
+            };
+            for row in rows.take(limit) {
+                emit(&row?.to_strings())?;
+            }
+        }
+        Format::Csv => {
+            let mut reader = CsvReader::open(file)?.delimiter(delimiter);
+            for row in reader.rows().take(limit) {
+                emit(&row?)?;
+            }
+        }
+        #[cfg(feature = "parquet-support")]
+        Format::Parquet => {
+            let reader = excelstream::parquet::ParquetReader::open(file)?;
+            emit(&reader.column_names())?;
+            for row in reader.rows()?.take(limit.saturating_sub(1)) {
+                emit(&row?)?;
+            }
+        }
+        #[cfg(not(feature = "parquet-support"))]
+        Format::Parquet => return Err(parquet_disabled()),
+    }
+    out.flush()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn sheets(file: &str) -> Result<ExitCode> {
+    let reader = StreamingReader::open(file)?;
+    let mut out = io::stdout().lock();
+    for name in reader.sheet_names() {
+        writeln!(out, "{}", name)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Prints the report; exits with 1 if the package has errors
+fn validate(file: &str) -> Result<ExitCode> {
+    if !Path::new(file).exists() {
+        return Err(ExcelError::FileNotFound(file.to_string()));
+    }
+    let report = validate_package(file)?;
+    println!("{}", report);
+    Ok(if report.is_valid() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Prints the differences; exits with 1 if the workbooks differ
+fn diff(first: &str, second: &str, options: DiffOptions) -> Result<ExitCode> {
+    for file in [first, second] {
+        if !Path::new(file).exists() {
+            return Err(ExcelError::FileNotFound(file.to_string()));
+        }
+    }
+    let report = compare(first, second, options)?;
+    println!("{}", report);
+    Ok(if report.is_identical() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
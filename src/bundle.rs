@@ -0,0 +1,315 @@
+//! Several CSV and XLSX files streamed into one ZIP archive
+//!
+//! Entries are written one after another: open an entry, write its rows,
+//! then open the next. CSV rows are compressed straight into the archive;
+//! an XLSX entry is built (compressed) in memory and copied in when it is
+//! finished. No temporary files are created.
+//!
+//! ```no_run
+//! use excelstream::ZipBundleWriter;
+//!
+//! let mut bundle = ZipBundleWriter::new("export.zip")?;
+//!
+//! for tenant in ["acme", "globex"] {
+//!     let mut csv = bundle.start_csv(&format!("{}.csv", tenant))?;
+//!     csv.write_row(["id", "amount"])?;
+//!     csv.write_row(["1", "100"])?;
+//! }
+//!
+//! let mut summary = bundle.start_xlsx("summary.xlsx")?;
+//! summary.write_header_bold(["Tenant", "Rows"])?;
+//! summary.write_row(["acme", "1"])?;
+//! summary.finish()?;
+//!
+//! bundle.finish()?;
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+
+use crate::csv::CsvEncoder;
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::StreamingZipWriter;
+use crate::report::{Checksum, ChecksumSetting, HashingWriter, WriteReport};
+use crate::types::CellValue;
+use crate::writer::ExcelWriter;
+use std::fmt::Display;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+
+/// ZIP archive of CSV and XLSX files written in sequence
+///
+/// See the [module documentation](self) for an example.
+pub struct ZipBundleWriter {
+    zip: Option<StreamingZipWriter<HashingWriter>>,
+    checksum: ChecksumSetting,
+    entries: Vec<String>,
+    compression_level: u32,
+}
+
+impl ZipBundleWriter {
+    /// Create the archive with Deflate level 6
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_compression(path, 6)
+    }
+
+    /// Create the archive with a Deflate level from 0 to 9
+    ///
+    /// XLSX entries are compressed at the same level.
+    pub fn with_compression<P: AsRef<Path>>(path: P, compression_level: u32) -> Result<Self> {
+        let checksum = ChecksumSetting::default();
+        let output = HashingWriter::create(path.as_ref(), checksum.clone())?;
+        let zip =
+            StreamingZipWriter::from_writer_with_compression(output, compression_level.min(9))?;
+        Ok(ZipBundleWriter {
+            zip: Some(zip),
+            checksum,
+            entries: Vec::new(),
+            compression_level: compression_level.min(9),
+        })
+    }
+
+    /// Start a CSV entry; its rows go straight into the archive
+    ///
+    /// The entry ends when the returned writer is dropped.
+    pub fn start_csv(&mut self, name: &str) -> Result<BundleCsvWriter<'_>> {
+        self.start_entry(name)?;
+        Ok(BundleCsvWriter {
+            bundle: self,
+            row_count: 0,
+            buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
+            delimiter: b',',
+            quote_char: b'"',
+        })
+    }
+
+    /// Start an XLSX entry
+    ///
+    /// The returned writer dereferences to an [`ExcelWriter`], so sheets,
+    /// styles and formulas work as usual. Call
+    /// [`BundleExcelWriter::finish`] to add the workbook to the archive; it
+    /// is discarded if the writer is dropped.
+    pub fn start_xlsx(&mut self, name: &str) -> Result<BundleExcelWriter<'_>> {
+        self.check_name(name)?;
+        let writer = ExcelWriter::in_memory(self.compression_level)?;
+        Ok(BundleExcelWriter {
+            bundle: self,
+            name: name.to_string(),
+            writer,
+        })
+    }
+
+    /// Names of the entries written so far, in order
+    pub fn entry_names(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Compute a checksum of the archive, returned by
+    /// [`finish_with_report`](Self::finish_with_report)
+    pub fn set_checksum(&mut self, checksum: Checksum) {
+        self.checksum.set(Some(checksum));
+    }
+
+    /// Write the central directory and close the archive
+    pub fn finish(self) -> Result<()> {
+        self.finish_with_report().map(|_| ())
+    }
+
+    /// Close the archive and report its size and checksum
+    pub fn finish_with_report(mut self) -> Result<WriteReport> {
+        let zip = self
+            .zip
+            .take()
+            .ok_or_else(|| ExcelError::InvalidState("Bundle already finished".to_string()))?;
+        zip.finish()?.finish()
+    }
+
+    fn check_name(&self, name: &str) -> Result<()> {
+        if name.is_empty() || name.starts_with('/') || name.ends_with('/') {
+            return Err(ExcelError::InvalidFormat(format!(
+                "Invalid bundle entry name '{}'",
+                name
+            )));
+        }
+        if self.entries.iter().any(|entry| entry == name) {
+            return Err(ExcelError::InvalidFormat(format!(
+                "Bundle already has an entry named '{}'",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    fn start_entry(&mut self, name: &str) -> Result<()> {
+        self.check_name(name)?;
+        self.zip_writer()?.start_entry(name)?;
+        self.entries.push(name.to_string());
+        Ok(())
+    }
+
+    fn zip_writer(&mut self) -> Result<&mut StreamingZipWriter<HashingWriter>> {
+        self.zip
+            .as_mut()
+            .ok_or_else(|| ExcelError::InvalidState("Bundle already finished".to_string()))
+    }
+}
+
+/// CSV entry of a [`ZipBundleWriter`]
+pub struct BundleCsvWriter<'a> {
+    bundle: &'a mut ZipBundleWriter,
+    row_count: u64,
+    buffer: Vec<u8>,
+    scratch: String,
+    delimiter: u8,
+    quote_char: u8,
+}
+
+impl BundleCsvWriter<'_> {
+    /// Set custom delimiter (builder pattern)
+    pub fn delimiter(mut self, delim: u8) -> Self {
+        self.delimiter = delim;
+        self
+    }
+
+    /// Set custom quote character (builder pattern)
+    pub fn quote_char(mut self, quote: u8) -> Self {
+        self.quote_char = quote;
+        self
+    }
+
+    /// Write a row of strings
+    pub fn write_row<I, S>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.buffer.clear();
+        let encoder = CsvEncoder::new(self.delimiter, self.quote_char);
+        encoder.encode_fields(data, &mut self.buffer);
+        self.buffer.push(b'\n');
+        self.flush_row()
+    }
+
+    /// Write a row of any `Display` values
+    pub fn write_row_display<I>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.buffer.clear();
+        let encoder = CsvEncoder::new(self.delimiter, self.quote_char);
+        encoder.encode_display(data, &mut self.scratch, &mut self.buffer);
+        self.buffer.push(b'\n');
+        self.flush_row()
+    }
+
+    /// Write a row of typed values
+    pub fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
+        self.write_row_display(cells)
+    }
+
+    fn flush_row(&mut self) -> Result<()> {
+        self.bundle.zip_writer()?.write_data(&self.buffer)?;
+        self.row_count += 1;
+        Ok(())
+    }
+
+    /// Get the number of rows written to this entry
+    pub fn row_count(&self) -> u64 {
+        self.row_count
+    }
+}
+
+/// XLSX entry of a [`ZipBundleWriter`]
+///
+/// Dereferences to [`ExcelWriter`] for writing rows and sheets.
+pub struct BundleExcelWriter<'a> {
+    bundle: &'a mut ZipBundleWriter,
+    name: String,
+    writer: ExcelWriter,
+}
+
+impl BundleExcelWriter<'_> {
+    /// Finish the workbook and add it to the archive
+    pub fn finish(self) -> Result<()> {
+        let bytes = self.writer.into_bytes()?;
+        self.bundle.start_entry(&self.name)?;
+        self.bundle.zip_writer()?.write_data(&bytes)?;
+        Ok(())
+    }
+}
+
+impl Deref for BundleExcelWriter<'_> {
+    type Target = ExcelWriter;
+
+    fn deref(&self) -> &ExcelWriter {
+        &self.writer
+    }
+}
+
+impl DerefMut for BundleExcelWriter<'_> {
+    fn deref_mut(&mut self) -> &mut ExcelWriter {
+        &mut self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fast_writer::StreamingZipReader;
+    use crate::streaming_reader::StreamingReader;
+
+    #[test]
+    fn test_bundle_csv_and_xlsx() -> Result<()> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("bundle.zip");
+
+        let mut bundle = ZipBundleWriter::new(&path)?;
+        bundle.set_checksum(Checksum::Crc32);
+        for tenant in ["acme", "globex"] {
+            let mut csv = bundle.start_csv(&format!("tenants/{}.csv", tenant))?;
+            csv.write_row(["id", "name"])?;
+            csv.write_row_display([1, 2])?;
+            assert_eq!(csv.row_count(), 2);
+        }
+        let mut summary = bundle.start_xlsx("summary.xlsx")?;
+        summary.write_row(["Tenant", "Rows"])?;
+        summary.write_row_typed(&[CellValue::String("acme".to_string()), CellValue::Int(2)])?;
+        summary.finish()?;
+        let mut csv = bundle.start_csv("last.csv")?.delimiter(b';');
+        csv.write_row(["a", "b;c"])?;
+
+        // Names must be unique, and an unfinished workbook adds nothing
+        assert!(bundle.start_csv("summary.xlsx").is_err());
+        assert!(bundle.start_xlsx("").is_err());
+        drop(bundle.start_xlsx("dropped.xlsx")?);
+        assert_eq!(
+            bundle.entry_names(),
+            [
+                "tenants/acme.csv",
+                "tenants/globex.csv",
+                "summary.xlsx",
+                "last.csv"
+            ]
+        );
+        let report = bundle.finish_with_report()?;
+        let bytes = std::fs::read(&path)?;
+        assert_eq!(report.bytes_written, bytes.len() as u64);
+
+        let mut zip = StreamingZipReader::open(&path)?;
+        assert_eq!(
+            zip.read_entry_by_name("tenants/globex.csv")?,
+            b"id,name\n1,2\n"
+        );
+        assert_eq!(zip.read_entry_by_name("last.csv")?, b"a;\"b;c\"\n");
+
+        let xlsx_path = dir.path().join("summary.xlsx");
+        std::fs::write(&xlsx_path, zip.read_entry_by_name("summary.xlsx")?)?;
+        let mut reader = StreamingReader::open(&xlsx_path)?;
+        let rows: Vec<Vec<String>> = reader
+            .rows_by_index(0)?
+            .map(|row| row.map(|row| row.to_strings()))
+            .collect::<Result<_>>()?;
+        assert_eq!(rows, vec![vec!["Tenant", "Rows"], vec!["acme", "2"]]);
+        Ok(())
+    }
+}
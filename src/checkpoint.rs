@@ -0,0 +1,530 @@
+//! Exports that survive a crash and resume where they stopped
+//!
+//! [`CheckpointWriter`] spills rows to a directory on local disk in chunks
+//! of [`checkpoint_interval`](CheckpointWriter::checkpoint_interval) rows.
+//! Every chunk is a small finished workbook, and a manifest in the same
+//! directory lists the chunks that were completely written. When a job
+//! dies, opening the writer again on the same directory keeps those chunks
+//! and drops the one that was being written; [`add_sheet`] reports how
+//! many rows of the sheet are already saved, so the job skips that many
+//! source rows and carries on. [`finish`] copies the chunks into the
+//! output workbook and removes them.
+//!
+//! ```no_run
+//! use excelstream::checkpoint::CheckpointWriter;
+//! use excelstream::types::CellValue;
+//!
+//! let mut writer = CheckpointWriter::open("nightly.xlsx", "nightly.spill")?
+//!     .checkpoint_interval(50_000);
+//! let saved = writer.add_sheet("Orders")?;
+//! // Rows 0..saved were written by a run that crashed
+//! for id in saved..1_000_000 {
+//!     writer.write_row_typed(&[CellValue::Int(id as i64), CellValue::from("open")])?;
+//! }
+//! writer.finish()?;
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+//!
+//! Rows are copied with their values and built-in [`CellStyle`]s; sheet
+//! settings such as column widths are not part of a checkpoint, and
+//! hyperlinks keep only their text.
+//!
+//! [`add_sheet`]: CheckpointWriter::add_sheet
+//! [`finish`]: CheckpointWriter::finish
+
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::UltraLowMemoryWorkbook;
+use crate::sheet_name;
+use crate::streaming_reader::{DetailedCell, StreamingReader};
+use crate::types::{CellStyle, CellValue, StyledCell};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest inside the spill directory
+const MANIFEST: &str = "checkpoint.txt";
+/// First line of the manifest
+const MANIFEST_HEADER: &str = "excelstream checkpoint 1";
+/// Compression of chunks, which are only read back once
+const CHUNK_COMPRESSION: u32 = 1;
+
+/// Every built-in style, in index order
+const BUILTIN_STYLES: [CellStyle; 16] = [
+    CellStyle::Default,
+    CellStyle::HeaderBold,
+    CellStyle::NumberInteger,
+    CellStyle::NumberDecimal,
+    CellStyle::NumberCurrency,
+    CellStyle::NumberPercentage,
+    CellStyle::DateDefault,
+    CellStyle::DateTimestamp,
+    CellStyle::TextBold,
+    CellStyle::TextItalic,
+    CellStyle::HighlightYellow,
+    CellStyle::HighlightGreen,
+    CellStyle::HighlightRed,
+    CellStyle::BorderThin,
+    CellStyle::DateTimeShort,
+    CellStyle::Hyperlink,
+];
+
+/// A sheet and its saved chunks
+#[derive(Debug)]
+struct SheetChunks {
+    name: String,
+    /// Chunk file names and their row counts, in row order
+    chunks: Vec<(String, u64)>,
+    /// Added by [`CheckpointWriter::add_sheet`] since the writer was opened
+    added: bool,
+}
+
+impl SheetChunks {
+    fn saved_rows(&self) -> u64 {
+        self.chunks.iter().map(|(_, rows)| rows).sum()
+    }
+}
+
+/// Chunk being written
+struct OpenChunk {
+    workbook: UltraLowMemoryWorkbook,
+    file: String,
+    rows: u64,
+}
+
+/// Writes a workbook through resumable on-disk checkpoints
+///
+/// See the [module documentation](self) for how resuming works.
+pub struct CheckpointWriter {
+    output: PathBuf,
+    dir: PathBuf,
+    manifest: File,
+    interval: u64,
+    sheets: Vec<SheetChunks>,
+    /// Index into `sheets` of the sheet rows go to
+    current: Option<usize>,
+    chunk: Option<OpenChunk>,
+    next_chunk: u32,
+    resumed: bool,
+}
+
+impl CheckpointWriter {
+    /// Writer of `output` that keeps its checkpoints in `spill_dir`
+    ///
+    /// The directory is created if needed. If it holds the checkpoints of
+    /// an earlier run, they are picked up and whatever that run wrote
+    /// after its last checkpoint is discarded.
+    pub fn open<P: AsRef<Path>, Q: AsRef<Path>>(output: P, spill_dir: Q) -> Result<Self> {
+        let dir = spill_dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let manifest_path = dir.join(MANIFEST);
+
+        let (sheets, resumed) = if manifest_path.exists() {
+            (read_manifest(&manifest_path)?, true)
+        } else {
+            fs::write(&manifest_path, format!("{}\n", MANIFEST_HEADER))?;
+            (Vec::new(), false)
+        };
+
+        // Chunks missing from the manifest were cut off by the crash
+        let mut next_chunk = 1;
+        for entry in fs::read_dir(&dir)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            let Some(number) = chunk_number(&name) else {
+                continue;
+            };
+            let saved = sheets
+                .iter()
+                .any(|sheet| sheet.chunks.iter().any(|(file, _)| *file == name));
+            if saved {
+                next_chunk = next_chunk.max(number + 1);
+            } else {
+                fs::remove_file(dir.join(&name))?;
+            }
+        }
+
+        let manifest = OpenOptions::new().append(true).open(&manifest_path)?;
+        Ok(CheckpointWriter {
+            output: output.as_ref().to_path_buf(),
+            dir,
+            manifest,
+            interval: 100_000,
+            sheets,
+            current: None,
+            chunk: None,
+            next_chunk,
+            resumed,
+        })
+    }
+
+    /// Rows per chunk (default: 100 000)
+    ///
+    /// A crash loses at most this many rows. Smaller chunks also mean more
+    /// files to copy at the end.
+    pub fn checkpoint_interval(mut self, rows: u64) -> Self {
+        self.interval = rows.max(1);
+        self
+    }
+
+    /// Whether checkpoints of an earlier run were found
+    pub fn is_resumed(&self) -> bool {
+        self.resumed
+    }
+
+    /// Send the following rows to sheet `name`, returning how many of its
+    /// rows are already saved
+    ///
+    /// Sheets appear in the output in the order they were first added,
+    /// across runs. A sheet can be added once per run.
+    pub fn add_sheet(&mut self, name: &str) -> Result<u64> {
+        self.close_chunk()?;
+        let index = match self.sheets.iter().position(|sheet| sheet.name == name) {
+            Some(index) if self.sheets[index].added => {
+                return Err(ExcelError::InvalidState(format!(
+                    "Sheet '{}' was already added",
+                    name
+                )));
+            }
+            Some(index) => index,
+            None => {
+                sheet_name::validate(name)?;
+                self.record(&format!("sheet\t{}", name))?;
+                self.sheets.push(SheetChunks {
+                    name: name.to_string(),
+                    chunks: Vec::new(),
+                    added: false,
+                });
+                self.sheets.len() - 1
+            }
+        };
+        self.sheets[index].added = true;
+        self.current = Some(index);
+        Ok(self.sheets[index].saved_rows())
+    }
+
+    /// Rows of sheet `name` saved by the last checkpoint
+    pub fn saved_rows(&self, name: &str) -> u64 {
+        self.sheets
+            .iter()
+            .find(|sheet| sheet.name == name)
+            .map_or(0, SheetChunks::saved_rows)
+    }
+
+    /// Write a row of strings
+    pub fn write_row<I, S>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let cells: Vec<StyledCell> = values
+            .into_iter()
+            .map(|value| {
+                StyledCell::new(
+                    CellValue::String(value.as_ref().to_string()),
+                    CellStyle::Default,
+                )
+            })
+            .collect();
+        self.write_row_styled(&cells)
+    }
+
+    /// Write a row of typed values
+    pub fn write_row_typed(&mut self, values: &[CellValue]) -> Result<()> {
+        let cells: Vec<StyledCell> = values
+            .iter()
+            .map(|value| StyledCell::new(value.clone(), value.default_style()))
+            .collect();
+        self.write_row_styled(&cells)
+    }
+
+    /// Write a row of styled cells
+    pub fn write_row_styled(&mut self, cells: &[StyledCell]) -> Result<()> {
+        let Some(index) = self.current else {
+            return Err(ExcelError::InvalidState(
+                "No sheet added to write to".to_string(),
+            ));
+        };
+        if self.chunk.is_none() {
+            let file = format!("chunk-{:06}.xlsx", self.next_chunk);
+            self.next_chunk += 1;
+            let mut workbook =
+                UltraLowMemoryWorkbook::with_compression(self.dir.join(&file), CHUNK_COMPRESSION)?;
+            workbook.add_worksheet(&self.sheets[index].name)?;
+            self.chunk = Some(OpenChunk {
+                workbook,
+                file,
+                rows: 0,
+            });
+        }
+        let chunk = self.chunk.as_mut().expect("chunk opened above");
+        chunk.workbook.write_row_styled(cells)?;
+        chunk.rows += 1;
+        if chunk.rows >= self.interval {
+            self.close_chunk()?;
+        }
+        Ok(())
+    }
+
+    /// Save the rows written so far without waiting for a full chunk
+    ///
+    /// Useful to line checkpoints up with commits of the data source.
+    pub fn checkpoint(&mut self) -> Result<()> {
+        self.close_chunk()
+    }
+
+    /// Write the output workbook from the saved chunks and remove them,
+    /// returning the number of rows written
+    ///
+    /// If this fails, the checkpoints are left in place and a new writer on
+    /// the same directory can finish again.
+    pub fn finish(mut self) -> Result<u64> {
+        self.close_chunk()?;
+
+        let mut workbook = UltraLowMemoryWorkbook::new(&self.output)?;
+        let mut written = 0u64;
+        let mut cells = Vec::new();
+        for sheet in &self.sheets {
+            workbook.add_worksheet(&sheet.name)?;
+            for (file, _) in &sheet.chunks {
+                let mut reader = StreamingReader::open(self.dir.join(file))?;
+                let mut rows = reader.rows_detailed(&sheet.name)?;
+                let mut last_row = 0;
+                while let Some(row) = rows.next().transpose()? {
+                    // Keep blank rows the reader skipped over
+                    for _ in last_row + 1..rows.row_number() {
+                        workbook.write_row_styled(&[])?;
+                        written += 1;
+                    }
+                    last_row = rows.row_number();
+                    cells.clear();
+                    cells.extend(row.into_iter().map(restore_cell));
+                    workbook.write_row_styled(&cells)?;
+                    written += 1;
+                }
+            }
+        }
+        workbook.close()?;
+
+        for sheet in &self.sheets {
+            for (file, _) in &sheet.chunks {
+                fs::remove_file(self.dir.join(file))?;
+            }
+        }
+        fs::remove_file(self.dir.join(MANIFEST))?;
+        // Leave the directory if it holds anything else
+        let _ = fs::remove_dir(&self.dir);
+        Ok(written)
+    }
+
+    /// Finish the open chunk, make it durable and list it in the manifest
+    fn close_chunk(&mut self) -> Result<()> {
+        let (Some(chunk), Some(index)) = (self.chunk.take(), self.current) else {
+            return Ok(());
+        };
+        chunk.workbook.close()?;
+        File::open(self.dir.join(&chunk.file))?.sync_all()?;
+        self.record(&format!(
+            "chunk\t{}\t{}\t{}",
+            chunk.rows, chunk.file, self.sheets[index].name
+        ))?;
+        self.sheets[index].chunks.push((chunk.file, chunk.rows));
+        Ok(())
+    }
+
+    /// Append a line to the manifest and wait until it is on disk
+    fn record(&mut self, line: &str) -> Result<()> {
+        writeln!(self.manifest, "{}", line)?;
+        self.manifest.sync_data()?;
+        Ok(())
+    }
+}
+
+/// Sheets and chunks listed in the manifest at `path`
+fn read_manifest(path: &Path) -> Result<Vec<SheetChunks>> {
+    let text = fs::read_to_string(path)?;
+    let mut lines = text.split_inclusive('\n');
+    if lines.next().map(str::trim_end) != Some(MANIFEST_HEADER) {
+        return Err(ExcelError::InvalidFormat(format!(
+            "{} is not a checkpoint manifest",
+            path.display()
+        )));
+    }
+
+    let mut sheets: Vec<SheetChunks> = Vec::new();
+    // A line without its newline was cut off mid-write
+    for line in lines.filter_map(|line| line.strip_suffix('\n')) {
+        let bad_line = || ExcelError::InvalidFormat(format!("Bad checkpoint line: {}", line));
+        let mut fields = line.splitn(4, '\t');
+        match fields.next() {
+            Some("sheet") => {
+                let name = fields.next().ok_or_else(bad_line)?;
+                sheets.push(SheetChunks {
+                    name: name.to_string(),
+                    chunks: Vec::new(),
+                    added: false,
+                });
+            }
+            Some("chunk") => {
+                let (Some(rows), Some(file), Some(name)) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    return Err(bad_line());
+                };
+                let rows = rows.parse().map_err(|_| bad_line())?;
+                let sheet = sheets
+                    .iter_mut()
+                    .find(|sheet| sheet.name == name)
+                    .ok_or_else(bad_line)?;
+                sheet.chunks.push((file.to_string(), rows));
+            }
+            _ => return Err(bad_line()),
+        }
+    }
+    Ok(sheets)
+}
+
+/// Number of a chunk file name (`chunk-000042.xlsx`)
+fn chunk_number(name: &str) -> Option<u32> {
+    name.strip_prefix("chunk-")?
+        .strip_suffix(".xlsx")?
+        .parse()
+        .ok()
+}
+
+/// Cell to write for one read back from a chunk
+fn restore_cell(cell: DetailedCell) -> StyledCell {
+    let style = cell
+        .style_index
+        .and_then(|index| BUILTIN_STYLES.get(index as usize).copied())
+        .unwrap_or(CellStyle::Default);
+    let raw = cell.raw_value.as_deref();
+    let value = match cell.type_attr.as_deref() {
+        None | Some("n") => match raw {
+            None => CellValue::Empty,
+            Some(raw) => match raw.parse::<i64>() {
+                _ if is_date_style(style) => CellValue::DateTime(raw.parse().unwrap_or(0.0)),
+                Ok(int) => CellValue::Int(int),
+                Err(_) => raw.parse().map_or(cell.value, CellValue::Float),
+            },
+        },
+        Some("b") => CellValue::Bool(raw == Some("1")),
+        Some("e") => CellValue::Error(raw.unwrap_or_default().to_string()),
+        _ => cell.value,
+    };
+    let value = match cell.formula {
+        Some(expr) if value == CellValue::Empty => CellValue::formula(expr),
+        Some(expr) => CellValue::formula_with_result(expr, value),
+        None => value,
+    };
+    StyledCell::new(value, style)
+}
+
+fn is_date_style(style: CellStyle) -> bool {
+    matches!(
+        style,
+        CellStyle::DateDefault | CellStyle::DateTimestamp | CellStyle::DateTimeShort
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resume_after_crash() {
+        let dir = TempDir::new().unwrap();
+        let output = dir.path().join("out.xlsx");
+        let spill = dir.path().join("spill");
+
+        {
+            let mut writer = CheckpointWriter::open(&output, &spill)
+                .unwrap()
+                .checkpoint_interval(2);
+            assert!(!writer.is_resumed());
+            assert_eq!(writer.add_sheet("Orders").unwrap(), 0);
+            for id in 0..5 {
+                writer
+                    .write_row_typed(&[CellValue::Int(id), CellValue::from("first run")])
+                    .unwrap();
+            }
+            // Dropped without finish: row 4 was never checkpointed
+        }
+
+        let mut writer = CheckpointWriter::open(&output, &spill).unwrap();
+        assert!(writer.is_resumed());
+        assert_eq!(writer.saved_rows("Orders"), 4);
+        assert_eq!(fs::read_dir(&spill).unwrap().count(), 3);
+        let saved = writer.add_sheet("Orders").unwrap();
+        assert_eq!(saved, 4);
+        for id in saved as i64..6 {
+            writer
+                .write_row_typed(&[CellValue::Int(id), CellValue::from("second run")])
+                .unwrap();
+        }
+        assert!(writer.add_sheet("Orders").is_err());
+        writer.add_sheet("Notes").unwrap();
+        writer.write_row(["done"]).unwrap();
+        assert_eq!(writer.finish().unwrap(), 7);
+        assert!(!spill.exists());
+
+        let mut reader = StreamingReader::open(&output).unwrap();
+        assert_eq!(reader.sheet_names(), vec!["Orders", "Notes"]);
+        let rows: Vec<Vec<String>> = reader
+            .rows("Orders")
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[3], vec!["3", "first run"]);
+        assert_eq!(rows[4], vec!["4", "second run"]);
+    }
+
+    #[test]
+    fn test_cells_survive_the_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let output = dir.path().join("out.xlsx");
+        let mut writer = CheckpointWriter::open(&output, dir.path().join("spill")).unwrap();
+        writer.add_sheet("Data").unwrap();
+        writer
+            .write_row_styled(&[
+                StyledCell::new(CellValue::from("Name"), CellStyle::HeaderBold),
+                StyledCell::new(CellValue::DateTime(45000.5), CellStyle::DateTimestamp),
+            ])
+            .unwrap();
+        writer.write_row_typed(&[]).unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::Float(1.25),
+                CellValue::Bool(true),
+                CellValue::formula_with_result("A3*2", CellValue::Float(2.5)),
+                CellValue::Error("#N/A".to_string()),
+            ])
+            .unwrap();
+        writer.finish().unwrap();
+
+        let mut reader = StreamingReader::open(&output).unwrap();
+        let rows: Vec<Vec<DetailedCell>> = reader
+            .rows_detailed("Data")
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        let last = rows.last().unwrap();
+        assert_eq!(rows[0][0].style_index, Some(CellStyle::HeaderBold.index()));
+        assert_eq!(rows[0][1].raw_value.as_deref(), Some("45000.5"));
+        assert_eq!(
+            rows[0][1].style_index,
+            Some(CellStyle::DateTimestamp.index())
+        );
+        assert_eq!(last[0].raw_value.as_deref(), Some("1.25"));
+        assert_eq!(last[1].value, CellValue::Bool(true));
+        assert_eq!(last[2].formula.as_deref(), Some("A3*2"));
+        assert_eq!(last[3].type_attr.as_deref(), Some("e"));
+        assert_eq!(reader.rows("Data").unwrap().count(), 3);
+
+        let restored = restore_cell(last[2].clone());
+        assert_eq!(
+            restored.value,
+            CellValue::formula_with_result("A3*2", CellValue::Float(2.5))
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Streaming CSV reader for cloud objects
+//!
+//! Reads a CSV object from S3 or GCS with ranged GETs, so only one chunk
+//! (8 MB by default) is held in memory and rows are available as soon as
+//! the first chunk arrives.
+
+use super::ranged::{RangedSource, DEFAULT_CHUNK_SIZE};
+use crate::csv::CsvParser;
+use crate::error::Result;
+
+/// CSV reader over any [`RangedSource`]
+///
+/// Use the [`S3CsvReader`](super::S3CsvReader) or
+/// [`GCSCsvReader`](super::GCSCsvReader) aliases to construct one.
+///
+/// Only plain (uncompressed) CSV objects are supported.
+pub struct CloudCsvReader<S: RangedSource> {
+    source: S,
+    offset: u64,
+    chunk_size: u64,
+    pending: Vec<u8>,
+    cursor: usize,
+    delimiter: u8,
+    quote_char: u8,
+    has_header: bool,
+    headers: Vec<String>,
+    row_count: u64,
+}
+
+impl<S: RangedSource> CloudCsvReader<S> {
+    /// Create a reader over a ranged source
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            offset: 0,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            pending: Vec::new(),
+            cursor: 0,
+            delimiter: b',',
+            quote_char: b'"',
+            has_header: false,
+            headers: Vec::new(),
+            row_count: 0,
+        }
+    }
+
+    /// Set the size of each ranged GET (builder pattern)
+    pub fn chunk_size(mut self, bytes: u64) -> Self {
+        self.chunk_size = bytes.max(1);
+        self
+    }
+
+    /// Set custom delimiter (builder pattern)
+    pub fn delimiter(mut self, delim: u8) -> Self {
+        self.delimiter = delim;
+        self
+    }
+
+    /// Set custom quote character (builder pattern)
+    pub fn quote_char(mut self, quote: u8) -> Self {
+        self.quote_char = quote;
+        self
+    }
+
+    /// Indicate that the first row contains headers (builder pattern)
+    ///
+    /// The header row is still returned by `read_row()`; it is also kept
+    /// and accessible via `headers()`.
+    pub fn has_header(mut self, has: bool) -> Self {
+        self.has_header = has;
+        self
+    }
+
+    /// Get header row if available
+    pub fn headers(&self) -> Option<&[String]> {
+        if self.headers.is_empty() {
+            None
+        } else {
+            Some(&self.headers)
+        }
+    }
+
+    /// Read a single record
+    ///
+    /// A quoted field can span several lines (`"Line1\nLine2"`), so one
+    /// record may cover more than one physical line. Returns `Ok(None)` once
+    /// the whole object has been consumed.
+    pub async fn read_row(&mut self) -> Result<Option<Vec<String>>> {
+        let size = self.source.size();
+        let parser = CsvParser::new(self.delimiter, self.quote_char);
+        // Start of the next physical line to scan; lines before it belong to
+        // the record and leave a quote open
+        let mut scan = self.cursor;
+        let mut in_quotes = false;
+        let range = loop {
+            if let Some(nl) = self.pending[scan..].iter().position(|&b| b == b'\n') {
+                let line_end = scan + nl + 1;
+                in_quotes ^= parser.flips_quote(&self.pending[scan..line_end]);
+                scan = line_end;
+                if !in_quotes {
+                    let start = self.cursor;
+                    self.cursor = line_end;
+                    break start..line_end - 1;
+                }
+                continue;
+            }
+
+            if self.offset >= size {
+                if self.cursor >= self.pending.len() {
+                    return Ok(None);
+                }
+                // Last record without trailing newline; an unterminated
+                // quote keeps what was read
+                let start = self.cursor;
+                self.cursor = self.pending.len();
+                break start..self.pending.len();
+            }
+
+            // Drop consumed bytes before fetching the next chunk
+            self.pending.drain(..self.cursor);
+            scan -= self.cursor;
+            self.cursor = 0;
+
+            let end = (self.offset + self.chunk_size).min(size);
+            let chunk = self.source.read_range(self.offset, end).await?;
+            self.offset = end;
+            self.pending.extend_from_slice(&chunk);
+        };
+
+        // Remove the record's line ending; ones inside quotes are data
+        let mut line = String::from_utf8_lossy(&self.pending[range]).into_owned();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+
+        let fields = parser.parse_line(&line);
+
+        if self.has_header && self.row_count == 0 {
+            self.headers = fields.clone();
+        }
+
+        self.row_count += 1;
+        Ok(Some(fields))
+    }
+
+    /// Get the number of rows read so far
+    pub fn row_count(&self) -> u64 {
+        self.row_count
+    }
+
+    /// Bytes fetched from the remote object so far
+    pub fn bytes_fetched(&self) -> u64 {
+        self.offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::ranged::tests::MemorySource;
+
+    #[tokio::test]
+    async fn test_read_rows_across_chunks() {
+        let data = b"id,name\n1,\"Smith, John\"\r\n2,Alice\n3,Bob".to_vec();
+        let mut reader = CloudCsvReader::new(MemorySource(data))
+            .chunk_size(5)
+            .has_header(true);
+
+        let mut rows = Vec::new();
+        while let Some(row) = reader.read_row().await.unwrap() {
+            rows.push(row);
+        }
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[1], vec!["1", "Smith, John"]);
+        assert_eq!(rows[3], vec!["3", "Bob"]);
+        assert_eq!(
+            reader.headers(),
+            Some(&["id".to_string(), "name".to_string()][..])
+        );
+        assert_eq!(reader.row_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_read_multiline_records_across_chunks() {
+        let data =
+            b"id,note\n1,\"Line1\r\nLine2, \"\"quoted\"\"\"\r\n2,plain\n3,\"tail\nend\"".to_vec();
+        let mut reader = CloudCsvReader::new(MemorySource(data)).chunk_size(4);
+
+        let mut rows = Vec::new();
+        while let Some(row) = reader.read_row().await.unwrap() {
+            rows.push(row);
+        }
+
+        assert_eq!(
+            rows,
+            vec![
+                vec!["id", "note"],
+                vec!["1", "Line1\r\nLine2, \"quoted\""],
+                vec!["2", "plain"],
+                vec!["3", "tail\nend"],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_fetches_what_is_needed() {
+        let data = b"a,b\nc,d\n".repeat(1000);
+        let size = data.len() as u64;
+        let mut reader = CloudCsvReader::new(MemorySource(data)).chunk_size(64);
+
+        reader.read_row().await.unwrap();
+        assert!(reader.bytes_fetched() < size);
+    }
+}
@@ -0,0 +1,293 @@
+//! GCS Excel and CSV readers with ranged streaming support
+//!
+//! Objects are fetched from Google Cloud Storage with ranged downloads
+//! (8 MB per request by default). For Excel files only the ZIP central
+//! directory and the parts that are read are fetched; CSV objects are
+//! parsed straight from the chunks.
+
+use super::csv_reader::CloudCsvReader;
+use super::ranged::{open_workbook, RangedSource, DEFAULT_CHUNK_SIZE};
+use crate::error::{ExcelError, Result};
+use crate::streaming_reader::{RowIterator, RowStructIterator, StreamingReader};
+
+use google_cloud_storage::client::{Client, ClientConfig};
+use google_cloud_storage::http::objects::download::Range;
+use google_cloud_storage::http::objects::get::GetObjectRequest;
+
+/// GCS Excel reader that streams an object from Google Cloud Storage
+///
+/// Works like [`S3ExcelReader`](super::S3ExcelReader): the central directory
+/// and then only the parts that are read are fetched with ranged downloads
+/// (one chunk in memory at a time), and rows stream with StreamingReader.
+///
+/// # Example
+///
+/// ```no_run
+/// use excelstream::cloud::GCSExcelReader;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut reader = GCSExcelReader::builder()
+///         .bucket("my-bucket")
+///         .object("reports/monthly.xlsx")
+///         .build()
+///         .await?;
+///
+///     for row in reader.rows("Sheet1")? {
+///         let row = row?;
+///         println!("Row {}: {:?}", row.index, row.to_strings());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct GCSExcelReader {
+    bucket: String,
+    object: String,
+    streaming_reader: StreamingReader,
+}
+
+impl GCSExcelReader {
+    /// Create a new GCS Excel reader builder
+    pub fn builder() -> GCSExcelReaderBuilder {
+        GCSExcelReaderBuilder::default()
+    }
+
+    /// Create GCSExcelReader from an existing GCS client
+    pub async fn from_gcs_client(
+        client: Client,
+        bucket: impl Into<String>,
+        object: impl Into<String>,
+    ) -> Result<Self> {
+        let bucket = bucket.into();
+        let object = object.into();
+        let source = GCSRangedSource::new(client, &bucket, &object).await?;
+        let streaming_reader = open_workbook(source, DEFAULT_CHUNK_SIZE).await?;
+
+        Ok(Self {
+            bucket,
+            object,
+            streaming_reader,
+        })
+    }
+
+    /// Get list of sheet names
+    pub fn sheet_names(&self) -> Vec<String> {
+        self.streaming_reader.sheet_names()
+    }
+
+    /// Stream rows from a worksheet (returns Row structs)
+    pub fn rows(&mut self, sheet_name: &str) -> Result<RowStructIterator<'_>> {
+        self.streaming_reader.rows(sheet_name)
+    }
+
+    /// Stream rows by sheet index (0 = first sheet)
+    pub fn rows_by_index(&mut self, sheet_index: usize) -> Result<RowStructIterator<'_>> {
+        self.streaming_reader.rows_by_index(sheet_index)
+    }
+
+    /// Stream rows from a worksheet (returns Vec<String>)
+    pub fn stream_rows(&mut self, sheet_name: &str) -> Result<RowIterator<'_>> {
+        self.streaming_reader.stream_rows(sheet_name)
+    }
+
+    /// Get worksheet dimensions (rows, columns)
+    pub fn dimensions(&mut self, sheet_name: &str) -> Result<(usize, usize)> {
+        self.streaming_reader.dimensions(sheet_name)
+    }
+
+    /// Get GCS bucket name
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    /// Get GCS object name
+    pub fn object(&self) -> &str {
+        &self.object
+    }
+}
+
+/// Builder for GCSExcelReader
+#[derive(Default)]
+pub struct GCSExcelReaderBuilder {
+    bucket: Option<String>,
+    object: Option<String>,
+}
+
+impl GCSExcelReaderBuilder {
+    /// Set the GCS bucket name
+    pub fn bucket(mut self, bucket: impl Into<String>) -> Self {
+        self.bucket = Some(bucket.into());
+        self
+    }
+
+    /// Set the GCS object name (file path)
+    pub fn object(mut self, object: impl Into<String>) -> Self {
+        self.object = Some(object.into());
+        self
+    }
+
+    /// Build the GCSExcelReader using default credentials
+    pub async fn build(self) -> Result<GCSExcelReader> {
+        let (bucket, object) = self.validate()?;
+        let client = default_client().await?;
+        GCSExcelReader::from_gcs_client(client, bucket, object).await
+    }
+
+    /// Build the GCSExcelReader with a pre-configured client
+    pub async fn build_with_client(self, client: Client) -> Result<GCSExcelReader> {
+        let (bucket, object) = self.validate()?;
+        GCSExcelReader::from_gcs_client(client, bucket, object).await
+    }
+
+    fn validate(self) -> Result<(String, String)> {
+        let bucket = self
+            .bucket
+            .ok_or_else(|| ExcelError::InvalidState("Bucket name required".to_string()))?;
+        let object = self
+            .object
+            .ok_or_else(|| ExcelError::InvalidState("Object name required".to_string()))?;
+        Ok((bucket, object))
+    }
+}
+
+/// A GCS object read with ranged downloads
+#[derive(Clone)]
+pub struct GCSRangedSource {
+    client: Client,
+    bucket: String,
+    object: String,
+    size: u64,
+}
+
+impl GCSRangedSource {
+    /// Look up the object metadata and prepare for ranged reads
+    pub async fn new(
+        client: Client,
+        bucket: impl Into<String>,
+        object: impl Into<String>,
+    ) -> Result<Self> {
+        let bucket = bucket.into();
+        let object = object.into();
+
+        let metadata = client
+            .get_object(&Self::request(&bucket, &object))
+            .await
+            .map_err(|e| {
+                ExcelError::ReadError(format!(
+                    "GCS get object gs://{}/{} failed: {}",
+                    bucket, object, e
+                ))
+            })?;
+
+        Ok(Self {
+            client,
+            bucket,
+            object,
+            size: metadata.size.max(0) as u64,
+        })
+    }
+
+    fn request(bucket: &str, object: &str) -> GetObjectRequest {
+        GetObjectRequest {
+            bucket: bucket.to_string(),
+            object: object.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+impl RangedSource for GCSRangedSource {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    async fn read_range(&self, start: u64, end: u64) -> Result<Vec<u8>> {
+        // GCS ranges are inclusive on both ends
+        let range = Range(Some(start), Some(end.saturating_sub(1)));
+        self.client
+            .download_object(&Self::request(&self.bucket, &self.object), &range)
+            .await
+            .map_err(|e| {
+                ExcelError::ReadError(format!(
+                    "GCS ranged download of gs://{}/{} failed: {}",
+                    self.bucket, self.object, e
+                ))
+            })
+    }
+}
+
+/// CSV reader that streams a GCS object with ranged downloads
+///
+/// # Example
+///
+/// ```no_run
+/// use excelstream::cloud::GCSCsvReader;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut reader = GCSCsvReader::open("my-bucket", "data.csv").await?;
+///
+///     while let Some(row) = reader.read_row().await? {
+///         println!("{:?}", row);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub type GCSCsvReader = CloudCsvReader<GCSRangedSource>;
+
+impl CloudCsvReader<GCSRangedSource> {
+    /// Open a CSV object on GCS using default credentials
+    pub async fn open(bucket: impl Into<String>, object: impl Into<String>) -> Result<Self> {
+        let client = default_client().await?;
+        Self::from_gcs_client(client, bucket, object).await
+    }
+
+    /// Open a CSV object on GCS with a pre-configured client
+    pub async fn from_gcs_client(
+        client: Client,
+        bucket: impl Into<String>,
+        object: impl Into<String>,
+    ) -> Result<Self> {
+        let source = GCSRangedSource::new(client, bucket, object).await?;
+        Ok(CloudCsvReader::new(source))
+    }
+}
+
+async fn default_client() -> Result<Client> {
+    let config = ClientConfig::default()
+        .with_auth()
+        .await
+        .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    Ok(Client::new(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_builder_validation_missing_bucket() {
+        let result = GCSExcelReaderBuilder::default()
+            .object("data.xlsx")
+            .build()
+            .await;
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("Bucket name required"));
+    }
+
+    #[tokio::test]
+    async fn test_builder_validation_missing_object() {
+        let result = GCSExcelReaderBuilder::default()
+            .bucket("my-bucket")
+            .build()
+            .await;
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("Object name required"));
+    }
+}
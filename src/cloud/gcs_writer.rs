@@ -1,15 +1,26 @@
 //! GCS Excel writer with TRUE direct streaming support (no temp files!)
 //!
 //! This module provides streaming Excel generation directly to Google Cloud Storage
-//! using s-zip's cloud support. NO local disk space required!
+//! as a resumable upload. NO local disk space required!
 
 use crate::error::{ExcelError, Result};
-use crate::types::{CellStyle, CellValue};
-
-#[cfg(feature = "cloud-gcs")]
-use s_zip::cloud::GCSZipWriter;
-#[cfg(feature = "cloud-gcs")]
-use s_zip::AsyncStreamingZipWriter;
+use crate::fast_writer::pipeline::ZipSink;
+use crate::progress::{CancellationToken, Progress};
+use crate::sheet_name::Shards;
+use crate::types::{CellRef, CellStyle, CellValue};
+use std::borrow::Borrow;
+use std::fmt::{Display, Write};
+use std::future::Future;
+
+use super::retry::AttemptError;
+use super::RetryPolicy;
+use google_cloud_storage::http::resumable_upload_client::{
+    ChunkSize, ResumableUploadClient, UploadStatus,
+};
+
+/// Size of an uploaded chunk; GCS wants a multiple of 256 KB for all but
+/// the last one
+const CHUNK_SIZE: usize = 8 * 1024 * 1024;
 
 /// GCS Excel writer that streams directly to Google Cloud Storage (no temp files!)
 ///
@@ -35,14 +46,277 @@ use s_zip::AsyncStreamingZipWriter;
 ///     Ok(())
 /// }
 /// ```
+///
+/// # Finishing or abandoning an upload
+///
+/// End every writer with [`save`](Self::save) (or its alias
+/// [`close`](Self::close)) or with [`abort`](Self::abort). The object is
+/// only created when the resumable upload is finalized by `save()`, and
+/// `abort()` cancels the upload session, so an aborted upload leaves
+/// nothing behind.
+///
+/// Dropping the writer without either call logs a warning to stderr and
+/// cancels the session. Inside a tokio runtime the cancel runs on a
+/// detached task, so `Drop` never blocks the task that owned the writer;
+/// outside a runtime it runs to completion on a temporary runtime before
+/// `drop` returns. Call `abort().await` explicitly when you want the
+/// cleanup to have finished before moving on (e.g. before the runtime
+/// shuts down).
+///
+/// # Transient errors
+///
+/// Chunks that fail with a 5xx, throttling or timeout error are retried
+/// with exponential backoff; see [`GCSExcelWriterBuilder::retry`]. When the
+/// attempts run out the row write fails but the row is already part of the
+/// workbook, and the chunk GCS didn't acknowledge is kept: call
+/// [`resume`](Self::resume) to send it again and carry on with the next
+/// row.
 pub struct GCSExcelWriter {
-    zip_writer: Option<AsyncStreamingZipWriter<GCSZipWriter>>,
+    zip_writer: Option<ZipSink<Vec<u8>>>,
+    upload: Option<ResumableUpload<ResumableUploadClient>>,
     current_row: u32,
     max_col: u32,
     xml_buffer: Vec<u8>,
+    scratch: String,
     worksheet_count: u32,
     worksheets: Vec<String>,
     in_worksheet: bool,
+    progress: Progress,
+    /// Rolling rows over to a new sheet, see `auto_shard_rows`
+    shards: Shards,
+}
+
+impl Drop for GCSExcelWriter {
+    fn drop(&mut self) {
+        let Some(upload) = self.upload.take() else {
+            return;
+        };
+
+        crate::trace::warning!(
+            "GCSExcelWriter dropped without save() or abort(); the upload was discarded"
+        );
+
+        let cancel = async move {
+            if let Err(e) = upload.cancel().await {
+                crate::trace::warning!("failed to cancel GCS upload: {}", e);
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(cancel);
+            }
+            // Nothing else runs on this thread, so waiting for the cancel is fine
+            Err(_) => match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime.block_on(cancel),
+                Err(e) => {
+                    crate::trace::warning!("failed to cancel GCS upload: {}", e);
+                }
+            },
+        }
+    }
+}
+
+/// How much of the object GCS has stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stored {
+    /// The first `n` bytes
+    Bytes(u64),
+    /// All of it; the object was created
+    Complete,
+}
+
+/// The calls behind a GCS resumable upload session, so tests can stand in
+/// for GCS
+pub(crate) trait ResumableSession: Send + Sync {
+    /// Send `data` as the object bytes from `offset` on; `total` is the
+    /// object size, known once the last chunk is sent
+    fn put(
+        &self,
+        offset: u64,
+        data: Vec<u8>,
+        total: Option<u64>,
+    ) -> impl Future<Output = std::result::Result<Stored, AttemptError>> + Send;
+
+    /// Ask how much GCS has stored, after a request that may have been cut
+    /// off halfway
+    fn status(&self) -> impl Future<Output = std::result::Result<Stored, AttemptError>> + Send;
+
+    /// Cancel the session; no object is created
+    fn cancel(&self) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Whether a failed GCS request is worth another attempt
+fn attempt_error(err: google_cloud_storage::http::Error) -> AttemptError {
+    use google_cloud_storage::http::Error;
+
+    let transient = match &err {
+        Error::Response(response) => matches!(response.code, 408 | 429 | 500..),
+        Error::HttpClient(e) => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+        _ => false,
+    };
+    let err = ExcelError::IoError(std::io::Error::other(err.to_string()));
+    if transient {
+        AttemptError::Transient(err)
+    } else {
+        AttemptError::Permanent(err)
+    }
+}
+
+fn stored(status: UploadStatus) -> Stored {
+    match status {
+        UploadStatus::Ok(_) => Stored::Complete,
+        UploadStatus::NotStarted => Stored::Bytes(0),
+        UploadStatus::ResumeIncomplete(range) => Stored::Bytes(range.last_byte + 1),
+    }
+}
+
+impl ResumableSession for ResumableUploadClient {
+    async fn put(
+        &self,
+        offset: u64,
+        data: Vec<u8>,
+        total: Option<u64>,
+    ) -> std::result::Result<Stored, AttemptError> {
+        // An empty last chunk only tells GCS the final size
+        let last_byte = (offset + data.len() as u64).saturating_sub(1);
+        let range = ChunkSize::new(offset, last_byte, total);
+        self.upload_multiple_chunk(data, &range)
+            .await
+            .map(stored)
+            .map_err(attempt_error)
+    }
+
+    async fn status(&self) -> std::result::Result<Stored, AttemptError> {
+        ResumableUploadClient::status(self, None)
+            .await
+            .map(stored)
+            .map_err(attempt_error)
+    }
+
+    async fn cancel(&self) -> Result<()> {
+        self.clone()
+            .cancel()
+            .await
+            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))
+    }
+}
+
+/// A resumable upload and how much of the object GCS has received
+///
+/// Bytes stay in `pending` until GCS acknowledges them. A failed chunk is
+/// retried per the [`RetryPolicy`]; once the attempts run out it is kept
+/// and sent again, after the bytes still missing, by the next
+/// [`upload_chunk`](Self::upload_chunk), [`resume`](Self::resume) or
+/// [`finish`](Self::finish).
+pub(crate) struct ResumableUpload<S> {
+    session: S,
+    retry: RetryPolicy,
+    /// Bytes acknowledged so far
+    offset: u64,
+    /// Bytes from `offset` on that GCS hasn't acknowledged yet
+    pending: Vec<u8>,
+    /// A request failed, so GCS may hold more than `offset` bytes
+    unsure: bool,
+}
+
+impl<S: ResumableSession> ResumableUpload<S> {
+    pub(crate) fn new(session: S, retry: RetryPolicy) -> Self {
+        ResumableUpload {
+            session,
+            retry,
+            offset: 0,
+            pending: Vec::new(),
+            unsure: false,
+        }
+    }
+
+    pub(crate) async fn upload_chunk(&mut self, chunk: Vec<u8>) -> Result<()> {
+        if self.pending.is_empty() {
+            self.pending = chunk;
+        } else {
+            self.pending.extend_from_slice(&chunk);
+        }
+        self.send(false).await
+    }
+
+    /// Send the bytes a failed upload left unacknowledged
+    pub(crate) async fn resume(&mut self) -> Result<()> {
+        if self.pending.is_empty() && !self.unsure {
+            return Ok(());
+        }
+        self.send(false).await
+    }
+
+    /// Send the last chunk and create the object
+    pub(crate) async fn finish(mut self, last_chunk: Vec<u8>) -> Result<()> {
+        self.pending.extend_from_slice(&last_chunk);
+        self.send(true).await
+    }
+
+    pub(crate) async fn cancel(&self) -> Result<()> {
+        self.session.cancel().await
+    }
+
+    /// Send `pending` until GCS holds all of it, retrying per the policy;
+    /// `last` also completes the object
+    async fn send(&mut self, last: bool) -> Result<()> {
+        let mut attempt = 1;
+        loop {
+            match self.try_send(last).await {
+                Ok(true) => return Ok(()),
+                // GCS stored part of the chunk; send the rest
+                Ok(false) => {}
+                Err(e) if self.retry.retry_after(attempt, &e).await => attempt += 1,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// One request; `Ok(true)` once nothing is left to send
+    async fn try_send(&mut self, last: bool) -> std::result::Result<bool, AttemptError> {
+        if self.unsure {
+            // The failed request may have been stored in part or in full
+            let stored = self.retry.attempt(self.session.status()).await?;
+            self.unsure = false;
+            if self.acknowledge(stored) || self.pending.is_empty() && !last {
+                return Ok(true);
+            }
+        }
+
+        let total = last.then(|| self.offset + self.pending.len() as u64);
+        let offset = self.offset;
+        self.unsure = true;
+        let stored = self
+            .retry
+            .attempt(self.session.put(offset, self.pending.clone(), total))
+            .await?;
+        self.unsure = false;
+        if self.acknowledge(stored) || self.pending.is_empty() && !last {
+            return Ok(true);
+        }
+        if self.offset == offset {
+            return Err(AttemptError::Transient(ExcelError::IoError(
+                std::io::Error::other("GCS stored none of the chunk"),
+            )));
+        }
+        Ok(false)
+    }
+
+    /// Drop the bytes GCS now holds from `pending`; `true` once the
+    /// object is complete
+    fn acknowledge(&mut self, stored: Stored) -> bool {
+        let end = self.offset + self.pending.len() as u64;
+        let bytes = match stored {
+            Stored::Complete => end,
+            Stored::Bytes(n) => n.clamp(self.offset, end),
+        };
+        self.pending.drain(..(bytes - self.offset) as usize);
+        self.offset = bytes;
+        stored == Stored::Complete
+    }
 }
 
 impl GCSExcelWriter {
@@ -51,63 +325,86 @@ impl GCSExcelWriter {
         GCSExcelWriterBuilder::default()
     }
 
-    async fn ensure_worksheet(&mut self) -> Result<()> {
+    /// Call `callback` with `(rows_written, bytes_written)` every
+    /// [`PROGRESS_INTERVAL`](crate::progress::PROGRESS_INTERVAL) rows and once
+    /// from `save()`; bytes are counted before compression
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(u64, u64) + Send + 'static,
+    {
+        self.progress.set_callback(Box::new(callback));
+    }
+
+    /// Make row writes and `save()` fail with `ExcelError::Cancelled` once
+    /// `token` is cancelled
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.progress.set_token(token);
+    }
+
+    /// Send the chunk a failed upload left unacknowledged
+    ///
+    /// Picks up at the byte GCS last stored, so nothing is sent twice or
+    /// skipped. The row whose write failed is already in the workbook;
+    /// don't write it again. Fails like the upload did if GCS is still
+    /// unreachable, and can be called again later.
+    ///
+    /// ```ignore
+    /// if let Err(e) = writer.write_row(&row).await {
+    ///     eprintln!("upload failed: {}, retrying in a minute", e);
+    ///     tokio::time::sleep(Duration::from_secs(60)).await;
+    ///     writer.resume().await?;
+    /// }
+    /// ```
+    pub async fn resume(&mut self) -> Result<()> {
+        match self.upload.as_mut() {
+            Some(upload) => upload.resume().await,
+            None => Ok(()),
+        }
+    }
+
+    fn ensure_worksheet(&mut self) -> Result<()> {
         if !self.in_worksheet {
-            self.add_worksheet("Sheet1").await?;
+            self.add_worksheet("Sheet1")?;
         }
         Ok(())
     }
 
     /// Add a new worksheet
-    async fn add_worksheet(&mut self, name: &str) -> Result<()> {
+    fn add_worksheet(&mut self, name: &str) -> Result<()> {
         // Finish previous worksheet if any
         if self.in_worksheet {
-            self.finish_current_worksheet().await?;
+            self.finish_current_worksheet()?;
         }
 
         self.worksheet_count += 1;
         self.worksheets.push(name.to_string());
         self.current_row = 0;
         self.max_col = 0;
+        self.shards.reset();
 
         // Start new worksheet entry in ZIP
         let entry_name = format!("xl/worksheets/sheet{}.xml", self.worksheet_count);
-        self.zip_writer
-            .as_mut()
-            .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?
-            .start_entry(&entry_name)
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.start_entry(&entry_name)?;
 
         // Write worksheet XML header
         let header = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
 <sheetData>"#;
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(header.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(header.as_bytes())?;
 
         self.in_worksheet = true;
         Ok(())
     }
 
-    async fn finish_current_worksheet(&mut self) -> Result<()> {
+    fn finish_current_worksheet(&mut self) -> Result<()> {
         if !self.in_worksheet {
             return Ok(());
         }
 
         // Close sheetData and worksheet tags
         let footer = "</sheetData></worksheet>";
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(footer.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(footer.as_bytes())?;
 
         self.in_worksheet = false;
         Ok(())
@@ -119,7 +416,7 @@ impl GCSExcelWriter {
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
-        self.ensure_worksheet().await?;
+        self.ensure_worksheet()?;
 
         let cells: Vec<_> = headers
             .into_iter()
@@ -140,62 +437,201 @@ impl GCSExcelWriter {
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
-        self.ensure_worksheet().await?;
+        self.ensure_worksheet()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
 
         self.current_row += 1;
-        let values: Vec<String> = row.into_iter().map(|s| s.as_ref().to_string()).collect();
-        self.max_col = self.max_col.max(values.len() as u32);
+        self.start_row_xml();
 
-        // Build row XML in buffer
+        let mut col_count = 0;
+        for (col_idx, value) in row.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            col_count += 1;
+            self.push_inline_str_cell(col_idx as u32, value.as_ref());
+        }
+
+        self.finish_row_xml(col_count).await
+    }
+
+    /// Write a data row of `Display` values (numbers, dates, custom types...)
+    ///
+    /// Values are formatted into a reused scratch buffer, so no intermediate
+    /// `Vec<String>` is built per row.
+    pub async fn write_row_display<I>(&mut self, row: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.ensure_worksheet()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
+
+        self.current_row += 1;
+        self.start_row_xml();
+
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let mut col_count = 0;
+        for (col_idx, value) in row.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.scratch = scratch;
+                self.current_row -= 1;
+                return Err(e);
+            }
+            col_count += 1;
+            scratch.clear();
+            // Writing into a String never fails
+            let _ = write!(scratch, "{}", value);
+            self.push_inline_str_cell(col_idx as u32, &scratch);
+        }
+        self.scratch = scratch;
+
+        self.finish_row_xml(col_count).await
+    }
+
+    fn start_row_xml(&mut self) {
         self.xml_buffer.clear();
         self.xml_buffer.extend_from_slice(b"<row r=\"");
         self.xml_buffer
             .extend_from_slice(self.current_row.to_string().as_bytes());
         self.xml_buffer.extend_from_slice(b"\">");
+    }
 
-        for (col_idx, value) in values.iter().enumerate() {
-            let col_letter = Self::column_letter(col_idx as u32 + 1);
-            self.xml_buffer.extend_from_slice(b"<c r=\"");
-            self.xml_buffer.extend_from_slice(col_letter.as_bytes());
+    fn push_inline_str_cell(&mut self, col_idx: u32, value: &str) {
+        let col_letter = Self::column_letter(col_idx + 1);
+        self.xml_buffer.extend_from_slice(b"<c r=\"");
+        self.xml_buffer.extend_from_slice(col_letter.as_bytes());
+        self.xml_buffer
+            .extend_from_slice(self.current_row.to_string().as_bytes());
+
+        if value.is_empty() {
+            self.xml_buffer.extend_from_slice(b"\"/>");
+        } else {
             self.xml_buffer
-                .extend_from_slice(self.current_row.to_string().as_bytes());
+                .extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
+            Self::write_escaped(&mut self.xml_buffer, value);
+            self.xml_buffer.extend_from_slice(b"</t></is></c>");
+        }
+    }
 
-            if value.is_empty() {
-                self.xml_buffer.extend_from_slice(b"\"/>");
-            } else {
-                self.xml_buffer
-                    .extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
-                Self::write_escaped(&mut self.xml_buffer, value.as_str());
-                self.xml_buffer.extend_from_slice(b"</t></is></c>");
-            }
+    /// Make room for the next row, rolling over to a new shard if the sheet
+    /// is full
+    fn check_row_limit(&mut self) -> Result<()> {
+        if !self.shards.is_full(self.current_row) {
+            return Ok(());
+        }
+        let name = self.worksheets.last().cloned().unwrap_or_default();
+        let next = self.shards.next(&name, &self.worksheets)?;
+        let max_col = self.max_col;
+        self.add_worksheet(&next.name)?;
+        // The header is row 1 again, so its XML is reused as it is
+        if let Some(header) = self.shards.started(next) {
+            self.current_row = 1;
+            self.max_col = max_col;
+            self.write_data(&header)?;
+            self.progress.row_written(header.len());
         }
+        Ok(())
+    }
 
+    async fn finish_row_xml(&mut self, col_count: u32) -> Result<()> {
+        self.max_col = self.max_col.max(col_count);
         self.xml_buffer.extend_from_slice(b"</row>");
+        self.shards
+            .keep_header(self.current_row, || self.xml_buffer.clone());
 
-        // Stream to GCS immediately
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(&self.xml_buffer)
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        let row = std::mem::take(&mut self.xml_buffer);
+        let written = self.write_data(&row);
+        self.xml_buffer = row;
+        written?;
+        self.progress.row_written(self.xml_buffer.len());
 
-        Ok(())
+        // Stream to GCS as soon as a chunk is full
+        self.upload_full_chunks().await
     }
 
     /// Write a data row with typed values
     pub async fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
-        let styled_cells: Vec<_> = cells
-            .iter()
-            .map(|v| crate::types::StyledCell::new(v.clone(), CellStyle::Default))
-            .collect();
+        self.write_row_typed_iter(cells).await
+    }
+
+    /// Write a data row from an iterator of owned or borrowed typed values,
+    /// without collecting it first
+    pub async fn write_row_typed_iter<I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CellValue>,
+    {
+        self.ensure_worksheet()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
+
+        self.current_row += 1;
+        self.start_row_xml();
+
+        let mut col_count = 0;
+        for (col_idx, value) in cells.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            col_count += 1;
+            let value = value.borrow();
+            let style_id = value.default_style().index();
+            self.push_styled_cell(col_idx as u32, value, style_id);
+        }
+
+        self.finish_row_xml(col_count).await
+    }
+
+    /// Write a data row of borrowed cells, see [`CellRef`]
+    pub async fn write_row_refs<'a, I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<CellRef<'a>>,
+    {
+        self.ensure_worksheet()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
 
-        self.write_row_styled(&styled_cells).await
+        self.current_row += 1;
+        self.start_row_xml();
+
+        let mut col_count = 0;
+        for (col_idx, cell) in cells.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            col_count += 1;
+            let owned;
+            let value = match cell.into() {
+                CellRef::String(s) => {
+                    self.push_inline_str_cell(col_idx as u32, s);
+                    continue;
+                }
+                CellRef::Value(value) => value,
+                cell => {
+                    owned = cell.into_owned();
+                    &owned
+                }
+            };
+            let style_id = value.default_style().index();
+            self.push_styled_cell(col_idx as u32, value, style_id);
+        }
+
+        self.finish_row_xml(col_count).await
     }
 
     /// Write a row with styled cells
     async fn write_row_styled(&mut self, cells: &[crate::types::StyledCell]) -> Result<()> {
-        self.ensure_worksheet().await?;
+        self.ensure_worksheet()?;
+        self.progress.check()?;
+        crate::sheet_name::check_columns(cells.len())?;
+        self.check_row_limit()?;
 
         self.current_row += 1;
         self.max_col = self.max_col.max(cells.len() as u32);
@@ -208,115 +644,180 @@ impl GCSExcelWriter {
         self.xml_buffer.extend_from_slice(b"\">");
 
         for (col_idx, styled_cell) in cells.iter().enumerate() {
-            let col_letter = Self::column_letter(col_idx as u32 + 1);
-            let value = &styled_cell.value;
             let style_id = styled_cell.style.index();
+            self.push_styled_cell(col_idx as u32, &styled_cell.value, style_id);
+        }
+
+        self.finish_row_xml(cells.len() as u32).await
+    }
+
+    /// Append one `<c>` element to the row being built
+    fn push_styled_cell(&mut self, col_idx: u32, value: &CellValue, style_id: u32) {
+        let col_letter = Self::column_letter(col_idx + 1);
+
+        self.xml_buffer.extend_from_slice(b"<c r=\"");
+        self.xml_buffer.extend_from_slice(col_letter.as_bytes());
+        self.xml_buffer
+            .extend_from_slice(self.current_row.to_string().as_bytes());
+        self.xml_buffer.extend_from_slice(b"\"");
 
-            self.xml_buffer.extend_from_slice(b"<c r=\"");
-            self.xml_buffer.extend_from_slice(col_letter.as_bytes());
+        // Add style attribute if not default
+        if style_id > 0 {
+            self.xml_buffer.extend_from_slice(b" s=\"");
             self.xml_buffer
-                .extend_from_slice(self.current_row.to_string().as_bytes());
+                .extend_from_slice(style_id.to_string().as_bytes());
             self.xml_buffer.extend_from_slice(b"\"");
+        }
 
-            // Add style attribute if not default
-            if style_id > 0 {
-                self.xml_buffer.extend_from_slice(b" s=\"");
+        // Write cell value based on type
+        match value {
+            CellValue::Empty => {
+                self.xml_buffer.extend_from_slice(b"/>");
+            }
+            CellValue::Int(i) => {
+                self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
+                self.xml_buffer.extend_from_slice(i.to_string().as_bytes());
+                self.xml_buffer.extend_from_slice(b"</v></c>");
+            }
+            CellValue::Float(f) => {
+                crate::fast_writer::number::push_number_cell(&mut self.xml_buffer, *f);
+            }
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => {
+                self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
+                self.xml_buffer.extend_from_slice(d.to_string().as_bytes());
+                self.xml_buffer.extend_from_slice(b"</v></c>");
+            }
+            CellValue::Bool(b) => {
+                self.xml_buffer.extend_from_slice(b" t=\"b\"><v>");
                 self.xml_buffer
-                    .extend_from_slice(style_id.to_string().as_bytes());
-                self.xml_buffer.extend_from_slice(b"\"");
+                    .extend_from_slice(if *b { b"1" } else { b"0" });
+                self.xml_buffer.extend_from_slice(b"</v></c>");
             }
-
-            // Write cell value based on type
-            match value {
-                CellValue::Empty => {
-                    self.xml_buffer.extend_from_slice(b"/>");
-                }
-                CellValue::Int(i) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(i.to_string().as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                CellValue::Float(f) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(f.to_string().as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                CellValue::Bool(b) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"b\"><v>");
-                    self.xml_buffer
-                        .extend_from_slice(if *b { b"1" } else { b"0" });
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                CellValue::String(s) => {
-                    self.xml_buffer
-                        .extend_from_slice(b" t=\"inlineStr\"><is><t>");
-                    Self::write_escaped(&mut self.xml_buffer, s);
-                    self.xml_buffer.extend_from_slice(b"</t></is></c>");
-                }
-                CellValue::Formula(f) => {
-                    self.xml_buffer.extend_from_slice(b"><f>");
-                    Self::write_escaped(&mut self.xml_buffer, f);
-                    self.xml_buffer.extend_from_slice(b"</f></c>");
-                }
-                CellValue::DateTime(dt) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(dt.to_string().as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
+            // Hyperlinks are written as their text only
+            CellValue::String(s) | CellValue::Hyperlink { text: s, .. } => {
+                self.xml_buffer
+                    .extend_from_slice(b" t=\"inlineStr\"><is><t>");
+                Self::write_escaped(&mut self.xml_buffer, s);
+                self.xml_buffer.extend_from_slice(b"</t></is></c>");
+            }
+            CellValue::BigInt(digits) => {
+                self.xml_buffer
+                    .extend_from_slice(b" t=\"inlineStr\"><is><t>");
+                Self::write_escaped(&mut self.xml_buffer, digits);
+                self.xml_buffer.extend_from_slice(b"</t></is></c>");
+            }
+            CellValue::Formula { expr, cached } => {
+                let result = cached.as_deref().and_then(CellValue::formula_result);
+                if let Some((type_attr, _)) = &result {
+                    self.xml_buffer.extend_from_slice(type_attr.as_bytes());
                 }
-                CellValue::Error(e) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"e\"><v>");
-                    Self::write_escaped(&mut self.xml_buffer, e);
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
+                self.xml_buffer.extend_from_slice(b"><f>");
+                Self::write_escaped(&mut self.xml_buffer, expr.strip_prefix('=').unwrap_or(expr));
+                self.xml_buffer.extend_from_slice(b"</f>");
+                if let Some((_, value)) = &result {
+                    self.xml_buffer.extend_from_slice(b"<v>");
+                    Self::write_escaped(&mut self.xml_buffer, value);
+                    self.xml_buffer.extend_from_slice(b"</v>");
                 }
+                self.xml_buffer.extend_from_slice(b"</c>");
+            }
+            CellValue::DateTime(dt) => {
+                crate::fast_writer::number::push_number_cell(&mut self.xml_buffer, *dt);
+            }
+            CellValue::Error(e) => {
+                self.xml_buffer.extend_from_slice(b" t=\"e\"><v>");
+                Self::write_escaped(&mut self.xml_buffer, e);
+                self.xml_buffer.extend_from_slice(b"</v></c>");
             }
         }
-
-        self.xml_buffer.extend_from_slice(b"</row>");
-
-        // Stream to GCS immediately
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(&self.xml_buffer)
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
-
-        Ok(())
     }
 
     /// Save and upload Excel file to GCS (streaming, no temp files!)
     pub async fn save(mut self) -> Result<()> {
+        self.progress.check()?;
         // Finish current worksheet
-        self.finish_current_worksheet().await?;
+        self.finish_current_worksheet()?;
+        self.progress.finish();
 
         // Write all required Excel files
-        self.write_content_types().await?;
-        self.write_rels().await?;
-        self.write_workbook().await?;
-        self.write_workbook_rels().await?;
-        self.write_styles().await?;
+        self.write_content_types()?;
+        self.write_rels()?;
+        self.write_workbook()?;
+        self.write_workbook_rels()?;
+        self.write_styles()?;
 
-        // Finish ZIP - this completes the GCS upload
+        // Finish ZIP - the last chunk completes the GCS upload
         let zip_writer = self
             .zip_writer
             .take()
             .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?;
+        let last_chunk = zip_writer.finish()?;
+        let upload = self
+            .upload
+            .take()
+            .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?;
+        upload.finish(last_chunk).await
+    }
 
-        zip_writer
-            .finish()
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    /// Finish the workbook and complete the upload
+    ///
+    /// Same as [`save`](Self::save); provided so the writer can be ended
+    /// with an explicit `close()` / `abort()` pair.
+    pub async fn close(self) -> Result<()> {
+        self.save().await
+    }
 
-        Ok(())
+    /// Abandon the upload without creating the object
+    ///
+    /// The resumable upload session is cancelled, so no object (and no
+    /// partial object) appears in the bucket.
+    pub async fn abort(mut self) -> Result<()> {
+        drop(self.zip_writer.take());
+        match self.upload.take() {
+            Some(upload) => upload.cancel().await,
+            None => Ok(()),
+        }
     }
 
-    async fn write_content_types(&mut self) -> Result<()> {
+    fn start_entry(&mut self, name: &str) -> Result<()> {
         self.zip_writer
             .as_mut()
-            .unwrap()
-            .start_entry("[Content_Types].xml")
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+            .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?
+            .start_entry(name)
+    }
+
+    /// Add `data` to the current entry
+    fn write_data(&mut self, data: &[u8]) -> Result<()> {
+        self.zip_writer
+            .as_mut()
+            .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?
+            .write_data(data)
+    }
+
+    /// Upload the full chunks written so far
+    ///
+    /// Only called once a row is complete, so a failed upload never leaves
+    /// a row or sheet half written.
+    async fn upload_full_chunks(&mut self) -> Result<()> {
+        let (Some(zip_writer), Some(upload)) = (self.zip_writer.as_mut(), self.upload.as_mut())
+        else {
+            return Err(ExcelError::InvalidState(
+                "Writer not initialized".to_string(),
+            ));
+        };
+        let output = zip_writer.output_mut();
+        while output.len() >= CHUNK_SIZE {
+            // Chunks before the last must be a multiple of 256 KB
+            let rest = output.split_off(CHUNK_SIZE);
+            let chunk = std::mem::replace(output, rest);
+            upload.upload_chunk(chunk).await?;
+        }
+        Ok(())
+    }
+
+    fn write_content_types(&mut self) -> Result<()> {
+        self.start_entry("[Content_Types].xml")?;
 
         let mut xml = String::from(
             r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
@@ -336,46 +837,26 @@ impl GCSExcelWriter {
 
         xml.push_str("</Types>");
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(xml.as_bytes())?;
 
         Ok(())
     }
 
-    async fn write_rels(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("_rels/.rels")
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    fn write_rels(&mut self) -> Result<()> {
+        self.start_entry("_rels/.rels")?;
 
         let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
 <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
 </Relationships>"#;
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(xml.as_bytes())?;
 
         Ok(())
     }
 
-    async fn write_workbook(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/workbook.xml")
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    fn write_workbook(&mut self) -> Result<()> {
+        self.start_entry("xl/workbook.xml")?;
 
         let mut xml = String::from(
             r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
@@ -387,29 +868,21 @@ impl GCSExcelWriter {
             let sheet_id = idx + 1;
             xml.push_str(&format!(
                 r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#,
-                name, sheet_id, sheet_id
+                crate::fast_writer::xml_writer::escape(name),
+                sheet_id,
+                sheet_id
             ));
         }
 
         xml.push_str("</sheets></workbook>");
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(xml.as_bytes())?;
 
         Ok(())
     }
 
-    async fn write_workbook_rels(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/_rels/workbook.xml.rels")
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    fn write_workbook_rels(&mut self) -> Result<()> {
+        self.start_entry("xl/_rels/workbook.xml.rels")?;
 
         let mut xml = String::from(
             r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
@@ -430,23 +903,13 @@ impl GCSExcelWriter {
 
         xml.push_str("</Relationships>");
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(xml.as_bytes())?;
 
         Ok(())
     }
 
-    async fn write_styles(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/styles.xml")
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    fn write_styles(&mut self) -> Result<()> {
+        self.start_entry("xl/styles.xml")?;
 
         let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
@@ -462,12 +925,7 @@ impl GCSExcelWriter {
 </cellXfs>
 </styleSheet>"#;
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(xml.as_bytes())?;
 
         Ok(())
     }
@@ -484,19 +942,7 @@ impl GCSExcelWriter {
     }
 
     fn write_escaped(buffer: &mut Vec<u8>, text: &str) {
-        for ch in text.chars() {
-            match ch {
-                '<' => buffer.extend_from_slice(b"&lt;"),
-                '>' => buffer.extend_from_slice(b"&gt;"),
-                '&' => buffer.extend_from_slice(b"&amp;"),
-                '"' => buffer.extend_from_slice(b"&quot;"),
-                '\'' => buffer.extend_from_slice(b"&apos;"),
-                _ => {
-                    let mut buf = [0; 4];
-                    buffer.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
-                }
-            }
-        }
+        crate::fast_writer::xml_writer::push_escaped(buffer, text)
     }
 }
 
@@ -505,6 +951,8 @@ impl GCSExcelWriter {
 pub struct GCSExcelWriterBuilder {
     bucket: Option<String>,
     object: Option<String>,
+    auto_shard_rows: Option<u32>,
+    retry: Option<RetryPolicy>,
 }
 
 impl GCSExcelWriterBuilder {
@@ -520,41 +968,107 @@ impl GCSExcelWriterBuilder {
         self
     }
 
+    /// Continue on a new sheet once a sheet holds `max_rows_per_sheet`
+    /// rows
+    ///
+    /// Rows past the limit go to `Sheet1_2`, `Sheet1_3`, ... for a sheet
+    /// named `Sheet1`, each starting with a copy of the sheet's first row
+    /// as its header. Without this, writing past Excel's 1 048 576 rows per
+    /// sheet fails.
+    pub fn auto_shard_rows(mut self, max_rows_per_sheet: u32) -> Self {
+        self.auto_shard_rows = Some(max_rows_per_sheet);
+        self
+    }
+
+    /// Retry failed chunk uploads per `policy`
+    ///
+    /// [`RetryPolicy::default`] is used when this is not called. A chunk
+    /// that GCS stored only in part is continued from the last stored byte.
+    ///
+    /// ```ignore
+    /// use excelstream::cloud::{GCSExcelWriter, RetryPolicy};
+    /// use std::time::Duration;
+    ///
+    /// let writer = GCSExcelWriter::builder()
+    ///     .bucket("exports")
+    ///     .object("orders.xlsx")
+    ///     .retry(RetryPolicy::new(8).with_attempt_timeout(Duration::from_secs(120)))
+    ///     .build()
+    ///     .await?;
+    /// ```
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
     /// Build the GCSExcelWriter
     #[cfg(feature = "cloud-gcs")]
     pub async fn build(self) -> Result<GCSExcelWriter> {
         use google_cloud_storage::client::{Client, ClientConfig};
 
-        let bucket = self
-            .bucket
-            .ok_or_else(|| ExcelError::InvalidState("Bucket name required".to_string()))?;
-        let object = self
-            .object
-            .ok_or_else(|| ExcelError::InvalidState("Object name required".to_string()))?;
-
         // Create GCS client
         let config = ClientConfig::default()
             .with_auth()
             .await
             .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
-        let client = Client::new(config);
 
-        // Create GCS writer - streams directly to GCS!
-        let gcs_writer = GCSZipWriter::new(client, &object, &bucket)
+        self.build_with_client(Client::new(config)).await
+    }
+
+    /// Build the GCSExcelWriter with a pre-configured GCS client
+    ///
+    /// Use this for custom endpoints such as a local fake-gcs-server.
+    #[cfg(feature = "cloud-gcs")]
+    pub async fn build_with_client(
+        self,
+        client: google_cloud_storage::client::Client,
+    ) -> Result<GCSExcelWriter> {
+        use google_cloud_storage::http::objects::upload::{UploadObjectRequest, UploadType};
+        use google_cloud_storage::http::objects::Object;
+
+        let bucket = self
+            .bucket
+            .ok_or_else(|| ExcelError::InvalidState("Bucket name required".to_string()))?;
+        let object = self
+            .object
+            .ok_or_else(|| ExcelError::InvalidState("Object name required".to_string()))?;
+        let shard_rows = self
+            .auto_shard_rows
+            .map(crate::sheet_name::check_shard_rows)
+            .transpose()?;
+
+        // Start the resumable upload - chunks stream directly to GCS!
+        let request = UploadObjectRequest {
+            bucket,
+            ..Default::default()
+        };
+        let metadata = Object {
+            name: object,
+            content_type: Some(
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string(),
+            ),
+            ..Default::default()
+        };
+        let session = client
+            .prepare_resumable_upload(&request, &UploadType::Multipart(Box::new(metadata)))
             .await
             .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
 
-        // Wrap in AsyncStreamingZipWriter
-        let zip_writer = AsyncStreamingZipWriter::from_writer(gcs_writer);
-
         Ok(GCSExcelWriter {
-            zip_writer: Some(zip_writer),
+            zip_writer: Some(ZipSink::new(Vec::new(), 6)),
+            upload: Some(ResumableUpload::new(
+                session,
+                self.retry.unwrap_or_default(),
+            )),
             current_row: 0,
             max_col: 0,
             xml_buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
             worksheet_count: 0,
             worksheets: Vec::new(),
             in_worksheet: false,
+            progress: Progress::default(),
+            shards: Shards::new(shard_rows),
         })
     }
 
@@ -565,3 +1079,127 @@ impl GCSExcelWriterBuilder {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Records the session calls instead of sending them
+    #[derive(Clone, Default)]
+    struct MockSession {
+        calls: Arc<Mutex<Vec<String>>>,
+        /// Puts still to fail, as the bytes of the chunk GCS keeps anyway
+        /// and whether the failure is transient
+        failures: Arc<Mutex<Vec<(u64, bool)>>>,
+        stored: Arc<Mutex<u64>>,
+    }
+
+    impl MockSession {
+        fn fail_next(&self, kept: u64, transient: bool) {
+            self.failures.lock().unwrap().push((kept, transient));
+        }
+    }
+
+    impl ResumableSession for MockSession {
+        async fn put(
+            &self,
+            offset: u64,
+            data: Vec<u8>,
+            total: Option<u64>,
+        ) -> std::result::Result<Stored, AttemptError> {
+            let shown = total.map_or("*".to_string(), |t| t.to_string());
+            let call = format!("put {}+{}/{}", offset, data.len(), shown);
+            self.calls.lock().unwrap().push(call);
+
+            let mut stored = self.stored.lock().unwrap();
+            if let Some((kept, transient)) = self.failures.lock().unwrap().pop() {
+                *stored = offset + kept;
+                let e = ExcelError::IoError(std::io::ErrorKind::ConnectionReset.into());
+                return Err(if transient {
+                    AttemptError::Transient(e)
+                } else {
+                    AttemptError::Permanent(e)
+                });
+            }
+            *stored = offset + data.len() as u64;
+            Ok(match total {
+                Some(_) => Stored::Complete,
+                None => Stored::Bytes(*stored),
+            })
+        }
+
+        async fn status(&self) -> std::result::Result<Stored, AttemptError> {
+            self.calls.lock().unwrap().push("status".to_string());
+            Ok(Stored::Bytes(*self.stored.lock().unwrap()))
+        }
+
+        async fn cancel(&self) -> Result<()> {
+            self.calls.lock().unwrap().push("cancel".to_string());
+            Ok(())
+        }
+    }
+
+    fn retry(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).with_initial_backoff(std::time::Duration::ZERO)
+    }
+
+    #[tokio::test]
+    async fn test_chunks_continue_at_the_acknowledged_offset() {
+        let session = MockSession::default();
+        let mut upload = ResumableUpload::new(session.clone(), RetryPolicy::disabled());
+        upload.upload_chunk(vec![0; 256]).await.unwrap();
+        upload.upload_chunk(vec![0; 256]).await.unwrap();
+        upload.finish(vec![0; 10]).await.unwrap();
+
+        let mut aborted = ResumableUpload::new(session.clone(), RetryPolicy::disabled());
+        aborted.upload_chunk(vec![0; 256]).await.unwrap();
+        aborted.cancel().await.unwrap();
+
+        assert_eq!(
+            *session.calls.lock().unwrap(),
+            vec![
+                "put 0+256/*",
+                "put 256+256/*",
+                "put 512+10/522",
+                "put 0+256/*",
+                "cancel"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_chunk_is_retried_from_the_stored_byte() {
+        let session = MockSession::default();
+        let mut upload = ResumableUpload::new(session.clone(), retry(3));
+        session.fail_next(128, true);
+        upload.upload_chunk(vec![0; 512]).await.unwrap();
+        upload.finish(vec![0; 10]).await.unwrap();
+
+        assert_eq!(
+            *session.calls.lock().unwrap(),
+            vec!["put 0+512/*", "status", "put 128+384/*", "put 512+10/522"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_sends_the_unacknowledged_chunk() {
+        let session = MockSession::default();
+        let mut upload = ResumableUpload::new(session.clone(), retry(1));
+        session.fail_next(0, true);
+        assert!(upload.upload_chunk(vec![0; 512]).await.is_err());
+        upload.resume().await.unwrap();
+        // Nothing left to resume
+        upload.resume().await.unwrap();
+
+        // Errors that would fail again aren't retried
+        let mut permanent = ResumableUpload::new(session.clone(), retry(3));
+        session.fail_next(0, false);
+        assert!(permanent.upload_chunk(vec![0; 256]).await.is_err());
+
+        assert_eq!(
+            *session.calls.lock().unwrap(),
+            vec!["put 0+512/*", "status", "put 0+512/*", "put 0+256/*"]
+        );
+    }
+}
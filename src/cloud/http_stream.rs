@@ -0,0 +1,542 @@
+//! Chunked HTTP streaming Excel writer
+//!
+//! [`HttpExcelWriter`](super::HttpExcelWriter) builds the whole workbook in a
+//! `Vec<u8>` before anything is sent. [`HttpExcelStreamWriter`] instead hands
+//! compressed ZIP chunks to an [`ExcelByteStream`] while rows are written, so
+//! the response can start immediately and server memory stays bounded no
+//! matter how many rows are exported.
+//!
+//! # Example with Axum
+//!
+//! ```no_run
+//! use excelstream::cloud::HttpExcelStreamWriter;
+//! use axum::{
+//!     body::Body,
+//!     http::header,
+//!     response::{IntoResponse, Response},
+//! };
+//!
+//! async fn download_report() -> Response {
+//!     let (mut writer, stream) = HttpExcelStreamWriter::new();
+//!
+//!     tokio::spawn(async move {
+//!         writer.write_header_bold(&["ID", "Name"]).await?;
+//!         for i in 0..2_000_000 {
+//!             writer.write_row_display([i.to_string(), format!("Row {}", i)]).await?;
+//!         }
+//!         writer.finish().await
+//!     });
+//!
+//!     (
+//!         [(header::CONTENT_TYPE, "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")],
+//!         Body::from_stream(stream),
+//!     )
+//!         .into_response()
+//! }
+//! ```
+
+use super::http_writer::{
+    package_parts, push_borrowed_cell, push_header_cell, push_inline_str_cell, push_typed_cell,
+    start_row_xml, WORKSHEET_FOOTER, WORKSHEET_HEADER,
+};
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::pipeline::ZipSink;
+use crate::progress::{CancellationToken, Progress};
+use crate::sheet_name::Shards;
+use crate::types::{CellRef, CellValue, SheetNamePolicy};
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use tokio::sync::mpsc;
+
+/// Default size of a body chunk (256 KB)
+pub const DEFAULT_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Number of chunks that may wait in the channel before the writer blocks
+const CHANNEL_CAPACITY: usize = 4;
+
+type Chunk = Result<Bytes>;
+
+/// HTTP Excel writer that streams the workbook as it is generated
+///
+/// Created together with its [`ExcelByteStream`]; the writer is usually
+/// moved into a spawned task while the stream is returned as the response
+/// body. When the client is slower than the writer, `write_*` calls wait
+/// until chunks are consumed, so at most a few chunks are held in memory.
+///
+/// The ZIP is written with data descriptors, so nothing already sent ever
+/// has to be patched.
+pub struct HttpExcelStreamWriter {
+    zip_writer: Option<ZipSink<Vec<u8>>>,
+    tx: mpsc::Sender<Chunk>,
+    chunk_size: usize,
+    worksheets: Vec<String>,
+    sheet_name_policy: SheetNamePolicy,
+    current_row: u32,
+    xml_buffer: Vec<u8>,
+    scratch: String,
+    in_worksheet: bool,
+    progress: Progress,
+    column_decimals: BTreeMap<u32, u8>,
+    /// Rolling rows over to a new sheet, see `auto_shard_rows`
+    shards: Shards,
+}
+
+impl HttpExcelStreamWriter {
+    /// Create a streaming writer and the byte stream it feeds
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> (Self, ExcelByteStream) {
+        Self::with_chunk_size(DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create a streaming writer that emits chunks of roughly `chunk_size` bytes
+    pub fn with_chunk_size(chunk_size: usize) -> (Self, ExcelByteStream) {
+        let chunk_size = chunk_size.max(1);
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+
+        let writer = Self {
+            zip_writer: Some(ZipSink::new(Vec::with_capacity(chunk_size), 6)),
+            tx,
+            chunk_size,
+            worksheets: Vec::new(),
+            sheet_name_policy: SheetNamePolicy::Strict,
+            current_row: 0,
+            xml_buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
+            in_worksheet: false,
+            progress: Progress::default(),
+            column_decimals: BTreeMap::new(),
+            shards: Shards::new(None),
+        };
+
+        (writer, ExcelByteStream { rx })
+    }
+
+    /// Call `callback` with `(rows_written, bytes_written)` every
+    /// [`PROGRESS_INTERVAL`](crate::progress::PROGRESS_INTERVAL) rows and once
+    /// from `finish()`; bytes are counted before compression
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(u64, u64) + Send + 'static,
+    {
+        self.progress.set_callback(Box::new(callback));
+    }
+
+    /// Make row writes and `finish()` fail with `ExcelError::Cancelled` once
+    /// `token` is cancelled
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.progress.set_token(token);
+    }
+
+    /// Choose what [`add_worksheet`](Self::add_worksheet) does with names
+    /// Excel wouldn't accept (default: return an error)
+    pub fn set_sheet_name_policy(&mut self, policy: SheetNamePolicy) {
+        self.sheet_name_policy = policy;
+    }
+
+    /// Round floats in column `col` (0-based) of typed rows to at most
+    /// `decimals` places, on every worksheet, or write them in full again
+    /// with `None`
+    pub fn set_column_decimals(&mut self, col: u32, decimals: Option<u8>) {
+        match decimals {
+            Some(decimals) => self.column_decimals.insert(col, decimals),
+            None => self.column_decimals.remove(&col),
+        };
+    }
+
+    /// Continue on a new sheet once a sheet holds `max_rows_per_sheet` rows
+    ///
+    /// Rows past the limit go to `Sheet1_2`, `Sheet1_3`, ... for a sheet
+    /// named `Sheet1`, each starting with a copy of the sheet's first row
+    /// as its header. Without this, writing past Excel's 1 048 576 rows per
+    /// sheet fails.
+    pub fn set_auto_shard_rows(&mut self, max_rows_per_sheet: u32) -> Result<()> {
+        self.shards.set_rows(max_rows_per_sheet)
+    }
+
+    /// Write a header row with bold formatting
+    pub async fn write_header_bold<I, S>(&mut self, headers: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.write_str_row(headers, push_header_cell).await
+    }
+
+    /// Write a data row (strings)
+    pub async fn write_row<I, S>(&mut self, row: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.write_str_row(row, push_inline_str_cell).await
+    }
+
+    /// Write a row of strings, each cell written by `push_cell`
+    async fn write_str_row<I, S>(
+        &mut self,
+        row: I,
+        push_cell: fn(&mut Vec<u8>, u32, u32, &str),
+    ) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.ensure_worksheet().await?;
+        self.progress.check()?;
+        self.check_row_limit().await?;
+
+        self.current_row += 1;
+        start_row_xml(&mut self.xml_buffer, self.current_row);
+        for (col_idx, value) in row.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            push_cell(
+                &mut self.xml_buffer,
+                self.current_row,
+                col_idx as u32,
+                value.as_ref(),
+            );
+        }
+
+        self.finish_row_xml().await
+    }
+
+    /// Write a data row of `Display` values (numbers, dates, custom types...)
+    pub async fn write_row_display<I>(&mut self, row: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.ensure_worksheet().await?;
+        self.progress.check()?;
+        self.check_row_limit().await?;
+
+        self.current_row += 1;
+        start_row_xml(&mut self.xml_buffer, self.current_row);
+        for (col_idx, value) in row.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            self.scratch.clear();
+            // Writing into a String never fails
+            let _ = write!(self.scratch, "{}", value);
+            push_inline_str_cell(
+                &mut self.xml_buffer,
+                self.current_row,
+                col_idx as u32,
+                &self.scratch,
+            );
+        }
+
+        self.finish_row_xml().await
+    }
+
+    /// Write a data row with typed values
+    pub async fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
+        self.write_row_typed_iter(cells).await
+    }
+
+    /// Write a data row from an iterator of owned or borrowed typed values,
+    /// without collecting it first
+    pub async fn write_row_typed_iter<I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CellValue>,
+    {
+        self.ensure_worksheet().await?;
+        self.progress.check()?;
+        self.check_row_limit().await?;
+
+        self.current_row += 1;
+        start_row_xml(&mut self.xml_buffer, self.current_row);
+        for (col_idx, value) in cells.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            push_typed_cell(
+                &mut self.xml_buffer,
+                self.current_row,
+                col_idx as u32,
+                value.borrow(),
+                self.column_decimals.get(&(col_idx as u32)).copied(),
+            );
+        }
+
+        self.finish_row_xml().await
+    }
+
+    /// Write a data row of borrowed cells, see [`CellRef`]
+    pub async fn write_row_refs<'a, I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<CellRef<'a>>,
+    {
+        self.ensure_worksheet().await?;
+        self.progress.check()?;
+        self.check_row_limit().await?;
+
+        self.current_row += 1;
+        start_row_xml(&mut self.xml_buffer, self.current_row);
+        for (col_idx, cell) in cells.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            push_borrowed_cell(
+                &mut self.xml_buffer,
+                self.current_row,
+                col_idx as u32,
+                cell.into(),
+                self.column_decimals.get(&(col_idx as u32)).copied(),
+            );
+        }
+
+        self.finish_row_xml().await
+    }
+
+    /// Add a new worksheet
+    pub async fn add_worksheet(&mut self, name: &str) -> Result<()> {
+        let name = crate::sheet_name::resolve(name, &self.worksheets, self.sheet_name_policy)?;
+        self.finish_current_worksheet().await?;
+
+        self.worksheets.push(name);
+        self.current_row = 0;
+        self.shards.reset();
+
+        let entry_name = format!("xl/worksheets/sheet{}.xml", self.worksheets.len());
+        self.zip_writer()?.start_entry(&entry_name)?;
+        self.write_data(WORKSHEET_HEADER.as_bytes()).await?;
+
+        self.in_worksheet = true;
+        Ok(())
+    }
+
+    /// Write the remaining workbook parts and close the stream
+    ///
+    /// The stream ends after the last chunk has been consumed. If the writer
+    /// is dropped without calling `finish()` the stream also ends, but the
+    /// client receives a truncated file; use [`abort`](Self::abort) to fail
+    /// the response instead.
+    pub async fn finish(mut self) -> Result<()> {
+        self.progress.check()?;
+        if self.worksheets.is_empty() {
+            self.add_worksheet("Sheet1").await?;
+        }
+        self.finish_current_worksheet().await?;
+        self.progress.finish();
+
+        for (entry_name, xml) in package_parts(&self.worksheets) {
+            self.zip_writer()?.start_entry(entry_name)?;
+            self.write_data(xml.as_bytes()).await?;
+        }
+
+        let zip_writer = self
+            .zip_writer
+            .take()
+            .ok_or_else(|| ExcelError::InvalidState("Writer already finished".to_string()))?;
+        let rest = zip_writer.finish()?;
+        self.send(rest).await
+    }
+
+    /// Stop writing and end the stream with `error`
+    ///
+    /// Web frameworks abort the response when the body stream yields an
+    /// error, so the client sees a failed download rather than a corrupt file.
+    pub async fn abort(self, error: ExcelError) {
+        // The receiver may already be gone; nothing left to report to then
+        let _ = self.tx.send(Err(error)).await;
+    }
+
+    fn zip_writer(&mut self) -> Result<&mut ZipSink<Vec<u8>>> {
+        self.zip_writer
+            .as_mut()
+            .ok_or_else(|| ExcelError::InvalidState("Writer already finished".to_string()))
+    }
+
+    /// Add `data` to the current entry and send the output once a chunk is full
+    async fn write_data(&mut self, data: &[u8]) -> Result<()> {
+        let chunk_size = self.chunk_size;
+        let output = self.zip_writer()?;
+        output.write_data(data)?;
+        if output.output_mut().len() < chunk_size {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(output.output_mut(), Vec::with_capacity(chunk_size));
+        self.send(chunk).await
+    }
+
+    /// Send a chunk, waiting for room in the channel
+    async fn send(&mut self, chunk: Vec<u8>) -> Result<()> {
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        self.tx.send(Ok(Bytes::from(chunk))).await.map_err(|_| {
+            ExcelError::IoError(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "HTTP response body was dropped",
+            ))
+        })
+    }
+
+    async fn ensure_worksheet(&mut self) -> Result<()> {
+        if !self.in_worksheet {
+            self.add_worksheet("Sheet1").await?;
+        }
+        Ok(())
+    }
+
+    /// Make room for the next row, rolling over to a new shard if the sheet
+    /// is full
+    async fn check_row_limit(&mut self) -> Result<()> {
+        if !self.shards.is_full(self.current_row) {
+            return Ok(());
+        }
+        let name = self.worksheets.last().cloned().unwrap_or_default();
+        let next = self.shards.next(&name, &self.worksheets)?;
+        self.add_worksheet(&next.name).await?;
+        // The header is row 1 again, so its XML is reused as it is
+        if let Some(header) = self.shards.started(next) {
+            self.current_row = 1;
+            self.write_data(&header).await?;
+            self.progress.row_written(header.len());
+        }
+        Ok(())
+    }
+
+    async fn finish_row_xml(&mut self) -> Result<()> {
+        self.xml_buffer.extend_from_slice(b"</row>");
+        self.shards
+            .keep_header(self.current_row, || self.xml_buffer.clone());
+
+        let row = std::mem::take(&mut self.xml_buffer);
+        let written = self.write_data(&row).await;
+        self.xml_buffer = row;
+        written?;
+        self.progress.row_written(self.xml_buffer.len());
+
+        Ok(())
+    }
+
+    async fn finish_current_worksheet(&mut self) -> Result<()> {
+        if !self.in_worksheet {
+            return Ok(());
+        }
+
+        self.write_data(WORKSHEET_FOOTER.as_bytes()).await?;
+
+        self.in_worksheet = false;
+        Ok(())
+    }
+}
+
+/// Body stream produced by [`HttpExcelStreamWriter`]
+///
+/// Implements `Stream<Item = Result<Bytes>>`, so it can be passed straight
+/// to `axum::body::Body::from_stream` (or any framework taking a byte stream).
+pub struct ExcelByteStream {
+    rx: mpsc::Receiver<Chunk>,
+}
+
+impl ExcelByteStream {
+    /// Receive the next chunk, or `None` once the workbook is complete
+    pub async fn next_chunk(&mut self) -> Option<Result<Bytes>> {
+        self.rx.recv().await
+    }
+}
+
+impl Stream for ExcelByteStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming_reader::StreamingReader;
+    use std::io::Write as _;
+
+    #[tokio::test]
+    async fn test_stream_yields_readable_workbook() {
+        let (mut writer, mut stream) = HttpExcelStreamWriter::with_chunk_size(1024);
+
+        let producer = tokio::spawn(async move {
+            writer.write_header_bold(["ID", "Name"]).await?;
+            for i in 0..2000 {
+                writer
+                    .write_row_display([i.to_string(), format!("Row {}", i)])
+                    .await?;
+            }
+            writer.finish().await
+        });
+
+        let mut chunks = 0;
+        let mut body = Vec::new();
+        while let Some(chunk) = stream.next_chunk().await {
+            body.extend_from_slice(&chunk.unwrap());
+            chunks += 1;
+        }
+        producer.await.unwrap().unwrap();
+
+        assert!(chunks > 1);
+        assert_eq!(&body[..2], b"PK");
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&body).unwrap();
+        let mut reader = StreamingReader::open(file.path()).unwrap();
+        let rows: Vec<_> = reader
+            .rows("Sheet1")
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(rows.len(), 2001);
+        assert_eq!(rows[2000].to_strings(), vec!["1999", "Row 1999"]);
+    }
+
+    #[tokio::test]
+    async fn test_header_is_bold() {
+        let (mut writer, mut stream) = HttpExcelStreamWriter::new();
+        writer.write_header_bold(["ID"]).await.unwrap();
+        writer.write_row(["1"]).await.unwrap();
+        let producer = tokio::spawn(writer.finish());
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        while let Some(chunk) = stream.next_chunk().await {
+            file.write_all(&chunk.unwrap()).unwrap();
+        }
+        producer.await.unwrap().unwrap();
+
+        let mut zip = crate::fast_writer::StreamingZipReader::open(file.path()).unwrap();
+        let sheet = zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap();
+        let sheet = String::from_utf8(sheet).unwrap();
+        assert!(sheet.contains(r#"<c r="A1" s="1" t="inlineStr">"#));
+        assert!(sheet.contains(r#"<c r="A2" t="inlineStr">"#));
+    }
+
+    #[tokio::test]
+    async fn test_abort_ends_stream_with_error() {
+        let (mut writer, mut stream) = HttpExcelStreamWriter::new();
+        writer.write_row(["a"]).await.unwrap();
+        writer
+            .abort(ExcelError::WriteError("database went away".to_string()))
+            .await;
+
+        let mut saw_error = false;
+        while let Some(chunk) = stream.next_chunk().await {
+            saw_error |= chunk.is_err();
+        }
+        assert!(saw_error);
+    }
+}
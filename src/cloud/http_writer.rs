@@ -6,57 +6,114 @@
 //! # Features
 //!
 //! - Stream Excel directly to HTTP response body
-//! - No temporary files required
+//! - No temporary files required, unless a [`SpillPolicy`] allows one for
+//!   exports larger than memory
 //! - Constant memory usage
-//! - Works with any async web framework (Axum, Actix-web, Warp, etc.)
-//!
-//! # Example with Axum
-//!
-//! ```no_run
-//! use excelstream::cloud::HttpExcelWriter;
-//! use axum::{
-//!     response::{IntoResponse, Response},
-//!     http::header,
-//! };
-//!
-//! async fn download_report() -> Response {
-//!     let mut writer = HttpExcelWriter::new();
-//!
-//!     writer.write_header_bold(&["Month", "Sales", "Profit"]).unwrap();
-//!     writer.write_row(&["January", "50000", "12000"]).unwrap();
-//!     writer.write_row(&["February", "55000", "15000"]).unwrap();
-//!
-//!     let bytes = writer.finish().unwrap();
-//!
-//!     (
-//!         [
-//!             (header::CONTENT_TYPE, "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
-//!             (header::CONTENT_DISPOSITION, "attachment; filename=\"report.xlsx\""),
-//!         ],
-//!         bytes
-//!     ).into_response()
-//! }
-//! ```
+//! - Works with any async web framework (Axum, Actix-web, Warp, etc.), and
+//!   without one under the `http-writer` feature, e.g. in WASM
+#![cfg_attr(
+    feature = "cloud-http",
+    doc = r##"
+# Example with Axum
+
+```no_run
+use excelstream::cloud::HttpExcelWriter;
+use axum::{
+    response::{IntoResponse, Response},
+    http::header,
+};
+
+async fn download_report() -> Response {
+    let mut writer = HttpExcelWriter::new();
+
+    writer.write_header_bold(&["Month", "Sales", "Profit"]).unwrap();
+    writer.write_row(&["January", "50000", "12000"]).unwrap();
+    writer.write_row(&["February", "55000", "15000"]).unwrap();
+
+    let bytes = writer.finish().unwrap();
+
+    (
+        [
+            (header::CONTENT_TYPE, "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"report.xlsx\""),
+        ],
+        bytes
+    ).into_response()
+}
+```
+"##
+)]
 
 use crate::error::{ExcelError, Result};
-use crate::types::CellValue;
+use crate::fast_writer::number;
+use crate::fast_writer::xml_writer::{escape, push_escaped};
+use crate::progress::{CancellationToken, Progress};
+use crate::sheet_name::Shards;
+use crate::types::{CellRef, CellValue, SheetNamePolicy};
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Where [`HttpExcelWriter`] keeps the file while it is built
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpillPolicy {
+    /// Keep the whole file in memory
+    #[default]
+    Memory,
+    /// Move the file to an anonymous temporary file once it grows past
+    /// this many bytes; the file is deleted when it is closed, even if the
+    /// process is killed
+    Threshold(usize),
+}
 
 /// In-memory buffer that implements Write + Seek traits
 struct MemoryBuffer {
     buffer: Vec<u8>,
     position: u64,
+    spill_threshold: Option<usize>,
+    /// Takes over from `buffer` once the threshold is passed
+    file: Option<File>,
 }
 
 impl MemoryBuffer {
-    fn new() -> Self {
+    fn new(spill: SpillPolicy) -> Self {
         Self {
             buffer: Vec::with_capacity(1024 * 1024), // 1MB initial capacity
             position: 0,
+            spill_threshold: match spill {
+                SpillPolicy::Memory => None,
+                SpillPolicy::Threshold(bytes) => Some(bytes),
+            },
+            file: None,
         }
     }
 
-    fn into_inner(self) -> Vec<u8> {
-        self.buffer
+    fn into_body(self) -> std::io::Result<ExcelBody> {
+        match self.file {
+            Some(mut file) => {
+                let len = file.seek(SeekFrom::End(0))?;
+                file.seek(SeekFrom::Start(0))?;
+                Ok(ExcelBody {
+                    inner: BodyInner::File(std::io::BufReader::with_capacity(64 * 1024, file)),
+                    len,
+                })
+            }
+            None => Ok(ExcelBody {
+                len: self.buffer.len() as u64,
+                inner: BodyInner::Memory(std::io::Cursor::new(self.buffer)),
+            }),
+        }
+    }
+
+    fn spill(&mut self) -> std::io::Result<()> {
+        let mut file = tempfile::tempfile()?;
+        std::io::Write::write_all(&mut file, &self.buffer)?;
+        file.seek(SeekFrom::Start(self.position))?;
+        self.buffer = Vec::new();
+        self.file = Some(file);
+        Ok(())
     }
 }
 
@@ -65,6 +122,19 @@ impl std::io::Write for MemoryBuffer {
         let pos = self.position as usize;
         let end_pos = pos + buf.len();
 
+        if self.file.is_none()
+            && self
+                .spill_threshold
+                .is_some_and(|threshold| end_pos.max(self.buffer.len()) > threshold)
+        {
+            self.spill()?;
+        }
+        if let Some(file) = &mut self.file {
+            std::io::Write::write_all(file, buf)?;
+            self.position = end_pos as u64;
+            return Ok(buf.len());
+        }
+
         // Extend buffer if needed
         if end_pos > self.buffer.len() {
             self.buffer.resize(end_pos, 0);
@@ -78,12 +148,19 @@ impl std::io::Write for MemoryBuffer {
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        Ok(())
+        match &mut self.file {
+            Some(file) => std::io::Write::flush(file),
+            None => Ok(()),
+        }
     }
 }
 
 impl std::io::Seek for MemoryBuffer {
     fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        if let Some(file) = &mut self.file {
+            self.position = file.seek(pos)?;
+            return Ok(self.position);
+        }
         let new_pos = match pos {
             std::io::SeekFrom::Start(offset) => offset as i64,
             std::io::SeekFrom::End(offset) => self.buffer.len() as i64 + offset,
@@ -102,10 +179,55 @@ impl std::io::Seek for MemoryBuffer {
     }
 }
 
+/// Finished file returned by [`HttpExcelWriter::finish_body`]
+///
+/// Reads the file from memory or, if it was spilled, from its temporary
+/// file, which is deleted when the body is dropped.
+pub struct ExcelBody {
+    inner: BodyInner,
+    len: u64,
+}
+
+enum BodyInner {
+    Memory(std::io::Cursor<Vec<u8>>),
+    File(std::io::BufReader<File>),
+}
+
+impl ExcelBody {
+    /// Size of the file in bytes, e.g. for `Content-Length`
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the file is empty (never the case for a finished workbook)
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the file was moved to a temporary file
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.inner, BodyInner::File(_))
+    }
+}
+
+impl Read for ExcelBody {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match &mut self.inner {
+            BodyInner::Memory(cursor) => cursor.read(buf),
+            BodyInner::File(reader) => reader.read(buf),
+        }
+    }
+}
+
 /// HTTP Excel writer that generates Excel files in memory for streaming responses
 ///
 /// This writer generates the entire Excel file in memory and can be used
 /// to stream responses in web servers.
+#[cfg_attr(
+    feature = "cloud-http",
+    doc = "For large exports, use [`HttpExcelStreamWriter`](super::HttpExcelStreamWriter), \
+           which sends the file in chunks while rows are written."
+)]
 ///
 /// # Example
 ///
@@ -130,10 +252,16 @@ pub struct HttpExcelWriter {
 struct InMemoryWorkbook {
     zip_writer: Option<s_zip::StreamingZipWriter<MemoryBuffer>>,
     worksheets: Vec<String>,
+    sheet_name_policy: SheetNamePolicy,
     worksheet_count: u32,
     current_row: u32,
     xml_buffer: Vec<u8>,
+    scratch: String,
     in_worksheet: bool,
+    progress: Progress,
+    column_decimals: BTreeMap<u32, u8>,
+    /// Rolling rows over to a new sheet, see `auto_shard_rows`
+    shards: Shards,
 }
 
 impl HttpExcelWriter {
@@ -151,7 +279,34 @@ impl HttpExcelWriter {
     ///   - 6: Balanced (recommended)
     ///   - 9: Maximum compression (slowest)
     pub fn with_compression(compression_level: u32) -> Self {
-        let workbook = InMemoryWorkbook::new(compression_level.min(9));
+        Self::with_options(compression_level, SpillPolicy::Memory)
+    }
+
+    /// Create a writer that moves the file to a temporary file past a size
+    /// threshold, so exports larger than memory can still be built
+    ///
+    /// Use [`finish_body`](Self::finish_body) to send the result without
+    /// loading it back into memory.
+    ///
+    /// ```no_run
+    /// use excelstream::cloud::http_writer::{HttpExcelWriter, SpillPolicy};
+    /// use std::io::Read;
+    ///
+    /// let mut writer = HttpExcelWriter::with_spill_policy(SpillPolicy::Threshold(64 << 20));
+    /// for i in 0..5_000_000 {
+    ///     writer.write_row_display([i, i * 2])?;
+    /// }
+    /// let mut body = writer.finish_body()?;
+    /// println!("{} bytes", body.len());
+    /// std::io::copy(&mut body, &mut std::io::sink())?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_spill_policy(spill: SpillPolicy) -> Self {
+        Self::with_options(6, spill)
+    }
+
+    fn with_options(compression_level: u32, spill: SpillPolicy) -> Self {
+        let workbook = InMemoryWorkbook::new(compression_level.min(9), spill);
 
         Self {
             workbook: Some(workbook),
@@ -159,6 +314,59 @@ impl HttpExcelWriter {
         }
     }
 
+    /// Call `callback` with `(rows_written, bytes_written)` every
+    /// [`PROGRESS_INTERVAL`](crate::progress::PROGRESS_INTERVAL) rows and once
+    /// from `finish()`; bytes are counted before compression
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(u64, u64) + Send + 'static,
+    {
+        if let Some(workbook) = &mut self.workbook {
+            workbook.progress.set_callback(Box::new(callback));
+        }
+    }
+
+    /// Make row writes and `finish()` fail with `ExcelError::Cancelled` once
+    /// `token` is cancelled
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        if let Some(workbook) = &mut self.workbook {
+            workbook.progress.set_token(token);
+        }
+    }
+
+    /// Choose what [`add_worksheet`](Self::add_worksheet) does with names
+    /// Excel wouldn't accept (default: return an error)
+    pub fn set_sheet_name_policy(&mut self, policy: SheetNamePolicy) {
+        if let Some(workbook) = &mut self.workbook {
+            workbook.sheet_name_policy = policy;
+        }
+    }
+
+    /// Round floats in column `col` (0-based) of typed rows to at most
+    /// `decimals` places, on every worksheet, or write them in full again
+    /// with `None`
+    pub fn set_column_decimals(&mut self, col: u32, decimals: Option<u8>) {
+        if let Some(workbook) = &mut self.workbook {
+            match decimals {
+                Some(decimals) => workbook.column_decimals.insert(col, decimals),
+                None => workbook.column_decimals.remove(&col),
+            };
+        }
+    }
+
+    /// Continue on a new sheet once a sheet holds `max_rows_per_sheet` rows
+    ///
+    /// Rows past the limit go to `Sheet1_2`, `Sheet1_3`, ... for a sheet
+    /// named `Sheet1`, each starting with a copy of the sheet's first row
+    /// as its header. Without this, writing past Excel's 1 048 576 rows per
+    /// sheet fails.
+    pub fn set_auto_shard_rows(&mut self, max_rows_per_sheet: u32) -> Result<()> {
+        match &mut self.workbook {
+            Some(workbook) => workbook.shards.set_rows(max_rows_per_sheet),
+            None => crate::sheet_name::check_shard_rows(max_rows_per_sheet).map(|_| ()),
+        }
+    }
+
     /// Write a header row with bold formatting
     pub fn write_header_bold<I, S>(&mut self, headers: I) -> Result<()>
     where
@@ -176,12 +384,7 @@ impl HttpExcelWriter {
             workbook.add_worksheet("Sheet1")?;
         }
 
-        let headers: Vec<String> = headers
-            .into_iter()
-            .map(|s| s.as_ref().to_string())
-            .collect();
-
-        workbook.write_row(&headers.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+        workbook.write_header(headers)
     }
 
     /// Write a data row (strings)
@@ -201,13 +404,64 @@ impl HttpExcelWriter {
             workbook.add_worksheet("Sheet1")?;
         }
 
-        let row: Vec<String> = row.into_iter().map(|s| s.as_ref().to_string()).collect();
+        workbook.write_row(row)
+    }
+
+    /// Write a data row of `Display` values (numbers, dates, custom types...)
+    ///
+    /// Values are formatted into a reused scratch buffer, so no intermediate
+    /// `Vec<String>` is built per row.
+    pub fn write_row_display<I>(&mut self, row: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.check_not_finished()?;
+
+        let workbook = self
+            .workbook
+            .as_mut()
+            .ok_or_else(|| ExcelError::InvalidState("Workbook not initialized".to_string()))?;
+
+        if workbook.worksheet_count == 0 {
+            workbook.add_worksheet("Sheet1")?;
+        }
 
-        workbook.write_row(&row.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+        workbook.write_row_display(row)
     }
 
     /// Write a data row with typed values
     pub fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
+        self.write_row_typed_iter(cells)
+    }
+
+    /// Write a data row from an iterator of owned or borrowed typed values,
+    /// without collecting it first
+    pub fn write_row_typed_iter<I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CellValue>,
+    {
+        self.check_not_finished()?;
+
+        let workbook = self
+            .workbook
+            .as_mut()
+            .ok_or_else(|| ExcelError::InvalidState("Workbook not initialized".to_string()))?;
+
+        if workbook.worksheet_count == 0 {
+            workbook.add_worksheet("Sheet1")?;
+        }
+
+        workbook.write_row_typed_iter(cells)
+    }
+
+    /// Write a data row of borrowed cells, see [`CellRef`]
+    pub fn write_row_refs<'a, I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<CellRef<'a>>,
+    {
         self.check_not_finished()?;
 
         let workbook = self
@@ -219,7 +473,7 @@ impl HttpExcelWriter {
             workbook.add_worksheet("Sheet1")?;
         }
 
-        workbook.write_row_typed(cells)
+        workbook.write_row_refs(cells)
     }
 
     /// Add a new worksheet
@@ -237,8 +491,19 @@ impl HttpExcelWriter {
     /// Finish writing and return the Excel file as bytes
     ///
     /// This consumes the writer and returns the complete Excel file
-    /// as a Vec<u8> that can be sent as an HTTP response.
-    pub fn finish(mut self) -> Result<Vec<u8>> {
+    /// as a `Vec<u8>` that can be sent as an HTTP response.
+    pub fn finish(self) -> Result<Vec<u8>> {
+        let mut body = self.finish_body()?;
+        let mut bytes = Vec::with_capacity(body.len() as usize);
+        body.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Finish writing and return the file as a reader
+    ///
+    /// Unlike [`finish`](Self::finish), a file spilled under a
+    /// [`SpillPolicy`] is read back from disk as the body is consumed.
+    pub fn finish_body(mut self) -> Result<ExcelBody> {
         if self.finished {
             return Err(ExcelError::InvalidState("Already finished".to_string()));
         }
@@ -248,10 +513,10 @@ impl HttpExcelWriter {
             .take()
             .ok_or_else(|| ExcelError::InvalidState("Workbook not initialized".to_string()))?;
 
-        let bytes = workbook.close()?;
+        let body = workbook.close()?;
         self.finished = true;
 
-        Ok(bytes)
+        Ok(body)
     }
 
     fn check_not_finished(&self) -> Result<()> {
@@ -272,8 +537,8 @@ impl Default for HttpExcelWriter {
 }
 
 impl InMemoryWorkbook {
-    fn new(compression_level: u32) -> Self {
-        let buffer = MemoryBuffer::new();
+    fn new(compression_level: u32, spill: SpillPolicy) -> Self {
+        let buffer = MemoryBuffer::new(spill);
         let zip_writer = s_zip::StreamingZipWriter::from_writer_with_compression(
             buffer,
             compression_level.min(9),
@@ -283,156 +548,216 @@ impl InMemoryWorkbook {
         Self {
             zip_writer: Some(zip_writer),
             worksheets: Vec::new(),
+            sheet_name_policy: SheetNamePolicy::Strict,
             worksheet_count: 0,
             current_row: 0,
             xml_buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
             in_worksheet: false,
+            progress: Progress::default(),
+            column_decimals: BTreeMap::new(),
+            shards: Shards::new(None),
         }
     }
 
     fn add_worksheet(&mut self, name: &str) -> Result<()> {
+        let name = crate::sheet_name::resolve(name, &self.worksheets, self.sheet_name_policy)?;
         // Finish previous worksheet if any
         self.finish_current_worksheet()?;
 
         self.worksheet_count += 1;
-        self.worksheets.push(name.to_string());
+        self.worksheets.push(name);
         self.current_row = 0;
+        self.shards.reset();
 
         // Start new worksheet entry in ZIP
         let entry_name = format!("xl/worksheets/sheet{}.xml", self.worksheet_count);
         self.zip_writer.as_mut().unwrap().start_entry(&entry_name)?;
 
-        // Write worksheet XML header
-        let header = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
-<sheetData>"#;
-
         self.zip_writer
             .as_mut()
             .unwrap()
-            .write_data(header.as_bytes())?;
+            .write_data(WORKSHEET_HEADER.as_bytes())?;
         self.in_worksheet = true;
 
         Ok(())
     }
 
-    fn write_row(&mut self, values: &[&str]) -> Result<()> {
+    fn write_row<I, S>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.write_str_row(values, push_inline_str_cell)
+    }
+
+    fn write_header<I, S>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.write_str_row(values, push_header_cell)
+    }
+
+    /// Write a row of strings, each cell written by `push_cell`
+    fn write_str_row<I, S>(
+        &mut self,
+        values: I,
+        push_cell: fn(&mut Vec<u8>, u32, u32, &str),
+    ) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
         if !self.in_worksheet {
             return Err(ExcelError::WriteError("No worksheet started".to_string()));
         }
+        self.progress.check()?;
+        self.check_row_limit()?;
 
         self.current_row += 1;
+        start_row_xml(&mut self.xml_buffer, self.current_row);
 
-        // Build row XML in buffer
-        self.xml_buffer.clear();
-        self.xml_buffer.extend_from_slice(b"<row r=\"");
-        self.xml_buffer
-            .extend_from_slice(self.current_row.to_string().as_bytes());
-        self.xml_buffer.extend_from_slice(b"\">");
-
-        for (col_idx, value) in values.iter().enumerate() {
-            let col_letter = Self::column_letter(col_idx as u32 + 1);
-            self.xml_buffer.extend_from_slice(b"<c r=\"");
-            self.xml_buffer.extend_from_slice(col_letter.as_bytes());
-            self.xml_buffer
-                .extend_from_slice(self.current_row.to_string().as_bytes());
-
-            if value.is_empty() {
-                self.xml_buffer.extend_from_slice(b"\"/>");
-            } else {
-                self.xml_buffer
-                    .extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
-                Self::write_escaped(&mut self.xml_buffer, value);
-                self.xml_buffer.extend_from_slice(b"</t></is></c>");
+        for (col_idx, value) in values.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
             }
+            push_cell(
+                &mut self.xml_buffer,
+                self.current_row,
+                col_idx as u32,
+                value.as_ref(),
+            );
         }
 
-        self.xml_buffer.extend_from_slice(b"</row>");
+        self.finish_row_xml()
+    }
 
-        // Stream to compressor immediately
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(&self.xml_buffer)?;
+    fn write_row_display<I>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        if !self.in_worksheet {
+            return Err(ExcelError::WriteError("No worksheet started".to_string()));
+        }
+        self.progress.check()?;
+        self.check_row_limit()?;
 
-        Ok(())
+        self.current_row += 1;
+        start_row_xml(&mut self.xml_buffer, self.current_row);
+
+        for (col_idx, value) in values.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            self.scratch.clear();
+            // Writing into a String never fails
+            let _ = write!(self.scratch, "{}", value);
+            push_inline_str_cell(
+                &mut self.xml_buffer,
+                self.current_row,
+                col_idx as u32,
+                &self.scratch,
+            );
+        }
+
+        self.finish_row_xml()
+    }
+
+    fn write_row_typed_iter<I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CellValue>,
+    {
+        if !self.in_worksheet {
+            return Err(ExcelError::WriteError("No worksheet started".to_string()));
+        }
+        self.progress.check()?;
+        self.check_row_limit()?;
+
+        self.current_row += 1;
+        start_row_xml(&mut self.xml_buffer, self.current_row);
+
+        for (col_idx, value) in cells.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            push_typed_cell(
+                &mut self.xml_buffer,
+                self.current_row,
+                col_idx as u32,
+                value.borrow(),
+                self.column_decimals.get(&(col_idx as u32)).copied(),
+            );
+        }
+
+        self.finish_row_xml()
     }
 
-    fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
+    fn write_row_refs<'a, I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<CellRef<'a>>,
+    {
         if !self.in_worksheet {
             return Err(ExcelError::WriteError("No worksheet started".to_string()));
         }
+        self.progress.check()?;
+        self.check_row_limit()?;
 
         self.current_row += 1;
+        start_row_xml(&mut self.xml_buffer, self.current_row);
 
-        // Build row XML in buffer
-        self.xml_buffer.clear();
-        self.xml_buffer.extend_from_slice(b"<row r=\"");
-        self.xml_buffer
-            .extend_from_slice(self.current_row.to_string().as_bytes());
-        self.xml_buffer.extend_from_slice(b"\">");
-
-        for (col_idx, value) in cells.iter().enumerate() {
-            let col_letter = Self::column_letter(col_idx as u32 + 1);
-
-            self.xml_buffer.extend_from_slice(b"<c r=\"");
-            self.xml_buffer.extend_from_slice(col_letter.as_bytes());
-            self.xml_buffer
-                .extend_from_slice(self.current_row.to_string().as_bytes());
-            self.xml_buffer.extend_from_slice(b"\"");
-
-            // Write cell value based on type
-            match value {
-                CellValue::Empty => {
-                    self.xml_buffer.extend_from_slice(b"/>");
-                }
-                CellValue::Int(i) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(i.to_string().as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                CellValue::Float(f) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(f.to_string().as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                CellValue::Bool(b) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"b\"><v>");
-                    self.xml_buffer
-                        .extend_from_slice(if *b { b"1" } else { b"0" });
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                CellValue::String(s) => {
-                    self.xml_buffer
-                        .extend_from_slice(b" t=\"inlineStr\"><is><t>");
-                    Self::write_escaped(&mut self.xml_buffer, s);
-                    self.xml_buffer.extend_from_slice(b"</t></is></c>");
-                }
-                CellValue::Formula(f) => {
-                    self.xml_buffer.extend_from_slice(b"><f>");
-                    Self::write_escaped(&mut self.xml_buffer, f);
-                    self.xml_buffer.extend_from_slice(b"</f></c>");
-                }
-                CellValue::DateTime(dt) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(dt.to_string().as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                CellValue::Error(e) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"e\"><v>");
-                    Self::write_escaped(&mut self.xml_buffer, e);
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
+        for (col_idx, cell) in cells.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
             }
+            push_borrowed_cell(
+                &mut self.xml_buffer,
+                self.current_row,
+                col_idx as u32,
+                cell.into(),
+                self.column_decimals.get(&(col_idx as u32)).copied(),
+            );
+        }
+
+        self.finish_row_xml()
+    }
+
+    /// Make room for the next row, rolling over to a new shard if the sheet
+    /// is full
+    fn check_row_limit(&mut self) -> Result<()> {
+        if !self.shards.is_full(self.current_row) {
+            return Ok(());
+        }
+        let name = self.worksheets.last().cloned().unwrap_or_default();
+        let next = self.shards.next(&name, &self.worksheets)?;
+        self.add_worksheet(&next.name)?;
+        // The header is row 1 again, so its XML is reused as it is
+        if let Some(header) = self.shards.started(next) {
+            self.current_row = 1;
+            self.zip_writer.as_mut().unwrap().write_data(&header)?;
+            self.progress.row_written(header.len());
         }
+        Ok(())
+    }
 
+    fn finish_row_xml(&mut self) -> Result<()> {
         self.xml_buffer.extend_from_slice(b"</row>");
+        self.shards
+            .keep_header(self.current_row, || self.xml_buffer.clone());
 
         // Stream to compressor immediately
         self.zip_writer
             .as_mut()
             .unwrap()
             .write_data(&self.xml_buffer)?;
+        self.progress.row_written(self.xml_buffer.len());
 
         Ok(())
     }
@@ -442,40 +767,188 @@ impl InMemoryWorkbook {
             self.zip_writer
                 .as_mut()
                 .unwrap()
-                .write_data(b"</sheetData></worksheet>")?;
+                .write_data(WORKSHEET_FOOTER.as_bytes())?;
             self.in_worksheet = false;
         }
         Ok(())
     }
 
-    fn close(mut self) -> Result<Vec<u8>> {
+    fn close(mut self) -> Result<ExcelBody> {
+        self.progress.check()?;
+
         // Finish current worksheet
         self.finish_current_worksheet()?;
+        self.progress.finish();
 
         // Write all other required ZIP entries
-        self.write_content_types()?;
-        self.write_rels()?;
-        self.write_workbook()?;
-        self.write_workbook_rels()?;
-        self.write_styles()?;
-        self.write_shared_strings()?;
-        self.write_app_props()?;
-        self.write_core_props()?;
+        let zip_writer = self.zip_writer.as_mut().unwrap();
+        for (entry_name, xml) in package_parts(&self.worksheets) {
+            zip_writer.start_entry(entry_name)?;
+            zip_writer.write_data(xml.as_bytes())?;
+        }
 
         // Finish ZIP and get buffer
         let zip_writer = self.zip_writer.take().unwrap();
         let buffer = zip_writer.finish()?;
 
-        Ok(buffer.into_inner())
+        Ok(buffer.into_body()?)
     }
+}
 
-    fn write_content_types(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("[Content_Types].xml")?;
-        let mut xml = String::from(
-            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+pub(super) const WORKSHEET_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+<sheetData>"#;
+
+pub(super) const WORKSHEET_FOOTER: &str = "</sheetData></worksheet>";
+
+/// Start a `<row>` element in the buffer (clears the buffer first)
+pub(super) fn start_row_xml(buffer: &mut Vec<u8>, row: u32) {
+    buffer.clear();
+    buffer.extend_from_slice(b"<row r=\"");
+    buffer.extend_from_slice(row.to_string().as_bytes());
+    buffer.extend_from_slice(b"\">");
+}
+
+fn push_cell_ref(buffer: &mut Vec<u8>, row: u32, col_idx: u32) {
+    buffer.extend_from_slice(b"<c r=\"");
+    buffer.extend_from_slice(column_letter(col_idx + 1).as_bytes());
+    buffer.extend_from_slice(row.to_string().as_bytes());
+    buffer.extend_from_slice(b"\"");
+}
+
+pub(super) fn push_inline_str_cell(buffer: &mut Vec<u8>, row: u32, col_idx: u32, value: &str) {
+    push_cell_ref(buffer, row, col_idx);
+    push_inline_str(buffer, value);
+}
+
+/// A string cell in the bold header style (`cellXfs` index 1 of the styles part)
+pub(super) fn push_header_cell(buffer: &mut Vec<u8>, row: u32, col_idx: u32, value: &str) {
+    push_cell_ref(buffer, row, col_idx);
+    buffer.extend_from_slice(b" s=\"1\"");
+    push_inline_str(buffer, value);
+}
+
+/// The rest of a string cell after its attributes
+fn push_inline_str(buffer: &mut Vec<u8>, value: &str) {
+    if value.is_empty() {
+        buffer.extend_from_slice(b"/>");
+    } else {
+        buffer.extend_from_slice(b" t=\"inlineStr\"><is><t>");
+        push_escaped(buffer, value);
+        buffer.extend_from_slice(b"</t></is></c>");
+    }
+}
+
+/// Floats are rounded to `decimals` places when given
+pub(super) fn push_typed_cell(
+    buffer: &mut Vec<u8>,
+    row: u32,
+    col_idx: u32,
+    value: &CellValue,
+    decimals: Option<u8>,
+) {
+    push_cell_ref(buffer, row, col_idx);
+
+    // Write cell value based on type
+    match value {
+        CellValue::Empty => {
+            buffer.extend_from_slice(b"/>");
+        }
+        CellValue::Int(i) => {
+            buffer.extend_from_slice(b" t=\"n\"><v>");
+            buffer.extend_from_slice(i.to_string().as_bytes());
+            buffer.extend_from_slice(b"</v></c>");
+        }
+        CellValue::Float(f) => {
+            let value = match decimals {
+                Some(decimals) => number::round_decimals(*f, decimals),
+                None => *f,
+            };
+            number::push_number_cell(buffer, value);
+        }
+        #[cfg(feature = "decimal")]
+        CellValue::Decimal(d) => {
+            buffer.extend_from_slice(b" t=\"n\"><v>");
+            buffer.extend_from_slice(d.to_string().as_bytes());
+            buffer.extend_from_slice(b"</v></c>");
+        }
+        CellValue::Bool(b) => {
+            buffer.extend_from_slice(b" t=\"b\"><v>");
+            buffer.extend_from_slice(if *b { b"1" } else { b"0" });
+            buffer.extend_from_slice(b"</v></c>");
+        }
+        // Hyperlinks are written as their text only
+        CellValue::String(s) | CellValue::Hyperlink { text: s, .. } => {
+            buffer.extend_from_slice(b" t=\"inlineStr\"><is><t>");
+            push_escaped(buffer, s);
+            buffer.extend_from_slice(b"</t></is></c>");
+        }
+        CellValue::BigInt(digits) => {
+            buffer.extend_from_slice(b" t=\"inlineStr\"><is><t>");
+            push_escaped(buffer, digits);
+            buffer.extend_from_slice(b"</t></is></c>");
+        }
+        CellValue::Formula { expr, cached } => {
+            let result = cached.as_deref().and_then(CellValue::formula_result);
+            if let Some((type_attr, _)) = &result {
+                buffer.extend_from_slice(type_attr.as_bytes());
+            }
+            buffer.extend_from_slice(b"><f>");
+            push_escaped(buffer, expr.strip_prefix('=').unwrap_or(expr));
+            buffer.extend_from_slice(b"</f>");
+            if let Some((_, value)) = &result {
+                buffer.extend_from_slice(b"<v>");
+                push_escaped(buffer, value);
+                buffer.extend_from_slice(b"</v>");
+            }
+            buffer.extend_from_slice(b"</c>");
+        }
+        CellValue::DateTime(dt) => {
+            number::push_number_cell(buffer, *dt);
+        }
+        CellValue::Error(e) => {
+            buffer.extend_from_slice(b" t=\"e\"><v>");
+            push_escaped(buffer, e);
+            buffer.extend_from_slice(b"</v></c>");
+        }
+    }
+}
+
+/// A [`CellRef`] cell; strings are written without an owned copy
+pub(super) fn push_borrowed_cell(
+    buffer: &mut Vec<u8>,
+    row: u32,
+    col_idx: u32,
+    cell: CellRef<'_>,
+    decimals: Option<u8>,
+) {
+    match cell {
+        CellRef::String(s) => push_inline_str_cell(buffer, row, col_idx, s),
+        CellRef::Value(value) => push_typed_cell(buffer, row, col_idx, value, decimals),
+        cell => push_typed_cell(buffer, row, col_idx, &cell.into_owned(), decimals),
+    }
+}
+
+/// All ZIP entries written after the worksheets, as (entry name, XML)
+pub(super) fn package_parts(worksheets: &[String]) -> Vec<(&'static str, String)> {
+    vec![
+        ("[Content_Types].xml", content_types_xml(worksheets.len())),
+        ("_rels/.rels", RELS_XML.to_string()),
+        ("xl/workbook.xml", workbook_xml(worksheets)),
+        (
+            "xl/_rels/workbook.xml.rels",
+            workbook_rels_xml(worksheets.len()),
+        ),
+        ("xl/styles.xml", STYLES_XML.to_string()),
+        ("xl/sharedStrings.xml", SHARED_STRINGS_XML.to_string()),
+        ("docProps/app.xml", APP_XML.to_string()),
+        ("docProps/core.xml", CORE_XML.to_string()),
+    ]
+}
+
+fn content_types_xml(worksheet_count: usize) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
 <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
 <Default Extension="xml" ContentType="application/xml"/>
@@ -484,111 +957,75 @@ impl InMemoryWorkbook {
 <Override PartName="/xl/sharedStrings.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sharedStrings+xml"/>
 <Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>
 <Override PartName="/docProps/app.xml" ContentType="application/vnd.openxmlformats-officedocument.extended-properties+xml"/>"#,
-        );
+    );
 
-        for i in 1..=self.worksheet_count {
-            xml.push_str(&format!(
-                r#"
+    for i in 1..=worksheet_count {
+        xml.push_str(&format!(
+            r#"
 <Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
-                i
-            ));
-        }
-
-        xml.push_str("\n</Types>");
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
-        Ok(())
+            i
+        ));
     }
 
-    fn write_rels(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("_rels/.rels")?;
-        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+    xml.push_str("\n</Types>");
+    xml
+}
+
+const RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
 <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
 <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>
 <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/extended-properties" Target="docProps/app.xml"/>
 </Relationships>"#;
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
-        Ok(())
-    }
 
-    fn write_workbook(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/workbook.xml")?;
-        let mut xml = String::from(
-            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+fn workbook_xml(worksheets: &[String]) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
 <sheets>"#,
-        );
+    );
 
-        for (i, name) in self.worksheets.iter().enumerate() {
-            xml.push_str(&format!(
-                r#"
+    for (i, name) in worksheets.iter().enumerate() {
+        xml.push_str(&format!(
+            r#"
 <sheet name="{}" sheetId="{}" r:id="rId{}"/>"#,
-                name,
-                i + 1,
-                i + 1
-            ));
-        }
-
-        xml.push_str("\n</sheets>\n</workbook>");
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
-        Ok(())
+            escape(name),
+            i + 1,
+            i + 1
+        ));
     }
 
-    fn write_workbook_rels(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/_rels/workbook.xml.rels")?;
-        let mut xml = String::from(
-            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
-        );
+    xml.push_str("\n</sheets>\n</workbook>");
+    xml
+}
 
-        for i in 1..=self.worksheet_count {
-            xml.push_str(&format!(
-                r#"
-<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{}.xml"/>"#,
-                i, i
-            ));
-        }
+fn workbook_rels_xml(worksheet_count: usize) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    );
 
+    for i in 1..=worksheet_count {
         xml.push_str(&format!(
             r#"
+<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{}.xml"/>"#,
+            i, i
+        ));
+    }
+
+    xml.push_str(&format!(
+        r#"
 <Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
 <Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/sharedStrings" Target="sharedStrings.xml"/>
 </Relationships>"#,
-            self.worksheet_count + 1,
-            self.worksheet_count + 2
-        ));
+        worksheet_count + 1,
+        worksheet_count + 2
+    ));
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
-        Ok(())
-    }
+    xml
+}
 
-    fn write_styles(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/styles.xml")?;
-        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+const STYLES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
 <numFmts count="0"/>
 <fonts count="2">
@@ -607,84 +1044,130 @@ impl InMemoryWorkbook {
 <xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/>
 </cellXfs>
 </styleSheet>"#;
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
-        Ok(())
-    }
 
-    fn write_shared_strings(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/sharedStrings.xml")?;
-        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+const SHARED_STRINGS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" count="0" uniqueCount="0"/>
 "#;
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
-        Ok(())
-    }
 
-    fn write_app_props(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("docProps/app.xml")?;
-        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+const APP_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties">
 <Application>ExcelStream HTTP</Application>
 </Properties>"#;
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
-        Ok(())
-    }
 
-    fn write_core_props(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("docProps/core.xml")?;
-        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+const CORE_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
 <dc:creator>ExcelStream HTTP</dc:creator>
 </cp:coreProperties>"#;
-        self.zip_writer
-            .as_mut()
+
+fn column_letter(n: u32) -> String {
+    let mut result = String::new();
+    let mut n = n;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        result.insert(0, (b'A' + rem as u8) as char);
+        n = (n - 1) / 26;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming_reader::StreamingReader;
+
+    fn read_back(body: &[u8]) -> Vec<Vec<String>> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, body).unwrap();
+        let mut reader = StreamingReader::open(file.path()).unwrap();
+        reader
+            .rows("Sheet1")
             .unwrap()
-            .write_data(xml.as_bytes())?;
-        Ok(())
+            .map(|row| row.unwrap().to_strings())
+            .collect()
     }
 
-    fn column_letter(n: u32) -> String {
-        let mut result = String::new();
-        let mut n = n;
-        while n > 0 {
-            let rem = (n - 1) % 26;
-            result.insert(0, (b'A' + rem as u8) as char);
-            n = (n - 1) / 26;
-        }
-        result
-    }
-
-    fn write_escaped(buffer: &mut Vec<u8>, s: &str) {
-        for c in s.chars() {
-            match c {
-                '&' => buffer.extend_from_slice(b"&amp;"),
-                '<' => buffer.extend_from_slice(b"&lt;"),
-                '>' => buffer.extend_from_slice(b"&gt;"),
-                '"' => buffer.extend_from_slice(b"&quot;"),
-                '\'' => buffer.extend_from_slice(b"&apos;"),
-                _ => {
-                    let mut buf = [0; 4];
-                    buffer.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
-                }
+    #[test]
+    fn test_spill_to_temp_file() {
+        let write = |spill| {
+            let mut writer = HttpExcelWriter::with_spill_policy(spill);
+            writer.write_header_bold(["ID", "Name"]).unwrap();
+            for i in 0..2000 {
+                writer
+                    .write_row_display([i.to_string(), format!("Row {}", i)])
+                    .unwrap();
             }
+            writer.finish_body().unwrap()
+        };
+
+        let mut memory = write(SpillPolicy::Memory);
+        assert!(!memory.is_spilled());
+        let mut spilled = write(SpillPolicy::Threshold(4096));
+        assert!(spilled.is_spilled());
+        assert!(spilled.len() > 4096);
+
+        let mut bytes = Vec::new();
+        spilled.read_to_end(&mut bytes).unwrap();
+        assert_eq!(bytes.len() as u64, spilled.len());
+        let rows = read_back(&bytes);
+        assert_eq!(rows.len(), 2001);
+        assert_eq!(rows[2000], vec!["1999", "Row 1999"]);
+
+        let mut in_memory = Vec::new();
+        memory.read_to_end(&mut in_memory).unwrap();
+        assert_eq!(read_back(&in_memory), rows);
+    }
+
+    #[test]
+    fn test_header_is_bold() {
+        let mut writer = HttpExcelWriter::new();
+        writer.write_header_bold(["ID"]).unwrap();
+        writer.write_row(["1"]).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, &bytes).unwrap();
+        let mut zip = crate::fast_writer::StreamingZipReader::open(file.path()).unwrap();
+        let sheet = zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap();
+        let sheet = String::from_utf8(sheet).unwrap();
+        assert!(sheet.contains(r#"<c r="A1" s="1" t="inlineStr">"#));
+        assert!(sheet.contains(r#"<c r="A2" t="inlineStr">"#));
+    }
+
+    #[test]
+    fn test_auto_shard_rows() {
+        let mut writer = HttpExcelWriter::new();
+        writer.set_auto_shard_rows(2).unwrap();
+        writer.write_header_bold(["ID"]).unwrap();
+        for i in 0..3 {
+            writer.write_row_display([i]).unwrap();
         }
+        let bytes = writer.finish().unwrap();
+
+        assert_eq!(read_back(&bytes), vec![vec!["ID"], vec!["0"]]);
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, &bytes).unwrap();
+        let mut reader = StreamingReader::open(file.path()).unwrap();
+        assert_eq!(reader.sheet_names(), vec!["Sheet1", "Sheet1_2", "Sheet1_3"]);
+        let last: Vec<Vec<String>> = reader
+            .rows("Sheet1_3")
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(last, vec![vec!["ID"], vec!["2"]]);
+    }
+
+    #[test]
+    fn test_auto_shard_rows_skips_taken_names() {
+        let mut writer = HttpExcelWriter::new();
+        writer.set_auto_shard_rows(2).unwrap();
+        writer.add_worksheet("Data_2").unwrap();
+        writer.add_worksheet("Data").unwrap();
+        for i in 0..3 {
+            writer.write_row_display([i]).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let reader = StreamingReader::from_bytes(bytes).unwrap();
+        assert_eq!(reader.sheet_names(), vec!["Data_2", "Data", "Data_3"]);
     }
 }
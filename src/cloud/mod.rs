@@ -6,52 +6,61 @@
 //! # Features
 //!
 //! - Stream Excel files directly to S3/GCS/Azure
+//! - Read Excel and CSV files from S3/GCS with ranged GETs
 //! - Multipart upload for large files
 //! - Constant memory usage (~30-35 MB)
 //! - No temporary files
-//!
-//! # S3 Example
-//!
-//! ```no_run
-//! use excelstream::cloud::S3ExcelWriter;
-//!
-//! #[tokio::main]
-//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut writer = S3ExcelWriter::builder()
-//!         .bucket("my-bucket")
-//!         .key("reports/monthly.xlsx")
-//!         .region("us-east-1")
-//!         .build()
-//!         .await?;
-//!
-//!     writer.write_row(&["ID", "Name", "Amount"]).await?;
-//!     writer.write_row(&["1", "Alice", "1000"]).await?;
-//!
-//!     writer.save().await?;
-//!     Ok(())
-//! }
-//! ```
-//!
-//! # GCS Example
-//!
-//! ```no_run
-//! use excelstream::cloud::GCSExcelWriter;
-//!
-//! #[tokio::main]
-//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut writer = GCSExcelWriter::builder()
-//!         .bucket("my-bucket")
-//!         .object("reports/monthly.xlsx")
-//!         .build()
-//!         .await?;
-//!
-//!     writer.write_row(&["ID", "Name", "Amount"]).await?;
-//!     writer.write_row(&["1", "Alice", "1000"]).await?;
-//!
-//!     writer.save().await?;
-//!     Ok(())
-//! }
-//! ```
+#![cfg_attr(
+    feature = "cloud-s3",
+    doc = r##"
+# S3 Example
+
+```no_run
+use excelstream::cloud::S3ExcelWriter;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = S3ExcelWriter::builder()
+        .bucket("my-bucket")
+        .key("reports/monthly.xlsx")
+        .region("us-east-1")
+        .build()
+        .await?;
+
+    writer.write_row(&["ID", "Name", "Amount"]).await?;
+    writer.write_row(&["1", "Alice", "1000"]).await?;
+
+    writer.save().await?;
+    Ok(())
+}
+```
+"##
+)]
+#![cfg_attr(
+    feature = "cloud-gcs",
+    doc = r##"
+# GCS Example
+
+```no_run
+use excelstream::cloud::GCSExcelWriter;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = GCSExcelWriter::builder()
+        .bucket("my-bucket")
+        .object("reports/monthly.xlsx")
+        .build()
+        .await?;
+
+    writer.write_row(&["ID", "Name", "Amount"]).await?;
+    writer.write_row(&["1", "Alice", "1000"]).await?;
+
+    writer.save().await?;
+    Ok(())
+}
+```
+"##
+)]
 
 #[cfg(feature = "cloud-s3")]
 pub mod s3_writer;
@@ -62,22 +71,51 @@ pub mod s3_reader;
 #[cfg(feature = "cloud-gcs")]
 pub mod gcs_writer;
 
-#[cfg(feature = "cloud-http")]
+#[cfg(feature = "cloud-gcs")]
+pub mod gcs_reader;
+
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+pub mod csv_reader;
+
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+pub mod ranged;
+
+#[cfg(feature = "http-writer")]
 pub mod http_writer;
 
+#[cfg(feature = "cloud-http")]
+pub mod http_stream;
+
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs", feature = "cloud-http"))]
 pub mod replicate;
 
+pub mod retry;
+
+pub use retry::RetryPolicy;
+
 #[cfg(feature = "cloud-s3")]
 pub use s3_writer::S3ExcelWriter;
 
 #[cfg(feature = "cloud-s3")]
-pub use s3_reader::S3ExcelReader;
+pub use s3_reader::{S3CsvReader, S3ExcelReader};
 
 #[cfg(feature = "cloud-gcs")]
 pub use gcs_writer::GCSExcelWriter;
 
+#[cfg(feature = "cloud-gcs")]
+pub use gcs_reader::{GCSCsvReader, GCSExcelReader};
+
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+pub use csv_reader::CloudCsvReader;
+
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+pub use ranged::RangedSource;
+
+#[cfg(feature = "http-writer")]
+pub use http_writer::{ExcelBody, HttpExcelWriter, SpillPolicy};
+
 #[cfg(feature = "cloud-http")]
-pub use http_writer::HttpExcelWriter;
+pub use http_stream::{ExcelByteStream, HttpExcelStreamWriter};
 
 use crate::error::Result;
 use std::io::Write;
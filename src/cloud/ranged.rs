@@ -0,0 +1,185 @@
+//! Ranged reads from cloud objects
+//!
+//! S3 and GCS both support HTTP `Range` requests, so a remote object can be
+//! consumed chunk by chunk instead of being downloaded in one response.
+//! [`RangedSource`] is the small abstraction the cloud readers are built on.
+
+use crate::archive::{Archive, RangedArchive};
+use crate::error::{ExcelError, Result};
+use crate::streaming_reader::{ReadOptions, StreamingReader};
+use std::future::Future;
+use std::path::PathBuf;
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+/// Default size of a single ranged GET (8 MB)
+pub const DEFAULT_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// A remote object that can be fetched in byte ranges
+pub trait RangedSource: Send {
+    /// Total object size in bytes
+    fn size(&self) -> u64;
+
+    /// Fetch bytes `start..end` (end exclusive)
+    fn read_range(&self, start: u64, end: u64) -> impl Future<Output = Result<Vec<u8>>> + Send;
+}
+
+/// Open an XLSX object without downloading it
+///
+/// The central directory is read from the end of the object; after that
+/// only the parts that are read (workbook, relationships, shared strings,
+/// then the sheets that are streamed) are fetched, `chunk_size` bytes per
+/// ranged GET.
+pub(crate) async fn open_workbook<S>(source: S, chunk_size: u64) -> Result<StreamingReader>
+where
+    S: RangedSource + Sync + 'static,
+{
+    let archive = RangedArchive::open(source, chunk_size).await?;
+    StreamingReader::from_archive(
+        Archive::Ranged(archive),
+        PathBuf::new(),
+        ReadOptions::default(),
+    )
+}
+
+/// A [`RangedSource`] read from synchronous code, such as the XLSX parser
+pub(crate) trait BlockingRangedSource: Send + Sync {
+    /// Fetch bytes `start..end` (end exclusive), blocking until they arrive
+    fn read_range_blocking(&self, start: u64, end: u64) -> Result<Vec<u8>>;
+}
+
+impl<S: RangedSource + Sync> BlockingRangedSource for S {
+    fn read_range_blocking(&self, start: u64, end: u64) -> Result<Vec<u8>> {
+        let fetch = self.read_range(start, end);
+        match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| handle.block_on(fetch))
+            }
+            // A current-thread runtime can't be blocked from its own thread,
+            // so the request gets a thread and a runtime of its own
+            Ok(_) => std::thread::scope(|scope| {
+                scope
+                    .spawn(|| block_on(fetch))
+                    .join()
+                    .unwrap_or_else(|_| Err(ExcelError::ReadError("ranged GET panicked".into())))
+            }),
+            Err(_) => block_on(fetch),
+        }
+    }
+}
+
+fn block_on<F: Future<Output = Result<Vec<u8>>>>(fetch: F) -> Result<Vec<u8>> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(ExcelError::IoError)?
+        .block_on(fetch)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::ExcelWriter;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    /// In-memory source used to test the ranged readers
+    pub(crate) struct MemorySource(pub Vec<u8>);
+
+    impl RangedSource for MemorySource {
+        fn size(&self) -> u64 {
+            self.0.len() as u64
+        }
+
+        async fn read_range(&self, start: u64, end: u64) -> Result<Vec<u8>> {
+            Ok(self.0[start as usize..end as usize].to_vec())
+        }
+    }
+
+    /// Source that counts the bytes it hands out
+    struct CountingSource {
+        inner: MemorySource,
+        fetched: Arc<AtomicU64>,
+    }
+
+    impl RangedSource for CountingSource {
+        fn size(&self) -> u64 {
+            self.inner.size()
+        }
+
+        async fn read_range(&self, start: u64, end: u64) -> Result<Vec<u8>> {
+            self.fetched.fetch_add(end - start, Ordering::Relaxed);
+            self.inner.read_range(start, end).await
+        }
+    }
+
+    /// Workbook with a large first sheet followed by a small "Small" one
+    fn workbook() -> Vec<u8> {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        // Distinct numbers and text, so the sheet compresses poorly
+        let mut seed = 1u64;
+        for _ in 0..20_000 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            writer
+                .write_row([seed.to_string(), format!("{:x}", seed >> 7)])
+                .unwrap();
+        }
+        writer.add_sheet("Small").unwrap();
+        writer.write_row(["name", "qty"]).unwrap();
+        writer.write_row(["apple", "3"]).unwrap();
+        writer.save().unwrap();
+        std::fs::read(temp.path()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_open_workbook_fetches_only_the_parts_read() {
+        let data = workbook();
+        let size = data.len() as u64;
+        let fetched = Arc::new(AtomicU64::new(0));
+        let source = CountingSource {
+            inner: MemorySource(data),
+            fetched: fetched.clone(),
+        };
+
+        let mut reader = open_workbook(source, 4096).await.unwrap();
+        assert_eq!(reader.sheet_names(), vec!["Sheet1", "Small"]);
+        let rows: Vec<_> = reader
+            .rows("Small")
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(rows, vec![vec!["name", "qty"], vec!["apple", "3"]]);
+
+        // The first sheet was never fetched
+        let small = fetched.load(Ordering::Relaxed);
+        assert!(small < size / 4, "fetched {} of {} bytes", small, size);
+
+        let big = reader.rows("Sheet1").unwrap().count();
+        assert_eq!(big, 20_000);
+        assert!(fetched.load(Ordering::Relaxed) > size / 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_open_workbook_on_a_multi_thread_runtime() {
+        let mut reader = open_workbook(MemorySource(workbook()), DEFAULT_CHUNK_SIZE)
+            .await
+            .unwrap();
+        assert_eq!(reader.rows("Small").unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_directory_behind_a_long_comment() {
+        let mut data = workbook();
+        // Grow the archive comment past the first fetch from the end
+        let eocd = data.len() - 22;
+        let comment = vec![b'x'; 20_000];
+        data[eocd + 20..eocd + 22].copy_from_slice(&(comment.len() as u16).to_le_bytes());
+        data.extend_from_slice(&comment);
+
+        let reader = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(open_workbook(MemorySource(data), DEFAULT_CHUNK_SIZE))
+            .unwrap();
+        assert_eq!(reader.sheet_names(), vec!["Sheet1", "Small"]);
+    }
+}
@@ -0,0 +1,199 @@
+//! Retry settings for cloud uploads
+
+#[cfg(feature = "cloud-gcs")]
+use crate::error::ExcelError;
+use std::time::Duration;
+
+/// How often and how patiently a failed upload request is retried
+///
+/// Transient failures (5xx responses, throttling, timeouts, dropped
+/// connections) are retried with exponential backoff plus jitter; other
+/// errors fail right away. S3 writers hand the policy to the AWS SDK
+/// client, GCS writers retry each chunk of the resumable upload with it.
+///
+/// A part or chunk keeps its bytes until the service acknowledges it, so a
+/// retry sends the same bytes again and the upload carries on where it was.
+/// When the attempts run out the bytes are still kept: the writer's
+/// `resume()` sends them once more, and so does the next upload or `save()`.
+///
+/// ```
+/// use excelstream::cloud::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(8)
+///     .with_initial_backoff(Duration::from_millis(500))
+///     .with_max_backoff(Duration::from_secs(30))
+///     .with_attempt_timeout(Duration::from_secs(120));
+/// assert_eq!(policy.backoff(1), Duration::from_millis(500));
+/// assert_eq!(policy.backoff(3), Duration::from_secs(2));
+/// assert_eq!(policy.backoff(10), Duration::from_secs(30));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per request, the first one included (1 disables retries)
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled for every further retry
+    pub initial_backoff: Duration,
+    /// Longest wait between two attempts
+    pub max_backoff: Duration,
+    /// Time one attempt may take before it counts as failed; `None` waits
+    /// as long as the connection stays up
+    pub attempt_timeout: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts, backing off from 1 s up to 20 s
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(20),
+            attempt_timeout: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Default backoff with `max_attempts` attempts per request
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// Fail on the first error
+    pub fn disabled() -> Self {
+        Self::new(1)
+    }
+
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    pub fn with_attempt_timeout(mut self, timeout: Duration) -> Self {
+        self.attempt_timeout = Some(timeout);
+        self
+    }
+
+    /// Longest wait before retry number `retry` (1-based), before jitter
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Wait before retry number `retry` (1-based): the backoff with up to
+    /// half of it taken off at random, so clients don't retry in lockstep
+    pub fn jittered_backoff(&self, retry: u32) -> Duration {
+        use std::collections::hash_map::RandomState;
+        use std::hash::BuildHasher;
+
+        let backoff = self.backoff(retry);
+        let random = RandomState::new().hash_one(retry) % 1024;
+        backoff - (backoff / 2).mul_f64(random as f64 / 1024.0)
+    }
+
+    /// Send one attempt of `request`, failing it after `attempt_timeout`
+    #[cfg(feature = "cloud-gcs")]
+    pub(crate) async fn attempt<T>(
+        &self,
+        request: impl std::future::Future<Output = Result<T, AttemptError>>,
+    ) -> Result<T, AttemptError> {
+        let Some(timeout) = self.attempt_timeout else {
+            return request.await;
+        };
+        tokio::time::timeout(timeout, request)
+            .await
+            .unwrap_or_else(|_| {
+                Err(AttemptError::Transient(ExcelError::IoError(
+                    std::io::ErrorKind::TimedOut.into(),
+                )))
+            })
+    }
+
+    /// After failed attempt number `attempt`: `false` when `error` is final
+    /// or no attempts are left, otherwise waits out the backoff and returns
+    /// `true`
+    #[cfg(feature = "cloud-gcs")]
+    pub(crate) async fn retry_after(&self, attempt: u32, error: &AttemptError) -> bool {
+        if !matches!(error, AttemptError::Transient(_)) || attempt >= self.max_attempts {
+            return false;
+        }
+        tokio::time::sleep(self.jittered_backoff(attempt)).await;
+        true
+    }
+
+    /// The same settings for the AWS SDK
+    #[cfg(feature = "cloud-s3")]
+    pub(crate) fn apply_to_s3(&self, config: aws_sdk_s3::config::Builder) -> aws_sdk_s3::Config {
+        use aws_sdk_s3::config::retry::RetryConfig;
+        use aws_sdk_s3::config::timeout::TimeoutConfig;
+
+        let retry = RetryConfig::standard()
+            .with_max_attempts(self.max_attempts.max(1))
+            .with_initial_backoff(self.initial_backoff)
+            .with_max_backoff(self.max_backoff);
+        let mut config = config.retry_config(retry);
+        if let Some(timeout) = self.attempt_timeout {
+            config = config.timeout_config(
+                TimeoutConfig::builder()
+                    .operation_attempt_timeout(timeout)
+                    .build(),
+            );
+        }
+        config.build()
+    }
+}
+
+/// Failure of one request attempt
+#[cfg(feature = "cloud-gcs")]
+#[derive(Debug)]
+pub(crate) enum AttemptError {
+    /// 5xx, throttling, timeout or dropped connection; worth another try
+    Transient(ExcelError),
+    /// Fails the same way however often it is sent
+    Permanent(ExcelError),
+}
+
+#[cfg(feature = "cloud-gcs")]
+impl From<AttemptError> for ExcelError {
+    fn from(err: AttemptError) -> Self {
+        match err {
+            AttemptError::Transient(e) | AttemptError::Permanent(e) => e,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy::default();
+        let waits: Vec<u64> = (1..=7).map(|n| policy.backoff(n).as_secs()).collect();
+        assert_eq!(waits, vec![1, 2, 4, 8, 16, 20, 20]);
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(20));
+
+        assert_eq!(RetryPolicy::new(0).max_attempts, 1);
+        assert_eq!(RetryPolicy::disabled().max_attempts, 1);
+    }
+
+    #[test]
+    fn test_jitter_takes_off_at_most_half() {
+        let policy = RetryPolicy::default();
+        for retry in 1..=7 {
+            let wait = policy.jittered_backoff(retry);
+            assert!(wait <= policy.backoff(retry));
+            assert!(wait >= policy.backoff(retry) / 2);
+        }
+    }
+}
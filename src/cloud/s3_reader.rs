@@ -1,8 +1,11 @@
-//! S3 Excel reader with direct streaming support
+//! S3 Excel and CSV readers with ranged streaming support
 //!
-//! This module provides reading Excel files directly from Amazon S3
-//! by downloading to a temporary file and using StreamingReader for parsing.
+//! Objects are fetched with ranged GETs (8 MB per request by default).
+//! For Excel files only the ZIP central directory and the parts that are
+//! read are fetched; CSV objects are parsed straight from the chunks.
 
+use super::csv_reader::CloudCsvReader;
+use super::ranged::{open_workbook, RangedSource, DEFAULT_CHUNK_SIZE};
 use crate::error::{ExcelError, Result};
 use crate::streaming_reader::{RowIterator, RowStructIterator, StreamingReader};
 
@@ -10,22 +13,20 @@ use crate::streaming_reader::{RowIterator, RowStructIterator, StreamingReader};
 use aws_sdk_s3::error::ProvideErrorMetadata;
 #[cfg(feature = "cloud-s3")]
 use aws_sdk_s3::Client;
-#[cfg(feature = "cloud-s3")]
-use std::io::Write;
-#[cfg(feature = "cloud-s3")]
-use tokio::io::AsyncReadExt;
 
 /// S3 Excel reader that downloads from Amazon S3 and streams rows
 ///
 /// # Architecture
 ///
-/// Downloads file from S3 to a temporary file, then uses StreamingReader
-/// for efficient row-by-row processing. Temp file is automatically cleaned
-/// up when S3ExcelReader is dropped.
+/// Nothing is downloaded as a whole. The ZIP central directory is read from
+/// the end of the object with a ranged GET, then StreamingReader fetches
+/// only the parts it reads: the workbook, its relationships and the shared
+/// strings when the reader is built, and a sheet's bytes chunk by chunk as
+/// its rows are streamed. Sheets that are never read are never fetched.
 ///
 /// # Memory Usage
 ///
-/// - Temp file: Full file size (local disk)
+/// - Download buffer: one ranged GET chunk (8 MB)
 /// - SST: 3-5 MB (in memory)
 /// - Per-row processing: ~100 KB
 ///
@@ -58,7 +59,6 @@ pub struct S3ExcelReader {
     key: String,
     _region: String,
     _s3_client: Option<Client>,
-    _temp_file: Option<tempfile::NamedTempFile>,
     streaming_reader: Option<StreamingReader>,
 }
 
@@ -69,7 +69,6 @@ impl std::fmt::Debug for S3ExcelReader {
             .field("key", &self.key)
             .field("region", &self._region)
             .field("has_s3_client", &self._s3_client.is_some())
-            .field("has_temp_file", &self._temp_file.is_some())
             .field("has_streaming_reader", &self.streaming_reader.is_some())
             .finish()
     }
@@ -136,64 +135,14 @@ impl S3ExcelReader {
         let bucket = bucket.into();
         let key = key.into();
 
-        // Download file from S3
-        let get_object_output = s3_client
-            .get_object()
-            .bucket(&bucket)
-            .key(&key)
-            .send()
-            .await
-            .map_err(|e| {
-                let error_code = e.code().unwrap_or("");
-                let error_message = e.message().unwrap_or("Unknown error");
-
-                match error_code {
-                    "NoSuchKey" => ExcelError::FileNotFound(format!("s3://{}/{}", bucket, key)),
-                    "NoSuchBucket" => {
-                        ExcelError::ReadError(format!("Bucket '{}' does not exist", bucket))
-                    }
-                    "AccessDenied" => ExcelError::ReadError(format!(
-                        "Access denied to s3://{}/{}. Error: {}",
-                        bucket, key, error_message
-                    )),
-                    _ => ExcelError::ReadError(format!(
-                        "S3 GetObject failed ({}): {}",
-                        error_code, error_message
-                    )),
-                }
-            })?;
-
-        // Create temp file
-        let mut temp_file = tempfile::NamedTempFile::new().map_err(|e| {
-            ExcelError::IoError(std::io::Error::other(format!(
-                "Failed to create temp file: {}",
-                e
-            )))
-        })?;
-
-        // Download S3 body to memory buffer first
-        let mut body = get_object_output.body.into_async_read();
-        let mut buffer = Vec::new();
-
-        use tokio::io::AsyncReadExt;
-        body.read_to_end(&mut buffer)
-            .await
-            .map_err(ExcelError::IoError)?;
-
-        // Write buffer to temp file
-        use std::io::Write;
-        temp_file.write_all(&buffer).map_err(ExcelError::IoError)?;
-        temp_file.flush().map_err(ExcelError::IoError)?;
-
-        // Open StreamingReader from temp file
-        let streaming_reader = StreamingReader::open(temp_file.path())?;
+        let source = S3RangedSource::new(s3_client.clone(), &bucket, &key).await?;
+        let streaming_reader = open_workbook(source, DEFAULT_CHUNK_SIZE).await?;
 
         Ok(Self {
             bucket,
             key,
             _region: "custom".to_string(),
             _s3_client: Some(s3_client),
-            _temp_file: Some(temp_file),
             streaming_reader: Some(streaming_reader),
         })
     }
@@ -330,7 +279,6 @@ impl Default for S3ExcelReader {
             key: String::new(),
             _region: "us-east-1".to_string(),
             _s3_client: None,
-            _temp_file: None,
             streaming_reader: None,
         }
     }
@@ -417,8 +365,8 @@ impl S3ExcelReaderBuilder {
     /// # Process
     /// 1. Validate bucket + key
     /// 2. Initialize AWS SDK client
-    /// 3. Download file from S3 to temp file
-    /// 4. Open StreamingReader from temp file
+    /// 3. Read the ZIP central directory from the end of the object
+    /// 4. Open StreamingReader over ranged GETs of the parts it reads
     /// 5. Return S3ExcelReader wrapper
     ///
     /// # Errors
@@ -513,61 +461,6 @@ impl S3ExcelReaderBuilder {
         ))
     }
 
-    #[cfg(feature = "cloud-s3")]
-    async fn download_from_s3(
-        client: &Client,
-        bucket: &str,
-        key: &str,
-    ) -> Result<aws_sdk_s3::operation::get_object::GetObjectOutput> {
-        client
-            .get_object()
-            .bucket(bucket)
-            .key(key)
-            .send()
-            .await
-            .map_err(|e| {
-                let error_code = e.code().unwrap_or("");
-                let error_message = e.message().unwrap_or("Unknown error");
-
-                match error_code {
-                    "NoSuchKey" => ExcelError::FileNotFound(format!("s3://{}/{}", bucket, key)),
-                    "NoSuchBucket" => {
-                        ExcelError::ReadError(format!("Bucket '{}' does not exist", bucket))
-                    }
-                    "AccessDenied" => ExcelError::ReadError(format!(
-                        "Access denied to s3://{}/{}. Error: {}",
-                        bucket, key, error_message
-                    )),
-                    _ => ExcelError::ReadError(format!(
-                        "S3 GetObject failed ({}): {}",
-                        error_code, error_message
-                    )),
-                }
-            })
-    }
-
-    #[cfg(feature = "cloud-s3")]
-    async fn create_reader_from_s3_response(
-        get_object_output: aws_sdk_s3::operation::get_object::GetObjectOutput,
-    ) -> Result<(tempfile::NamedTempFile, StreamingReader)> {
-        let mut body = get_object_output.body.into_async_read();
-        let mut buffer = Vec::new();
-
-        body.read_to_end(&mut buffer)
-            .await
-            .map_err(ExcelError::IoError)?;
-
-        let mut temp_file = tempfile::NamedTempFile::new().map_err(ExcelError::IoError)?;
-
-        temp_file.write_all(&buffer).map_err(ExcelError::IoError)?;
-        temp_file.flush().map_err(ExcelError::IoError)?;
-
-        let temp_path = temp_file.path().to_path_buf();
-        let streaming_reader = StreamingReader::open(&temp_path)?;
-
-        Ok((temp_file, streaming_reader))
-    }
-
     #[cfg(feature = "cloud-s3")]
     async fn build_reader_from_client(
         s3_client: Client,
@@ -575,21 +468,135 @@ impl S3ExcelReaderBuilder {
         key: String,
         region_str: String,
     ) -> Result<S3ExcelReader> {
-        let get_object_output = Self::download_from_s3(&s3_client, &bucket, &key).await?;
-        let (temp_file, streaming_reader) =
-            Self::create_reader_from_s3_response(get_object_output).await?;
+        let source = S3RangedSource::new(s3_client.clone(), &bucket, &key).await?;
+        let streaming_reader = open_workbook(source, DEFAULT_CHUNK_SIZE).await?;
 
         Ok(S3ExcelReader {
             bucket,
             key,
             _region: region_str,
             _s3_client: Some(s3_client),
-            _temp_file: Some(temp_file),
             streaming_reader: Some(streaming_reader),
         })
     }
 }
 
+/// An S3 object read with ranged GETs
+#[derive(Clone)]
+pub struct S3RangedSource {
+    client: Client,
+    bucket: String,
+    key: String,
+    size: u64,
+}
+
+impl S3RangedSource {
+    /// Look up the object size (HEAD) and prepare for ranged reads
+    pub async fn new(
+        client: Client,
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+    ) -> Result<Self> {
+        let bucket = bucket.into();
+        let key = key.into();
+
+        let head = client
+            .head_object()
+            .bucket(&bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| map_s3_error(&e, &bucket, &key))?;
+        let size = head.content_length().unwrap_or(0).max(0) as u64;
+
+        Ok(Self {
+            client,
+            bucket,
+            key,
+            size,
+        })
+    }
+}
+
+impl RangedSource for S3RangedSource {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    async fn read_range(&self, start: u64, end: u64) -> Result<Vec<u8>> {
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .range(format!("bytes={}-{}", start, end.saturating_sub(1)))
+            .send()
+            .await
+            .map_err(|e| map_s3_error(&e, &self.bucket, &self.key))?;
+
+        let body =
+            output.body.collect().await.map_err(|e| {
+                ExcelError::ReadError(format!("Failed to read S3 object body: {}", e))
+            })?;
+        Ok(body.into_bytes().to_vec())
+    }
+}
+
+/// CSV reader that streams an S3 object with ranged GETs
+///
+/// # Example
+///
+/// ```no_run
+/// use excelstream::cloud::S3CsvReader;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let config = aws_config::load_from_env().await;
+///     let client = aws_sdk_s3::Client::new(&config);
+///
+///     let mut reader = S3CsvReader::from_s3_client(client, "my-bucket", "data.csv")
+///         .await?
+///         .has_header(true);
+///
+///     while let Some(row) = reader.read_row().await? {
+///         println!("{:?}", row);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub type S3CsvReader = CloudCsvReader<S3RangedSource>;
+
+impl CloudCsvReader<S3RangedSource> {
+    /// Open a CSV object on S3
+    pub async fn from_s3_client(
+        s3_client: Client,
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+    ) -> Result<Self> {
+        let source = S3RangedSource::new(s3_client, bucket, key).await?;
+        Ok(CloudCsvReader::new(source))
+    }
+}
+
+fn map_s3_error<E: ProvideErrorMetadata>(e: &E, bucket: &str, key: &str) -> ExcelError {
+    let error_code = e.code().unwrap_or("");
+    let error_message = e.message().unwrap_or("Unknown error");
+
+    match error_code {
+        // HEAD responses carry no body, so a missing key shows up as "NotFound"
+        "NoSuchKey" | "NotFound" => ExcelError::FileNotFound(format!("s3://{}/{}", bucket, key)),
+        "NoSuchBucket" => ExcelError::ReadError(format!("Bucket '{}' does not exist", bucket)),
+        "AccessDenied" => ExcelError::ReadError(format!(
+            "Access denied to s3://{}/{}. Error: {}",
+            bucket, key, error_message
+        )),
+        _ => ExcelError::ReadError(format!(
+            "S3 GetObject failed ({}): {}",
+            error_code, error_message
+        )),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,17 +1,26 @@
 //! S3 Excel writer with TRUE direct streaming support (no temp files!)
 //!
 //! This module provides streaming Excel generation directly to Amazon S3
-//! using s-zip's cloud support. NO local disk space required!
+//! as a multipart upload. NO local disk space required!
 
+use super::RetryPolicy;
 use crate::error::{ExcelError, Result};
-use crate::types::{CellStyle, CellValue};
+use crate::fast_writer::pipeline::ZipSink;
+use crate::progress::{CancellationToken, Progress};
+use crate::sheet_name::Shards;
+use crate::types::{CellRef, CellStyle, CellValue};
+use std::borrow::Borrow;
+use std::fmt::{Display, Write};
+use std::future::Future;
 
 #[cfg(feature = "cloud-s3")]
 use aws_sdk_s3::Client;
 #[cfg(feature = "cloud-s3")]
 use s_zip::cloud::S3ZipWriter;
-#[cfg(feature = "cloud-s3")]
-use s_zip::AsyncStreamingZipWriter;
+use tokio::io::AsyncWriteExt;
+
+/// Size of an uploaded part; S3 wants at least 5 MB for all but the last
+const PART_SIZE: usize = 8 * 1024 * 1024;
 
 /// S3 Excel writer that streams directly to Amazon S3 (no temp files!)
 ///
@@ -38,14 +47,45 @@ use s_zip::AsyncStreamingZipWriter;
 ///     Ok(())
 /// }
 /// ```
+///
+/// # Finishing or abandoning an upload
+///
+/// Every writer should end with [`save`](Self::save) (or its alias
+/// [`close`](Self::close)) or with [`abort`](Self::abort). `abort()` aborts
+/// the in-progress multipart upload so S3 does not keep billing for the
+/// parts already sent.
+///
+/// Dropping the writer without either call logs a warning to stderr and
+/// aborts the upload. Inside a tokio runtime the abort runs on a detached
+/// task, so `Drop` never blocks the current task; outside a runtime it runs
+/// to completion on a temporary runtime before `drop` returns. Only the
+/// upload this writer started is aborted, never other uploads to the same
+/// key. Cleanup is best effort: a writer created with
+/// [`from_s3_writer`](Self::from_s3_writer) does not know its upload id and
+/// can't abort at all, so add an `AbortIncompleteMultipartUpload` lifecycle
+/// rule to the bucket.
+///
+/// # Transient errors
+///
+/// Requests that fail with a 5xx, throttling or timeout error are retried
+/// with exponential backoff, resending the part that was not acknowledged;
+/// see [`S3ExcelWriterBuilder::retry`]. When the attempts run out the row
+/// write fails but the row is already part of the workbook, and the part is
+/// kept: call [`resume`](Self::resume) to send it again and carry on with
+/// the next row.
 pub struct S3ExcelWriter {
-    zip_writer: Option<AsyncStreamingZipWriter<S3ZipWriter>>,
+    zip_writer: Option<ZipSink<Vec<u8>>>,
+    upload: Option<Upload>,
     current_row: u32,
     max_col: u32,
     xml_buffer: Vec<u8>,
+    scratch: String,
     worksheet_count: u32,
     worksheets: Vec<String>,
     in_worksheet: bool,
+    progress: Progress,
+    /// Rolling rows over to a new sheet, see `auto_shard_rows`
+    shards: Shards,
 }
 
 impl std::fmt::Debug for S3ExcelWriter {
@@ -61,6 +101,291 @@ impl std::fmt::Debug for S3ExcelWriter {
     }
 }
 
+impl Drop for S3ExcelWriter {
+    fn drop(&mut self) {
+        let Some(upload) = self.upload.take() else {
+            return;
+        };
+
+        crate::trace::warning!(
+            "S3ExcelWriter dropped without save() or abort(); the upload was not completed"
+        );
+
+        let Upload::Multipart(upload) = upload else {
+            crate::trace::warning!(
+                "the S3 upload of a writer from from_s3_writer() can't be aborted and was left behind"
+            );
+            return;
+        };
+        // Nothing was sent yet, so there is no upload to abort
+        if upload.upload_id().is_none() {
+            return;
+        }
+        let abort = async move {
+            if let Err(e) = upload.abort().await {
+                let upload_id = upload.upload_id().unwrap_or_default();
+                crate::trace::warning!("failed to abort S3 upload {}: {}", upload_id, e);
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(abort);
+            }
+            // Nothing else runs on this thread, so waiting for the abort is fine
+            Err(_) => match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime.block_on(abort),
+                Err(e) => {
+                    crate::trace::warning!("failed to abort S3 upload: {}", e);
+                }
+            },
+        }
+    }
+}
+
+/// Where the finished ZIP bytes go
+enum Upload {
+    /// Multipart upload started by the builder
+    Multipart(MultipartUpload<Client>),
+    /// Writer handed to [`S3ExcelWriter::from_s3_writer`]; its upload id is
+    /// private to s-zip
+    External(S3ZipWriter),
+}
+
+impl Upload {
+    async fn send_part(&mut self, part: Vec<u8>) -> Result<()> {
+        match self {
+            Upload::Multipart(upload) => upload.upload_part(part).await,
+            Upload::External(writer) => writer.write_all(&part).await.map_err(ExcelError::IoError),
+        }
+    }
+
+    async fn complete(self, last_part: Vec<u8>) -> Result<()> {
+        match self {
+            Upload::Multipart(mut upload) => {
+                if !last_part.is_empty() {
+                    upload.upload_part(last_part).await?;
+                }
+                upload.complete().await
+            }
+            Upload::External(mut writer) => {
+                writer.write_all(&last_part).await?;
+                writer.shutdown().await.map_err(ExcelError::IoError)
+            }
+        }
+    }
+}
+
+/// The S3 calls behind a multipart upload, so tests can stand in for S3
+pub(crate) trait MultipartApi: Send + Sync {
+    /// Start an upload and return its id
+    fn create(&self, bucket: &str, key: &str) -> impl Future<Output = Result<String>> + Send;
+
+    /// Upload part `part_number` (1-based) and return its ETag
+    fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: i32,
+        body: Vec<u8>,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// Assemble the object from `parts`, as `(part_number, etag)` pairs
+    fn complete(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[(i32, String)],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Discard the upload and the parts sent so far
+    fn abort(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl MultipartApi for Client {
+    async fn create(&self, bucket: &str, key: &str) -> Result<String> {
+        let output = self
+            .create_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .content_type("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+            .send()
+            .await
+            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        output.upload_id().map(str::to_string).ok_or_else(|| {
+            ExcelError::IoError(std::io::Error::other(
+                "S3 did not return an upload id".to_string(),
+            ))
+        })
+    }
+
+    async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: i32,
+        body: Vec<u8>,
+    ) -> Result<String> {
+        let output = self
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(body.into())
+            .send()
+            .await
+            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        Ok(output.e_tag().unwrap_or_default().to_string())
+    }
+
+    async fn complete(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[(i32, String)],
+    ) -> Result<()> {
+        use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+
+        let parts = parts
+            .iter()
+            .map(|(number, etag)| {
+                CompletedPart::builder()
+                    .part_number(*number)
+                    .e_tag(etag)
+                    .build()
+            })
+            .collect();
+        self.complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        Ok(())
+    }
+
+    async fn abort(&self, bucket: &str, key: &str, upload_id: &str) -> Result<()> {
+        self.abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await
+            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        Ok(())
+    }
+}
+
+/// One multipart upload, identified by the upload id S3 returned for it
+///
+/// The upload is only created with the first part, so a writer that is
+/// built and dropped never talks to S3.
+pub(crate) struct MultipartUpload<C> {
+    client: C,
+    bucket: String,
+    key: String,
+    upload_id: Option<String>,
+    /// `(part_number, etag)` of every part S3 acknowledged
+    parts: Vec<(i32, String)>,
+    /// Bytes of the next part, kept until S3 acknowledges them
+    pending: Vec<u8>,
+}
+
+impl<C: MultipartApi> MultipartUpload<C> {
+    pub(crate) fn new(client: C, bucket: String, key: String) -> Self {
+        MultipartUpload {
+            client,
+            bucket,
+            key,
+            upload_id: None,
+            parts: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Id of the upload, once it was created
+    pub(crate) fn upload_id(&self) -> Option<&str> {
+        self.upload_id.as_deref()
+    }
+
+    async fn ensure_started(&mut self) -> Result<String> {
+        if let Some(upload_id) = &self.upload_id {
+            return Ok(upload_id.clone());
+        }
+        let upload_id = self.client.create(&self.bucket, &self.key).await?;
+        self.upload_id = Some(upload_id.clone());
+        Ok(upload_id)
+    }
+
+    /// Upload `body` as the next part; after a failed upload it goes out
+    /// together with the part that failed
+    pub(crate) async fn upload_part(&mut self, body: Vec<u8>) -> Result<()> {
+        if self.pending.is_empty() {
+            self.pending = body;
+        } else {
+            self.pending.extend_from_slice(&body);
+        }
+        self.resume().await
+    }
+
+    /// Send the part a failed upload left unacknowledged, under the same
+    /// part number
+    pub(crate) async fn resume(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let upload_id = self.ensure_started().await?;
+        let part_number = self.parts.len() as i32 + 1;
+        let etag = self
+            .client
+            .upload_part(
+                &self.bucket,
+                &self.key,
+                &upload_id,
+                part_number,
+                self.pending.clone(),
+            )
+            .await?;
+        self.pending.clear();
+        self.parts.push((part_number, etag));
+        Ok(())
+    }
+
+    pub(crate) async fn complete(mut self) -> Result<()> {
+        self.resume().await?;
+        let upload_id = self.ensure_started().await?;
+        self.client
+            .complete(&self.bucket, &self.key, &upload_id, &self.parts)
+            .await
+    }
+
+    /// Abort this upload only; other uploads to the same key are untouched
+    pub(crate) async fn abort(&self) -> Result<()> {
+        match &self.upload_id {
+            Some(upload_id) => self.client.abort(&self.bucket, &self.key, upload_id).await,
+            None => Ok(()),
+        }
+    }
+}
+
 impl S3ExcelWriter {
     /// Create a new S3 Excel writer builder
     pub fn builder() -> S3ExcelWriterBuilder {
@@ -103,69 +428,101 @@ impl S3ExcelWriter {
     #[cfg(feature = "cloud-s3")]
     pub fn from_s3_writer(s3_writer: S3ZipWriter) -> Self {
         Self {
-            zip_writer: Some(AsyncStreamingZipWriter::from_writer(s3_writer)),
+            zip_writer: Some(ZipSink::new(Vec::new(), 6)),
+            upload: Some(Upload::External(s3_writer)),
             current_row: 0,
             max_col: 0,
             xml_buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
             worksheet_count: 0,
             worksheets: Vec::new(),
             in_worksheet: false,
+            progress: Progress::default(),
+            shards: Shards::new(None),
         }
     }
 
-    async fn ensure_worksheet(&mut self) -> Result<()> {
+    /// Call `callback` with `(rows_written, bytes_written)` every
+    /// [`PROGRESS_INTERVAL`](crate::progress::PROGRESS_INTERVAL) rows and once
+    /// from `save()`; bytes are counted before compression
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(u64, u64) + Send + 'static,
+    {
+        self.progress.set_callback(Box::new(callback));
+    }
+
+    /// Make row writes and `save()` fail with `ExcelError::Cancelled` once
+    /// `token` is cancelled
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.progress.set_token(token);
+    }
+
+    /// Send the part a failed upload left unacknowledged
+    ///
+    /// The part is sent again under its part number, so S3 ends up with
+    /// every byte exactly once. The row whose write failed is already in
+    /// the workbook; don't write it again. Fails like the upload did if S3
+    /// is still unreachable, and can be called again later. A writer from
+    /// [`from_s3_writer`](Self::from_s3_writer) uploads through s-zip and
+    /// has nothing to resume.
+    pub async fn resume(&mut self) -> Result<()> {
+        match self.upload.as_mut() {
+            Some(Upload::Multipart(upload)) => upload.resume().await,
+            Some(Upload::External(_)) | None => Ok(()),
+        }
+    }
+
+    /// Continue on a new sheet once a sheet holds `max_rows_per_sheet` rows
+    ///
+    /// Rows past the limit go to `Sheet1_2`, `Sheet1_3`, ... for a sheet
+    /// named `Sheet1`, each starting with a copy of the sheet's first row
+    /// as its header. Without this, writing past Excel's 1 048 576 rows per
+    /// sheet fails. Also available as
+    /// [`S3ExcelWriterBuilder::auto_shard_rows`].
+    pub fn set_auto_shard_rows(&mut self, max_rows_per_sheet: u32) -> Result<()> {
+        self.shards.set_rows(max_rows_per_sheet)
+    }
+
+    fn ensure_worksheet(&mut self) -> Result<()> {
         if !self.in_worksheet {
-            self.add_worksheet("Sheet1").await?;
+            self.add_worksheet("Sheet1")?;
         }
         Ok(())
     }
 
     /// Add a new worksheet
-    async fn add_worksheet(&mut self, name: &str) -> Result<()> {
+    fn add_worksheet(&mut self, name: &str) -> Result<()> {
         if self.in_worksheet {
-            self.finish_current_worksheet().await?;
+            self.finish_current_worksheet()?;
         }
 
         self.worksheet_count += 1;
         self.worksheets.push(name.to_string());
         self.current_row = 0;
         self.max_col = 0;
+        self.shards.reset();
 
         let entry_name = format!("xl/worksheets/sheet{}.xml", self.worksheet_count);
-        self.zip_writer
-            .as_mut()
-            .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?
-            .start_entry(&entry_name)
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.start_entry(&entry_name)?;
 
         let header = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
 <sheetData>"#;
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(header.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(header.as_bytes())?;
 
         self.in_worksheet = true;
         Ok(())
     }
 
-    async fn finish_current_worksheet(&mut self) -> Result<()> {
+    fn finish_current_worksheet(&mut self) -> Result<()> {
         if !self.in_worksheet {
             return Ok(());
         }
 
         let footer = "</sheetData></worksheet>";
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(footer.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(footer.as_bytes())?;
 
         self.in_worksheet = false;
         Ok(())
@@ -177,7 +534,7 @@ impl S3ExcelWriter {
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
-        self.ensure_worksheet().await?;
+        self.ensure_worksheet()?;
 
         let cells: Vec<_> = headers
             .into_iter()
@@ -198,60 +555,200 @@ impl S3ExcelWriter {
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
-        self.ensure_worksheet().await?;
+        self.ensure_worksheet()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
 
         self.current_row += 1;
-        let values: Vec<String> = row.into_iter().map(|s| s.as_ref().to_string()).collect();
-        self.max_col = self.max_col.max(values.len() as u32);
+        self.start_row_xml();
+
+        let mut col_count = 0;
+        for (col_idx, value) in row.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            col_count += 1;
+            self.push_inline_str_cell(col_idx as u32, value.as_ref());
+        }
+
+        self.finish_row_xml(col_count).await
+    }
 
+    /// Write a data row of `Display` values (numbers, dates, custom types...)
+    ///
+    /// Values are formatted into a reused scratch buffer, so no intermediate
+    /// `Vec<String>` is built per row.
+    pub async fn write_row_display<I>(&mut self, row: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.ensure_worksheet()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
+
+        self.current_row += 1;
+        self.start_row_xml();
+
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let mut col_count = 0;
+        for (col_idx, value) in row.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.scratch = scratch;
+                self.current_row -= 1;
+                return Err(e);
+            }
+            col_count += 1;
+            scratch.clear();
+            // Writing into a String never fails
+            let _ = write!(scratch, "{}", value);
+            self.push_inline_str_cell(col_idx as u32, &scratch);
+        }
+        self.scratch = scratch;
+
+        self.finish_row_xml(col_count).await
+    }
+
+    fn start_row_xml(&mut self) {
         self.xml_buffer.clear();
         self.xml_buffer.extend_from_slice(b"<row r=\"");
         self.xml_buffer
             .extend_from_slice(self.current_row.to_string().as_bytes());
         self.xml_buffer.extend_from_slice(b"\">");
+    }
 
-        for (col_idx, value) in values.iter().enumerate() {
-            let col_letter = Self::column_letter(col_idx as u32 + 1);
-            self.xml_buffer.extend_from_slice(b"<c r=\"");
-            self.xml_buffer.extend_from_slice(col_letter.as_bytes());
+    fn push_inline_str_cell(&mut self, col_idx: u32, value: &str) {
+        let col_letter = Self::column_letter(col_idx + 1);
+        self.xml_buffer.extend_from_slice(b"<c r=\"");
+        self.xml_buffer.extend_from_slice(col_letter.as_bytes());
+        self.xml_buffer
+            .extend_from_slice(self.current_row.to_string().as_bytes());
+
+        if value.is_empty() {
+            self.xml_buffer.extend_from_slice(b"\"/>");
+        } else {
             self.xml_buffer
-                .extend_from_slice(self.current_row.to_string().as_bytes());
+                .extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
+            Self::write_escaped(&mut self.xml_buffer, value);
+            self.xml_buffer.extend_from_slice(b"</t></is></c>");
+        }
+    }
 
-            if value.is_empty() {
-                self.xml_buffer.extend_from_slice(b"\"/>");
-            } else {
-                self.xml_buffer
-                    .extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
-                Self::write_escaped(&mut self.xml_buffer, value.as_str());
-                self.xml_buffer.extend_from_slice(b"</t></is></c>");
-            }
+    /// Make room for the next row, rolling over to a new shard if the sheet
+    /// is full
+    fn check_row_limit(&mut self) -> Result<()> {
+        if !self.shards.is_full(self.current_row) {
+            return Ok(());
         }
+        let name = self.worksheets.last().cloned().unwrap_or_default();
+        let next = self.shards.next(&name, &self.worksheets)?;
+        let max_col = self.max_col;
+        self.add_worksheet(&next.name)?;
+        // The header is row 1 again, so its XML is reused as it is
+        if let Some(header) = self.shards.started(next) {
+            self.current_row = 1;
+            self.max_col = max_col;
+            self.write_data(&header)?;
+            self.progress.row_written(header.len());
+        }
+        Ok(())
+    }
 
+    async fn finish_row_xml(&mut self, col_count: u32) -> Result<()> {
+        self.max_col = self.max_col.max(col_count);
         self.xml_buffer.extend_from_slice(b"</row>");
+        self.shards
+            .keep_header(self.current_row, || self.xml_buffer.clone());
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(&self.xml_buffer)
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        let row = std::mem::take(&mut self.xml_buffer);
+        let written = self.write_data(&row);
+        self.xml_buffer = row;
+        written?;
+        self.progress.row_written(self.xml_buffer.len());
 
-        Ok(())
+        self.upload_full_part().await
     }
 
     /// Write a data row with typed values
     pub async fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
-        let styled_cells: Vec<_> = cells
-            .iter()
-            .map(|v| crate::types::StyledCell::new(v.clone(), CellStyle::Default))
-            .collect();
+        self.write_row_typed_iter(cells).await
+    }
+
+    /// Write a data row from an iterator of owned or borrowed typed values,
+    /// without collecting it first
+    pub async fn write_row_typed_iter<I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CellValue>,
+    {
+        self.ensure_worksheet()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
+
+        self.current_row += 1;
+        self.start_row_xml();
+
+        let mut col_count = 0;
+        for (col_idx, value) in cells.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            col_count += 1;
+            let value = value.borrow();
+            let style_id = value.default_style().effective_for(value).index();
+            self.push_styled_cell(col_idx as u32, value, style_id);
+        }
+
+        self.finish_row_xml(col_count).await
+    }
+
+    /// Write a data row of borrowed cells, see [`CellRef`]
+    pub async fn write_row_refs<'a, I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<CellRef<'a>>,
+    {
+        self.ensure_worksheet()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
 
-        self.write_row_styled(&styled_cells).await
+        self.current_row += 1;
+        self.start_row_xml();
+
+        let mut col_count = 0;
+        for (col_idx, cell) in cells.into_iter().enumerate() {
+            if let Err(e) = crate::sheet_name::check_columns(col_idx + 1) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+            col_count += 1;
+            let owned;
+            let value = match cell.into() {
+                CellRef::String(s) => {
+                    self.push_inline_str_cell(col_idx as u32, s);
+                    continue;
+                }
+                CellRef::Value(value) => value,
+                cell => {
+                    owned = cell.into_owned();
+                    &owned
+                }
+            };
+            let style_id = value.default_style().effective_for(value).index();
+            self.push_styled_cell(col_idx as u32, value, style_id);
+        }
+
+        self.finish_row_xml(col_count).await
     }
 
     /// Write a row with styled cells
     pub async fn write_row_styled(&mut self, cells: &[crate::types::StyledCell]) -> Result<()> {
-        self.ensure_worksheet().await?;
+        self.ensure_worksheet()?;
+        self.progress.check()?;
+        crate::sheet_name::check_columns(cells.len())?;
+        self.check_row_limit()?;
 
         self.current_row += 1;
         self.max_col = self.max_col.max(cells.len() as u32);
@@ -263,109 +760,178 @@ impl S3ExcelWriter {
         self.xml_buffer.extend_from_slice(b"\">");
 
         for (col_idx, styled_cell) in cells.iter().enumerate() {
-            let col_letter = Self::column_letter(col_idx as u32 + 1);
-            let value = &styled_cell.value;
-            let style_id = styled_cell.style.index();
+            let style_id = styled_cell.effective_style().index();
+            self.push_styled_cell(col_idx as u32, &styled_cell.value, style_id);
+        }
+
+        self.finish_row_xml(cells.len() as u32).await
+    }
+
+    /// Append one `<c>` element to the row being built
+    fn push_styled_cell(&mut self, col_idx: u32, value: &CellValue, style_id: u32) {
+        let col_letter = Self::column_letter(col_idx + 1);
 
-            self.xml_buffer.extend_from_slice(b"<c r=\"");
-            self.xml_buffer.extend_from_slice(col_letter.as_bytes());
+        self.xml_buffer.extend_from_slice(b"<c r=\"");
+        self.xml_buffer.extend_from_slice(col_letter.as_bytes());
+        self.xml_buffer
+            .extend_from_slice(self.current_row.to_string().as_bytes());
+        self.xml_buffer.extend_from_slice(b"\"");
+
+        if style_id > 0 {
+            self.xml_buffer.extend_from_slice(b" s=\"");
             self.xml_buffer
-                .extend_from_slice(self.current_row.to_string().as_bytes());
+                .extend_from_slice(style_id.to_string().as_bytes());
             self.xml_buffer.extend_from_slice(b"\"");
+        }
 
-            if style_id > 0 {
-                self.xml_buffer.extend_from_slice(b" s=\"");
+        match value {
+            CellValue::Empty => {
+                self.xml_buffer.extend_from_slice(b"/>");
+            }
+            CellValue::Int(i) => {
+                self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
+                self.xml_buffer.extend_from_slice(i.to_string().as_bytes());
+                self.xml_buffer.extend_from_slice(b"</v></c>");
+            }
+            CellValue::Float(f) => {
+                crate::fast_writer::number::push_number_cell(&mut self.xml_buffer, *f);
+            }
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => {
+                self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
+                self.xml_buffer.extend_from_slice(d.to_string().as_bytes());
+                self.xml_buffer.extend_from_slice(b"</v></c>");
+            }
+            CellValue::Bool(b) => {
+                self.xml_buffer.extend_from_slice(b" t=\"b\"><v>");
                 self.xml_buffer
-                    .extend_from_slice(style_id.to_string().as_bytes());
-                self.xml_buffer.extend_from_slice(b"\"");
+                    .extend_from_slice(if *b { b"1" } else { b"0" });
+                self.xml_buffer.extend_from_slice(b"</v></c>");
             }
-
-            match value {
-                CellValue::Empty => {
-                    self.xml_buffer.extend_from_slice(b"/>");
-                }
-                CellValue::Int(i) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(i.to_string().as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                CellValue::Float(f) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(f.to_string().as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                CellValue::Bool(b) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"b\"><v>");
-                    self.xml_buffer
-                        .extend_from_slice(if *b { b"1" } else { b"0" });
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                CellValue::String(s) => {
-                    self.xml_buffer
-                        .extend_from_slice(b" t=\"inlineStr\"><is><t>");
-                    Self::write_escaped(&mut self.xml_buffer, s);
-                    self.xml_buffer.extend_from_slice(b"</t></is></c>");
-                }
-                CellValue::Formula(f) => {
-                    self.xml_buffer.extend_from_slice(b"><f>");
-                    Self::write_escaped(&mut self.xml_buffer, f);
-                    self.xml_buffer.extend_from_slice(b"</f></c>");
-                }
-                CellValue::DateTime(dt) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(dt.to_string().as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
+            // Hyperlinks are written as their text only
+            CellValue::String(s) | CellValue::Hyperlink { text: s, .. } => {
+                self.xml_buffer
+                    .extend_from_slice(b" t=\"inlineStr\"><is><t>");
+                Self::write_escaped(&mut self.xml_buffer, s);
+                self.xml_buffer.extend_from_slice(b"</t></is></c>");
+            }
+            CellValue::BigInt(digits) => {
+                self.xml_buffer
+                    .extend_from_slice(b" t=\"inlineStr\"><is><t>");
+                Self::write_escaped(&mut self.xml_buffer, digits);
+                self.xml_buffer.extend_from_slice(b"</t></is></c>");
+            }
+            CellValue::Formula { expr, cached } => {
+                let result = cached.as_deref().and_then(CellValue::formula_result);
+                if let Some((type_attr, _)) = &result {
+                    self.xml_buffer.extend_from_slice(type_attr.as_bytes());
                 }
-                CellValue::Error(e) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"e\"><v>");
-                    Self::write_escaped(&mut self.xml_buffer, e);
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
+                self.xml_buffer.extend_from_slice(b"><f>");
+                Self::write_escaped(&mut self.xml_buffer, expr.strip_prefix('=').unwrap_or(expr));
+                self.xml_buffer.extend_from_slice(b"</f>");
+                if let Some((_, value)) = &result {
+                    self.xml_buffer.extend_from_slice(b"<v>");
+                    Self::write_escaped(&mut self.xml_buffer, value);
+                    self.xml_buffer.extend_from_slice(b"</v>");
                 }
+                self.xml_buffer.extend_from_slice(b"</c>");
+            }
+            CellValue::DateTime(dt) => {
+                crate::fast_writer::number::push_number_cell(&mut self.xml_buffer, *dt);
+            }
+            CellValue::Error(e) => {
+                self.xml_buffer.extend_from_slice(b" t=\"e\"><v>");
+                Self::write_escaped(&mut self.xml_buffer, e);
+                self.xml_buffer.extend_from_slice(b"</v></c>");
             }
         }
-
-        self.xml_buffer.extend_from_slice(b"</row>");
-
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(&self.xml_buffer)
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
-
-        Ok(())
     }
 
     /// Save and upload Excel file to S3 (streaming, no temp files!)
     pub async fn save(mut self) -> Result<()> {
-        self.finish_current_worksheet().await?;
+        self.progress.check()?;
+        self.finish_current_worksheet()?;
+        self.progress.finish();
 
-        self.write_content_types().await?;
-        self.write_rels().await?;
-        self.write_workbook().await?;
-        self.write_workbook_rels().await?;
-        self.write_styles().await?;
+        self.write_content_types()?;
+        self.write_rels()?;
+        self.write_workbook()?;
+        self.write_workbook_rels()?;
+        self.write_styles()?;
 
         let zip_writer = self
             .zip_writer
             .take()
             .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?;
+        let last_part = zip_writer.finish()?;
+        let upload = self
+            .upload
+            .take()
+            .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?;
+        upload.complete(last_part).await
+    }
 
-        zip_writer
-            .finish()
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    /// Finish the workbook and complete the upload
+    ///
+    /// Same as [`save`](Self::save); provided so the writer can be ended
+    /// with an explicit `close()` / `abort()` pair.
+    pub async fn close(self) -> Result<()> {
+        self.save().await
+    }
 
-        Ok(())
+    /// Abandon the upload without creating the object
+    ///
+    /// Aborts the multipart upload this writer started when it was built
+    /// by [`S3ExcelWriterBuilder`]. Other uploads to the same key are left
+    /// alone. For a writer from [`from_s3_writer`](Self::from_s3_writer)
+    /// the parts are only discarded locally (see the type-level docs).
+    pub async fn abort(mut self) -> Result<()> {
+        drop(self.zip_writer.take());
+        match self.upload.take() {
+            Some(Upload::Multipart(upload)) => upload.abort().await,
+            Some(Upload::External(_)) | None => Ok(()),
+        }
     }
 
-    async fn write_content_types(&mut self) -> Result<()> {
+    fn start_entry(&mut self, name: &str) -> Result<()> {
         self.zip_writer
             .as_mut()
-            .unwrap()
-            .start_entry("[Content_Types].xml")
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+            .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?
+            .start_entry(name)
+    }
+
+    /// Add `data` to the current entry
+    fn write_data(&mut self, data: &[u8]) -> Result<()> {
+        self.zip_writer
+            .as_mut()
+            .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?
+            .write_data(data)
+    }
+
+    /// Upload a part once one is full
+    ///
+    /// Only called once a row is complete, so a failed upload never leaves
+    /// a row or sheet half written.
+    async fn upload_full_part(&mut self) -> Result<()> {
+        let zip_writer = self
+            .zip_writer
+            .as_mut()
+            .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))?;
+        if zip_writer.output_mut().len() < PART_SIZE {
+            return Ok(());
+        }
+        let part = std::mem::take(zip_writer.output_mut());
+        self.upload_mut()?.send_part(part).await
+    }
+
+    fn upload_mut(&mut self) -> Result<&mut Upload> {
+        self.upload
+            .as_mut()
+            .ok_or_else(|| ExcelError::InvalidState("Writer not initialized".to_string()))
+    }
+
+    fn write_content_types(&mut self) -> Result<()> {
+        self.start_entry("[Content_Types].xml")?;
 
         let mut xml = String::from(
             r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
@@ -385,46 +951,26 @@ impl S3ExcelWriter {
 
         xml.push_str("</Types>");
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(xml.as_bytes())?;
 
         Ok(())
     }
 
-    async fn write_rels(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("_rels/.rels")
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    fn write_rels(&mut self) -> Result<()> {
+        self.start_entry("_rels/.rels")?;
 
         let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
 <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
 </Relationships>"#;
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(xml.as_bytes())?;
 
         Ok(())
     }
 
-    async fn write_workbook(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/workbook.xml")
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    fn write_workbook(&mut self) -> Result<()> {
+        self.start_entry("xl/workbook.xml")?;
 
         let mut xml = String::from(
             r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
@@ -436,29 +982,21 @@ impl S3ExcelWriter {
             let sheet_id = idx + 1;
             xml.push_str(&format!(
                 r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#,
-                name, sheet_id, sheet_id
+                crate::fast_writer::xml_writer::escape(name),
+                sheet_id,
+                sheet_id
             ));
         }
 
         xml.push_str("</sheets></workbook>");
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(xml.as_bytes())?;
 
         Ok(())
     }
 
-    async fn write_workbook_rels(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/_rels/workbook.xml.rels")
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    fn write_workbook_rels(&mut self) -> Result<()> {
+        self.start_entry("xl/_rels/workbook.xml.rels")?;
 
         let mut xml = String::from(
             r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
@@ -479,23 +1017,13 @@ impl S3ExcelWriter {
 
         xml.push_str("</Relationships>");
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(xml.as_bytes())?;
 
         Ok(())
     }
 
-    async fn write_styles(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/styles.xml")
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+    fn write_styles(&mut self) -> Result<()> {
+        self.start_entry("xl/styles.xml")?;
 
         let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
@@ -504,10 +1032,11 @@ impl S3ExcelWriter {
 <numFmt numFmtId="165" formatCode="mm/dd/yyyy hh:mm:ss"/>
 <numFmt numFmtId="166" formatCode="mm/dd/yyyy hh:mm"/>
 </numFmts>
-<fonts count="3">
+<fonts count="4">
 <font><sz val="11"/><name val="Calibri"/></font>
 <font><b/><sz val="11"/><name val="Calibri"/></font>
 <font><i/><sz val="11"/><name val="Calibri"/></font>
+<font><u/><sz val="11"/><color rgb="FF0563C1"/><name val="Calibri"/></font>
 </fonts>
 <fills count="5">
 <fill><patternFill patternType="none"/></fill>
@@ -520,7 +1049,7 @@ impl S3ExcelWriter {
 <border><left/><right/><top/><bottom/><diagonal/></border>
 <border><left style="thin"/><right style="thin"/><top style="thin"/><bottom style="thin"/></border>
 </borders>
-<cellXfs count="15">
+<cellXfs count="16">
 <xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>
 <xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/>
 <xf numFmtId="3" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>
@@ -536,15 +1065,11 @@ impl S3ExcelWriter {
 <xf numFmtId="0" fontId="0" fillId="4" borderId="0" xfId="0" applyFill="1"/>
 <xf numFmtId="0" fontId="0" fillId="0" borderId="1" xfId="0" applyBorder="1"/>
 <xf numFmtId="166" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>
+<xf numFmtId="0" fontId="3" fillId="0" borderId="0" xfId="0" applyFont="1"/>
 </cellXfs>
 </styleSheet>"#;
 
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
+        self.write_data(xml.as_bytes())?;
 
         Ok(())
     }
@@ -561,19 +1086,7 @@ impl S3ExcelWriter {
     }
 
     fn write_escaped(buffer: &mut Vec<u8>, text: &str) {
-        for ch in text.chars() {
-            match ch {
-                '<' => buffer.extend_from_slice(b"&lt;"),
-                '>' => buffer.extend_from_slice(b"&gt;"),
-                '&' => buffer.extend_from_slice(b"&amp;"),
-                '"' => buffer.extend_from_slice(b"&quot;"),
-                '\'' => buffer.extend_from_slice(b"&apos;"),
-                _ => {
-                    let mut buf = [0; 4];
-                    buffer.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
-                }
-            }
-        }
+        crate::fast_writer::xml_writer::push_escaped(buffer, text)
     }
 }
 
@@ -586,6 +1099,8 @@ pub struct S3ExcelWriterBuilder {
     region: Option<String>,
     endpoint_url: Option<String>,
     force_path_style: bool,
+    retry: Option<RetryPolicy>,
+    auto_shard_rows: Option<u32>,
 }
 
 impl Default for S3ExcelWriterBuilder {
@@ -596,6 +1111,8 @@ impl Default for S3ExcelWriterBuilder {
             region: Some("us-east-1".to_string()),
             endpoint_url: None,
             force_path_style: false,
+            retry: None,
+            auto_shard_rows: None,
         }
     }
 }
@@ -639,6 +1156,35 @@ impl S3ExcelWriterBuilder {
         self
     }
 
+    /// Retry failed requests, part uploads included, per `policy`
+    ///
+    /// [`build`](Self::build) uses [`RetryPolicy::default`] when this is not
+    /// called. With [`build_with_client`](Self::build_with_client) the
+    /// client's own retry settings are kept unless a policy is set here.
+    ///
+    /// ```ignore
+    /// use excelstream::cloud::{RetryPolicy, S3ExcelWriter};
+    /// use std::time::Duration;
+    ///
+    /// let writer = S3ExcelWriter::builder()
+    ///     .bucket("exports")
+    ///     .key("orders.xlsx")
+    ///     .retry(RetryPolicy::new(8).with_attempt_timeout(Duration::from_secs(120)))
+    ///     .build()
+    ///     .await?;
+    /// ```
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Continue on a new sheet once a sheet holds `max_rows_per_sheet`
+    /// rows, see [`S3ExcelWriter::set_auto_shard_rows`]
+    pub fn auto_shard_rows(mut self, max_rows_per_sheet: u32) -> Self {
+        self.auto_shard_rows = Some(max_rows_per_sheet);
+        self
+    }
+
     /// Build the S3ExcelWriter
     ///
     /// # Examples
@@ -666,39 +1212,35 @@ impl S3ExcelWriterBuilder {
     /// ```
     #[cfg(feature = "cloud-s3")]
     pub async fn build(self) -> Result<S3ExcelWriter> {
-        let bucket = self
-            .bucket
-            .ok_or_else(|| ExcelError::InvalidState("Bucket name required".to_string()))?;
-        let key = self
-            .key
-            .ok_or_else(|| ExcelError::InvalidState("Object key required".to_string()))?;
-        let region = self.region.unwrap_or_else(|| "us-east-1".to_string());
-
-        let mut builder = S3ZipWriter::builder()
-            .region(&region)
-            .bucket(&bucket)
-            .key(&key);
-
+        if self.bucket.is_none() {
+            return Err(ExcelError::InvalidState("Bucket name required".to_string()));
+        }
+        if self.key.is_none() {
+            return Err(ExcelError::InvalidState("Object key required".to_string()));
+        }
+        let region = self
+            .region
+            .clone()
+            .unwrap_or_else(|| "us-east-1".to_string());
+
+        // NOTE: Credentials come from the default provider chain:
+        // - AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY (/ AWS_SESSION_TOKEN)
+        // - shared config and credentials files, SSO, instance roles
+        // See MULTI_CLOUD_CONFIG.md for multi-cloud setup
+        let sdk_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_sdk_s3::config::Region::new(region))
+            .load()
+            .await;
+        let mut config = aws_sdk_s3::config::Builder::from(&sdk_config);
         if let Some(endpoint) = &self.endpoint_url {
-            builder = builder.endpoint_url(endpoint);
+            config = config.endpoint_url(endpoint);
         }
-
         if self.force_path_style {
-            builder = builder.force_path_style(true);
+            config = config.force_path_style(true);
         }
+        let client = Client::from_conf(self.retry.unwrap_or_default().apply_to_s3(config));
 
-        // NOTE: Credentials are loaded from environment variables:
-        // - AWS_ACCESS_KEY_ID
-        // - AWS_SECRET_ACCESS_KEY
-        // - AWS_SESSION_TOKEN (optional, for temporary credentials)
-        // See MULTI_CLOUD_CONFIG.md for multi-cloud setup
-
-        let s3_writer = builder
-            .build()
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
-
-        Self::create_writer_from_s3_writer(s3_writer)
+        self.build_with_client(client).await
     }
 
     #[cfg(not(feature = "cloud-s3"))]
@@ -716,46 +1258,176 @@ impl S3ExcelWriterBuilder {
         let key = self
             .key
             .ok_or_else(|| ExcelError::InvalidState("Object key required".to_string()))?;
-        let region = self.region.unwrap_or_else(|| "us-east-1".to_string());
-
-        let s3_writer = S3ZipWriter::builder()
-            .client(client)
-            .region(&region)
-            .bucket(&bucket)
-            .key(&key)
-            .build()
-            .await
-            .map_err(|e| ExcelError::IoError(std::io::Error::other(e.to_string())))?;
-
-        Self::create_writer_from_s3_writer(s3_writer)
-    }
-
-    #[cfg(not(feature = "cloud-s3"))]
-    pub async fn build_with_client(self, _client: Client) -> Result<S3ExcelWriter> {
-        Err(ExcelError::InvalidState(
-            "cloud-s3 feature not enabled".to_string(),
-        ))
-    }
-
-    #[cfg(feature = "cloud-s3")]
-    fn create_writer_from_s3_writer(s3_writer: S3ZipWriter) -> Result<S3ExcelWriter> {
-        let zip_writer = AsyncStreamingZipWriter::from_writer(s3_writer);
+        let shard_rows = self
+            .auto_shard_rows
+            .map(crate::sheet_name::check_shard_rows)
+            .transpose()?;
+        let client = match &self.retry {
+            Some(policy) => Client::from_conf(policy.apply_to_s3(client.config().to_builder())),
+            None => client,
+        };
 
         Ok(S3ExcelWriter {
-            zip_writer: Some(zip_writer),
+            zip_writer: Some(ZipSink::new(Vec::new(), 6)),
+            upload: Some(Upload::Multipart(MultipartUpload::new(client, bucket, key))),
             current_row: 0,
             max_col: 0,
             xml_buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
             worksheet_count: 0,
             worksheets: Vec::new(),
             in_worksheet: false,
+            progress: Progress::default(),
+            shards: Shards::new(shard_rows),
         })
     }
+
+    #[cfg(not(feature = "cloud-s3"))]
+    pub async fn build_with_client(self, _client: Client) -> Result<S3ExcelWriter> {
+        Err(ExcelError::InvalidState(
+            "cloud-s3 feature not enabled".to_string(),
+        ))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Records the multipart calls instead of sending them
+    #[derive(Clone, Default)]
+    struct MockS3 {
+        calls: Arc<Mutex<Vec<String>>>,
+        /// Part uploads still to fail
+        failures: Arc<Mutex<u32>>,
+    }
+
+    impl MockS3 {
+        fn log(&self, call: String) {
+            self.calls.lock().unwrap().push(call);
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl MultipartApi for MockS3 {
+        async fn create(&self, _bucket: &str, key: &str) -> Result<String> {
+            let created = self
+                .calls()
+                .iter()
+                .filter(|c| c.starts_with("create"))
+                .count();
+            let upload_id = format!("upload-{}", created + 1);
+            self.log(format!("create {} {}", key, upload_id));
+            Ok(upload_id)
+        }
+
+        async fn upload_part(
+            &self,
+            _bucket: &str,
+            _key: &str,
+            upload_id: &str,
+            part_number: i32,
+            body: Vec<u8>,
+        ) -> Result<String> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                self.log(format!("part {} {} failed", upload_id, part_number));
+                return Err(ExcelError::IoError(
+                    std::io::ErrorKind::ConnectionReset.into(),
+                ));
+            }
+            self.log(format!("part {} {} {}", upload_id, part_number, body.len()));
+            Ok(format!("etag-{}", part_number))
+        }
+
+        async fn complete(
+            &self,
+            _bucket: &str,
+            _key: &str,
+            upload_id: &str,
+            parts: &[(i32, String)],
+        ) -> Result<()> {
+            self.log(format!("complete {} {}", upload_id, parts.len()));
+            Ok(())
+        }
+
+        async fn abort(&self, _bucket: &str, _key: &str, upload_id: &str) -> Result<()> {
+            self.log(format!("abort {}", upload_id));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_abort_targets_only_its_own_upload() {
+        let s3 = MockS3::default();
+        let mut ours = MultipartUpload::new(s3.clone(), "bucket".into(), "report.xlsx".into());
+        let mut other = MultipartUpload::new(s3.clone(), "bucket".into(), "report.xlsx".into());
+
+        ours.upload_part(vec![1; 16]).await.unwrap();
+        other.upload_part(vec![2; 16]).await.unwrap();
+        ours.abort().await.unwrap();
+        other.complete().await.unwrap();
+
+        assert_eq!(
+            s3.calls(),
+            vec![
+                "create report.xlsx upload-1",
+                "part upload-1 1 16",
+                "create report.xlsx upload-2",
+                "part upload-2 1 16",
+                "abort upload-1",
+                "complete upload-2 1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_part_is_resent_under_its_number() {
+        let s3 = MockS3::default();
+        let mut upload = MultipartUpload::new(s3.clone(), "bucket".into(), "report.xlsx".into());
+        upload.upload_part(vec![1; 16]).await.unwrap();
+
+        *s3.failures.lock().unwrap() = 2;
+        assert!(upload.upload_part(vec![2; 16]).await.is_err());
+        assert!(upload.resume().await.is_err());
+        upload.resume().await.unwrap();
+        upload.upload_part(vec![3; 16]).await.unwrap();
+
+        // A part that failed goes out with the next one
+        *s3.failures.lock().unwrap() = 1;
+        assert!(upload.upload_part(vec![4; 16]).await.is_err());
+        upload.upload_part(vec![5; 8]).await.unwrap();
+        upload.complete().await.unwrap();
+
+        assert_eq!(
+            s3.calls(),
+            vec![
+                "create report.xlsx upload-1",
+                "part upload-1 1 16",
+                "part upload-1 2 failed",
+                "part upload-1 2 failed",
+                "part upload-1 2 16",
+                "part upload-1 3 16",
+                "part upload-1 4 failed",
+                "part upload-1 4 24",
+                "complete upload-1 4",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_abort_before_first_part_sends_nothing() {
+        let s3 = MockS3::default();
+        let upload = MultipartUpload::new(s3.clone(), "bucket".into(), "report.xlsx".into());
+        assert_eq!(upload.upload_id(), None);
+        upload.abort().await.unwrap();
+        assert!(s3.calls().is_empty());
+    }
 
     #[test]
     fn test_builder_validation_missing_bucket() {
@@ -794,7 +1466,8 @@ mod tests {
             .key("path/to/file.xlsx")
             .region("ap-southeast-1")
             .endpoint_url("http://localhost:9000")
-            .force_path_style(true);
+            .force_path_style(true)
+            .retry(RetryPolicy::new(5));
 
         assert_eq!(builder.bucket, Some("my-bucket".to_string()));
         assert_eq!(builder.key, Some("path/to/file.xlsx".to_string()));
@@ -804,6 +1477,7 @@ mod tests {
             Some("http://localhost:9000".to_string())
         );
         assert!(builder.force_path_style);
+        assert_eq!(builder.retry.map(|r| r.max_attempts), Some(5));
     }
 
     #[cfg(feature = "cloud-s3")]
@@ -0,0 +1,369 @@
+//! Compare StreamingReader output against calamine
+//!
+//! A migration aid: read the same workbook with both [`StreamingReader`] and
+//! calamine and list every cell where the two disagree. The resulting
+//! [`CompatReport`] prints one line per difference, which is usually enough
+//! to turn a vague "values are read differently" report into a fix.
+//!
+//! Requires the `calamine-compat` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use excelstream::compat::compare_with_calamine;
+//!
+//! let report = compare_with_calamine("data.xlsx")?;
+//! if !report.is_identical() {
+//!     println!("{}", report);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::error::{ExcelError, Result};
+use crate::streaming_reader::StreamingReader;
+use crate::types::CellValue;
+use std::fmt;
+use std::path::Path;
+
+use calamine::{open_workbook_auto, Data, Range, Reader};
+
+/// Default cap on the number of differences collected per report
+pub const DEFAULT_MAX_DIFFERENCES: usize = 1000;
+
+/// A cell where StreamingReader and calamine disagree
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellDiff {
+    /// Sheet name
+    pub sheet: String,
+    /// Row index (0-based)
+    pub row: u32,
+    /// Column index (0-based)
+    pub col: u32,
+    /// Value returned by StreamingReader
+    pub streaming: CellValue,
+    /// Value returned by calamine
+    pub calamine: CellValue,
+}
+
+impl CellDiff {
+    /// Cell reference in A1 notation (e.g. "B3")
+    pub fn cell_ref(&self) -> String {
+        let mut letters = String::new();
+        let mut n = self.col + 1;
+        while n > 0 {
+            let rem = (n - 1) % 26;
+            letters.insert(0, (b'A' + rem as u8) as char);
+            n = (n - 1) / 26;
+        }
+        format!("{}{}", letters, self.row + 1)
+    }
+}
+
+impl fmt::Display for CellDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}!{}: streaming={:?} calamine={:?}",
+            self.sheet,
+            self.cell_ref(),
+            self.streaming,
+            self.calamine
+        )
+    }
+}
+
+/// Result of comparing a workbook with both readers
+#[derive(Debug, Clone, Default)]
+pub struct CompatReport {
+    /// Sheets read by both readers
+    pub sheets_compared: Vec<String>,
+    /// Sheets that only one reader found
+    pub sheet_mismatches: Vec<String>,
+    /// Number of cells compared
+    pub cells_compared: u64,
+    /// Cell-level differences (capped at the configured maximum)
+    pub differences: Vec<CellDiff>,
+    /// True if more differences existed than were collected
+    pub truncated: bool,
+}
+
+impl CompatReport {
+    /// True if both readers returned the same sheets and values
+    pub fn is_identical(&self) -> bool {
+        self.sheet_mismatches.is_empty() && self.differences.is_empty()
+    }
+}
+
+impl fmt::Display for CompatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Compared {} cells in {} sheet(s): {} difference(s){}",
+            self.cells_compared,
+            self.sheets_compared.len(),
+            self.differences.len(),
+            if self.truncated { " (truncated)" } else { "" }
+        )?;
+        for sheet in &self.sheet_mismatches {
+            writeln!(f, "  sheet mismatch: {}", sheet)?;
+        }
+        for diff in &self.differences {
+            writeln!(f, "  {}", diff)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare every sheet of a workbook with default settings
+pub fn compare_with_calamine<P: AsRef<Path>>(path: P) -> Result<CompatReport> {
+    CalamineCompat::new(path).run()
+}
+
+/// Configurable comparison between StreamingReader and calamine
+///
+/// Rows are matched by the position reported by `StreamingReader::rows()`,
+/// so a reader that skips or merges rows shows up as differences too.
+pub struct CalamineCompat {
+    path: std::path::PathBuf,
+    sheets: Option<Vec<String>>,
+    max_differences: usize,
+    float_tolerance: f64,
+}
+
+impl CalamineCompat {
+    /// Prepare a comparison for the workbook at `path`
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            sheets: None,
+            max_differences: DEFAULT_MAX_DIFFERENCES,
+            float_tolerance: 1e-9,
+        }
+    }
+
+    /// Only compare the given sheet (can be called several times)
+    pub fn sheet(mut self, name: impl Into<String>) -> Self {
+        self.sheets.get_or_insert_with(Vec::new).push(name.into());
+        self
+    }
+
+    /// Stop collecting after `max` differences
+    pub fn max_differences(mut self, max: usize) -> Self {
+        self.max_differences = max;
+        self
+    }
+
+    /// Relative tolerance used when comparing numbers
+    pub fn float_tolerance(mut self, tolerance: f64) -> Self {
+        self.float_tolerance = tolerance;
+        self
+    }
+
+    /// Read the workbook with both readers and collect the differences
+    pub fn run(self) -> Result<CompatReport> {
+        let mut streaming = StreamingReader::open(&self.path)?;
+        let mut workbook = open_workbook_auto(&self.path)
+            .map_err(|e| ExcelError::CaliamineError(e.to_string()))?;
+
+        let streaming_sheets = streaming.sheet_names();
+        let calamine_sheets = workbook.sheet_names();
+
+        let mut report = CompatReport::default();
+        for name in &streaming_sheets {
+            if !calamine_sheets.contains(name) {
+                report
+                    .sheet_mismatches
+                    .push(format!("'{}' only found by StreamingReader", name));
+            }
+        }
+        for name in &calamine_sheets {
+            if !streaming_sheets.contains(name) {
+                report
+                    .sheet_mismatches
+                    .push(format!("'{}' only found by calamine", name));
+            }
+        }
+
+        let sheets = match &self.sheets {
+            Some(sheets) => sheets.clone(),
+            None => streaming_sheets
+                .iter()
+                .filter(|name| calamine_sheets.contains(name))
+                .cloned()
+                .collect(),
+        };
+
+        for sheet in sheets {
+            let range = workbook
+                .worksheet_range(&sheet)
+                .map_err(|e| ExcelError::CaliamineError(e.to_string()))?;
+            self.compare_sheet(&mut streaming, &sheet, &range, &mut report)?;
+            report.sheets_compared.push(sheet);
+        }
+
+        Ok(report)
+    }
+
+    fn compare_sheet(
+        &self,
+        streaming: &mut StreamingReader,
+        sheet: &str,
+        range: &Range<Data>,
+        report: &mut CompatReport,
+    ) -> Result<()> {
+        let (end_row, end_col) = range.end().unwrap_or((0, 0));
+        let calamine_rows = if range.is_empty() { 0 } else { end_row + 1 };
+        let calamine_cols = if range.is_empty() { 0 } else { end_col + 1 };
+
+        let mut seen_rows = 0u32;
+        for row in streaming.rows(sheet)? {
+            let row = row?;
+            seen_rows = row.index + 1;
+
+            let width = (row.cells.len() as u32).max(calamine_cols);
+            for col in 0..width {
+                let streaming_value = row.cells.get(col as usize).unwrap_or(&CellValue::Empty);
+                let calamine_value = calamine_cell(range, row.index, col);
+                self.record(
+                    report,
+                    sheet,
+                    row.index,
+                    col,
+                    streaming_value,
+                    calamine_value,
+                );
+            }
+        }
+
+        // Rows calamine found past the end of the streamed rows
+        for row in seen_rows..calamine_rows {
+            for col in 0..calamine_cols {
+                let calamine_value = calamine_cell(range, row, col);
+                self.record(report, sheet, row, col, &CellValue::Empty, calamine_value);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn record(
+        &self,
+        report: &mut CompatReport,
+        sheet: &str,
+        row: u32,
+        col: u32,
+        streaming: &CellValue,
+        calamine: CellValue,
+    ) {
+        report.cells_compared += 1;
+        if self.values_match(streaming, &calamine) {
+            return;
+        }
+
+        if report.differences.len() >= self.max_differences {
+            report.truncated = true;
+            return;
+        }
+
+        report.differences.push(CellDiff {
+            sheet: sheet.to_string(),
+            row,
+            col,
+            streaming: streaming.clone(),
+            calamine,
+        });
+    }
+
+    fn values_match(&self, a: &CellValue, b: &CellValue) -> bool {
+        if a == b {
+            return true;
+        }
+
+        match (numeric_value(a), numeric_value(b)) {
+            (Some(x), Some(y)) => (x - y).abs() <= self.float_tolerance * x.abs().max(1.0),
+            (None, None) => a.as_string() == b.as_string(),
+            // A number on one side and text on the other is a real difference
+            _ => false,
+        }
+    }
+}
+
+/// Numeric value of number-like cells; text is never treated as a number
+fn numeric_value(value: &CellValue) -> Option<f64> {
+    match value {
+        CellValue::Int(_) | CellValue::Float(_) | CellValue::DateTime(_) => value.as_f64(),
+        _ => None,
+    }
+}
+
+fn calamine_cell(range: &Range<Data>, row: u32, col: u32) -> CellValue {
+    range
+        .get_value((row, col))
+        .map(data_to_cell_value)
+        .unwrap_or(CellValue::Empty)
+}
+
+fn data_to_cell_value(data: &Data) -> CellValue {
+    match data {
+        Data::Empty => CellValue::Empty,
+        Data::String(s) => CellValue::String(s.clone()),
+        Data::Float(f) => CellValue::Float(*f),
+        Data::Int(i) => CellValue::Int(*i),
+        Data::Bool(b) => CellValue::Bool(*b),
+        Data::DateTime(d) => CellValue::DateTime(d.as_f64()),
+        Data::Error(e) => CellValue::Error(e.to_string()),
+        Data::DateTimeIso(s) => CellValue::String(s.clone()),
+        Data::DurationIso(s) => CellValue::String(s.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::ExcelWriter;
+
+    #[test]
+    fn test_identical_workbook() {
+        // calamine picks the format from the file extension
+        let temp = tempfile::Builder::new().suffix(".xlsx").tempfile().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_header(["Name", "Qty", "Price"]).unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::String("Widget".to_string()),
+                CellValue::Int(3),
+                CellValue::Float(9.99),
+            ])
+            .unwrap();
+        writer.save().unwrap();
+
+        let report = compare_with_calamine(temp.path()).unwrap();
+        assert_eq!(report.sheets_compared, vec!["Sheet1"]);
+        assert_eq!(report.cells_compared, 6);
+        assert!(report.is_identical(), "{}", report);
+    }
+
+    #[test]
+    fn test_values_match_rules() {
+        let compat = CalamineCompat::new("unused.xlsx");
+        assert!(compat.values_match(&CellValue::Int(3), &CellValue::Float(3.0)));
+        assert!(compat.values_match(&CellValue::Empty, &CellValue::String(String::new())));
+        assert!(!compat.values_match(&CellValue::String("3".to_string()), &CellValue::Int(3)));
+    }
+
+    #[test]
+    fn test_cell_diff_display() {
+        let diff = CellDiff {
+            sheet: "Data".to_string(),
+            row: 2,
+            col: 27,
+            streaming: CellValue::Int(1),
+            calamine: CellValue::Float(1.5),
+        };
+        assert_eq!(diff.cell_ref(), "AB3");
+        assert_eq!(
+            diff.to_string(),
+            "Data!AB3: streaming=Int(1) calamine=Float(1.5)"
+        );
+    }
+}
@@ -0,0 +1,812 @@
+//! Streaming converters between XLSX and CSV, in both directions
+//!
+//! Converters connect a reader and a writer row by row, so memory use does
+//! not grow with the size of the sheet.
+
+use crate::csv::CompressionMethod;
+use crate::csv_reader::CsvReader;
+use crate::csv_writer::CsvWriter;
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::UltraLowMemoryWorkbook;
+use crate::row_io::{copy, RowSink, RowSource};
+use crate::streaming_reader::StreamingReader;
+use crate::types::{CellStyle, CellValue, StyledCell, MAX_EXACT_EXCEL_INT};
+use chrono::{NaiveDate, NaiveDateTime};
+use std::path::Path;
+
+/// Which worksheet to convert
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SheetSelector {
+    /// Zero-based sheet index
+    Index(usize),
+    /// Sheet name
+    Name(String),
+}
+
+/// How date cells are written to CSV
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DateRendering {
+    /// ISO 8601: `2024-03-01`, or `2024-03-01 12:30:00` with a time (default)
+    #[default]
+    Iso,
+    /// chrono `strftime` pattern, e.g. `"%d/%m/%Y"`
+    Format(String),
+    /// Excel serial number, e.g. `45352`
+    Serial,
+}
+
+/// Streams one worksheet of an XLSX file into a CSV file
+///
+/// Cells are rendered the way Excel's own CSV export does: booleans as
+/// `TRUE` / `FALSE`, error cells as their code (`#N/A`), hyperlinks as their
+/// text. Date cells (the ISO dates reported by [`StreamingReader`]) follow
+/// [`DateRendering`]. The output is compressed according to its extension
+/// (`.csv.gz`, `.csv.zst`) unless [`Self::compression`] is set.
+///
+/// # Example
+/// ```no_run
+/// use excelstream::convert::{DateRendering, XlsxToCsvConverter};
+///
+/// let rows = XlsxToCsvConverter::new()
+///     .sheet("Orders")
+///     .delimiter(b';')
+///     .date_rendering(DateRendering::Format("%d/%m/%Y".to_string()))
+///     .convert("orders.xlsx", "orders.csv.gz")?;
+/// println!("{} rows exported", rows);
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct XlsxToCsvConverter {
+    sheet: SheetSelector,
+    delimiter: u8,
+    date_rendering: DateRendering,
+    compression: Option<(CompressionMethod, u32)>,
+    skip_empty_rows: bool,
+}
+
+impl Default for XlsxToCsvConverter {
+    fn default() -> Self {
+        XlsxToCsvConverter {
+            sheet: SheetSelector::Index(0),
+            delimiter: b',',
+            date_rendering: DateRendering::Iso,
+            compression: None,
+            skip_empty_rows: false,
+        }
+    }
+}
+
+impl XlsxToCsvConverter {
+    /// Converter for the first sheet with comma delimiter and ISO dates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert the sheet with this name
+    pub fn sheet(mut self, name: &str) -> Self {
+        self.sheet = SheetSelector::Name(name.to_string());
+        self
+    }
+
+    /// Convert the sheet at this zero-based index
+    pub fn sheet_index(mut self, index: usize) -> Self {
+        self.sheet = SheetSelector::Index(index);
+        self
+    }
+
+    /// Set the field delimiter (default: `,`)
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set how date cells are rendered (default: ISO 8601)
+    pub fn date_rendering(mut self, rendering: DateRendering) -> Self {
+        self.date_rendering = rendering;
+        self
+    }
+
+    /// Compress the output explicitly instead of by file extension
+    pub fn compression(mut self, method: CompressionMethod, level: u32) -> Self {
+        self.compression = Some((method, level));
+        self
+    }
+
+    /// Leave out blank rows (default: false), as
+    /// [`ReadOptions::skip_empty_rows`](crate::streaming_reader::ReadOptions::skip_empty_rows)
+    /// does
+    pub fn skip_empty_rows(mut self, skip: bool) -> Self {
+        self.skip_empty_rows = skip;
+        self
+    }
+
+    /// Convert `input` to `output`, returning the number of rows written
+    pub fn convert<P: AsRef<Path>, Q: AsRef<Path>>(&self, input: P, output: Q) -> Result<u64> {
+        let mut reader = StreamingReader::open(input)?;
+        let mut source = reader
+            .sheet_source(self.sheet.clone())?
+            .skip_empty_rows(self.skip_empty_rows);
+
+        let writer = match self.compression {
+            Some((method, level)) => CsvWriter::with_compression(output, method, level)?,
+            None => CsvWriter::new(output)?,
+        };
+        let sink = RenderedCsv {
+            converter: self,
+            writer: writer.delimiter(self.delimiter),
+            fields: Vec::new(),
+        };
+        copy(&mut source, sink)
+    }
+
+    /// Append the CSV text of `cell` to `out`
+    fn render(&self, cell: &CellValue, out: &mut String) -> Result<()> {
+        use std::fmt::Write;
+
+        match cell {
+            CellValue::String(s) => match parse_iso_datetime(s) {
+                Some(datetime) => self.render_date(datetime, s, out)?,
+                None => out.push_str(s),
+            },
+            CellValue::DateTime(serial) => match serial_to_datetime(*serial) {
+                Some(datetime) => {
+                    let iso = format_iso(datetime);
+                    self.render_date(datetime, &iso, out)?
+                }
+                None => {
+                    let _ = write!(out, "{}", serial);
+                }
+            },
+            CellValue::Bool(b) => out.push_str(if *b { "TRUE" } else { "FALSE" }),
+            CellValue::Error(code) => out.push_str(code),
+            CellValue::Hyperlink { text, .. } => out.push_str(text),
+            other => {
+                let _ = write!(out, "{}", other);
+            }
+        }
+        Ok(())
+    }
+
+    fn render_date(&self, datetime: NaiveDateTime, iso: &str, out: &mut String) -> Result<()> {
+        use std::fmt::Write;
+
+        match &self.date_rendering {
+            DateRendering::Iso => out.push_str(iso),
+            DateRendering::Format(pattern) => {
+                write!(out, "{}", datetime.format(pattern)).map_err(|_| {
+                    ExcelError::InvalidFormat(format!("Invalid date pattern '{}'", pattern))
+                })?;
+            }
+            DateRendering::Serial => {
+                let serial = datetime_to_serial(datetime);
+                let _ = write!(out, "{}", serial);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// CSV output of [`XlsxToCsvConverter`], rendering cells as it is set up to
+struct RenderedCsv<'a> {
+    converter: &'a XlsxToCsvConverter,
+    writer: CsvWriter,
+    // Reused between rows
+    fields: Vec<String>,
+}
+
+impl RowSink for RenderedCsv<'_> {
+    fn write_schema(&mut self, names: &[String]) -> Result<()> {
+        self.writer.write_row(names)
+    }
+
+    fn write_cells(&mut self, cells: &[CellValue]) -> Result<()> {
+        self.fields.resize_with(cells.len(), String::new);
+        for (field, cell) in self.fields.iter_mut().zip(cells) {
+            field.clear();
+            self.converter.render(cell, field)?;
+        }
+        self.writer.write_row(&self.fields)
+    }
+
+    fn finish(self) -> Result<()> {
+        self.writer.save()
+    }
+}
+
+/// Column type inferred by [`CsvToXlsxConverter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Whole numbers, written as numbers
+    Int,
+    /// Decimal numbers, written as numbers
+    Float,
+    /// `true` / `false` (any case), written as booleans
+    Bool,
+    /// `YYYY-MM-DD`, written as dates
+    Date,
+    /// `YYYY-MM-DD HH:MM:SS` (or with `T`), written as timestamps
+    DateTime,
+    /// Anything else, written as text
+    Text,
+}
+
+impl ColumnType {
+    /// Narrowest type that fits a single non-empty value
+    fn of(value: &str) -> ColumnType {
+        if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            return ColumnType::Bool;
+        }
+        let digits = value.strip_prefix('-').unwrap_or(value);
+        // Leading zeros (zip codes, ids) must stay text
+        let leading_zero = digits.len() > 1 && digits.starts_with('0');
+        if !leading_zero && value.parse::<i64>().is_ok() {
+            return ColumnType::Int;
+        }
+        let numeric_chars = value
+            .bytes()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'-' | b'+' | b'e' | b'E'));
+        if numeric_chars && !leading_zero && value.parse::<f64>().is_ok() {
+            return ColumnType::Float;
+        }
+        match parse_iso_datetime(value) {
+            Some(_) if value.len() == 10 => ColumnType::Date,
+            Some(_) => ColumnType::DateTime,
+            None => ColumnType::Text,
+        }
+    }
+
+    /// Narrowest type that fits a cell read from a sheet, `None` when empty
+    ///
+    /// Unlike CSV text, sheet cells already carry their type; only strings
+    /// are inspected, to recover the ISO dates the reader produces.
+    #[cfg(any(feature = "parquet-support", feature = "polars"))]
+    pub(crate) fn of_cell(cell: &CellValue) -> Option<ColumnType> {
+        let column_type = match cell {
+            CellValue::Empty => return None,
+            CellValue::String(s) if s.is_empty() => return None,
+            CellValue::Int(_) => ColumnType::Int,
+            CellValue::Float(_) => ColumnType::Float,
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(_) => ColumnType::Float,
+            CellValue::Bool(_) => ColumnType::Bool,
+            CellValue::DateTime(_) => ColumnType::DateTime,
+            CellValue::String(s) => match parse_iso_datetime(s) {
+                Some(_) if s.len() == 10 => ColumnType::Date,
+                Some(_) => ColumnType::DateTime,
+                None => ColumnType::Text,
+            },
+            _ => ColumnType::Text,
+        };
+        Some(column_type)
+    }
+
+    /// Type that fits values of both `self` and `other`
+    pub(crate) fn merge(self, other: ColumnType) -> ColumnType {
+        use ColumnType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Int, Float) | (Float, Int) => Float,
+            (Date, DateTime) | (DateTime, Date) => DateTime,
+            _ => Text,
+        }
+    }
+}
+
+/// Streams a CSV file into a typed XLSX worksheet
+///
+/// The first [`Self::sample_rows`] data rows are buffered to infer a
+/// [`ColumnType`] per column; every later row is written as it is read.
+/// Values that do not fit their column's type (e.g. text appearing after the
+/// sample window) are written as text. Compressed inputs (`.csv.gz`,
+/// `.csv.zst`) are detected by [`CsvReader`].
+///
+/// # Example
+/// ```no_run
+/// use excelstream::convert::{ColumnType, CsvToXlsxConverter};
+///
+/// let rows = CsvToXlsxConverter::new()
+///     .sheet_name("Orders")
+///     .sample_rows(500)
+///     .column_type(0, ColumnType::Text) // keep order ids as text
+///     .convert("orders.csv.gz", "orders.xlsx")?;
+/// println!("{} rows imported", rows);
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvToXlsxConverter {
+    delimiter: u8,
+    has_header: bool,
+    sample_rows: usize,
+    sheet_name: String,
+    compression_level: u32,
+    column_types: Vec<(usize, ColumnType)>,
+}
+
+impl Default for CsvToXlsxConverter {
+    fn default() -> Self {
+        CsvToXlsxConverter {
+            delimiter: b',',
+            has_header: true,
+            sample_rows: 1000,
+            sheet_name: "Sheet1".to_string(),
+            compression_level: 6,
+            column_types: Vec::new(),
+        }
+    }
+}
+
+impl CsvToXlsxConverter {
+    /// Converter for comma-separated input with a header row
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the field delimiter (default: `,`)
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Whether the first row is a header, written in bold (default: true)
+    pub fn has_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    /// Number of data rows used to infer column types (default: 1000)
+    pub fn sample_rows(mut self, rows: usize) -> Self {
+        self.sample_rows = rows.max(1);
+        self
+    }
+
+    /// Name of the worksheet (default: `Sheet1`)
+    pub fn sheet_name(mut self, name: &str) -> Self {
+        self.sheet_name = name.to_string();
+        self
+    }
+
+    /// XLSX compression level 0-9 (default: 6)
+    pub fn compression_level(mut self, level: u32) -> Self {
+        self.compression_level = level;
+        self
+    }
+
+    /// Force the type of the zero-based column `index` instead of inferring it
+    pub fn column_type(mut self, index: usize, column_type: ColumnType) -> Self {
+        self.column_types.retain(|(i, _)| *i != index);
+        self.column_types.push((index, column_type));
+        self
+    }
+
+    /// Infer the column types of `input` from the sample window
+    pub fn infer_types<P: AsRef<Path>>(&self, input: P) -> Result<Vec<ColumnType>> {
+        let mut reader = self.open(input)?;
+        if self.has_header {
+            reader.read_row()?;
+        }
+        let mut sample = Vec::new();
+        while sample.len() < self.sample_rows {
+            match reader.read_row()? {
+                Some(row) => sample.push(row),
+                None => break,
+            }
+        }
+        Ok(self.infer(&sample))
+    }
+
+    /// Convert `input` to `output`, returning the number of rows written
+    /// (including the header)
+    pub fn convert<P: AsRef<Path>, Q: AsRef<Path>>(&self, input: P, output: Q) -> Result<u64> {
+        let mut reader = self.open(input)?.has_header(self.has_header);
+        let header = reader.schema()?.is_some();
+
+        let mut workbook =
+            UltraLowMemoryWorkbook::with_compression(output, self.compression_level)?;
+        workbook.add_worksheet(&self.sheet_name)?;
+        let sink = TypedSheet {
+            converter: self,
+            workbook,
+            sample: Vec::new(),
+            types: None,
+            cells: Vec::new(),
+        };
+        Ok(copy(&mut reader, sink)? + u64::from(header))
+    }
+
+    fn open<P: AsRef<Path>>(&self, input: P) -> Result<CsvReader> {
+        Ok(CsvReader::open(input)?.delimiter(self.delimiter))
+    }
+
+    /// Column types for the sampled rows, with overrides applied
+    fn infer(&self, sample: &[Vec<String>]) -> Vec<ColumnType> {
+        let columns = sample.iter().map(Vec::len).max().unwrap_or(0);
+        let mut types: Vec<Option<ColumnType>> = vec![None; columns];
+        for row in sample {
+            for (slot, value) in types.iter_mut().zip(row) {
+                if value.is_empty() {
+                    continue;
+                }
+                let value_type = ColumnType::of(value);
+                *slot = Some(slot.map_or(value_type, |t| t.merge(value_type)));
+            }
+        }
+
+        let mut types: Vec<ColumnType> = types
+            .into_iter()
+            .map(|t| t.unwrap_or(ColumnType::Text))
+            .collect();
+        for &(index, column_type) in &self.column_types {
+            if index >= types.len() {
+                types.resize(index + 1, ColumnType::Text);
+            }
+            types[index] = column_type;
+        }
+        types
+    }
+}
+
+/// XLSX output of [`CsvToXlsxConverter`]
+///
+/// Rows are held back until the sample window is full (or the input ends),
+/// then written with the column types inferred from them.
+struct TypedSheet<'a> {
+    converter: &'a CsvToXlsxConverter,
+    workbook: UltraLowMemoryWorkbook,
+    sample: Vec<Vec<String>>,
+    types: Option<Vec<ColumnType>>,
+    // Reused between rows
+    cells: Vec<StyledCell>,
+}
+
+impl TypedSheet<'_> {
+    fn write_sample(&mut self) -> Result<()> {
+        let types = self.converter.infer(&self.sample);
+        for row in std::mem::take(&mut self.sample) {
+            write_typed(
+                &mut self.workbook,
+                &mut self.cells,
+                &types,
+                row.iter().map(String::as_str),
+            )?;
+        }
+        self.types = Some(types);
+        Ok(())
+    }
+}
+
+impl RowSink for TypedSheet<'_> {
+    fn write_schema(&mut self, names: &[String]) -> Result<()> {
+        let cells: Vec<StyledCell> = names
+            .iter()
+            .map(|name| StyledCell::new(CellValue::String(name.clone()), CellStyle::HeaderBold))
+            .collect();
+        self.workbook.write_row_styled(&cells)
+    }
+
+    fn write_cells(&mut self, cells: &[CellValue]) -> Result<()> {
+        // CSV fields only come as text or empty
+        let fields = cells.iter().map(|cell| match cell {
+            CellValue::String(s) => s.as_str(),
+            _ => "",
+        });
+        match &self.types {
+            Some(types) => write_typed(&mut self.workbook, &mut self.cells, types, fields),
+            None => {
+                self.sample.push(fields.map(str::to_string).collect());
+                if self.sample.len() >= self.converter.sample_rows {
+                    self.write_sample()?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(mut self) -> Result<()> {
+        if self.types.is_none() {
+            self.write_sample()?;
+        }
+        self.workbook.close()
+    }
+}
+
+/// Write one row of CSV fields as cells of the column `types`
+fn write_typed<'a>(
+    workbook: &mut UltraLowMemoryWorkbook,
+    cells: &mut Vec<StyledCell>,
+    types: &[ColumnType],
+    fields: impl Iterator<Item = &'a str>,
+) -> Result<()> {
+    cells.clear();
+    cells.extend(
+        fields
+            .enumerate()
+            .map(|(i, value)| typed_cell(value, types.get(i).copied().unwrap_or(ColumnType::Text))),
+    );
+    workbook.write_row_styled(cells)
+}
+
+/// Cell for `value` in a column of type `column_type`, falling back to text
+fn typed_cell(value: &str, column_type: ColumnType) -> StyledCell {
+    let text =
+        |value: &str| StyledCell::new(CellValue::String(value.to_string()), CellStyle::Default);
+    if value.is_empty() {
+        return StyledCell::new(CellValue::Empty, CellStyle::Default);
+    }
+
+    match column_type {
+        ColumnType::Int => match value.parse::<i64>() {
+            Ok(n) if n.abs() <= MAX_EXACT_EXCEL_INT => {
+                StyledCell::new(CellValue::Int(n), CellStyle::Default)
+            }
+            Ok(_) => StyledCell::new(CellValue::BigInt(value.to_string()), CellStyle::Default),
+            Err(_) => text(value),
+        },
+        ColumnType::Float => match value.parse::<f64>() {
+            Ok(f) if f.is_finite() => StyledCell::new(CellValue::Float(f), CellStyle::Default),
+            _ => text(value),
+        },
+        ColumnType::Bool => {
+            if value.eq_ignore_ascii_case("true") {
+                StyledCell::new(CellValue::Bool(true), CellStyle::Default)
+            } else if value.eq_ignore_ascii_case("false") {
+                StyledCell::new(CellValue::Bool(false), CellStyle::Default)
+            } else {
+                text(value)
+            }
+        }
+        ColumnType::Date | ColumnType::DateTime => match parse_iso_datetime(value) {
+            Some(datetime) => {
+                let style = if column_type == ColumnType::Date {
+                    CellStyle::DateDefault
+                } else {
+                    CellStyle::DateTimestamp
+                };
+                StyledCell::new(CellValue::DateTime(datetime_to_serial(datetime)), style)
+            }
+            None => text(value),
+        },
+        ColumnType::Text => text(value),
+    }
+}
+
+fn excel_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1899, 12, 30)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("valid epoch")
+}
+
+pub(crate) fn datetime_to_serial(datetime: NaiveDateTime) -> f64 {
+    (datetime - excel_epoch()).num_seconds() as f64 / 86_400.0
+}
+
+pub(crate) fn serial_to_datetime(serial: f64) -> Option<NaiveDateTime> {
+    let seconds = (serial * 86_400.0).round();
+    if !(0.0..=253_402_300_799.0).contains(&seconds) {
+        return None;
+    }
+    excel_epoch().checked_add_signed(chrono::Duration::try_seconds(seconds as i64)?)
+}
+
+pub(crate) fn format_iso(datetime: NaiveDateTime) -> String {
+    if datetime.time() == chrono::NaiveTime::MIN {
+        datetime.format("%Y-%m-%d").to_string()
+    } else {
+        datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+    }
+}
+
+/// Recognize `YYYY-MM-DD` / `YYYY-MM-DD HH:MM:SS` text, as produced by the
+/// reader for date cells (a `T` separator is accepted too)
+pub(crate) fn parse_iso_datetime(s: &str) -> Option<NaiveDateTime> {
+    let bytes = s.as_bytes();
+    if bytes.len() < 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    match bytes.len() {
+        10 => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0),
+        19 if bytes[10] == b'T' => NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").ok(),
+        19 => NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").ok(),
+        _ => None,
+    }
+}
+
+/// Date/time of a date cell, read back either as an ISO string or a serial
+#[cfg(any(feature = "parquet-support", feature = "polars"))]
+pub(crate) fn cell_datetime(cell: &CellValue) -> Option<NaiveDateTime> {
+    match cell {
+        CellValue::String(s) => parse_iso_datetime(s),
+        CellValue::DateTime(serial) => serial_to_datetime(*serial),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::ExcelWriter;
+    use tempfile::TempDir;
+
+    fn write_sample(path: &Path) {
+        let mut writer = ExcelWriter::new(path).unwrap();
+        writer
+            .write_header(["Name", "Joined", "Active", "Score"])
+            .unwrap();
+        writer
+            .write_row_styled(&[
+                (
+                    CellValue::String("Doe, Jane".to_string()),
+                    CellStyle::Default,
+                ),
+                (CellValue::DateTime(45352.0), CellStyle::DateDefault),
+                (CellValue::Bool(true), CellStyle::Default),
+                (CellValue::Float(9.5), CellStyle::Default),
+            ])
+            .unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::String("Bob".to_string()),
+                CellValue::Empty,
+                CellValue::Bool(false),
+                CellValue::Error("#N/A".to_string()),
+            ])
+            .unwrap();
+        writer.add_sheet("Other").unwrap();
+        writer.write_row(["x", "y"]).unwrap();
+        writer.save().unwrap();
+    }
+
+    #[test]
+    fn test_xlsx_to_csv() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in.xlsx");
+        write_sample(&input);
+
+        let output = dir.path().join("out.csv");
+        let rows = XlsxToCsvConverter::new().convert(&input, &output).unwrap();
+        assert_eq!(rows, 3);
+        assert_eq!(
+            std::fs::read_to_string(&output).unwrap(),
+            "Name,Joined,Active,Score\n\"Doe, Jane\",2024-03-01,TRUE,9.5\nBob,,FALSE,#N/A\n"
+        );
+
+        let rows = XlsxToCsvConverter::new()
+            .sheet("Other")
+            .delimiter(b';')
+            .convert(&input, &output)
+            .unwrap();
+        assert_eq!(rows, 1);
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "x;y\n");
+    }
+
+    #[test]
+    fn test_xlsx_to_csv_date_rendering() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in.xlsx");
+        write_sample(&input);
+        let output = dir.path().join("out.csv");
+
+        XlsxToCsvConverter::new()
+            .date_rendering(DateRendering::Format("%d/%m/%Y".to_string()))
+            .convert(&input, &output)
+            .unwrap();
+        let csv = std::fs::read_to_string(&output).unwrap();
+        assert!(csv.contains(",01/03/2024,"), "{}", csv);
+
+        XlsxToCsvConverter::new()
+            .date_rendering(DateRendering::Serial)
+            .convert(&input, &output)
+            .unwrap();
+        let csv = std::fs::read_to_string(&output).unwrap();
+        assert!(csv.contains(",45352,"), "{}", csv);
+    }
+
+    #[test]
+    fn test_xlsx_to_csv_compressed_and_missing_sheet() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in.xlsx");
+        write_sample(&input);
+
+        let output = dir.path().join("out.csv.zst");
+        XlsxToCsvConverter::new().convert(&input, &output).unwrap();
+        let mut reader = crate::csv_reader::CsvReader::open(&output).unwrap();
+        assert_eq!(reader.rows().count(), 3);
+
+        assert!(XlsxToCsvConverter::new()
+            .sheet_index(5)
+            .convert(&input, dir.path().join("missing.csv"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_iso_datetime() {
+        assert!(parse_iso_datetime("2024-03-01").is_some());
+        assert!(parse_iso_datetime("2024-03-01 12:30:00").is_some());
+        assert!(parse_iso_datetime("2024-13-01").is_none());
+        assert!(parse_iso_datetime("Hello world").is_none());
+        assert_eq!(
+            serial_to_datetime(45352.5).map(format_iso).as_deref(),
+            Some("2024-03-01 12:00:00")
+        );
+    }
+
+    #[test]
+    fn test_column_type_inference() {
+        let converter = CsvToXlsxConverter::new().column_type(5, ColumnType::Text);
+        let rows: Vec<Vec<String>> = [
+            ["1", "1.5", "true", "2024-03-01", "00123", "7", ""],
+            ["2", "2", "FALSE", "2024-03-01 08:30:00", "00456", "8", ""],
+            ["-3", "", "", "2024-03-02", "789", "9", ""],
+        ]
+        .iter()
+        .map(|row| row.iter().map(|v| v.to_string()).collect())
+        .collect();
+
+        assert_eq!(
+            converter.infer(&rows),
+            vec![
+                ColumnType::Int,
+                ColumnType::Float,
+                ColumnType::Bool,
+                ColumnType::DateTime,
+                ColumnType::Text,
+                ColumnType::Text,
+                ColumnType::Text,
+            ]
+        );
+        assert_eq!(ColumnType::of("1e3"), ColumnType::Float);
+        assert_eq!(ColumnType::of("inf"), ColumnType::Text);
+        assert_eq!(ColumnType::of("2024-03-01T08:30:00"), ColumnType::DateTime);
+    }
+
+    #[test]
+    fn test_csv_to_xlsx_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in.csv");
+        std::fs::write(
+            &input,
+            "id;price;active;joined;zip\n1;9.99;true;2024-03-01;00123\n2;10;false;2024-03-02;04567\n3;n/a;true;2024-03-03;99999\n",
+        )
+        .unwrap();
+
+        let converter = CsvToXlsxConverter::new()
+            .delimiter(b';')
+            .sample_rows(2)
+            .sheet_name("Import");
+        assert_eq!(
+            converter.infer_types(&input).unwrap(),
+            vec![
+                ColumnType::Int,
+                ColumnType::Float,
+                ColumnType::Bool,
+                ColumnType::Date,
+                ColumnType::Text
+            ]
+        );
+
+        let output = dir.path().join("out.xlsx");
+        assert_eq!(converter.convert(&input, &output).unwrap(), 4);
+
+        let mut reader = StreamingReader::open(&output).unwrap();
+        assert_eq!(reader.sheet_names(), vec!["Import"]);
+        let rows: Vec<_> = reader
+            .rows("Import")
+            .unwrap()
+            .map(|row| row.unwrap().cells)
+            .collect();
+        assert_eq!(rows[0][0], CellValue::String("id".to_string()));
+        assert_eq!(
+            rows[1],
+            vec![
+                CellValue::Int(1),
+                CellValue::Float(9.99),
+                CellValue::Bool(true),
+                CellValue::String("2024-03-01".to_string()),
+                CellValue::String("00123".to_string()),
+            ]
+        );
+        // "n/a" appears after the sample window and is kept as text
+        assert_eq!(rows[3][1], CellValue::String("n/a".to_string()));
+    }
+}
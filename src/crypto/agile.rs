@@ -0,0 +1,475 @@
+//! ECMA-376 Agile Encryption (MS-OFFCRYPTO 2.3.4.10 – 2.3.4.15)
+//!
+//! A random 256-bit secret key encrypts the package in 4096-byte segments.
+//! The secret key itself is stored encrypted with a key derived from the
+//! password by 100 000 rounds of SHA-512, together with a verifier that lets
+//! readers reject a wrong password, and an HMAC over the encrypted package.
+//!
+//! Files are written with AES-256 and SHA-512; reading accepts the other key
+//! sizes and hash algorithms Office has used. The HMAC is not checked on read.
+
+use super::cfb::{CfbWriter, Entry};
+use super::{
+    aes_cbc_decrypt, aes_cbc_encrypt, base64_decode, base64_encode, password_hash, random_bytes,
+    read_full, HashAlgorithm,
+};
+use crate::error::{ExcelError, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const HASH: HashAlgorithm = HashAlgorithm::Sha512;
+const SPIN_COUNT: u32 = 100_000;
+const KEY_BYTES: usize = 32;
+const BLOCK: usize = 16;
+const SEGMENT: usize = 4096;
+
+const BLOCK_VERIFIER_INPUT: [u8; 8] = [0xfe, 0xa7, 0xd2, 0x76, 0x3b, 0x4b, 0x9e, 0x79];
+const BLOCK_VERIFIER_VALUE: [u8; 8] = [0xd7, 0xaa, 0x0f, 0x6d, 0x30, 0x61, 0x34, 0x4e];
+const BLOCK_KEY_VALUE: [u8; 8] = [0x14, 0x6e, 0x0b, 0xe7, 0xab, 0xac, 0xd0, 0xd6];
+const BLOCK_HMAC_KEY: [u8; 8] = [0x5f, 0xb2, 0xad, 0x01, 0x0c, 0xb9, 0xe1, 0xf6];
+const BLOCK_HMAC_VALUE: [u8; 8] = [0xa0, 0x67, 0x7f, 0x02, 0xb2, 0x2c, 0x84, 0x33];
+
+const ENCRYPTION_INFO: &str = "EncryptionInfo";
+const ENCRYPTED_PACKAGE: &str = "EncryptedPackage";
+
+/// Encrypt the OOXML package at `plain` into a compound file written to
+/// `output`
+pub(crate) fn encrypt_package<W: Write>(plain: &Path, output: W, password: &str) -> Result<W> {
+    let mut input = File::open(plain)?;
+    let package_len = input.metadata()?.len();
+
+    let key_salt: [u8; 16] = random_bytes()?;
+    let password_salt: [u8; 16] = random_bytes()?;
+    let secret_key: [u8; KEY_BYTES] = random_bytes()?;
+    let verifier_input: [u8; 16] = random_bytes()?;
+    let hmac_key: [u8; 64] = random_bytes()?;
+
+    // Password key encryptor
+    let hash = password_hash(HASH, password, &password_salt, SPIN_COUNT);
+    let mut encrypted_verifier_input = verifier_input;
+    aes_cbc_encrypt(
+        &derive_key(HASH, &hash, &BLOCK_VERIFIER_INPUT, KEY_BYTES),
+        &password_salt,
+        &mut encrypted_verifier_input,
+    )?;
+    let mut encrypted_verifier_value = HASH.hash(&[&verifier_input]);
+    aes_cbc_encrypt(
+        &derive_key(HASH, &hash, &BLOCK_VERIFIER_VALUE, KEY_BYTES),
+        &password_salt,
+        &mut encrypted_verifier_value,
+    )?;
+    let mut encrypted_key = secret_key;
+    aes_cbc_encrypt(
+        &derive_key(HASH, &hash, &BLOCK_KEY_VALUE, KEY_BYTES),
+        &password_salt,
+        &mut encrypted_key,
+    )?;
+
+    let mut encrypted_hmac_key = hmac_key;
+    aes_cbc_encrypt(
+        &secret_key,
+        &block_iv(HASH, &key_salt, &BLOCK_HMAC_KEY, BLOCK),
+        &mut encrypted_hmac_key,
+    )?;
+
+    let info = |encrypted_hmac_value: &[u8]| {
+        encryption_info(&[
+            ("keySalt", &key_salt[..]),
+            ("hmacKey", &encrypted_hmac_key[..]),
+            ("hmacValue", encrypted_hmac_value),
+            ("passwordSalt", &password_salt[..]),
+            ("verifierInput", &encrypted_verifier_input[..]),
+            ("verifierValue", &encrypted_verifier_value[..]),
+            ("keyValue", &encrypted_key[..]),
+        ])
+    };
+    // Base64 of a fixed-size value has a fixed length, so the stream size
+    // is known before the HMAC is
+    let info_len = info(&[0u8; 64]).len() as u64;
+
+    let encrypted_len = 8 + package_len.div_ceil(BLOCK as u64) * BLOCK as u64;
+    let mut entries = data_spaces();
+    entries.push(Entry::Stream(ENCRYPTION_INFO.to_string(), info_len));
+    entries.push(Entry::Stream(ENCRYPTED_PACKAGE.to_string(), encrypted_len));
+    let mut cfb = CfbWriter::new(output, entries)?;
+
+    let mut mac =
+        <Hmac<Sha512> as Mac>::new_from_slice(&hmac_key).expect("HMAC accepts keys of any length");
+    let size = package_len.to_le_bytes();
+    mac.update(&size);
+    cfb.write(ENCRYPTED_PACKAGE, &size)?;
+
+    let mut segment = vec![0u8; SEGMENT];
+    let mut index = 0u32;
+    loop {
+        let n = read_full(&mut input, &mut segment)?;
+        if n == 0 {
+            break;
+        }
+        let len = n.div_ceil(BLOCK) * BLOCK;
+        segment[n..len].fill(0);
+        aes_cbc_encrypt(
+            &secret_key,
+            &block_iv(HASH, &key_salt, &index.to_le_bytes(), BLOCK),
+            &mut segment[..len],
+        )?;
+        mac.update(&segment[..len]);
+        cfb.write(ENCRYPTED_PACKAGE, &segment[..len])?;
+        index += 1;
+    }
+
+    let mut encrypted_hmac_value = mac.finalize().into_bytes().to_vec();
+    aes_cbc_encrypt(
+        &secret_key,
+        &block_iv(HASH, &key_salt, &BLOCK_HMAC_VALUE, BLOCK),
+        &mut encrypted_hmac_value,
+    )?;
+    cfb.write(ENCRYPTION_INFO, &info(&encrypted_hmac_value))?;
+    for (path, data) in data_space_streams() {
+        cfb.write(path, &data)?;
+    }
+    cfb.finish()
+}
+
+/// Decrypt an agile `EncryptedPackage` described by the `EncryptionInfo` XML
+pub(crate) fn decrypt_package(
+    xml: &[u8],
+    password: &str,
+    package: &mut impl Read,
+    output: &mut impl Write,
+) -> Result<()> {
+    let xml = std::str::from_utf8(xml)
+        .map_err(|_| ExcelError::InvalidFormat("EncryptionInfo is not UTF-8".to_string()))?;
+    let key_data = element(xml, "keyData", "saltValue")
+        .ok_or_else(|| missing("keyData"))
+        .and_then(CipherParams::parse)?;
+    // Certificate key encryptors have no spin count
+    let encryptor = element(xml, "encryptedKey", "spinCount").ok_or_else(|| {
+        ExcelError::NotSupported("Workbook is not encrypted with a password".to_string())
+    })?;
+    let key_params = CipherParams::parse(encryptor)?;
+    let spin_count: u32 = number(encryptor, "spinCount")?;
+
+    let hash = password_hash(key_params.hash, password, &key_params.salt, spin_count);
+    let iv = padded(&key_params.salt, key_params.block_size);
+    let decrypt = |block_key: &[u8], name: &str| -> Result<Vec<u8>> {
+        let mut value = base64_decode(attr(encryptor, name).ok_or_else(|| missing(name))?)?;
+        let key = derive_key(key_params.hash, &hash, block_key, key_params.key_bytes);
+        aes_cbc_decrypt(&key, &iv, &mut value)?;
+        Ok(value)
+    };
+
+    let mut verifier_input = decrypt(&BLOCK_VERIFIER_INPUT, "encryptedVerifierHashInput")?;
+    verifier_input.truncate(key_params.salt.len());
+    let expected = key_params.hash.hash(&[&verifier_input]);
+    let verifier_value = decrypt(&BLOCK_VERIFIER_VALUE, "encryptedVerifierHashValue")?;
+    if verifier_value.get(..expected.len()) != Some(&expected[..]) {
+        return Err(ExcelError::ReadError("Incorrect password".to_string()));
+    }
+    let mut secret_key = decrypt(&BLOCK_KEY_VALUE, "encryptedKeyValue")?;
+    secret_key.truncate(key_data.key_bytes);
+
+    let mut size = [0u8; 8];
+    package.read_exact(&mut size)?;
+    let mut remaining = u64::from_le_bytes(size);
+    let mut segment = vec![0u8; SEGMENT];
+    let mut index = 0u32;
+    while remaining > 0 {
+        let n = read_full(package, &mut segment)?;
+        let len = n - n % BLOCK;
+        if len == 0 {
+            return Err(ExcelError::InvalidFormat(
+                "Encrypted package is truncated".to_string(),
+            ));
+        }
+        let iv = block_iv(
+            key_data.hash,
+            &key_data.salt,
+            &index.to_le_bytes(),
+            key_data.block_size,
+        );
+        aes_cbc_decrypt(&secret_key, &iv, &mut segment[..len])?;
+        let take = remaining.min(len as u64) as usize;
+        output.write_all(&segment[..take])?;
+        remaining -= take as u64;
+        index += 1;
+    }
+    Ok(())
+}
+
+/// Cipher settings shared by `keyData` and `encryptedKey`
+struct CipherParams {
+    salt: Vec<u8>,
+    block_size: usize,
+    key_bytes: usize,
+    hash: HashAlgorithm,
+}
+
+impl CipherParams {
+    fn parse(element: &str) -> Result<Self> {
+        for (name, expected) in [
+            ("cipherAlgorithm", "AES"),
+            ("cipherChaining", "ChainingModeCBC"),
+        ] {
+            let value = attr(element, name).ok_or_else(|| missing(name))?;
+            if value != expected {
+                return Err(ExcelError::NotSupported(format!("{} '{}'", name, value)));
+            }
+        }
+        Ok(CipherParams {
+            salt: base64_decode(attr(element, "saltValue").ok_or_else(|| missing("saltValue"))?)?,
+            block_size: number(element, "blockSize")?,
+            key_bytes: number::<usize>(element, "keyBits")? / 8,
+            hash: HashAlgorithm::from_name(
+                attr(element, "hashAlgorithm").ok_or_else(|| missing("hashAlgorithm"))?,
+            )?,
+        })
+    }
+}
+
+/// Attribute text of the first element named `name` (any prefix) carrying `required`
+fn element<'a>(xml: &'a str, name: &str, required: &str) -> Option<&'a str> {
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        let tag = &rest[start + 1..];
+        let end = tag.find('>')?;
+        let body = &tag[..end];
+        let qname = body
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        if qname.rsplit(':').next() == Some(name) && attr(body, required).is_some() {
+            return Some(body);
+        }
+        rest = &tag[end..];
+    }
+    None
+}
+
+fn attr<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{}=\"", name);
+    let mut offset = 0;
+    while let Some(i) = element[offset..].find(&pattern) {
+        let at = offset + i;
+        if element[..at].ends_with(char::is_whitespace) {
+            let value = &element[at + pattern.len()..];
+            return value.find('"').map(|end| &value[..end]);
+        }
+        offset = at + pattern.len();
+    }
+    None
+}
+
+fn number<T: std::str::FromStr>(element: &str, name: &str) -> Result<T> {
+    attr(element, name)
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| missing(name))
+}
+
+fn missing(name: &str) -> ExcelError {
+    ExcelError::InvalidFormat(format!("EncryptionInfo has no valid '{}'", name))
+}
+
+/// `value` truncated, or padded with 0x36, to `len` bytes
+fn padded(value: &[u8], len: usize) -> Vec<u8> {
+    let mut value = value.to_vec();
+    value.resize(len, 0x36);
+    value
+}
+
+/// Key for one of the password key encryptor's values: H(hash + blockKey)
+fn derive_key(
+    algorithm: HashAlgorithm,
+    hash: &[u8],
+    block_key: &[u8],
+    key_bytes: usize,
+) -> Vec<u8> {
+    padded(&algorithm.hash(&[hash, block_key]), key_bytes)
+}
+
+/// IV for a data segment or integrity value: H(keySalt + blockKey)
+fn block_iv(
+    algorithm: HashAlgorithm,
+    key_salt: &[u8],
+    block_key: &[u8],
+    block_size: usize,
+) -> Vec<u8> {
+    padded(&algorithm.hash(&[key_salt, block_key]), block_size)
+}
+
+/// `EncryptionInfo` stream: version 4.4, flags, then the XML descriptor
+fn encryption_info(values: &[(&str, &[u8])]) -> Vec<u8> {
+    let value = |name: &str| {
+        values
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| base64_encode(v))
+            .unwrap_or_default()
+    };
+    let params = r#"saltSize="16" blockSize="16" keyBits="256" hashSize="64" cipherAlgorithm="AES" cipherChaining="ChainingModeCBC" hashAlgorithm="SHA512""#;
+
+    let xml = format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\r\n",
+            "<encryption xmlns=\"http://schemas.microsoft.com/office/2006/encryption\" ",
+            "xmlns:p=\"http://schemas.microsoft.com/office/2006/keyEncryptor/password\" ",
+            "xmlns:c=\"http://schemas.microsoft.com/office/2006/keyEncryptor/certificate\">",
+            "<keyData {params} saltValue=\"{key_salt}\"/>",
+            "<dataIntegrity encryptedHmacKey=\"{hmac_key}\" encryptedHmacValue=\"{hmac_value}\"/>",
+            "<keyEncryptors><keyEncryptor uri=\"http://schemas.microsoft.com/office/2006/keyEncryptor/password\">",
+            "<p:encryptedKey spinCount=\"{spin}\" {params} saltValue=\"{password_salt}\" ",
+            "encryptedVerifierHashInput=\"{verifier_input}\" ",
+            "encryptedVerifierHashValue=\"{verifier_value}\" ",
+            "encryptedKeyValue=\"{key_value}\"/>",
+            "</keyEncryptor></keyEncryptors></encryption>"
+        ),
+        params = params,
+        key_salt = value("keySalt"),
+        hmac_key = value("hmacKey"),
+        hmac_value = value("hmacValue"),
+        spin = SPIN_COUNT,
+        password_salt = value("passwordSalt"),
+        verifier_input = value("verifierInput"),
+        verifier_value = value("verifierValue"),
+        key_value = value("keyValue"),
+    );
+
+    let mut info = Vec::with_capacity(8 + xml.len());
+    info.extend_from_slice(&4u16.to_le_bytes());
+    info.extend_from_slice(&4u16.to_le_bytes());
+    info.extend_from_slice(&0x40u32.to_le_bytes());
+    info.extend_from_slice(xml.as_bytes());
+    info
+}
+
+/// Length-prefixed UTF-16 string padded to 4 bytes (UNICODE-LP-P4)
+fn lp_p4(out: &mut Vec<u8>, s: &str) {
+    let units: Vec<u16> = s.encode_utf16().collect();
+    out.extend_from_slice(&(units.len() as u32 * 2).to_le_bytes());
+    for unit in &units {
+        out.extend_from_slice(&unit.to_le_bytes());
+    }
+    if units.len() % 2 == 1 {
+        out.extend_from_slice(&[0, 0]);
+    }
+}
+
+const DATA_SPACE: &str = "StrongEncryptionDataSpace";
+const TRANSFORM: &str = "StrongEncryptionTransform";
+
+/// `\x06DataSpaces` storage declaring the package as encrypted (MS-OFFCRYPTO 2.1)
+fn data_space_streams() -> Vec<(&'static str, Vec<u8>)> {
+    let version_1_0 = [1u8, 0, 0, 0];
+
+    let mut version = Vec::new();
+    lp_p4(&mut version, "Microsoft.Container.DataSpaces");
+    for _ in 0..3 {
+        version.extend_from_slice(&version_1_0);
+    }
+
+    let mut entry = Vec::new();
+    entry.extend_from_slice(&1u32.to_le_bytes()); // reference components
+    entry.extend_from_slice(&0u32.to_le_bytes()); // stream
+    lp_p4(&mut entry, ENCRYPTED_PACKAGE);
+    lp_p4(&mut entry, DATA_SPACE);
+    let mut map = Vec::new();
+    map.extend_from_slice(&8u32.to_le_bytes()); // header length
+    map.extend_from_slice(&1u32.to_le_bytes()); // entries
+    map.extend_from_slice(&(entry.len() as u32 + 4).to_le_bytes());
+    map.extend_from_slice(&entry);
+
+    let mut definition = Vec::new();
+    definition.extend_from_slice(&8u32.to_le_bytes()); // header length
+    definition.extend_from_slice(&1u32.to_le_bytes()); // transforms
+    lp_p4(&mut definition, TRANSFORM);
+
+    let mut id = Vec::new();
+    lp_p4(&mut id, "{FF9A3F03-56EF-4613-BDD5-5A41C1D07246}");
+    let mut primary = Vec::new();
+    primary.extend_from_slice(&(8 + id.len() as u32).to_le_bytes());
+    primary.extend_from_slice(&1u32.to_le_bytes()); // transform type
+    primary.extend_from_slice(&id);
+    lp_p4(&mut primary, "Microsoft.Container.EncryptionTransform");
+    for _ in 0..3 {
+        primary.extend_from_slice(&version_1_0);
+    }
+    primary.extend_from_slice(&0u32.to_le_bytes()); // encryption name (empty)
+    primary.extend_from_slice(&0u32.to_le_bytes()); // block size
+    primary.extend_from_slice(&0u32.to_le_bytes()); // cipher mode
+    primary.extend_from_slice(&4u32.to_le_bytes()); // reserved
+
+    vec![
+        ("\u{6}DataSpaces/Version", version),
+        ("\u{6}DataSpaces/DataSpaceMap", map),
+        (
+            "\u{6}DataSpaces/DataSpaceInfo/StrongEncryptionDataSpace",
+            definition,
+        ),
+        (
+            "\u{6}DataSpaces/TransformInfo/StrongEncryptionTransform/\u{6}Primary",
+            primary,
+        ),
+    ]
+}
+
+fn data_spaces() -> Vec<Entry> {
+    let streams = data_space_streams();
+    let size = |i: usize| streams[i].1.len() as u64;
+    vec![Entry::Storage(
+        "\u{6}DataSpaces".to_string(),
+        vec![
+            Entry::Stream("Version".to_string(), size(0)),
+            Entry::Stream("DataSpaceMap".to_string(), size(1)),
+            Entry::Storage(
+                "DataSpaceInfo".to_string(),
+                vec![Entry::Stream(DATA_SPACE.to_string(), size(2))],
+            ),
+            Entry::Storage(
+                "TransformInfo".to_string(),
+                vec![Entry::Storage(
+                    TRANSFORM.to_string(),
+                    vec![Entry::Stream("\u{6}Primary".to_string(), size(3))],
+                )],
+            ),
+        ],
+    )]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_space_sizes() {
+        let streams = data_space_streams();
+        // DataSpaceMap entry: length, count, type, "EncryptedPackage", data space name
+        assert_eq!(streams[1].1.len(), 8 + 4 + 4 + 4 + (4 + 32) + (4 + 52));
+        // TransformInfoHeader length covers everything before the transform name
+        assert_eq!(&streams[3].1[..4], &88u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_find_attributes() {
+        let xml = r#"<encryption><keyData saltValue="a" hashSize="64"/><c:encryptedKey saltValue="b"/><p:encryptedKey spinCount="10" saltValue="c" keyBits="128"/></encryption>"#;
+        let key = element(xml, "encryptedKey", "spinCount").unwrap();
+        assert_eq!(attr(key, "saltValue"), Some("c"));
+        assert_eq!(number::<usize>(key, "keyBits").unwrap(), 128);
+        assert_eq!(attr(key, "Bits"), None);
+        assert_eq!(
+            attr(element(xml, "keyData", "saltValue").unwrap(), "hashSize"),
+            Some("64")
+        );
+    }
+
+    #[test]
+    fn test_encryption_info_header() {
+        let info = encryption_info(&[("keySalt", &[0u8; 16][..])]);
+        assert_eq!(&info[..8], &[4, 0, 4, 0, 0x40, 0, 0, 0]);
+        let xml = std::str::from_utf8(&info[8..]).unwrap();
+        assert!(xml.contains(r#"<keyData saltSize="16""#));
+        assert!(xml.contains(r#"saltValue="AAAAAAAAAAAAAAAAAAAAAA==""#));
+    }
+}
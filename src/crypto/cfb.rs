@@ -0,0 +1,925 @@
+//! Compound File Binary (OLE2) container reader and writer
+//!
+//! Encrypted OOXML packages are stored as streams of a version 3 compound
+//! file (512-byte sectors). The layout is computed up front from the stream
+//! sizes so the header can be written first and large streams can be passed
+//! through without buffering:
+//!
+//! ```text
+//! header | large streams | mini stream | directory | mini FAT | FAT | DIFAT
+//! ```
+//!
+//! Streams under 4096 bytes live in the mini stream and are buffered until
+//! [`CfbWriter::finish`].
+//!
+//! [`CfbReader`] accepts both 512- and 4096-byte sector files.
+
+use crate::error::{ExcelError, Result};
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+pub(crate) const SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+const SECTOR: u64 = 512;
+const MINI_SECTOR: u64 = 64;
+const MINI_CUTOFF: u64 = 4096;
+/// FAT entries per sector
+const PER_SECTOR: u64 = SECTOR / 4;
+/// FAT sector ids held by the header
+const HEADER_DIFAT: u64 = 109;
+
+const FREESECT: u32 = 0xFFFF_FFFF;
+const ENDOFCHAIN: u32 = 0xFFFF_FFFE;
+const FATSECT: u32 = 0xFFFF_FFFD;
+const DIFSECT: u32 = 0xFFFF_FFFC;
+const NOSTREAM: u32 = 0xFFFF_FFFF;
+
+const TYPE_STORAGE: u8 = 1;
+const TYPE_STREAM: u8 = 2;
+const TYPE_ROOT: u8 = 5;
+
+/// Directory tree entry; stream contents are supplied through [`CfbWriter::write`]
+#[derive(Debug, Clone)]
+pub(crate) enum Entry {
+    Storage(String, Vec<Entry>),
+    Stream(String, u64),
+}
+
+#[derive(Debug)]
+struct Node {
+    name: String,
+    kind: u8,
+    size: u64,
+    children: Vec<usize>,
+    left: u32,
+    right: u32,
+    child: u32,
+    /// First sector, or first mini sector for small streams
+    start: u32,
+}
+
+/// Contiguous run of sectors forming one chain
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    start: u64,
+    len: u64,
+    /// Value of every FAT entry in the run, or `None` for a chain
+    mark: Option<u32>,
+}
+
+/// Streaming writer for a compound file with a fixed directory tree
+pub(crate) struct CfbWriter<W: Write> {
+    out: W,
+    nodes: Vec<Node>,
+    paths: HashMap<String, usize>,
+    /// Streams stored in regular sectors, in the order they must be written
+    large: Vec<usize>,
+    next_large: usize,
+    large_written: u64,
+    /// Buffered contents of small streams
+    small: HashMap<usize, Vec<u8>>,
+    mini_sectors: u64,
+    runs: Vec<Run>,
+    fat_sectors: u64,
+    difat_start: u64,
+    difat_sectors: u64,
+}
+
+impl<W: Write> CfbWriter<W> {
+    /// Lay out `entries` under the root storage and write the header
+    ///
+    /// Streams are addressed by their `/`-separated path, e.g.
+    /// `"\u{6}DataSpaces/Version"`.
+    pub(crate) fn new(mut out: W, entries: Vec<Entry>) -> Result<Self> {
+        let mut nodes = vec![Node {
+            name: "Root Entry".to_string(),
+            kind: TYPE_ROOT,
+            size: 0,
+            children: Vec::new(),
+            left: NOSTREAM,
+            right: NOSTREAM,
+            child: NOSTREAM,
+            start: ENDOFCHAIN,
+        }];
+        let mut paths = HashMap::new();
+        add_entries(&mut nodes, &mut paths, 0, "", entries)?;
+        for id in 0..nodes.len() {
+            let mut children = nodes[id].children.clone();
+            children.sort_by(|&a, &b| compare_names(&nodes[a].name, &nodes[b].name));
+            nodes[id].child = build_tree(&mut nodes, &children);
+        }
+
+        let mut runs = Vec::new();
+        let mut next = 0u64;
+        let mut large = Vec::new();
+        let mut mini_sectors = 0u64;
+        for (id, node) in nodes.iter_mut().enumerate() {
+            if node.kind != TYPE_STREAM {
+                continue;
+            }
+            if node.size == 0 {
+                node.start = ENDOFCHAIN;
+            } else if node.size >= MINI_CUTOFF {
+                let len = node.size.div_ceil(SECTOR);
+                node.start = sector_id(next)?;
+                runs.push(Run {
+                    start: next,
+                    len,
+                    mark: None,
+                });
+                next += len;
+                large.push(id);
+            } else {
+                node.start = sector_id(mini_sectors)?;
+                mini_sectors += node.size.div_ceil(MINI_SECTOR);
+            }
+        }
+
+        // The root entry owns the mini stream
+        let mini_len = (mini_sectors * MINI_SECTOR).div_ceil(SECTOR);
+        if mini_len > 0 {
+            nodes[0].start = sector_id(next)?;
+            nodes[0].size = mini_sectors * MINI_SECTOR;
+            runs.push(Run {
+                start: next,
+                len: mini_len,
+                mark: None,
+            });
+            next += mini_len;
+        }
+
+        let dir_start = next;
+        let dir_len = (nodes.len() as u64).div_ceil(SECTOR / 128);
+        runs.push(Run {
+            start: dir_start,
+            len: dir_len,
+            mark: None,
+        });
+        next += dir_len;
+
+        let minifat_start = next;
+        let minifat_len = mini_sectors.div_ceil(PER_SECTOR);
+        if minifat_len > 0 {
+            runs.push(Run {
+                start: minifat_start,
+                len: minifat_len,
+                mark: None,
+            });
+            next += minifat_len;
+        }
+
+        // FAT and DIFAT sectors also need FAT entries, so grow until stable
+        let (mut fat_sectors, mut difat_sectors) = (0u64, 0u64);
+        loop {
+            let fat = (next + fat_sectors + difat_sectors).div_ceil(PER_SECTOR);
+            let difat = fat.saturating_sub(HEADER_DIFAT).div_ceil(PER_SECTOR - 1);
+            if (fat, difat) == (fat_sectors, difat_sectors) {
+                break;
+            }
+            fat_sectors = fat;
+            difat_sectors = difat;
+        }
+        let fat_start = next;
+        runs.push(Run {
+            start: fat_start,
+            len: fat_sectors,
+            mark: Some(FATSECT),
+        });
+        let difat_start = fat_start + fat_sectors;
+        if difat_sectors > 0 {
+            runs.push(Run {
+                start: difat_start,
+                len: difat_sectors,
+                mark: Some(DIFSECT),
+            });
+        }
+        sector_id(difat_start + difat_sectors)?;
+
+        let mut header = Vec::with_capacity(SECTOR as usize);
+        header.extend_from_slice(&SIGNATURE);
+        header.extend_from_slice(&[0u8; 16]); // CLSID
+        header.extend_from_slice(&0x003Eu16.to_le_bytes()); // minor version
+        header.extend_from_slice(&0x0003u16.to_le_bytes()); // major version
+        header.extend_from_slice(&0xFFFEu16.to_le_bytes()); // byte order
+        header.extend_from_slice(&9u16.to_le_bytes()); // sector shift
+        header.extend_from_slice(&6u16.to_le_bytes()); // mini sector shift
+        header.extend_from_slice(&[0u8; 6]);
+        header.extend_from_slice(&0u32.to_le_bytes()); // directory sectors (v3)
+        header.extend_from_slice(&(fat_sectors as u32).to_le_bytes());
+        header.extend_from_slice(&(dir_start as u32).to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes()); // transaction signature
+        header.extend_from_slice(&(MINI_CUTOFF as u32).to_le_bytes());
+        header.extend_from_slice(
+            &(if minifat_len > 0 {
+                minifat_start as u32
+            } else {
+                ENDOFCHAIN
+            })
+            .to_le_bytes(),
+        );
+        header.extend_from_slice(&(minifat_len as u32).to_le_bytes());
+        header.extend_from_slice(
+            &(if difat_sectors > 0 {
+                difat_start as u32
+            } else {
+                ENDOFCHAIN
+            })
+            .to_le_bytes(),
+        );
+        header.extend_from_slice(&(difat_sectors as u32).to_le_bytes());
+        for i in 0..HEADER_DIFAT {
+            let id = if i < fat_sectors {
+                (fat_start + i) as u32
+            } else {
+                FREESECT
+            };
+            header.extend_from_slice(&id.to_le_bytes());
+        }
+        out.write_all(&header)?;
+
+        Ok(CfbWriter {
+            out,
+            nodes,
+            paths,
+            large,
+            next_large: 0,
+            large_written: 0,
+            small: HashMap::new(),
+            mini_sectors,
+            runs,
+            fat_sectors,
+            difat_start,
+            difat_sectors,
+        })
+    }
+
+    /// Append `data` to the stream at `path`
+    ///
+    /// Streams of 4096 bytes or more are written straight through and must
+    /// be filled one after another in tree order.
+    pub(crate) fn write(&mut self, path: &str, data: &[u8]) -> Result<()> {
+        let id = *self
+            .paths
+            .get(path)
+            .ok_or_else(|| ExcelError::WriteError(format!("No stream '{}' in container", path)))?;
+        let size = self.nodes[id].size;
+
+        if size < MINI_CUTOFF {
+            let buf = self.small.entry(id).or_default();
+            if buf.len() as u64 + data.len() as u64 > size {
+                return Err(overflow(path, size));
+            }
+            buf.extend_from_slice(data);
+            return Ok(());
+        }
+
+        if self.large.get(self.next_large) != Some(&id) {
+            return Err(ExcelError::WriteError(format!(
+                "Stream '{}' written out of order",
+                path
+            )));
+        }
+        if self.large_written + data.len() as u64 > size {
+            return Err(overflow(path, size));
+        }
+        self.out.write_all(data)?;
+        self.large_written += data.len() as u64;
+        if self.large_written == size {
+            pad(&mut self.out, size, SECTOR)?;
+            self.next_large += 1;
+            self.large_written = 0;
+        }
+        Ok(())
+    }
+
+    /// Write the mini stream, directory and allocation tables
+    pub(crate) fn finish(mut self) -> Result<W> {
+        if self.next_large < self.large.len() {
+            let node = &self.nodes[self.large[self.next_large]];
+            return Err(ExcelError::WriteError(format!(
+                "Stream '{}' is incomplete",
+                node.name
+            )));
+        }
+
+        // Mini stream, in mini sector order
+        let mut minifat = Vec::with_capacity(self.mini_sectors as usize);
+        for id in 0..self.nodes.len() {
+            let node = &self.nodes[id];
+            if node.kind != TYPE_STREAM || node.size == 0 || node.size >= MINI_CUTOFF {
+                continue;
+            }
+            let data = self.small.remove(&id).unwrap_or_default();
+            if data.len() as u64 != node.size {
+                return Err(ExcelError::WriteError(format!(
+                    "Stream '{}' is incomplete",
+                    node.name
+                )));
+            }
+            self.out.write_all(&data)?;
+            pad(&mut self.out, node.size, MINI_SECTOR)?;
+
+            let len = node.size.div_ceil(MINI_SECTOR);
+            let first = minifat.len() as u32;
+            for i in 0..len as u32 {
+                minifat.push(if i + 1 == len as u32 {
+                    ENDOFCHAIN
+                } else {
+                    first + i + 1
+                });
+            }
+        }
+        pad(&mut self.out, self.mini_sectors * MINI_SECTOR, SECTOR)?;
+
+        // Directory, padded with unused entries
+        let mut dir = Vec::with_capacity(self.nodes.len() * 128 + SECTOR as usize);
+        for node in &self.nodes {
+            push_dir_entry(&mut dir, node);
+        }
+        while !(dir.len() as u64).is_multiple_of(SECTOR) {
+            push_dir_entry(
+                &mut dir,
+                &Node {
+                    name: String::new(),
+                    kind: 0,
+                    size: 0,
+                    children: Vec::new(),
+                    left: NOSTREAM,
+                    right: NOSTREAM,
+                    child: NOSTREAM,
+                    start: 0,
+                },
+            );
+        }
+        self.out.write_all(&dir)?;
+
+        // Mini FAT
+        if !minifat.is_empty() {
+            while !(minifat.len() as u64).is_multiple_of(PER_SECTOR) {
+                minifat.push(FREESECT);
+            }
+            self.write_ids(&minifat)?;
+        }
+
+        // FAT, generated from the runs so huge files need no table in memory
+        let mut sector = Vec::with_capacity(PER_SECTOR as usize);
+        for fat in 0..self.fat_sectors {
+            sector.clear();
+            for i in 0..PER_SECTOR {
+                sector.push(self.fat_entry(fat * PER_SECTOR + i));
+            }
+            self.write_ids(&sector)?;
+        }
+
+        // DIFAT: FAT sector ids beyond the header's 109, chained
+        let fat_start = self.difat_start - self.fat_sectors;
+        for d in 0..self.difat_sectors {
+            sector.clear();
+            for i in 0..PER_SECTOR - 1 {
+                let k = HEADER_DIFAT + d * (PER_SECTOR - 1) + i;
+                sector.push(if k < self.fat_sectors {
+                    (fat_start + k) as u32
+                } else {
+                    FREESECT
+                });
+            }
+            sector.push(if d + 1 < self.difat_sectors {
+                (self.difat_start + d + 1) as u32
+            } else {
+                ENDOFCHAIN
+            });
+            self.write_ids(&sector)?;
+        }
+
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn fat_entry(&self, sector: u64) -> u32 {
+        for run in &self.runs {
+            if sector >= run.start && sector < run.start + run.len {
+                return match run.mark {
+                    Some(mark) => mark,
+                    None if sector + 1 == run.start + run.len => ENDOFCHAIN,
+                    None => (sector + 1) as u32,
+                };
+            }
+        }
+        FREESECT
+    }
+
+    fn write_ids(&mut self, ids: &[u32]) -> Result<()> {
+        let mut bytes = Vec::with_capacity(ids.len() * 4);
+        for id in ids {
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        self.out.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+/// Stream lookup over a compound file
+pub(crate) struct CfbReader<R: Read + Seek> {
+    inner: R,
+    sector_size: u64,
+    fat: Vec<u32>,
+    minifat: Vec<u32>,
+    mini_stream: Vec<u8>,
+    entries: Vec<DirEntry>,
+}
+
+#[derive(Debug)]
+struct DirEntry {
+    name: String,
+    kind: u8,
+    left: u32,
+    right: u32,
+    child: u32,
+    start: u32,
+    size: u64,
+}
+
+impl<R: Read + Seek> CfbReader<R> {
+    pub(crate) fn new(mut inner: R) -> Result<Self> {
+        let mut header = [0u8; 512];
+        inner.seek(SeekFrom::Start(0))?;
+        inner
+            .read_exact(&mut header)
+            .map_err(|_| invalid("file is shorter than its header"))?;
+        if header[..8] != SIGNATURE {
+            return Err(invalid("missing signature"));
+        }
+        let u32_at = |offset: usize| {
+            u32::from_le_bytes([
+                header[offset],
+                header[offset + 1],
+                header[offset + 2],
+                header[offset + 3],
+            ])
+        };
+        let sector_size = match u16::from_le_bytes([header[0x1E], header[0x1F]]) {
+            9 => 512u64,
+            12 => 4096u64,
+            shift => return Err(invalid(&format!("sector shift {}", shift))),
+        };
+        let per_sector = (sector_size / 4) as usize;
+
+        let mut reader = CfbReader {
+            inner,
+            sector_size,
+            fat: Vec::new(),
+            minifat: Vec::new(),
+            mini_stream: Vec::new(),
+            entries: Vec::new(),
+        };
+
+        // FAT sector ids: 109 in the header, the rest in the DIFAT chain
+        let fat_count = u32_at(0x2C) as usize;
+        let mut fat_sectors: Vec<u32> = (0..HEADER_DIFAT as usize)
+            .map(|i| u32_at(0x4C + i * 4))
+            .take(fat_count)
+            .collect();
+        let mut difat = u32_at(0x44);
+        for _ in 0..u32_at(0x48) {
+            if fat_sectors.len() >= fat_count || difat >= DIFSECT {
+                break;
+            }
+            let ids = reader.read_ids(difat)?;
+            fat_sectors.extend(&ids[..per_sector - 1]);
+            difat = ids[per_sector - 1];
+        }
+        fat_sectors.truncate(fat_count);
+        for sector in fat_sectors {
+            let ids = reader.read_ids(sector)?;
+            reader.fat.extend(ids);
+        }
+
+        let dir = reader.read_chain(u32_at(0x30), u64::MAX)?;
+        for raw in dir.chunks_exact(128) {
+            let name_len = (u16::from_le_bytes([raw[64], raw[65]]) as usize).min(64);
+            let units: Vec<u16> = raw[..name_len.saturating_sub(2)]
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            let field = |offset: usize| {
+                u32::from_le_bytes([
+                    raw[offset],
+                    raw[offset + 1],
+                    raw[offset + 2],
+                    raw[offset + 3],
+                ])
+            };
+            let mut size = u64::from(field(120)) | u64::from(field(124)) << 32;
+            if sector_size == 512 {
+                // Version 3 writers may leave garbage in the high half
+                size &= 0xFFFF_FFFF;
+            }
+            reader.entries.push(DirEntry {
+                name: String::from_utf16_lossy(&units),
+                kind: raw[66],
+                left: field(68),
+                right: field(72),
+                child: field(76),
+                start: field(116),
+                size,
+            });
+        }
+        let root = reader
+            .entries
+            .first()
+            .filter(|e| e.kind == TYPE_ROOT)
+            .ok_or_else(|| invalid("missing root entry"))?;
+        let (mini_start, mini_size) = (root.start, root.size);
+
+        if u32_at(0x40) > 0 {
+            let minifat = reader.read_chain(u32_at(0x3C), u64::MAX)?;
+            reader.minifat = minifat
+                .chunks_exact(4)
+                .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                .collect();
+        }
+        if mini_size > 0 {
+            reader.mini_stream = reader.read_chain(mini_start, mini_size)?;
+        }
+        Ok(reader)
+    }
+
+    /// Whole contents of the stream at a `/`-separated path
+    pub(crate) fn read_stream(&mut self, path: &str) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.open_stream(path)?.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    /// Reader over the stream at a `/`-separated path
+    ///
+    /// Streams in regular sectors are read on demand; mini streams are
+    /// copied out of the already loaded mini stream.
+    pub(crate) fn open_stream(&mut self, path: &str) -> Result<StreamReader<'_, R>> {
+        let id = self.find(path).ok_or_else(|| {
+            ExcelError::ReadError(format!("No stream '{}' in compound file", path))
+        })?;
+        let (start, size) = (self.entries[id].start, self.entries[id].size);
+
+        if size < MINI_CUTOFF {
+            let sectors = chain(&self.minifat, start)?;
+            let mut data = Vec::with_capacity(size as usize);
+            for sector in sectors {
+                let offset = sector as usize * MINI_SECTOR as usize;
+                let block = self
+                    .mini_stream
+                    .get(offset..offset + MINI_SECTOR as usize)
+                    .ok_or_else(|| invalid("mini sector out of range"))?;
+                data.extend_from_slice(block);
+            }
+            data.truncate(size as usize);
+            return Ok(StreamReader::Mini(Cursor::new(data)));
+        }
+
+        Ok(StreamReader::Regular {
+            sectors: chain(&self.fat, start)?,
+            reader: self,
+            pos: 0,
+            size,
+        })
+    }
+
+    fn find(&self, path: &str) -> Option<usize> {
+        let mut id = 0usize;
+        for name in path.split('/') {
+            id = self.find_child(self.entries.get(id)?.child, name)?;
+        }
+        (self.entries[id].kind == TYPE_STREAM).then_some(id)
+    }
+
+    /// Search the sibling tree rooted at `node` for `name`
+    fn find_child(&self, node: u32, name: &str) -> Option<usize> {
+        let mut pending = vec![node];
+        let mut visited = 0;
+        while let Some(id) = pending.pop() {
+            let entry = self.entries.get(id as usize)?;
+            visited += 1;
+            if visited > self.entries.len() {
+                return None;
+            }
+            if compare_names(&entry.name, name).is_eq() {
+                return Some(id as usize);
+            }
+            pending.extend(
+                [entry.left, entry.right]
+                    .into_iter()
+                    .filter(|&s| s != NOSTREAM),
+            );
+        }
+        None
+    }
+
+    fn seek_sector(&mut self, sector: u32) -> Result<()> {
+        self.inner
+            .seek(SeekFrom::Start((sector as u64 + 1) * self.sector_size))?;
+        Ok(())
+    }
+
+    fn read_ids(&mut self, sector: u32) -> Result<Vec<u32>> {
+        let mut buf = vec![0u8; self.sector_size as usize];
+        self.seek_sector(sector)?;
+        self.inner
+            .read_exact(&mut buf)
+            .map_err(|_| invalid("sector out of range"))?;
+        Ok(buf
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect())
+    }
+
+    /// Contents of the sector chain starting at `start`, up to `limit` bytes
+    fn read_chain(&mut self, start: u32, limit: u64) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut buf = vec![0u8; self.sector_size as usize];
+        for sector in chain(&self.fat, start)? {
+            if data.len() as u64 >= limit {
+                break;
+            }
+            self.seek_sector(sector)?;
+            self.inner
+                .read_exact(&mut buf)
+                .map_err(|_| invalid("sector out of range"))?;
+            data.extend_from_slice(&buf);
+        }
+        if limit != u64::MAX {
+            data.truncate(limit as usize);
+        }
+        Ok(data)
+    }
+}
+
+/// Reader returned by [`CfbReader::open_stream`]
+pub(crate) enum StreamReader<'a, R: Read + Seek> {
+    Mini(Cursor<Vec<u8>>),
+    Regular {
+        reader: &'a mut CfbReader<R>,
+        sectors: Vec<u32>,
+        pos: u64,
+        size: u64,
+    },
+}
+
+impl<R: Read + Seek> Read for StreamReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let (reader, sectors, pos, size) = match self {
+            StreamReader::Mini(cursor) => return cursor.read(buf),
+            StreamReader::Regular {
+                reader,
+                sectors,
+                pos,
+                size,
+            } => (reader, sectors, pos, *size),
+        };
+        if *pos >= size || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let sector_size = reader.sector_size;
+        let index = (*pos / sector_size) as usize;
+        let first = *sectors.get(index).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "sector chain too short")
+        })?;
+        // Read across consecutive sectors in one go
+        let mut run = 1;
+        while sectors.get(index + run) == Some(&(first + run as u32)) {
+            run += 1;
+        }
+        let offset = *pos % sector_size;
+        let available = (run as u64 * sector_size - offset).min(size - *pos);
+        let n = (buf.len() as u64).min(available) as usize;
+
+        reader
+            .inner
+            .seek(SeekFrom::Start((first as u64 + 1) * sector_size + offset))?;
+        reader.inner.read_exact(&mut buf[..n])?;
+        *pos += n as u64;
+        Ok(n)
+    }
+}
+
+/// Sector ids of the chain starting at `start`
+fn chain(table: &[u32], start: u32) -> Result<Vec<u32>> {
+    let mut sectors = Vec::new();
+    let mut next = start;
+    while next < DIFSECT {
+        if sectors.len() >= table.len() {
+            return Err(invalid("sector chain loops"));
+        }
+        sectors.push(next);
+        next = *table
+            .get(next as usize)
+            .ok_or_else(|| invalid("sector out of range"))?;
+    }
+    Ok(sectors)
+}
+
+fn invalid(reason: &str) -> ExcelError {
+    ExcelError::InvalidFormat(format!("Invalid compound file: {}", reason))
+}
+
+fn add_entries(
+    nodes: &mut Vec<Node>,
+    paths: &mut HashMap<String, usize>,
+    parent: usize,
+    prefix: &str,
+    entries: Vec<Entry>,
+) -> Result<()> {
+    for entry in entries {
+        let (name, kind, size, children) = match entry {
+            Entry::Storage(name, children) => (name, TYPE_STORAGE, 0, children),
+            Entry::Stream(name, size) => (name, TYPE_STREAM, size, Vec::new()),
+        };
+        if name.encode_utf16().count() > 31 || name.contains(['/', '\\', ':', '!']) {
+            return Err(ExcelError::WriteError(format!(
+                "Invalid compound file entry name '{}'",
+                name
+            )));
+        }
+        let path = format!("{}{}", prefix, name);
+        let id = nodes.len();
+        nodes.push(Node {
+            name,
+            kind,
+            size,
+            children: Vec::new(),
+            left: NOSTREAM,
+            right: NOSTREAM,
+            child: NOSTREAM,
+            start: 0,
+        });
+        nodes[parent].children.push(id);
+        if kind == TYPE_STORAGE {
+            add_entries(nodes, paths, id, &format!("{}/", path), children)?;
+        }
+        paths.insert(path, id);
+    }
+    Ok(())
+}
+
+/// Directory order: shorter names first, then case-insensitive by code unit
+fn compare_names(a: &str, b: &str) -> std::cmp::Ordering {
+    let upper = |s: &str| -> Vec<u16> { s.to_uppercase().encode_utf16().collect() };
+    let (a, b) = (upper(a), upper(b));
+    a.len().cmp(&b.len()).then_with(|| a.cmp(&b))
+}
+
+/// Balanced binary search tree over sorted `ids`, returning its root
+///
+/// Every node is black, which readers accept for a balanced tree.
+fn build_tree(nodes: &mut [Node], ids: &[usize]) -> u32 {
+    if ids.is_empty() {
+        return NOSTREAM;
+    }
+    let mid = ids.len() / 2;
+    let left = build_tree(nodes, &ids[..mid]);
+    let right = build_tree(nodes, &ids[mid + 1..]);
+    nodes[ids[mid]].left = left;
+    nodes[ids[mid]].right = right;
+    ids[mid] as u32
+}
+
+fn push_dir_entry(dir: &mut Vec<u8>, node: &Node) {
+    let mut name = [0u8; 64];
+    let units: Vec<u16> = node.name.encode_utf16().collect();
+    for (i, unit) in units.iter().enumerate() {
+        name[i * 2..i * 2 + 2].copy_from_slice(&unit.to_le_bytes());
+    }
+    dir.extend_from_slice(&name);
+    let name_len = if units.is_empty() {
+        0
+    } else {
+        (units.len() as u16 + 1) * 2
+    };
+    dir.extend_from_slice(&name_len.to_le_bytes());
+    dir.push(node.kind);
+    dir.push(1); // black
+    dir.extend_from_slice(&node.left.to_le_bytes());
+    dir.extend_from_slice(&node.right.to_le_bytes());
+    dir.extend_from_slice(&node.child.to_le_bytes());
+    dir.extend_from_slice(&[0u8; 16]); // CLSID
+    dir.extend_from_slice(&[0u8; 4]); // state bits
+    dir.extend_from_slice(&[0u8; 16]); // creation and modified time
+    let (start, size) = match node.kind {
+        TYPE_STREAM | TYPE_ROOT => (node.start, node.size),
+        _ => (0, 0),
+    };
+    dir.extend_from_slice(&start.to_le_bytes());
+    dir.extend_from_slice(&size.to_le_bytes());
+}
+
+fn pad<W: Write>(out: &mut W, written: u64, unit: u64) -> Result<()> {
+    let rem = written % unit;
+    if rem != 0 {
+        out.write_all(&vec![0u8; (unit - rem) as usize])?;
+    }
+    Ok(())
+}
+
+fn sector_id(sector: u64) -> Result<u32> {
+    u32::try_from(sector)
+        .ok()
+        .filter(|&id| id < DIFSECT)
+        .ok_or_else(|| ExcelError::WriteError("Compound file too large".to_string()))
+}
+
+fn overflow(path: &str, size: u64) -> ExcelError {
+    ExcelError::WriteError(format!(
+        "Stream '{}' exceeds its declared size of {} bytes",
+        path, size
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_layout() {
+        let entries = vec![
+            Entry::Storage(
+                "Dir".to_string(),
+                vec![Entry::Stream("Small".to_string(), 100)],
+            ),
+            Entry::Stream("Big".to_string(), 5000),
+        ];
+        let mut cfb = CfbWriter::new(Vec::new(), entries).unwrap();
+        cfb.write("Big", &[7u8; 3000]).unwrap();
+        cfb.write("Dir/Small", &[1u8; 100]).unwrap();
+        cfb.write("Big", &[7u8; 2000]).unwrap();
+        assert!(cfb.write("Big", &[0u8]).is_err());
+        let bytes = cfb.finish().unwrap();
+
+        assert_eq!(&bytes[..8], &SIGNATURE);
+        // Header, Big: 10 sectors, mini stream, directory, mini FAT and FAT: 1 each
+        assert_eq!(bytes.len() as u64, SECTOR * (1 + 10 + 4));
+        assert_eq!(u32_at(&bytes, 0x2C), 1); // FAT sectors
+        assert_eq!(u32_at(&bytes, 0x30), 11); // first directory sector
+        assert_eq!(u32_at(&bytes, 0x3C), 12); // first mini FAT sector
+        assert_eq!(u32_at(&bytes, 0x4C), 13); // first FAT sector
+
+        let data = |sector: usize| 512 * (sector + 1);
+        assert_eq!(bytes[data(0)], 7);
+        assert_eq!(bytes[data(10)], 1);
+        assert_eq!(u32_at(&bytes, data(13) + 8 * 4), 9);
+        assert_eq!(u32_at(&bytes, data(13) + 9 * 4), ENDOFCHAIN);
+        assert_eq!(u32_at(&bytes, data(13) + 13 * 4), FATSECT);
+
+        // Root entry's child is the middle of ["Big", "Dir"] in name order
+        let root = data(11);
+        assert_eq!(&bytes[root..root + 2], &[b'R', 0]);
+        assert_eq!(bytes[root + 66], TYPE_ROOT);
+        assert_eq!(u32_at(&bytes, root + 76), 1);
+        assert_eq!(u32_at(&bytes, root + 116), 10); // mini stream start
+    }
+
+    #[test]
+    fn test_read_back() {
+        let big: Vec<u8> = (0..70_000u32).map(|i| (i % 253) as u8).collect();
+        let entries = vec![
+            Entry::Storage(
+                "Dir".to_string(),
+                vec![
+                    Entry::Stream("Small".to_string(), 100),
+                    Entry::Stream("Empty".to_string(), 0),
+                ],
+            ),
+            Entry::Stream("Big".to_string(), big.len() as u64),
+            Entry::Stream("Tiny".to_string(), 3),
+        ];
+        let mut cfb = CfbWriter::new(Vec::new(), entries).unwrap();
+        cfb.write("Big", &big).unwrap();
+        cfb.write("Dir/Small", &[1u8; 100]).unwrap();
+        cfb.write("Tiny", b"abc").unwrap();
+        let bytes = cfb.finish().unwrap();
+
+        let mut reader = CfbReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.read_stream("Big").unwrap(), big);
+        assert_eq!(reader.read_stream("dir/small").unwrap(), vec![1u8; 100]);
+        assert_eq!(reader.read_stream("Tiny").unwrap(), b"abc");
+        assert!(reader.read_stream("Dir/Empty").unwrap().is_empty());
+        assert!(reader.read_stream("Dir").is_err());
+        assert!(reader.read_stream("Missing").is_err());
+    }
+
+    #[test]
+    fn test_incomplete_stream() {
+        let cfb = CfbWriter::new(Vec::new(), vec![Entry::Stream("A".to_string(), 10)]).unwrap();
+        assert!(cfb.finish().is_err());
+    }
+
+    #[test]
+    fn test_name_order() {
+        use std::cmp::Ordering;
+        assert_eq!(compare_names("Zz", "abc"), Ordering::Less);
+        assert_eq!(compare_names("abc", "ABD"), Ordering::Less);
+        assert_eq!(compare_names("abc", "ABC"), Ordering::Equal);
+    }
+}
@@ -0,0 +1,454 @@
+//! Password protection for XLSX packages (MS-OFFCRYPTO)
+//!
+//! An encrypted workbook is not a ZIP file: the finished OOXML package is
+//! encrypted with ECMA-376 Agile Encryption (AES-256, SHA-512) and stored as
+//! the `EncryptedPackage` stream of a compound file, next to the
+//! `EncryptionInfo` stream that describes how to derive the key from the
+//! password.
+//!
+//! Reading also accepts Standard Encryption (AES-ECB with a SHA-1 key), as
+//! written by Office 2007.
+
+mod agile;
+mod cfb;
+mod standard;
+
+use crate::error::{ExcelError, Result};
+use crate::report::{Checksum, ChecksumSetting, HashingWriter, WriteReport};
+use aes::cipher::consts::U16;
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::{Aes128, Aes192, Aes256};
+use cfb::CfbReader;
+use sha2::Digest;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub(crate) use agile::encrypt_package;
+
+/// Whether `path` is a compound file rather than a ZIP package
+pub(crate) fn is_encrypted(path: &Path) -> Result<bool> {
+    let mut signature = [0u8; 8];
+    let mut file = File::open(path)?;
+    Ok(read_full(&mut file, &mut signature)? == 8 && signature == cfb::SIGNATURE)
+}
+
+/// Decrypt the package stored in the compound file at `input` into `output`
+pub(crate) fn decrypt_package(input: &Path, password: &str, output: &mut impl Write) -> Result<()> {
+    let mut cfb = CfbReader::new(BufReader::new(File::open(input)?))?;
+    let info = cfb.read_stream("EncryptionInfo").map_err(|_| {
+        ExcelError::ReadError("Compound file has no EncryptionInfo stream".to_string())
+    })?;
+    if info.len() < 8 {
+        return Err(ExcelError::InvalidFormat(
+            "Truncated EncryptionInfo stream".to_string(),
+        ));
+    }
+    let version = (
+        u16::from_le_bytes([info[0], info[1]]),
+        u16::from_le_bytes([info[2], info[3]]),
+    );
+    let mut package = cfb.open_stream("EncryptedPackage")?;
+
+    match version {
+        (4, 4) => agile::decrypt_package(&info[8..], password, &mut package, output),
+        (2..=4, 2) => standard::decrypt_package(&info, password, &mut package, output),
+        (major, minor) => Err(ExcelError::NotSupported(format!(
+            "Encryption version {}.{}",
+            major, minor
+        ))),
+    }
+}
+
+/// Decrypted copy of an encrypted workbook, deleted on drop
+#[derive(Debug)]
+pub(crate) struct DecryptedPackage {
+    path: PathBuf,
+}
+
+impl DecryptedPackage {
+    pub(crate) fn new(input: &Path, password: &str) -> Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "excelstream-{}-{}.xlsx",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let package = DecryptedPackage { path };
+
+        let mut out = BufWriter::new(File::create(&package.path)?);
+        decrypt_package(input, password, &mut out)?;
+        out.flush()?;
+        Ok(package)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for DecryptedPackage {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Unencrypted package waiting to be encrypted to its final location
+#[derive(Debug)]
+pub(crate) struct PendingEncryption {
+    plain: PathBuf,
+    target: PathBuf,
+    password: String,
+    checksum: Option<Checksum>,
+}
+
+impl PendingEncryption {
+    /// Stage the unencrypted package next to `target`
+    ///
+    /// Returns the path the package should be written to.
+    pub(crate) fn new(target: &Path, password: &str) -> Result<(Self, PathBuf)> {
+        if password.is_empty() {
+            return Err(ExcelError::WriteError(
+                "Password must not be empty".to_string(),
+            ));
+        }
+        let file_name = target
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let plain =
+            target.with_file_name(format!(".{}.{}.unencrypted", file_name, std::process::id()));
+        Ok((
+            PendingEncryption {
+                plain: plain.clone(),
+                target: target.to_path_buf(),
+                password: password.to_string(),
+                checksum: None,
+            },
+            plain,
+        ))
+    }
+
+    /// Encrypt the staged package into the target and delete the staged copy
+    pub(crate) fn finish(self) -> Result<WriteReport> {
+        let setting = ChecksumSetting::default();
+        setting.set(self.checksum);
+        let result = HashingWriter::create(&self.target, setting)
+            .and_then(|output| encrypt_package(&self.plain, output, &self.password))
+            .and_then(HashingWriter::finish);
+        let _ = fs::remove_file(&self.plain);
+        result
+    }
+
+    /// Checksum the encrypted file rather than the staged package
+    pub(crate) fn set_checksum(&mut self, checksum: Checksum) {
+        self.checksum = Some(checksum);
+    }
+
+    /// Delete the staged copy after a failed write
+    pub(crate) fn discard(self) {
+        let _ = fs::remove_file(&self.plain);
+    }
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    getrandom::getrandom(&mut buf)
+        .map_err(|e| ExcelError::WriteError(format!("No random source: {}", e)))?;
+    Ok(buf)
+}
+
+/// Hash algorithm named in `EncryptionInfo`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "SHA1" | "SHA-1" => Ok(HashAlgorithm::Sha1),
+            "SHA256" => Ok(HashAlgorithm::Sha256),
+            "SHA384" => Ok(HashAlgorithm::Sha384),
+            "SHA512" => Ok(HashAlgorithm::Sha512),
+            other => Err(ExcelError::NotSupported(format!(
+                "Hash algorithm '{}'",
+                other
+            ))),
+        }
+    }
+
+    /// Hash of the concatenated `parts`
+    fn hash(self, parts: &[&[u8]]) -> Vec<u8> {
+        fn run<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+            let mut hasher = D::new();
+            for part in parts {
+                hasher.update(part);
+            }
+            hasher.finalize().to_vec()
+        }
+
+        match self {
+            HashAlgorithm::Sha1 => run::<sha1::Sha1>(parts),
+            HashAlgorithm::Sha256 => run::<sha2::Sha256>(parts),
+            HashAlgorithm::Sha384 => run::<sha2::Sha384>(parts),
+            HashAlgorithm::Sha512 => run::<sha2::Sha512>(parts),
+        }
+    }
+}
+
+/// H(salt + password), then `spin_count` rounds of H(round + hash)
+fn password_hash(
+    algorithm: HashAlgorithm,
+    password: &str,
+    salt: &[u8],
+    spin_count: u32,
+) -> Vec<u8> {
+    let password: Vec<u8> = password.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let mut hash = algorithm.hash(&[salt, &password]);
+    for i in 0..spin_count {
+        hash = algorithm.hash(&[&i.to_le_bytes(), &hash]);
+    }
+    hash
+}
+
+fn read_full(input: &mut impl Read, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn check_blocks(key: &[u8], data: &[u8]) -> Result<()> {
+    if !matches!(key.len(), 16 | 24 | 32) {
+        return Err(unsupported_key(key));
+    }
+    if !data.len().is_multiple_of(16) {
+        return Err(ExcelError::InvalidFormat(
+            "Encrypted data is not a whole number of AES blocks".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn unsupported_key(key: &[u8]) -> ExcelError {
+    ExcelError::NotSupported(format!("{}-bit AES key", key.len() * 8))
+}
+
+/// AES-CBC without padding; `data` must be a whole number of blocks
+fn aes_cbc_encrypt(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<()> {
+    fn run<C>(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<()>
+    where
+        C: BlockEncrypt<BlockSize = U16> + KeyInit,
+    {
+        let cipher = C::new_from_slice(key).map_err(|_| unsupported_key(key))?;
+        let mut prev = [0u8; 16];
+        prev.copy_from_slice(&iv[..16]);
+        for block in data.chunks_exact_mut(16) {
+            for (p, b) in prev.iter_mut().zip(block.iter()) {
+                *p ^= b;
+            }
+            let mut encrypted = prev.into();
+            cipher.encrypt_block(&mut encrypted);
+            block.copy_from_slice(&encrypted);
+            prev.copy_from_slice(block);
+        }
+        Ok(())
+    }
+
+    check_blocks(key, data)?;
+    match key.len() {
+        16 => run::<Aes128>(key, iv, data),
+        24 => run::<Aes192>(key, iv, data),
+        _ => run::<Aes256>(key, iv, data),
+    }
+}
+
+/// AES-CBC decryption without padding
+fn aes_cbc_decrypt(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<()> {
+    fn run<C>(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<()>
+    where
+        C: BlockDecrypt<BlockSize = U16> + KeyInit,
+    {
+        let cipher = C::new_from_slice(key).map_err(|_| unsupported_key(key))?;
+        let mut prev = [0u8; 16];
+        prev.copy_from_slice(&iv[..16]);
+        for block in data.chunks_exact_mut(16) {
+            let mut next = [0u8; 16];
+            next.copy_from_slice(block);
+            let mut decrypted = next.into();
+            cipher.decrypt_block(&mut decrypted);
+            for ((b, d), p) in block.iter_mut().zip(decrypted.iter()).zip(&prev) {
+                *b = d ^ p;
+            }
+            prev = next;
+        }
+        Ok(())
+    }
+
+    check_blocks(key, data)?;
+    match key.len() {
+        16 => run::<Aes128>(key, iv, data),
+        24 => run::<Aes192>(key, iv, data),
+        _ => run::<Aes256>(key, iv, data),
+    }
+}
+
+/// AES-ECB decryption, used by Standard Encryption
+fn aes_ecb_decrypt(key: &[u8], data: &mut [u8]) -> Result<()> {
+    fn run<C>(key: &[u8], data: &mut [u8]) -> Result<()>
+    where
+        C: BlockDecrypt<BlockSize = U16> + KeyInit,
+    {
+        let cipher = C::new_from_slice(key).map_err(|_| unsupported_key(key))?;
+        for block in data.chunks_exact_mut(16) {
+            let mut bytes = [0u8; 16];
+            bytes.copy_from_slice(block);
+            let mut decrypted = bytes.into();
+            cipher.decrypt_block(&mut decrypted);
+            block.copy_from_slice(&decrypted);
+        }
+        Ok(())
+    }
+
+    check_blocks(key, data)?;
+    match key.len() {
+        16 => run::<Aes128>(key, data),
+        24 => run::<Aes192>(key, data),
+        _ => run::<Aes256>(key, data),
+    }
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for c in text.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' | b' ' | b'\r' | b'\n' | b'\t' => continue,
+            _ => {
+                return Err(ExcelError::InvalidFormat(format!(
+                    "Invalid base64 in EncryptionInfo: '{}'",
+                    text
+                )))
+            }
+        };
+        acc = acc << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // Large packages use regular sectors, small ones the mini stream
+        for len in [10_000usize, 100] {
+            let plain_path = dir.path().join("plain.zip");
+            let encrypted = dir.path().join("encrypted.xlsx");
+            let plain: Vec<u8> = (0..len).map(|i| (i * 7 % 256) as u8).collect();
+            fs::write(&plain_path, &plain).unwrap();
+
+            let output = File::create(&encrypted).unwrap();
+            encrypt_package(&plain_path, output, "pässword").unwrap();
+            assert!(is_encrypted(&encrypted).unwrap());
+            assert!(!is_encrypted(&plain_path).unwrap());
+
+            let mut out = Vec::new();
+            decrypt_package(&encrypted, "pässword", &mut out).unwrap();
+            assert_eq!(out, plain);
+
+            let err = decrypt_package(&encrypted, "password", &mut Vec::new()).unwrap_err();
+            assert!(err.to_string().contains("Incorrect password"));
+        }
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+
+        for data in [
+            &b""[..],
+            b"f",
+            b"fo",
+            b"foo",
+            b"foobar",
+            &[0xff, 0x00, 0x80],
+        ] {
+            assert_eq!(base64_decode(&base64_encode(data)).unwrap(), data);
+        }
+        assert!(base64_decode("Zm9v!").is_err());
+    }
+
+    #[test]
+    fn test_aes_cbc() {
+        // NIST SP 800-38A F.2.5, first two blocks
+        let key = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d,
+            0x77, 0x81, 0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3,
+            0x09, 0x14, 0xdf, 0xf4,
+        ];
+        let iv: Vec<u8> = (0u8..16).collect();
+        let mut data = [
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93,
+            0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac,
+            0x45, 0xaf, 0x8e, 0x51,
+        ];
+        aes_cbc_encrypt(&key, &iv, &mut data).unwrap();
+        let encrypted = data;
+        assert_eq!(
+            data,
+            [
+                0xf5, 0x8c, 0x4c, 0x04, 0xd6, 0xe5, 0xf1, 0xba, 0x77, 0x9e, 0xab, 0xfb, 0x5f, 0x7b,
+                0xfb, 0xd6, 0x9c, 0xfc, 0x4e, 0x96, 0x7e, 0xdb, 0x80, 0x8d, 0x67, 0x9f, 0x77, 0x7b,
+                0xc6, 0x70, 0x2c, 0x7d,
+            ]
+        );
+
+        aes_cbc_decrypt(&key, &iv, &mut data).unwrap();
+        assert_eq!(data[..4], [0x6b, 0xc1, 0xbe, 0xe2]);
+        assert_ne!(data, encrypted);
+    }
+}
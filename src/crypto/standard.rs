@@ -0,0 +1,176 @@
+//! ECMA-376 Standard Encryption (MS-OFFCRYPTO 2.3.4.5 – 2.3.4.9)
+//!
+//! Office 2007 encrypts the whole package with AES in ECB mode. The key is
+//! derived from 50 000 rounds of SHA-1 over the password and checked against
+//! an encrypted verifier before any data is decrypted.
+
+use super::{aes_ecb_decrypt, password_hash, read_full, HashAlgorithm};
+use crate::error::{ExcelError, Result};
+use std::io::{Read, Write};
+
+const SPIN_COUNT: u32 = 50_000;
+const ALG_AES_128: u32 = 0x660E;
+const ALG_AES_192: u32 = 0x660F;
+const ALG_AES_256: u32 = 0x6610;
+const ALG_SHA1: u32 = 0x8004;
+/// Offset of the EncryptionHeader, after version, flags and header size
+const HEADER: usize = 12;
+
+/// Decrypt a Standard Encryption `EncryptedPackage`; `info` is the whole
+/// `EncryptionInfo` stream
+pub(crate) fn decrypt_package(
+    info: &[u8],
+    password: &str,
+    package: &mut impl Read,
+    output: &mut impl Write,
+) -> Result<()> {
+    let u32_at = |offset: usize| {
+        info.get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| ExcelError::InvalidFormat("Truncated EncryptionInfo stream".to_string()))
+    };
+
+    let header_size = u32_at(8)? as usize;
+    let alg_id = u32_at(HEADER + 8)?;
+    let alg_hash = u32_at(HEADER + 12)?;
+    let key_bits = u32_at(HEADER + 16)?;
+    match alg_id {
+        // 0 means "implied by the flags", which is AES-128 for OOXML
+        0 | ALG_AES_128 | ALG_AES_192 | ALG_AES_256 => {}
+        other => {
+            return Err(ExcelError::NotSupported(format!(
+                "Standard Encryption algorithm 0x{:04X}",
+                other
+            )))
+        }
+    }
+    if !matches!(alg_hash, 0 | ALG_SHA1) {
+        return Err(ExcelError::NotSupported(format!(
+            "Standard Encryption hash 0x{:04X}",
+            alg_hash
+        )));
+    }
+    let key_bytes = if key_bits == 0 {
+        16
+    } else {
+        key_bits as usize / 8
+    };
+
+    // EncryptionVerifier: salt size, salt, verifier, hash size, verifier hash
+    let verifier = HEADER + header_size;
+    if u32_at(verifier)? != 16 || info.len() < verifier + 72 {
+        return Err(ExcelError::InvalidFormat(
+            "Invalid Standard Encryption verifier".to_string(),
+        ));
+    }
+    let salt = &info[verifier + 4..verifier + 20];
+    let key = derive_key(password, salt, key_bytes);
+
+    let mut check = [0u8; 48];
+    check[..16].copy_from_slice(&info[verifier + 20..verifier + 36]);
+    check[16..].copy_from_slice(&info[verifier + 40..verifier + 72]);
+    aes_ecb_decrypt(&key, &mut check)?;
+    if HashAlgorithm::Sha1.hash(&[&check[..16]]) != check[16..36] {
+        return Err(ExcelError::ReadError("Incorrect password".to_string()));
+    }
+
+    let mut size = [0u8; 8];
+    package.read_exact(&mut size)?;
+    let mut remaining = u64::from_le_bytes(size);
+    let mut chunk = vec![0u8; 4096];
+    while remaining > 0 {
+        let n = read_full(package, &mut chunk)?;
+        let len = n - n % 16;
+        if len == 0 {
+            return Err(ExcelError::InvalidFormat(
+                "Encrypted package is truncated".to_string(),
+            ));
+        }
+        aes_ecb_decrypt(&key, &mut chunk[..len])?;
+        let take = remaining.min(len as u64) as usize;
+        output.write_all(&chunk[..take])?;
+        remaining -= take as u64;
+    }
+    Ok(())
+}
+
+/// Password hash, then the CryptDeriveKey expansion of H(hash + block 0)
+fn derive_key(password: &str, salt: &[u8], key_bytes: usize) -> Vec<u8> {
+    let sha1 = HashAlgorithm::Sha1;
+    let hash = password_hash(sha1, password, salt, SPIN_COUNT);
+    let hash = sha1.hash(&[&hash, &0u32.to_le_bytes()]);
+
+    let mut inner = [0x36u8; 64];
+    let mut outer = [0x5cu8; 64];
+    for (i, b) in hash.iter().enumerate() {
+        inner[i] ^= b;
+        outer[i] ^= b;
+    }
+    let mut key = sha1.hash(&[&inner]);
+    key.extend(sha1.hash(&[&outer]));
+    key.truncate(key_bytes);
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::cipher::{BlockEncrypt, KeyInit};
+    use aes::{Aes128, Block};
+    use std::io::Cursor;
+
+    fn ecb_encrypt(key: &[u8], data: &mut [u8]) {
+        let cipher = Aes128::new_from_slice(key).unwrap();
+        for chunk in data.chunks_exact_mut(16) {
+            let mut block = Block::from(<[u8; 16]>::try_from(&*chunk).unwrap());
+            cipher.encrypt_block(&mut block);
+            chunk.copy_from_slice(&block);
+        }
+    }
+
+    /// EncryptionInfo and EncryptedPackage as Office 2007 would write them
+    fn encrypt(password: &str, plain: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let salt = [7u8; 16];
+        let key = derive_key(password, &salt, 16);
+
+        let mut header = Vec::new();
+        for v in [0x24u32, 0, ALG_AES_128, ALG_SHA1, 128, 0x18, 0, 0] {
+            header.extend_from_slice(&v.to_le_bytes());
+        }
+        header.extend_from_slice(&[0u8; 2]); // empty CSP name
+
+        let mut info = vec![3, 0, 2, 0, 0x24, 0, 0, 0];
+        info.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        info.extend_from_slice(&header);
+        let verifier = [9u8; 16];
+        let mut check = [0u8; 48];
+        check[..16].copy_from_slice(&verifier);
+        check[16..36].copy_from_slice(&HashAlgorithm::Sha1.hash(&[&verifier]));
+        ecb_encrypt(&key, &mut check);
+        info.extend_from_slice(&16u32.to_le_bytes());
+        info.extend_from_slice(&salt);
+        info.extend_from_slice(&check[..16]);
+        info.extend_from_slice(&20u32.to_le_bytes());
+        info.extend_from_slice(&check[16..]);
+
+        let mut data = plain.to_vec();
+        data.resize(plain.len().div_ceil(16) * 16, 0);
+        ecb_encrypt(&key, &mut data);
+        let mut package = (plain.len() as u64).to_le_bytes().to_vec();
+        package.extend_from_slice(&data);
+        (info, package)
+    }
+
+    #[test]
+    fn test_decrypt() {
+        let plain: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+        let (info, package) = encrypt("pass", &plain);
+
+        let mut out = Vec::new();
+        decrypt_package(&info, "pass", &mut Cursor::new(&package), &mut out).unwrap();
+        assert_eq!(out, plain);
+
+        let err = decrypt_package(&info, "wrong", &mut Cursor::new(&package), &mut Vec::new());
+        assert!(err.unwrap_err().to_string().contains("Incorrect password"));
+    }
+}
@@ -1,5 +1,7 @@
 //! CSV encoding with RFC 4180-like behavior
 
+use std::fmt::{Display, Write};
+
 /// CSV encoder for writing properly formatted CSV data
 pub struct CsvEncoder {
     delimiter: u8,
@@ -17,11 +19,43 @@ impl CsvEncoder {
 
     /// Encode entire row into buffer
     pub fn encode_row(&self, fields: &[&str], buffer: &mut Vec<u8>) {
-        for (i, field) in fields.iter().enumerate() {
+        self.encode_fields(fields, buffer);
+    }
+
+    /// Encode a row straight from an iterator of string-like fields
+    ///
+    /// Unlike [`encode_row`](Self::encode_row) the fields don't need to be
+    /// collected into a slice first.
+    pub fn encode_fields<I, S>(&self, fields: I, buffer: &mut Vec<u8>)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for (i, field) in fields.into_iter().enumerate() {
             if i > 0 {
                 buffer.push(self.delimiter);
             }
-            self.encode_field(field, buffer);
+            self.encode_field(field.as_ref(), buffer);
+        }
+    }
+
+    /// Encode a row of `Display` values
+    ///
+    /// Each field is formatted into `scratch`, which is cleared and reused for
+    /// every field, so no per-field `String` is allocated.
+    pub fn encode_display<I>(&self, fields: I, scratch: &mut String, buffer: &mut Vec<u8>)
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                buffer.push(self.delimiter);
+            }
+            scratch.clear();
+            // Writing into a String never fails
+            let _ = write!(scratch, "{}", field);
+            self.encode_field(scratch, buffer);
         }
     }
 
@@ -112,6 +146,19 @@ mod tests {
         assert_eq!(String::from_utf8(buffer).unwrap(), ",,");
     }
 
+    #[test]
+    fn test_encode_display() {
+        let encoder = CsvEncoder::new(b',', b'"');
+        let mut buffer = Vec::new();
+        let mut scratch = String::new();
+        encoder.encode_display(
+            [&1 as &dyn Display, &2.5, &"a,b"],
+            &mut scratch,
+            &mut buffer,
+        );
+        assert_eq!(String::from_utf8(buffer).unwrap(), r#"1,2.5,"a,b""#);
+    }
+
     #[test]
     fn test_custom_delimiter() {
         let encoder = CsvEncoder::new(b';', b'"');
@@ -0,0 +1,186 @@
+//! Transcoding of CSV input to UTF-8 (requires the `encoding` feature)
+
+use encoding_rs::{Decoder, Encoding, UTF_8, WINDOWS_1252};
+use std::io::{self, Read};
+
+/// Bytes read from the input per call
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Bytes looked at before choosing between UTF-8 and the fallback encoding
+const SNIFF_SIZE: usize = 64 * 1024;
+
+/// How CSV input is decoded
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DecodeOptions {
+    /// Encoding of the input, or the fallback when detecting
+    pub(crate) encoding: &'static Encoding,
+    /// Use UTF-8 when the start of the input is valid UTF-8
+    pub(crate) detect: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            encoding: WINDOWS_1252,
+            detect: false,
+        }
+    }
+}
+
+/// Look up an encoding by its WHATWG label ("windows-1252", "shift_jis", "latin1", ...)
+pub(crate) fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+/// `Read` adapter that turns input in any encoding into UTF-8
+///
+/// A byte order mark always wins over the configured encoding and is
+/// removed. Malformed sequences become U+FFFD.
+pub(crate) struct DecodingReader<R> {
+    inner: R,
+    options: DecodeOptions,
+    /// Created once enough input has been seen to pick the encoding
+    decoder: Option<Decoder>,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    output_pos: usize,
+    eof: bool,
+    finished: bool,
+}
+
+impl<R: Read> DecodingReader<R> {
+    pub(crate) fn new(inner: R, options: DecodeOptions) -> Self {
+        Self {
+            inner,
+            options,
+            decoder: None,
+            input: Vec::with_capacity(CHUNK_SIZE),
+            output: Vec::new(),
+            output_pos: 0,
+            eof: false,
+            finished: false,
+        }
+    }
+
+    fn fill_input(&mut self) -> io::Result<()> {
+        let len = self.input.len();
+        self.input.resize(len + CHUNK_SIZE, 0);
+        let result = loop {
+            match self.inner.read(&mut self.input[len..]) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+        self.input.truncate(len + *result.as_ref().unwrap_or(&0));
+        if result? == 0 {
+            self.eof = true;
+        }
+        Ok(())
+    }
+
+    fn choose_encoding(&self) -> &'static Encoding {
+        if self.options.detect {
+            // A sequence cut off at the end of the sample still counts as valid
+            let valid = match std::str::from_utf8(&self.input) {
+                Ok(_) => true,
+                Err(e) => e.error_len().is_none() && !self.eof,
+            };
+            if valid {
+                return UTF_8;
+            }
+        }
+        self.options.encoding
+    }
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.output_pos == self.output.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.fill_input()?;
+            if self.decoder.is_none() {
+                if self.options.detect && self.input.len() < SNIFF_SIZE && !self.eof {
+                    continue;
+                }
+                // new_decoder() lets a BOM override the chosen encoding
+                self.decoder = Some(self.choose_encoding().new_decoder());
+            }
+            let Some(decoder) = self.decoder.as_mut() else {
+                continue;
+            };
+
+            let max_len = decoder
+                .max_utf8_buffer_length(self.input.len())
+                .ok_or_else(|| io::Error::other("CSV chunk too large to decode"))?;
+            self.output.clear();
+            self.output.resize(max_len, 0);
+            self.output_pos = 0;
+            // With room for the worst case the whole input is consumed; an
+            // incomplete sequence at the end stays inside the decoder
+            let (_, read, written, _) =
+                decoder.decode_to_utf8(&self.input, &mut self.output, self.eof);
+            self.output.truncate(written);
+            self.input.drain(..read);
+            self.finished = self.eof;
+        }
+
+        let n = buf.len().min(self.output.len() - self.output_pos);
+        buf[..n].copy_from_slice(&self.output[self.output_pos..self.output_pos + n]);
+        self.output_pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out one byte per read, to split every multi-byte sequence
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(1);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    fn decode(input: &[u8], options: DecodeOptions) -> String {
+        let mut text = String::new();
+        DecodingReader::new(Trickle(input), options)
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn test_decode_fixed_encoding() {
+        let cp1252 = DecodeOptions::default();
+        assert_eq!(decode(b"caf\xe9,\x80 5\n", cp1252), "café,€ 5\n");
+
+        let sjis = DecodeOptions {
+            encoding: encoding_for_label("Shift_JIS").unwrap(),
+            detect: false,
+        };
+        assert_eq!(decode(b"\x93\xfa\x96\x7b,1\n", sjis), "日本,1\n");
+
+        // A BOM wins over the configured encoding
+        assert_eq!(decode(b"\xff\xfea\x00\xe9\x00", cp1252), "aé");
+        assert_eq!(decode(b"\xef\xbb\xbfcaf\xc3\xa9", cp1252), "café");
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        let detect = DecodeOptions {
+            detect: true,
+            ..Default::default()
+        };
+        assert_eq!(decode("café,日本\n".as_bytes(), detect), "café,日本\n");
+        assert_eq!(decode(b"caf\xe9\n", detect), "café\n");
+        assert_eq!(decode(b"", detect), "");
+        assert!(encoding_for_label("no-such-encoding").is_none());
+    }
+}
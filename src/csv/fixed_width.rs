@@ -0,0 +1,434 @@
+//! Fixed-width text files, as produced by mainframe and COBOL exports
+//!
+//! Each record is one line; each field occupies a fixed number of characters.
+//! Compression and streaming work as for [`CsvReader`](crate::CsvReader) and
+//! [`CsvWriter`](crate::CsvWriter).
+
+use crate::csv::CompressionMethod;
+use crate::csv_reader::TextSource;
+use crate::csv_writer::TextOutput;
+use crate::error::{ExcelError, Result};
+use crate::report::{Checksum, WriteReport};
+use crate::types::CellValue;
+use std::path::Path;
+
+/// Side of the column a value is placed on when it is padded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    /// Pad with spaces on the right (text)
+    #[default]
+    Left,
+    /// Pad with spaces on the left (numbers)
+    Right,
+}
+
+fn check_widths(widths: &[usize]) -> Result<()> {
+    if widths.is_empty() || widths.contains(&0) {
+        return Err(ExcelError::InvalidFormat(
+            "Fixed-width columns need at least one column and non-zero widths".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Fixed-width file reader
+///
+/// Splits each line into fields by column width, counted in characters.
+/// Compressed files (`.zst`, `.zip`, `.gz`) are decompressed while reading.
+/// Lines shorter than the record yield empty trailing fields, characters
+/// past the last column are ignored, and empty lines are skipped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use excelstream::csv::FixedWidthReader;
+///
+/// // account(10) name(20) balance(12)
+/// let mut reader = FixedWidthReader::open("accounts.dat", &[10, 20, 12]).unwrap();
+///
+/// for row in reader.rows() {
+///     let row = row.unwrap();
+///     println!("{} {}", row[0], row[2]);
+/// }
+/// ```
+pub struct FixedWidthReader {
+    source: TextSource,
+    line_buffer: String,
+    widths: Vec<usize>,
+    trim: bool,
+    row_count: u64,
+}
+
+impl FixedWidthReader {
+    /// Open a fixed-width file - auto-detects compression from file extension
+    pub fn open<P: AsRef<Path>>(path: P, widths: &[usize]) -> Result<Self> {
+        check_widths(widths)?;
+        Ok(FixedWidthReader {
+            source: TextSource::open(path.as_ref(), &[])?,
+            line_buffer: String::with_capacity(256),
+            widths: widths.to_vec(),
+            trim: true,
+            row_count: 0,
+        })
+    }
+
+    /// Trim spaces around each field (builder pattern, default `true`)
+    pub fn trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Decode the file from another encoding instead of UTF-8 (builder pattern)
+    ///
+    /// See [`CsvReader::encoding`](crate::CsvReader::encoding). Column widths
+    /// count decoded characters.
+    ///
+    /// Requires the `encoding` feature.
+    #[cfg(feature = "encoding")]
+    pub fn encoding(mut self, label: &str) -> Result<Self> {
+        self.source.set_encoding(label)?;
+        Ok(self)
+    }
+
+    /// Detect the encoding of the file (builder pattern)
+    ///
+    /// See [`CsvReader::detect_encoding`](crate::CsvReader::detect_encoding).
+    ///
+    /// Requires the `encoding` feature.
+    #[cfg(feature = "encoding")]
+    pub fn detect_encoding(mut self) -> Self {
+        self.source.detect_encoding();
+        self
+    }
+
+    /// Read a single record, `Ok(None)` at EOF
+    pub fn read_row(&mut self) -> Result<Option<Vec<String>>> {
+        loop {
+            self.line_buffer.clear();
+            if !self.source.read_line(&mut self.line_buffer)? {
+                return Ok(None);
+            }
+            let line = self.line_buffer.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut rest = line;
+            let fields = self
+                .widths
+                .iter()
+                .map(|&width| {
+                    let end = rest
+                        .char_indices()
+                        .nth(width)
+                        .map_or(rest.len(), |(i, _)| i);
+                    let (field, tail) = rest.split_at(end);
+                    rest = tail;
+                    if self.trim {
+                        field.trim().to_string()
+                    } else {
+                        field.to_string()
+                    }
+                })
+                .collect();
+
+            self.row_count += 1;
+            return Ok(Some(fields));
+        }
+    }
+
+    /// Get iterator over rows
+    pub fn rows(&mut self) -> FixedWidthRowIterator<'_> {
+        FixedWidthRowIterator { reader: self }
+    }
+
+    /// Get the number of rows read so far
+    pub fn row_count(&self) -> u64 {
+        self.row_count
+    }
+}
+
+/// Iterator over fixed-width rows
+pub struct FixedWidthRowIterator<'a> {
+    reader: &'a mut FixedWidthReader,
+}
+
+impl Iterator for FixedWidthRowIterator<'_> {
+    type Item = Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_row().transpose()
+    }
+}
+
+/// Fixed-width file writer
+///
+/// Pads every field with spaces to its column width, counted in characters.
+/// A value wider than its column is an error unless
+/// [`truncate`](Self::truncate) is enabled. Compression is picked from the
+/// file extension as for [`CsvWriter`](crate::CsvWriter).
+///
+/// # Examples
+///
+/// ```no_run
+/// use excelstream::csv::{Alignment, FixedWidthWriter};
+///
+/// let mut writer = FixedWidthWriter::new("accounts.dat.gz", &[10, 20, 12])
+///     .unwrap()
+///     .align(2, Alignment::Right);
+/// writer.write_row(["0000012345", "Alice", "1500.00"]).unwrap();
+/// writer.save().unwrap();
+/// ```
+pub struct FixedWidthWriter {
+    output: TextOutput,
+    widths: Vec<usize>,
+    alignments: Vec<Alignment>,
+    truncate: bool,
+    line_ending: &'static [u8],
+    row_count: u64,
+    buffer: String,
+}
+
+impl FixedWidthWriter {
+    /// Create a new fixed-width writer - auto-detects compression from file extension
+    ///
+    /// `.zst`/`.zip` use Zstd (level 3), `.gz` uses Deflate (level 6),
+    /// anything else is written uncompressed.
+    pub fn new<P: AsRef<Path>>(path: P, widths: &[usize]) -> Result<Self> {
+        check_widths(widths)?;
+        Ok(Self::from_output(
+            TextOutput::create(path.as_ref(), "txt")?,
+            widths,
+        ))
+    }
+
+    /// Create a writer with explicit compression method and level
+    pub fn with_compression<P: AsRef<Path>>(
+        path: P,
+        widths: &[usize],
+        method: CompressionMethod,
+        level: u32,
+    ) -> Result<Self> {
+        check_widths(widths)?;
+        let output = TextOutput::with_compression(path.as_ref(), method, level, "txt")?;
+        Ok(Self::from_output(output, widths))
+    }
+
+    fn from_output(output: TextOutput, widths: &[usize]) -> Self {
+        FixedWidthWriter {
+            output,
+            widths: widths.to_vec(),
+            alignments: vec![Alignment::Left; widths.len()],
+            truncate: false,
+            line_ending: b"\n",
+            row_count: 0,
+            buffer: String::with_capacity(widths.iter().sum::<usize>() + 2),
+        }
+    }
+
+    /// Set the alignment of one column (builder pattern, default left)
+    ///
+    /// Out-of-range columns are ignored.
+    pub fn align(mut self, column: usize, alignment: Alignment) -> Self {
+        if let Some(slot) = self.alignments.get_mut(column) {
+            *slot = alignment;
+        }
+        self
+    }
+
+    /// Cut values that don't fit their column instead of failing (builder pattern)
+    pub fn truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// End lines with `\r\n` instead of `\n` (builder pattern)
+    pub fn crlf(mut self, crlf: bool) -> Self {
+        self.line_ending = if crlf { b"\r\n" } else { b"\n" };
+        self
+    }
+
+    /// Write a row of strings
+    ///
+    /// Missing trailing fields are written as blanks; more fields than
+    /// columns is an error.
+    pub fn write_row<I, S>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.buffer.clear();
+        let mut fields = data.into_iter();
+        for column in 0..self.widths.len() {
+            match fields.next() {
+                Some(value) => self.push_field(column, value.as_ref())?,
+                None => self.push_field(column, "")?,
+            }
+        }
+        if fields.next().is_some() {
+            return Err(ExcelError::WriteError(format!(
+                "Row {} has more than {} fields",
+                self.row_count + 1,
+                self.widths.len()
+            )));
+        }
+        self.flush_row()
+    }
+
+    /// Write a row of typed values, formatted as [`CellValue`] displays them
+    pub fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
+        self.write_row(cells.iter().map(|cell| cell.to_string()))
+    }
+
+    /// Append `value` padded to the width of `column`
+    fn push_field(&mut self, column: usize, value: &str) -> Result<()> {
+        let width = self.widths[column];
+        if value.contains(['\n', '\r']) {
+            return Err(ExcelError::WriteError(format!(
+                "Row {}, column {}: line breaks can't be written to a fixed-width file",
+                self.row_count + 1,
+                column + 1
+            )));
+        }
+
+        let len = value.chars().count();
+        let value = if len > width {
+            if !self.truncate {
+                return Err(ExcelError::WriteError(format!(
+                    "Row {}, column {}: '{}' is wider than {} characters",
+                    self.row_count + 1,
+                    column + 1,
+                    value,
+                    width
+                )));
+            }
+            let end = value
+                .char_indices()
+                .nth(width)
+                .map_or(value.len(), |(i, _)| i);
+            &value[..end]
+        } else {
+            value
+        };
+
+        let padding = width - len.min(width);
+        match self.alignments[column] {
+            Alignment::Left => {
+                self.buffer.push_str(value);
+                self.buffer.extend(std::iter::repeat_n(' ', padding));
+            }
+            Alignment::Right => {
+                self.buffer.extend(std::iter::repeat_n(' ', padding));
+                self.buffer.push_str(value);
+            }
+        }
+        Ok(())
+    }
+
+    fn flush_row(&mut self) -> Result<()> {
+        self.output.write_all(self.buffer.as_bytes())?;
+        self.output.write_all(self.line_ending)?;
+        self.row_count += 1;
+        Ok(())
+    }
+
+    /// Get the number of rows written
+    pub fn row_count(&self) -> u64 {
+        self.row_count
+    }
+
+    /// Compute a checksum of the output file, returned by
+    /// [`save_with_report`](Self::save_with_report)
+    pub fn set_checksum(&mut self, checksum: Checksum) {
+        self.output.set_checksum(checksum);
+    }
+
+    /// Finalize and save the file
+    pub fn save(self) -> Result<()> {
+        self.save_with_report().map(|_| ())
+    }
+
+    /// Save the file and report its size and checksum
+    pub fn save_with_report(mut self) -> Result<WriteReport> {
+        self.output.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_width_round_trip() -> Result<()> {
+        let dir = tempfile::TempDir::new().unwrap();
+        for name in ["accounts.dat", "accounts.dat.gz", "accounts.dat.zst"] {
+            let path = dir.path().join(name);
+            let mut writer = FixedWidthWriter::new(&path, &[5, 8, 7])?.align(2, Alignment::Right);
+            writer.write_row(["00001", "Zoë", "15.50"])?;
+            writer.write_row(["00002", "Bob"])?;
+            writer.write_row_typed(&[
+                CellValue::String("00003".to_string()),
+                CellValue::Empty,
+                CellValue::Int(-7),
+            ])?;
+            assert_eq!(writer.row_count(), 3);
+            writer.save()?;
+
+            if name == "accounts.dat" {
+                assert_eq!(
+                    std::fs::read_to_string(&path)?,
+                    "00001Zoë       15.50\n00002Bob            \n00003             -7\n"
+                );
+            }
+
+            let mut reader = FixedWidthReader::open(&path, &[5, 8, 7])?;
+            let rows: Vec<Vec<String>> = reader.rows().collect::<Result<_>>()?;
+            assert_eq!(
+                rows,
+                vec![
+                    vec!["00001", "Zoë", "15.50"],
+                    vec!["00002", "Bob", ""],
+                    vec!["00003", "", "-7"],
+                ]
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixed_width_short_lines_and_untrimmed() -> Result<()> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("short.txt");
+        std::fs::write(&path, "AB  xy\r\n\r\nCDEFGHIJKL\nM\n")?;
+
+        let mut reader = FixedWidthReader::open(&path, &[4, 3])?.trim(false);
+        assert_eq!(reader.read_row()?, Some(vec!["AB  ".into(), "xy".into()]));
+        assert_eq!(reader.read_row()?, Some(vec!["CDEF".into(), "GHI".into()]));
+        assert_eq!(reader.read_row()?, Some(vec!["M".into(), "".into()]));
+        assert_eq!(reader.read_row()?, None);
+        assert_eq!(reader.row_count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixed_width_errors() -> Result<()> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("errors.txt");
+        assert!(FixedWidthWriter::new(&path, &[]).is_err());
+        assert!(FixedWidthWriter::new(&path, &[3, 0]).is_err());
+
+        let mut writer = FixedWidthWriter::new(&path, &[3, 2])?;
+        assert!(writer.write_row(["toolong", "x"]).is_err());
+        assert!(writer.write_row(["a", "b", "c"]).is_err());
+        assert!(writer.write_row(["a\nb", "x"]).is_err());
+        writer.save()?;
+
+        let mut writer = FixedWidthWriter::new(&path, &[3, 2])?
+            .truncate(true)
+            .crlf(true);
+        writer.write_row(["toolong", "日本語"])?;
+        writer.save()?;
+        assert_eq!(std::fs::read_to_string(&path)?, "too日本\r\n");
+        Ok(())
+    }
+}
@@ -1,10 +1,51 @@
 //! CSV utilities for encoding and parsing
 
 mod encoder;
+#[cfg(feature = "encoding")]
+pub(crate) mod encoding;
+mod fixed_width;
 mod parser;
 
 pub use encoder::CsvEncoder;
+pub use fixed_width::{Alignment, FixedWidthReader, FixedWidthWriter};
 pub use parser::CsvParser;
 
 // Re-export CompressionMethod from s-zip for convenience
 pub use s_zip::CompressionMethod;
+
+use std::path::Path;
+
+/// Compression implied by a file extension: `.zst`/`.zip` → Zstd level 3,
+/// `.gz` → Deflate level 6, anything else is written uncompressed
+///
+/// Extensions are matched case-insensitively. Without the `zstd` feature
+/// `.zst`/`.zip` use Deflate as well.
+pub(crate) fn compression_for_path(path: &Path) -> Option<(CompressionMethod, u32)> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match Some(ext.as_str()) {
+        #[cfg(feature = "zstd")]
+        Some("zst") | Some("zip") => Some((CompressionMethod::Zstd, 3)),
+        #[cfg(not(feature = "zstd"))]
+        Some("zst") | Some("zip") => Some((CompressionMethod::Deflate, 6)),
+        Some("gz") => Some((CompressionMethod::Deflate, 6)),
+        _ => None,
+    }
+}
+
+/// Whether the path names a TSV file, compressed or not (`.tsv`, `.tsv.gz`, ...)
+pub(crate) fn is_tsv(path: &Path) -> bool {
+    let inner = if compression_for_path(path).is_some() {
+        Path::new(path.file_stem().unwrap_or_default())
+    } else {
+        path
+    };
+    inner
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tsv"))
+}
+
+/// Whether the path ends in `.gz`, in any case
+pub(crate) fn is_gzip(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
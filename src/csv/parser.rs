@@ -15,6 +15,16 @@ impl CsvParser {
         }
     }
 
+    /// Whether a physical line opens or closes a quoted field
+    ///
+    /// A record whose quoted field holds a newline spans several lines; a
+    /// line flips the quote state when it has an odd number of quote chars
+    /// (`""` escapes count as two). Readers keep appending lines while a
+    /// quote is open.
+    pub(crate) fn flips_quote(&self, line: &[u8]) -> bool {
+        line.iter().filter(|&&b| b == self.quote_char).count() % 2 == 1
+    }
+
     /// Parse CSV line into fields
     pub fn parse_line(&self, line: &str) -> Vec<String> {
         let mut fields = Vec::with_capacity(16); // Pre-allocate for typical row size
@@ -1,11 +1,25 @@
 //! CSV file reading with streaming support and decompression
 
-use crate::csv::CsvParser;
+#[cfg(feature = "encoding")]
+use crate::csv::encoding::{encoding_for_label, DecodeOptions, DecodingReader};
+use crate::csv::{compression_for_path, is_tsv, CsvParser};
 use crate::error::{ExcelError, Result};
 use crate::fast_writer::StreamingZipReader;
+use crate::row_io::RowSource;
+use crate::types::CellValue;
+use flate2::read::MultiGzDecoder;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Decompressed bytes per chunk handed over by the decompression thread
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Chunks decompressed ahead of the reader before the thread blocks
+const QUEUE_DEPTH: usize = 4;
+/// First bytes of a gzip stream (RFC 1952)
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
 
 /// CSV file reader with streaming capabilities and decompression support
 ///
@@ -45,14 +59,11 @@ use std::path::Path;
 /// }
 /// ```
 pub struct CsvReader {
-    // Input sources (one active)
-    direct_reader: Option<BufReader<File>>,
-    zip_reader_data: Option<Vec<u8>>,
+    source: TextSource,
 
     // Parser state
     line_buffer: String,
     row_count: u64,
-    lines_iter: Option<Box<dyn Iterator<Item = String>>>,
 
     // Configuration
     delimiter: u8,
@@ -67,7 +78,8 @@ impl CsvReader {
     /// # File Extensions
     /// - `.csv` → Uncompressed, direct read
     /// - `.csv.zst`, `.csv.zip` → Zstd decompression
-    /// - `.csv.gz` → Deflate/Gzip decompression
+    /// - `.csv.gz` → gzip, or a ZIP archive as written by older versions
+    /// - `.tsv`, `.tsv.zst`, `.tsv.zip`, `.tsv.gz` → same, tab delimited
     ///
     /// # Examples
     ///
@@ -82,59 +94,32 @@ impl CsvReader {
     /// ```
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path_ref = path.as_ref();
-        let path_str = path_ref.to_str().unwrap_or("");
-
-        if path_str.ends_with(".csv.zst")
-            || path_str.ends_with(".csv.zip")
-            || path_str.ends_with(".csv.gz")
-        {
-            // Compressed - use s-zip
-            let mut zip = StreamingZipReader::open(path_ref)
-                .map_err(|e| ExcelError::ReadError(format!("Failed to open ZIP: {}", e)))?;
-
-            // Find first .csv entry
-            let entry_name = zip
-                .entries()
-                .iter()
-                .find(|e| e.name.ends_with(".csv"))
-                .or_else(|| zip.entries().first())
-                .ok_or_else(|| ExcelError::ReadError("No CSV entry found in archive".to_string()))?
-                .name
-                .clone();
+        Ok(CsvReader {
+            source: TextSource::open(path_ref, &[".csv", ".tsv"])?,
+            line_buffer: String::with_capacity(1024),
+            row_count: 0,
+            delimiter: if is_tsv(path_ref) { b'\t' } else { b',' },
+            quote_char: b'"',
+            has_header: false,
+            headers: Vec::new(),
+        })
+    }
 
-            // Read decompressed data
-            let data = zip
-                .read_entry_by_name(&entry_name)
-                .map_err(|e| ExcelError::ReadError(format!("Failed to read ZIP entry: {}", e)))?;
-
-            Ok(CsvReader {
-                direct_reader: None,
-                zip_reader_data: Some(data),
-                line_buffer: String::with_capacity(1024),
-                row_count: 0,
-                lines_iter: None,
-                delimiter: b',',
-                quote_char: b'"',
-                has_header: false,
-                headers: Vec::new(),
-            })
-        } else {
-            // Plain CSV
-            let file = File::open(path_ref)
-                .map_err(|e| ExcelError::ReadError(format!("Failed to open CSV file: {}", e)))?;
-
-            Ok(CsvReader {
-                direct_reader: Some(BufReader::new(file)),
-                zip_reader_data: None,
-                line_buffer: String::with_capacity(1024),
-                row_count: 0,
-                lines_iter: None,
-                delimiter: b',',
-                quote_char: b'"',
-                has_header: false,
-                headers: Vec::new(),
-            })
-        }
+    /// Open a tab-separated file, whatever its extension
+    ///
+    /// Paths ending in `.tsv` (optionally followed by `.gz`, `.zst` or
+    /// `.zip`) already use a tab delimiter with [`open`](Self::open); this is
+    /// for TSV data in `.txt` or other files. Fields may still be quoted.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::csv_reader::CsvReader;
+    ///
+    /// let mut reader = CsvReader::open_tsv("export.txt").unwrap();
+    /// ```
+    pub fn open_tsv<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::open(path)?.delimiter(b'\t'))
     }
 
     /// Set custom delimiter (builder pattern)
@@ -168,6 +153,45 @@ impl CsvReader {
         self
     }
 
+    /// Decode the file from another encoding instead of UTF-8 (builder pattern)
+    ///
+    /// `label` is a WHATWG encoding label such as `"windows-1252"`,
+    /// `"shift_jis"` or `"latin1"`. A byte order mark at the start of the file
+    /// still takes precedence. With [`detect_encoding`](Self::detect_encoding)
+    /// this is the encoding used when the file isn't UTF-8.
+    ///
+    /// Requires the `encoding` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::csv_reader::CsvReader;
+    ///
+    /// let mut reader = CsvReader::open("export.csv")
+    ///     .unwrap()
+    ///     .encoding("shift_jis")
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "encoding")]
+    pub fn encoding(mut self, label: &str) -> Result<Self> {
+        self.source.set_encoding(label)?;
+        Ok(self)
+    }
+
+    /// Detect the encoding of the file (builder pattern)
+    ///
+    /// Uses the byte order mark if there is one, otherwise UTF-8 when the
+    /// first 64 KB are valid UTF-8, otherwise the encoding given to
+    /// [`encoding`](Self::encoding) (Windows-1252 by default). Only the start
+    /// of the file is sampled.
+    ///
+    /// Requires the `encoding` feature.
+    #[cfg(feature = "encoding")]
+    pub fn detect_encoding(mut self) -> Self {
+        self.source.detect_encoding();
+        self
+    }
+
     /// Get header row if available
     ///
     /// Returns `Some(&[String])` if headers were parsed, `None` otherwise.
@@ -179,9 +203,11 @@ impl CsvReader {
         }
     }
 
-    /// Read a single row
+    /// Read a single record
     ///
-    /// Returns `Ok(None)` when EOF is reached.
+    /// A quoted field can span several lines (`"Line1\nLine2"`), so one record
+    /// may consume more than one physical line. Returns `Ok(None)` when EOF is
+    /// reached.
     ///
     /// # Examples
     ///
@@ -195,42 +221,28 @@ impl CsvReader {
     /// }
     /// ```
     pub fn read_row(&mut self) -> Result<Option<Vec<String>>> {
-        // Clear buffer
         self.line_buffer.clear();
 
-        // Read line from source
-        let bytes_read = if let Some(ref mut reader) = self.direct_reader {
-            reader
-                .read_line(&mut self.line_buffer)
-                .map_err(|e| ExcelError::ReadError(format!("Failed to read line: {}", e)))?
-        } else if let Some(ref data) = self.zip_reader_data {
-            // For ZIP data, we need to parse lines ourselves
-            // This is a simplified approach - in production, consider using a proper line iterator
-            if self.lines_iter.is_none() {
-                let content = String::from_utf8_lossy(data).to_string();
-                let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-                self.lines_iter = Some(Box::new(lines.into_iter()));
+        // A quoted field may contain newlines, so keep appending physical
+        // lines while a quote is open
+        let parser = CsvParser::new(self.delimiter, self.quote_char);
+        let mut in_quotes = false;
+        loop {
+            let start = self.line_buffer.len();
+            if !self.source.read_line(&mut self.line_buffer)? {
+                break; // EOF; an unterminated quote keeps what was read
             }
-
-            if let Some(ref mut iter) = self.lines_iter {
-                if let Some(line) = iter.next() {
-                    self.line_buffer = line;
-                    self.line_buffer.len()
-                } else {
-                    return Ok(None); // EOF
-                }
-            } else {
-                return Ok(None);
+            in_quotes ^= parser.flips_quote(&self.line_buffer.as_bytes()[start..]);
+            if !in_quotes {
+                break;
             }
-        } else {
-            return Err(ExcelError::ReadError("No reader available".to_string()));
-        };
+        }
 
-        if bytes_read == 0 {
+        if self.line_buffer.is_empty() {
             return Ok(None); // EOF
         }
 
-        // Remove trailing newline (for direct reader)
+        // Remove the record's line ending; ones inside quotes are data
         if self.line_buffer.ends_with('\n') {
             self.line_buffer.pop();
             if self.line_buffer.ends_with('\r') {
@@ -238,8 +250,7 @@ impl CsvReader {
             }
         }
 
-        // Parse line
-        let parser = CsvParser::new(self.delimiter, self.quote_char);
+        // Parse record
         let fields = parser.parse_line(&self.line_buffer);
 
         // Handle header row
@@ -304,6 +315,215 @@ impl<'a> Iterator for CsvRowIterator<'a> {
     }
 }
 
+/// The header is the schema when [`has_header`](CsvReader::has_header) is
+/// set; fields are text cells, empty fields `Empty`
+impl RowSource for CsvReader {
+    fn schema(&mut self) -> Result<Option<Vec<String>>> {
+        if self.has_header && self.row_count == 0 {
+            self.read_row()?;
+        }
+        Ok(self.headers().map(<[String]>::to_vec))
+    }
+
+    fn rows(&mut self) -> Result<impl Iterator<Item = Result<Vec<CellValue>>> + '_> {
+        Ok(CsvReader::rows(self).map(|row| {
+            row.map(|fields| {
+                fields
+                    .into_iter()
+                    .map(|field| {
+                        if field.is_empty() {
+                            CellValue::Empty
+                        } else {
+                            CellValue::String(field)
+                        }
+                    })
+                    .collect()
+            })
+        }))
+    }
+}
+
+/// Lines of a plain or compressed text file
+///
+/// Shared by [`CsvReader`] and [`FixedWidthReader`](crate::csv::FixedWidthReader).
+/// Compressed files (`.zst`, `.zip`, `.gz`) are decompressed while reading;
+/// each may be a gzip stream or a ZIP archive, told apart by their first bytes.
+pub(crate) struct TextSource {
+    // Plain file, or an archive entry decompressed as it is read
+    reader: BufReader<Box<dyn Read + Send>>,
+    started: bool,
+    /// Transcoding set up by the builders, applied on the first read
+    #[cfg(feature = "encoding")]
+    decoding: Option<DecodeOptions>,
+}
+
+impl TextSource {
+    /// Open `path`; in an archive, the first entry ending in one of
+    /// `extensions` is read, or the first entry if none does
+    pub(crate) fn open(path: &Path, extensions: &[&str]) -> Result<Self> {
+        let source: Box<dyn Read + Send> = if compression_for_path(path).is_some() {
+            let mut file = BufReader::new(File::open(path).map_err(|e| {
+                ExcelError::ReadError(format!("Failed to open {}: {}", path.display(), e))
+            })?);
+            let magic = file.fill_buf().map_err(|e| {
+                ExcelError::ReadError(format!("Failed to read {}: {}", path.display(), e))
+            })?;
+            if magic.starts_with(&GZIP_MAGIC) {
+                // Concatenated members (`cat a.gz b.gz`) read as one stream
+                return Ok(Self::from_reader(Box::new(MultiGzDecoder::new(file))));
+            }
+
+            // Otherwise a ZIP archive - use s-zip
+            let zip = StreamingZipReader::open(path)
+                .map_err(|e| ExcelError::ReadError(format!("Failed to open ZIP: {}", e)))?;
+
+            let entry_name = zip
+                .entries()
+                .iter()
+                .find(|e| extensions.iter().any(|ext| e.name.ends_with(ext)))
+                .or_else(|| zip.entries().first())
+                .ok_or_else(|| ExcelError::ReadError("No entry found in archive".to_string()))?
+                .name
+                .clone();
+
+            Box::new(EntryStream::spawn(zip, entry_name)?)
+        } else {
+            let file = File::open(path).map_err(|e| {
+                ExcelError::ReadError(format!("Failed to open {}: {}", path.display(), e))
+            })?;
+            Box::new(file)
+        };
+
+        Ok(Self::from_reader(source))
+    }
+
+    fn from_reader(source: Box<dyn Read + Send>) -> Self {
+        TextSource {
+            reader: BufReader::with_capacity(CHUNK_SIZE, source),
+            started: false,
+            #[cfg(feature = "encoding")]
+            decoding: None,
+        }
+    }
+
+    #[cfg(feature = "encoding")]
+    pub(crate) fn set_encoding(&mut self, label: &str) -> Result<()> {
+        let encoding = encoding_for_label(label)
+            .ok_or_else(|| ExcelError::InvalidFormat(format!("Unknown encoding '{}'", label)))?;
+        self.decoding
+            .get_or_insert_with(DecodeOptions::default)
+            .encoding = encoding;
+        Ok(())
+    }
+
+    #[cfg(feature = "encoding")]
+    pub(crate) fn detect_encoding(&mut self) {
+        self.decoding
+            .get_or_insert_with(DecodeOptions::default)
+            .detect = true;
+    }
+
+    /// Append the next physical line, with its line ending, to `buf`.
+    /// Returns false at EOF.
+    pub(crate) fn read_line(&mut self, buf: &mut String) -> Result<bool> {
+        #[cfg(feature = "encoding")]
+        if let Some(options) = self.decoding.take() {
+            // Nothing has been buffered yet, so the source can be rewrapped
+            if !self.started && self.reader.buffer().is_empty() {
+                let source =
+                    std::mem::replace(&mut self.reader, BufReader::new(Box::new(io::empty())))
+                        .into_inner();
+                let decoded: Box<dyn Read + Send> = Box::new(DecodingReader::new(source, options));
+                self.reader = BufReader::with_capacity(CHUNK_SIZE, decoded);
+            }
+        }
+
+        let start = buf.len();
+        let bytes_read = self
+            .reader
+            .read_line(buf)
+            .map_err(|e| ExcelError::ReadError(format!("Failed to read line: {}", e)))?;
+
+        // Excel writes a UTF-8 BOM in "CSV UTF-8" exports
+        if !self.started && buf[start..].starts_with('\u{feff}') {
+            buf.drain(start..start + '\u{feff}'.len_utf8());
+        }
+        self.started = true;
+        Ok(bytes_read > 0)
+    }
+}
+
+/// Archive entry decompressed on a worker thread
+///
+/// s-zip's streaming entry reader borrows the archive, so both live on the
+/// worker and the decompressed bytes come back over a bounded channel. Only
+/// a few chunks are in memory at a time, however large the entry is.
+struct EntryStream {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl EntryStream {
+    fn spawn(mut zip: StreamingZipReader, entry_name: String) -> Result<Self> {
+        let (sender, receiver) = mpsc::sync_channel(QUEUE_DEPTH);
+        thread::Builder::new()
+            .name("excelstream-csv-inflate".to_string())
+            .spawn(move || {
+                let mut reader = match zip.read_entry_streaming_by_name(&entry_name) {
+                    Ok(reader) => reader,
+                    Err(e) => {
+                        let message = format!("Failed to read ZIP entry: {}", e);
+                        let _ = sender.send(Err(io::Error::other(message)));
+                        return;
+                    }
+                };
+                loop {
+                    let mut chunk = vec![0; CHUNK_SIZE];
+                    match reader.read(&mut chunk) {
+                        Ok(0) => break,
+                        Ok(n) => {
+                            chunk.truncate(n);
+                            // The CsvReader was dropped; stop early
+                            if sender.send(Ok(chunk)).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                        Err(e) => {
+                            let _ = sender.send(Err(e));
+                            break;
+                        }
+                    }
+                }
+            })?;
+        Ok(EntryStream {
+            receiver,
+            chunk: Vec::new(),
+            pos: 0,
+        })
+    }
+}
+
+impl Read for EntryStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.pos = 0;
+                }
+                // The worker hung up: end of entry
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,4 +588,201 @@ mod tests {
         std::fs::remove_file(path).ok();
         Ok(())
     }
+
+    #[test]
+    fn test_read_multiline_records() -> Result<()> {
+        let path = "test_read_multiline.csv";
+        {
+            let mut writer = CsvWriter::new(path)?;
+            writer.write_row(["ID", "Note"])?;
+            writer.write_row(["1", "Line1\nLine2"])?;
+            writer.write_row(["2", "Say \"Hi\",\nthen \"\"bye\"\""])?;
+            writer.write_row(["3", "plain"])?;
+            writer.save()?;
+        }
+
+        let mut reader = CsvReader::open(path)?;
+        let rows: Vec<Vec<String>> = reader.rows().collect::<Result<_>>()?;
+        assert_eq!(
+            rows,
+            vec![
+                vec!["ID", "Note"],
+                vec!["1", "Line1\nLine2"],
+                vec!["2", "Say \"Hi\",\nthen \"\"bye\"\""],
+                vec!["3", "plain"],
+            ]
+        );
+        assert_eq!(reader.row_count(), 4);
+
+        std::fs::remove_file(path).ok();
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_crlf_records() -> Result<()> {
+        let path = "test_read_crlf.csv";
+        std::fs::write(
+            path,
+            "a,b\r\n\"x\r\ny\",\"\"\"q\"\"\"\r\n\r\nlast,\"open\nquote",
+        )?;
+
+        let mut reader = CsvReader::open(path)?.has_header(true);
+        let rows: Vec<Vec<String>> = reader.rows().collect::<Result<_>>()?;
+        assert_eq!(
+            reader.headers(),
+            Some(&["a".to_string(), "b".to_string()][..])
+        );
+        assert_eq!(
+            rows,
+            vec![
+                vec!["x\r\ny", "\"q\""],
+                vec![""],
+                // Unterminated quote at EOF keeps the rest of the file
+                vec!["last", "open\nquote"],
+            ]
+        );
+
+        std::fs::remove_file(path).ok();
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_stream_chunks() -> Result<()> {
+        use crate::fast_writer::StreamingZipWriter;
+
+        let path = "test_entry_stream.csv.zip";
+        let data: Vec<u8> = (0..200_000u32)
+            .flat_map(|i| format!("{},row {}\n", i, i).into_bytes())
+            .collect();
+        {
+            let mut zip = StreamingZipWriter::with_compression(Path::new(path), 6)?;
+            zip.start_entry("data.csv")?;
+            zip.write_data(&data)?;
+            zip.finish()?;
+        }
+
+        // Spans many chunks; read through small buffers
+        let zip = StreamingZipReader::open(Path::new(path))?;
+        let mut stream = EntryStream::spawn(zip, "data.csv".to_string())?;
+        let mut read = Vec::new();
+        let mut buf = [0u8; 1000];
+        loop {
+            let n = stream.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            read.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(read, data);
+
+        let mut reader = CsvReader::open(path)?;
+        assert_eq!(reader.rows().count(), 200_000);
+        assert_eq!(reader.row_count(), 200_000);
+
+        std::fs::remove_file(path).ok();
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_strips_utf8_bom() -> Result<()> {
+        let path = "test_read_bom.csv";
+        std::fs::write(path, "\u{feff}id,name\n1,caf\u{e9}\n")?;
+
+        let mut reader = CsvReader::open(path)?;
+        let rows: Vec<Vec<String>> = reader.rows().collect::<Result<_>>()?;
+        assert_eq!(rows, vec![vec!["id", "name"], vec!["1", "caf\u{e9}"]]);
+
+        std::fs::remove_file(path).ok();
+        Ok(())
+    }
+
+    #[test]
+    fn test_tsv_round_trip() -> Result<()> {
+        let dir = tempfile::TempDir::new().unwrap();
+        for name in ["data.tsv", "data.tsv.gz", "data.tsv.zst"] {
+            let path = dir.path().join(name);
+            let mut writer = CsvWriter::new(&path)?;
+            writer.write_row(["id", "note"])?;
+            writer.write_row(["1", "a,b\tc"])?;
+            writer.save()?;
+
+            let mut reader = CsvReader::open(&path)?;
+            let rows: Vec<Vec<String>> = reader.rows().collect::<Result<_>>()?;
+            assert_eq!(rows, vec![vec!["id", "note"], vec!["1", "a,b\tc"]]);
+        }
+
+        let path = dir.path().join("export.txt");
+        let mut writer = CsvWriter::new_tsv(&path)?;
+        writer.write_row(["x", "y,z"])?;
+        writer.save()?;
+        assert_eq!(std::fs::read_to_string(&path)?, "x\ty,z\n");
+        let mut reader = CsvReader::open_tsv(&path)?;
+        assert_eq!(
+            reader.read_row()?,
+            Some(vec!["x".to_string(), "y,z".to_string()])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_gzip_round_trip() -> Result<()> {
+        use crate::fast_writer::StreamingZipWriter;
+        use flate2::read::GzDecoder;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("data.csv.gz");
+        let mut writer = CsvWriter::new(&path)?;
+        writer.write_row(["id", "name"])?;
+        writer.write_row(["1", "Zoë"])?;
+        writer.save()?;
+
+        // A real gzip file, readable by gunzip
+        let mut text = String::new();
+        GzDecoder::new(File::open(&path)?).read_to_string(&mut text)?;
+        assert_eq!(text, "id,name\n1,Zoë\n");
+
+        // Concatenated members read as one file
+        let mut bytes = std::fs::read(&path)?;
+        bytes.extend_from_slice(&bytes.clone());
+        std::fs::write(&path, bytes)?;
+        let mut reader = CsvReader::open(&path)?;
+        assert_eq!(reader.rows().count(), 4);
+
+        // ZIP archives written under a .gz name by older versions still open
+        {
+            let mut zip = StreamingZipWriter::with_compression(&path, 6)?;
+            zip.start_entry("data.csv")?;
+            zip.write_data(b"old,zip\n")?;
+            zip.finish()?;
+        }
+        let mut reader = CsvReader::open(&path)?;
+        assert_eq!(
+            reader.read_row()?,
+            Some(vec!["old".to_string(), "zip".to_string()])
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn test_read_windows_1252_and_shift_jis() -> Result<()> {
+        let path = "test_read_cp1252.csv";
+        std::fs::write(path, b"name,price\r\n\"Caf\xe9, cr\xe8me\",\x805\r\n")?;
+        let mut reader = CsvReader::open(path)?.detect_encoding();
+        let rows: Vec<Vec<String>> = reader.rows().collect::<Result<_>>()?;
+        assert_eq!(rows[1], vec!["Caf\u{e9}, cr\u{e8}me", "\u{20ac}5"]);
+        std::fs::remove_file(path).ok();
+
+        let path = "test_read_sjis.csv";
+        std::fs::write(path, b"\x93\xfa\x96\x7b,1\n")?;
+        let mut reader = CsvReader::open(path)?.encoding("shift_jis")?;
+        assert_eq!(
+            reader.read_row()?,
+            Some(vec!["\u{65e5}\u{672c}".to_string(), "1".to_string()])
+        );
+        std::fs::remove_file(path).ok();
+
+        assert!(CsvReader::open("Cargo.toml")?.encoding("klingon").is_err());
+        Ok(())
+    }
 }
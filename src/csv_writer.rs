@@ -1,12 +1,16 @@
 //! CSV file writing with streaming support and compression
 
-use crate::csv::{CompressionMethod, CsvEncoder};
+use crate::csv::{compression_for_path, is_gzip, is_tsv, CompressionMethod, CsvEncoder};
 use crate::error::{ExcelError, Result};
 use crate::fast_writer::StreamingZipWriter;
-use crate::types::CellValue;
+use crate::report::{self, Checksum, ChecksumSetting, HashingWriter, WriteReport};
+use crate::types::{CellValue, CompressionProfile};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fmt::Display;
 use std::fs::File;
-use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 /// CSV file writer with streaming capabilities and compression support
 ///
@@ -29,7 +33,9 @@ use std::path::Path;
 /// Auto-detects compression from file extension:
 /// - `.csv` → Uncompressed
 /// - `.csv.zst` or `.csv.zip` → Zstd compression (level 3)
-/// - `.csv.gz` → Deflate/Gzip compression (level 6)
+/// - `.csv.gz` → gzip (level 6), readable by `gunzip`
+///
+/// `.tsv` paths (compressed or not) are written tab delimited.
 ///
 /// ```no_run
 /// use excelstream::csv_writer::CsvWriter;
@@ -46,13 +52,12 @@ use std::path::Path;
 /// ).unwrap();
 /// ```
 pub struct CsvWriter {
-    // Dual-mode output
-    zip_writer: Option<StreamingZipWriter<File>>,
-    direct_writer: Option<BufWriter<File>>,
+    output: TextOutput,
 
     // State
     row_count: u64,
     buffer: Vec<u8>,
+    scratch: String,
 
     // Configuration
     delimiter: u8,
@@ -66,7 +71,8 @@ impl CsvWriter {
     /// # File Extensions
     /// - `.csv` → Uncompressed
     /// - `.csv.zst` or `.csv.zip` → Zstd compression (level 3)
-    /// - `.csv.gz` → Deflate compression (level 6)
+    /// - `.csv.gz` → gzip (level 6)
+    /// - `.tsv`, `.tsv.zst`, `.tsv.zip`, `.tsv.gz` → same, tab delimited
     ///
     /// # Examples
     ///
@@ -84,26 +90,39 @@ impl CsvWriter {
     /// ```
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path_ref = path.as_ref();
-        let path_str = path_ref.to_str().unwrap_or("");
+        Ok(Self::from_output(
+            TextOutput::create(path_ref, "csv")?,
+            path_ref,
+        ))
+    }
 
-        if path_str.ends_with(".csv.zst") || path_str.ends_with(".csv.zip") {
-            Self::with_compression(path_ref, CompressionMethod::Zstd, 3)
-        } else if path_str.ends_with(".csv.gz") {
-            Self::with_compression(path_ref, CompressionMethod::Deflate, 6)
-        } else {
-            // Plain CSV - direct file write
-            let file = File::create(path_ref)
-                .map_err(|e| ExcelError::WriteError(format!("Failed to create CSV file: {}", e)))?;
+    /// Create a tab-separated writer, whatever the file extension
+    ///
+    /// Compression is still picked from the extension as in [`new`](Self::new).
+    /// Fields containing tabs, quotes or newlines are quoted.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::csv_writer::CsvWriter;
+    ///
+    /// let mut writer = CsvWriter::new_tsv("export.txt").unwrap();
+    /// writer.write_row(["id", "name"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn new_tsv<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(path)?.delimiter(b'\t'))
+    }
 
-            Ok(CsvWriter {
-                zip_writer: None,
-                direct_writer: Some(BufWriter::new(file)),
-                row_count: 0,
-                buffer: Vec::with_capacity(4096),
-                delimiter: b',',
-                quote_char: b'"',
-                line_ending: b"\n",
-            })
+    fn from_output(output: TextOutput, path: &Path) -> Self {
+        CsvWriter {
+            output,
+            row_count: 0,
+            buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
+            delimiter: if is_tsv(path) { b'\t' } else { b',' },
+            quote_char: b'"',
+            line_ending: b"\n",
         }
     }
 
@@ -135,37 +154,39 @@ impl CsvWriter {
         level: u32,
     ) -> Result<Self> {
         let path_ref = path.as_ref();
+        let output = TextOutput::with_compression(path_ref, method, level, "csv")?;
+        Ok(Self::from_output(output, path_ref))
+    }
 
-        // Create ZIP with single CSV entry
-        let mut zip = StreamingZipWriter::with_method(path_ref, method, level)
-            .map_err(|e| ExcelError::WriteError(format!("Failed to create ZIP writer: {}", e)))?;
-
-        // Entry name: extract from path or use "data.csv"
-        let entry_name = path_ref
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .map(|s| {
-                // Remove .zip/.zst/.gz extension if present
-                let clean = s
-                    .trim_end_matches(".csv")
-                    .trim_end_matches(".zst")
-                    .trim_end_matches(".gz");
-                format!("{}.csv", clean)
-            })
-            .unwrap_or_else(|| "data.csv".to_string());
-
-        zip.start_entry(&entry_name)
-            .map_err(|e| ExcelError::WriteError(format!("Failed to start ZIP entry: {}", e)))?;
-
-        Ok(CsvWriter {
-            zip_writer: Some(zip),
-            direct_writer: None,
-            row_count: 0,
-            buffer: Vec::with_capacity(4096),
-            delimiter: b',',
-            quote_char: b'"',
-            line_ending: b"\n",
-        })
+    /// Create a compressed CSV writer using a [`CompressionProfile`]
+    ///
+    /// `.gz` paths (in any case) are gzipped, everything else uses Zstd
+    /// (Deflate without the `zstd` feature), each at the profile's level.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::csv_writer::CsvWriter;
+    /// use excelstream::CompressionProfile;
+    ///
+    /// let mut writer =
+    ///     CsvWriter::with_compression_profile("archive.csv.zst", CompressionProfile::Smallest)
+    ///         .unwrap();
+    /// ```
+    pub fn with_compression_profile<P: AsRef<Path>>(
+        path: P,
+        profile: CompressionProfile,
+    ) -> Result<Self> {
+        let path_ref = path.as_ref();
+        #[cfg(feature = "zstd")]
+        if !is_gzip(path_ref) {
+            return Self::with_compression(path_ref, CompressionMethod::Zstd, profile.zstd_level());
+        }
+        Self::with_compression(
+            path_ref,
+            CompressionMethod::Deflate,
+            profile.deflate_level(),
+        )
     }
 
     /// Set custom delimiter (builder pattern)
@@ -212,22 +233,44 @@ impl CsvWriter {
 
         // Encode row using CSV encoder
         let encoder = CsvEncoder::new(self.delimiter, self.quote_char);
-        let fields: Vec<String> = data.into_iter().map(|s| s.as_ref().to_string()).collect();
-        let refs: Vec<&str> = fields.iter().map(|s| s.as_str()).collect();
+        encoder.encode_fields(data, &mut self.buffer);
+        self.buffer.extend_from_slice(self.line_ending);
+
+        self.flush_row()
+    }
 
-        encoder.encode_row(&refs, &mut self.buffer);
+    /// Write a row of any `Display` values (numbers, dates, custom types...)
+    ///
+    /// Fields are formatted into a reused scratch buffer, so no intermediate
+    /// `Vec<String>` is allocated per row.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::csv_writer::CsvWriter;
+    ///
+    /// let mut writer = CsvWriter::new("data.csv").unwrap();
+    /// writer.write_row_display([1, 2, 3]).unwrap();
+    /// writer.write_row_display([1.5, 2.25]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_row_display<I>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.buffer.clear();
+
+        let encoder = CsvEncoder::new(self.delimiter, self.quote_char);
+        encoder.encode_display(data, &mut self.scratch, &mut self.buffer);
         self.buffer.extend_from_slice(self.line_ending);
 
-        // Write to output
-        if let Some(ref mut zip) = self.zip_writer {
-            zip.write_data(&self.buffer)
-                .map_err(|e| ExcelError::WriteError(format!("Failed to write to ZIP: {}", e)))?;
-        } else if let Some(ref mut writer) = self.direct_writer {
-            writer
-                .write_all(&self.buffer)
-                .map_err(|e| ExcelError::WriteError(format!("Failed to write to file: {}", e)))?;
-        }
+        self.flush_row()
+    }
 
+    /// Write the encoded row in `self.buffer` to the output
+    fn flush_row(&mut self) -> Result<()> {
+        self.output.write_all(&self.buffer)?;
         self.row_count += 1;
         Ok(())
     }
@@ -250,9 +293,7 @@ impl CsvWriter {
     /// ]).unwrap();
     /// ```
     pub fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
-        let strings: Vec<String> = cells.iter().map(|c| c.as_string()).collect();
-        let refs: Vec<&str> = strings.iter().map(|s| s.as_str()).collect();
-        self.write_row(refs)
+        self.write_row_display(cells)
     }
 
     /// Write multiple rows at once
@@ -300,16 +341,158 @@ impl CsvWriter {
     /// writer.write_row(&["Name", "Age"]).unwrap();
     /// writer.save().unwrap();
     /// ```
-    pub fn save(mut self) -> Result<()> {
+    pub fn save(self) -> Result<()> {
+        self.save_with_report().map(|_| ())
+    }
+
+    /// Compute a checksum of the output file, returned by
+    /// [`save_with_report`](Self::save_with_report)
+    ///
+    /// Plain CSV is hashed while it is written; compressed output is read
+    /// back once after the archive is finished.
+    pub fn set_checksum(&mut self, checksum: Checksum) {
+        self.output.set_checksum(checksum);
+    }
+
+    /// Save the file and report its size and checksum
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::csv_writer::CsvWriter;
+    /// use excelstream::Checksum;
+    ///
+    /// let mut writer = CsvWriter::new("output.csv").unwrap();
+    /// writer.set_checksum(Checksum::Crc32);
+    /// writer.write_row(&["Name", "Age"]).unwrap();
+    /// let report = writer.save_with_report().unwrap();
+    /// println!("{} bytes, crc32 {}", report.bytes_written, report.digest.unwrap());
+    /// ```
+    pub fn save_with_report(mut self) -> Result<WriteReport> {
+        self.output.finish()
+    }
+}
+
+/// Plain or compressed output file
+///
+/// Shared by [`CsvWriter`] and [`FixedWidthWriter`](crate::csv::FixedWidthWriter).
+/// Deflated `.gz` paths get a gzip stream; other compressed output is a ZIP
+/// archive holding a single entry.
+pub(crate) struct TextOutput {
+    // One of three outputs
+    zip_writer: Option<StreamingZipWriter<File>>,
+    gzip_writer: Option<GzEncoder<HashingWriter>>,
+    direct_writer: Option<HashingWriter>,
+    // Compressed output is hashed from disk once the archive is finished
+    zip_path: Option<PathBuf>,
+    checksum: ChecksumSetting,
+}
+
+impl TextOutput {
+    /// Create `path`, compressed according to its extension
+    pub(crate) fn create(path: &Path, default_ext: &str) -> Result<Self> {
+        if let Some((method, level)) = compression_for_path(path) {
+            return Self::with_compression(path, method, level, default_ext);
+        }
+
+        let checksum = ChecksumSetting::default();
+        let file = HashingWriter::create(path, checksum.clone()).map_err(|e| {
+            ExcelError::WriteError(format!("Failed to create {}: {}", path.display(), e))
+        })?;
+        Ok(TextOutput {
+            zip_writer: None,
+            gzip_writer: None,
+            direct_writer: Some(file),
+            zip_path: None,
+            checksum,
+        })
+    }
+
+    /// Create a gzip file (Deflate and a `.gz` path), otherwise a ZIP archive
+    /// with one entry named after the file: `data.csv.zst` holds `data.csv`,
+    /// `data.zst` holds `data.<default_ext>`
+    pub(crate) fn with_compression(
+        path: &Path,
+        method: CompressionMethod,
+        level: u32,
+        default_ext: &str,
+    ) -> Result<Self> {
+        if matches!(method, CompressionMethod::Deflate) && is_gzip(path) {
+            let checksum = ChecksumSetting::default();
+            let file = HashingWriter::create(path, checksum.clone()).map_err(|e| {
+                ExcelError::WriteError(format!("Failed to create {}: {}", path.display(), e))
+            })?;
+            return Ok(TextOutput {
+                zip_writer: None,
+                gzip_writer: Some(GzEncoder::new(file, Compression::new(level.min(9)))),
+                direct_writer: None,
+                zip_path: None,
+                checksum,
+            });
+        }
+
+        let mut zip = StreamingZipWriter::with_method(path, method, level)
+            .map_err(|e| ExcelError::WriteError(format!("Failed to create ZIP writer: {}", e)))?;
+
+        let entry_name = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(stem) if Path::new(stem).extension().is_some() => stem.to_string(),
+            Some(stem) => format!("{}.{}", stem, default_ext),
+            None => format!("data.{}", default_ext),
+        };
+        zip.start_entry(&entry_name)
+            .map_err(|e| ExcelError::WriteError(format!("Failed to start ZIP entry: {}", e)))?;
+
+        Ok(TextOutput {
+            zip_writer: Some(zip),
+            gzip_writer: None,
+            direct_writer: None,
+            zip_path: Some(path.to_path_buf()),
+            checksum: ChecksumSetting::default(),
+        })
+    }
+
+    pub(crate) fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        if let Some(ref mut zip) = self.zip_writer {
+            zip.write_data(bytes)
+                .map_err(|e| ExcelError::WriteError(format!("Failed to write to ZIP: {}", e)))?;
+        } else if let Some(ref mut gzip) = self.gzip_writer {
+            gzip.write_all(bytes)
+                .map_err(|e| ExcelError::WriteError(format!("Failed to write to gzip: {}", e)))?;
+        } else if let Some(ref mut writer) = self.direct_writer {
+            writer
+                .write_all(bytes)
+                .map_err(|e| ExcelError::WriteError(format!("Failed to write to file: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_checksum(&mut self, checksum: Checksum) {
+        self.checksum.set(Some(checksum));
+    }
+
+    /// Finish the file and report its size and checksum
+    pub(crate) fn finish(&mut self) -> Result<WriteReport> {
         if let Some(zip) = self.zip_writer.take() {
             zip.finish()
                 .map_err(|e| ExcelError::WriteError(format!("Failed to finish ZIP: {}", e)))?;
-        } else if let Some(mut writer) = self.direct_writer.take() {
+            let path = self.zip_path.take().unwrap_or_default();
+            report::read_back(&path, self.checksum.get())
+        } else if let Some(gzip) = self.gzip_writer.take() {
+            let writer = gzip
+                .finish()
+                .map_err(|e| ExcelError::WriteError(format!("Failed to finish gzip: {}", e)))?;
+            writer
+                .finish()
+                .map_err(|e| ExcelError::WriteError(format!("Failed to flush file: {}", e)))
+        } else if let Some(writer) = self.direct_writer.take() {
             writer
-                .flush()
-                .map_err(|e| ExcelError::WriteError(format!("Failed to flush file: {}", e)))?;
+                .finish()
+                .map_err(|e| ExcelError::WriteError(format!("Failed to flush file: {}", e)))
+        } else {
+            Err(ExcelError::WriteError(
+                "Writer already finished".to_string(),
+            ))
         }
-        Ok(())
     }
 }
 
@@ -362,6 +545,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_write_row_display() -> Result<()> {
+        let path = "test_display.csv";
+        {
+            let mut writer = CsvWriter::new(path)?;
+            writer.write_row_display([1, 22, 333])?;
+            writer.write_row_display(["x,y"].iter())?;
+            writer.save()?;
+        }
+
+        let mut content = String::new();
+        File::open(path)?.read_to_string(&mut content)?;
+        assert_eq!(content, "1,22,333\n\"x,y\"\n");
+
+        std::fs::remove_file(path).ok();
+        Ok(())
+    }
+
     #[test]
     fn test_edge_cases() -> Result<()> {
         let path = "test_edge.csv";
@@ -381,4 +582,40 @@ mod tests {
         std::fs::remove_file(path).ok();
         Ok(())
     }
+
+    #[test]
+    fn test_save_with_report() -> Result<()> {
+        use crate::report::{Checksum, Digest};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        for name in ["report.csv", "report.csv.gz"] {
+            let path = dir.path().join(name);
+            let mut writer = CsvWriter::new(&path)?;
+            writer.set_checksum(Checksum::Crc32);
+            writer.write_row(["Name", "Age"])?;
+            writer.write_row(["Alice", "30"])?;
+            let report = writer.save_with_report()?;
+
+            let bytes = std::fs::read(&path)?;
+            assert_eq!(report.bytes_written, bytes.len() as u64);
+            assert_eq!(report.digest, Some(Digest::Crc32(crc32fast::hash(&bytes))));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_compression_profile_gzip_suffix_any_case() -> Result<()> {
+        let dir = tempfile::TempDir::new().unwrap();
+        for name in ["report.CSV.GZ", "report.gz"] {
+            let path = dir.path().join(name);
+            let mut writer =
+                CsvWriter::with_compression_profile(&path, CompressionProfile::Fastest)?;
+            writer.write_row(["Name", "Age"])?;
+            writer.save()?;
+
+            let bytes = std::fs::read(&path)?;
+            assert_eq!(&bytes[..2], &[0x1f, 0x8b], "{} is not gzip", name);
+        }
+        Ok(())
+    }
 }
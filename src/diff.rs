@@ -0,0 +1,497 @@
+//! Cell-by-cell comparison of two workbooks
+//!
+//! [`compare`] streams the sheets of both files side by side, one row of
+//! each at a time, and reports the cells that were added, removed or
+//! changed. Rows line up by their row number in the sheet, so a blank row
+//! inserted in one file shows up as changes below it rather than being
+//! skipped over. Numbers can be compared with a tolerance, for reports
+//! whose last digits move between runs.
+//!
+//! ```no_run
+//! use excelstream::diff::{compare, DiffOptions};
+//!
+//! let report = compare(
+//!     "previous/report.xlsx",
+//!     "report.xlsx",
+//!     DiffOptions::new().tolerance(1e-9),
+//! )?;
+//! if !report.is_identical() {
+//!     eprintln!("{}", report);
+//!     std::process::exit(1);
+//! }
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+
+use crate::error::{ExcelError, Result};
+use crate::streaming_reader::{RowIterator, StreamingReader};
+use crate::types::{Cell, CellValue};
+use std::fmt;
+use std::path::Path;
+
+/// Changes kept per sheet by default; the rest are only counted
+pub const DEFAULT_MAX_CHANGES: usize = 1_000;
+
+/// What [`compare`] looks at and how numbers are matched
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    tolerance: f64,
+    relative_tolerance: f64,
+    max_changes: usize,
+    sheets: Vec<String>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            tolerance: 0.0,
+            relative_tolerance: 0.0,
+            max_changes: DEFAULT_MAX_CHANGES,
+            sheets: Vec::new(),
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Exact comparison of every sheet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Numbers that differ by at most `tolerance` are equal
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.abs();
+        self
+    }
+
+    /// Numbers that differ by at most `tolerance` times the larger of the
+    /// two are equal
+    pub fn relative_tolerance(mut self, tolerance: f64) -> Self {
+        self.relative_tolerance = tolerance.abs();
+        self
+    }
+
+    /// Changes listed per sheet (default: [`DEFAULT_MAX_CHANGES`]); later
+    /// ones are counted in [`SheetDiff::change_count`] only
+    pub fn max_changes_per_sheet(mut self, max: usize) -> Self {
+        self.max_changes = max;
+        self
+    }
+
+    /// Compare only the sheet `name`; call again for more sheets
+    pub fn sheet(mut self, name: &str) -> Self {
+        self.sheets.push(name.to_string());
+        self
+    }
+
+    fn same(&self, a: &CellValue, b: &CellValue) -> bool {
+        match (number(a), number(b)) {
+            (Some(x), Some(y)) => {
+                let allowed = self
+                    .tolerance
+                    .max(self.relative_tolerance * x.abs().max(y.abs()));
+                x == y || (x - y).abs() <= allowed
+            }
+            _ => a.as_string() == b.as_string(),
+        }
+    }
+}
+
+/// Numeric value of number and date cells; text is compared as text
+fn number(value: &CellValue) -> Option<f64> {
+    match value {
+        CellValue::Int(_) | CellValue::Float(_) | CellValue::DateTime(_) => value.as_f64(),
+        #[cfg(feature = "decimal")]
+        CellValue::Decimal(_) => value.as_f64(),
+        _ => None,
+    }
+}
+
+fn is_empty(value: &CellValue) -> bool {
+    match value {
+        CellValue::Empty => true,
+        CellValue::String(s) => s.is_empty(),
+        _ => false,
+    }
+}
+
+/// How a cell differs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Empty in the first workbook only
+    Added,
+    /// Empty in the second workbook only
+    Removed,
+    /// Different values in both
+    Changed,
+}
+
+/// A cell that differs between the two workbooks
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellChange {
+    pub kind: ChangeKind,
+    /// 0-based row and column
+    pub row: u32,
+    pub col: u32,
+    /// Value in the first workbook (`Empty` when added)
+    pub old: CellValue,
+    /// Value in the second workbook (`Empty` when removed)
+    pub new: CellValue,
+}
+
+impl CellChange {
+    /// Cell reference, e.g. `B4`
+    pub fn reference(&self) -> String {
+        format!("{}{}", Cell::col_to_letter(self.col), self.row + 1)
+    }
+}
+
+impl fmt::Display for CellChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ChangeKind::Added => write!(f, "{} added: {}", self.reference(), self.new),
+            ChangeKind::Removed => write!(f, "{} removed: {}", self.reference(), self.old),
+            ChangeKind::Changed => {
+                write!(
+                    f,
+                    "{} changed: {} -> {}",
+                    self.reference(),
+                    self.old,
+                    self.new
+                )
+            }
+        }
+    }
+}
+
+/// Whether a sheet is in both workbooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetStatus {
+    /// In both; its cells were compared
+    Compared,
+    /// Only in the first workbook
+    OnlyInFirst,
+    /// Only in the second workbook
+    OnlyInSecond,
+}
+
+/// Differences found in one sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetDiff {
+    pub name: String,
+    pub status: SheetStatus,
+    /// The first [`DiffOptions::max_changes_per_sheet`] changes, in row order
+    pub changes: Vec<CellChange>,
+    /// Number of changed cells, including those not listed
+    pub change_count: u64,
+}
+
+impl SheetDiff {
+    /// True if the sheet is in both workbooks with the same cells
+    pub fn is_identical(&self) -> bool {
+        self.status == SheetStatus::Compared && self.change_count == 0
+    }
+
+    fn push(&mut self, change: CellChange, max_changes: usize) {
+        if self.changes.len() < max_changes {
+            self.changes.push(change);
+        }
+        self.change_count += 1;
+    }
+}
+
+/// Result of [`compare`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffReport {
+    /// Sheets of the first workbook in order, then those only in the second
+    pub sheets: Vec<SheetDiff>,
+}
+
+impl DiffReport {
+    /// True if both workbooks have the same sheets and cells
+    pub fn is_identical(&self) -> bool {
+        self.sheets.iter().all(SheetDiff::is_identical)
+    }
+
+    /// Changed cells over all compared sheets
+    pub fn change_count(&self) -> u64 {
+        self.sheets.iter().map(|sheet| sheet.change_count).sum()
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_identical() {
+            return write!(f, "no differences");
+        }
+        let mut first = true;
+        for sheet in self.sheets.iter().filter(|sheet| !sheet.is_identical()) {
+            if !first {
+                writeln!(f)?;
+            }
+            first = false;
+            match sheet.status {
+                SheetStatus::OnlyInFirst => write!(f, "sheet '{}': only in first", sheet.name)?,
+                SheetStatus::OnlyInSecond => write!(f, "sheet '{}': only in second", sheet.name)?,
+                SheetStatus::Compared => {
+                    write!(f, "sheet '{}': {} changed", sheet.name, sheet.change_count)?;
+                    for change in &sheet.changes {
+                        write!(f, "\n  {}", change)?;
+                    }
+                    let unlisted = sheet.change_count - sheet.changes.len() as u64;
+                    if unlisted > 0 {
+                        write!(f, "\n  ... and {} more", unlisted)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare the workbooks at `path_a` and `path_b`
+///
+/// Sheets are matched by name. Cells are equal when both are empty, when
+/// both are numbers (or dates) within the tolerances of `options`, or
+/// otherwise when their text is the same.
+pub fn compare<P, Q>(path_a: P, path_b: Q, options: DiffOptions) -> Result<DiffReport>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let mut reader_a = StreamingReader::open(path_a)?;
+    let mut reader_b = StreamingReader::open(path_b)?;
+    let names_a = reader_a.sheet_names();
+    let names_b = reader_b.sheet_names();
+
+    let names: Vec<String> = if options.sheets.is_empty() {
+        let only_b = names_b.iter().filter(|name| !names_a.contains(name));
+        names_a.iter().chain(only_b).cloned().collect()
+    } else {
+        for name in &options.sheets {
+            if !names_a.contains(name) && !names_b.contains(name) {
+                return Err(ExcelError::SheetNotFound {
+                    sheet: name.clone(),
+                    available: names_a.join(", "),
+                });
+            }
+        }
+        options.sheets.clone()
+    };
+
+    let mut report = DiffReport::default();
+    for name in names {
+        let status = match (names_a.contains(&name), names_b.contains(&name)) {
+            (true, false) => SheetStatus::OnlyInFirst,
+            (false, true) => SheetStatus::OnlyInSecond,
+            _ => SheetStatus::Compared,
+        };
+        let mut sheet = SheetDiff {
+            name,
+            status,
+            changes: Vec::new(),
+            change_count: 0,
+        };
+        if status == SheetStatus::Compared {
+            let rows_a = reader_a.stream_rows(&sheet.name)?;
+            let rows_b = reader_b.stream_rows(&sheet.name)?;
+            compare_rows(rows_a, rows_b, &options, &mut sheet)?;
+        }
+        report.sheets.push(sheet);
+    }
+    Ok(report)
+}
+
+/// Walk both sheets in row order, comparing rows with the same number
+fn compare_rows(
+    mut rows_a: RowIterator<'_>,
+    mut rows_b: RowIterator<'_>,
+    options: &DiffOptions,
+    sheet: &mut SheetDiff,
+) -> Result<()> {
+    let mut next_a = next_row(&mut rows_a)?;
+    let mut next_b = next_row(&mut rows_b)?;
+    loop {
+        let row = match (&next_a, &next_b) {
+            (None, None) => return Ok(()),
+            (Some((a, _)), Some((b, _))) => (*a).min(*b),
+            (Some((a, _)), None) => *a,
+            (None, Some((b, _))) => *b,
+        };
+        let cells_a = match next_a.take_if(|(index, _)| *index == row) {
+            Some((_, cells)) => {
+                next_a = next_row(&mut rows_a)?;
+                cells
+            }
+            None => Vec::new(),
+        };
+        let cells_b = match next_b.take_if(|(index, _)| *index == row) {
+            Some((_, cells)) => {
+                next_b = next_row(&mut rows_b)?;
+                cells
+            }
+            None => Vec::new(),
+        };
+        compare_cells(row, cells_a, cells_b, options, sheet);
+    }
+}
+
+/// Next row and its 0-based sheet row number
+fn next_row(rows: &mut RowIterator<'_>) -> Result<Option<(u32, Vec<CellValue>)>> {
+    let Some(cells) = rows.next().transpose()? else {
+        return Ok(None);
+    };
+    Ok(Some((rows.row_number().saturating_sub(1), cells)))
+}
+
+fn compare_cells(
+    row: u32,
+    cells_a: Vec<CellValue>,
+    cells_b: Vec<CellValue>,
+    options: &DiffOptions,
+    sheet: &mut SheetDiff,
+) {
+    let width = cells_a.len().max(cells_b.len());
+    let mut cells_a = cells_a.into_iter();
+    let mut cells_b = cells_b.into_iter();
+    for col in 0..width {
+        let old = cells_a.next().unwrap_or(CellValue::Empty);
+        let new = cells_b.next().unwrap_or(CellValue::Empty);
+        let kind = match (is_empty(&old), is_empty(&new)) {
+            (true, true) => continue,
+            (true, false) => ChangeKind::Added,
+            (false, true) => ChangeKind::Removed,
+            (false, false) if options.same(&old, &new) => continue,
+            (false, false) => ChangeKind::Changed,
+        };
+        let change = CellChange {
+            kind,
+            row,
+            col: col as u32,
+            old,
+            new,
+        };
+        sheet.push(change, options.max_changes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CellValue::{Float, Int};
+    use crate::ExcelWriter;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, sheets: &[(&str, Vec<Vec<CellValue>>)]) {
+        let mut writer = ExcelWriter::new(path).unwrap();
+        for (i, (name, rows)) in sheets.iter().enumerate() {
+            // The writer starts on "Sheet1"
+            if i > 0 {
+                writer.add_sheet(name).unwrap();
+            }
+            for row in rows {
+                writer.write_row_typed(row).unwrap();
+            }
+        }
+        writer.save().unwrap();
+    }
+
+    fn text(s: &str) -> CellValue {
+        CellValue::String(s.to_string())
+    }
+
+    #[test]
+    fn test_compare_workbooks() {
+        let dir = TempDir::new().unwrap();
+        let (a, b) = (dir.path().join("a.xlsx"), dir.path().join("b.xlsx"));
+        write(
+            &a,
+            &[
+                (
+                    "Sheet1",
+                    vec![
+                        vec![text("id"), text("amount"), text("note")],
+                        vec![Int(1), Float(10.0), text("x")],
+                        vec![Int(2), Float(0.1 + 0.2), text("")],
+                        vec![Int(3), Float(5.0), text("gone")],
+                    ],
+                ),
+                ("Old", vec![vec![text("a")]]),
+            ],
+        );
+        write(
+            &b,
+            &[
+                (
+                    "Sheet1",
+                    vec![
+                        vec![text("id"), text("amount"), text("note")],
+                        vec![Int(1), Float(12.0), text("x")],
+                        vec![Int(2), Float(0.3), text("new")],
+                        vec![Int(3), Float(5.0)],
+                        vec![Int(4)],
+                    ],
+                ),
+                ("New", vec![]),
+            ],
+        );
+
+        let report = compare(&a, &b, DiffOptions::new()).unwrap();
+        let sheet1 = &report.sheets[0];
+        let changes: Vec<String> = sheet1.changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            [
+                "B2 changed: 10 -> 12",
+                "B3 changed: 0.30000000000000004 -> 0.3",
+                "C3 added: new",
+                "C4 removed: gone",
+                "A5 added: 4",
+            ]
+        );
+        assert_eq!(report.sheets[1].status, SheetStatus::OnlyInFirst);
+        assert_eq!(report.sheets[2].status, SheetStatus::OnlyInSecond);
+        assert!(!report.is_identical());
+
+        // Float noise within tolerance; one listed change, one counted
+        let report = compare(
+            &a,
+            &b,
+            DiffOptions::new()
+                .tolerance(1e-9)
+                .max_changes_per_sheet(1)
+                .sheet("Sheet1"),
+        )
+        .unwrap();
+        assert_eq!(report.sheets.len(), 1);
+        assert_eq!(report.change_count(), 4);
+        assert_eq!(
+            report.to_string(),
+            "sheet 'Sheet1': 4 changed\n  B2 changed: 10 -> 12\n  ... and 3 more"
+        );
+
+        let same = compare(&a, &a, DiffOptions::new()).unwrap();
+        assert!(same.is_identical());
+        assert_eq!(same.to_string(), "no differences");
+        assert!(compare(&a, &b, DiffOptions::new().sheet("Nope")).is_err());
+    }
+
+    #[test]
+    fn test_rows_line_up_by_row_number() {
+        let dir = TempDir::new().unwrap();
+        let (a, b) = (dir.path().join("a.xlsx"), dir.path().join("b.xlsx"));
+        write(&a, &[("Sheet1", vec![vec![text("a")], vec![text("b")]])]);
+        // A blank row pushes "b" down to row 3
+        write(
+            &b,
+            &[("Sheet1", vec![vec![text("a")], vec![], vec![text("b")]])],
+        );
+
+        let report = compare(&a, &b, DiffOptions::new()).unwrap();
+        let changes: Vec<String> = report.sheets[0]
+            .changes
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(changes, ["A2 removed: b", "A3 added: b"]);
+    }
+}
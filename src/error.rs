@@ -20,6 +20,10 @@ pub enum ExcelError {
     #[error("Sheet '{sheet}' not found. Available sheets: {available}")]
     SheetNotFound { sheet: String, available: String },
 
+    /// Sheet name Excel doesn't accept, see [`sheet_name`](crate::sheet_name)
+    #[error("Invalid sheet name '{name}': {reason}")]
+    InvalidSheetName { name: String, reason: String },
+
     /// Error occurred while writing a row
     #[error("Failed to write row {row} to sheet '{sheet}': {source}")]
     WriteRowError {
@@ -29,13 +33,33 @@ pub enum ExcelError {
         source: Box<ExcelError>,
     },
 
+    /// The package has no part at `path`
+    #[error("ZIP entry not found: {path}")]
+    ZipEntryMissing { path: String },
+
+    /// Worksheet XML that can't be read
+    ///
+    /// `row` is the 1-based row number from the sheet and `col` the 0-based
+    /// position of the cell in the row, as returned by the row iterators.
+    #[error("Malformed XML in sheet '{sheet}'{}: {message}", location(*.row, *.col))]
+    XmlParse {
+        sheet: String,
+        row: Option<u32>,
+        col: Option<u32>,
+        message: String,
+    },
+
+    /// Cell with a `t` attribute that isn't part of SpreadsheetML
+    #[error("Unsupported cell type '{t}' in cell {cell}")]
+    UnsupportedCellType { t: String, cell: String },
+
     /// Invalid cell reference
     #[error("Invalid cell reference: {0}")]
     InvalidCell(String),
 
     /// IO error wrapper
     #[error("IO error: {0}")]
-    IoError(#[from] std::io::Error),
+    IoError(#[source] std::io::Error),
 
     /// Calamine error wrapper
     #[error("Calamine error: {0}")]
@@ -60,6 +84,46 @@ pub enum ExcelError {
     /// ZIP error
     #[error("ZIP error: {0}")]
     ZipError(String),
+
+    /// Write stopped through a `CancellationToken`
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    /// A row or column setting is past column XFD, the last of Excel's
+    /// 16 384 columns
+    #[error("Column {column} is past XFD, the last of Excel's 16384 columns")]
+    TooManyColumns {
+        /// 1-based column that doesn't fit
+        column: u64,
+    },
+
+    /// A file went past one of the reader's
+    /// [`ReadLimits`](crate::streaming_reader::ReadLimits)
+    #[error("{part} exceeds the read limit {limit} = {max}")]
+    LimitExceeded {
+        /// Package part or row that went past the limit
+        part: String,
+        /// Name of the `ReadLimits` field
+        limit: String,
+        max: u64,
+    },
+}
+
+// A reader can only fail with an io::Error, so limit errors found while
+// reading travel inside one and are unwrapped here
+impl From<std::io::Error> for ExcelError {
+    fn from(err: std::io::Error) -> Self {
+        // OS errors carry no inner error to unwrap
+        if err.get_ref().is_none() {
+            return ExcelError::IoError(err);
+        }
+        let kind = err.kind();
+        match err.into_inner().map(|inner| inner.downcast::<ExcelError>()) {
+            Some(Ok(inner)) => *inner,
+            Some(Err(inner)) => ExcelError::IoError(std::io::Error::new(kind, inner)),
+            None => ExcelError::IoError(kind.into()),
+        }
+    }
 }
 
 // Convert s-zip errors to ExcelError for backward compatibility
@@ -68,9 +132,7 @@ impl From<s_zip::SZipError> for ExcelError {
         match err {
             s_zip::SZipError::Io(e) => ExcelError::IoError(e),
             s_zip::SZipError::InvalidFormat(msg) => ExcelError::InvalidFormat(msg),
-            s_zip::SZipError::EntryNotFound(name) => {
-                ExcelError::ReadError(format!("ZIP entry not found: {}", name))
-            }
+            s_zip::SZipError::EntryNotFound(path) => ExcelError::ZipEntryMissing { path },
             s_zip::SZipError::UnsupportedCompression(method) => {
                 ExcelError::NotSupported(format!("Unsupported compression method: {}", method))
             }
@@ -78,4 +140,88 @@ impl From<s_zip::SZipError> for ExcelError {
     }
 }
 
-// Note: std::io::Error is already mapped via the `IoError(#[from] std::io::Error)` variant above.
+/// " at B7", " at row 7" or nothing, for `XmlParse` messages
+fn location(row: Option<u32>, col: Option<u32>) -> String {
+    let column = col.map(crate::types::Cell::col_to_letter);
+    match (row, column) {
+        (Some(row), Some(column)) => format!(" at {}{}", column, row),
+        (Some(row), None) => format!(" at row {}", row),
+        (None, Some(column)) => format!(" in column {}", column),
+        (None, None) => String::new(),
+    }
+}
+
+// Lets ExcelError be the error type of the record serializer
+#[cfg(feature = "serde")]
+impl serde::ser::Error for ExcelError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ExcelError::WriteError(msg.to_string())
+    }
+}
+
+// ...and of the row deserializer
+#[cfg(feature = "serde")]
+impl serde::de::Error for ExcelError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ExcelError::ReadError(msg.to_string())
+    }
+}
+
+// Note: std::io::Error is mapped by the `From` impl above, which unwraps
+// ExcelErrors carried inside one and wraps anything else in `IoError`.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_parse_location() {
+        let error = ExcelError::XmlParse {
+            sheet: "Data".to_string(),
+            row: Some(7),
+            col: Some(27),
+            message: "unterminated <c>".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Malformed XML in sheet 'Data' at AB7: unterminated <c>"
+        );
+        assert_eq!(location(Some(3), None), " at row 3");
+        assert_eq!(location(None, None), "");
+    }
+
+    #[test]
+    fn test_limit_error_through_io() {
+        let limit = ExcelError::LimitExceeded {
+            part: "xl/sharedStrings.xml".to_string(),
+            limit: "max_entry_size".to_string(),
+            max: 1024,
+        };
+        let message = limit.to_string();
+        let error = ExcelError::from(std::io::Error::other(limit));
+        assert!(matches!(error, ExcelError::LimitExceeded { max: 1024, .. }));
+        assert_eq!(
+            message,
+            "xl/sharedStrings.xml exceeds the read limit max_entry_size = 1024"
+        );
+
+        let error = ExcelError::from(std::io::Error::other("disk on fire"));
+        assert!(matches!(&error, ExcelError::IoError(e) if e.kind() == std::io::ErrorKind::Other));
+        assert_eq!(error.to_string(), "IO error: disk on fire");
+    }
+
+    #[test]
+    fn test_source_chain() {
+        use std::error::Error;
+
+        let error = ExcelError::WriteRowError {
+            row: 2,
+            sheet: "Sheet1".to_string(),
+            source: Box::new(ExcelError::ZipEntryMissing {
+                path: "xl/workbook.xml".to_string(),
+            }),
+        };
+        let source = error.source().unwrap().to_string();
+        assert_eq!(source, "ZIP entry not found: xl/workbook.xml");
+    }
+}
@@ -0,0 +1,296 @@
+//! Chart parts for ZeroTempWorkbook
+//!
+//! Each chart is a `xl/charts/chartN.xml` part. The charts of one worksheet
+//! share a `xl/drawings/drawingN.xml` that anchors them to cells, and the
+//! worksheet points at that drawing through its relationships.
+
+use super::zero_temp_workbook::ZeroTempWorkbook;
+use crate::types::{ChartOptions, ChartType};
+
+const CHART_NS: &str = "http://schemas.openxmlformats.org/drawingml/2006/chart";
+// Axis ids only need to be unique within a chart
+const CAT_AX_ID: &str = "500000001";
+const VAL_AX_ID: &str = "500000002";
+
+/// Cell block holding chart data, 1-based and inclusive
+///
+/// The first column holds the categories, the first row the series names and
+/// every further column one series.
+#[derive(Debug, Clone)]
+pub(crate) struct ChartRange {
+    /// Sheet name as used in formulas, quoted when needed
+    pub sheet: String,
+    pub first_col: u32,
+    pub first_row: u32,
+    pub last_col: u32,
+    pub last_row: u32,
+}
+
+impl ChartRange {
+    /// Absolute reference like `Sheet1!$B$2:$B$10`
+    fn reference(&self, col: u32, first_row: u32, last_row: u32) -> String {
+        let mut col_letters = Vec::with_capacity(3);
+        ZeroTempWorkbook::push_column_letter(&mut col_letters, col);
+        let col_letters = String::from_utf8(col_letters).unwrap_or_default();
+
+        if first_row == last_row {
+            format!("{}!${}${}", self.sheet, col_letters, first_row)
+        } else {
+            format!(
+                "{}!${}${}:${}${}",
+                self.sheet, col_letters, first_row, col_letters, last_row
+            )
+        }
+    }
+}
+
+/// Quote a sheet name for use in a formula when it is not a plain identifier
+pub(crate) fn formula_sheet_name(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        name.to_string()
+    } else {
+        format!("'{}'", name.replace('\'', "''"))
+    }
+}
+
+/// A chart waiting to be written when its worksheet is finished
+#[derive(Debug, Clone)]
+pub(crate) struct Chart {
+    pub chart_type: ChartType,
+    pub range: ChartRange,
+    /// Top-left anchor cell, 0-based (column, row)
+    pub anchor: (u32, u32),
+    pub options: ChartOptions,
+}
+
+/// `<c:title>` with plain rich text
+fn push_title(xml: &mut Vec<u8>, title: &str) {
+    xml.extend_from_slice(b"<c:title><c:tx><c:rich><a:bodyPr/><a:p><a:r><a:t>");
+    ZeroTempWorkbook::write_escaped(xml, title);
+    xml.extend_from_slice(b"</a:t></a:r></a:p></c:rich></c:tx><c:overlay val=\"0\"/></c:title>");
+}
+
+/// `xl/charts/chartN.xml`
+pub(crate) fn chart_xml(chart: &Chart) -> Vec<u8> {
+    let range = &chart.range;
+    let options = &chart.options;
+    let mut xml = Vec::with_capacity(2048);
+
+    xml.extend_from_slice(
+        br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<c:chartSpace xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><c:chart>"#,
+    );
+    match &options.title {
+        Some(title) => push_title(&mut xml, title),
+        None => xml.extend_from_slice(b"<c:autoTitleDeleted val=\"1\"/>"),
+    }
+    xml.extend_from_slice(b"<c:plotArea><c:layout/>");
+
+    match chart.chart_type {
+        ChartType::Bar | ChartType::Column => {
+            xml.extend_from_slice(b"<c:barChart><c:barDir val=\"");
+            xml.extend_from_slice(if chart.chart_type == ChartType::Bar {
+                b"bar"
+            } else {
+                b"col"
+            });
+            xml.extend_from_slice(b"\"/><c:grouping val=\"clustered\"/><c:varyColors val=\"0\"/>");
+        }
+        ChartType::Line => {
+            xml.extend_from_slice(
+                b"<c:lineChart><c:grouping val=\"standard\"/><c:varyColors val=\"0\"/>",
+            );
+        }
+        ChartType::Pie => {
+            xml.extend_from_slice(b"<c:pieChart><c:varyColors val=\"1\"/>");
+        }
+    }
+
+    // A pie only shows one series
+    let last_series_col = if chart.chart_type == ChartType::Pie {
+        range.first_col + 1
+    } else {
+        range.last_col
+    };
+    let data_first_row = range.first_row + 1;
+    let categories = range.reference(range.first_col, data_first_row, range.last_row);
+
+    let mut num_buffer = itoa::Buffer::new();
+    for (i, col) in (range.first_col + 1..=last_series_col).enumerate() {
+        let idx = num_buffer.format(i).to_string();
+        xml.extend_from_slice(b"<c:ser><c:idx val=\"");
+        xml.extend_from_slice(idx.as_bytes());
+        xml.extend_from_slice(b"\"/><c:order val=\"");
+        xml.extend_from_slice(idx.as_bytes());
+        xml.extend_from_slice(b"\"/><c:tx><c:strRef><c:f>");
+        ZeroTempWorkbook::write_escaped(
+            &mut xml,
+            &range.reference(col, range.first_row, range.first_row),
+        );
+        xml.extend_from_slice(b"</c:f></c:strRef></c:tx>");
+        if chart.chart_type == ChartType::Line {
+            xml.extend_from_slice(b"<c:marker><c:symbol val=\"circle\"/></c:marker>");
+        }
+        xml.extend_from_slice(b"<c:cat><c:strRef><c:f>");
+        ZeroTempWorkbook::write_escaped(&mut xml, &categories);
+        xml.extend_from_slice(b"</c:f></c:strRef></c:cat>");
+        xml.extend_from_slice(b"<c:val><c:numRef><c:f>");
+        ZeroTempWorkbook::write_escaped(
+            &mut xml,
+            &range.reference(col, data_first_row, range.last_row),
+        );
+        xml.extend_from_slice(b"</c:f></c:numRef></c:val></c:ser>");
+    }
+
+    match chart.chart_type {
+        ChartType::Bar | ChartType::Column => {
+            xml.extend_from_slice(b"<c:gapWidth val=\"150\"/>");
+            push_axis_ids(&mut xml);
+            xml.extend_from_slice(b"</c:barChart>");
+        }
+        ChartType::Line => {
+            xml.extend_from_slice(b"<c:marker val=\"1\"/>");
+            push_axis_ids(&mut xml);
+            xml.extend_from_slice(b"</c:lineChart>");
+        }
+        ChartType::Pie => {
+            xml.extend_from_slice(b"<c:firstSliceAng val=\"0\"/></c:pieChart>");
+        }
+    }
+
+    if chart.chart_type != ChartType::Pie {
+        // Horizontal bars put categories on the left and values at the bottom
+        let (cat_pos, val_pos) = if chart.chart_type == ChartType::Bar {
+            (b"l", b"b")
+        } else {
+            (b"b", b"l")
+        };
+        push_axis(&mut xml, false, cat_pos, options.x_axis_title.as_deref());
+        push_axis(&mut xml, true, val_pos, options.y_axis_title.as_deref());
+    }
+
+    xml.extend_from_slice(b"</c:plotArea>");
+    if options.legend {
+        xml.extend_from_slice(
+            b"<c:legend><c:legendPos val=\"r\"/><c:overlay val=\"0\"/></c:legend>",
+        );
+    }
+    xml.extend_from_slice(b"<c:plotVisOnly val=\"1\"/></c:chart></c:chartSpace>");
+    xml
+}
+
+fn push_axis_ids(xml: &mut Vec<u8>) {
+    for id in [CAT_AX_ID, VAL_AX_ID] {
+        xml.extend_from_slice(b"<c:axId val=\"");
+        xml.extend_from_slice(id.as_bytes());
+        xml.extend_from_slice(b"\"/>");
+    }
+}
+
+/// `<c:catAx>` or `<c:valAx>`
+fn push_axis(xml: &mut Vec<u8>, value_axis: bool, pos: &[u8], title: Option<&str>) {
+    let (tag, id, cross) = if value_axis {
+        (b"valAx".as_slice(), VAL_AX_ID, CAT_AX_ID)
+    } else {
+        (b"catAx".as_slice(), CAT_AX_ID, VAL_AX_ID)
+    };
+
+    xml.extend_from_slice(b"<c:");
+    xml.extend_from_slice(tag);
+    xml.extend_from_slice(b"><c:axId val=\"");
+    xml.extend_from_slice(id.as_bytes());
+    xml.extend_from_slice(
+        b"\"/><c:scaling><c:orientation val=\"minMax\"/></c:scaling><c:delete val=\"0\"/><c:axPos val=\"",
+    );
+    xml.extend_from_slice(pos);
+    xml.extend_from_slice(b"\"/>");
+    if value_axis {
+        xml.extend_from_slice(b"<c:majorGridlines/>");
+    }
+    if let Some(title) = title {
+        push_title(xml, title);
+    }
+    xml.extend_from_slice(b"<c:crossAx val=\"");
+    xml.extend_from_slice(cross.as_bytes());
+    xml.extend_from_slice(b"\"/></c:");
+    xml.extend_from_slice(tag);
+    xml.push(b'>');
+}
+
+/// `xl/drawings/drawingN.xml` anchoring `charts` to their cells
+///
+/// Chart `i` is referenced as relationship `rId{i + 1}`.
+pub(crate) fn drawing_xml(charts: &[Chart]) -> Vec<u8> {
+    let mut xml = Vec::with_capacity(256 + charts.len() * 768);
+    xml.extend_from_slice(
+        br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<xdr:wsDr xmlns:xdr="http://schemas.openxmlformats.org/drawingml/2006/spreadsheetDrawing" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main">"#,
+    );
+
+    let mut num_buffer = itoa::Buffer::new();
+    for (i, chart) in charts.iter().enumerate() {
+        let (col, row) = chart.anchor;
+        xml.extend_from_slice(b"<xdr:twoCellAnchor>");
+        for (tag, col, row) in [
+            (b"from".as_slice(), col, row),
+            (
+                b"to".as_slice(),
+                col + chart.options.width_cols,
+                row + chart.options.height_rows,
+            ),
+        ] {
+            xml.extend_from_slice(b"<xdr:");
+            xml.extend_from_slice(tag);
+            xml.extend_from_slice(b"><xdr:col>");
+            xml.extend_from_slice(num_buffer.format(col).as_bytes());
+            xml.extend_from_slice(b"</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>");
+            xml.extend_from_slice(num_buffer.format(row).as_bytes());
+            xml.extend_from_slice(b"</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:");
+            xml.extend_from_slice(tag);
+            xml.push(b'>');
+        }
+
+        xml.extend_from_slice(
+            b"<xdr:graphicFrame macro=\"\"><xdr:nvGraphicFramePr><xdr:cNvPr id=\"",
+        );
+        xml.extend_from_slice(num_buffer.format(i + 2).as_bytes());
+        xml.extend_from_slice(b"\" name=\"Chart ");
+        xml.extend_from_slice(num_buffer.format(i + 1).as_bytes());
+        xml.extend_from_slice(
+            br#""/><xdr:cNvGraphicFramePr/></xdr:nvGraphicFramePr><xdr:xfrm><a:off x="0" y="0"/><a:ext cx="0" cy="0"/></xdr:xfrm><a:graphic><a:graphicData uri=""#,
+        );
+        xml.extend_from_slice(CHART_NS.as_bytes());
+        xml.extend_from_slice(b"\"><c:chart xmlns:c=\"");
+        xml.extend_from_slice(CHART_NS.as_bytes());
+        xml.extend_from_slice(
+            b"\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\" r:id=\"rId",
+        );
+        xml.extend_from_slice(num_buffer.format(i + 1).as_bytes());
+        xml.extend_from_slice(
+            b"\"/></a:graphicData></a:graphic></xdr:graphicFrame><xdr:clientData/></xdr:twoCellAnchor>",
+        );
+    }
+    xml.extend_from_slice(b"</xdr:wsDr>");
+    xml
+}
+
+/// `xl/drawings/_rels/drawingN.xml.rels` for charts numbered from `first_chart`
+pub(crate) fn drawing_rels_xml(first_chart: u32, count: u32) -> Vec<u8> {
+    let mut xml = Vec::with_capacity(256 + count as usize * 160);
+    xml.extend_from_slice(
+        br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    );
+    let mut num_buffer = itoa::Buffer::new();
+    for i in 0..count {
+        xml.extend_from_slice(b"\n<Relationship Id=\"rId");
+        xml.extend_from_slice(num_buffer.format(i + 1).as_bytes());
+        xml.extend_from_slice(
+            b"\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart\" Target=\"../charts/chart",
+        );
+        xml.extend_from_slice(num_buffer.format(first_chart + i).as_bytes());
+        xml.extend_from_slice(b".xml\"/>");
+    }
+    xml.extend_from_slice(b"\n</Relationships>");
+    xml
+}
@@ -0,0 +1,153 @@
+//! Conditional formatting for ZeroTempWorkbook
+//!
+//! Rules are written into the worksheet's `<conditionalFormatting>`
+//! elements; the styles of `cellIs` and expression rules become
+//! differential formats (`<dxfs>`) of styles.xml, shared by the workbook.
+
+use super::zero_temp_workbook::ZeroTempWorkbook;
+use crate::types::{argb_color, ConditionalFormat, ConditionalStyle};
+
+/// `<conditionalFormatting>` elements of a worksheet, one per rule
+///
+/// Priorities follow the order the rules were added, first rule wins.
+pub(crate) fn conditional_formats_xml(
+    formats: &[(String, ConditionalFormat, Option<usize>)],
+) -> Vec<u8> {
+    let mut xml = Vec::with_capacity(formats.len() * 192);
+    let mut num_buffer = itoa::Buffer::new();
+
+    for (i, (sqref, format, dxf_id)) in formats.iter().enumerate() {
+        xml.extend_from_slice(b"<conditionalFormatting sqref=\"");
+        xml.extend_from_slice(sqref.as_bytes());
+        xml.extend_from_slice(b"\"><cfRule type=\"");
+        xml.extend_from_slice(match format {
+            ConditionalFormat::CellIs { .. } => b"cellIs".as_slice(),
+            ConditionalFormat::Expression { .. } => b"expression",
+            ConditionalFormat::ColorScale { .. } => b"colorScale",
+            ConditionalFormat::DataBar { .. } => b"dataBar",
+            ConditionalFormat::IconSet { .. } => b"iconSet",
+        });
+        xml.push(b'"');
+        if let Some(dxf_id) = dxf_id {
+            xml.extend_from_slice(b" dxfId=\"");
+            xml.extend_from_slice(num_buffer.format(*dxf_id).as_bytes());
+            xml.push(b'"');
+        }
+        xml.extend_from_slice(b" priority=\"");
+        xml.extend_from_slice(num_buffer.format(i + 1).as_bytes());
+        xml.push(b'"');
+
+        match format {
+            ConditionalFormat::CellIs {
+                operator,
+                value,
+                max,
+                ..
+            } => {
+                xml.extend_from_slice(b" operator=\"");
+                xml.extend_from_slice(operator.as_str().as_bytes());
+                xml.extend_from_slice(b"\"><formula>");
+                ZeroTempWorkbook::write_escaped(&mut xml, value);
+                xml.extend_from_slice(b"</formula>");
+                if let Some(max) = max {
+                    xml.extend_from_slice(b"<formula>");
+                    ZeroTempWorkbook::write_escaped(&mut xml, max);
+                    xml.extend_from_slice(b"</formula>");
+                }
+            }
+            ConditionalFormat::Expression { formula, .. } => {
+                xml.extend_from_slice(b"><formula>");
+                ZeroTempWorkbook::write_escaped(&mut xml, formula);
+                xml.extend_from_slice(b"</formula>");
+            }
+            ConditionalFormat::ColorScale {
+                min_color,
+                mid_color,
+                max_color,
+            } => {
+                xml.extend_from_slice(b"><colorScale><cfvo type=\"min\"/>");
+                if mid_color.is_some() {
+                    xml.extend_from_slice(b"<cfvo type=\"percentile\" val=\"50\"/>");
+                }
+                xml.extend_from_slice(b"<cfvo type=\"max\"/>");
+                for color in [Some(min_color), mid_color.as_ref(), Some(max_color)]
+                    .into_iter()
+                    .flatten()
+                {
+                    push_color(&mut xml, color);
+                }
+                xml.extend_from_slice(b"</colorScale>");
+            }
+            ConditionalFormat::DataBar { color } => {
+                xml.extend_from_slice(b"><dataBar><cfvo type=\"min\"/><cfvo type=\"max\"/>");
+                push_color(&mut xml, color);
+                xml.extend_from_slice(b"</dataBar>");
+            }
+            ConditionalFormat::IconSet {
+                icons,
+                reverse,
+                show_value,
+            } => {
+                xml.extend_from_slice(b"><iconSet iconSet=\"");
+                xml.extend_from_slice(icons.as_str().as_bytes());
+                xml.push(b'"');
+                if !show_value {
+                    xml.extend_from_slice(b" showValue=\"0\"");
+                }
+                if *reverse {
+                    xml.extend_from_slice(b" reverse=\"1\"");
+                }
+                xml.push(b'>');
+                let count = icons.icon_count();
+                for band in 0..count {
+                    xml.extend_from_slice(b"<cfvo type=\"percent\" val=\"");
+                    xml.extend_from_slice(num_buffer.format(band * 100 / count).as_bytes());
+                    xml.extend_from_slice(b"\"/>");
+                }
+                xml.extend_from_slice(b"</iconSet>");
+            }
+        }
+        xml.extend_from_slice(b"</cfRule></conditionalFormatting>");
+    }
+    xml
+}
+
+/// `<color rgb="..."/>`, for colors already checked by `argb_color`
+fn push_color(xml: &mut Vec<u8>, color: &str) {
+    xml.extend_from_slice(b"<color rgb=\"");
+    xml.extend_from_slice(argb_color(color).unwrap_or_default().as_bytes());
+    xml.extend_from_slice(b"\"/>");
+}
+
+/// `<dxfs>` element of styles.xml
+pub(crate) fn dxfs_xml(dxfs: &[ConditionalStyle]) -> Vec<u8> {
+    let mut xml = Vec::with_capacity(32 + dxfs.len() * 128);
+    xml.extend_from_slice(b"<dxfs count=\"");
+    xml.extend_from_slice(itoa::Buffer::new().format(dxfs.len()).as_bytes());
+    xml.extend_from_slice(b"\">");
+    for dxf in dxfs {
+        xml.extend_from_slice(b"<dxf>");
+        if dxf.bold || dxf.italic || dxf.font_color.is_some() {
+            xml.extend_from_slice(b"<font>");
+            if dxf.bold {
+                xml.extend_from_slice(b"<b/>");
+            }
+            if dxf.italic {
+                xml.extend_from_slice(b"<i/>");
+            }
+            if let Some(color) = &dxf.font_color {
+                push_color(&mut xml, color);
+            }
+            xml.extend_from_slice(b"</font>");
+        }
+        // Differential fills take their color from bgColor
+        if let Some(color) = &dxf.fill_color {
+            xml.extend_from_slice(b"<fill><patternFill><bgColor rgb=\"");
+            xml.extend_from_slice(color.as_bytes());
+            xml.extend_from_slice(b"\"/></patternFill></fill>");
+        }
+        xml.extend_from_slice(b"</dxf>");
+    }
+    xml.extend_from_slice(b"</dxfs>");
+    xml
+}
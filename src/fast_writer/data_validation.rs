@@ -0,0 +1,93 @@
+//! Data validations for ZeroTempWorkbook
+//!
+//! Rules are written into the worksheet's `<dataValidations>` when the
+//! worksheet is finished.
+
+use super::zero_temp_workbook::ZeroTempWorkbook;
+use crate::error::{ExcelError, Result};
+use crate::types::{DataValidation, ValidationErrorStyle};
+
+/// Longest inline list Excel accepts in a list validation
+const MAX_VALIDATION_LIST_LEN: usize = 255;
+
+/// Fail for an inline list Excel would reject or misread
+pub(crate) fn check_list(validation: &DataValidation) -> Result<()> {
+    if let Some(values) = &validation.list_values {
+        if let Some(value) = values.iter().find(|v| v.contains(',')) {
+            return Err(ExcelError::WriteError(format!(
+                "List validation value '{}' contains a comma; use DataValidation::list_range instead",
+                value
+            )));
+        }
+        // formula1 holds the list wrapped in quotes
+        let len = validation.formula1.chars().count() - 2;
+        if len > MAX_VALIDATION_LIST_LEN {
+            return Err(ExcelError::WriteError(format!(
+                "List validation is {} characters long; Excel allows at most {}, use DataValidation::list_range instead",
+                len, MAX_VALIDATION_LIST_LEN
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `<dataValidations>` element of a worksheet
+pub(crate) fn data_validations_xml(validations: &[(String, DataValidation)]) -> Vec<u8> {
+    let mut xml = Vec::with_capacity(64 + validations.len() * 128);
+    xml.extend_from_slice(b"<dataValidations count=\"");
+    xml.extend_from_slice(itoa::Buffer::new().format(validations.len()).as_bytes());
+    xml.extend_from_slice(b"\">");
+
+    for (sqref, dv) in validations {
+        xml.extend_from_slice(b"<dataValidation type=\"");
+        xml.extend_from_slice(dv.kind.as_bytes());
+        xml.push(b'"');
+        match dv.error_style {
+            ValidationErrorStyle::Stop => {}
+            ValidationErrorStyle::Warning => xml.extend_from_slice(b" errorStyle=\"warning\""),
+            ValidationErrorStyle::Information => {
+                xml.extend_from_slice(b" errorStyle=\"information\"")
+            }
+        }
+        if let Some(operator) = dv.operator {
+            xml.extend_from_slice(b" operator=\"");
+            xml.extend_from_slice(operator.as_str().as_bytes());
+            xml.push(b'"');
+        }
+        if dv.allow_blank {
+            xml.extend_from_slice(b" allowBlank=\"1\"");
+        }
+        // showDropDown="1" actually hides the arrow
+        if !dv.show_dropdown {
+            xml.extend_from_slice(b" showDropDown=\"1\"");
+        }
+        xml.extend_from_slice(b" showInputMessage=\"1\" showErrorMessage=\"1\"");
+        if let Some((title, message)) = &dv.error {
+            xml.extend_from_slice(b" errorTitle=\"");
+            ZeroTempWorkbook::write_escaped(&mut xml, title);
+            xml.extend_from_slice(b"\" error=\"");
+            ZeroTempWorkbook::write_escaped(&mut xml, message);
+            xml.push(b'"');
+        }
+        if let Some((title, message)) = &dv.input {
+            xml.extend_from_slice(b" promptTitle=\"");
+            ZeroTempWorkbook::write_escaped(&mut xml, title);
+            xml.extend_from_slice(b"\" prompt=\"");
+            ZeroTempWorkbook::write_escaped(&mut xml, message);
+            xml.push(b'"');
+        }
+        xml.extend_from_slice(b" sqref=\"");
+        xml.extend_from_slice(sqref.as_bytes());
+        xml.extend_from_slice(b"\"><formula1>");
+        ZeroTempWorkbook::write_escaped(&mut xml, &dv.formula1);
+        xml.extend_from_slice(b"</formula1>");
+        if let Some(formula2) = &dv.formula2 {
+            xml.extend_from_slice(b"<formula2>");
+            ZeroTempWorkbook::write_escaped(&mut xml, formula2);
+            xml.extend_from_slice(b"</formula2>");
+        }
+        xml.extend_from_slice(b"</dataValidation>");
+    }
+    xml.extend_from_slice(b"</dataValidations>");
+    xml
+}
@@ -0,0 +1,150 @@
+//! Shared string table with a fixed memory budget
+//!
+//! Instead of keeping every distinct string in a map, the table remembers a
+//! 64-bit hash per string and writes the `<si>` element straight into a
+//! [`SpillBuffer`], which moves to a temporary file once it grows. Lookups
+//! compare a second, independently seeded hash so that two strings sharing
+//! the first hash are told apart; the newcomer is then written inline
+//! instead of pointing at the wrong entry.
+
+use super::spill::{SpillBuffer, DEFAULT_SPILL_THRESHOLD};
+use super::zero_temp_workbook::ZeroTempWorkbook;
+use crate::error::Result;
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+
+/// Approximate heap cost of one entry, including the map's spare capacity
+const ENTRY_COST: usize = 48;
+
+pub(crate) struct HashedStrings {
+    /// First hash -> (index, second hash)
+    index: HashMap<u64, (u32, u64)>,
+    max_entries: usize,
+    /// `<si>` elements in index order
+    items: SpillBuffer,
+    unique_count: u32,
+    total_count: u32,
+}
+
+impl HashedStrings {
+    /// Table whose index stays within about `memory_limit` bytes
+    pub(crate) fn new(memory_limit: usize) -> Self {
+        HashedStrings {
+            index: HashMap::new(),
+            max_entries: memory_limit / ENTRY_COST,
+            items: SpillBuffer::new(DEFAULT_SPILL_THRESHOLD),
+            unique_count: 0,
+            total_count: 0,
+        }
+    }
+
+    pub(crate) fn set_memory_limit(&mut self, memory_limit: usize) {
+        self.max_entries = memory_limit / ENTRY_COST;
+    }
+
+    /// Index of `s` in the table, or `None` if the cell has to be written
+    /// inline: the budget is used up or `s` collides with another string
+    pub(crate) fn add_string(&mut self, s: &str) -> Result<Option<u32>> {
+        let check = hash(1, s);
+        let full = self.index.len() >= self.max_entries;
+        match self.index.entry(hash(0, s)) {
+            Entry::Occupied(entry) => {
+                let (index, stored_check) = *entry.get();
+                if stored_check != check {
+                    return Ok(None);
+                }
+                self.total_count += 1;
+                Ok(Some(index))
+            }
+            Entry::Vacant(_) if full => Ok(None),
+            Entry::Vacant(entry) => {
+                let index = self.unique_count;
+                entry.insert((index, check));
+
+                let mut xml = Vec::with_capacity(s.len() + 32);
+                xml.extend_from_slice(b"<si><t>");
+                ZeroTempWorkbook::write_escaped(&mut xml, s);
+                xml.extend_from_slice(b"</t></si>");
+                self.items.write_all(&xml)?;
+
+                self.unique_count += 1;
+                self.total_count += 1;
+                Ok(Some(index))
+            }
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.unique_count == 0
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.unique_count as usize
+    }
+
+    /// Pass `xl/sharedStrings.xml` to `sink` in chunks
+    pub(crate) fn write_xml(&mut self, mut sink: impl FnMut(&[u8]) -> Result<()>) -> Result<()> {
+        let header = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <sst xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+             count=\"{}\" uniqueCount=\"{}\">",
+            self.total_count, self.unique_count
+        );
+        sink(header.as_bytes())?;
+        self.items.drain_into(&mut sink)?;
+        sink(b"</sst>")
+    }
+}
+
+fn hash(seed: u8, s: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    s.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xml(table: &mut HashedStrings) -> String {
+        let mut out = Vec::new();
+        table
+            .write_xml(|chunk| {
+                out.extend_from_slice(chunk);
+                Ok(())
+            })
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_dedup_within_budget() {
+        let mut table = HashedStrings::new(2 * ENTRY_COST);
+        assert_eq!(table.add_string("a & b").unwrap(), Some(0));
+        assert_eq!(table.add_string("c").unwrap(), Some(1));
+        assert_eq!(table.add_string("a & b").unwrap(), Some(0));
+        // Budget used up: new strings go inline, known ones are still shared
+        assert_eq!(table.add_string("d").unwrap(), None);
+        assert_eq!(table.add_string("c").unwrap(), Some(1));
+
+        let xml = xml(&mut table);
+        assert!(xml.contains("count=\"4\" uniqueCount=\"2\""));
+        assert!(xml.ends_with(
+            "<si><t>a &amp; b</t></si>\
+             <si><t>c</t></si></sst>"
+        ));
+    }
+
+    #[test]
+    fn test_collision_falls_back_to_inline() {
+        let mut table = HashedStrings::new(1024);
+        assert_eq!(table.add_string("x").unwrap(), Some(0));
+        // Pretend "y" hashes like "x" but differs in the second hash
+        let (index, check) = table.index.remove(&hash(0, "x")).unwrap();
+        table.index.insert(hash(0, "y"), (index, check));
+        assert_eq!(table.add_string("y").unwrap(), None);
+    }
+}
@@ -0,0 +1,60 @@
+//! Hyperlinks for ZeroTempWorkbook
+//!
+//! Links are collected while rows are written and end up in the worksheet's
+//! `<hyperlinks>`. Links into the workbook (`#Sheet2!A1`) use a location;
+//! the others point at an external relationship of the worksheet.
+
+use super::zero_temp_workbook::ZeroTempWorkbook;
+use crate::error::{ExcelError, Result};
+
+/// Longest hyperlink target Excel accepts
+const MAX_URL_LEN: usize = 2079;
+/// Most hyperlinks Excel keeps on one worksheet
+const MAX_HYPERLINKS_PER_SHEET: usize = 65_530;
+
+/// Fail for a target Excel can't store, or when the worksheet already has
+/// `existing` links and can't take another
+pub(crate) fn check_hyperlink(url: &str, existing: usize) -> Result<()> {
+    if url.len() > MAX_URL_LEN {
+        return Err(ExcelError::WriteError(format!(
+            "Hyperlink target is {} characters long; Excel allows at most {}",
+            url.len(),
+            MAX_URL_LEN
+        )));
+    }
+    if existing >= MAX_HYPERLINKS_PER_SHEET {
+        return Err(ExcelError::WriteError(format!(
+            "Worksheet already has {} hyperlinks, the most Excel supports",
+            MAX_HYPERLINKS_PER_SHEET
+        )));
+    }
+    Ok(())
+}
+
+/// `<hyperlinks>` element of a worksheet
+///
+/// Targets starting with `#` point inside the workbook and use the
+/// `location` attribute; the rest get relationship ids `rId1`, `rId2`, ...
+/// in the order written by `write_worksheet_rels`.
+pub(crate) fn hyperlinks_xml(hyperlinks: &[(String, String)]) -> Vec<u8> {
+    let mut xml = Vec::with_capacity(32 + hyperlinks.len() * 48);
+    let mut num_buffer = itoa::Buffer::new();
+    let mut rel_id = 0u32;
+
+    xml.extend_from_slice(b"<hyperlinks>");
+    for (cell_ref, target) in hyperlinks {
+        xml.extend_from_slice(b"<hyperlink ref=\"");
+        xml.extend_from_slice(cell_ref.as_bytes());
+        if let Some(location) = target.strip_prefix('#') {
+            xml.extend_from_slice(b"\" location=\"");
+            ZeroTempWorkbook::write_escaped(&mut xml, location);
+        } else {
+            rel_id += 1;
+            xml.extend_from_slice(b"\" r:id=\"rId");
+            xml.extend_from_slice(num_buffer.format(rel_id).as_bytes());
+        }
+        xml.extend_from_slice(b"\"/>");
+    }
+    xml.extend_from_slice(b"</hyperlinks>");
+    xml
+}
@@ -6,8 +6,17 @@
 //! - Optimized ZIP compression (using s-zip library)
 //! - Streaming-first design
 
+mod chart;
+mod conditional_format;
+mod data_validation;
+mod hashed_strings;
+mod hyperlink;
 pub mod memory;
+pub(crate) mod number;
+pub(crate) mod pipeline;
 pub mod shared_strings;
+pub(crate) mod spill;
+mod table;
 pub mod ultra_low_memory;
 pub mod worksheet;
 pub mod xml_writer;
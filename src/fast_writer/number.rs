@@ -0,0 +1,118 @@
+//! Text of numeric cell values
+//!
+//! `f64`'s `Display` never switches to exponent notation, so `1e-300` is
+//! written as 300 digits and `1e21` as 22. Values here are formatted with
+//! ryu, the shortest text that reads back as the same `f64`, in the form
+//! Excel writes itself: `1.5E-7`, `12`, `0.1`. The decimal separator is
+//! always `.`, whatever the locale.
+
+/// Append the shortest text that parses back to `value`, which must be finite
+pub(crate) fn push_f64(out: &mut Vec<u8>, value: f64) {
+    if value == 0.0 {
+        // Also turns -0 into 0
+        out.push(b'0');
+        return;
+    }
+    let mut buffer = ryu::Buffer::new();
+    let text = buffer.format_finite(value);
+    let text = text.strip_suffix(".0").unwrap_or(text);
+    match text.split_once('e') {
+        Some((mantissa, exponent)) => {
+            out.extend_from_slice(mantissa.as_bytes());
+            out.push(b'E');
+            out.extend_from_slice(exponent.as_bytes());
+        }
+        None => out.extend_from_slice(text.as_bytes()),
+    }
+}
+
+/// [`push_f64`] into a `String`
+pub(crate) fn f64_text(value: f64) -> String {
+    let mut out = Vec::with_capacity(24);
+    push_f64(&mut out, value);
+    // ryu and push_f64 only produce ASCII
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// Append the type attribute, value and end tag of a numeric cell, after
+/// `<c r="…"` and any style attribute
+///
+/// NaN and infinities have no SpreadsheetML form; they are written as the
+/// `#NUM!` error Excel shows for them.
+pub(crate) fn push_number_cell(out: &mut Vec<u8>, value: f64) {
+    if value.is_finite() {
+        out.extend_from_slice(b" t=\"n\"><v>");
+        push_f64(out, value);
+        out.extend_from_slice(b"</v></c>");
+    } else {
+        out.extend_from_slice(b" t=\"e\"><v>#NUM!</v></c>");
+    }
+}
+
+/// `value` rounded to `decimals` places
+///
+/// Values too large to have digits that far right are returned unchanged.
+pub(crate) fn round_decimals(value: f64, decimals: u8) -> f64 {
+    let scale = 10f64.powi(i32::from(decimals));
+    let scaled = value * scale;
+    // Past 2^52 an f64 has no fractional digits left to round
+    if !scaled.is_finite() || scaled.abs() >= 4_503_599_627_370_496.0 {
+        return value;
+    }
+    scaled.round() / scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortest_text() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-12.0, "-12"),
+            (0.1, "0.1"),
+            (0.1 + 0.2, "0.30000000000000004"),
+            (1234.5678, "1234.5678"),
+            (1e-7, "1E-7"),
+            (-2.5e-12, "-2.5E-12"),
+            (1e21, "1E21"),
+            (1.7976931348623157e308, "1.7976931348623157E308"),
+            (5e-324, "5E-324"),
+            (45352.5, "45352.5"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(f64_text(value), expected, "{:?}", value);
+            // Round-trips exactly
+            assert_eq!(f64_text(value).parse::<f64>().unwrap(), value);
+        }
+        assert!(f64_text(1e300).len() < 8);
+    }
+
+    #[test]
+    fn test_number_cell() {
+        let mut out = Vec::new();
+        push_number_cell(&mut out, 2.5);
+        push_number_cell(&mut out, f64::NAN);
+        push_number_cell(&mut out, f64::NEG_INFINITY);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#" t="n"><v>2.5</v></c> t="e"><v>#NUM!</v></c> t="e"><v>#NUM!</v></c>"#
+        );
+    }
+
+    #[test]
+    fn test_round_decimals() {
+        assert_eq!(f64_text(round_decimals(0.1 + 0.2, 2)), "0.3");
+        assert_eq!(f64_text(round_decimals(2.0 / 3.0, 4)), "0.6667");
+        assert_eq!(f64_text(round_decimals(-1.005e-3, 3)), "-0.001");
+        assert_eq!(round_decimals(1234.5, 0), 1235.0);
+        assert_eq!(round_decimals(1e300, 2), 1e300);
+        assert_eq!(
+            round_decimals(9_007_199_254_740_993.0, 2),
+            9_007_199_254_740_993.0
+        );
+    }
+}
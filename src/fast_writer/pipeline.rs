@@ -0,0 +1,475 @@
+//! ZIP output of ZeroTempWorkbook, compressed inline or on worker threads
+//!
+//! The sink only needs `Write`, so the same code also feeds the in-memory
+//! buffers the cloud writers upload or stream from.
+//!
+//! Entries are written with data descriptors (general purpose bit 3), so the
+//! output is only ever appended to. Inline, each entry is a single deflate
+//! stream. With compression workers, entry data is cut into chunks that are
+//! deflated independently on a pool of threads. Every chunk ends with a sync
+//! flush, which stops it on a byte boundary without a final block, so the
+//! chunks are written back in order and closed with an empty final block to
+//! form one valid deflate stream. Workers only touch buffers they own.
+
+use crate::error::{ExcelError, Result};
+use crate::report::HashingWriter;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Entry data collected before it is handed to a worker
+const CHUNK_SIZE: usize = 256 * 1024;
+/// Compressed bytes held by the inline encoder before they are written out
+const FLUSH_SIZE: usize = 64 * 1024;
+/// Chunks in flight per worker before `write_data` waits for the oldest one
+const CHUNKS_PER_WORKER: usize = 2;
+
+pub(crate) struct ZipSink<W: Write = HashingWriter> {
+    output: CountingWriter<W>,
+    level: Compression,
+    entries: Vec<EntryRecord>,
+    current: Option<CurrentEntry>,
+    pool: Option<WorkerPool>,
+}
+
+/// Central directory record of a finished entry
+struct EntryRecord {
+    name: String,
+    /// Written uncompressed with its sizes in the local header
+    stored: bool,
+    offset: u64,
+    crc32: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+}
+
+struct CurrentEntry {
+    name: String,
+    offset: u64,
+    data_offset: u64,
+    crc: crc32fast::Hasher,
+    uncompressed_size: u64,
+    /// Inline deflate stream; unused while workers compress
+    encoder: Option<DeflateEncoder<Vec<u8>>>,
+    /// Data not yet handed to a worker
+    chunk: Vec<u8>,
+}
+
+impl<W: Write> ZipSink<W> {
+    pub(crate) fn new(output: W, compression_level: u32) -> Self {
+        ZipSink {
+            output: CountingWriter {
+                inner: output,
+                written: 0,
+            },
+            level: Compression::new(compression_level.min(9)),
+            entries: Vec::new(),
+            current: None,
+            pool: None,
+        }
+    }
+
+    pub(crate) fn start_entry(&mut self, name: &str) -> Result<()> {
+        self.finish_entry()?;
+
+        let offset = self.output.written;
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend_from_slice(&[0x50, 0x4b, 0x03, 0x04]); // signature
+        header.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        header.extend_from_slice(&8u16.to_le_bytes()); // flags: data descriptor
+        header.extend_from_slice(&8u16.to_le_bytes()); // method: deflate
+        header.extend_from_slice(&[0, 0, 0, 0]); // mod time/date
+        header.extend_from_slice(&[0; 12]); // crc32 and sizes follow the data
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        header.extend_from_slice(name.as_bytes());
+        self.output.write_all(&header)?;
+
+        self.current = Some(CurrentEntry {
+            name: name.to_string(),
+            offset,
+            data_offset: self.output.written,
+            crc: crc32fast::Hasher::new(),
+            uncompressed_size: 0,
+            encoder: None,
+            chunk: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Add a complete entry uncompressed (method 0), with its CRC and sizes
+    /// in the local header and no data descriptor or extra field
+    ///
+    /// ODF packages need this for their leading `mimetype` entry, which
+    /// type detection reads straight from byte 38 of the file.
+    pub(crate) fn add_stored_entry(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        self.finish_entry()?;
+
+        let offset = self.output.written;
+        let crc32 = crc32fast::hash(data);
+        let size = u32::try_from(data.len()).map_err(|_| {
+            ExcelError::WriteError(format!("Stored ZIP entry {} is over 4 GiB", name))
+        })?;
+        let mut header = Vec::with_capacity(30 + name.len() + data.len());
+        header.extend_from_slice(&[0x50, 0x4b, 0x03, 0x04]); // signature
+        header.extend_from_slice(&10u16.to_le_bytes()); // version needed
+        header.extend_from_slice(&0u16.to_le_bytes()); // flags
+        header.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+        header.extend_from_slice(&[0, 0, 0, 0]); // mod time/date
+        header.extend_from_slice(&crc32.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes()); // compressed size
+        header.extend_from_slice(&size.to_le_bytes()); // uncompressed size
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        header.extend_from_slice(name.as_bytes());
+        header.extend_from_slice(data);
+        self.output.write_all(&header)?;
+
+        self.entries.push(EntryRecord {
+            name: name.to_string(),
+            stored: true,
+            offset,
+            crc32,
+            compressed_size: data.len() as u64,
+            uncompressed_size: data.len() as u64,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn write_data(&mut self, data: &[u8]) -> Result<()> {
+        let entry = self
+            .current
+            .as_mut()
+            .ok_or_else(|| ExcelError::WriteError("No ZIP entry started".to_string()))?;
+        entry.crc.update(data);
+        entry.uncompressed_size += data.len() as u64;
+
+        match &mut self.pool {
+            Some(pool) => {
+                entry.chunk.extend_from_slice(data);
+                if entry.chunk.len() >= CHUNK_SIZE {
+                    let chunk = std::mem::replace(&mut entry.chunk, Vec::with_capacity(CHUNK_SIZE));
+                    pool.submit(chunk, &mut self.output)?;
+                }
+            }
+            None => {
+                let level = self.level;
+                let encoder = entry
+                    .encoder
+                    .get_or_insert_with(|| DeflateEncoder::new(Vec::new(), level));
+                encoder.write_all(data)?;
+                if encoder.get_ref().len() >= FLUSH_SIZE {
+                    let compressed = std::mem::take(encoder.get_mut());
+                    self.output.write_all(&compressed)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Output written so far, e.g. to hand a buffer's contents on
+    #[cfg(any(feature = "cloud-http", feature = "cloud-s3", feature = "cloud-gcs"))]
+    pub(crate) fn output_mut(&mut self) -> &mut W {
+        &mut self.output.inner
+    }
+
+    /// Write the central directory and return the output
+    pub(crate) fn finish(mut self) -> Result<W> {
+        self.finish_entry()?;
+        if let Some(pool) = self.pool.take() {
+            pool.shutdown();
+        }
+        self.write_central_directory()?;
+        self.output.inner.flush()?;
+        Ok(self.output.inner)
+    }
+
+    /// Number of compression worker threads; 0 compresses inline
+    pub(crate) fn workers(&self) -> usize {
+        self.pool.as_ref().map_or(0, |pool| pool.workers.len())
+    }
+
+    /// Compress on `workers` threads from here on, or inline for 0
+    ///
+    /// Data written so far is compressed and written out first.
+    pub(crate) fn set_workers(&mut self, workers: usize) -> Result<()> {
+        if workers == self.workers() {
+            return Ok(());
+        }
+        if let Some(entry) = &mut self.current {
+            if let Some(encoder) = entry.encoder.take() {
+                self.output.write_all(&sync_flush(encoder)?)?;
+            }
+            if let Some(pool) = &mut self.pool {
+                if !entry.chunk.is_empty() {
+                    pool.submit(std::mem::take(&mut entry.chunk), &mut self.output)?;
+                }
+            }
+        }
+        if let Some(mut pool) = self.pool.take() {
+            pool.drain(&mut self.output)?;
+            pool.shutdown();
+        }
+        if workers > 0 {
+            self.pool = Some(WorkerPool::new(workers, self.level)?);
+        }
+        Ok(())
+    }
+
+    fn finish_entry(&mut self) -> Result<()> {
+        let Some(entry) = self.current.take() else {
+            return Ok(());
+        };
+        if let Some(pool) = &mut self.pool {
+            if !entry.chunk.is_empty() {
+                pool.submit(entry.chunk, &mut self.output)?;
+            }
+            pool.drain(&mut self.output)?;
+        }
+        // An inline stream ends itself; chunks from the workers are closed
+        // with an empty final block
+        let encoder = entry
+            .encoder
+            .unwrap_or_else(|| DeflateEncoder::new(Vec::new(), self.level));
+        self.output.write_all(&encoder.finish()?)?;
+
+        let crc32 = entry.crc.finalize();
+        let compressed_size = self.output.written - entry.data_offset;
+        let uncompressed_size = entry.uncompressed_size;
+
+        let mut descriptor = Vec::with_capacity(24);
+        descriptor.extend_from_slice(&[0x50, 0x4b, 0x07, 0x08]);
+        descriptor.extend_from_slice(&crc32.to_le_bytes());
+        if compressed_size > u32::MAX as u64 || uncompressed_size > u32::MAX as u64 {
+            descriptor.extend_from_slice(&compressed_size.to_le_bytes());
+            descriptor.extend_from_slice(&uncompressed_size.to_le_bytes());
+        } else {
+            descriptor.extend_from_slice(&(compressed_size as u32).to_le_bytes());
+            descriptor.extend_from_slice(&(uncompressed_size as u32).to_le_bytes());
+        }
+        self.output.write_all(&descriptor)?;
+
+        self.entries.push(EntryRecord {
+            name: entry.name,
+            stored: false,
+            offset: entry.offset,
+            crc32,
+            compressed_size,
+            uncompressed_size,
+        });
+        Ok(())
+    }
+
+    fn write_central_directory(&mut self) -> Result<()> {
+        let directory_offset = self.output.written;
+        for entry in &self.entries {
+            let mut zip64 = Vec::new();
+            if entry.uncompressed_size > u32::MAX as u64 {
+                zip64.extend_from_slice(&entry.uncompressed_size.to_le_bytes());
+            }
+            if entry.compressed_size > u32::MAX as u64 {
+                zip64.extend_from_slice(&entry.compressed_size.to_le_bytes());
+            }
+            if entry.offset > u32::MAX as u64 {
+                zip64.extend_from_slice(&entry.offset.to_le_bytes());
+            }
+            let mut extra = Vec::new();
+            if !zip64.is_empty() {
+                extra.extend_from_slice(&0x0001u16.to_le_bytes());
+                extra.extend_from_slice(&(zip64.len() as u16).to_le_bytes());
+                extra.extend_from_slice(&zip64);
+            }
+
+            // Stored entries: no flags, method 0; others: data descriptor, deflate
+            let (flags, method) = if entry.stored { (0u16, 0u16) } else { (8, 8) };
+            let mut record = Vec::with_capacity(46 + entry.name.len() + extra.len());
+            record.extend_from_slice(&[0x50, 0x4b, 0x01, 0x02]); // signature
+            record.extend_from_slice(&20u16.to_le_bytes()); // version made by
+            record.extend_from_slice(&20u16.to_le_bytes()); // version needed
+            record.extend_from_slice(&flags.to_le_bytes());
+            record.extend_from_slice(&method.to_le_bytes());
+            record.extend_from_slice(&[0, 0, 0, 0]); // mod time/date
+            record.extend_from_slice(&entry.crc32.to_le_bytes());
+            record.extend_from_slice(&clamp_u32(entry.compressed_size).to_le_bytes());
+            record.extend_from_slice(&clamp_u32(entry.uncompressed_size).to_le_bytes());
+            record.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            record.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+            record.extend_from_slice(&0u16.to_le_bytes()); // comment length
+            record.extend_from_slice(&0u16.to_le_bytes()); // disk number
+            record.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+            record.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+            record.extend_from_slice(&clamp_u32(entry.offset).to_le_bytes());
+            record.extend_from_slice(entry.name.as_bytes());
+            record.extend_from_slice(&extra);
+            self.output.write_all(&record)?;
+        }
+        let directory_size = self.output.written - directory_offset;
+        let count = self.entries.len() as u64;
+
+        let mut end = Vec::new();
+        if count > u16::MAX as u64
+            || directory_size > u32::MAX as u64
+            || directory_offset > u32::MAX as u64
+        {
+            let zip64_end_offset = self.output.written;
+            end.extend_from_slice(&[0x50, 0x4b, 0x06, 0x06]); // ZIP64 end record
+            end.extend_from_slice(&44u64.to_le_bytes()); // size of the rest
+            end.extend_from_slice(&45u16.to_le_bytes()); // version made by
+            end.extend_from_slice(&45u16.to_le_bytes()); // version needed
+            end.extend_from_slice(&0u32.to_le_bytes()); // this disk
+            end.extend_from_slice(&0u32.to_le_bytes()); // directory disk
+            end.extend_from_slice(&count.to_le_bytes());
+            end.extend_from_slice(&count.to_le_bytes());
+            end.extend_from_slice(&directory_size.to_le_bytes());
+            end.extend_from_slice(&directory_offset.to_le_bytes());
+            end.extend_from_slice(&[0x50, 0x4b, 0x06, 0x07]); // ZIP64 end locator
+            end.extend_from_slice(&0u32.to_le_bytes());
+            end.extend_from_slice(&zip64_end_offset.to_le_bytes());
+            end.extend_from_slice(&1u32.to_le_bytes()); // total disks
+        }
+        let count16 = count.min(u16::MAX as u64) as u16;
+        end.extend_from_slice(&[0x50, 0x4b, 0x05, 0x06]); // end of central directory
+        end.extend_from_slice(&0u16.to_le_bytes()); // this disk
+        end.extend_from_slice(&0u16.to_le_bytes()); // directory disk
+        end.extend_from_slice(&count16.to_le_bytes());
+        end.extend_from_slice(&count16.to_le_bytes());
+        end.extend_from_slice(&clamp_u32(directory_size).to_le_bytes());
+        end.extend_from_slice(&clamp_u32(directory_offset).to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.output.write_all(&end)?;
+        Ok(())
+    }
+}
+
+/// Lets `XmlWriter` stream large parts straight into the current entry
+impl<W: Write> std::io::Write for ZipSink<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_data(buf)
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Output that counts bytes for entry offsets and sizes
+struct CountingWriter<W> {
+    inner: W,
+    written: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        self.inner.write_all(data)?;
+        self.written += data.len() as u64;
+        Ok(())
+    }
+}
+
+/// A chunk to compress and where to send the result
+type Job = (Vec<u8>, SyncSender<io::Result<Vec<u8>>>);
+
+struct WorkerPool {
+    jobs: Sender<Job>,
+    /// Results in the order the chunks were submitted
+    pending: VecDeque<Receiver<io::Result<Vec<u8>>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    fn new(count: usize, level: Compression) -> Result<Self> {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..count)
+            .map(|i| {
+                let receiver = Arc::clone(&receiver);
+                thread::Builder::new()
+                    .name(format!("excelstream-compress-{}", i))
+                    .spawn(move || work(&receiver, level))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(WorkerPool {
+            jobs,
+            pending: VecDeque::new(),
+            workers,
+        })
+    }
+
+    fn submit<W: Write>(&mut self, chunk: Vec<u8>, output: &mut CountingWriter<W>) -> Result<()> {
+        if self.pending.len() >= self.workers.len() * CHUNKS_PER_WORKER {
+            self.write_oldest(output)?;
+        }
+        let (sender, receiver) = mpsc::sync_channel(1);
+        self.jobs
+            .send((chunk, sender))
+            .map_err(|_| stopped_early())?;
+        self.pending.push_back(receiver);
+        Ok(())
+    }
+
+    /// Wait for every submitted chunk and write them out in order
+    fn drain<W: Write>(&mut self, output: &mut CountingWriter<W>) -> Result<()> {
+        while !self.pending.is_empty() {
+            self.write_oldest(output)?;
+        }
+        Ok(())
+    }
+
+    fn write_oldest<W: Write>(&mut self, output: &mut CountingWriter<W>) -> Result<()> {
+        if let Some(result) = self.pending.pop_front() {
+            let compressed = result.recv().map_err(|_| stopped_early())??;
+            output.write_all(&compressed)?;
+        }
+        Ok(())
+    }
+
+    fn shutdown(self) {
+        drop(self.jobs);
+        for worker in self.workers {
+            if let Err(panic) = worker.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+fn work(jobs: &Mutex<Receiver<Job>>, level: Compression) {
+    loop {
+        let job = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            Err(_) => return,
+        };
+        let Ok((chunk, result)) = job else {
+            return;
+        };
+        // Nobody is waiting only if the sink was dropped mid-write
+        let _ = result.send(deflate_chunk(&chunk, level));
+    }
+}
+
+/// Deflate a chunk on its own, ending on a byte boundary without a final block
+fn deflate_chunk(chunk: &[u8], level: Compression) -> io::Result<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::with_capacity(chunk.len() / 4), level);
+    encoder.write_all(chunk)?;
+    sync_flush(encoder)
+}
+
+fn sync_flush(mut encoder: DeflateEncoder<Vec<u8>>) -> io::Result<Vec<u8>> {
+    encoder.flush()?;
+    // Whatever the encoder writes when dropped goes to the empty buffer left behind
+    Ok(std::mem::take(encoder.get_mut()))
+}
+
+fn clamp_u32(value: u64) -> u32 {
+    value.min(u32::MAX as u64) as u32
+}
+
+fn stopped_early() -> ExcelError {
+    ExcelError::WriteError("Compression worker stopped early".to_string())
+}
@@ -33,6 +33,11 @@ impl SharedStrings {
         }
     }
 
+    /// Change how many unique strings the table may hold
+    pub fn set_max_unique(&mut self, max_unique: usize) {
+        self.max_unique_strings = max_unique;
+    }
+
     /// Add a string and get its index
     pub fn add_string(&mut self, s: &str) -> u32 {
         // Increment total count for every string reference
@@ -56,6 +61,21 @@ impl SharedStrings {
         index
     }
 
+    /// Add a string only while the table has room for new entries
+    ///
+    /// Returns `None` for a string not yet in the table once
+    /// `max_unique` strings are stored; the caller writes that one inline.
+    pub fn try_add_string(&mut self, s: &str) -> Option<u32> {
+        if let Some(&index) = self.string_map.get(s) {
+            self.total_count += 1;
+            return Some(index);
+        }
+        if self.strings.len() >= self.max_unique_strings {
+            return None;
+        }
+        Some(self.add_string(s))
+    }
+
     /// Get number of unique strings
     pub fn count(&self) -> usize {
         self.strings.len()
@@ -119,4 +139,22 @@ mod tests {
         assert_eq!(idx3, 0); // Should return same index
         assert_eq!(ss.count(), 2);
     }
+
+    #[test]
+    fn test_try_add_string_stops_at_limit() {
+        let mut ss = SharedStrings::with_capacity(4, 2);
+
+        assert_eq!(ss.try_add_string("a"), Some(0));
+        assert_eq!(ss.try_add_string("b"), Some(1));
+        assert_eq!(ss.try_add_string("c"), None);
+        assert_eq!(ss.try_add_string("a"), Some(0));
+        assert_eq!(ss.count(), 2);
+
+        let mut out = Vec::new();
+        let mut xml = XmlWriter::new(&mut out);
+        ss.write_xml(&mut xml).unwrap();
+        xml.flush().unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("count=\"3\" uniqueCount=\"2\""));
+    }
 }
@@ -0,0 +1,205 @@
+//! Append-only byte buffer that moves to a temporary file once it grows
+
+use crate::error::Result;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bytes kept in memory before spilling to disk
+pub(crate) const DEFAULT_SPILL_THRESHOLD: usize = 1024 * 1024;
+
+/// Holds the row XML of a worksheet that can't be streamed into the ZIP yet
+///
+/// Data stays in memory up to a threshold and is then moved to a file in
+/// the system temp directory, which is removed when the buffer is dropped.
+pub(crate) struct SpillBuffer {
+    memory: Vec<u8>,
+    file: Option<(BufWriter<File>, PathBuf)>,
+    threshold: usize,
+}
+
+impl SpillBuffer {
+    pub(crate) fn new(threshold: usize) -> Self {
+        SpillBuffer {
+            memory: Vec::new(),
+            file: None,
+            threshold,
+        }
+    }
+
+    /// Pass everything written so far to `sink`, in order
+    pub(crate) fn drain_into(&mut self, mut sink: impl FnMut(&[u8]) -> Result<()>) -> Result<()> {
+        if let Some((writer, _)) = &mut self.file {
+            writer.flush()?;
+            let file = writer.get_mut();
+            file.seek(SeekFrom::Start(0))?;
+            let mut chunk = vec![0u8; 64 * 1024];
+            loop {
+                let n = file.read(&mut chunk)?;
+                if n == 0 {
+                    break;
+                }
+                sink(&chunk[..n])?;
+            }
+        } else if !self.memory.is_empty() {
+            sink(&self.memory)?;
+        }
+        Ok(())
+    }
+
+    /// Read everything written so far from the start; a spilled file is
+    /// removed when the reader is dropped
+    pub(crate) fn into_reader(mut self) -> Result<SpillReader> {
+        match self.file.take() {
+            Some((writer, path)) => {
+                let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+                file.seek(SeekFrom::Start(0))?;
+                Ok(SpillReader::File(
+                    BufReader::with_capacity(64 * 1024, file),
+                    path,
+                ))
+            }
+            None => Ok(SpillReader::Memory(io::Cursor::new(std::mem::take(
+                &mut self.memory,
+            )))),
+        }
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "excelstream-{}-{}.sheet",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let mut writer = BufWriter::with_capacity(64 * 1024, file);
+        writer.write_all(&self.memory)?;
+        self.memory = Vec::new();
+        self.file = Some((writer, path));
+        Ok(())
+    }
+}
+
+impl Write for SpillBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.file.is_none() && self.memory.len() + buf.len() > self.threshold {
+            self.spill()?;
+        }
+        match &mut self.file {
+            Some((writer, _)) => writer.write_all(buf)?,
+            None => self.memory.extend_from_slice(buf),
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some((writer, _)) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for SpillBuffer {
+    fn drop(&mut self) {
+        if let Some((writer, path)) = self.file.take() {
+            drop(writer);
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Reader over the contents of a [`SpillBuffer`]
+pub(crate) enum SpillReader {
+    Memory(io::Cursor<Vec<u8>>),
+    File(BufReader<File>, PathBuf),
+}
+
+impl Read for SpillReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            SpillReader::Memory(cursor) => cursor.read(buf),
+            SpillReader::File(reader, _) => reader.read(buf),
+        }
+    }
+}
+
+impl Drop for SpillReader {
+    fn drop(&mut self) {
+        if let SpillReader::File(_, path) = self {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(buffer: &mut SpillBuffer) -> Vec<u8> {
+        let mut out = Vec::new();
+        buffer
+            .drain_into(|chunk| {
+                out.extend_from_slice(chunk);
+                Ok(())
+            })
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_in_memory() {
+        let mut buffer = SpillBuffer::new(64);
+        buffer.write_all(b"<row/>").unwrap();
+        assert!(buffer.file.is_none());
+        assert_eq!(contents(&mut buffer), b"<row/>");
+    }
+
+    #[test]
+    fn test_spills_to_disk() {
+        let mut buffer = SpillBuffer::new(16);
+        let mut expected = Vec::new();
+        for i in 0..100 {
+            let row = format!("<row r=\"{}\"/>", i);
+            buffer.write_all(row.as_bytes()).unwrap();
+            expected.extend_from_slice(row.as_bytes());
+        }
+        let path = buffer.file.as_ref().unwrap().1.clone();
+        assert!(path.exists());
+        assert_eq!(contents(&mut buffer), expected);
+
+        drop(buffer);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_into_reader() {
+        let mut buffer = SpillBuffer::new(16);
+        buffer.write_all(b"0123456789").unwrap();
+        let mut read_back = Vec::new();
+        buffer
+            .into_reader()
+            .unwrap()
+            .read_to_end(&mut read_back)
+            .unwrap();
+        assert_eq!(read_back, b"0123456789");
+
+        let mut buffer = SpillBuffer::new(16);
+        for _ in 0..10 {
+            buffer.write_all(b"0123456789").unwrap();
+        }
+        let path = buffer.file.as_ref().unwrap().1.clone();
+        let mut reader = buffer.into_reader().unwrap();
+        let mut read_back = Vec::new();
+        reader.read_to_end(&mut read_back).unwrap();
+        assert_eq!(read_back, b"0123456789".repeat(10));
+        drop(reader);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,139 @@
+//! Table (ListObject) parts for ZeroTempWorkbook
+//!
+//! Each table is a `xl/tables/tableN.xml` part referenced from its
+//! worksheet's `<tableParts>` through the worksheet relationships.
+
+use super::zero_temp_workbook::ZeroTempWorkbook;
+use crate::types::TableOptions;
+
+/// A table waiting to be written when its worksheet is finished
+#[derive(Debug, Clone)]
+pub(crate) struct Table {
+    /// Workbook-wide table id
+    pub id: u32,
+    pub name: String,
+    /// 1-based, inclusive (first column, first row, last column, last row)
+    pub first_col: u32,
+    pub first_row: u32,
+    pub last_col: u32,
+    pub last_row: u32,
+    pub options: TableOptions,
+}
+
+impl Table {
+    fn push_ref(&self, xml: &mut Vec<u8>, last_row: u32) {
+        let mut num_buffer = itoa::Buffer::new();
+        ZeroTempWorkbook::push_column_letter(xml, self.first_col);
+        xml.extend_from_slice(num_buffer.format(self.first_row).as_bytes());
+        xml.push(b':');
+        ZeroTempWorkbook::push_column_letter(xml, self.last_col);
+        xml.extend_from_slice(num_buffer.format(last_row).as_bytes());
+    }
+
+    /// True if the two tables share a cell
+    pub(crate) fn overlaps(&self, other: &Table) -> bool {
+        self.first_col <= other.last_col
+            && other.first_col <= self.last_col
+            && self.first_row <= other.last_row
+            && other.first_row <= self.last_row
+    }
+}
+
+/// Whether `name` can be used as a table name in structured references
+pub(crate) fn is_valid_table_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let starts_ok = matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_' || c == '\\');
+    if !starts_ok
+        || name.chars().count() > 255
+        || !chars.all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+    {
+        return false;
+    }
+    // Names that read as cell references (A1 or R1C1 style) are rejected
+    let upper = name.to_ascii_uppercase();
+    let letters = upper.trim_end_matches(|c: char| c.is_ascii_digit());
+    let is_a1 = letters.len() < upper.len()
+        && (1..=3).contains(&letters.len())
+        && letters.chars().all(|c| c.is_ascii_uppercase());
+    let is_r1c1 = upper
+        .strip_prefix('R')
+        .map(|rest| rest.trim_start_matches(|c: char| c.is_ascii_digit()))
+        .and_then(|rest| rest.strip_prefix('C'))
+        .is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit()));
+    !(is_a1 || is_r1c1 || upper == "R" || upper == "C")
+}
+
+/// `xl/tables/tableN.xml`
+pub(crate) fn table_xml(table: &Table) -> Vec<u8> {
+    let options = &table.options;
+    let mut num_buffer = itoa::Buffer::new();
+    let mut xml = Vec::with_capacity(512 + options.column_names.len() * 48);
+    xml.extend_from_slice(
+        br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<table xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" id=""#,
+    );
+    xml.extend_from_slice(num_buffer.format(table.id).as_bytes());
+    xml.extend_from_slice(b"\" name=\"");
+    ZeroTempWorkbook::write_escaped(&mut xml, &table.name);
+    xml.extend_from_slice(b"\" displayName=\"");
+    ZeroTempWorkbook::write_escaped(&mut xml, &table.name);
+    xml.extend_from_slice(b"\" ref=\"");
+    table.push_ref(&mut xml, table.last_row);
+    xml.push(b'"');
+    if options.totals_row {
+        xml.extend_from_slice(b" totalsRowCount=\"1\"");
+    }
+    xml.extend_from_slice(b"><autoFilter ref=\"");
+    let data_last_row = table.last_row - options.totals_row as u32;
+    table.push_ref(&mut xml, data_last_row);
+    xml.extend_from_slice(b"\"/><tableColumns count=\"");
+    let count = table.last_col - table.first_col + 1;
+    xml.extend_from_slice(num_buffer.format(count).as_bytes());
+    xml.extend_from_slice(b"\">");
+    for i in 0..count {
+        xml.extend_from_slice(b"<tableColumn id=\"");
+        xml.extend_from_slice(num_buffer.format(i + 1).as_bytes());
+        xml.extend_from_slice(b"\" name=\"");
+        match options.column_names.get(i as usize) {
+            Some(name) => ZeroTempWorkbook::write_escaped(&mut xml, name),
+            None => {
+                xml.extend_from_slice(b"Column");
+                xml.extend_from_slice(num_buffer.format(i + 1).as_bytes());
+            }
+        }
+        xml.push(b'"');
+        if options.totals_row {
+            if i == 0 {
+                xml.extend_from_slice(b" totalsRowLabel=\"Total\"");
+            } else {
+                xml.extend_from_slice(b" totalsRowFunction=\"sum\"");
+            }
+        }
+        xml.extend_from_slice(b"/>");
+    }
+    xml.extend_from_slice(b"</tableColumns><tableStyleInfo name=\"");
+    ZeroTempWorkbook::write_escaped(&mut xml, &options.style);
+    xml.extend_from_slice(b"\" showFirstColumn=\"0\" showLastColumn=\"0\" showRowStripes=\"");
+    xml.push(if options.banded_rows { b'1' } else { b'0' });
+    xml.extend_from_slice(b"\" showColumnStripes=\"");
+    xml.push(if options.banded_columns { b'1' } else { b'0' });
+    xml.extend_from_slice(b"\"/></table>");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_names() {
+        assert!(is_valid_table_name("Sales"));
+        assert!(is_valid_table_name("_tbl.2024"));
+        assert!(!is_valid_table_name("2024Sales"));
+        assert!(!is_valid_table_name("My Table"));
+        assert!(!is_valid_table_name("AB12"));
+        assert!(!is_valid_table_name("r"));
+        assert!(!is_valid_table_name("R2C3"));
+        assert!(is_valid_table_name("ABCD12"));
+    }
+}
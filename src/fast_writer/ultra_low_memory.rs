@@ -2,7 +2,15 @@
 
 use super::zero_temp_workbook::ZeroTempWorkbook;
 use crate::error::Result;
-use crate::types::{CellValue, ProtectionOptions};
+use crate::progress::{CancellationToken, ProgressCallback, WriterMetrics};
+use crate::report::{Checksum, WriteReport};
+use crate::types::{
+    AutofitOptions, CellRef, CellStyle, CellValue, ChartOptions, ChartType, ColumnSlice,
+    ConditionalFormat, DataValidation, DocumentProperties, OutlineSettings, PageSetup,
+    ProtectionOptions, SheetNamePolicy, StringMode, TableOptions,
+};
+use std::borrow::Borrow;
+use std::fmt::Display;
 use std::path::Path;
 
 pub struct UltraLowMemoryWorkbook {
@@ -27,14 +35,177 @@ impl UltraLowMemoryWorkbook {
         })
     }
 
+    /// Build the workbook in memory instead of a file
+    pub(crate) fn in_memory(compression_level: u32) -> Result<Self> {
+        Ok(UltraLowMemoryWorkbook {
+            inner: ZeroTempWorkbook::in_memory(compression_level.min(9))?,
+            compression_level: compression_level.min(9),
+        })
+    }
+
     pub fn protect_sheet(&mut self, options: ProtectionOptions) -> Result<()> {
         self.inner.protect_sheet(options)
     }
 
+    pub fn add_conditional_format(&mut self, range: &str, format: ConditionalFormat) -> Result<()> {
+        self.inner.add_conditional_format(range, format)
+    }
+
+    pub fn set_page_setup(&mut self, setup: PageSetup) -> Result<()> {
+        self.inner.set_page_setup(setup)
+    }
+
+    pub fn set_sheet_hidden(&mut self, name: &str, hidden: bool) -> Result<()> {
+        self.inner.set_sheet_hidden(name, hidden)
+    }
+
+    pub fn set_tab_color(&mut self, name: &str, color: &str) -> Result<()> {
+        self.inner.set_tab_color(name, color)
+    }
+
+    pub fn move_sheet(&mut self, name: &str, position: usize) -> Result<()> {
+        self.inner.move_sheet(name, position)
+    }
+
+    pub fn set_active_sheet(&mut self, name: &str) -> Result<()> {
+        self.inner.set_active_sheet(name)
+    }
+
+    pub fn set_header(&mut self, text: &str) -> Result<()> {
+        self.inner.set_header(text)
+    }
+
+    pub fn set_footer(&mut self, text: &str) -> Result<()> {
+        self.inner.set_footer(text)
+    }
+
+    pub fn set_print_title_rows(&mut self, rows: &str) -> Result<()> {
+        self.inner.set_print_title_rows(rows)
+    }
+
+    pub fn define_name(&mut self, name: &str, refers_to: &str) -> Result<()> {
+        self.inner.define_name(name, refers_to)
+    }
+
+    pub fn set_properties(&mut self, properties: DocumentProperties) -> Result<()> {
+        self.inner.set_properties(properties)
+    }
+
+    pub fn add_chart(
+        &mut self,
+        chart_type: ChartType,
+        data_range: &str,
+        anchor_cell: &str,
+        options: ChartOptions,
+    ) -> Result<()> {
+        self.inner
+            .add_chart(chart_type, data_range, anchor_cell, options)
+    }
+
+    pub fn add_shared_formula(&mut self, range: &str, formula: &str) -> Result<()> {
+        self.inner.add_shared_formula(range, formula)
+    }
+
+    pub fn add_table(&mut self, range: &str, options: TableOptions) -> Result<()> {
+        self.inner.add_table(range, options)
+    }
+
+    pub fn add_data_validation(&mut self, range: &str, validation: DataValidation) -> Result<()> {
+        self.inner.add_data_validation(range, validation)
+    }
+
     pub fn add_worksheet(&mut self, name: &str) -> Result<()> {
         self.inner.add_worksheet(name)
     }
 
+    pub fn set_sheet_name_policy(&mut self, policy: SheetNamePolicy) {
+        self.inner.set_sheet_name_policy(policy)
+    }
+
+    pub fn set_date_system(&mut self, system: crate::types::DateSystem) -> Result<()> {
+        self.inner.set_date_system(system)
+    }
+
+    pub fn set_timezone_policy(&mut self, policy: crate::types::TimezonePolicy) {
+        self.inner.set_timezone_policy(policy)
+    }
+
+    pub fn timezone_policy(&self) -> crate::types::TimezonePolicy {
+        self.inner.timezone_policy()
+    }
+
+    pub fn current_sheet_name(&self) -> Option<&str> {
+        self.inner.current_sheet_name()
+    }
+
+    pub fn current_row(&self) -> u32 {
+        self.inner.current_row()
+    }
+
+    pub fn set_auto_shard_rows(&mut self, max_rows_per_sheet: u32) -> Result<()> {
+        self.inner.set_auto_shard_rows(max_rows_per_sheet)
+    }
+
+    pub fn set_outline_settings(&mut self, settings: OutlineSettings) -> Result<()> {
+        self.inner.set_outline_settings(settings)
+    }
+
+    pub fn group_columns(&mut self, first: u32, last: u32, collapsed: bool) -> Result<()> {
+        self.inner.group_columns(first, last, collapsed)
+    }
+
+    pub fn set_column_width(&mut self, col: u32, width: f64) -> Result<()> {
+        self.inner.set_column_width(col, width)
+    }
+
+    pub fn defer_column_widths(&mut self) -> Result<()> {
+        self.inner.defer_column_widths()
+    }
+
+    pub fn set_column_style(&mut self, col: u32, style: CellStyle) -> Result<()> {
+        self.inner.set_column_style(col, style)
+    }
+
+    pub fn set_column_decimals(&mut self, col: u32, decimals: Option<u8>) -> Result<()> {
+        self.inner.set_column_decimals(col, decimals)
+    }
+
+    pub fn set_column_as_text(&mut self, col: u32, as_text: bool) -> Result<()> {
+        self.inner.set_column_as_text(col, as_text)
+    }
+
+    pub fn set_row_banding(&mut self, even: CellStyle, odd: CellStyle) {
+        self.inner.set_row_banding(even, odd)
+    }
+
+    pub fn clear_row_banding(&mut self) {
+        self.inner.clear_row_banding()
+    }
+
+    pub fn autofit_columns(&mut self, options: AutofitOptions) -> Result<()> {
+        self.inner.autofit_columns(options)
+    }
+
+    pub fn start_row_group(&mut self) -> Result<()> {
+        self.inner.start_row_group()
+    }
+
+    pub fn end_row_group(&mut self, collapsed: bool) -> Result<()> {
+        self.inner.end_row_group(collapsed)
+    }
+
+    pub fn add_buffered_worksheet(&mut self, name: &str) -> Result<usize> {
+        self.inner.add_buffered_worksheet(name)
+    }
+
+    pub(crate) fn with_buffered_worksheet<T>(
+        &mut self,
+        index: usize,
+        f: impl FnOnce(&mut ZeroTempWorkbook) -> Result<T>,
+    ) -> Result<T> {
+        self.inner.with_buffered_worksheet(index, f)
+    }
+
     pub fn write_row<I, S>(&mut self, values: I) -> Result<()>
     where
         I: IntoIterator<Item = S>,
@@ -43,14 +214,36 @@ impl UltraLowMemoryWorkbook {
         self.inner.write_row(values)
     }
 
+    pub fn write_row_display<I>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.inner.write_row_display(values)
+    }
+
     pub fn write_row_typed(&mut self, values: &[CellValue]) -> Result<()> {
-        // Convert to StyledCell with default style to preserve types
-        let styled_cells: Vec<crate::types::StyledCell> = values
-            .iter()
-            .map(|v| crate::types::StyledCell::new(v.clone(), crate::types::CellStyle::Default))
-            .collect();
+        self.inner.write_row_typed_iter(values)
+    }
+
+    pub fn write_row_refs<'a, I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<CellRef<'a>>,
+    {
+        self.inner.write_row_refs(cells)
+    }
+
+    pub fn write_columns(&mut self, columns: &[ColumnSlice]) -> Result<usize> {
+        self.inner.write_columns(columns)
+    }
 
-        self.inner.write_row_styled(&styled_cells)
+    pub fn write_row_typed_iter<I>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CellValue>,
+    {
+        self.inner.write_row_typed_iter(values)
     }
 
     pub fn write_row_styled(&mut self, values: &[crate::types::StyledCell]) -> Result<()> {
@@ -58,6 +251,23 @@ impl UltraLowMemoryWorkbook {
         self.inner.write_row_styled(values)
     }
 
+    pub fn write_row_at(&mut self, row: u32, cells: &[crate::types::StyledCell]) -> Result<()> {
+        self.inner.write_row_at(row, cells)
+    }
+
+    pub fn write_cell(&mut self, row: u32, col: u32, cell: crate::types::StyledCell) -> Result<()> {
+        self.inner.write_cell(row, col, cell)
+    }
+
+    /// Write every row of an Arrow batch as typed cells
+    #[cfg(feature = "parquet-support")]
+    pub fn write_record_batch(
+        &mut self,
+        batch: &arrow::record_batch::RecordBatch,
+    ) -> Result<usize> {
+        crate::parquet::batch::for_each_row(batch, |cells| self.inner.write_row_styled(cells))
+    }
+
     pub fn set_compression_level(&mut self, level: u32) {
         self.compression_level = level.min(9);
     }
@@ -66,16 +276,47 @@ impl UltraLowMemoryWorkbook {
         self.compression_level
     }
 
+    pub fn set_progress_callback(&mut self, callback: ProgressCallback) {
+        self.inner.set_progress_callback(callback)
+    }
+
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.inner.set_cancellation_token(token)
+    }
+
+    pub fn set_metrics(&mut self, metrics: Box<dyn WriterMetrics>) {
+        self.inner.set_metrics(metrics)
+    }
+
+    pub fn set_checksum(&mut self, checksum: Checksum) {
+        self.inner.set_checksum(checksum)
+    }
+
+    pub fn set_compression_workers(&mut self, workers: usize) -> Result<()> {
+        self.inner.set_compression_workers(workers)
+    }
+
+    pub fn compression_workers(&self) -> usize {
+        self.inner.compression_workers()
+    }
+
+    pub fn set_string_mode(&mut self, mode: StringMode) -> Result<()> {
+        self.inner.set_string_mode(mode)
+    }
+
     pub fn close(self) -> Result<()> {
         self.inner.close()
     }
 
-    // Stub methods for API compatibility
-    pub fn set_column_width(&mut self, _col: u32, _width: f64) -> Result<()> {
-        // TODO: Implement in ZeroTempWorkbook
-        Ok(())
+    pub fn close_with_report(self) -> Result<WriteReport> {
+        self.inner.close_with_report()
     }
 
+    pub(crate) fn close_into_bytes(self) -> Result<Vec<u8>> {
+        self.inner.close_into_bytes()
+    }
+
+    // Stub methods for API compatibility
     pub fn set_next_row_height(&mut self, _height: f64) -> Result<()> {
         // TODO: Implement in ZeroTempWorkbook
         Ok(())
@@ -1,24 +1,46 @@
 //! Fast worksheet implementation with optimized row writing
 
+use super::number::f64_text;
 use super::shared_strings::SharedStrings;
 use super::xml_writer::XmlWriter;
 use crate::error::Result;
 use std::io::Write;
 
 /// Cell reference generator
+///
+/// Column letters are worked out once per column and cached, however wide
+/// the rows get, so a cell reference costs a copy rather than a `format!`.
 struct CellRef {
     row: u32,
     col: u32,
+    /// Letters of columns 1 to `letters.len()`
+    letters: Vec<Box<str>>,
+    /// Reference of the current cell
+    buffer: String,
 }
 
 impl CellRef {
     fn new() -> Self {
-        CellRef { row: 1, col: 0 }
+        CellRef {
+            row: 0,
+            col: 0,
+            letters: Vec::new(),
+            buffer: String::new(),
+        }
     }
 
-    fn next_cell(&mut self) -> String {
+    /// Reference of the next cell in the row; callers check the row width
+    /// against [`MAX_SHEET_COLUMNS`](crate::sheet_name::MAX_SHEET_COLUMNS)
+    fn next_cell(&mut self) -> &str {
         self.col += 1;
-        self.to_cell_ref(self.row, self.col)
+        while self.letters.len() < self.col as usize {
+            let col = self.letters.len() as u32 + 1;
+            self.letters.push(column_letters(col).into_boxed_str());
+        }
+        self.buffer.clear();
+        self.buffer.push_str(&self.letters[self.col as usize - 1]);
+        self.buffer.push_str(itoa::Buffer::new().format(self.row));
+        &self.buffer
     }
 
     fn next_row(&mut self) {
@@ -26,16 +48,22 @@ impl CellRef {
         self.col = 0;
     }
 
+    #[cfg(test)]
     fn to_cell_ref(&self, row: u32, col: u32) -> String {
-        let mut col_str = String::new();
-        let mut n = col;
-        while n > 0 {
-            let rem = (n - 1) % 26;
-            col_str.insert(0, (b'A' + rem as u8) as char);
-            n = (n - 1) / 26;
-        }
-        format!("{}{}", col_str, row)
+        format!("{}{}", column_letters(col), row)
+    }
+}
+
+/// Letters of the 1-based column `col` (`A`, `Z`, `AA`, ...)
+fn column_letters(col: u32) -> String {
+    let mut letters = String::new();
+    let mut n = col;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        letters.insert(0, (b'A' + rem as u8) as char);
+        n = (n - 1) / 26;
     }
+    letters
 }
 
 /// Fast worksheet writer
@@ -79,6 +107,7 @@ impl<W: Write> FastWorksheet<W> {
 
     /// Write a row of string data
     pub fn write_row(&mut self, values: &[&str]) -> Result<()> {
+        crate::sheet_name::check_columns(values.len())?;
         self.cell_ref.next_row();
         self.row_count += 1;
 
@@ -93,7 +122,7 @@ impl<W: Write> FastWorksheet<W> {
             let string_index = self.shared_strings.add_string(value);
 
             self.xml_writer.start_element("c")?;
-            self.xml_writer.attribute("r", &cell_ref)?;
+            self.xml_writer.attribute("r", cell_ref)?;
             self.xml_writer.attribute("t", "s")?; // String type
             self.xml_writer.close_start_tag()?;
 
@@ -127,6 +156,7 @@ impl<W: Write> FastWorksheet<W> {
     pub fn write_row_styled(&mut self, cells: &[crate::types::StyledCell]) -> Result<()> {
         use crate::types::CellValue;
 
+        crate::sheet_name::check_columns(cells.len())?;
         self.cell_ref.next_row();
         self.row_count += 1;
 
@@ -138,17 +168,19 @@ impl<W: Write> FastWorksheet<W> {
         // Write cells
         for cell in cells {
             let cell_ref = self.cell_ref.next_cell();
-            let style_index = cell.style.index();
+            let style_index = cell.effective_style().index();
 
             match &cell.value {
                 CellValue::Empty => {
                     // Skip empty cells
                 }
-                CellValue::String(s) => {
+                CellValue::String(s)
+                | CellValue::BigInt(s)
+                | CellValue::Hyperlink { text: s, .. } => {
                     let string_index = self.shared_strings.add_string(s);
 
                     self.xml_writer.start_element("c")?;
-                    self.xml_writer.attribute("r", &cell_ref)?;
+                    self.xml_writer.attribute("r", cell_ref)?;
                     if style_index > 0 {
                         self.xml_writer.attribute_int("s", style_index as i64)?;
                     }
@@ -164,7 +196,7 @@ impl<W: Write> FastWorksheet<W> {
                 }
                 CellValue::Int(n) => {
                     self.xml_writer.start_element("c")?;
-                    self.xml_writer.attribute("r", &cell_ref)?;
+                    self.xml_writer.attribute("r", cell_ref)?;
                     if style_index > 0 {
                         self.xml_writer.attribute_int("s", style_index as i64)?;
                     }
@@ -180,23 +212,48 @@ impl<W: Write> FastWorksheet<W> {
                 }
                 CellValue::Float(f) => {
                     self.xml_writer.start_element("c")?;
-                    self.xml_writer.attribute("r", &cell_ref)?;
+                    self.xml_writer.attribute("r", cell_ref)?;
                     if style_index > 0 {
                         self.xml_writer.attribute_int("s", style_index as i64)?;
                     }
-                    self.xml_writer.attribute("t", "n")?; // Number type
+                    if f.is_finite() {
+                        self.xml_writer.attribute("t", "n")?; // Number type
+                    } else {
+                        self.xml_writer.attribute("t", "e")?; // No number form: #NUM!
+                    }
+                    self.xml_writer.close_start_tag()?;
+
+                    self.xml_writer.start_element("v")?;
+                    self.xml_writer.close_start_tag()?;
+                    if f.is_finite() {
+                        self.xml_writer.write_str(&f64_text(*f))?;
+                    } else {
+                        self.xml_writer.write_str("#NUM!")?;
+                    }
+                    self.xml_writer.end_element("v")?;
+
+                    self.xml_writer.end_element("c")?;
+                }
+                #[cfg(feature = "decimal")]
+                CellValue::Decimal(d) => {
+                    self.xml_writer.start_element("c")?;
+                    self.xml_writer.attribute("r", cell_ref)?;
+                    if style_index > 0 {
+                        self.xml_writer.attribute_int("s", style_index as i64)?;
+                    }
+                    self.xml_writer.attribute("t", "n")?;
                     self.xml_writer.close_start_tag()?;
 
                     self.xml_writer.start_element("v")?;
                     self.xml_writer.close_start_tag()?;
-                    self.xml_writer.write_str(&f.to_string())?;
+                    self.xml_writer.write_str(&d.to_string())?;
                     self.xml_writer.end_element("v")?;
 
                     self.xml_writer.end_element("c")?;
                 }
                 CellValue::Bool(b) => {
                     self.xml_writer.start_element("c")?;
-                    self.xml_writer.attribute("r", &cell_ref)?;
+                    self.xml_writer.attribute("r", cell_ref)?;
                     if style_index > 0 {
                         self.xml_writer.attribute_int("s", style_index as i64)?;
                     }
@@ -210,20 +267,35 @@ impl<W: Write> FastWorksheet<W> {
 
                     self.xml_writer.end_element("c")?;
                 }
-                CellValue::Formula(formula) => {
+                CellValue::Formula { expr, cached } => {
+                    let result = cached.as_deref().and_then(CellValue::formula_result);
                     self.xml_writer.start_element("c")?;
-                    self.xml_writer.attribute("r", &cell_ref)?;
+                    self.xml_writer.attribute("r", cell_ref)?;
                     if style_index > 0 {
                         self.xml_writer.attribute_int("s", style_index as i64)?;
                     }
+                    match result.as_ref().map(|(type_attr, _)| *type_attr) {
+                        Some(" t=\"b\"") => self.xml_writer.attribute("t", "b")?,
+                        Some(" t=\"e\"") => self.xml_writer.attribute("t", "e")?,
+                        Some(" t=\"str\"") => self.xml_writer.attribute("t", "str")?,
+                        _ => {}
+                    }
                     self.xml_writer.close_start_tag()?;
 
                     // Write formula
                     self.xml_writer.start_element("f")?;
                     self.xml_writer.close_start_tag()?;
-                    self.xml_writer.write_str(formula)?;
+                    self.xml_writer
+                        .write_str(expr.strip_prefix('=').unwrap_or(expr))?;
                     self.xml_writer.end_element("f")?;
 
+                    if let Some((_, value)) = &result {
+                        self.xml_writer.start_element("v")?;
+                        self.xml_writer.close_start_tag()?;
+                        self.xml_writer.write_str(value)?;
+                        self.xml_writer.end_element("v")?;
+                    }
+
                     self.xml_writer.end_element("c")?;
                 }
                 CellValue::DateTime(_) | CellValue::Error(_) => {
@@ -232,7 +304,7 @@ impl<W: Write> FastWorksheet<W> {
                     let string_index = self.shared_strings.add_string(&s);
 
                     self.xml_writer.start_element("c")?;
-                    self.xml_writer.attribute("r", &cell_ref)?;
+                    self.xml_writer.attribute("r", cell_ref)?;
                     if style_index > 0 {
                         self.xml_writer.attribute_int("s", style_index as i64)?;
                     }
@@ -282,6 +354,7 @@ mod tests {
         assert_eq!(cell_ref.to_cell_ref(1, 26), "Z1");
         assert_eq!(cell_ref.to_cell_ref(1, 27), "AA1");
         assert_eq!(cell_ref.to_cell_ref(100, 1), "A100");
+        assert_eq!(cell_ref.to_cell_ref(1, 16_384), "XFD1");
     }
 
     #[test]
@@ -298,6 +371,28 @@ mod tests {
         let xml = String::from_utf8(output).unwrap();
         assert!(xml.contains("<row r=\"1\">"));
         assert!(xml.contains("<row r=\"2\">"));
+        assert!(xml.contains("<c r=\"A1\" t=\"s\">"));
+        assert!(xml.contains("<c r=\"B2\" t=\"s\">"));
         assert_eq!(ss.count(), 4); // Name, Age, Alice, 30
     }
+
+    #[test]
+    fn test_worksheet_column_limit() {
+        let mut output = Vec::new();
+        let mut ws = FastWorksheet::new(&mut output, SharedStrings::new()).unwrap();
+
+        let widest = vec!["x"; 16_384];
+        ws.write_row(&widest).unwrap();
+        let too_wide = vec!["x"; 16_385];
+        let err = ws.write_row(&too_wide).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::ExcelError::TooManyColumns { column: 16_385 }
+        ));
+        assert_eq!(ws.row_count(), 1);
+        ws.finish().unwrap();
+
+        let xml = String::from_utf8(output).unwrap();
+        assert!(xml.contains("<c r=\"XFD1\" t=\"s\">"));
+    }
 }
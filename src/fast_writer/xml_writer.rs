@@ -3,6 +3,41 @@
 use crate::error::Result;
 use std::io::Write;
 
+/// Whether `c` may appear in an XML 1.0 document at all, escaped or not
+pub(crate) fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+/// Append `text` escaped for element content or a quoted attribute value
+///
+/// Characters XML can't represent (most C0 controls, U+FFFE, U+FFFF) are
+/// dropped; they would make Excel reject the whole part.
+pub(crate) fn push_escaped(buffer: &mut Vec<u8>, text: &str) {
+    // Iterate over Unicode scalar values to avoid splitting UTF-8 sequences.
+    for c in text.chars() {
+        match c {
+            '&' => buffer.extend_from_slice(b"&amp;"),
+            '<' => buffer.extend_from_slice(b"&lt;"),
+            '>' => buffer.extend_from_slice(b"&gt;"),
+            '"' => buffer.extend_from_slice(b"&quot;"),
+            '\'' => buffer.extend_from_slice(b"&apos;"),
+            c if !is_xml_char(c) => {}
+            c => {
+                let mut buf = [0u8; 4];
+                buffer.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+}
+
+/// `text` escaped as by [`push_escaped`]
+pub(crate) fn escape(text: &str) -> String {
+    let mut buffer = Vec::with_capacity(text.len());
+    push_escaped(&mut buffer, text);
+    // Escaping only removes characters or inserts ASCII
+    String::from_utf8(buffer).expect("escaped text is UTF-8")
+}
+
 /// Fast XML writer that writes directly to output without intermediate buffers
 pub struct XmlWriter<W: Write> {
     writer: W,
@@ -98,28 +133,7 @@ impl<W: Write> XmlWriter<W> {
     /// Write text content with XML escaping
     #[inline]
     pub fn write_escaped(&mut self, text: &str) -> Result<()> {
-        // Iterate over Unicode scalar values to avoid splitting UTF-8 sequences.
-        for ch in text.chars() {
-            match ch {
-                '&' => self.write_raw(b"&amp;")?,
-                '<' => self.write_raw(b"&lt;")?,
-                '>' => self.write_raw(b"&gt;")?,
-                '"' => self.write_raw(b"&quot;")?,
-                '\'' => self.write_raw(b"&apos;")?,
-                // Allowed XML whitespace characters: tab (U+0009), LF (U+000A), CR (U+000D)
-                c if (c as u32) < 0x20 && c != '\t' && c != '\n' && c != '\r' => {
-                    // Drop illegal control characters (they would break XML parsers on Windows)
-                    continue;
-                }
-                c => {
-                    // Write UTF-8 bytes of the character
-                    let mut buf = [0u8; 4];
-                    let s = c.encode_utf8(&mut buf);
-                    self.buffer.extend_from_slice(s.as_bytes());
-                }
-            }
-        }
-
+        push_escaped(&mut self.buffer, text);
         self.auto_flush()
     }
 
@@ -169,4 +183,11 @@ mod tests {
             "&lt;test&gt;&amp;value&lt;/test&gt;"
         );
     }
+
+    #[test]
+    fn test_escape_drops_forbidden_characters() {
+        assert_eq!(escape("a\u{1}b\tc\u{fffe}'\"😀"), "ab\tc&apos;&quot;😀");
+        assert!(!is_xml_char('\u{b}'));
+        assert!(is_xml_char('\u{10ffff}'));
+    }
 }
@@ -2,230 +2,2353 @@
 //!
 //! Expected memory: 8-12 MB (vs 17MB with temp files)
 
+use super::chart::{self, Chart, ChartRange};
+use super::conditional_format;
+use super::data_validation;
+use super::hashed_strings::HashedStrings;
+use super::hyperlink;
+use super::number::{f64_text, push_number_cell, round_decimals};
+use super::pipeline::ZipSink;
 use super::shared_strings::SharedStrings;
-use super::StreamingZipWriter;
+use super::spill::{SpillBuffer, DEFAULT_SPILL_THRESHOLD};
+use super::table::{self, Table};
+use super::xml_writer::XmlWriter;
 use crate::error::Result;
-use crate::types::ProtectionOptions;
+use crate::progress::{
+    CancellationToken, Progress, ProgressCallback, WriterMetrics, PROGRESS_INTERVAL,
+};
+use crate::report::{Checksum, ChecksumSetting, HashingWriter, OutputStats, WriteReport};
+use crate::sheet_name::Shards;
+use crate::types::{
+    argb_color, AutofitOptions, CellRef, CellStyle, CellValue, ChartOptions, ChartType,
+    ColumnSlice, ConditionalFormat, ConditionalStyle, DataValidation, DateSystem,
+    DocumentProperties, Orientation, OutlineSettings, PageSetup, PropertyValue, ProtectionOptions,
+    SheetNamePolicy, StringMode, StyledCell, TableOptions, TimezonePolicy,
+};
 use itoa;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Write};
+use std::io::Write as _;
+
+/// Deepest row or column outline level Excel supports
+const MAX_OUTLINE_LEVEL: usize = 7;
+/// Width of a default column (8.43 characters of 11pt Calibri)
+const DEFAULT_COL_WIDTH: &str = "9.140625";
+/// Longest header or footer Excel accepts, codes included
+const MAX_HEADER_FOOTER_LEN: usize = 255;
+/// Last 0-based row of a worksheet
+const MAX_ROW: u32 = 1_048_575;
+/// Estimated column units per character of content, and the cell padding
+/// added on top, for auto-fitted columns
+const AUTOFIT_CHAR_WIDTH: f64 = 1.1;
+const AUTOFIT_PADDING: f64 = 1.0;
+
+const WORKSHEET_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+"#;
 
 /// Workbook that streams XML directly into compressor (no temp files)
 pub struct ZeroTempWorkbook {
-    zip_writer: Option<StreamingZipWriter<std::fs::File>>,
+    zip_writer: Option<ZipSink>,
+    checksum: ChecksumSetting,
     worksheets: Vec<String>,
+    // Tab order, as indices into `worksheets`
+    sheet_order: Vec<usize>,
+    // Indices into `worksheets` of hidden sheets
+    hidden_sheets: Vec<usize>,
+    // Index into `worksheets` of the tab shown when the file opens
+    active_tab: Option<usize>,
+    // ARGB tab colors by index into `worksheets`
+    tab_colors: BTreeMap<usize, String>,
     worksheet_count: u32,
     current_row: u32,
     max_col: u32,
     xml_buffer: Vec<u8>,
-    #[allow(dead_code)]
+    scratch: String,
+    string_mode: StringMode,
+    sheet_name_policy: SheetNamePolicy,
     shared_strings: SharedStrings,
+    hashed_strings: Option<HashedStrings>,
     #[allow(dead_code)]
     protection: Option<ProtectionOptions>,
+    page_setup: Option<PageSetup>,
+    // (name, formula, local sheet index) for workbook.xml's <definedNames>
+    defined_names: Vec<(String, String, Option<usize>)>,
+    // Current sheet has BigInt cells stored as text
+    has_numbers_as_text: bool,
+    // (cell reference, target) of the current sheet's hyperlinks
+    hyperlinks: Vec<(String, String)>,
+    // Hyperlinks of the row being built, kept once the row is written
+    row_hyperlinks: Vec<(String, String)>,
+    // (sqref, rule, dxf id) of the current sheet's conditional formats
+    conditional_formats: Vec<(String, ConditionalFormat, Option<usize>)>,
+    // Differential formats referenced by conditional formats, workbook-wide
+    dxfs: Vec<ConditionalStyle>,
+    // (sqref, rule) of the current sheet's data validations
+    data_validations: Vec<(String, DataValidation)>,
+    // Charts of the current sheet
+    charts: Vec<Chart>,
+    // Charts / drawings written so far, numbering xl/charts and xl/drawings
+    chart_count: u32,
+    drawing_count: u32,
+    // Tables of the current sheet
+    tables: Vec<Table>,
+    // Shared formulas of the current sheet, indexed by their `si`
+    shared_formulas: Vec<SharedFormula>,
+    // Tables written so far, numbering xl/tables
+    table_count: u32,
+    // Names of all tables in the workbook, which must be unique
+    table_names: Vec<String>,
     in_worksheet: bool,
+    // docProps metadata, written on close
+    properties: DocumentProperties,
+    progress: Progress,
+    metrics: Option<Box<dyn WriterMetrics>>,
+    // Size and flushes of the output, for `metrics`
+    output_stats: OutputStats,
+    // 1-based number of the worksheet being streamed
+    active_sheet: u32,
+    // Worksheets whose rows are held back until close
+    buffered: Vec<BufferedSheet>,
+    // Index into `buffered` while rows are routed there
+    redirect: Option<usize>,
+    // Worksheet XML up to <sheetData> is written with the first row
+    sheet_data_started: bool,
+    outline: OutlineSettings,
+    // (first, last, collapsed) 0-based column groups of the current sheet
+    column_groups: Vec<(u32, u32, bool)>,
+    // First row of each open row group, innermost last
+    row_groups: Vec<u32>,
+    // Rows of open row groups, held back until the outermost group ends
+    group_rows: Option<SpillBuffer>,
+    // (first, last) rows of collapsed groups among `group_rows`
+    hidden_rows: Vec<(u32, u32)>,
+    // The next row is the summary row of a collapsed group
+    collapse_next_row: bool,
+    // Explicit 0-based column widths of the current sheet
+    column_widths: BTreeMap<u32, f64>,
+    // Rows held back so `<cols>` can be written when the sheet finishes
+    deferred: Option<DeferredCols>,
+    // Styles of cells written without one, by 0-based column, on the current sheet
+    column_styles: BTreeMap<u32, CellStyle>,
+    // Most decimals written for floats, by 0-based column, on the current sheet
+    column_decimals: BTreeMap<u32, u8>,
+    // 0-based columns of the current sheet whose numbers are written as text
+    text_columns: BTreeSet<u32>,
+    // (even, odd) row styles of the current sheet, below the column styles
+    row_banding: Option<(CellStyle, CellStyle)>,
+    // Cells set with `write_cell` on the current sheet by 0-based row and
+    // column, written when the sheet finishes
+    sparse_cells: BTreeMap<u32, BTreeMap<u32, StyledCell>>,
+    // Rolling rows over to a new sheet, see `set_auto_shard_rows`; the
+    // header is row 1 and its width
+    shards: Shards<(Vec<u8>, u32)>,
+    // Day zero of the serials date cells are stored as
+    date_system: DateSystem,
+    // Set with `set_timezone_policy`, and then recorded in the custom
+    // properties
+    timezone_policy: Option<TimezonePolicy>,
+}
+
+/// Rows of a sheet whose column widths are settled after its data, see
+/// [`ZeroTempWorkbook::defer_column_widths`]
+struct DeferredCols {
+    /// Set by `autofit_columns`
+    autofit: Option<AutofitOptions>,
+    /// Widest estimated content per 0-based column, 0 for empty columns
+    widths: Vec<f64>,
+    /// Row XML, written after `<cols>` when the sheet finishes
+    rows: SpillBuffer,
+}
+
+/// Formula repeated over a block of cells, see
+/// [`ZeroTempWorkbook::add_shared_formula`]
+struct SharedFormula {
+    /// 1-based, inclusive (first column, first row, last column, last row)
+    block: (u32, u32, u32, u32),
+    range: String,
+    expr: String,
+    master_written: bool,
+}
+
+/// Rows and per-sheet state of a worksheet added with
+/// [`ZeroTempWorkbook::add_buffered_worksheet`]
+struct BufferedSheet {
+    number: u32,
+    rows: SpillBuffer,
+    current_row: u32,
+    max_col: u32,
+    has_numbers_as_text: bool,
 }
 
 impl ZeroTempWorkbook {
     pub fn new(path: &str, compression_level: u32) -> Result<Self> {
-        let zip_writer = StreamingZipWriter::with_compression(path, compression_level)?;
+        let checksum = ChecksumSetting::default();
+        let output = HashingWriter::create(std::path::Path::new(path), checksum.clone())?;
+        Self::with_output(output, checksum, compression_level)
+    }
+
+    /// Build the workbook in memory; [`close_into_bytes`](Self::close_into_bytes)
+    /// returns the finished file
+    pub(crate) fn in_memory(compression_level: u32) -> Result<Self> {
+        let checksum = ChecksumSetting::default();
+        let output = HashingWriter::in_memory(checksum.clone());
+        Self::with_output(output, checksum, compression_level)
+    }
+
+    fn with_output(
+        output: HashingWriter,
+        checksum: ChecksumSetting,
+        compression_level: u32,
+    ) -> Result<Self> {
+        let output_stats = output.stats();
 
         Ok(Self {
-            zip_writer: Some(zip_writer),
+            zip_writer: Some(ZipSink::new(output, compression_level)),
+            checksum,
             worksheets: Vec::new(),
+            sheet_order: Vec::new(),
+            hidden_sheets: Vec::new(),
+            active_tab: None,
+            tab_colors: BTreeMap::new(),
             worksheet_count: 0,
             current_row: 0,
             max_col: 0,
             xml_buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
+            string_mode: StringMode::Inline,
+            sheet_name_policy: SheetNamePolicy::Strict,
             shared_strings: SharedStrings::new(),
+            hashed_strings: None,
             protection: None,
+            page_setup: None,
+            defined_names: Vec::new(),
+            has_numbers_as_text: false,
+            hyperlinks: Vec::new(),
+            row_hyperlinks: Vec::new(),
+            conditional_formats: Vec::new(),
+            dxfs: Vec::new(),
+            data_validations: Vec::new(),
+            charts: Vec::new(),
+            chart_count: 0,
+            drawing_count: 0,
+            tables: Vec::new(),
+            shared_formulas: Vec::new(),
+            table_count: 0,
+            table_names: Vec::new(),
             in_worksheet: false,
+            properties: DocumentProperties::default(),
+            progress: Progress::default(),
+            metrics: None,
+            output_stats,
+            active_sheet: 0,
+            buffered: Vec::new(),
+            redirect: None,
+            sheet_data_started: false,
+            outline: OutlineSettings::default(),
+            column_groups: Vec::new(),
+            row_groups: Vec::new(),
+            group_rows: None,
+            hidden_rows: Vec::new(),
+            collapse_next_row: false,
+            column_widths: BTreeMap::new(),
+            deferred: None,
+            column_styles: BTreeMap::new(),
+            column_decimals: BTreeMap::new(),
+            text_columns: BTreeSet::new(),
+            row_banding: None,
+            sparse_cells: BTreeMap::new(),
+            shards: Shards::new(None),
+            date_system: DateSystem::V1900,
+            timezone_policy: None,
         })
     }
 
+    /// How [`Self::add_worksheet`] and [`Self::add_buffered_worksheet`]
+    /// treat names Excel wouldn't accept (default: reject them)
+    pub fn set_sheet_name_policy(&mut self, policy: SheetNamePolicy) {
+        self.sheet_name_policy = policy;
+    }
+
+    /// Store dates as serials of `system`, see [`DateSystem`]
+    ///
+    /// Fails once a row or cell has been written, since those dates are
+    /// already stored.
+    pub fn set_date_system(&mut self, system: DateSystem) -> Result<()> {
+        if self.current_row > 0
+            || self.worksheet_count > 1
+            || !self.sparse_cells.is_empty()
+            || !self.buffered.is_empty()
+        {
+            return Err(crate::error::ExcelError::WriteError(
+                "The date system must be set before the first row of the workbook".to_string(),
+            ));
+        }
+        self.date_system = system;
+        Ok(())
+    }
+
+    /// Name of the streamed worksheet rows are written to
+    pub fn current_sheet_name(&self) -> Option<&str> {
+        let index = (self.active_sheet as usize).checked_sub(1)?;
+        self.worksheets.get(index).map(String::as_str)
+    }
+
+    /// Rows written to the streamed worksheet so far
+    pub fn current_row(&self) -> u32 {
+        self.current_row
+    }
+
+    /// Roll over to a new sheet once a sheet holds `max_rows_per_sheet` rows
+    ///
+    /// Rows past the limit go to `Data_2`, `Data_3`, ... for a sheet named
+    /// `Data`, each starting with a copy of the sheet's first row as its
+    /// header. Column widths, styles and banding carry over to the new
+    /// sheets. Without this, a row past Excel's 1 048 576 rows fails.
+    pub fn set_auto_shard_rows(&mut self, max_rows_per_sheet: u32) -> Result<()> {
+        self.shards.set_rows(max_rows_per_sheet)?;
+        Ok(())
+    }
+
     pub fn add_worksheet(&mut self, name: &str) -> Result<()> {
+        let name = crate::sheet_name::resolve(name, &self.worksheets, self.sheet_name_policy)?;
         // Finish previous worksheet if any
         self.finish_current_worksheet()?;
 
         self.worksheet_count += 1;
-        self.worksheets.push(name.to_string());
+        self.sheet_order.push(self.worksheets.len());
+        self.worksheets.push(name);
+        self.active_sheet = self.worksheet_count;
         self.current_row = 0;
         self.max_col = 0;
         // Reset protection for new worksheet
         self.protection = None;
+        self.page_setup = None;
+        self.has_numbers_as_text = false;
+        self.hyperlinks.clear();
+        self.conditional_formats.clear();
+        self.data_validations.clear();
+        self.charts.clear();
+        self.tables.clear();
+        self.shared_formulas.clear();
+        self.outline = OutlineSettings::default();
+        self.column_groups.clear();
+        self.column_widths.clear();
+        self.deferred = None;
+        self.column_styles.clear();
+        self.column_decimals.clear();
+        self.text_columns.clear();
+        self.row_banding = None;
+        self.shards.reset();
 
-        // Start new worksheet entry in ZIP
+        // Start new worksheet entry in ZIP; the header follows with the first row
         let entry_name = format!("xl/worksheets/sheet{}.xml", self.worksheet_count);
         self.zip_writer.as_mut().unwrap().start_entry(&entry_name)?;
+        self.sheet_data_started = false;
+        self.in_worksheet = true;
 
-        // Write worksheet XML header
-        let header = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
-<sheetData>"#;
+        Ok(())
+    }
 
-        self.zip_writer
+    /// Write the worksheet XML that precedes `<sheetData>`, once per sheet
+    fn start_sheet_data(&mut self) -> Result<()> {
+        if self.sheet_data_started {
+            return Ok(());
+        }
+        self.sheet_data_started = true;
+
+        let mut xml = Vec::with_capacity(512);
+        xml.extend_from_slice(WORKSHEET_HEADER.as_bytes());
+        let tab_color = self.tab_colors.get(&(self.active_sheet as usize - 1));
+        if tab_color.is_some() || self.outline != OutlineSettings::default() {
+            xml.extend_from_slice(b"<sheetPr>");
+            if let Some(color) = tab_color {
+                Self::push_tab_color(&mut xml, color);
+            }
+            if self.outline != OutlineSettings::default() {
+                xml.extend_from_slice(b"<outlinePr");
+                if !self.outline.summary_below {
+                    xml.extend_from_slice(b" summaryBelow=\"0\"");
+                }
+                if !self.outline.summary_right {
+                    xml.extend_from_slice(b" summaryRight=\"0\"");
+                }
+                xml.extend_from_slice(b"/>");
+            }
+            xml.extend_from_slice(b"</sheetPr>");
+        }
+        if !self.column_groups.is_empty()
+            || !self.column_widths.is_empty()
+            || self.deferred.is_some()
+        {
+            self.push_cols_xml(&mut xml);
+        }
+        xml.extend_from_slice(b"<sheetData>");
+
+        self.zip_writer.as_mut().unwrap().write_data(&xml)?;
+        Ok(())
+    }
+
+    fn check_before_first_row(&self, what: &str) -> Result<()> {
+        if !self.in_worksheet || self.sheet_data_started {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "{} must be set before the first row of a worksheet",
+                what
+            )));
+        }
+        Ok(())
+    }
+
+    /// Choose where summary rows and columns of outline groups are placed
+    ///
+    /// Must be called before the first row of the current worksheet.
+    pub fn set_outline_settings(&mut self, settings: OutlineSettings) -> Result<()> {
+        self.check_before_first_row("Outline settings")?;
+        self.outline = settings;
+        Ok(())
+    }
+
+    /// Group columns `first..=last` (0-based) under an outline button
+    ///
+    /// Overlapping calls nest: a column inside two groups gets level 2.
+    /// A collapsed group starts hidden. Must be called before the first row
+    /// of the current worksheet, because `<cols>` precedes the cell data.
+    pub fn group_columns(&mut self, first: u32, last: u32, collapsed: bool) -> Result<()> {
+        self.check_before_first_row("Column groups")?;
+        crate::sheet_name::check_columns(last as usize + 1)?;
+        if first > last {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Invalid column group {}..={}",
+                first, last
+            )));
+        }
+        let deepest = (first..=last)
+            .map(|col| self.column_level(col))
+            .max()
+            .unwrap_or(0);
+        if deepest >= MAX_OUTLINE_LEVEL {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Excel supports at most {} nested outline levels",
+                MAX_OUTLINE_LEVEL
+            )));
+        }
+        self.column_groups.push((first, last, collapsed));
+        Ok(())
+    }
+
+    fn column_level(&self, col: u32) -> usize {
+        self.column_groups
+            .iter()
+            .filter(|(first, last, _)| (*first..=*last).contains(&col))
+            .count()
+    }
+
+    /// Set the width of a column (0-based) in Excel's column units
+    ///
+    /// Must be called before the first row of the current worksheet, unless
+    /// [`Self::defer_column_widths`] or [`Self::autofit_columns`] was called
+    /// for it; then widths can be set until the sheet finishes, and they
+    /// override estimated widths.
+    pub fn set_column_width(&mut self, col: u32, width: f64) -> Result<()> {
+        if self.deferred.is_none() && self.sheet_data_started {
+            return Err(crate::error::ExcelError::WriteError(
+                "Column widths must be set before the first row of a worksheet, \
+                 or after defer_column_widths()"
+                    .to_string(),
+            ));
+        }
+        self.check_before_first_row("Column widths")?;
+        crate::sheet_name::check_columns(col as usize + 1)?;
+        if !(0.0..=255.0).contains(&width) {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Invalid width {} for column {}",
+                width, col
+            )));
+        }
+        self.column_widths.insert(col, width);
+        Ok(())
+    }
+
+    /// Style cells of column `col` (0-based) that are written without one
+    ///
+    /// Applies to rows of the current worksheet written from now on, taking
+    /// precedence over [`Self::set_row_banding`]. `CellStyle::Default`
+    /// removes the column's style.
+    pub fn set_column_style(&mut self, col: u32, style: CellStyle) -> Result<()> {
+        crate::sheet_name::check_columns(col as usize + 1)?;
+        if style == CellStyle::Default {
+            self.column_styles.remove(&col);
+        } else {
+            self.column_styles.insert(col, style);
+        }
+        Ok(())
+    }
+
+    /// Round floats written to column `col` (0-based) to at most `decimals`
+    /// places, or write them in full again with `None`
+    ///
+    /// Applies to rows of the current worksheet written from now on. Only
+    /// the stored value changes; use a number style to control how many
+    /// decimals Excel displays.
+    pub fn set_column_decimals(&mut self, col: u32, decimals: Option<u8>) -> Result<()> {
+        crate::sheet_name::check_columns(col as usize + 1)?;
+        match decimals {
+            Some(decimals) => self.column_decimals.insert(col, decimals),
+            None => self.column_decimals.remove(&col),
+        };
+        Ok(())
+    }
+
+    /// Write numbers in column `col` (0-based) as text, or as numbers again
+    /// with `false`
+    ///
+    /// Applies to rows of the current worksheet written from now on. Excel
+    /// keeps 15 significant digits of a number, so IDs and account numbers
+    /// longer than that lose their last digits; as text every digit is kept.
+    /// Excel's "number stored as text" warning is turned off for the sheet.
+    pub fn set_column_as_text(&mut self, col: u32, as_text: bool) -> Result<()> {
+        crate::sheet_name::check_columns(col as usize + 1)?;
+        if as_text {
+            self.text_columns.insert(col);
+        } else {
+            self.text_columns.remove(&col);
+        }
+        Ok(())
+    }
+
+    /// Digits of a number in a text column, as a cell written like `BigInt`
+    fn number_as_text(&self, col: u32, value: &CellValue) -> Option<CellValue> {
+        if !self.text_columns.contains(&col) {
+            return None;
+        }
+        let text = match value {
+            CellValue::Int(i) => i.to_string(),
+            CellValue::Float(f) if f.is_finite() => {
+                let value = match self.column_decimals.get(&col) {
+                    Some(&decimals) => round_decimals(*f, decimals),
+                    None => *f,
+                };
+                f64_text(value)
+            }
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => d.to_string(),
+            _ => return None,
+        };
+        Some(CellValue::BigInt(text))
+    }
+
+    /// Alternate the style of unstyled cells between even and odd rows of
+    /// the current worksheet, by row number as Excel shows it
+    pub fn set_row_banding(&mut self, even: CellStyle, odd: CellStyle) {
+        self.row_banding = Some((even, odd));
+    }
+
+    /// Stop banding the current worksheet's rows
+    pub fn clear_row_banding(&mut self) {
+        self.row_banding = None;
+    }
+
+    /// Style of a cell in `col_idx` of the current row written without one
+    fn fallback_style(&self, col_idx: u32) -> CellStyle {
+        // Column styles and banding belong to the streamed sheet
+        if self.redirect.is_some() {
+            return CellStyle::Default;
+        }
+        if let Some(style) = self.column_style(col_idx) {
+            return style;
+        }
+        match self.row_banding {
+            Some((even, _)) if self.current_row.is_multiple_of(2) => even,
+            Some((_, odd)) => odd,
+            None => CellStyle::Default,
+        }
+    }
+
+    /// Style set for column `col_idx` of the streamed sheet
+    fn column_style(&self, col_idx: u32) -> Option<CellStyle> {
+        if self.redirect.is_some() {
+            return None;
+        }
+        self.column_styles.get(&col_idx).copied()
+    }
+
+    /// Allow column widths to be set while the current sheet's rows are
+    /// written, e.g. from the longest value seen
+    ///
+    /// `<cols>` precedes the cell data, so the sheet's rows are held back
+    /// (spilling to a temporary file past 1 MB) and written after the
+    /// widths when the sheet finishes. Must be called before the first row
+    /// of the current worksheet.
+    pub fn defer_column_widths(&mut self) -> Result<()> {
+        self.check_before_first_row("Deferred column widths")?;
+        if self.deferred.is_none() {
+            self.deferred = Some(DeferredCols {
+                autofit: None,
+                widths: Vec::new(),
+                rows: SpillBuffer::new(DEFAULT_SPILL_THRESHOLD),
+            });
+        }
+        Ok(())
+    }
+
+    /// Size the current sheet's columns to fit their content
+    ///
+    /// Widths are estimated from the text of each cell as rows are written;
+    /// rows are held back as with [`Self::defer_column_widths`]. Widths set
+    /// with [`Self::set_column_width`] take precedence. Must be called before
+    /// the first row of the current worksheet.
+    pub fn autofit_columns(&mut self, options: AutofitOptions) -> Result<()> {
+        if !(0.0..=255.0).contains(&options.min_width) || options.max_width < options.min_width {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Invalid autofit widths {}..={}",
+                options.min_width, options.max_width
+            )));
+        }
+        self.defer_column_widths()?;
+        if let Some(deferred) = &mut self.deferred {
+            deferred.autofit = Some(options);
+        }
+        Ok(())
+    }
+
+    /// Record the width of a cell's text on an auto-fitted sheet
+    fn measure_text(&mut self, col_idx: u32, text: &str) {
+        let Some(deferred) = self
+            .deferred
             .as_mut()
-            .unwrap()
-            .write_data(header.as_bytes())?;
-        self.in_worksheet = true;
+            .filter(|deferred| deferred.autofit.is_some() && self.redirect.is_none())
+        else {
+            return;
+        };
+        // Longest line, with East Asian wide characters taking two columns
+        let chars = text
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| if is_wide_char(c) { 2 } else { 1 })
+                    .sum::<usize>()
+            })
+            .max()
+            .unwrap_or(0);
+        if chars == 0 {
+            return;
+        }
+        let width = chars as f64 * AUTOFIT_CHAR_WIDTH + AUTOFIT_PADDING;
+        let col = col_idx as usize;
+        if deferred.widths.len() <= col {
+            deferred.widths.resize(col + 1, 0.0);
+        }
+        deferred.widths[col] = deferred.widths[col].max(width);
+    }
+
+    /// Record the displayed width of a typed cell on an auto-fitted sheet
+    fn measure_value(&mut self, col_idx: u32, value: &CellValue) {
+        let autofit = self.deferred.as_ref().is_some_and(|d| d.autofit.is_some());
+        if !autofit || self.redirect.is_some() {
+            return;
+        }
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.clear();
+        match value {
+            CellValue::Empty => {}
+            CellValue::String(s) => scratch.push_str(s),
+            CellValue::Hyperlink { text, .. } => scratch.push_str(text),
+            CellValue::BigInt(digits) => scratch.push_str(digits),
+            CellValue::Error(e) => scratch.push_str(e),
+            CellValue::Bool(b) => scratch.push_str(if *b { "TRUE" } else { "FALSE" }),
+            CellValue::Int(i) => scratch.push_str(itoa::Buffer::new().format(*i)),
+            // Excel's General format shows at most 11 characters of a number
+            CellValue::Float(f) => {
+                let _ = write!(scratch, "{}", f);
+                scratch.truncate(11);
+            }
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => {
+                let _ = write!(scratch, "{}", d);
+                scratch.truncate(11);
+            }
+            // A date, or a date and time, in the usual formats
+            CellValue::DateTime(serial) => scratch.push_str(if serial.fract() == 0.0 {
+                "yyyy-mm-dd"
+            } else {
+                "yyyy-mm-dd hh:mm:ss"
+            }),
+            CellValue::Formula { cached, .. } => {
+                if let Some(cached) = cached.as_deref() {
+                    self.scratch = scratch;
+                    return self.measure_value(col_idx, cached);
+                }
+            }
+        }
+        self.measure_text(col_idx, &scratch);
+        self.scratch = scratch;
+    }
 
+    /// `<sheetFormatPr>` and `<cols>` for the current sheet's column groups
+    /// and widths
+    fn push_cols_xml(&self, xml: &mut Vec<u8>) {
+        // Column carrying the +/- button of a collapsed group
+        let marker = |first: u32, last: u32| {
+            if self.outline.summary_right {
+                Some(last + 1)
+            } else {
+                first.checked_sub(1)
+            }
+        };
+        let fitted = |col: u32| {
+            let deferred = self.deferred.as_ref()?;
+            let options = deferred.autofit?;
+            let width = *deferred.widths.get(col as usize).filter(|w| **w > 0.0)?;
+            let width = width.clamp(options.min_width, options.max_width);
+            Some((width * 100.0).round() / 100.0)
+        };
+        let fitted_cols = self
+            .deferred
+            .as_ref()
+            .map_or(0, |deferred| deferred.widths.len() as u32);
+        let start = self
+            .column_groups
+            .iter()
+            .map(|g| g.0)
+            .chain(self.column_widths.keys().copied())
+            .chain((0..fitted_cols).filter(|col| fitted(*col).is_some()))
+            .min();
+        let end = self
+            .column_groups
+            .iter()
+            .map(|g| g.1)
+            .chain(self.column_widths.keys().copied())
+            .chain(fitted_cols.checked_sub(1))
+            .max();
+        let (Some(start), Some(end)) = (start, end) else {
+            return;
+        };
+        let attrs = |col: u32| {
+            let level = self.column_level(col);
+            let mut hidden = false;
+            let mut collapsed = false;
+            for &(first, last, is_collapsed) in &self.column_groups {
+                if is_collapsed {
+                    hidden |= (first..=last).contains(&col);
+                    collapsed |= marker(first, last) == Some(col);
+                }
+            }
+            let width = self
+                .column_widths
+                .get(&col)
+                .copied()
+                .or_else(|| fitted(col));
+            (level, hidden, collapsed, width)
+        };
+
+        let mut num_buffer = itoa::Buffer::new();
+        if !self.column_groups.is_empty() {
+            let deepest = (start..=end).map(|col| self.column_level(col)).max();
+            xml.extend_from_slice(b"<sheetFormatPr defaultRowHeight=\"15\" outlineLevelCol=\"");
+            xml.extend_from_slice(num_buffer.format(deepest.unwrap_or(0)).as_bytes());
+            xml.extend_from_slice(b"\"/>");
+        }
+        xml.extend_from_slice(b"<cols>");
+
+        // One <col> per run of columns sharing the same attributes
+        let mut col = start.saturating_sub(1);
+        let last_col = (end + 1).min(16_383);
+        while col <= last_col {
+            let run = attrs(col);
+            let mut run_end = col;
+            while run_end < last_col && attrs(run_end + 1) == run {
+                run_end += 1;
+            }
+            let (level, hidden, collapsed, width) = run;
+            if level > 0 || hidden || collapsed || width.is_some() {
+                xml.extend_from_slice(b"<col min=\"");
+                xml.extend_from_slice(num_buffer.format(col + 1).as_bytes());
+                xml.extend_from_slice(b"\" max=\"");
+                xml.extend_from_slice(num_buffer.format(run_end + 1).as_bytes());
+                xml.extend_from_slice(b"\" width=\"");
+                match width {
+                    Some(width) => {
+                        xml.extend_from_slice(width.to_string().as_bytes());
+                        xml.extend_from_slice(b"\" customWidth=\"1");
+                    }
+                    None => xml.extend_from_slice(DEFAULT_COL_WIDTH.as_bytes()),
+                }
+                xml.push(b'"');
+                if hidden {
+                    xml.extend_from_slice(b" hidden=\"1\"");
+                }
+                if level > 0 {
+                    xml.extend_from_slice(b" outlineLevel=\"");
+                    xml.extend_from_slice(num_buffer.format(level).as_bytes());
+                    xml.push(b'"');
+                }
+                if collapsed {
+                    xml.extend_from_slice(b" collapsed=\"1\"");
+                }
+                xml.extend_from_slice(b"/>");
+            }
+            col = run_end + 1;
+        }
+        xml.extend_from_slice(b"</cols>");
+    }
+
+    /// Open a row group; rows written until the matching
+    /// [`Self::end_row_group`] get the next outline level
+    ///
+    /// Rows inside groups are held back (spilling to a temporary file past
+    /// 1 MB) until the outermost group ends, since collapsing a group hides
+    /// rows that were already written.
+    pub fn start_row_group(&mut self) -> Result<()> {
+        if !self.in_worksheet {
+            return Err(crate::error::ExcelError::WriteError(
+                "No worksheet started".to_string(),
+            ));
+        }
+        if self.row_groups.len() >= MAX_OUTLINE_LEVEL {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Excel supports at most {} nested outline levels",
+                MAX_OUTLINE_LEVEL
+            )));
+        }
+        if self.group_rows.is_none() {
+            self.group_rows = Some(SpillBuffer::new(DEFAULT_SPILL_THRESHOLD));
+        }
+        self.row_groups.push(self.current_row + 1);
+        Ok(())
+    }
+
+    /// Close the innermost row group, optionally collapsed
+    ///
+    /// With summary rows below (the default), the next row written is the
+    /// summary row carrying the group's +/- button.
+    pub fn end_row_group(&mut self, collapsed: bool) -> Result<()> {
+        let first = self.row_groups.pop().ok_or_else(|| {
+            crate::error::ExcelError::WriteError("No row group to end".to_string())
+        })?;
+        if collapsed && self.current_row >= first {
+            self.hidden_rows.push((first, self.current_row));
+            self.collapse_next_row = self.outline.summary_below;
+        }
+        if self.row_groups.is_empty() {
+            self.flush_row_groups()?;
+        }
+        Ok(())
+    }
+
+    /// Stream the held-back rows of finished row groups, hiding the rows of
+    /// collapsed groups
+    fn flush_row_groups(&mut self) -> Result<()> {
+        let Some(mut rows) = self.group_rows.take() else {
+            return Ok(());
+        };
+        if self.deferred.is_none() {
+            self.start_sheet_data()?;
+        }
+        let hidden = std::mem::take(&mut self.hidden_rows);
+        let out: &mut dyn std::io::Write = match &mut self.deferred {
+            Some(deferred) => &mut deferred.rows,
+            None => self.zip_writer.as_mut().unwrap(),
+        };
+
+        // Frames are row number and length (u32 LE each), then the row XML
+        let mut pending = Vec::new();
+        rows.drain_into(|chunk| {
+            pending.extend_from_slice(chunk);
+            let mut pos = 0;
+            while let Some(frame) = pending.get(pos..pos + 8) {
+                let row = u32::from_le_bytes([frame[0], frame[1], frame[2], frame[3]]);
+                let len = u32::from_le_bytes([frame[4], frame[5], frame[6], frame[7]]) as usize;
+                let Some(xml) = pending.get(pos + 8..pos + 8 + len) else {
+                    break;
+                };
+                if hidden
+                    .iter()
+                    .any(|&(first, last)| (first..=last).contains(&row))
+                {
+                    // Right after `<row r="N"`
+                    let at = xml[8..]
+                        .iter()
+                        .position(|&b| b == b'"')
+                        .map_or(xml.len(), |i| i + 9);
+                    out.write_all(&xml[..at])?;
+                    out.write_all(b" hidden=\"1\"")?;
+                    out.write_all(&xml[at..])?;
+                } else {
+                    out.write_all(xml)?;
+                }
+                pos += 8 + len;
+            }
+            pending.drain(..pos);
+            Ok(())
+        })
+    }
+
+    /// Add a worksheet whose rows are buffered instead of streamed
+    ///
+    /// Only one worksheet can be an open ZIP entry at a time, so rows for a
+    /// buffered sheet are kept in memory, spilled to a temporary file past
+    /// 1 MB, and copied into the ZIP by [`Self::close`]. The sheet keeps its
+    /// position in the tab order. Returns the sheet's index among the
+    /// buffered sheets; [`ExcelWriter::worksheet`](crate::ExcelWriter::worksheet)
+    /// wraps it in a handle for writing rows.
+    ///
+    /// Sheet settings such as protection, page setup and hyperlinks are not
+    /// available on buffered sheets; they apply to the streamed sheet.
+    pub fn add_buffered_worksheet(&mut self, name: &str) -> Result<usize> {
+        let name = crate::sheet_name::resolve(name, &self.worksheets, self.sheet_name_policy)?;
+
+        self.worksheet_count += 1;
+        self.sheet_order.push(self.worksheets.len());
+        self.worksheets.push(name);
+        self.buffered.push(BufferedSheet {
+            number: self.worksheet_count,
+            rows: SpillBuffer::new(DEFAULT_SPILL_THRESHOLD),
+            current_row: 0,
+            max_col: 0,
+            has_numbers_as_text: false,
+        });
+        Ok(self.buffered.len() - 1)
+    }
+
+    /// Run `f` with row writes routed to the buffered worksheet `index`
+    pub(crate) fn with_buffered_worksheet<T>(
+        &mut self,
+        index: usize,
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        if index >= self.buffered.len() || self.redirect.is_some() {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "No buffered worksheet with index {}",
+                index
+            )));
+        }
+
+        self.swap_sheet_state(index);
+        let in_worksheet = std::mem::replace(&mut self.in_worksheet, true);
+        self.redirect = Some(index);
+        let result = f(self);
+        self.redirect = None;
+        self.in_worksheet = in_worksheet;
+        self.swap_sheet_state(index);
+        result
+    }
+
+    /// Exchange the row counters of the streamed sheet with a buffered one
+    fn swap_sheet_state(&mut self, index: usize) {
+        let sheet = &mut self.buffered[index];
+        std::mem::swap(&mut self.current_row, &mut sheet.current_row);
+        std::mem::swap(&mut self.max_col, &mut sheet.max_col);
+        std::mem::swap(
+            &mut self.has_numbers_as_text,
+            &mut sheet.has_numbers_as_text,
+        );
+    }
+
+    /// Index into `worksheets` of the sheet named `name` (case-insensitive,
+    /// like Excel)
+    fn sheet_index(&self, name: &str) -> Result<usize> {
+        self.worksheets
+            .iter()
+            .position(|existing| crate::sheet_name::same_name(existing, name))
+            .ok_or_else(|| crate::error::ExcelError::SheetNotFound {
+                sheet: name.to_string(),
+                available: self.worksheets.join(", "),
+            })
+    }
+
+    /// Hide or show the sheet named `name`
+    ///
+    /// At least one sheet must stay visible, and the active sheet can't be
+    /// hidden.
+    pub fn set_sheet_hidden(&mut self, name: &str, hidden: bool) -> Result<()> {
+        let index = self.sheet_index(name)?;
+        self.hidden_sheets.retain(|&i| i != index);
+        if !hidden {
+            return Ok(());
+        }
+        if self.active_tab == Some(index) {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Sheet '{}' is the active sheet and can't be hidden",
+                name
+            )));
+        }
+        if self.hidden_sheets.len() + 1 >= self.worksheets.len() {
+            return Err(crate::error::ExcelError::WriteError(
+                "A workbook must keep at least one visible sheet".to_string(),
+            ));
+        }
+        self.hidden_sheets.push(index);
+        Ok(())
+    }
+
+    /// Color the tab of the sheet named `name`, e.g. `"FF0000"` or `"#3366FF"`
+    ///
+    /// The color is part of the worksheet XML, so a streamed sheet takes it
+    /// only before its first row; buffered sheets take it until close.
+    pub fn set_tab_color(&mut self, name: &str, color: &str) -> Result<()> {
+        let index = self.sheet_index(name)?;
+        let argb = argb_color(color).ok_or_else(|| {
+            crate::error::ExcelError::WriteError(format!("Invalid tab color '{}'", color))
+        })?;
+        let buffered = self
+            .buffered
+            .iter()
+            .any(|sheet| sheet.number as usize == index + 1);
+        let current = self.in_worksheet && self.active_sheet as usize == index + 1;
+        if !buffered && (!current || self.sheet_data_started) {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Tab color of sheet '{}' must be set before its first row",
+                name
+            )));
+        }
+        self.tab_colors.insert(index, argb);
+        Ok(())
+    }
+
+    /// Move the sheet named `name` to tab position `position` (0-based)
+    pub fn move_sheet(&mut self, name: &str, position: usize) -> Result<()> {
+        let index = self.sheet_index(name)?;
+        if position >= self.sheet_order.len() {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Sheet position {} is out of range (workbook has {} sheets)",
+                position,
+                self.sheet_order.len()
+            )));
+        }
+        self.sheet_order.retain(|&i| i != index);
+        self.sheet_order.insert(position, index);
         Ok(())
     }
 
+    /// Show the sheet named `name` when the workbook is opened
+    pub fn set_active_sheet(&mut self, name: &str) -> Result<()> {
+        let index = self.sheet_index(name)?;
+        if self.hidden_sheets.contains(&index) {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Hidden sheet '{}' can't be the active sheet",
+                name
+            )));
+        }
+        self.active_tab = Some(index);
+        Ok(())
+    }
+
+    fn push_tab_color(xml: &mut Vec<u8>, argb: &str) {
+        xml.extend_from_slice(b"<tabColor rgb=\"");
+        xml.extend_from_slice(argb.as_bytes());
+        xml.extend_from_slice(b"\"/>");
+    }
+
+    /// Set the workbook's title, author, custom properties, etc.
+    pub fn set_properties(&mut self, properties: DocumentProperties) -> Result<()> {
+        // Names are written without characters XML can't hold
+        let blank = |name: &str| {
+            !name
+                .chars()
+                .any(|c| super::xml_writer::is_xml_char(c) && !c.is_whitespace())
+        };
+        if let Some((name, _)) = properties.custom.iter().find(|(n, _)| blank(n)) {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Invalid custom property name '{}'",
+                name
+            )));
+        }
+        self.properties = properties;
+        self.record_timezone_policy();
+        Ok(())
+    }
+
+    /// Choose how timestamps with a time zone become date cells, and
+    /// record the choice in the custom property `Timezone`
+    pub fn set_timezone_policy(&mut self, policy: TimezonePolicy) {
+        self.timezone_policy = Some(policy);
+        self.record_timezone_policy();
+    }
+
+    /// How timestamps with a time zone become date cells
+    pub fn timezone_policy(&self) -> TimezonePolicy {
+        self.timezone_policy.unwrap_or_default()
+    }
+
+    fn record_timezone_policy(&mut self) {
+        if let Some(policy) = self.timezone_policy {
+            let properties = std::mem::take(&mut self.properties);
+            self.properties = properties.custom("Timezone", policy.to_string());
+        }
+    }
+
     pub fn protect_sheet(&mut self, options: ProtectionOptions) -> Result<()> {
         self.protection = Some(options);
         Ok(())
     }
 
-    pub fn write_row<I, S>(&mut self, values: I) -> Result<()>
-    where
-        I: IntoIterator<Item = S>,
-        S: AsRef<str>,
-    {
+    /// Set margins, orientation, print area, etc. of the current worksheet
+    pub fn set_page_setup(&mut self, setup: PageSetup) -> Result<()> {
+        if let Some(area) = &setup.print_area {
+            Self::absolute_range(area)?;
+        }
+        if let Some((first, last)) = setup.repeat_rows {
+            if first == 0 || last > 1_048_576 {
+                return Err(crate::error::ExcelError::InvalidCell(format!(
+                    "rows {}:{}",
+                    first, last
+                )));
+            }
+        }
+        for text in [&setup.header, &setup.footer].into_iter().flatten() {
+            Self::check_header_footer(text)?;
+        }
+        self.page_setup = Some(setup);
+        Ok(())
+    }
+
+    /// Set the current worksheet's page header, keeping the rest of its
+    /// page setup
+    pub fn set_header(&mut self, text: &str) -> Result<()> {
+        Self::check_header_footer(text)?;
+        self.page_setup
+            .get_or_insert_with(PageSetup::default)
+            .header = Some(text.to_string());
+        Ok(())
+    }
+
+    /// Set the current worksheet's page footer, keeping the rest of its
+    /// page setup
+    pub fn set_footer(&mut self, text: &str) -> Result<()> {
+        Self::check_header_footer(text)?;
+        self.page_setup
+            .get_or_insert_with(PageSetup::default)
+            .footer = Some(text.to_string());
+        Ok(())
+    }
+
+    /// Repeat `rows` (e.g. `"1:1"` or `"$1:$2"`) at the top of every printed
+    /// page of the current worksheet
+    pub fn set_print_title_rows(&mut self, rows: &str) -> Result<()> {
+        let invalid = || crate::error::ExcelError::InvalidCell(format!("rows {}", rows));
+        let (first, last) = rows.split_once(':').unwrap_or((rows, rows));
+        let parse = |row: &str| {
+            row.trim()
+                .trim_start_matches('$')
+                .parse::<u32>()
+                .ok()
+                .filter(|row| (1..=1_048_576).contains(row))
+        };
+        let (first, last) = parse(first).zip(parse(last)).ok_or_else(invalid)?;
+        if first > last {
+            return Err(invalid());
+        }
+        self.page_setup
+            .get_or_insert_with(PageSetup::default)
+            .repeat_rows = Some((first, last));
+        Ok(())
+    }
+
+    fn check_header_footer(text: &str) -> Result<()> {
+        if text.chars().count() > MAX_HEADER_FOOTER_LEN {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Headers and footers are limited to {} characters",
+                MAX_HEADER_FOOTER_LEN
+            )));
+        }
+        Ok(())
+    }
+
+    /// Define a workbook-level name, e.g. `TaxRate` for `Sheet1!$B$1`
+    pub fn define_name(&mut self, name: &str, refers_to: &str) -> Result<()> {
+        let invalid = |reason: &str| {
+            crate::error::ExcelError::WriteError(format!(
+                "Invalid defined name '{}': {}",
+                name, reason
+            ))
+        };
+
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(c) if c.is_alphabetic() || c == '_' || c == '\\' => {}
+            _ => return Err(invalid("must start with a letter, '_' or '\\'")),
+        }
+        if !chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '\\')) {
+            return Err(invalid(
+                "only letters, digits, '_', '.' and '\\' are allowed",
+            ));
+        }
+        if name.chars().count() > 255 {
+            return Err(invalid("longer than 255 characters"));
+        }
+        // Names that read as cell references (A1, XFD10, R, C) are ambiguous
+        let upper = name.to_ascii_uppercase();
+        if Self::parse_cell_ref(&upper).is_some() || upper == "R" || upper == "C" {
+            return Err(invalid("looks like a cell reference"));
+        }
+        if self
+            .defined_names
+            .iter()
+            .any(|(existing, _, scope)| scope.is_none() && existing.eq_ignore_ascii_case(name))
+        {
+            return Err(invalid("already defined"));
+        }
+
+        let refers_to = refers_to.trim_start_matches('=');
+        if refers_to.is_empty() {
+            return Err(invalid("empty reference"));
+        }
+        self.defined_names
+            .push((name.to_string(), refers_to.to_string(), None));
+        Ok(())
+    }
+
+    /// Turn `A1:F20` into `$A$1:$F$20`
+    fn absolute_range(range: &str) -> Result<String> {
+        let invalid = || crate::error::ExcelError::InvalidCell(range.to_string());
+        let cells = range.replace('$', "").to_ascii_uppercase();
+        let mut out = Vec::with_capacity(cells.len() + 4);
+
+        for (i, cell) in cells.split(':').enumerate() {
+            let (col, row) = Self::parse_cell_ref(cell).ok_or_else(invalid)?;
+            if i > 1 {
+                return Err(invalid());
+            }
+            if i == 1 {
+                out.push(b':');
+            }
+            out.push(b'$');
+            Self::push_column_letter(&mut out, col);
+            out.push(b'$');
+            out.extend_from_slice(itoa::Buffer::new().format(row).as_bytes());
+        }
+        Ok(String::from_utf8(out).unwrap_or_default())
+    }
+
+    /// `<pageMargins>` and `<pageSetup>` for the current sheet
+    fn page_setup_xml(setup: &PageSetup) -> Vec<u8> {
+        let [left, right, top, bottom] = setup.margins;
+        let [header, footer] = setup.header_footer_margins;
+        let mut xml = format!(
+            r#"<pageMargins left="{}" right="{}" top="{}" bottom="{}" header="{}" footer="{}"/><pageSetup"#,
+            left, right, top, bottom, header, footer
+        );
+        if let Some(size) = setup.paper_size {
+            xml.push_str(&format!(r#" paperSize="{}""#, size as u32));
+        }
+        xml.push_str(match setup.orientation {
+            Orientation::Portrait => r#" orientation="portrait"/>"#,
+            Orientation::Landscape => r#" orientation="landscape"/>"#,
+        });
+        let mut xml = xml.into_bytes();
+        if setup.header.is_some() || setup.footer.is_some() {
+            xml.extend_from_slice(b"<headerFooter>");
+            if let Some(header) = &setup.header {
+                xml.extend_from_slice(b"<oddHeader>");
+                Self::write_escaped(&mut xml, header);
+                xml.extend_from_slice(b"</oddHeader>");
+            }
+            if let Some(footer) = &setup.footer {
+                xml.extend_from_slice(b"<oddFooter>");
+                Self::write_escaped(&mut xml, footer);
+                xml.extend_from_slice(b"</oddFooter>");
+            }
+            xml.extend_from_slice(b"</headerFooter>");
+        }
+        xml
+    }
+
+    /// Add a conditional formatting rule for `range` on the current worksheet
+    ///
+    /// Like data validations, rules are written when the worksheet is
+    /// finished. Styles of `cellIs` / expression rules are collected into the
+    /// `<dxfs>` of styles.xml.
+    pub fn add_conditional_format(&mut self, range: &str, format: ConditionalFormat) -> Result<()> {
+        let sqref = Self::parse_sqref(range)?;
+
+        if let Some(color) = format
+            .colors()
+            .into_iter()
+            .find(|c| argb_color(c).is_none())
+        {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Invalid color '{}' in conditional format; expected hex RGB like \"FF0000\"",
+                color
+            )));
+        }
+
+        let dxf_id = format.style().map(|style| {
+            let style = ConditionalStyle {
+                font_color: style.font_color.as_deref().and_then(argb_color),
+                fill_color: style.fill_color.as_deref().and_then(argb_color),
+                ..style.clone()
+            };
+            match self.dxfs.iter().position(|s| *s == style) {
+                Some(id) => id,
+                None => {
+                    self.dxfs.push(style);
+                    self.dxfs.len() - 1
+                }
+            }
+        });
+
+        self.conditional_formats.push((sqref, format, dxf_id));
+        Ok(())
+    }
+
+    /// Add a data validation rule for `range` on the current worksheet
+    ///
+    /// `range` is a cell or range (`"B2:B100"`), or several separated by
+    /// spaces. Rules are written when the worksheet is finished, so they can
+    /// be added at any point while the sheet is current.
+    pub fn add_data_validation(&mut self, range: &str, validation: DataValidation) -> Result<()> {
+        let sqref = Self::parse_sqref(range)?;
+
+        data_validation::check_list(&validation)?;
+
+        self.data_validations.push((sqref, validation));
+        Ok(())
+    }
+
+    /// Normalize a space-separated list of cell references / ranges to a
+    /// `sqref` attribute value (absolute `$` markers removed)
+    fn parse_sqref(range: &str) -> Result<String> {
+        let invalid = || crate::error::ExcelError::InvalidCell(range.to_string());
+
+        let mut parts = Vec::new();
+        for part in range.split_whitespace() {
+            let part = part.replace('$', "").to_ascii_uppercase();
+            let mut ends = part.split(':');
+            let count = ends.clone().count();
+            if count > 2 || !ends.all(|end| Self::parse_cell_ref(end).is_some()) {
+                return Err(invalid());
+            }
+            parts.push(part);
+        }
+
+        if parts.is_empty() {
+            return Err(invalid());
+        }
+        Ok(parts.join(" "))
+    }
+
+    /// Parse an upper-case A1-style reference inside Excel's grid
+    /// (XFD1048576) into 1-based (column, row)
+    fn parse_cell_ref(s: &str) -> Option<(u32, u32)> {
+        let digits_at = s.find(|c: char| c.is_ascii_digit()).unwrap_or(s.len());
+        let (letters, digits) = s.split_at(digits_at);
+        if letters.is_empty()
+            || letters.len() > 3
+            || !letters.bytes().all(|b| b.is_ascii_uppercase())
+        {
+            return None;
+        }
+        let col = letters
+            .bytes()
+            .fold(0u32, |acc, b| acc * 26 + (b - b'A' + 1) as u32);
+        let row = match digits.parse::<u32>() {
+            Ok(row) if !digits.starts_with('0') => row,
+            _ => return None,
+        };
+        (col <= 16_384 && (1..=1_048_576).contains(&row)).then_some((col, row))
+    }
+
+    /// Add a chart of the block `data_range` anchored at `anchor_cell`
+    ///
+    /// `data_range` may name another sheet (`"Data!A1:C13"`); without a sheet
+    /// it refers to the current one. The chart part is written when the
+    /// current worksheet is finished.
+    pub fn add_chart(
+        &mut self,
+        chart_type: ChartType,
+        data_range: &str,
+        anchor_cell: &str,
+        options: ChartOptions,
+    ) -> Result<()> {
+        let range = self.parse_chart_range(data_range)?;
+        let anchor = Self::parse_cell_ref(&anchor_cell.replace('$', "").to_ascii_uppercase())
+            .ok_or_else(|| crate::error::ExcelError::InvalidCell(anchor_cell.to_string()))?;
+
+        self.charts.push(Chart {
+            chart_type,
+            range,
+            anchor: (anchor.0 - 1, anchor.1 - 1),
+            options,
+        });
+        Ok(())
+    }
+
+    /// Turn `range` of the current worksheet into an Excel table
+    ///
+    /// The first row of the range is the header row. The table part is
+    /// written when the current worksheet is finished, so the range may
+    /// cover rows that have not been written yet.
+    pub fn add_table(&mut self, range: &str, options: TableOptions) -> Result<()> {
+        let invalid = |msg: String| Err(crate::error::ExcelError::WriteError(msg));
+        let (first_col, first_row, last_col, last_row) = Self::parse_block(range)?;
+
+        let id = self.table_count + self.tables.len() as u32 + 1;
+        let table = Table {
+            id,
+            name: options
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Table{}", id)),
+            first_col,
+            first_row,
+            last_col,
+            last_row,
+            options,
+        };
+
+        // Header, at least one data row, and the totals row if any
+        let min_rows = 2 + table.options.totals_row as u32;
+        if table.last_row - table.first_row + 1 < min_rows {
+            return invalid(format!(
+                "Table range {} needs a header row and at least one data row",
+                range
+            ));
+        }
+        if !table::is_valid_table_name(&table.name) {
+            return invalid(format!("'{}' is not a valid table name", table.name));
+        }
+        if self
+            .table_names
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(&table.name))
+        {
+            return invalid(format!("A table named '{}' already exists", table.name));
+        }
+        let names = &table.options.column_names;
+        if !names.is_empty() {
+            if names.len() as u32 != table.last_col - table.first_col + 1 {
+                return invalid(format!(
+                    "Table range {} has {} columns but {} column names were given",
+                    range,
+                    table.last_col - table.first_col + 1,
+                    names.len()
+                ));
+            }
+            for (i, name) in names.iter().enumerate() {
+                if name.trim().is_empty()
+                    || names[..i]
+                        .iter()
+                        .any(|other| other.to_lowercase() == name.to_lowercase())
+                {
+                    return invalid(format!(
+                        "Table column names must be unique and not blank: '{}'",
+                        name
+                    ));
+                }
+            }
+        }
+        if let Some(other) = self.tables.iter().find(|other| other.overlaps(&table)) {
+            return invalid(format!(
+                "Table range {} overlaps table '{}'",
+                range, other.name
+            ));
+        }
+
+        self.table_names.push(table.name.clone());
+        self.tables.push(table);
+        Ok(())
+    }
+
+    /// Parse a block like `"B2:D10"` into 1-based (first column, first row,
+    /// last column, last row)
+    fn parse_block(range: &str) -> Result<(u32, u32, u32, u32)> {
+        let cells = range.replace('$', "").to_ascii_uppercase();
+        let ((col_a, row_a), (col_b, row_b)) = cells
+            .split_once(':')
+            .and_then(|(a, b)| Some((Self::parse_cell_ref(a)?, Self::parse_cell_ref(b)?)))
+            .ok_or_else(|| crate::error::ExcelError::InvalidCell(range.to_string()))?;
+        Ok((
+            col_a.min(col_b),
+            row_a.min(row_b),
+            col_a.max(col_b),
+            row_a.max(row_b),
+        ))
+    }
+
+    /// Fill the block `range` of the current worksheet with one formula
+    ///
+    /// `formula` is the formula of the top-left cell; Excel shifts its
+    /// relative references for every other cell, as if it had been filled
+    /// down or right. Cells of the block are written as empty cells (an empty
+    /// string or `CellValue::Empty`) and receive the formula; other values
+    /// are written as given. The whole block is stored once, which keeps
+    /// files with the same formula on every row much smaller.
+    ///
+    /// Must be added before the block's first row is written.
+    pub fn add_shared_formula(&mut self, range: &str, formula: &str) -> Result<()> {
+        let block = Self::parse_block(range)?;
+        let (first_col, first_row, last_col, last_row) = block;
+        if !self.in_worksheet || self.current_row >= first_row {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Shared formula {} must be added before row {} is written",
+                range, first_row
+            )));
+        }
+        let overlaps = self.shared_formulas.iter().any(|other| {
+            let (col_a, row_a, col_b, row_b) = other.block;
+            first_col <= col_b && col_a <= last_col && first_row <= row_b && row_a <= last_row
+        });
+        if overlaps {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Shared formula {} overlaps another shared formula",
+                range
+            )));
+        }
+
+        let mut cells = Vec::with_capacity(16);
+        let mut num_buffer = itoa::Buffer::new();
+        Self::push_column_letter(&mut cells, first_col);
+        cells.extend_from_slice(num_buffer.format(first_row).as_bytes());
+        cells.push(b':');
+        Self::push_column_letter(&mut cells, last_col);
+        cells.extend_from_slice(num_buffer.format(last_row).as_bytes());
+
+        self.shared_formulas.push(SharedFormula {
+            block,
+            // Column letters and digits are always ASCII
+            range: String::from_utf8(cells).unwrap_or_default(),
+            expr: formula.strip_prefix('=').unwrap_or(formula).to_string(),
+            master_written: false,
+        });
+        Ok(())
+    }
+
+    /// Finish an empty cell with the shared formula covering it, if any
+    ///
+    /// `xml_buffer` holds the cell's start tag up to its attributes.
+    fn push_shared_formula(&mut self, col_idx: u32) -> Result<bool> {
+        if self.redirect.is_some() {
+            return Ok(false);
+        }
+        let (col, row) = (col_idx + 1, self.current_row);
+        let Some(si) = self.shared_formulas.iter().position(|f| {
+            let (col_a, row_a, col_b, row_b) = f.block;
+            (col_a..=col_b).contains(&col) && (row_a..=row_b).contains(&row)
+        }) else {
+            return Ok(false);
+        };
+
+        let formula = &mut self.shared_formulas[si];
+        let mut num_buffer = itoa::Buffer::new();
+        if (col, row) == (formula.block.0, formula.block.1) {
+            formula.master_written = true;
+            self.xml_buffer
+                .extend_from_slice(b"><f t=\"shared\" ref=\"");
+            self.xml_buffer.extend_from_slice(formula.range.as_bytes());
+            self.xml_buffer.extend_from_slice(b"\" si=\"");
+            self.xml_buffer
+                .extend_from_slice(num_buffer.format(si).as_bytes());
+            self.xml_buffer.extend_from_slice(b"\">");
+            Self::write_escaped(&mut self.xml_buffer, &formula.expr);
+            self.xml_buffer.extend_from_slice(b"</f></c>");
+        } else if formula.master_written {
+            self.xml_buffer.extend_from_slice(b"><f t=\"shared\" si=\"");
+            self.xml_buffer
+                .extend_from_slice(num_buffer.format(si).as_bytes());
+            self.xml_buffer.extend_from_slice(b"\"/></c>");
+        } else {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Shared formula {} needs an empty cell at its top-left corner",
+                formula.range
+            )));
+        }
+        Ok(true)
+    }
+
+    /// Rest of a formula cell after its start tag's attributes
+    fn push_formula(buffer: &mut Vec<u8>, expr: &str, cached: Option<&CellValue>) {
+        let result = cached.and_then(CellValue::formula_result);
+        if let Some((type_attr, _)) = &result {
+            buffer.extend_from_slice(type_attr.as_bytes());
+        }
+        buffer.extend_from_slice(b"><f>");
+        Self::write_escaped(buffer, expr.strip_prefix('=').unwrap_or(expr));
+        buffer.extend_from_slice(b"</f>");
+        if let Some((_, value)) = &result {
+            buffer.extend_from_slice(b"<v>");
+            Self::write_escaped(buffer, value);
+            buffer.extend_from_slice(b"</v>");
+        }
+        buffer.extend_from_slice(b"</c>");
+    }
+
+    fn parse_chart_range(&self, data_range: &str) -> Result<ChartRange> {
+        let (sheet, cells) = match data_range.rsplit_once('!') {
+            Some((sheet, cells)) => {
+                let sheet = match sheet.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+                    Some(quoted) => quoted.replace("''", "'"),
+                    None => sheet.to_string(),
+                };
+                (sheet, cells)
+            }
+            None => {
+                let active = (self.active_sheet as usize).checked_sub(1);
+                let sheet = active.and_then(|i| self.worksheets.get(i)).ok_or_else(|| {
+                    crate::error::ExcelError::WriteError(
+                        "Add a worksheet before adding a chart".to_string(),
+                    )
+                })?;
+                (sheet.clone(), data_range)
+            }
+        };
+
+        let cells = cells.replace('$', "").to_ascii_uppercase();
+        let corners = cells
+            .split_once(':')
+            .and_then(|(a, b)| Some((Self::parse_cell_ref(a)?, Self::parse_cell_ref(b)?)))
+            .ok_or_else(|| crate::error::ExcelError::InvalidCell(data_range.to_string()))?;
+        let ((col_a, row_a), (col_b, row_b)) = corners;
+
+        let range = ChartRange {
+            sheet: chart::formula_sheet_name(&sheet),
+            first_col: col_a.min(col_b),
+            first_row: row_a.min(row_b),
+            last_col: col_a.max(col_b),
+            last_row: row_a.max(row_b),
+        };
+        if range.last_col == range.first_col || range.last_row == range.first_row {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Chart data range {} needs a category column, a header row and at least one series",
+                data_range
+            )));
+        }
+        Ok(range)
+    }
+
+    pub fn write_row<I, S>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        if !self.in_worksheet {
+            return Err(crate::error::ExcelError::WriteError(
+                "No worksheet started".to_string(),
+            ));
+        }
+        self.check_no_sparse_cells()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
+
+        self.current_row += 1;
+        self.start_row_xml();
+
+        let mut col_count = 0;
+        for (col_idx, value) in values.into_iter().enumerate() {
+            col_count += 1;
+            if let Err(e) = self.push_inline_str_cell(col_idx as u32, value.as_ref()) {
+                self.current_row -= 1;
+                return Err(e);
+            }
+        }
+
+        self.finish_row_xml(col_count)
+    }
+
+    /// Write a row of `Display` values as inline strings
+    ///
+    /// Each value is formatted into a reused scratch buffer instead of
+    /// being collected into a `Vec<String>` first.
+    pub fn write_row_display<I>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        if !self.in_worksheet {
+            return Err(crate::error::ExcelError::WriteError(
+                "No worksheet started".to_string(),
+            ));
+        }
+        self.check_no_sparse_cells()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
+
+        self.current_row += 1;
+        self.start_row_xml();
+
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let mut col_count = 0;
+        for (col_idx, value) in values.into_iter().enumerate() {
+            col_count += 1;
+            scratch.clear();
+            // Writing into a String never fails
+            let _ = write!(scratch, "{}", value);
+            let pushed = self.push_inline_str_cell(col_idx as u32, &scratch);
+            if let Err(e) = pushed {
+                self.scratch = scratch;
+                self.current_row -= 1;
+                return Err(e);
+            }
+        }
+        self.scratch = scratch;
+
+        self.finish_row_xml(col_count)
+    }
+
+    fn start_row_xml(&mut self) {
+        self.xml_buffer.clear();
+        self.row_hyperlinks.clear();
+        self.xml_buffer.extend_from_slice(b"<row r=\"");
+        let mut num_buffer = itoa::Buffer::new();
+        self.xml_buffer
+            .extend_from_slice(num_buffer.format(self.current_row).as_bytes());
+        self.xml_buffer.push(b'"');
+        if self.redirect.is_none() {
+            if !self.row_groups.is_empty() {
+                self.xml_buffer.extend_from_slice(b" outlineLevel=\"");
+                self.xml_buffer
+                    .extend_from_slice(num_buffer.format(self.row_groups.len()).as_bytes());
+                self.xml_buffer.push(b'"');
+            }
+            if std::mem::take(&mut self.collapse_next_row) {
+                self.xml_buffer.extend_from_slice(b" collapsed=\"1\"");
+            }
+        }
+        self.xml_buffer.push(b'>');
+    }
+
+    fn push_inline_str_cell(&mut self, col_idx: u32, v: &str) -> Result<()> {
+        crate::sheet_name::check_columns(col_idx as usize + 1)?;
+        let mut num_buffer = itoa::Buffer::new();
+        self.xml_buffer.extend_from_slice(b"<c r=\"");
+        Self::push_column_letter(&mut self.xml_buffer, col_idx + 1);
+        self.xml_buffer
+            .extend_from_slice(num_buffer.format(self.current_row).as_bytes());
+        self.xml_buffer.push(b'"');
+        let style_id = self.fallback_style(col_idx).index();
+        if style_id > 0 {
+            self.xml_buffer.extend_from_slice(b" s=\"");
+            self.xml_buffer
+                .extend_from_slice(num_buffer.format(style_id).as_bytes());
+            self.xml_buffer.push(b'"');
+        }
+
+        if v.is_empty() {
+            if !self.push_shared_formula(col_idx)? {
+                self.xml_buffer.extend_from_slice(b"/>");
+            }
+        } else {
+            self.push_string_value(v)?;
+            self.measure_text(col_idx, v);
+        }
+        Ok(())
+    }
+
+    /// Type attribute and value of a string cell, shared or inline depending
+    /// on the string mode
+    fn push_string_value(&mut self, v: &str) -> Result<()> {
+        let index = match self.string_mode {
+            StringMode::Inline => None,
+            StringMode::SharedDeduped => Some(self.shared_strings.add_string(v)),
+            StringMode::SharedThresholded { .. } => self.shared_strings.try_add_string(v),
+            StringMode::SharedHashed { .. } => match &mut self.hashed_strings {
+                Some(table) => table.add_string(v)?,
+                None => None,
+            },
+        };
+        match index {
+            Some(index) => {
+                self.xml_buffer.extend_from_slice(b" t=\"s\"><v>");
+                self.xml_buffer
+                    .extend_from_slice(itoa::Buffer::new().format(index).as_bytes());
+                self.xml_buffer.extend_from_slice(b"</v></c>");
+            }
+            None => {
+                self.xml_buffer
+                    .extend_from_slice(b" t=\"inlineStr\"><is><t>");
+                Self::write_escaped(&mut self.xml_buffer, v);
+                self.xml_buffer.extend_from_slice(b"</t></is></c>");
+            }
+        }
+        Ok(())
+    }
+
+    fn finish_row_xml(&mut self, col_count: u32) -> Result<()> {
+        self.max_col = self.max_col.max(col_count);
+        self.xml_buffer.extend_from_slice(b"</row>");
+        self.emit_row()
+    }
+
+    /// Stream the row in `xml_buffer` to the compressor, or hold it back for
+    /// a buffered worksheet, an open row group or a sheet with deferred
+    /// column widths
+    fn emit_row(&mut self) -> Result<()> {
+        if self.redirect.is_none() {
+            self.shards
+                .keep_header(self.current_row, || (self.xml_buffer.clone(), self.max_col));
+        }
+        if let Some(index) = self.redirect {
+            self.buffered[index].rows.write_all(&self.xml_buffer)?;
+        } else if let Some(rows) = &mut self.group_rows {
+            rows.write_all(&self.current_row.to_le_bytes())?;
+            rows.write_all(&(self.xml_buffer.len() as u32).to_le_bytes())?;
+            rows.write_all(&self.xml_buffer)?;
+        } else if let Some(deferred) = &mut self.deferred {
+            deferred.rows.write_all(&self.xml_buffer)?;
+        } else {
+            self.start_sheet_data()?;
+            self.zip_writer
+                .as_mut()
+                .unwrap()
+                .write_data(&self.xml_buffer)?;
+        }
+        self.hyperlinks.append(&mut self.row_hyperlinks);
+        self.progress.row_written(self.xml_buffer.len());
+        if self.progress.rows().is_multiple_of(PROGRESS_INTERVAL) {
+            self.notify_metrics();
+        }
+        Ok(())
+    }
+
+    /// Pass the running totals to the metrics observer, if any
+    fn notify_metrics(&mut self) {
+        let Some(metrics) = &mut self.metrics else {
+            return;
+        };
+        let unique_strings = match &self.hashed_strings {
+            Some(table) if !table.is_empty() => table.len(),
+            _ => self.shared_strings.count(),
+        };
+        metrics.rows_written(self.progress.rows());
+        metrics.bytes_written(self.progress.bytes());
+        metrics.bytes_compressed(self.output_stats.bytes());
+        metrics.sst_unique_strings(unique_strings as u64);
+        metrics.flush_count(self.output_stats.flushes());
+    }
+
+    /// Write a row with cell styling
+    pub fn write_row_styled(&mut self, cells: &[crate::types::StyledCell]) -> Result<()> {
+        if !self.in_worksheet {
+            return Err(crate::error::ExcelError::WriteError(
+                "No worksheet started".to_string(),
+            ));
+        }
+        self.check_no_sparse_cells()?;
+        self.progress.check()?;
+        crate::sheet_name::check_columns(cells.len())?;
+        self.check_row_limit()?;
+
+        self.current_row += 1;
+        self.max_col = self.max_col.max(cells.len() as u32);
+
+        // Build row XML in buffer
+        self.start_row_xml();
+        for (col_idx, styled_cell) in cells.iter().enumerate() {
+            let pushed =
+                self.push_styled_cell(col_idx as u32, &styled_cell.value, styled_cell.style);
+            if let Err(e) = pushed {
+                self.current_row -= 1;
+                return Err(e);
+            }
+        }
+
+        self.xml_buffer.extend_from_slice(b"</row>");
+        self.emit_row()
+    }
+
+    /// Write a row of typed values straight from an iterator
+    ///
+    /// Items can be owned `CellValue`s or references to them; either way
+    /// the cells are encoded into the reused row buffer as they come, with
+    /// the same default styles as [`Self::write_row_styled`] would give a
+    /// plain value (date formats for dates, and so on).
+    pub fn write_row_typed_iter<I>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CellValue>,
+    {
+        if !self.in_worksheet {
+            return Err(crate::error::ExcelError::WriteError(
+                "No worksheet started".to_string(),
+            ));
+        }
+        self.check_no_sparse_cells()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
+
+        self.current_row += 1;
+        self.start_row_xml();
+
+        let mut col_count = 0;
+        for (col_idx, value) in values.into_iter().enumerate() {
+            col_count += 1;
+            let value = value.borrow();
+            let pushed = crate::sheet_name::check_columns(col_idx + 1)
+                .and_then(|_| self.push_styled_cell(col_idx as u32, value, value.default_style()));
+            if let Err(e) = pushed {
+                self.current_row -= 1;
+                return Err(e);
+            }
+        }
+
+        self.finish_row_xml(col_count)
+    }
+
+    /// Write a row of borrowed cells, see [`CellRef`]
+    ///
+    /// Strings are escaped straight from the caller's data; other values
+    /// are written like [`Self::write_row_typed_iter`] writes them.
+    pub fn write_row_refs<'a, I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<CellRef<'a>>,
+    {
+        if !self.in_worksheet {
+            return Err(crate::error::ExcelError::WriteError(
+                "No worksheet started".to_string(),
+            ));
+        }
+        self.check_no_sparse_cells()?;
+        self.progress.check()?;
+        self.check_row_limit()?;
+
+        self.current_row += 1;
+        self.start_row_xml();
+
+        let mut col_count = 0;
+        for (col_idx, cell) in cells.into_iter().enumerate() {
+            col_count += 1;
+            let col_idx = col_idx as u32;
+            let pushed = match cell.into() {
+                CellRef::String(s) => self.push_inline_str_cell(col_idx, s),
+                CellRef::Value(value) => crate::sheet_name::check_columns(col_idx as usize + 1)
+                    .and_then(|_| self.push_styled_cell(col_idx, value, value.default_style())),
+                cell => {
+                    // Everything else is a number or flag, owned without allocating
+                    let value = cell.into_owned();
+                    crate::sheet_name::check_columns(col_idx as usize + 1)
+                        .and_then(|_| self.push_styled_cell(col_idx, &value, value.default_style()))
+                }
+            };
+            if let Err(e) = pushed {
+                self.current_row -= 1;
+                return Err(e);
+            }
+        }
+
+        self.finish_row_xml(col_count)
+    }
+
+    /// Write equally long columns as rows, returning the number of rows
+    ///
+    /// Row `i` holds value `i` of every column; the values are read from
+    /// the slices while the row XML is built, so nothing is transposed
+    /// first. A failing row (e.g. a full sheet) stops the batch, leaving
+    /// the rows before it written.
+    pub fn write_columns(&mut self, columns: &[ColumnSlice]) -> Result<usize> {
+        crate::sheet_name::check_columns(columns.len())?;
+        let rows = columns.first().map_or(0, ColumnSlice::len);
+        if let Some((col_idx, column)) = columns
+            .iter()
+            .enumerate()
+            .find(|(_, column)| column.len() != rows)
+        {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Column {} has {} values, but column 1 has {}",
+                col_idx + 1,
+                column.len(),
+                rows
+            )));
+        }
+
+        for row in 0..rows {
+            self.write_row_refs(
+                columns
+                    .iter()
+                    .map(|column| column.get(row).unwrap_or(CellRef::Empty)),
+            )?;
+        }
+        Ok(rows)
+    }
+
+    /// One `<c>` element of the row being built, for a value written with
+    /// `style`
+    fn push_styled_cell(
+        &mut self,
+        col_idx: u32,
+        cell_value: &CellValue,
+        style: CellStyle,
+    ) -> Result<()> {
+        let mut num_buffer = itoa::Buffer::new();
+        let as_text = self.number_as_text(col_idx, cell_value);
+        let value = as_text.as_ref().unwrap_or(cell_value);
+        let style_id = match style.effective_for(cell_value) {
+            CellStyle::Default => self.fallback_style(col_idx),
+            // A column style (e.g. currency) replaces the implicit style
+            // of decimals and hyperlinks
+            implicit if style == CellStyle::Default => {
+                self.column_style(col_idx).unwrap_or(implicit)
+            }
+            style => style,
+        }
+        .index();
+
+        self.xml_buffer.extend_from_slice(b"<c r=\"");
+        Self::push_column_letter(&mut self.xml_buffer, col_idx + 1);
+        self.xml_buffer
+            .extend_from_slice(num_buffer.format(self.current_row).as_bytes());
+        self.xml_buffer.extend_from_slice(b"\"");
+
+        // Add style attribute if not default
+        if style_id > 0 {
+            self.xml_buffer.extend_from_slice(b" s=\"");
+            self.xml_buffer
+                .extend_from_slice(num_buffer.format(style_id).as_bytes());
+            self.xml_buffer.extend_from_slice(b"\"");
+        }
+
+        // Write cell value based on type
+        match value {
+            crate::types::CellValue::Empty => {
+                if !self.push_shared_formula(col_idx)? {
+                    self.xml_buffer.extend_from_slice(b"/>");
+                }
+            }
+            crate::types::CellValue::Int(i) => {
+                self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
+                self.xml_buffer
+                    .extend_from_slice(num_buffer.format(*i).as_bytes());
+                self.xml_buffer.extend_from_slice(b"</v></c>");
+            }
+            crate::types::CellValue::Float(f) => {
+                let value = match self.column_decimals.get(&(col_idx)) {
+                    Some(&decimals) => round_decimals(*f, decimals),
+                    None => *f,
+                };
+                push_number_cell(&mut self.xml_buffer, value);
+            }
+            #[cfg(feature = "decimal")]
+            crate::types::CellValue::Decimal(d) => {
+                // Decimal's Display is the exact lexical form, no float noise
+                self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
+                self.xml_buffer.extend_from_slice(d.to_string().as_bytes());
+                self.xml_buffer.extend_from_slice(b"</v></c>");
+            }
+            crate::types::CellValue::Bool(b) => {
+                self.xml_buffer.extend_from_slice(b" t=\"b\"><v>");
+                self.xml_buffer
+                    .extend_from_slice(if *b { b"1" } else { b"0" });
+                self.xml_buffer.extend_from_slice(b"</v></c>");
+            }
+            crate::types::CellValue::String(s) => self.push_string_value(s)?,
+            crate::types::CellValue::BigInt(digits) => {
+                // Stored as text: Excel would round it to 15 significant digits
+                self.has_numbers_as_text = true;
+                self.xml_buffer
+                    .extend_from_slice(b" t=\"inlineStr\"><is><t>");
+                Self::write_escaped(&mut self.xml_buffer, digits);
+                self.xml_buffer.extend_from_slice(b"</t></is></c>");
+            }
+            crate::types::CellValue::Hyperlink { url, text } => {
+                self.push_hyperlink(col_idx, url)?;
+                self.push_string_value(text)?;
+            }
+            crate::types::CellValue::Formula { expr, cached } => {
+                let rebased;
+                let cached = match cached.as_deref() {
+                    Some(CellValue::DateTime(dt)) => {
+                        rebased = CellValue::DateTime(self.date_system.rebase(*dt));
+                        Some(&rebased)
+                    }
+                    cached => cached,
+                };
+                Self::push_formula(&mut self.xml_buffer, expr, cached);
+            }
+            crate::types::CellValue::DateTime(dt) => {
+                push_number_cell(&mut self.xml_buffer, self.date_system.rebase(*dt));
+            }
+            crate::types::CellValue::Error(e) => {
+                self.xml_buffer.extend_from_slice(b" t=\"e\"><v>");
+                Self::write_escaped(&mut self.xml_buffer, e);
+                self.xml_buffer.extend_from_slice(b"</v></c>");
+            }
+        }
+        self.measure_value(col_idx, value);
+        Ok(())
+    }
+
+    /// Stream a row at 0-based `row`, leaving the rows in between empty
+    ///
+    /// `row` must be below every row written so far; a row at the current
+    /// position is the same as [`Self::write_row_styled`]. Rows can't jump
+    /// past the `auto_shard_rows` limit, since they would need a new sheet.
+    pub fn write_row_at(&mut self, row: u32, cells: &[StyledCell]) -> Result<()> {
+        if row < self.current_row {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Row {} is not below the last written row {}; rows must be written in order",
+                row + 1,
+                self.current_row
+            )));
+        }
+        let limit = self.shards.limit();
+        if row > self.current_row && row >= limit {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Row {} is past the last row of the sheet ({})",
+                row + 1,
+                limit
+            )));
+        }
+
+        let next = self.current_row;
+        self.current_row = row;
+        let written = self.write_row_styled(cells);
+        if written.is_err() && self.current_row == row {
+            self.current_row = next;
+        }
+        written
+    }
+
+    /// Set one cell of the current worksheet, at 0-based `row` and `col`
+    ///
+    /// Cells can be set in any order and overwrite each other; they are
+    /// kept in memory and written, sorted, when the worksheet finishes. The
+    /// row must not have been streamed yet, and once a cell is set no more
+    /// rows can be streamed to the sheet.
+    pub fn write_cell(&mut self, row: u32, col: u32, cell: StyledCell) -> Result<()> {
         if !self.in_worksheet {
             return Err(crate::error::ExcelError::WriteError(
                 "No worksheet started".to_string(),
             ));
         }
+        crate::sheet_name::check_columns(col as usize + 1)?;
+        if row > MAX_ROW {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Cell ({}, {}) is outside the worksheet",
+                row, col
+            )));
+        }
+        if self.shards.is_enabled() && self.shards.is_full(row) {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Row {} is past the auto_shard_rows limit; write_cell can't roll over to a new sheet",
+                row + 1
+            )));
+        }
+        if row < self.current_row {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Row {} was already written; write_cell can only set rows below the last streamed row",
+                row + 1
+            )));
+        }
+        self.sparse_cells.entry(row).or_default().insert(col, cell);
+        Ok(())
+    }
 
-        self.current_row += 1;
-
-        // Build row XML in buffer
-        self.xml_buffer.clear();
-        self.xml_buffer.extend_from_slice(b"<row r=\"");
+    /// Make room for the next streamed row, rolling over to a new shard if
+    /// the sheet is full
+    fn check_row_limit(&mut self) -> Result<()> {
+        if !self.shards.is_full(self.current_row) {
+            return Ok(());
+        }
+        let name = self.current_sheet_name().unwrap_or_default().to_string();
+        // Buffered worksheets are kept whole
+        if self.redirect.is_some() {
+            return Err(crate::sheet_name::sheet_full(&name));
+        }
+        if self.shards.is_enabled() && !self.row_groups.is_empty() {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Sheet '{}' is full, but a row group can't continue on the next shard",
+                name
+            )));
+        }
 
-        let mut num_buffer = itoa::Buffer::new();
-        self.xml_buffer
-            .extend_from_slice(num_buffer.format(self.current_row).as_bytes());
+        let next = self.shards.next(&name, &self.worksheets)?;
+        let column_widths = std::mem::take(&mut self.column_widths);
+        let column_styles = std::mem::take(&mut self.column_styles);
+        let column_decimals = std::mem::take(&mut self.column_decimals);
+        let text_columns = std::mem::take(&mut self.text_columns);
+        let row_banding = self.row_banding;
+        let autofit = self.deferred.as_ref().map(|deferred| deferred.autofit);
 
-        self.xml_buffer.extend_from_slice(b"\">");
+        self.add_worksheet(&next.name)?;
+        self.column_widths = column_widths;
+        self.column_styles = column_styles;
+        self.column_decimals = column_decimals;
+        self.text_columns = text_columns;
+        self.row_banding = row_banding;
+        if let Some(autofit) = autofit {
+            self.deferred = Some(DeferredCols {
+                autofit,
+                widths: Vec::new(),
+                rows: SpillBuffer::new(DEFAULT_SPILL_THRESHOLD),
+            });
+        }
 
-        let mut col_count = 0;
-        for (col_idx, value) in values.into_iter().enumerate() {
-            col_count += 1;
+        // The header is row 1 again, so its XML is reused as it is
+        if let Some((xml, width)) = self.shards.started(next) {
+            self.current_row = 1;
+            self.max_col = width;
+            self.xml_buffer = xml;
+            self.emit_row()?;
+        }
+        Ok(())
+    }
 
-            self.xml_buffer.extend_from_slice(b"<c r=\"");
-            Self::push_column_letter(&mut self.xml_buffer, col_idx as u32 + 1);
-            self.xml_buffer
-                .extend_from_slice(num_buffer.format(self.current_row).as_bytes());
+    fn check_no_sparse_cells(&self) -> Result<()> {
+        // Buffered worksheets keep their own rows
+        if self.redirect.is_some() {
+            return Ok(());
+        }
+        match self.sparse_cells.keys().next() {
+            Some(row) => Err(crate::error::ExcelError::WriteError(format!(
+                "Rows can't be streamed to a sheet after write_cell (row {} has cells set)",
+                row + 1
+            ))),
+            None => Ok(()),
+        }
+    }
 
-            let v = value.as_ref();
-            if v.is_empty() {
-                self.xml_buffer.extend_from_slice(b"\"/>");
-            } else {
-                self.xml_buffer
-                    .extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
-                Self::write_escaped(&mut self.xml_buffer, v);
-                self.xml_buffer.extend_from_slice(b"</t></is></c>");
+    /// Write the cells set with `write_cell`, row by row, filling gaps
+    /// within a row with empty cells
+    fn write_sparse_cells(&mut self) -> Result<()> {
+        let mut cells = Vec::new();
+        for (row, row_cells) in std::mem::take(&mut self.sparse_cells) {
+            cells.clear();
+            for (col, cell) in row_cells {
+                cells.resize(
+                    col as usize,
+                    StyledCell::new(CellValue::Empty, CellStyle::Default),
+                );
+                cells.push(cell);
             }
+            self.current_row = row;
+            self.write_row_styled(&cells)?;
         }
-        self.max_col = self.max_col.max(col_count);
-
-        self.xml_buffer.extend_from_slice(b"</row>");
-
-        // Stream to compressor immediately
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(&self.xml_buffer)?;
-
         Ok(())
     }
 
-    /// Write a row with cell styling
-    pub fn write_row_styled(&mut self, cells: &[crate::types::StyledCell]) -> Result<()> {
-        if !self.in_worksheet {
+    /// Remember a hyperlink on the current row for the `<hyperlinks>` section;
+    /// it is only kept if the whole row is written
+    fn push_hyperlink(&mut self, col_idx: u32, url: &str) -> Result<()> {
+        if self.redirect.is_some() {
             return Err(crate::error::ExcelError::WriteError(
-                "No worksheet started".to_string(),
+                "Hyperlinks are not supported on buffered worksheets".to_string(),
             ));
         }
+        hyperlink::check_hyperlink(url, self.hyperlinks.len() + self.row_hyperlinks.len())?;
 
-        self.current_row += 1;
-        self.max_col = self.max_col.max(cells.len() as u32);
+        let mut cell_ref = Vec::with_capacity(12);
+        Self::push_column_letter(&mut cell_ref, col_idx + 1);
+        cell_ref.extend_from_slice(itoa::Buffer::new().format(self.current_row).as_bytes());
+        // Column letters and digits are always ASCII
+        let cell_ref = String::from_utf8(cell_ref).unwrap_or_default();
 
-        // Build row XML in buffer
-        self.xml_buffer.clear();
-        self.xml_buffer.extend_from_slice(b"<row r=\"");
+        self.row_hyperlinks.push((cell_ref, url.to_string()));
+        Ok(())
+    }
 
-        let mut num_buffer = itoa::Buffer::new();
-        self.xml_buffer
-            .extend_from_slice(num_buffer.format(self.current_row).as_bytes());
-        self.xml_buffer.extend_from_slice(b"\">");
+    /// Write `xl/worksheets/_rels/sheetN.xml.rels` for external hyperlinks,
+    /// the drawing holding the sheet's charts and the sheet's tables
+    ///
+    /// The drawing comes after the hyperlinks, see [`Self::drawing_rel_id`],
+    /// and the tables come last.
+    fn write_worksheet_rels(&mut self) -> Result<()> {
+        let mut external = self
+            .hyperlinks
+            .iter()
+            .filter(|(_, target)| !target.starts_with('#'))
+            .peekable();
+        if external.peek().is_none() && self.charts.is_empty() && self.tables.is_empty() {
+            return Ok(());
+        }
 
-        for (col_idx, styled_cell) in cells.iter().enumerate() {
-            let value = &styled_cell.value;
-            let style_id = styled_cell.style.index();
+        let mut xml = Vec::with_capacity(256);
+        xml.extend_from_slice(
+            br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+        );
+        let mut num_buffer = itoa::Buffer::new();
+        for (i, (_, target)) in external.enumerate() {
+            xml.extend_from_slice(b"\n<Relationship Id=\"rId");
+            xml.extend_from_slice(num_buffer.format(i + 1).as_bytes());
+            xml.extend_from_slice(
+                b"\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink\" Target=\"",
+            );
+            Self::write_escaped(&mut xml, target);
+            xml.extend_from_slice(b"\" TargetMode=\"External\"/>");
+        }
+        if !self.charts.is_empty() {
+            xml.extend_from_slice(b"\n<Relationship Id=\"rId");
+            xml.extend_from_slice(num_buffer.format(self.drawing_rel_id()).as_bytes());
+            xml.extend_from_slice(
+                b"\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/drawing\" Target=\"../drawings/drawing",
+            );
+            xml.extend_from_slice(num_buffer.format(self.drawing_count).as_bytes());
+            xml.extend_from_slice(b".xml\"/>");
+        }
+        let first_table_rel_id = self.table_rel_id();
+        for i in 0..self.tables.len() {
+            xml.extend_from_slice(b"\n<Relationship Id=\"rId");
+            xml.extend_from_slice(num_buffer.format(first_table_rel_id + i).as_bytes());
+            xml.extend_from_slice(
+                b"\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/table\" Target=\"../tables/table",
+            );
+            xml.extend_from_slice(
+                num_buffer
+                    .format(self.table_count as usize + i + 1)
+                    .as_bytes(),
+            );
+            xml.extend_from_slice(b".xml\"/>");
+        }
+        xml.extend_from_slice(b"\n</Relationships>");
 
-            self.xml_buffer.extend_from_slice(b"<c r=\"");
-            Self::push_column_letter(&mut self.xml_buffer, col_idx as u32 + 1);
-            self.xml_buffer
-                .extend_from_slice(num_buffer.format(self.current_row).as_bytes());
-            self.xml_buffer.extend_from_slice(b"\"");
+        let entry_name = format!("xl/worksheets/_rels/sheet{}.xml.rels", self.active_sheet);
+        let zip = self.zip_writer.as_mut().unwrap();
+        zip.start_entry(&entry_name)?;
+        zip.write_data(&xml)?;
+        Ok(())
+    }
 
-            // Add style attribute if not default
-            if style_id > 0 {
-                self.xml_buffer.extend_from_slice(b" s=\"");
-                self.xml_buffer
-                    .extend_from_slice(num_buffer.format(style_id).as_bytes());
-                self.xml_buffer.extend_from_slice(b"\"");
-            }
+    /// Relationship id of the current sheet's drawing, after its external
+    /// hyperlinks
+    fn drawing_rel_id(&self) -> usize {
+        let external = self
+            .hyperlinks
+            .iter()
+            .filter(|(_, target)| !target.starts_with('#'))
+            .count();
+        external + 1
+    }
 
-            // Write cell value based on type
-            match value {
-                crate::types::CellValue::Empty => {
-                    self.xml_buffer.extend_from_slice(b"/>");
-                }
-                crate::types::CellValue::Int(i) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer
-                        .extend_from_slice(num_buffer.format(*i).as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                crate::types::CellValue::Float(f) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(f.to_string().as_bytes()); // Float doesn't use itoa
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                crate::types::CellValue::Bool(b) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"b\"><v>");
-                    self.xml_buffer
-                        .extend_from_slice(if *b { b"1" } else { b"0" });
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                crate::types::CellValue::String(s) => {
-                    self.xml_buffer
-                        .extend_from_slice(b" t=\"inlineStr\"><is><t>");
-                    Self::write_escaped(&mut self.xml_buffer, s);
-                    self.xml_buffer.extend_from_slice(b"</t></is></c>");
-                }
-                crate::types::CellValue::Formula(f) => {
-                    self.xml_buffer.extend_from_slice(b"><f>");
-                    Self::write_escaped(&mut self.xml_buffer, f);
-                    self.xml_buffer.extend_from_slice(b"</f></c>");
-                }
-                crate::types::CellValue::DateTime(dt) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"n\"><v>");
-                    self.xml_buffer.extend_from_slice(dt.to_string().as_bytes());
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-                crate::types::CellValue::Error(e) => {
-                    self.xml_buffer.extend_from_slice(b" t=\"e\"><v>");
-                    Self::write_escaped(&mut self.xml_buffer, e);
-                    self.xml_buffer.extend_from_slice(b"</v></c>");
-                }
-            }
-        }
+    /// Relationship id of the current sheet's first table
+    fn table_rel_id(&self) -> usize {
+        self.drawing_rel_id() + !self.charts.is_empty() as usize
+    }
 
-        self.xml_buffer.extend_from_slice(b"</row>");
+    /// Write the drawing and chart parts for the current sheet's charts
+    fn write_charts(&mut self) -> Result<()> {
+        let drawing = chart::drawing_xml(&self.charts);
+        let drawing_rels = chart::drawing_rels_xml(self.chart_count + 1, self.charts.len() as u32);
+        let zip = self.zip_writer.as_mut().unwrap();
 
-        // Stream to compressor immediately
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(&self.xml_buffer)?;
+        zip.start_entry(&format!("xl/drawings/drawing{}.xml", self.drawing_count))?;
+        zip.write_data(&drawing)?;
+        zip.start_entry(&format!(
+            "xl/drawings/_rels/drawing{}.xml.rels",
+            self.drawing_count
+        ))?;
+        zip.write_data(&drawing_rels)?;
 
+        for chart in &self.charts {
+            self.chart_count += 1;
+            zip.start_entry(&format!("xl/charts/chart{}.xml", self.chart_count))?;
+            zip.write_data(&chart::chart_xml(chart))?;
+        }
         Ok(())
     }
 
     fn finish_current_worksheet(&mut self) -> Result<()> {
         if self.in_worksheet {
+            // Groups left open end expanded
+            self.row_groups.clear();
+            self.collapse_next_row = false;
+            self.write_sparse_cells()?;
+            self.flush_row_groups()?;
+            self.start_sheet_data()?;
+            if let Some(mut deferred) = self.deferred.take() {
+                let zip = self.zip_writer.as_mut().unwrap();
+                deferred.rows.drain_into(|chunk| zip.write_data(chunk))?;
+            }
+
             // Close sheetData
             self.zip_writer
                 .as_mut()
@@ -287,19 +2410,230 @@ impl ZeroTempWorkbook {
                     .write_data(protection_xml.as_bytes())?;
             }
 
+            if !self.conditional_formats.is_empty() {
+                let formats_xml =
+                    conditional_format::conditional_formats_xml(&self.conditional_formats);
+                self.zip_writer.as_mut().unwrap().write_data(&formats_xml)?;
+            }
+
+            if !self.data_validations.is_empty() {
+                let validations_xml = data_validation::data_validations_xml(&self.data_validations);
+                self.zip_writer
+                    .as_mut()
+                    .unwrap()
+                    .write_data(&validations_xml)?;
+            }
+
+            if !self.hyperlinks.is_empty() {
+                let hyperlinks_xml = hyperlink::hyperlinks_xml(&self.hyperlinks);
+                self.zip_writer
+                    .as_mut()
+                    .unwrap()
+                    .write_data(&hyperlinks_xml)?;
+            }
+
+            if let Some(setup) = self.page_setup.take() {
+                let setup_xml = Self::page_setup_xml(&setup);
+                self.zip_writer.as_mut().unwrap().write_data(&setup_xml)?;
+
+                // Print area and titles are names scoped to this sheet
+                let sheet_index = self.active_sheet as usize - 1;
+                let sheet = chart::formula_sheet_name(&self.worksheets[sheet_index]);
+                if let Some(area) = &setup.print_area {
+                    self.defined_names.push((
+                        "_xlnm.Print_Area".to_string(),
+                        format!("{}!{}", sheet, Self::absolute_range(area)?),
+                        Some(sheet_index),
+                    ));
+                }
+                if let Some((first, last)) = setup.repeat_rows {
+                    self.defined_names.push((
+                        "_xlnm.Print_Titles".to_string(),
+                        format!("{}!${}:${}", sheet, first, last),
+                        Some(sheet_index),
+                    ));
+                }
+            }
+
+            // Silence Excel's "number stored as text" warning for BigInt cells
+            if self.has_numbers_as_text {
+                let ignored_xml = Self::ignored_errors_xml(self.max_col, self.current_row);
+                self.zip_writer.as_mut().unwrap().write_data(&ignored_xml)?;
+            }
+
+            if !self.charts.is_empty() {
+                self.drawing_count += 1;
+                let drawing_xml = format!("<drawing r:id=\"rId{}\"/>", self.drawing_rel_id());
+                self.zip_writer
+                    .as_mut()
+                    .unwrap()
+                    .write_data(drawing_xml.as_bytes())?;
+            }
+
+            if !self.tables.is_empty() {
+                let first_rel_id = self.table_rel_id();
+                let mut parts_xml = Vec::with_capacity(48 + self.tables.len() * 32);
+                let mut num_buffer = itoa::Buffer::new();
+                parts_xml.extend_from_slice(b"<tableParts count=\"");
+                parts_xml.extend_from_slice(num_buffer.format(self.tables.len()).as_bytes());
+                parts_xml.extend_from_slice(b"\">");
+                for i in 0..self.tables.len() {
+                    parts_xml.extend_from_slice(b"<tablePart r:id=\"rId");
+                    parts_xml.extend_from_slice(num_buffer.format(first_rel_id + i).as_bytes());
+                    parts_xml.extend_from_slice(b"\"/>");
+                }
+                parts_xml.extend_from_slice(b"</tableParts>");
+                self.zip_writer.as_mut().unwrap().write_data(&parts_xml)?;
+            }
+
             // Close worksheet
             self.zip_writer
                 .as_mut()
                 .unwrap()
                 .write_data(b"</worksheet>")?;
             self.in_worksheet = false;
+
+            self.write_worksheet_rels()?;
+            if !self.charts.is_empty() {
+                self.write_charts()?;
+            }
+            for table in std::mem::take(&mut self.tables) {
+                self.table_count += 1;
+                let zip = self.zip_writer.as_mut().unwrap();
+                zip.start_entry(&format!("xl/tables/table{}.xml", self.table_count))?;
+                zip.write_data(&table::table_xml(&table))?;
+            }
+            self.hyperlinks.clear();
+            self.conditional_formats.clear();
+            self.data_validations.clear();
+            self.charts.clear();
+        }
+        Ok(())
+    }
+
+    /// `<ignoredErrors>` covering the used range, for BigInt cells stored as text
+    fn ignored_errors_xml(max_col: u32, rows: u32) -> Vec<u8> {
+        let mut xml = Vec::with_capacity(96);
+        xml.extend_from_slice(b"<ignoredErrors><ignoredError sqref=\"A1:");
+        Self::push_column_letter(&mut xml, max_col.max(1));
+        xml.extend_from_slice(itoa::Buffer::new().format(rows.max(1)).as_bytes());
+        xml.extend_from_slice(b"\" numberStoredAsText=\"1\"/></ignoredErrors>");
+        xml
+    }
+
+    /// Copy a buffered worksheet into its own ZIP entry
+    fn write_buffered_worksheet(&mut self, mut sheet: BufferedSheet) -> Result<()> {
+        let zip = self.zip_writer.as_mut().unwrap();
+        zip.start_entry(&format!("xl/worksheets/sheet{}.xml", sheet.number))?;
+        zip.write_data(WORKSHEET_HEADER.as_bytes())?;
+        if let Some(color) = self.tab_colors.get(&(sheet.number as usize - 1)) {
+            let mut xml = b"<sheetPr>".to_vec();
+            Self::push_tab_color(&mut xml, color);
+            xml.extend_from_slice(b"</sheetPr>");
+            zip.write_data(&xml)?;
+        }
+        zip.write_data(b"<sheetData>")?;
+        sheet.rows.drain_into(|chunk| zip.write_data(chunk))?;
+        zip.write_data(b"</sheetData>")?;
+        if sheet.has_numbers_as_text {
+            zip.write_data(&Self::ignored_errors_xml(sheet.max_col, sheet.current_row))?;
+        }
+        zip.write_data(b"</worksheet>")?;
+        Ok(())
+    }
+
+    /// Called with `(rows_written, bytes_written)` every
+    /// [`PROGRESS_INTERVAL`] rows and once on close
+    pub fn set_progress_callback(&mut self, callback: ProgressCallback) {
+        self.progress.set_callback(callback);
+    }
+
+    /// Report write statistics to `metrics`, see [`WriterMetrics`]
+    pub fn set_metrics(&mut self, metrics: Box<dyn WriterMetrics>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Make row writes fail with `ExcelError::Cancelled` once `token` is cancelled
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.progress.set_token(token);
+    }
+
+    /// Compute a checksum of the finished file while it is written
+    pub fn set_checksum(&mut self, checksum: Checksum) {
+        self.checksum.set(Some(checksum));
+    }
+
+    /// Deflate on `workers` threads while the calling thread builds rows
+    ///
+    /// 0 (the default) compresses on the calling thread. Can be changed at
+    /// any time; data written so far is compressed and written out first.
+    pub fn set_compression_workers(&mut self, workers: usize) -> Result<()> {
+        self.zip_writer.as_mut().unwrap().set_workers(workers)
+    }
+
+    /// Number of compression worker threads, 0 when compressing inline
+    pub fn compression_workers(&self) -> usize {
+        self.zip_writer.as_ref().map_or(0, ZipSink::workers)
+    }
+
+    /// Choose how string cells are stored; see [`StringMode`]
+    ///
+    /// Applies to cells written from now on; strings already in the shared
+    /// table stay there. Switching between [`StringMode::SharedHashed`] and
+    /// the other shared modes fails once a string has been shared, since the
+    /// two tables can't be merged.
+    pub fn set_string_mode(&mut self, mode: StringMode) -> Result<()> {
+        let hashed_in_use = self.hashed_strings.as_ref().is_some_and(|t| !t.is_empty());
+        let switches_table = match mode {
+            StringMode::Inline => false,
+            StringMode::SharedHashed { .. } => self.shared_strings.count() > 0,
+            _ => hashed_in_use,
+        };
+        if switches_table {
+            return Err(crate::error::ExcelError::WriteError(format!(
+                "Cannot switch to {:?} after strings were added to the other shared string table",
+                mode
+            )));
         }
+
+        match mode {
+            StringMode::Inline => {}
+            StringMode::SharedDeduped => self.shared_strings.set_max_unique(usize::MAX),
+            StringMode::SharedThresholded { max_unique } => {
+                self.shared_strings.set_max_unique(max_unique)
+            }
+            StringMode::SharedHashed { memory_limit } => match &mut self.hashed_strings {
+                Some(table) => table.set_memory_limit(memory_limit),
+                None => self.hashed_strings = Some(HashedStrings::new(memory_limit)),
+            },
+        }
+        self.string_mode = mode;
         Ok(())
     }
 
-    pub fn close(mut self) -> Result<()> {
+    pub fn close(self) -> Result<()> {
+        self.close_with_report().map(|_| ())
+    }
+
+    /// Finish the workbook and report the file's size and checksum
+    pub fn close_with_report(self) -> Result<WriteReport> {
+        self.close_output()?.finish()
+    }
+
+    /// Finish a workbook created with [`in_memory`](Self::in_memory)
+    pub(crate) fn close_into_bytes(self) -> Result<Vec<u8>> {
+        self.close_output()?.into_bytes()
+    }
+
+    fn close_output(mut self) -> Result<HashingWriter> {
+        self.progress.check()?;
+
         // Finish current worksheet
         self.finish_current_worksheet()?;
+        for sheet in std::mem::take(&mut self.buffered) {
+            self.write_buffered_worksheet(sheet)?;
+        }
+        self.progress.finish();
 
         // Write all other required ZIP entries
         self.write_content_types()?;
@@ -310,11 +2644,15 @@ impl ZeroTempWorkbook {
         self.write_shared_strings()?;
         self.write_app_props()?;
         self.write_core_props()?;
+        self.write_custom_props()?;
 
         // Finish ZIP
-        self.zip_writer.take().unwrap().finish()?;
-
-        Ok(())
+        let mut output = self.zip_writer.take().unwrap().finish()?;
+        if self.metrics.is_some() {
+            output.flush()?;
+            self.notify_metrics();
+        }
+        Ok(output)
     }
 
     fn write_content_types(&mut self) -> Result<()> {
@@ -333,6 +2671,12 @@ impl ZeroTempWorkbook {
 <Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>
 <Override PartName="/docProps/app.xml" ContentType="application/vnd.openxmlformats-officedocument.extended-properties+xml"/>"#,
         );
+        if !self.properties.custom.is_empty() {
+            xml.push_str(
+                r#"
+<Override PartName="/docProps/custom.xml" ContentType="application/vnd.openxmlformats-officedocument.custom-properties+xml"/>"#,
+            );
+        }
 
         for i in 1..=self.worksheet_count {
             xml.push_str(&format!(
@@ -342,6 +2686,28 @@ impl ZeroTempWorkbook {
             ));
         }
 
+        for i in 1..=self.drawing_count {
+            xml.push_str(&format!(
+                r#"
+<Override PartName="/xl/drawings/drawing{}.xml" ContentType="application/vnd.openxmlformats-officedocument.drawing+xml"/>"#,
+                i
+            ));
+        }
+        for i in 1..=self.chart_count {
+            xml.push_str(&format!(
+                r#"
+<Override PartName="/xl/charts/chart{}.xml" ContentType="application/vnd.openxmlformats-officedocument.drawingml.chart+xml"/>"#,
+                i
+            ));
+        }
+        for i in 1..=self.table_count {
+            xml.push_str(&format!(
+                r#"
+<Override PartName="/xl/tables/table{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.table+xml"/>"#,
+                i
+            ));
+        }
+
         xml.push_str("\n</Types>");
         self.zip_writer
             .as_mut()
@@ -355,12 +2721,20 @@ impl ZeroTempWorkbook {
             .as_mut()
             .unwrap()
             .start_entry("_rels/.rels")?;
-        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+        let mut xml = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
 <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
 <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>
-<Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/extended-properties" Target="docProps/app.xml"/>
-</Relationships>"#;
+<Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/extended-properties" Target="docProps/app.xml"/>"#,
+        );
+        if !self.properties.custom.is_empty() {
+            xml.push_str(
+                r#"
+<Relationship Id="rId4" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/custom-properties" Target="docProps/custom.xml"/>"#,
+            );
+        }
+        xml.push_str("\n</Relationships>");
         self.zip_writer
             .as_mut()
             .unwrap()
@@ -375,21 +2749,63 @@ impl ZeroTempWorkbook {
             .start_entry("xl/workbook.xml")?;
         let mut xml = String::from(
             r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
-<sheets>"#,
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
         );
+        if self.date_system == DateSystem::V1904 {
+            xml.push_str("\n<workbookPr date1904=\"1\"/>");
+        }
+
+        // Tab positions follow `sheet_order`; the default active tab is the
+        // first visible one
+        let position = |index: usize| self.sheet_order.iter().position(|&i| i == index);
+        let active = self.active_tab.and_then(position).or_else(|| {
+            self.sheet_order
+                .iter()
+                .position(|i| !self.hidden_sheets.contains(i))
+        });
+        if let Some(active) = active.filter(|&tab| tab > 0) {
+            xml.push_str(&format!(
+                r#"
+<bookViews><workbookView activeTab="{}"/></bookViews>"#,
+                active
+            ));
+        }
 
-        for (i, name) in self.worksheets.iter().enumerate() {
+        xml.push_str("\n<sheets>");
+        for &i in &self.sheet_order {
             xml.push_str(&format!(
                 r#"
-<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#,
-                name,
-                i + 1,
+<sheet name="{}" sheetId="{}""#,
+                super::xml_writer::escape(&self.worksheets[i]),
                 i + 1
             ));
+            if self.hidden_sheets.contains(&i) {
+                xml.push_str(r#" state="hidden""#);
+            }
+            xml.push_str(&format!(r#" r:id="rId{}"/>"#, i + 1));
+        }
+
+        xml.push_str("\n</sheets>");
+
+        if !self.defined_names.is_empty() {
+            xml.push_str("\n<definedNames>");
+            for (name, formula, local_sheet) in &self.defined_names {
+                xml.push_str(&format!(
+                    r#"<definedName name="{}""#,
+                    super::xml_writer::escape(name)
+                ));
+                // localSheetId is a tab position, not a sheet number
+                if let Some(index) = local_sheet.and_then(position) {
+                    xml.push_str(&format!(r#" localSheetId="{}""#, index));
+                }
+                xml.push('>');
+                xml.push_str(&super::xml_writer::escape(formula));
+                xml.push_str("</definedName>");
+            }
+            xml.push_str("</definedNames>");
         }
 
-        xml.push_str("\n</sheets>\n</workbook>");
+        xml.push_str("\n</workbook>");
         self.zip_writer
             .as_mut()
             .unwrap()
@@ -443,10 +2859,11 @@ impl ZeroTempWorkbook {
 <numFmt numFmtId="165" formatCode="mm/dd/yyyy hh:mm:ss"/>
 <numFmt numFmtId="166" formatCode="mm/dd/yyyy hh:mm"/>
 </numFmts>
-<fonts count="3">
+<fonts count="4">
 <font><sz val="11"/><name val="Calibri"/></font>
 <font><b/><sz val="11"/><name val="Calibri"/></font>
 <font><i/><sz val="11"/><name val="Calibri"/></font>
+<font><u/><sz val="11"/><color rgb="FF0563C1"/><name val="Calibri"/></font>
 </fonts>
 <fills count="5">
 <fill><patternFill patternType="none"/></fill>
@@ -459,7 +2876,7 @@ impl ZeroTempWorkbook {
 <border><left/><right/><top/><bottom/><diagonal/></border>
 <border><left style="thin"/><right style="thin"/><top style="thin"/><bottom style="thin"/></border>
 </borders>
-<cellXfs count="15">
+<cellXfs count="16">
 <xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>
 <xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/>
 <xf numFmtId="3" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>
@@ -475,63 +2892,162 @@ impl ZeroTempWorkbook {
 <xf numFmtId="0" fontId="0" fillId="4" borderId="0" xfId="0" applyFill="1"/>
 <xf numFmtId="0" fontId="0" fillId="0" borderId="1" xfId="0" applyBorder="1"/>
 <xf numFmtId="166" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>
+<xf numFmtId="0" fontId="3" fillId="0" borderId="0" xfId="0" applyFont="1"/>
 </cellXfs>
-</styleSheet>"#;
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
+"#;
+        let dxfs_xml = if self.dxfs.is_empty() {
+            Vec::new()
+        } else {
+            conditional_format::dxfs_xml(&self.dxfs)
+        };
+        let zip = self.zip_writer.as_mut().unwrap();
+        zip.write_data(xml.as_bytes())?;
+        zip.write_data(&dxfs_xml)?;
+        zip.write_data(b"\n</styleSheet>")?;
         Ok(())
     }
 
     fn write_shared_strings(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("xl/sharedStrings.xml")?;
+        let zip = self.zip_writer.as_mut().unwrap();
+        zip.start_entry("xl/sharedStrings.xml")?;
+        if let Some(table) = self.hashed_strings.as_mut().filter(|t| !t.is_empty()) {
+            return table.write_xml(|chunk| zip.write_data(chunk));
+        }
+        if self.shared_strings.count() > 0 {
+            let mut writer = XmlWriter::new(zip);
+            self.shared_strings.write_xml(&mut writer)?;
+            return writer.flush();
+        }
         let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" count="0" uniqueCount="0"/>
 "#;
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
-        Ok(())
+        zip.write_data(xml.as_bytes())
     }
 
     fn write_app_props(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("docProps/app.xml")?;
-        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+        let mut xml = Vec::with_capacity(512);
+        xml.extend_from_slice(
+            br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties">
-<Application>ExcelStream</Application>
-</Properties>"#;
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
+<Application>ExcelStream</Application>"#,
+        );
+        let props = &self.properties;
+        for (tag, value) in [("Manager", &props.manager), ("Company", &props.company)] {
+            if let Some(value) = value {
+                Self::push_element(&mut xml, tag, value);
+            }
+        }
+        xml.extend_from_slice(b"\n</Properties>");
+
+        let zip = self.zip_writer.as_mut().unwrap();
+        zip.start_entry("docProps/app.xml")?;
+        zip.write_data(&xml)?;
         Ok(())
     }
 
     fn write_core_props(&mut self) -> Result<()> {
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .start_entry("docProps/core.xml")?;
-        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
-<dc:creator>ExcelStream</dc:creator>
-</cp:coreProperties>"#;
-        self.zip_writer
-            .as_mut()
-            .unwrap()
-            .write_data(xml.as_bytes())?;
+        let props = &self.properties;
+        let created = props.created.unwrap_or_else(chrono::Utc::now);
+        let modified = props.modified.unwrap_or(created);
+
+        let mut xml = Vec::with_capacity(1024);
+        xml.extend_from_slice(
+            br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">"#,
+        );
+        let author = props.author.as_deref().unwrap_or("ExcelStream");
+        for (tag, value) in [
+            ("dc:title", props.title.as_deref()),
+            ("dc:subject", props.subject.as_deref()),
+            ("dc:creator", Some(author)),
+            ("cp:keywords", props.keywords.as_deref()),
+            ("dc:description", props.comments.as_deref()),
+            ("cp:lastModifiedBy", Some(author)),
+            ("cp:category", props.category.as_deref()),
+        ] {
+            if let Some(value) = value {
+                Self::push_element(&mut xml, tag, value);
+            }
+        }
+        for (tag, time) in [("created", created), ("modified", modified)] {
+            let _ = write!(
+                self.scratch,
+                "\n<dcterms:{0} xsi:type=\"dcterms:W3CDTF\">{1}</dcterms:{0}>",
+                tag,
+                time.format("%Y-%m-%dT%H:%M:%SZ")
+            );
+            xml.extend_from_slice(self.scratch.as_bytes());
+            self.scratch.clear();
+        }
+        xml.extend_from_slice(b"\n</cp:coreProperties>");
+
+        let zip = self.zip_writer.as_mut().unwrap();
+        zip.start_entry("docProps/core.xml")?;
+        zip.write_data(&xml)?;
+        Ok(())
+    }
+
+    /// `docProps/custom.xml`, only written when custom properties are set
+    fn write_custom_props(&mut self) -> Result<()> {
+        if self.properties.custom.is_empty() {
+            return Ok(());
+        }
+
+        let mut xml = Vec::with_capacity(256 + self.properties.custom.len() * 128);
+        xml.extend_from_slice(
+            br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/custom-properties" xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes">"#,
+        );
+        // Property ids 0 and 1 are reserved
+        for (pid, (name, value)) in (2..).zip(&self.properties.custom) {
+            let _ = write!(
+                self.scratch,
+                "\n<property fmtid=\"{{D5CDD505-2E9C-101B-9397-08002B2CF9AE}}\" pid=\"{}\" name=\"",
+                pid
+            );
+            xml.extend_from_slice(self.scratch.as_bytes());
+            self.scratch.clear();
+            Self::write_escaped(&mut xml, name);
+            xml.extend_from_slice(b"\">");
+
+            let (tag, text) = match value {
+                PropertyValue::Text(text) => ("vt:lpwstr", text.clone()),
+                PropertyValue::Int(n) if i32::try_from(*n).is_ok() => ("vt:i4", n.to_string()),
+                PropertyValue::Int(n) => ("vt:r8", n.to_string()),
+                PropertyValue::Number(n) => ("vt:r8", n.to_string()),
+                PropertyValue::Bool(b) => ("vt:bool", b.to_string()),
+                PropertyValue::DateTime(t) => {
+                    ("vt:filetime", t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+                }
+            };
+            xml.push(b'<');
+            xml.extend_from_slice(tag.as_bytes());
+            xml.push(b'>');
+            Self::write_escaped(&mut xml, &text);
+            xml.extend_from_slice(b"</");
+            xml.extend_from_slice(tag.as_bytes());
+            xml.extend_from_slice(b"></property>");
+        }
+        xml.extend_from_slice(b"\n</Properties>");
+
+        let zip = self.zip_writer.as_mut().unwrap();
+        zip.start_entry("docProps/custom.xml")?;
+        zip.write_data(&xml)?;
         Ok(())
     }
 
-    fn push_column_letter(buffer: &mut Vec<u8>, mut n: u32) {
+    /// `\n<tag>escaped value</tag>`
+    fn push_element(xml: &mut Vec<u8>, tag: &str, value: &str) {
+        xml.extend_from_slice(b"\n<");
+        xml.extend_from_slice(tag.as_bytes());
+        xml.push(b'>');
+        Self::write_escaped(xml, value);
+        xml.extend_from_slice(b"</");
+        xml.extend_from_slice(tag.as_bytes());
+        xml.push(b'>');
+    }
+
+    pub(super) fn push_column_letter(buffer: &mut Vec<u8>, mut n: u32) {
         if n == 0 {
             return;
         }
@@ -548,19 +3064,29 @@ impl ZeroTempWorkbook {
         }
     }
 
-    fn write_escaped(buffer: &mut Vec<u8>, s: &str) {
-        for c in s.chars() {
-            match c {
-                '&' => buffer.extend_from_slice(b"&amp;"),
-                '<' => buffer.extend_from_slice(b"&lt;"),
-                '>' => buffer.extend_from_slice(b"&gt;"),
-                '"' => buffer.extend_from_slice(b"&quot;"),
-                '\'' => buffer.extend_from_slice(b"&apos;"),
-                _ => {
-                    let mut buf = [0; 4];
-                    buffer.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
-                }
-            }
-        }
+    pub(super) fn write_escaped(buffer: &mut Vec<u8>, s: &str) {
+        super::xml_writer::push_escaped(buffer, s)
     }
 }
+
+/// Whether a character takes two columns in a typical spreadsheet font
+/// (CJK ideographs, Hangul, kana, fullwidth forms and emoji)
+fn is_wide_char(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F300..=0x1F64F
+            | 0x1F900..=0x1F9FF
+            | 0x20000..=0x3FFFD
+    )
+}
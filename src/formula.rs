@@ -0,0 +1,915 @@
+//! Lightweight formula evaluation for the streaming reader
+//!
+//! Supports numbers, text and boolean literals, the arithmetic (`+ - * / ^ %`),
+//! text (`&`) and comparison (`= <> < > <= >=`) operators, the functions
+//! `SUM`, `AVERAGE`, `COUNT`, `IF` and `CONCAT` (plus `CONCATENATE`), and
+//! references to cells and ranges on the same sheet. Anything else (other
+//! functions, other sheets, defined names, array formulas) makes
+//! [`evaluate`] return `None` so the caller can keep the formula as read.
+
+use crate::types::CellValue;
+
+/// Largest column index Excel allows (XFD)
+const MAX_COL: u32 = 16_383;
+/// Largest 0-based row index Excel allows
+const MAX_ROW: u32 = 1_048_575;
+
+/// Evaluate `expr` (with or without the leading `=`)
+///
+/// `lookup(row, col)` returns the value of a cell (both 0-based), or `None`
+/// when the cell isn't known yet; a formula depending on such a cell is not
+/// evaluated. Returns `None` when the formula can't be evaluated.
+pub(crate) fn evaluate(
+    expr: &str,
+    lookup: impl Fn(u32, u32) -> Option<CellValue>,
+) -> Option<CellValue> {
+    let expr = expr.strip_prefix('=').unwrap_or(expr);
+    let tokens = tokenize(expr)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let ast = parser.comparison()?;
+    if parser.pos != parser.tokens.len() {
+        return None;
+    }
+    let value = Evaluator { lookup }.eval(&ast)?;
+    Some(value.into_cell())
+}
+
+/// Move the relative references in `expr` by `rows` and `cols`
+///
+/// Used to expand a shared formula from its master cell to the other cells
+/// of its range. `$`-anchored parts stay put; references pushed off the
+/// sheet become `#REF!`.
+pub(crate) fn shift_references(expr: &str, rows: i64, cols: i64) -> String {
+    let bytes = expr.as_bytes();
+    let mut out = String::with_capacity(expr.len() + 8);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                // String literal or quoted sheet name, copied as is
+                let end = quoted_end(bytes, i, quote);
+                out.push_str(&expr[i..end]);
+                i = end;
+            }
+            b if is_word_byte(b) => {
+                let end = word_end(bytes, i);
+                let word = &expr[i..end];
+                let is_call = bytes.get(end) == Some(&b'(');
+                match parse_cell_ref(word).filter(|_| !is_call) {
+                    Some(cell) => match cell.shifted(rows, cols) {
+                        Some(cell) => cell.push_to(&mut out),
+                        None => out.push_str("#REF!"),
+                    },
+                    None => out.push_str(word),
+                }
+                i = end;
+            }
+            _ => {
+                let ch = expr[i..].chars().next().unwrap_or_default();
+                out.push(ch);
+                i += ch.len_utf8().max(1);
+            }
+        }
+    }
+    out
+}
+
+/// Rewrite the row of every reference to the same sheet in `expr`
+///
+/// `map` gets the 0-based row and whether the reference ends a range
+/// (`B2:B9`) and returns the new row. Unlike [`shift_references`] this
+/// moves `$`-anchored rows too, the way inserting rows in Excel does.
+#[cfg(feature = "serde")]
+pub(crate) fn map_row_references(expr: &str, mut map: impl FnMut(u32, bool) -> u32) -> String {
+    let bytes = expr.as_bytes();
+    let mut out = String::with_capacity(expr.len() + 8);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                let end = quoted_end(bytes, i, quote);
+                out.push_str(&expr[i..end]);
+                i = end;
+            }
+            b if is_word_byte(b) => {
+                let end = word_end(bytes, i);
+                let word = &expr[i..end];
+                let is_call = bytes.get(end) == Some(&b'(');
+                let prev = i.checked_sub(1).map(|p| bytes[p]);
+                match parse_cell_ref(word).filter(|_| !is_call && prev != Some(b'!')) {
+                    Some(mut cell) => {
+                        cell.row = map(cell.row, prev == Some(b':')).min(MAX_ROW);
+                        cell.push_to(&mut out);
+                    }
+                    None => out.push_str(word),
+                }
+                i = end;
+            }
+            _ => {
+                let ch = expr[i..].chars().next().unwrap_or_default();
+                out.push(ch);
+                i += ch.len_utf8().max(1);
+            }
+        }
+    }
+    out
+}
+
+/// 0-based row and column of a reference such as `B3` or `$B$3`
+#[cfg(feature = "serde")]
+pub(crate) fn cell_position(reference: &str) -> Option<(u32, u32)> {
+    parse_cell_ref(reference).map(|cell| (cell.row, cell.col))
+}
+
+/// A cell reference such as `B3` or `$B$3` (0-based)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CellRef {
+    col: u32,
+    row: u32,
+    col_absolute: bool,
+    row_absolute: bool,
+}
+
+impl CellRef {
+    fn shifted(self, rows: i64, cols: i64) -> Option<CellRef> {
+        let col = if self.col_absolute {
+            self.col as i64
+        } else {
+            self.col as i64 + cols
+        };
+        let row = if self.row_absolute {
+            self.row as i64
+        } else {
+            self.row as i64 + rows
+        };
+        if !(0..=MAX_COL as i64).contains(&col) || !(0..=MAX_ROW as i64).contains(&row) {
+            return None;
+        }
+        Some(CellRef {
+            col: col as u32,
+            row: row as u32,
+            ..self
+        })
+    }
+
+    fn push_to(&self, out: &mut String) {
+        if self.col_absolute {
+            out.push('$');
+        }
+        let mut letters = [0u8; 3];
+        let mut len = 0;
+        let mut n = self.col + 1;
+        while n > 0 {
+            letters[len] = b'A' + ((n - 1) % 26) as u8;
+            n = (n - 1) / 26;
+            len += 1;
+        }
+        for &letter in letters[..len].iter().rev() {
+            out.push(letter as char);
+        }
+        if self.row_absolute {
+            out.push('$');
+        }
+        out.push_str(&(self.row + 1).to_string());
+    }
+}
+
+/// Parse `A1`, `$A1`, `A$1` or `$A$1`
+fn parse_cell_ref(word: &str) -> Option<CellRef> {
+    let (col_absolute, rest) = match word.strip_prefix('$') {
+        Some(rest) => (true, rest),
+        None => (false, word),
+    };
+    let letters = rest.len()
+        - rest
+            .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+            .len();
+    if !(1..=3).contains(&letters) {
+        return None;
+    }
+    let (col_part, rest) = rest.split_at(letters);
+    let (row_absolute, digits) = match rest.strip_prefix('$') {
+        Some(digits) => (true, digits),
+        None => (false, rest),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let col = col_part.bytes().fold(0u32, |acc, b| {
+        acc * 26 + (b.to_ascii_uppercase() - b'A' + 1) as u32
+    }) - 1;
+    let row = digits.parse::<u32>().ok()?.checked_sub(1)?;
+    if col > MAX_COL || row > MAX_ROW {
+        return None;
+    }
+    Some(CellRef {
+        col,
+        row,
+        col_absolute,
+        row_absolute,
+    })
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'$' || b == b'_' || b == b'.'
+}
+
+fn word_end(bytes: &[u8], start: usize) -> usize {
+    let mut end = start;
+    while end < bytes.len() && is_word_byte(bytes[end]) {
+        end += 1;
+    }
+    end
+}
+
+/// End of the quoted run starting at `start`; doubled quotes are escapes
+fn quoted_end(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        if bytes[i] == quote {
+            if bytes.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Cell(CellRef),
+    /// Function name or boolean literal, upper-cased
+    Name(String),
+    Op(Op),
+    Percent,
+    Colon,
+    Comma,
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+fn tokenize(expr: &str) -> Option<Vec<Token>> {
+    let bytes = expr.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let single = match b {
+            b' ' | b'\t' | b'\r' | b'\n' => {
+                i += 1;
+                continue;
+            }
+            b'+' => Some(Token::Op(Op::Add)),
+            b'-' => Some(Token::Op(Op::Sub)),
+            b'*' => Some(Token::Op(Op::Mul)),
+            b'/' => Some(Token::Op(Op::Div)),
+            b'^' => Some(Token::Op(Op::Pow)),
+            b'&' => Some(Token::Op(Op::Concat)),
+            b'=' => Some(Token::Op(Op::Eq)),
+            b'%' => Some(Token::Percent),
+            b':' => Some(Token::Colon),
+            b',' => Some(Token::Comma),
+            b'(' => Some(Token::LParen),
+            b')' => Some(Token::RParen),
+            _ => None,
+        };
+        if let Some(token) = single {
+            tokens.push(token);
+            i += 1;
+            continue;
+        }
+        match b {
+            b'<' | b'>' => {
+                let next = bytes.get(i + 1).copied();
+                let (op, len) = match (b, next) {
+                    (b'<', Some(b'>')) => (Op::Ne, 2),
+                    (b'<', Some(b'=')) => (Op::Le, 2),
+                    (b'>', Some(b'=')) => (Op::Ge, 2),
+                    (b'<', _) => (Op::Lt, 1),
+                    _ => (Op::Gt, 1),
+                };
+                tokens.push(Token::Op(op));
+                i += len;
+            }
+            b'"' => {
+                let end = quoted_end(bytes, i, b'"');
+                if end - i < 2 || bytes[end - 1] != b'"' {
+                    return None;
+                }
+                tokens.push(Token::Text(expr[i + 1..end - 1].replace("\"\"", "\"")));
+                i = end;
+            }
+            b'0'..=b'9' | b'.' => {
+                let mut end = i;
+                while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+                    end += 1;
+                }
+                if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+                    let mut exp = end + 1;
+                    if exp < bytes.len() && (bytes[exp] == b'+' || bytes[exp] == b'-') {
+                        exp += 1;
+                    }
+                    if exp < bytes.len() && bytes[exp].is_ascii_digit() {
+                        end = exp;
+                        while end < bytes.len() && bytes[end].is_ascii_digit() {
+                            end += 1;
+                        }
+                    }
+                }
+                tokens.push(Token::Number(expr[i..end].parse().ok()?));
+                i = end;
+            }
+            b if is_word_byte(b) => {
+                let end = word_end(bytes, i);
+                let word = &expr[i..end];
+                let is_call = bytes.get(end) == Some(&b'(');
+                match parse_cell_ref(word).filter(|_| !is_call) {
+                    Some(cell) => tokens.push(Token::Cell(cell)),
+                    None => tokens.push(Token::Name(word.to_ascii_uppercase())),
+                }
+                i = end;
+            }
+            // Sheet-qualified references, error literals, arrays, ...
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Sum,
+    Average,
+    Count,
+    If,
+    Concat,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Cell(CellRef),
+    Range(CellRef, CellRef),
+    Negate(Box<Expr>),
+    Percent(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+/// Recursive descent parser, one method per precedence level
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn binary_level(&mut self, ops: &[Op], operand: fn(&mut Self) -> Option<Expr>) -> Option<Expr> {
+        let mut left = operand(self)?;
+        while let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if !ops.contains(&op) {
+                break;
+            }
+            self.pos += 1;
+            let right = operand(self)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Some(left)
+    }
+
+    fn comparison(&mut self) -> Option<Expr> {
+        let ops = [Op::Eq, Op::Ne, Op::Lt, Op::Le, Op::Gt, Op::Ge];
+        self.binary_level(&ops, Self::concat)
+    }
+
+    fn concat(&mut self) -> Option<Expr> {
+        self.binary_level(&[Op::Concat], Self::additive)
+    }
+
+    fn additive(&mut self) -> Option<Expr> {
+        self.binary_level(&[Op::Add, Op::Sub], Self::multiplicative)
+    }
+
+    fn multiplicative(&mut self) -> Option<Expr> {
+        self.binary_level(&[Op::Mul, Op::Div], Self::power)
+    }
+
+    fn power(&mut self) -> Option<Expr> {
+        self.binary_level(&[Op::Pow], Self::unary)
+    }
+
+    fn unary(&mut self) -> Option<Expr> {
+        match self.peek() {
+            Some(Token::Op(Op::Sub)) => {
+                self.pos += 1;
+                Some(Expr::Negate(Box::new(self.unary()?)))
+            }
+            Some(Token::Op(Op::Add)) => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.postfix(),
+        }
+    }
+
+    fn postfix(&mut self) -> Option<Expr> {
+        let mut expr = self.primary()?;
+        while self.peek() == Some(&Token::Percent) {
+            self.pos += 1;
+            expr = Expr::Percent(Box::new(expr));
+        }
+        Some(expr)
+    }
+
+    fn primary(&mut self) -> Option<Expr> {
+        match self.advance()? {
+            Token::Number(n) => Some(Expr::Number(n)),
+            Token::Text(s) => Some(Expr::Text(s)),
+            Token::Cell(first) => {
+                if self.peek() != Some(&Token::Colon) {
+                    return Some(Expr::Cell(first));
+                }
+                self.pos += 1;
+                match self.advance()? {
+                    Token::Cell(last) => Some(Expr::Range(first, last)),
+                    _ => None,
+                }
+            }
+            Token::LParen => {
+                let expr = self.comparison()?;
+                (self.advance()? == Token::RParen).then_some(expr)
+            }
+            Token::Name(name) if self.peek() == Some(&Token::LParen) => {
+                let function = match name.as_str() {
+                    "SUM" => Function::Sum,
+                    "AVERAGE" => Function::Average,
+                    "COUNT" => Function::Count,
+                    "IF" => Function::If,
+                    "CONCAT" | "CONCATENATE" | "_XLFN.CONCAT" => Function::Concat,
+                    _ => return None,
+                };
+                self.pos += 1;
+                let args = self.arguments()?;
+                let arity_ok = match function {
+                    Function::If => (2..=3).contains(&args.len()),
+                    _ => !args.is_empty(),
+                };
+                arity_ok.then_some(Expr::Call(function, args))
+            }
+            Token::Name(name) => match name.as_str() {
+                "TRUE" => Some(Expr::Bool(true)),
+                "FALSE" => Some(Expr::Bool(false)),
+                // Defined names aren't resolved
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Arguments after the opening parenthesis, up to and including `)`
+    fn arguments(&mut self) -> Option<Vec<Expr>> {
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::RParen) {
+            self.pos += 1;
+            return Some(args);
+        }
+        loop {
+            args.push(self.comparison()?);
+            match self.advance()? {
+                Token::Comma => continue,
+                Token::RParen => return Some(args),
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// A computed value, or an Excel error such as `#DIV/0!`
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Error(String),
+    Empty,
+}
+
+impl Value {
+    /// `None` for a formula cell with no cached result
+    fn from_cell(cell: CellValue) -> Option<Value> {
+        Some(match cell {
+            CellValue::Empty => Value::Empty,
+            CellValue::String(s) => Value::Text(s),
+            CellValue::Int(i) => Value::Number(i as f64),
+            CellValue::Float(f) | CellValue::DateTime(f) => Value::Number(f),
+            CellValue::Bool(b) => Value::Bool(b),
+            CellValue::Error(e) => Value::Error(e),
+            CellValue::Formula { cached, .. } => Value::from_cell(*cached?)?,
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => Value::Number(d.to_string().parse().ok()?),
+            CellValue::BigInt(s) => Value::Text(s),
+            CellValue::Hyperlink { text, .. } => Value::Text(text),
+        })
+    }
+
+    fn into_cell(self) -> CellValue {
+        match self {
+            Value::Number(n) if !n.is_finite() => CellValue::Error("#NUM!".to_string()),
+            Value::Number(n)
+                if n.fract() == 0.0 && (i64::MIN as f64..=i64::MAX as f64).contains(&n) =>
+            {
+                CellValue::Int(n as i64)
+            }
+            Value::Number(n) => CellValue::Float(n),
+            Value::Text(s) => CellValue::String(s),
+            Value::Bool(b) => CellValue::Bool(b),
+            Value::Error(e) => CellValue::Error(e),
+            // A formula pointing at an empty cell shows 0
+            Value::Empty => CellValue::Int(0),
+        }
+    }
+
+    fn to_number(&self) -> Result<f64, Value> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            Value::Bool(b) => Ok(*b as u8 as f64),
+            Value::Empty => Ok(0.0),
+            Value::Text(s) => s
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .ok_or_else(value_error),
+            Value::Error(_) => Err(self.clone()),
+        }
+    }
+
+    fn to_text(&self) -> Result<String, Value> {
+        match self {
+            Value::Number(n) => Ok(format_number(*n)),
+            Value::Bool(true) => Ok("TRUE".to_string()),
+            Value::Bool(false) => Ok("FALSE".to_string()),
+            Value::Empty => Ok(String::new()),
+            Value::Text(s) => Ok(s.clone()),
+            Value::Error(_) => Err(self.clone()),
+        }
+    }
+
+    fn to_bool(&self) -> Result<bool, Value> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            Value::Number(n) => Ok(*n != 0.0),
+            Value::Empty => Ok(false),
+            Value::Text(s) if s.eq_ignore_ascii_case("TRUE") => Ok(true),
+            Value::Text(s) if s.eq_ignore_ascii_case("FALSE") => Ok(false),
+            Value::Text(_) => Err(value_error()),
+            Value::Error(_) => Err(self.clone()),
+        }
+    }
+}
+
+fn value_error() -> Value {
+    Value::Error("#VALUE!".to_string())
+}
+
+/// Number as Excel displays it in text: at most 15 significant digits
+fn format_number(n: f64) -> String {
+    let rounded: f64 = format!("{:.14e}", n).parse().unwrap_or(n);
+    if rounded.fract() == 0.0 && rounded.abs() < 1e15 {
+        format!("{}", rounded as i64)
+    } else {
+        rounded.to_string()
+    }
+}
+
+/// Excel ordering: numbers < text < booleans, text compared case-insensitively
+fn compare(left: &Value, right: &Value) -> std::cmp::Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Number(_) | Value::Empty => 0,
+            Value::Text(_) => 1,
+            _ => 2,
+        }
+    }
+    // An empty cell compares as 0, "" or FALSE depending on the other side
+    let blank_as = |other: &Value| match other {
+        Value::Text(_) => Value::Text(String::new()),
+        Value::Bool(_) => Value::Bool(false),
+        _ => Value::Number(0.0),
+    };
+    let left = match left {
+        Value::Empty => blank_as(right),
+        _ => left.clone(),
+    };
+    let right = match right {
+        Value::Empty => blank_as(&left),
+        _ => right.clone(),
+    };
+    match (&left, &right) {
+        (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+        (Value::Text(a), Value::Text(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        _ => rank(&left).cmp(&rank(&right)),
+    }
+}
+
+struct Evaluator<F> {
+    lookup: F,
+}
+
+impl<F: Fn(u32, u32) -> Option<CellValue>> Evaluator<F> {
+    fn cell(&self, cell: CellRef) -> Option<Value> {
+        Value::from_cell((self.lookup)(cell.row, cell.col)?)
+    }
+
+    /// Values of a range, row by row
+    fn range(&self, first: CellRef, last: CellRef) -> Option<Vec<Value>> {
+        let (top, bottom) = (first.row.min(last.row), first.row.max(last.row));
+        let (left, right) = (first.col.min(last.col), first.col.max(last.col));
+        let mut values = Vec::new();
+        for row in top..=bottom {
+            for col in left..=right {
+                values.push(Value::from_cell((self.lookup)(row, col)?)?);
+            }
+        }
+        Some(values)
+    }
+
+    fn eval(&self, expr: &Expr) -> Option<Value> {
+        Some(match expr {
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Text(s) => Value::Text(s.clone()),
+            Expr::Bool(b) => Value::Bool(*b),
+            Expr::Cell(cell) => self.cell(*cell)?,
+            // A bare range outside a function needs implicit intersection
+            Expr::Range(..) => return None,
+            Expr::Negate(inner) => match self.eval(inner)?.to_number() {
+                Ok(n) => Value::Number(-n),
+                Err(e) => e,
+            },
+            Expr::Percent(inner) => match self.eval(inner)?.to_number() {
+                Ok(n) => Value::Number(n / 100.0),
+                Err(e) => e,
+            },
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                binary(*op, &left, &right)
+            }
+            Expr::Call(function, args) => self.call(*function, args)?,
+        })
+    }
+
+    /// Values of one function argument; references keep their cells apart
+    /// from literals because SUM and friends skip text found in cells
+    fn argument(&self, arg: &Expr) -> Option<(Vec<Value>, bool)> {
+        match arg {
+            Expr::Cell(cell) => Some((vec![self.cell(*cell)?], true)),
+            Expr::Range(first, last) => Some((self.range(*first, *last)?, true)),
+            _ => Some((vec![self.eval(arg)?], false)),
+        }
+    }
+
+    fn call(&self, function: Function, args: &[Expr]) -> Option<Value> {
+        match function {
+            Function::If => {
+                let condition = match self.eval(&args[0])?.to_bool() {
+                    Ok(condition) => condition,
+                    Err(e) => return Some(e),
+                };
+                match (condition, args.get(2)) {
+                    (true, _) => self.eval(&args[1]),
+                    (false, Some(otherwise)) => self.eval(otherwise),
+                    (false, None) => Some(Value::Bool(false)),
+                }
+            }
+            Function::Concat => {
+                let mut text = String::new();
+                for arg in args {
+                    for value in self.argument(arg)?.0 {
+                        match value.to_text() {
+                            Ok(s) => text.push_str(&s),
+                            Err(e) => return Some(e),
+                        }
+                    }
+                }
+                Some(Value::Text(text))
+            }
+            Function::Sum | Function::Average | Function::Count => {
+                let mut sum = 0.0;
+                let mut count = 0usize;
+                for arg in args {
+                    let (values, from_cells) = self.argument(arg)?;
+                    for value in values {
+                        let number = match (&value, from_cells) {
+                            (Value::Number(n), _) => Ok(*n),
+                            (Value::Error(_), _) if function == Function::Count => continue,
+                            (Value::Error(_), _) => Err(value.clone()),
+                            // Text, booleans and blanks in cells are skipped
+                            (_, true) => continue,
+                            (_, false) => value.to_number(),
+                        };
+                        match number {
+                            Ok(n) => {
+                                sum += n;
+                                count += 1;
+                            }
+                            Err(_) if function == Function::Count => {}
+                            Err(e) => return Some(e),
+                        }
+                    }
+                }
+                Some(match function {
+                    Function::Sum => Value::Number(sum),
+                    Function::Count => Value::Number(count as f64),
+                    _ if count == 0 => Value::Error("#DIV/0!".to_string()),
+                    _ => Value::Number(sum / count as f64),
+                })
+            }
+        }
+    }
+}
+
+fn binary(op: Op, left: &Value, right: &Value) -> Value {
+    match op {
+        Op::Concat => match (left.to_text(), right.to_text()) {
+            (Ok(a), Ok(b)) => Value::Text(a + &b),
+            (Err(e), _) | (_, Err(e)) => e,
+        },
+        Op::Eq | Op::Ne | Op::Lt | Op::Le | Op::Gt | Op::Ge => {
+            for value in [left, right] {
+                if let Value::Error(_) = value {
+                    return value.clone();
+                }
+            }
+            let ordering = compare(left, right);
+            Value::Bool(match op {
+                Op::Eq => ordering.is_eq(),
+                Op::Ne => ordering.is_ne(),
+                Op::Lt => ordering.is_lt(),
+                Op::Le => ordering.is_le(),
+                Op::Gt => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            })
+        }
+        _ => {
+            let (a, b) = match (left.to_number(), right.to_number()) {
+                (Ok(a), Ok(b)) => (a, b),
+                (Err(e), _) | (_, Err(e)) => return e,
+            };
+            let result = match op {
+                Op::Add => a + b,
+                Op::Sub => a - b,
+                Op::Mul => a * b,
+                Op::Div if b == 0.0 => return Value::Error("#DIV/0!".to_string()),
+                Op::Div => a / b,
+                _ => a.powf(b),
+            };
+            if result.is_finite() {
+                Value::Number(result)
+            } else {
+                Value::Error("#NUM!".to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A1:C2 hold 1 2 "x" / 4 "5" (empty); row 3 and below are unknown
+    fn lookup(row: u32, col: u32) -> Option<CellValue> {
+        let rows = [
+            vec![
+                CellValue::Int(1),
+                CellValue::Int(2),
+                CellValue::String("x".to_string()),
+            ],
+            vec![CellValue::Int(4), CellValue::String("5".to_string())],
+        ];
+        let cells = rows.get(row as usize)?;
+        Some(cells.get(col as usize).cloned().unwrap_or(CellValue::Empty))
+    }
+
+    fn eval(expr: &str) -> Option<CellValue> {
+        evaluate(expr, lookup)
+    }
+
+    #[test]
+    fn test_arithmetic_and_precedence() {
+        assert_eq!(eval("=1+2*3"), Some(CellValue::Int(7)));
+        assert_eq!(eval("=(1+2)*3"), Some(CellValue::Int(9)));
+        assert_eq!(eval("=-2^2"), Some(CellValue::Int(4)));
+        assert_eq!(eval("=2^3^2"), Some(CellValue::Int(64)));
+        assert_eq!(eval("=50%"), Some(CellValue::Float(0.5)));
+        assert_eq!(eval("=A1+B1*A2"), Some(CellValue::Int(9)));
+        assert_eq!(eval("=B2*2"), Some(CellValue::Int(10)));
+        assert_eq!(eval("=C2+1"), Some(CellValue::Int(1)));
+        assert_eq!(eval("=A1/0"), Some(CellValue::Error("#DIV/0!".to_string())));
+        assert_eq!(eval("=C1+1"), Some(CellValue::Error("#VALUE!".to_string())));
+        assert_eq!(eval("=1.5E+2"), Some(CellValue::Int(150)));
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(eval("=SUM(A1:C2)"), Some(CellValue::Int(7)));
+        assert_eq!(eval("=SUM(A1:B1,10,\"5\")"), Some(CellValue::Int(18)));
+        assert_eq!(eval("=AVERAGE(A1:A2)"), Some(CellValue::Float(2.5)));
+        assert_eq!(
+            eval("=AVERAGE(C1:C2)"),
+            Some(CellValue::Error("#DIV/0!".to_string()))
+        );
+        assert_eq!(eval("=COUNT(A1:C2)"), Some(CellValue::Int(3)));
+        assert_eq!(
+            eval("=IF(A1>0,\"pos\",\"neg\")"),
+            Some(CellValue::String("pos".to_string()))
+        );
+        assert_eq!(eval("=IF(A1>1,\"big\")"), Some(CellValue::Bool(false)));
+        assert_eq!(
+            eval("=CONCAT(C1,\"-\",A1:B1)&TRUE"),
+            Some(CellValue::String("x-12TRUE".to_string()))
+        );
+        assert_eq!(
+            eval("=CONCATENATE(\"a\"\"b\",0.1+0.2)"),
+            Some(CellValue::String("a\"b0.3".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert_eq!(eval("=C1=\"X\""), Some(CellValue::Bool(true)));
+        assert_eq!(eval("=C1>100"), Some(CellValue::Bool(true)));
+        assert_eq!(eval("=C2=0"), Some(CellValue::Bool(true)));
+        assert_eq!(eval("=A1<>B1"), Some(CellValue::Bool(true)));
+        assert_eq!(eval("=TRUE>C1"), Some(CellValue::Bool(true)));
+    }
+
+    #[test]
+    fn test_unsupported_formulas() {
+        // Unknown cells, other sheets, names and functions
+        assert_eq!(eval("=A3+1"), None);
+        assert_eq!(eval("=SUM(A1:A3)"), None);
+        assert_eq!(eval("=Sheet2!A1"), None);
+        assert_eq!(eval("='My Sheet'!A1"), None);
+        assert_eq!(eval("=TaxRate*2"), None);
+        assert_eq!(eval("=VLOOKUP(1,A1:B2,2)"), None);
+        assert_eq!(eval("=A1:B1"), None);
+        assert_eq!(eval("=(1+2"), None);
+        assert_eq!(
+            evaluate("=A1*2", |_, _| Some(CellValue::formula("=B1"))),
+            None
+        );
+        assert_eq!(
+            evaluate("=A1*2", |_, _| Some(CellValue::formula_with_result(
+                "=B1",
+                CellValue::Int(3)
+            ))),
+            Some(CellValue::Int(6))
+        );
+    }
+
+    #[test]
+    fn test_shift_references() {
+        assert_eq!(shift_references("A1*2", 1, 0), "A2*2");
+        assert_eq!(
+            shift_references("SUM($A1:A$1)+B$2&\"A1\"", 2, 1),
+            "SUM($A3:B$1)+C$2&\"A1\""
+        );
+        assert_eq!(shift_references("LOG10(Z9)", 0, 1), "LOG10(AA9)");
+        assert_eq!(shift_references("A1-1", -1, 0), "#REF!-1");
+    }
+}
@@ -0,0 +1,410 @@
+//! Grouped reports: group header rows, detail rows and subtotal rows
+//!
+//! [`GroupedReportWriter`] writes rows sorted by their group columns into
+//! the layout Excel's Data > Subtotal command produces, plus a header row
+//! per group: each group opens with a bold row showing its key, its detail
+//! rows sit one outline level deeper, and it closes with a bold subtotal
+//! row of `SUBTOTAL` formulas that carries the group's +/- button. Key
+//! columns are left blank in detail rows, so nested groups read as an
+//! indented outline.
+//!
+//! ```no_run
+//! use excelstream::grouped_report::GroupedReportWriter;
+//! use excelstream::types::CellValue;
+//! use excelstream::ExcelWriter;
+//!
+//! let mut writer = ExcelWriter::new("sales.xlsx")?;
+//! let mut report = GroupedReportWriter::new(&mut writer)
+//!     .group_by(0)
+//!     .group_by(1)
+//!     .subtotal(3);
+//! report.write_header(["Region", "Product", "Month", "Amount"])?;
+//! for (region, product, month, amount) in [
+//!     ("North", "Tea", "Jan", 120.0),
+//!     ("North", "Tea", "Feb", 80.0),
+//!     ("North", "Coffee", "Jan", 200.0),
+//!     ("South", "Tea", "Jan", 50.0),
+//! ] {
+//!     report.write_row_typed(&[
+//!         CellValue::from(region),
+//!         CellValue::from(product),
+//!         CellValue::from(month),
+//!         CellValue::Float(amount),
+//!     ])?;
+//! }
+//! // North Total, South Total and the grand total
+//! report.finish()?;
+//! writer.save()?;
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+
+use crate::error::{ExcelError, Result};
+use crate::types::{Cell, CellStyle, CellValue};
+use crate::writer::ExcelWriter;
+
+/// Aggregate computed by the `SUBTOTAL` formulas of subtotal rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubtotalFunction {
+    #[default]
+    Sum,
+    Average,
+    /// Number of numeric cells
+    Count,
+    Max,
+    Min,
+}
+
+impl SubtotalFunction {
+    /// `function_num` argument of `SUBTOTAL`
+    fn number(self) -> u8 {
+        match self {
+            SubtotalFunction::Average => 1,
+            SubtotalFunction::Count => 2,
+            SubtotalFunction::Max => 4,
+            SubtotalFunction::Min => 5,
+            SubtotalFunction::Sum => 9,
+        }
+    }
+}
+
+/// Numeric cells seen in one subtotal column of a group
+#[derive(Debug, Clone, Copy)]
+struct Stats {
+    sum: f64,
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats {
+            sum: 0.0,
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Stats {
+    fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// What Excel shows for the formula, stored as its cached result
+    fn result(&self, function: SubtotalFunction) -> CellValue {
+        match function {
+            SubtotalFunction::Sum => CellValue::Float(self.sum),
+            SubtotalFunction::Count => CellValue::Int(self.count as i64),
+            SubtotalFunction::Average if self.count == 0 => CellValue::Error("#DIV/0!".into()),
+            SubtotalFunction::Average => CellValue::Float(self.sum / self.count as f64),
+            _ if self.count == 0 => CellValue::Float(0.0),
+            SubtotalFunction::Max => CellValue::Float(self.max),
+            SubtotalFunction::Min => CellValue::Float(self.min),
+        }
+    }
+}
+
+/// A group whose subtotal row hasn't been written yet
+struct OpenGroup {
+    key: CellValue,
+    /// Excel row number of the group's first row after its header
+    first_row: u32,
+    stats: Vec<Stats>,
+}
+
+/// Writes rows sorted by group columns as a grouped report with subtotals
+///
+/// See the [module documentation](self) for the layout and an example.
+/// Groups are closed whenever a key changes, so rows must arrive sorted by
+/// the group columns, outermost first; unsorted input repeats groups.
+/// Groups nest up to 7 levels, Excel's outline limit. Call
+/// [`finish`](Self::finish) after the last row to close the open groups.
+pub struct GroupedReportWriter<'a> {
+    writer: &'a mut ExcelWriter,
+    group_by: Vec<usize>,
+    subtotals: Vec<usize>,
+    function: SubtotalFunction,
+    collapsed: bool,
+    grand_total: bool,
+    open: Vec<OpenGroup>,
+    /// Excel row number of the first group header
+    first_row: Option<u32>,
+    totals: Vec<Stats>,
+}
+
+impl<'a> GroupedReportWriter<'a> {
+    /// Report written to the current sheet of `writer`
+    pub fn new(writer: &'a mut ExcelWriter) -> Self {
+        GroupedReportWriter {
+            writer,
+            group_by: Vec::new(),
+            subtotals: Vec::new(),
+            function: SubtotalFunction::Sum,
+            collapsed: false,
+            grand_total: true,
+            open: Vec::new(),
+            first_row: None,
+            totals: Vec::new(),
+        }
+    }
+
+    /// Group by column `col` (0-based); call again for nested groups
+    pub fn group_by(mut self, col: usize) -> Self {
+        self.group_by.push(col);
+        self
+    }
+
+    /// Add a `SUBTOTAL` formula for column `col` (0-based) to subtotal rows
+    pub fn subtotal(mut self, col: usize) -> Self {
+        self.subtotals.push(col);
+        self.totals.push(Stats::default());
+        self
+    }
+
+    /// Aggregate used by every subtotal column (default: sum)
+    pub fn function(mut self, function: SubtotalFunction) -> Self {
+        self.function = function;
+        self
+    }
+
+    /// Write groups collapsed to their subtotal rows (default: false)
+    pub fn collapsed(mut self, collapsed: bool) -> Self {
+        self.collapsed = collapsed;
+        self
+    }
+
+    /// Write a grand total row after the last group (default: true)
+    pub fn grand_total(mut self, enabled: bool) -> Self {
+        self.grand_total = enabled;
+        self
+    }
+
+    /// Write a bold header row above the report
+    pub fn write_header<I, S>(&mut self, headers: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.writer.write_header_bold(headers)
+    }
+
+    /// Write a detail row, closing and opening groups as its keys change
+    pub fn write_row_typed(&mut self, row: &[CellValue]) -> Result<()> {
+        if self.group_by.is_empty() {
+            return Err(ExcelError::InvalidState(
+                "Grouped report has no group columns".to_string(),
+            ));
+        }
+        let keys: Vec<CellValue> = self
+            .group_by
+            .iter()
+            .map(|&col| row.get(col).cloned().unwrap_or(CellValue::Empty))
+            .collect();
+
+        let unchanged = self
+            .open
+            .iter()
+            .zip(&keys)
+            .take_while(|(group, key)| group.key == **key)
+            .count();
+        while self.open.len() > unchanged {
+            self.close_group()?;
+        }
+        for key in keys.into_iter().skip(unchanged) {
+            self.open_group(key)?;
+        }
+
+        for (i, &col) in self.subtotals.iter().enumerate() {
+            let Some(value) = row
+                .get(col)
+                .and_then(CellValue::as_f64)
+                .filter(|n| n.is_finite())
+            else {
+                continue;
+            };
+            self.totals[i].add(value);
+            for group in &mut self.open {
+                group.stats[i].add(value);
+            }
+        }
+
+        let mut cells = row.to_vec();
+        for &col in &self.group_by {
+            if let Some(cell) = cells.get_mut(col) {
+                *cell = CellValue::Empty;
+            }
+        }
+        self.writer.write_row_typed(&cells)
+    }
+
+    /// Close the open groups and write the grand total row
+    pub fn finish(mut self) -> Result<()> {
+        while !self.open.is_empty() {
+            self.close_group()?;
+        }
+        if let (true, Some(first_row)) = (self.grand_total, self.first_row) {
+            let row = self.total_row(
+                self.group_by[0],
+                "Grand Total".to_string(),
+                first_row,
+                &self.totals,
+            );
+            self.writer.write_row_styled(&row)?;
+        }
+        Ok(())
+    }
+
+    /// Width of rows written by the report itself
+    fn width(&self) -> usize {
+        let last = self.group_by.iter().chain(&self.subtotals).max();
+        last.map_or(0, |&col| col + 1)
+    }
+
+    fn open_group(&mut self, key: CellValue) -> Result<()> {
+        let col = self.group_by[self.open.len()];
+        let mut row = vec![(CellValue::Empty, CellStyle::Default); self.width()];
+        row[col] = (key.clone(), CellStyle::TextBold);
+        self.writer.write_row_styled(&row)?;
+        let header_row = self.writer.current_row();
+        self.first_row.get_or_insert(header_row);
+
+        self.writer.start_group()?;
+        self.open.push(OpenGroup {
+            key,
+            first_row: header_row + 1,
+            stats: vec![Stats::default(); self.subtotals.len()],
+        });
+        Ok(())
+    }
+
+    fn close_group(&mut self) -> Result<()> {
+        let Some(group) = self.open.pop() else {
+            return Ok(());
+        };
+        self.writer.end_group(self.collapsed)?;
+        let col = self.group_by[self.open.len()];
+        let label = format!("{} Total", group.key.as_string());
+        let row = self.total_row(col, label, group.first_row, &group.stats);
+        self.writer.write_row_styled(&row)
+    }
+
+    /// Bold row with `label` in column `col` and a `SUBTOTAL` over rows
+    /// `first_row` to the last one written in each subtotal column
+    fn total_row(
+        &self,
+        col: usize,
+        label: String,
+        first_row: u32,
+        stats: &[Stats],
+    ) -> Vec<(CellValue, CellStyle)> {
+        let last_row = self.writer.current_row().max(first_row);
+        let mut row = vec![(CellValue::Empty, CellStyle::Default); self.width()];
+        row[col] = (CellValue::String(label), CellStyle::TextBold);
+        for (&col, stats) in self.subtotals.iter().zip(stats) {
+            let letter = Cell::col_to_letter(col as u32);
+            let expr = format!(
+                "SUBTOTAL({},{}{}:{}{})",
+                self.function.number(),
+                letter,
+                first_row,
+                letter,
+                last_row
+            );
+            let value = CellValue::formula_with_result(expr, stats.result(self.function));
+            row[col] = (value, CellStyle::TextBold);
+        }
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fast_writer::StreamingZipReader;
+
+    #[test]
+    fn test_grouped_report() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("grouped.xlsx");
+        let mut writer = ExcelWriter::new(&path).unwrap();
+        let mut report = GroupedReportWriter::new(&mut writer)
+            .group_by(0)
+            .group_by(1)
+            .subtotal(2);
+        report
+            .write_header(["Region", "Product", "Amount"])
+            .unwrap();
+        for (region, product, amount) in [
+            ("North", "Tea", 10),
+            ("North", "Tea", 20),
+            ("North", "Coffee", 5),
+            ("South", "Tea", 7),
+        ] {
+            report
+                .write_row_typed(&[
+                    CellValue::from(region),
+                    CellValue::from(product),
+                    CellValue::Int(amount),
+                ])
+                .unwrap();
+        }
+        report.finish().unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(&path).unwrap();
+        let xml = zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap();
+        let xml = String::from_utf8(xml).unwrap();
+        let rows: Vec<&str> = xml.split("<row ").skip(1).collect();
+        assert_eq!(rows.len(), 16);
+
+        // North header, Tea header, two details, Tea total
+        assert!(rows[1].starts_with(r#"r="2"><c r="A2" s="8""#));
+        assert!(rows[2].starts_with(r#"r="3" outlineLevel="1"><c r="A3"/><c r="B3" s="8""#));
+        assert!(rows[3].starts_with(r#"r="4" outlineLevel="2"><c r="A4"/><c r="B4"/>"#));
+        assert!(rows[5].starts_with(r#"r="6" outlineLevel="1">"#));
+        assert!(rows[5].contains("<f>SUBTOTAL(9,C4:C5)</f><v>30</v>"));
+        // Coffee group, then the North total over both products
+        assert!(rows[8].contains("<f>SUBTOTAL(9,C8:C8)</f><v>5</v>"));
+        assert!(rows[9].starts_with(r#"r="10"><c r="A10" s="8""#));
+        assert!(rows[9].contains("North Total"));
+        assert!(rows[9].contains("<f>SUBTOTAL(9,C3:C9)</f><v>35</v>"));
+        // South, then the grand total over every group
+        assert!(rows[14].contains("<f>SUBTOTAL(9,C12:C14)</f><v>7</v>"));
+        assert!(rows[15].contains("Grand Total"));
+        assert!(rows[15].contains("<f>SUBTOTAL(9,C2:C15)</f><v>42</v>"));
+    }
+
+    #[test]
+    fn test_grouped_report_functions() {
+        let mut stats = Stats::default();
+        assert_eq!(
+            stats.result(SubtotalFunction::Average),
+            CellValue::Error("#DIV/0!".to_string())
+        );
+        assert_eq!(stats.result(SubtotalFunction::Max), CellValue::Float(0.0));
+        stats.add(4.0);
+        stats.add(-1.0);
+        assert_eq!(stats.result(SubtotalFunction::Count), CellValue::Int(2));
+        assert_eq!(
+            stats.result(SubtotalFunction::Average),
+            CellValue::Float(1.5)
+        );
+        assert_eq!(stats.result(SubtotalFunction::Min), CellValue::Float(-1.0));
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut writer = ExcelWriter::new(dir.path().join("empty.xlsx")).unwrap();
+        let mut report = GroupedReportWriter::new(&mut writer).subtotal(1);
+        assert!(report.write_row_typed(&[CellValue::Int(1)]).is_err());
+        // No rows: nothing to total
+        GroupedReportWriter::new(&mut writer)
+            .group_by(0)
+            .finish()
+            .unwrap();
+        assert_eq!(writer.current_row(), 0);
+    }
+}
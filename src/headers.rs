@@ -0,0 +1,369 @@
+//! Finding the header row of hand-made sheets
+//!
+//! Sheets exported by people rarely start with their header: a merged
+//! title, a "generated on" line or a blank row usually come first.
+//! [`HeaderDetector`] scores the first rows of a sheet and picks the one
+//! that looks most like a header (many distinct text cells, no numbers or
+//! dates), and [`normalize_header`] turns its cells into `snake_case`
+//! keys. [`StreamingReader::rows_with_headers`] puts the two together and
+//! returns the rows below the header as records.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use excelstream::ExcelReader;
+//!
+//! let mut reader = ExcelReader::open("quarterly report.xlsx")?;
+//! let mut records = reader.rows_with_headers("Sheet1")?;
+//! println!("header on row {}: {:?}", records.header_row(), records.header().names);
+//! for record in &mut records {
+//!     let record = record?;
+//!     println!("{:?} owes {:?}", record["customer_name"], record["amount_due"]);
+//! }
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+//!
+//! [`StreamingReader::rows_with_headers`]: crate::streaming_reader::StreamingReader::rows_with_headers
+
+use crate::error::{ExcelError, Result};
+use crate::streaming_reader::RowIterator;
+use crate::types::{is_blank_row, CellValue};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Settings for picking the header row among the first rows of a sheet
+///
+/// A row is a candidate when it has at least `min_columns` distinct text
+/// cells. Candidates score one point per distinct text cell and lose two
+/// for every number, date, boolean or repeated text, so title rows (one
+/// merged cell) and data rows (mostly numbers) lose to the real header.
+/// Ties go to the upper row.
+///
+/// ```
+/// use excelstream::headers::HeaderDetector;
+/// use excelstream::types::CellValue;
+///
+/// let rows = vec![
+///     vec![CellValue::from("Sales report 2024")],
+///     vec![CellValue::Empty],
+///     vec![CellValue::from("Region"), CellValue::from("Units Sold")],
+///     vec![CellValue::from("North"), CellValue::Int(120)],
+/// ];
+/// let header = HeaderDetector::default().detect(&rows).unwrap();
+/// assert_eq!(header.index, 2);
+/// assert_eq!(header.names, ["region", "units_sold"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderDetector {
+    /// Rows (blank rows excluded) searched for the header (default: 10)
+    pub scan_rows: usize,
+    /// Fewest distinct text cells a header row can have (default: 2)
+    pub min_columns: usize,
+}
+
+impl Default for HeaderDetector {
+    fn default() -> Self {
+        HeaderDetector {
+            scan_rows: 10,
+            min_columns: 2,
+        }
+    }
+}
+
+/// Header row found by [`HeaderDetector::detect`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedHeader {
+    /// Position of the header in the rows given to `detect`
+    pub index: usize,
+    /// Header cells as they read in the sheet, trimmed
+    pub raw: Vec<String>,
+    /// Unique `snake_case` keys, one per column; see [`normalize_header`]
+    pub names: Vec<String>,
+}
+
+impl HeaderDetector {
+    /// The best header row among the first `scan_rows` of `rows`, if any
+    /// row qualifies
+    pub fn detect(&self, rows: &[Vec<CellValue>]) -> Option<DetectedHeader> {
+        let mut best: Option<(usize, i64)> = None;
+        for (index, row) in rows.iter().take(self.scan_rows).enumerate() {
+            let Some(score) = self.score(row) else {
+                continue;
+            };
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((index, score));
+            }
+        }
+
+        let (index, _) = best?;
+        let row = &rows[index];
+        let width = row.iter().rposition(|cell| !cell.is_blank())? + 1;
+        let raw: Vec<String> = row[..width]
+            .iter()
+            .map(|cell| cell.as_string().trim().to_string())
+            .collect();
+        Some(DetectedHeader {
+            index,
+            names: unique_names(&raw),
+            raw,
+        })
+    }
+
+    /// Score of `row` as a header, `None` if it can't be one
+    fn score(&self, row: &[CellValue]) -> Option<i64> {
+        let mut seen = HashSet::new();
+        let mut text = 0;
+        let mut other = 0;
+        for cell in row.iter().filter(|cell| !cell.is_blank()) {
+            match cell {
+                CellValue::String(s) if seen.insert(s.trim().to_lowercase()) => text += 1,
+                _ => other += 1,
+            }
+        }
+        (text >= self.min_columns).then(|| text as i64 - 2 * other as i64)
+    }
+}
+
+/// `snake_case` key for a header cell
+///
+/// Letters are lowercased, a capital following a lowercase letter or digit
+/// starts a new word (`firstName` → `first_name`), and any run of other
+/// characters becomes one underscore, trimmed at both ends.
+///
+/// ```
+/// use excelstream::headers::normalize_header;
+///
+/// assert_eq!(normalize_header("  Order ID "), "order_id");
+/// assert_eq!(normalize_header("Unit Price ($)"), "unit_price");
+/// assert_eq!(normalize_header("firstName"), "first_name");
+/// assert_eq!(normalize_header("E-mail / Phone"), "e_mail_phone");
+/// ```
+pub fn normalize_header(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+    let mut word_end = false;
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            if c.is_uppercase() && word_end {
+                key.push('_');
+            }
+            key.extend(c.to_lowercase());
+            word_end = c.is_lowercase() || c.is_numeric();
+        } else {
+            if !key.is_empty() && !key.ends_with('_') {
+                key.push('_');
+            }
+            word_end = false;
+        }
+    }
+    while key.ends_with('_') {
+        key.pop();
+    }
+    key
+}
+
+/// Normalized keys for `raw`, numbering blanks (`column_3`) and repeats
+/// (`amount_2`) so every column keeps its own key
+fn unique_names(raw: &[String]) -> Vec<String> {
+    let mut taken = HashSet::new();
+    raw.iter()
+        .enumerate()
+        .map(|(col, name)| {
+            let key = match normalize_header(name) {
+                key if key.is_empty() => format!("column_{}", col + 1),
+                key => key,
+            };
+            let mut unique = key.clone();
+            let mut n = 2;
+            while !taken.insert(unique.clone()) {
+                unique = format!("{}_{}", key, n);
+                n += 1;
+            }
+            unique
+        })
+        .collect()
+}
+
+/// Rows below a detected header, as records keyed by the header names
+///
+/// Returned by [`StreamingReader::rows_with_headers`]. Every record has a
+/// key for each header column, with `CellValue::Empty` for missing cells;
+/// cells right of the header are dropped. Blank rows are skipped.
+///
+/// [`StreamingReader::rows_with_headers`]: crate::streaming_reader::StreamingReader::rows_with_headers
+pub struct HeaderRowIterator<'a> {
+    rows: RowIterator<'a>,
+    header: DetectedHeader,
+    header_row: u32,
+    /// Rows read while looking for the header, below it
+    pending: VecDeque<(u32, Vec<CellValue>)>,
+    row_number: u32,
+}
+
+impl<'a> HeaderRowIterator<'a> {
+    pub(crate) fn new(
+        mut rows: RowIterator<'a>,
+        detector: &HeaderDetector,
+        sheet_name: &str,
+    ) -> Result<Self> {
+        let mut scanned = Vec::new();
+        let mut row_numbers = Vec::new();
+        while scanned.len() < detector.scan_rows {
+            let Some(row) = rows.next() else {
+                break;
+            };
+            scanned.push(row?);
+            row_numbers.push(rows.row_number());
+        }
+
+        let header = detector.detect(&scanned).ok_or_else(|| {
+            ExcelError::ReadError(format!(
+                "No header row found in the first {} rows of sheet '{}'",
+                detector.scan_rows, sheet_name
+            ))
+        })?;
+        let header_row = row_numbers[header.index];
+        let pending = row_numbers
+            .into_iter()
+            .zip(scanned)
+            .skip(header.index + 1)
+            .collect();
+        Ok(HeaderRowIterator {
+            rows,
+            header,
+            header_row,
+            pending,
+            row_number: header_row,
+        })
+    }
+
+    /// The header the records are keyed by
+    pub fn header(&self) -> &DetectedHeader {
+        &self.header
+    }
+
+    /// 1-based sheet row of the header
+    pub fn header_row(&self) -> u32 {
+        self.header_row
+    }
+
+    /// 1-based sheet row of the last record returned
+    pub fn row_number(&self) -> u32 {
+        self.row_number
+    }
+
+    fn next_row(&mut self) -> Option<Result<(u32, Vec<CellValue>)>> {
+        if let Some(row) = self.pending.pop_front() {
+            return Some(Ok(row));
+        }
+        Some(
+            self.rows
+                .next()?
+                .map(|cells| (self.rows.row_number(), cells)),
+        )
+    }
+}
+
+impl Iterator for HeaderRowIterator<'_> {
+    type Item = Result<HashMap<String, CellValue>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (row_number, cells) = match self.next_row()? {
+                Ok(row) => row,
+                Err(e) => return Some(Err(e)),
+            };
+            if is_blank_row(&cells) {
+                continue;
+            }
+
+            self.row_number = row_number;
+            let mut cells = cells.into_iter();
+            let record = self
+                .header
+                .names
+                .iter()
+                .map(|name| (name.clone(), cells.next().unwrap_or(CellValue::Empty)))
+                .collect();
+            return Some(Ok(record));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming_reader::StreamingReader;
+    use crate::ExcelWriter;
+    use tempfile::NamedTempFile;
+
+    fn text(s: &str) -> CellValue {
+        CellValue::String(s.to_string())
+    }
+
+    #[test]
+    fn test_detect_skips_titles_and_data() {
+        let rows = vec![
+            vec![text("Quarterly report")],
+            vec![text("Region:"), text("North")],
+            vec![
+                text("Customer Name"),
+                text("amountDue"),
+                CellValue::Empty,
+                text("Amount Due"),
+            ],
+            vec![text("Acme"), CellValue::Float(12.5), CellValue::Empty],
+        ];
+        let header = HeaderDetector::default().detect(&rows).unwrap();
+        assert_eq!(header.index, 2);
+        assert_eq!(header.raw[1], "amountDue");
+        assert_eq!(
+            header.names,
+            ["customer_name", "amount_due", "column_3", "amount_due_2"]
+        );
+
+        // Nothing qualifies within the scanned rows
+        let detector = HeaderDetector {
+            scan_rows: 1,
+            ..HeaderDetector::default()
+        };
+        assert_eq!(detector.detect(&rows), None);
+        assert_eq!(HeaderDetector::default().detect(&[]), None);
+    }
+
+    #[test]
+    fn test_rows_with_headers() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_row(["Sales 2024"]).unwrap();
+        writer
+            .write_row_at(2, &[text("Region"), text("Units Sold")])
+            .unwrap();
+        writer
+            .write_row_typed(&[text("North"), CellValue::Int(120)])
+            .unwrap();
+        writer.write_row(["", ""]).unwrap();
+        writer.write_row_typed(&[text("South")]).unwrap();
+        writer.add_sheet("Numbers").unwrap();
+        writer
+            .write_row_typed(&[CellValue::Int(1), CellValue::Int(2)])
+            .unwrap();
+        writer.save().unwrap();
+
+        let mut reader = StreamingReader::open(temp.path()).unwrap();
+        let mut records = reader.rows_with_headers("Sheet1").unwrap();
+        assert_eq!(records.header_row(), 3);
+        assert_eq!(records.header().names, ["region", "units_sold"]);
+
+        let north = records.next().unwrap().unwrap();
+        assert_eq!(north["region"], text("North"));
+        assert_eq!(north["units_sold"], CellValue::Int(120));
+        assert_eq!(records.row_number(), 4);
+        let south = records.next().unwrap().unwrap();
+        assert_eq!(south["units_sold"], CellValue::Empty);
+        assert_eq!(records.row_number(), 6);
+        assert!(records.next().is_none());
+        drop(records);
+
+        let err = reader.rows_with_headers("Numbers").err().unwrap();
+        assert!(err.to_string().contains("No header row found"));
+    }
+}
@@ -57,11 +57,60 @@
 //!     ).into_response()
 //! }
 //! ```
+//!
+//! # Chunked streaming
+//!
+//! [`HttpCsvWriter`] holds the whole file until `finish()`. For large exports
+//! [`HttpCsvStreamWriter`] writes rows to any [`Write`] (a response body, a
+//! socket, a `BufWriter<File>`) as they are produced, optionally gzipped for
+//! `Content-Encoding: gzip`.
+#![cfg_attr(
+    feature = "cloud-http",
+    doc = "With the `cloud-http` feature, [`HttpCsvStreamWriter::channel`] feeds a stream of `Bytes` instead:"
+)]
+#![cfg_attr(
+    not(feature = "cloud-http"),
+    doc = "With the `cloud-http` feature, `HttpCsvStreamWriter::channel` feeds a stream of `Bytes` instead:"
+)]
+//!
+//! ```ignore
+//! use excelstream::HttpCsvStreamWriter;
+//! use axum::{body::Body, http::header, response::IntoResponse};
+//!
+//! async fn download_csv() -> impl IntoResponse {
+//!     let (mut writer, stream) = HttpCsvStreamWriter::channel_gzip(6);
+//!     let encoding = writer.content_encoding().unwrap_or("identity");
+//!
+//!     // Row writes block while the client catches up
+//!     tokio::task::spawn_blocking(move || {
+//!         writer.write_row(["ID", "Name"])?;
+//!         for i in 0..1_000_000 {
+//!             writer.write_row_display([i, i * 2])?;
+//!         }
+//!         writer.finish().map(|_| ())
+//!     });
+//!
+//!     (
+//!         [
+//!             (header::CONTENT_TYPE, "text/csv"),
+//!             (header::CONTENT_ENCODING, encoding),
+//!         ],
+//!         Body::from_stream(stream),
+//!     )
+//! }
+//! ```
 
 use crate::csv::CsvEncoder;
 use crate::error::{ExcelError, Result};
 use crate::fast_writer::StreamingZipWriter;
 use crate::types::CellValue;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fmt::Display;
+use std::io::Write;
+
+/// Default number of bytes collected before they are written out (64 KB)
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
 
 /// In-memory buffer that implements Write + Seek traits
 struct MemoryBuffer {
@@ -151,6 +200,7 @@ pub struct HttpCsvWriter {
     // State
     row_count: u64,
     buffer: Vec<u8>,
+    scratch: String,
     finished: bool,
 
     // Configuration
@@ -180,6 +230,7 @@ impl HttpCsvWriter {
             direct_buffer: Some(MemoryBuffer::new()),
             row_count: 0,
             buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
             finished: false,
             delimiter: b',',
             quote_char: b'"',
@@ -228,6 +279,7 @@ impl HttpCsvWriter {
             direct_buffer: None,
             row_count: 0,
             buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
             finished: false,
             delimiter: b',',
             quote_char: b'"',
@@ -264,29 +316,62 @@ impl HttpCsvWriter {
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
-        if self.finished {
-            return Err(ExcelError::WriteError(
-                "Writer already finished".to_string(),
-            ));
-        }
+        self.check_not_finished()?;
 
         // Reuse buffer
         self.buffer.clear();
 
         // Encode row
         let encoder = CsvEncoder::new(self.delimiter, self.quote_char);
-        let fields: Vec<String> = data.into_iter().map(|s| s.as_ref().to_string()).collect();
-        let refs: Vec<&str> = fields.iter().map(|s| s.as_str()).collect();
+        encoder.encode_fields(data, &mut self.buffer);
+        self.buffer.extend_from_slice(self.line_ending);
+
+        self.flush_row()
+    }
 
-        encoder.encode_row(&refs, &mut self.buffer);
+    /// Write a row of any `Display` values without building a `Vec<String>`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use excelstream::HttpCsvWriter;
+    ///
+    /// let mut writer = HttpCsvWriter::new();
+    /// writer.write_row_display([1, 2, 3])?;
+    /// writer.write_row_display([0.5, 1.25])?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn write_row_display<I>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.check_not_finished()?;
+
+        self.buffer.clear();
+
+        let encoder = CsvEncoder::new(self.delimiter, self.quote_char);
+        encoder.encode_display(data, &mut self.scratch, &mut self.buffer);
         self.buffer.extend_from_slice(self.line_ending);
 
-        // Write to output
+        self.flush_row()
+    }
+
+    fn check_not_finished(&self) -> Result<()> {
+        if self.finished {
+            return Err(ExcelError::WriteError(
+                "Writer already finished".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Write the encoded row in `self.buffer` to the output
+    fn flush_row(&mut self) -> Result<()> {
         if let Some(ref mut zip) = self.zip_writer {
             zip.write_data(&self.buffer)
                 .map_err(|e| ExcelError::WriteError(format!("Failed to write to ZIP: {}", e)))?;
         } else if let Some(ref mut buffer) = self.direct_buffer {
-            use std::io::Write;
             buffer
                 .write_all(&self.buffer)
                 .map_err(|e| ExcelError::WriteError(format!("Failed to write to buffer: {}", e)))?;
@@ -312,9 +397,7 @@ impl HttpCsvWriter {
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     pub fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
-        let strings: Vec<String> = cells.iter().map(|c| c.as_string()).collect();
-        let refs: Vec<&str> = strings.iter().map(|s| s.as_str()).collect();
-        self.write_row(refs)
+        self.write_row_display(cells)
     }
 
     /// Get the number of rows written
@@ -367,6 +450,305 @@ impl Default for HttpCsvWriter {
     }
 }
 
+/// Streaming CSV writer for HTTP responses
+///
+/// Rows are encoded into a buffer that is written to `W` whenever it reaches
+/// the chunk size, so memory stays bounded however many rows are sent. With
+/// [`gzip`](Self::gzip) the output is a plain gzip stream for
+/// `Content-Encoding: gzip`, which browsers and HTTP clients decode
+/// transparently, rather than a ZIP archive.
+///
+/// Buffered rows are lost if the writer is dropped; call
+/// [`finish`](Self::finish) to write them and end the gzip stream.
+///
+/// # Example
+///
+/// ```no_run
+/// use excelstream::HttpCsvStreamWriter;
+/// use std::net::TcpStream;
+///
+/// let socket = TcpStream::connect("127.0.0.1:8080")?;
+/// let mut writer = HttpCsvStreamWriter::gzip(socket, 6);
+/// writer.write_row(["ID", "Name"])?;
+/// writer.write_row(["1", "Alice"])?;
+/// writer.finish()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct HttpCsvStreamWriter<W: Write> {
+    output: Option<StreamOutput<W>>,
+
+    // State
+    row_count: u64,
+    buffer: Vec<u8>,
+    scratch: String,
+
+    // Configuration
+    chunk_size: usize,
+    delimiter: u8,
+    quote_char: u8,
+    line_ending: &'static [u8],
+}
+
+/// Destination of [`HttpCsvStreamWriter`], gzipped or not
+enum StreamOutput<W: Write> {
+    Plain(W),
+    Gzip(GzEncoder<W>),
+}
+
+impl<W: Write> HttpCsvStreamWriter<W> {
+    /// Stream uncompressed CSV to `writer`
+    pub fn new(writer: W) -> Self {
+        Self::with_output(StreamOutput::Plain(writer))
+    }
+
+    /// Stream gzip-compressed CSV to `writer`
+    ///
+    /// `level` ranges from 0 (store) to 9 (smallest); 6 is a good balance.
+    pub fn gzip(writer: W, level: u32) -> Self {
+        let encoder = GzEncoder::new(writer, Compression::new(level.min(9)));
+        Self::with_output(StreamOutput::Gzip(encoder))
+    }
+
+    fn with_output(output: StreamOutput<W>) -> Self {
+        Self {
+            output: Some(output),
+            row_count: 0,
+            buffer: Vec::with_capacity(DEFAULT_CHUNK_SIZE),
+            scratch: String::new(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            delimiter: b',',
+            quote_char: b'"',
+            line_ending: b"\n",
+        }
+    }
+
+    /// Set how many bytes are collected before writing (builder pattern)
+    ///
+    /// Smaller chunks reach the client sooner, larger ones mean fewer writes.
+    pub fn chunk_size(mut self, bytes: usize) -> Self {
+        self.chunk_size = bytes.max(1);
+        self
+    }
+
+    /// Set custom delimiter (builder pattern)
+    pub fn delimiter(mut self, delim: u8) -> Self {
+        self.delimiter = delim;
+        self
+    }
+
+    /// Set custom quote character (builder pattern)
+    pub fn quote_char(mut self, quote: u8) -> Self {
+        self.quote_char = quote;
+        self
+    }
+
+    /// Value for the `Content-Encoding` header: `Some("gzip")` for
+    /// [`gzip`](Self::gzip) writers, `None` otherwise
+    pub fn content_encoding(&self) -> Option<&'static str> {
+        match self.output {
+            Some(StreamOutput::Gzip(_)) => Some("gzip"),
+            _ => None,
+        }
+    }
+
+    /// Write a row of strings
+    pub fn write_row<I, S>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let encoder = CsvEncoder::new(self.delimiter, self.quote_char);
+        encoder.encode_fields(data, &mut self.buffer);
+        self.buffer.extend_from_slice(self.line_ending);
+        self.end_row()
+    }
+
+    /// Write a row of any `Display` values without building a `Vec<String>`
+    pub fn write_row_display<I>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        let encoder = CsvEncoder::new(self.delimiter, self.quote_char);
+        encoder.encode_display(data, &mut self.scratch, &mut self.buffer);
+        self.buffer.extend_from_slice(self.line_ending);
+        self.end_row()
+    }
+
+    /// Write a row of typed values
+    pub fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
+        self.write_row_display(cells)
+    }
+
+    fn end_row(&mut self) -> Result<()> {
+        self.row_count += 1;
+        if self.buffer.len() >= self.chunk_size {
+            self.write_buffer()?;
+        }
+        Ok(())
+    }
+
+    /// Write the buffered rows to the output
+    fn write_buffer(&mut self) -> Result<()> {
+        let output = self
+            .output
+            .as_mut()
+            .ok_or_else(|| ExcelError::InvalidState("Writer already finished".to_string()))?;
+        let result = match output {
+            StreamOutput::Plain(writer) => writer.write_all(&self.buffer),
+            StreamOutput::Gzip(encoder) => encoder.write_all(&self.buffer),
+        };
+        result.map_err(|e| ExcelError::WriteError(format!("Failed to write CSV chunk: {}", e)))?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Send everything written so far to the client
+    ///
+    /// Gzip output is flushed at a block boundary, so the client can decode
+    /// every row up to here. Flushing often makes the gzip stream larger.
+    pub fn flush(&mut self) -> Result<()> {
+        self.write_buffer()?;
+        let result = match self.output.as_mut() {
+            Some(StreamOutput::Plain(writer)) => writer.flush(),
+            Some(StreamOutput::Gzip(encoder)) => encoder.flush(),
+            None => Ok(()),
+        };
+        result.map_err(|e| ExcelError::WriteError(format!("Failed to flush CSV stream: {}", e)))
+    }
+
+    /// Get the number of rows written
+    pub fn row_count(&self) -> u64 {
+        self.row_count
+    }
+
+    /// Write the remaining rows, end the gzip stream and return the writer
+    pub fn finish(mut self) -> Result<W> {
+        self.write_buffer()?;
+        let output = self
+            .output
+            .take()
+            .ok_or_else(|| ExcelError::InvalidState("Writer already finished".to_string()))?;
+        let mut writer = match output {
+            StreamOutput::Plain(writer) => writer,
+            StreamOutput::Gzip(encoder) => encoder
+                .finish()
+                .map_err(|e| ExcelError::WriteError(format!("Failed to finish gzip: {}", e)))?,
+        };
+        writer
+            .flush()
+            .map_err(|e| ExcelError::WriteError(format!("Failed to flush CSV stream: {}", e)))?;
+        Ok(writer)
+    }
+}
+
+#[cfg(feature = "cloud-http")]
+pub use channel::{ChunkSender, CsvByteStream};
+
+#[cfg(feature = "cloud-http")]
+mod channel {
+    use super::{HttpCsvStreamWriter, StreamOutput};
+    use crate::error::{ExcelError, Result};
+    use bytes::Bytes;
+    use futures_core::Stream;
+    use std::io::Write;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::sync::mpsc;
+
+    /// Number of chunks that may wait in the channel before the writer blocks
+    const CHANNEL_CAPACITY: usize = 4;
+
+    /// [`Write`] end of [`HttpCsvStreamWriter::channel`]; every write becomes
+    /// one body chunk
+    pub struct ChunkSender {
+        tx: mpsc::Sender<Result<Bytes>>,
+    }
+
+    impl Write for ChunkSender {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            self.tx
+                .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+                .map_err(|_| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::BrokenPipe,
+                        "HTTP response body was dropped",
+                    )
+                })?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Body stream fed by a channel [`HttpCsvStreamWriter`]
+    ///
+    /// Implements `Stream<Item = Result<Bytes>>`, so it can be passed straight
+    /// to `axum::body::Body::from_stream`. It ends when the writer finishes or
+    /// is dropped.
+    pub struct CsvByteStream {
+        rx: mpsc::Receiver<Result<Bytes>>,
+    }
+
+    impl CsvByteStream {
+        /// Receive the next chunk, or `None` once the file is complete
+        pub async fn next_chunk(&mut self) -> Option<Result<Bytes>> {
+            self.rx.recv().await
+        }
+    }
+
+    impl Stream for CsvByteStream {
+        type Item = Result<Bytes>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.rx.poll_recv(cx)
+        }
+    }
+
+    impl HttpCsvStreamWriter<ChunkSender> {
+        /// Create a writer and the byte stream it feeds
+        ///
+        /// Row writes block while the channel is full, so use the writer
+        /// from `tokio::task::spawn_blocking` or a plain thread, never from
+        /// async code directly.
+        pub fn channel() -> (Self, CsvByteStream) {
+            let (sender, stream) = Self::sender();
+            (Self::new(sender), stream)
+        }
+
+        /// Like [`channel`](Self::channel), gzip-compressed
+        pub fn channel_gzip(level: u32) -> (Self, CsvByteStream) {
+            let (sender, stream) = Self::sender();
+            (Self::gzip(sender, level), stream)
+        }
+
+        fn sender() -> (ChunkSender, CsvByteStream) {
+            let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+            (ChunkSender { tx }, CsvByteStream { rx })
+        }
+
+        /// Stop writing and end the stream with `error`
+        ///
+        /// Web frameworks abort the response when the body stream yields an
+        /// error, so the client sees a failed download rather than a
+        /// truncated file.
+        pub fn abort(mut self, error: ExcelError) {
+            let tx = match self.output.take() {
+                Some(StreamOutput::Plain(sender)) => sender.tx,
+                Some(StreamOutput::Gzip(encoder)) => encoder.get_ref().tx.clone(),
+                None => return,
+            };
+            // The receiver may already be gone; nothing left to report to then
+            let _ = tx.blocking_send(Err(error));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -423,4 +805,61 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_http_csv_display() -> Result<()> {
+        let mut writer = HttpCsvWriter::new();
+        writer.write_row_display([1, 2, 3])?;
+        writer.write_row_display([0.5, 1.25])?;
+
+        let bytes = writer.finish()?;
+        assert_eq!(String::from_utf8(bytes).unwrap(), "1,2,3\n0.5,1.25\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_csv_stream_chunks() -> Result<()> {
+        let mut writer = HttpCsvStreamWriter::new(Vec::new()).chunk_size(16);
+        assert_eq!(writer.content_encoding(), None);
+        writer.write_row(["ID", "Name"])?;
+        // Still buffered: below the chunk size
+        assert!(writer.output.as_ref().is_some_and(|output| match output {
+            StreamOutput::Plain(bytes) => bytes.is_empty(),
+            StreamOutput::Gzip(_) => false,
+        }));
+        writer.write_row(["1", "Alice, Jr."])?;
+        writer.write_row_display([2, 3])?;
+        assert_eq!(writer.row_count(), 3);
+
+        let bytes = writer.finish()?;
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "ID,Name\n1,\"Alice, Jr.\"\n2,3\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_csv_stream_gzip() -> Result<()> {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let mut writer = HttpCsvStreamWriter::gzip(Vec::new(), 6);
+        assert_eq!(writer.content_encoding(), Some("gzip"));
+        let mut expected = String::new();
+        for i in 0..20_000 {
+            writer.write_row_display([i, i * 2])?;
+            expected.push_str(&format!("{},{}\n", i, i * 2));
+        }
+        writer.flush()?;
+        let bytes = writer.finish()?;
+
+        // A plain gzip member, not a ZIP archive
+        assert_eq!(&bytes[..2], &[0x1f, 0x8b]);
+        let mut decoded = String::new();
+        GzDecoder::new(&bytes[..]).read_to_string(&mut decoded)?;
+        assert_eq!(decoded, expected);
+        Ok(())
+    }
 }
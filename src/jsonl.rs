@@ -0,0 +1,510 @@
+//! JSON Lines (NDJSON) reading and writing
+//!
+//! [`JsonlReader`] flattens each object into dotted column names
+//! (`{"user": {"id": 1}}` becomes a `user.id` column) so log files can be fed
+//! straight into [`ExcelWriter`](crate::ExcelWriter) or
+//! [`CsvWriter`](crate::CsvWriter). [`JsonlWriter`] turns typed rows back into
+//! one JSON object per line.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use excelstream::jsonl::JsonlReader;
+//! use excelstream::ExcelWriter;
+//!
+//! let mut reader = JsonlReader::open("events.jsonl")?.max_depth(2);
+//! let mut writer = ExcelWriter::new("events.xlsx")?;
+//!
+//! writer.write_header_bold(reader.columns()?.to_vec())?;
+//! while let Some(row) = reader.read_row()? {
+//!     writer.write_row_typed(&row)?;
+//! }
+//! writer.save()?;
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+
+use crate::convert::{format_iso, serial_to_datetime};
+use crate::error::{ExcelError, Result};
+use crate::row_io::{RowSink, RowSource};
+use crate::types::CellValue;
+use indexmap::{IndexMap, IndexSet};
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Streaming JSON Lines reader with nested-object flattening
+///
+/// Columns are the flattened keys seen in the first
+/// [`sample_rows`](Self::sample_rows) records, in order of first appearance.
+/// Keys that only show up later are not part of [`read_row`](Self::read_row)
+/// output; use [`read_record`](Self::read_record) to see every key.
+///
+/// Values map to cells as: `null` → `Empty`, booleans → `Bool`, integers →
+/// `Int` (`BigInt` beyond 2^53), other numbers → `Float`, strings → `String`.
+/// Arrays, and objects nested deeper than [`max_depth`](Self::max_depth), are
+/// kept as compact JSON text. Blank lines are skipped.
+pub struct JsonlReader {
+    lines: std::io::Lines<BufReader<File>>,
+    line_number: u64,
+    max_depth: usize,
+    separator: String,
+    sample_rows: usize,
+    columns: Option<Vec<String>>,
+    buffered: VecDeque<IndexMap<String, CellValue>>,
+}
+
+impl JsonlReader {
+    /// Open a JSON Lines file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path.as_ref()).map_err(|e| {
+            ExcelError::ReadError(format!("Failed to open {}: {}", path.as_ref().display(), e))
+        })?;
+        Ok(JsonlReader {
+            lines: BufReader::new(file).lines(),
+            line_number: 0,
+            max_depth: usize::MAX,
+            separator: ".".to_string(),
+            sample_rows: 1000,
+            columns: None,
+            buffered: VecDeque::new(),
+        })
+    }
+
+    /// Flatten nested objects at most `depth` levels deep (default: unlimited)
+    ///
+    /// With `max_depth(1)`, `{"user": {"id": 1}}` yields a `user` column
+    /// holding `{"id":1}` as text.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth.max(1);
+        self
+    }
+
+    /// Separator between nested key names (default: `.`)
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Number of records scanned to discover the columns (default: 1000)
+    pub fn sample_rows(mut self, rows: usize) -> Self {
+        self.sample_rows = rows.max(1);
+        self
+    }
+
+    /// Column names, scanning the sample window on first call
+    pub fn columns(&mut self) -> Result<&[String]> {
+        if self.columns.is_none() {
+            let mut columns: IndexSet<String> = IndexSet::new();
+            while self.buffered.len() < self.sample_rows {
+                match self.next_record()? {
+                    Some(record) => {
+                        for key in record.keys() {
+                            if !columns.contains(key) {
+                                columns.insert(key.clone());
+                            }
+                        }
+                        self.buffered.push_back(record);
+                    }
+                    None => break,
+                }
+            }
+            self.columns = Some(columns.into_iter().collect());
+        }
+        Ok(self.columns.as_deref().unwrap_or_default())
+    }
+
+    /// Read the next record as a row aligned with [`columns`](Self::columns)
+    ///
+    /// Missing keys are `Empty`.
+    pub fn read_row(&mut self) -> Result<Option<Vec<CellValue>>> {
+        self.columns()?;
+        let Some(mut record) = self.read_record()? else {
+            return Ok(None);
+        };
+        let columns = self.columns.as_deref().unwrap_or_default();
+        Ok(Some(
+            columns
+                .iter()
+                .map(|column| record.swap_remove(column).unwrap_or(CellValue::Empty))
+                .collect(),
+        ))
+    }
+
+    /// Read the next record with all of its flattened keys
+    pub fn read_record(&mut self) -> Result<Option<IndexMap<String, CellValue>>> {
+        match self.buffered.pop_front() {
+            Some(record) => Ok(Some(record)),
+            None => self.next_record(),
+        }
+    }
+
+    /// Number of lines read so far
+    pub fn line_number(&self) -> u64 {
+        self.line_number
+    }
+
+    fn next_record(&mut self) -> Result<Option<IndexMap<String, CellValue>>> {
+        for line in self.lines.by_ref() {
+            let line = line?;
+            self.line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let value: Value = serde_json::from_str(&line).map_err(|e| {
+                ExcelError::ReadError(format!("Line {}: invalid JSON: {}", self.line_number, e))
+            })?;
+            let Value::Object(object) = value else {
+                return Err(ExcelError::ReadError(format!(
+                    "Line {}: expected a JSON object",
+                    self.line_number
+                )));
+            };
+
+            let mut record = IndexMap::new();
+            flatten(&object, "", 1, self.max_depth, &self.separator, &mut record);
+            return Ok(Some(record));
+        }
+        Ok(None)
+    }
+}
+
+impl Iterator for JsonlReader {
+    type Item = Result<Vec<CellValue>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_row().transpose()
+    }
+}
+
+/// The schema is [`columns`](JsonlReader::columns)
+impl RowSource for JsonlReader {
+    fn schema(&mut self) -> Result<Option<Vec<String>>> {
+        Ok(Some(self.columns()?.to_vec()))
+    }
+
+    fn rows(&mut self) -> Result<impl Iterator<Item = Result<Vec<CellValue>>> + '_> {
+        Ok(self.by_ref())
+    }
+}
+
+fn flatten(
+    object: &Map<String, Value>,
+    prefix: &str,
+    depth: usize,
+    max_depth: usize,
+    separator: &str,
+    record: &mut IndexMap<String, CellValue>,
+) {
+    for (key, value) in object {
+        let name = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}{}{}", prefix, separator, key)
+        };
+        match value {
+            Value::Object(nested) if depth < max_depth && !nested.is_empty() => {
+                flatten(nested, &name, depth + 1, max_depth, separator, record);
+            }
+            value => {
+                record.insert(name, json_to_cell(value));
+            }
+        }
+    }
+}
+
+fn json_to_cell(value: &Value) -> CellValue {
+    match value {
+        Value::Null => CellValue::Empty,
+        Value::Bool(b) => CellValue::Bool(*b),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                CellValue::integer(i as i128)
+            } else if let Some(u) = n.as_u64() {
+                CellValue::integer(u as i128)
+            } else {
+                CellValue::Float(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        Value::String(s) => CellValue::String(s.clone()),
+        Value::Array(_) | Value::Object(_) => CellValue::String(value.to_string()),
+    }
+}
+
+/// Streaming JSON Lines writer
+///
+/// Each row becomes one object keyed by the column names. With
+/// [`nest`](Self::nest), dotted names are expanded back into nested objects,
+/// undoing [`JsonlReader`]'s flattening.
+///
+/// Cells map to JSON as: `Empty` → `null`, `Int` / `Float` → numbers
+/// (non-finite floats → `null`), `Bool` → booleans, `DateTime` → ISO 8601
+/// strings, and everything else (including `BigInt` and `Decimal`, to keep
+/// every digit) → strings.
+///
+/// # Example
+///
+/// ```no_run
+/// use excelstream::jsonl::JsonlWriter;
+/// use excelstream::types::CellValue;
+///
+/// let mut writer = JsonlWriter::new("out.jsonl", ["user.id", "user.name", "active"])?.nest(true);
+/// writer.write_row(&[
+///     CellValue::Int(1),
+///     CellValue::String("Alice".to_string()),
+///     CellValue::Bool(true),
+/// ])?;
+/// // {"user":{"id":1,"name":"Alice"},"active":true}
+/// writer.save()?;
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+pub struct JsonlWriter {
+    writer: BufWriter<File>,
+    columns: Vec<String>,
+    separator: Option<String>,
+    row_count: u64,
+}
+
+impl JsonlWriter {
+    /// Create a writer whose rows use `columns` as keys
+    pub fn new<P, I, S>(path: P, columns: I) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let file = File::create(path.as_ref())
+            .map_err(|e| ExcelError::WriteError(format!("Failed to create JSONL file: {}", e)))?;
+        Ok(JsonlWriter {
+            writer: BufWriter::new(file),
+            columns: columns
+                .into_iter()
+                .map(|c| c.as_ref().to_string())
+                .collect(),
+            separator: None,
+            row_count: 0,
+        })
+    }
+
+    /// Expand `.`-separated column names into nested objects (default: false)
+    pub fn nest(mut self, nest: bool) -> Self {
+        self.separator = nest.then(|| ".".to_string());
+        self
+    }
+
+    /// Expand column names split on `separator` into nested objects
+    pub fn nest_with(mut self, separator: &str) -> Self {
+        self.separator = Some(separator.to_string());
+        self
+    }
+
+    /// Write one row; cells beyond the column count are an error, missing
+    /// trailing cells are written as `null`
+    pub fn write_row(&mut self, cells: &[CellValue]) -> Result<()> {
+        if cells.len() > self.columns.len() {
+            return Err(ExcelError::WriteError(format!(
+                "Row has {} cells but only {} columns",
+                cells.len(),
+                self.columns.len()
+            )));
+        }
+
+        let mut object = Map::new();
+        for (i, column) in self.columns.iter().enumerate() {
+            let value = cells.get(i).map_or(Value::Null, cell_to_json);
+            match &self.separator {
+                Some(separator) => insert_nested(&mut object, column, separator, value),
+                None => {
+                    object.insert(column.clone(), value);
+                }
+            }
+        }
+
+        serde_json::to_writer(&mut self.writer, &object)
+            .map_err(|e| ExcelError::WriteError(e.to_string()))?;
+        self.writer.write_all(b"\n")?;
+        self.row_count += 1;
+        Ok(())
+    }
+
+    /// Number of rows written so far
+    pub fn row_count(&self) -> u64 {
+        self.row_count
+    }
+
+    /// Flush and close the file
+    pub fn save(mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// A writer created without columns takes the schema's names as keys;
+/// otherwise the schema is ignored
+impl RowSink for JsonlWriter {
+    fn write_schema(&mut self, names: &[String]) -> Result<()> {
+        if self.columns.is_empty() && self.row_count == 0 {
+            self.columns = names.to_vec();
+        }
+        Ok(())
+    }
+
+    fn write_cells(&mut self, cells: &[CellValue]) -> Result<()> {
+        self.write_row(cells)
+    }
+
+    fn finish(self) -> Result<()> {
+        self.save()
+    }
+}
+
+fn cell_to_json(cell: &CellValue) -> Value {
+    match cell {
+        CellValue::Empty => Value::Null,
+        CellValue::Int(i) => Value::from(*i),
+        CellValue::Float(f) => serde_json::Number::from_f64(*f).map_or(Value::Null, Value::Number),
+        CellValue::Bool(b) => Value::Bool(*b),
+        CellValue::DateTime(serial) => match serial_to_datetime(*serial) {
+            Some(datetime) => Value::String(format_iso(datetime)),
+            None => Value::Null,
+        },
+        cell => Value::String(cell.as_string()),
+    }
+}
+
+/// Insert `value` under `path` split on `separator`, creating objects on
+/// the way; a path that runs into a non-object value is kept as a flat key
+fn insert_nested(object: &mut Map<String, Value>, path: &str, separator: &str, value: Value) {
+    let Some((head, rest)) = path.split_once(separator) else {
+        object.insert(path.to_string(), value);
+        return;
+    };
+    match object
+        .entry(head.to_string())
+        .or_insert_with(|| Value::Object(Map::new()))
+    {
+        Value::Object(nested) => insert_nested(nested, rest, separator, value),
+        _ => {
+            object.insert(path.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_flatten_and_columns() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("events.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"id": 1, "user": {"name": "Alice", "geo": {"cc": "DE"}}, "tags": ["a", "b"]}"#,
+                "\n\n",
+                r#"{"id": 2, "user": {"name": "Bob"}, "score": 9.5, "ok": true, "big": 18446744073709551615}"#,
+                "\n",
+                r#"{"id": 3, "late": "ignored by read_row"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let mut reader = JsonlReader::open(&path).unwrap().sample_rows(2);
+        assert_eq!(
+            reader.columns().unwrap(),
+            [
+                "id",
+                "user.name",
+                "user.geo.cc",
+                "tags",
+                "score",
+                "ok",
+                "big"
+            ]
+        );
+        let rows: Vec<_> = reader.by_ref().collect::<Result<_>>().unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[0],
+            vec![
+                CellValue::Int(1),
+                CellValue::String("Alice".to_string()),
+                CellValue::String("DE".to_string()),
+                CellValue::String(r#"["a","b"]"#.to_string()),
+                CellValue::Empty,
+                CellValue::Empty,
+                CellValue::Empty,
+            ]
+        );
+        assert_eq!(rows[1][4], CellValue::Float(9.5));
+        assert_eq!(rows[1][5], CellValue::Bool(true));
+        assert_eq!(rows[1][6], CellValue::BigInt(u64::MAX.to_string()));
+        assert_eq!(reader.line_number(), 4);
+
+        let mut reader = JsonlReader::open(&path).unwrap().max_depth(2);
+        let record = reader.read_record().unwrap().unwrap();
+        assert_eq!(
+            record.get("user.geo"),
+            Some(&CellValue::String(r#"{"cc":"DE"}"#.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_line_reports_line_number() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("bad.jsonl");
+        std::fs::write(&path, "{\"a\": 1}\n[1, 2]\n").unwrap();
+
+        let mut reader = JsonlReader::open(&path).unwrap();
+        assert!(reader.read_record().unwrap().is_some());
+        let err = reader.read_record().unwrap_err();
+        assert!(err.to_string().contains("Line 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_writer_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("out.jsonl");
+        let mut writer = JsonlWriter::new(&path, ["user.id", "user.name", "when", "amount"])
+            .unwrap()
+            .nest(true);
+        writer
+            .write_row(&[
+                CellValue::Int(7),
+                CellValue::String("Alice".to_string()),
+                CellValue::DateTime(45352.5),
+                CellValue::Float(f64::NAN),
+            ])
+            .unwrap();
+        writer.write_row(&[CellValue::Int(8)]).unwrap();
+        assert!(writer
+            .write_row(&vec![CellValue::Empty; 5])
+            .unwrap_err()
+            .to_string()
+            .contains("5 cells"));
+        assert_eq!(writer.row_count(), 2);
+        writer.save().unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            r#"{"user":{"id":7,"name":"Alice"},"when":"2024-03-01 12:00:00","amount":null}"#
+        );
+
+        let mut reader = JsonlReader::open(&path).unwrap();
+        assert_eq!(
+            reader.columns().unwrap(),
+            ["user.id", "user.name", "when", "amount"]
+        );
+        assert_eq!(
+            reader.read_row().unwrap().unwrap()[..2],
+            [CellValue::Int(7), CellValue::String("Alice".to_string())]
+        );
+    }
+}
@@ -53,7 +53,7 @@
 //! // Write with formulas
 //! writer.write_row_typed(&[
 //!     CellValue::String("Total".to_string()),
-//!     CellValue::Formula("=COUNT(B2:B10)".to_string()),
+//!     CellValue::formula("=COUNT(B2:B10)"),
 //!     CellValue::Empty,
 //! ])?;
 //!
@@ -62,9 +62,13 @@
 //! # }
 //! ```
 
+mod archive;
 pub mod error;
 pub mod fast_writer;
+mod formula;
+pub mod sheet_name;
 pub mod streaming_reader;
+mod trace;
 pub mod types;
 pub mod writer;
 
@@ -74,32 +78,126 @@ pub mod csv_reader;
 pub mod csv_writer;
 pub mod http_csv_writer;
 
+// OpenDocument spreadsheet output
+pub mod ods_writer;
+pub mod progress;
+pub mod report;
+
+// XLSX <-> CSV converters
+pub mod convert;
+
+// Reader and writer traits shared by every format
+pub mod row_io;
+
+// Several CSV/XLSX files in one ZIP archive
+pub mod bundle;
+
+// Group-by summary sheets
+pub mod summary;
+
+// Reports with group headers, subtotals and outline levels
+pub mod grouped_report;
+
+// Concatenating sheets of many workbooks
+pub mod merge;
+
+// Exports that resume from on-disk checkpoints after a crash
+pub mod checkpoint;
+
 // Cloud storage integration (optional)
 #[cfg(any(
     feature = "cloud-s3",
     feature = "cloud-gcs",
     feature = "cloud-azure",
-    feature = "cloud-http"
+    feature = "cloud-http",
+    feature = "http-writer"
 ))]
 pub mod cloud;
 
+// Password-protected XLSX (optional)
+#[cfg(feature = "encryption")]
+mod crypto;
+
+// JSON Lines support (optional)
+#[cfg(feature = "jsonl")]
+pub mod jsonl;
+
 // Parquet support (optional)
 #[cfg(feature = "parquet-support")]
 pub mod parquet;
 
+// Polars DataFrame interop (optional)
+#[cfg(feature = "polars")]
+pub mod polars;
+
+// Database query results to Excel (optional)
+#[cfg(feature = "sqlx")]
+pub mod sqlx;
+
 // Incremental append mode
 pub mod append;
 
+// Package checks before shipping generated files
+pub mod validate;
+
+// Header row detection for hand-made sheets
+pub mod headers;
+
+// Cell-by-cell workbook comparison
+pub mod diff;
+
+// Serde integration: write structs as rows (optional)
+#[cfg(feature = "serde")]
+pub mod record;
+
+// Filling workbooks designed in Excel (optional)
+#[cfg(feature = "serde")]
+pub mod template;
+
+// Reader comparison against calamine (optional)
+#[cfg(feature = "calamine-compat")]
+pub mod compat;
+
+pub use checkpoint::CheckpointWriter;
 pub use error::{ExcelError, Result};
+pub use grouped_report::{GroupedReportWriter, SubtotalFunction};
+pub use headers::{DetectedHeader, HeaderDetector};
+pub use merge::WorkbookMerger;
+pub use ods_writer::OdsWriter;
+pub use progress::{CancellationToken, WriterMetrics};
+pub use report::{Checksum, Digest, WriteReport};
+pub use row_io::{copy, RowSink, RowSource};
 pub use streaming_reader::StreamingReader as ExcelReader; // Re-export for backward compatibility
-pub use types::{Cell, CellStyle, CellValue, ProtectionOptions, Row, StyledCell};
-pub use writer::ExcelWriter;
+pub use streaming_reader::{
+    DefinedName, DetailedCell, NamedRange, ReadLimits, ReadMode, ReadOptions, ReadWarning,
+    RowsOptions, SheetMetadata, SheetProfile, SheetVisibility, WorkbookProfile,
+};
+pub use summary::SummarySheetBuilder;
+pub use types::{
+    AutofitOptions, Cell, CellRef, CellStyle, CellValue, ChartOptions, ChartType, ColumnSlice,
+    ComparisonOperator, CompressionProfile, ConditionalFormat, ConditionalStyle, DataValidation,
+    DateSystem, DocumentProperties, IconSet, Orientation, OutlineSettings, PageSetup, PaperSize,
+    PropertyValue, ProtectionOptions, Row, SheetNamePolicy, StringMode, StyledCell, TableOptions,
+    TimezonePolicy, ValidationErrorStyle,
+};
+pub use writer::{ExcelWriter, SheetWriter, WorksheetHandle};
 
 // CSV exports
+pub use bundle::ZipBundleWriter;
+pub use convert::{CsvToXlsxConverter, XlsxToCsvConverter};
 pub use csv::CompressionMethod;
+pub use csv::{FixedWidthReader, FixedWidthWriter};
 pub use csv_reader::CsvReader;
 pub use csv_writer::CsvWriter;
-pub use http_csv_writer::HttpCsvWriter;
+pub use http_csv_writer::{HttpCsvStreamWriter, HttpCsvWriter};
+
+// JSON Lines exports
+#[cfg(feature = "jsonl")]
+pub use jsonl::{JsonlReader, JsonlWriter};
+
+// Decimal type used by CellValue::Decimal
+#[cfg(feature = "decimal")]
+pub use rust_decimal::Decimal;
 
 #[cfg(test)]
 mod tests {
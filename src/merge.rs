@@ -0,0 +1,345 @@
+//! Concatenate the sheets of many workbooks into one
+//!
+//! [`WorkbookMerger`] appends the rows of every same-named sheet of its
+//! input files, in the order the files were added, into one output
+//! workbook. Inputs are read one at a time and row by row, so merging
+//! hundreds of daily exports needs no more memory than copying one.
+//!
+//! ```no_run
+//! use excelstream::WorkbookMerger;
+//!
+//! let rows = WorkbookMerger::new()
+//!     .add_files(["2024-03-01.xlsx", "2024-03-02.xlsx", "2024-03-03.xlsx"])
+//!     .source_column("source_file")
+//!     .merge("march.xlsx")?;
+//! println!("{} rows", rows);
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+
+use crate::convert::{datetime_to_serial, parse_iso_datetime};
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::UltraLowMemoryWorkbook;
+use crate::streaming_reader::StreamingReader;
+use crate::types::{CellStyle, CellValue, StyledCell};
+use std::path::{Path, PathBuf};
+
+/// Merges same-named sheets of several workbooks
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct WorkbookMerger {
+    inputs: Vec<PathBuf>,
+    has_header: bool,
+    source_column: Option<String>,
+    sheets: Vec<String>,
+    compression_level: u32,
+}
+
+impl Default for WorkbookMerger {
+    fn default() -> Self {
+        WorkbookMerger {
+            inputs: Vec::new(),
+            has_header: true,
+            source_column: None,
+            sheets: Vec::new(),
+            compression_level: 6,
+        }
+    }
+}
+
+impl WorkbookMerger {
+    /// Merger of every sheet, each starting with a header row
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the workbook at `path`
+    pub fn add_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.inputs.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Append several workbooks, in order
+    pub fn add_files<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.inputs
+            .extend(paths.into_iter().map(|path| path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Whether the first row of each sheet is a header (default: true)
+    ///
+    /// The header is written once, in bold, and must be the same in every
+    /// file that has the sheet.
+    pub fn has_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    /// Add a first column named `name` holding the file name each row came
+    /// from
+    pub fn source_column(mut self, name: &str) -> Self {
+        self.source_column = Some(name.to_string());
+        self
+    }
+
+    /// Merge only the sheet `name`; call again for more sheets
+    pub fn sheet(mut self, name: &str) -> Self {
+        self.sheets.push(name.to_string());
+        self
+    }
+
+    /// XLSX compression level 0-9 (default: 6)
+    pub fn compression_level(mut self, level: u32) -> Self {
+        self.compression_level = level;
+        self
+    }
+
+    /// Write the merged workbook to `output`, returning the number of rows
+    /// written (including headers)
+    ///
+    /// Sheets appear in the order they are first found among the inputs.
+    /// Cells are copied as [`StreamingReader`] returns them, with its ISO
+    /// date text turned back into dates; blank rows are dropped.
+    pub fn merge<P: AsRef<Path>>(&self, output: P) -> Result<u64> {
+        if self.inputs.is_empty() {
+            return Err(ExcelError::InvalidState(
+                "No input files to merge".to_string(),
+            ));
+        }
+
+        // Every input is opened up front so a bad file fails before writing
+        let mut names_per_input = Vec::with_capacity(self.inputs.len());
+        for input in &self.inputs {
+            names_per_input.push(StreamingReader::open(input)?.sheet_names());
+        }
+        let sheets = if self.sheets.is_empty() {
+            let mut sheets: Vec<String> = Vec::new();
+            for name in names_per_input.iter().flatten() {
+                if !sheets.contains(name) {
+                    sheets.push(name.clone());
+                }
+            }
+            sheets
+        } else {
+            for sheet in &self.sheets {
+                if !names_per_input.iter().any(|names| names.contains(sheet)) {
+                    return Err(ExcelError::SheetNotFound {
+                        sheet: sheet.clone(),
+                        available: names_per_input[0].join(", "),
+                    });
+                }
+            }
+            self.sheets.clone()
+        };
+
+        let mut workbook =
+            UltraLowMemoryWorkbook::with_compression(output, self.compression_level)?;
+        let mut written = 0u64;
+        let mut cells = Vec::new();
+        for sheet in &sheets {
+            workbook.add_worksheet(sheet)?;
+            // Header written so far and the file it came from
+            let mut header: Option<(Vec<String>, &Path)> = None;
+
+            for (input, names) in self.inputs.iter().zip(&names_per_input) {
+                if !names.contains(sheet) {
+                    continue;
+                }
+                let mut reader = StreamingReader::open(input)?;
+                let mut rows = reader.stream_rows(sheet)?;
+
+                if self.has_header {
+                    let Some(first) = rows.next().transpose()? else {
+                        continue;
+                    };
+                    let names = header_names(&first);
+                    match &header {
+                        None => {
+                            cells.clear();
+                            if let Some(name) = &self.source_column {
+                                cells.push(bold(name.clone()));
+                            }
+                            cells.extend(names.iter().cloned().map(bold));
+                            workbook.write_row_styled(&cells)?;
+                            written += 1;
+                            header = Some((names, input));
+                        }
+                        Some((expected, first_input)) if *expected != names => {
+                            return Err(ExcelError::InvalidFormat(format!(
+                                "Header of sheet '{}' in {} differs from the one in {}",
+                                sheet,
+                                input.display(),
+                                first_input.display()
+                            )));
+                        }
+                        Some(_) => {}
+                    }
+                }
+
+                let source = self.source_column.as_ref().map(|_| {
+                    StyledCell::new(CellValue::String(file_name(input)), CellStyle::Default)
+                });
+                for row in rows {
+                    let row = row?;
+                    cells.clear();
+                    cells.extend(source.clone());
+                    cells.extend(row.into_iter().map(output_cell));
+                    workbook.write_row_styled(&cells)?;
+                    written += 1;
+                }
+            }
+        }
+
+        workbook.close()?;
+        Ok(written)
+    }
+}
+
+/// Header text with trailing empty cells dropped
+fn header_names(row: &[CellValue]) -> Vec<String> {
+    let mut names: Vec<String> = row.iter().map(CellValue::as_string).collect();
+    while names.last().is_some_and(|name| name.is_empty()) {
+        names.pop();
+    }
+    names
+}
+
+fn bold(name: String) -> StyledCell {
+    StyledCell::new(CellValue::String(name), CellStyle::HeaderBold)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Cell to write for a value read from an input
+fn output_cell(value: CellValue) -> StyledCell {
+    if let CellValue::String(text) = &value {
+        if let Some(datetime) = parse_iso_datetime(text) {
+            let style = if text.len() == 10 {
+                CellStyle::DateDefault
+            } else {
+                CellStyle::DateTimestamp
+            };
+            return StyledCell::new(CellValue::DateTime(datetime_to_serial(datetime)), style);
+        }
+    }
+    StyledCell::new(value, CellStyle::Default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExcelWriter;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, sheets: &[(&str, &[&[&str]])]) {
+        let mut writer = ExcelWriter::new(path).unwrap();
+        for (i, (name, rows)) in sheets.iter().enumerate() {
+            // The writer starts on "Sheet1"
+            if i > 0 {
+                writer.add_sheet(name).unwrap();
+            }
+            for row in rows.iter() {
+                writer.write_row(row.iter()).unwrap();
+            }
+        }
+        writer.save().unwrap();
+    }
+
+    fn read(path: &Path, sheet: &str) -> Vec<Vec<String>> {
+        let mut reader = StreamingReader::open(path).unwrap();
+        reader
+            .rows(sheet)
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_sheets() {
+        let dir = TempDir::new().unwrap();
+        let (a, b, out) = (
+            dir.path().join("day1.xlsx"),
+            dir.path().join("day2.xlsx"),
+            dir.path().join("merged.xlsx"),
+        );
+        write(
+            &a,
+            &[
+                ("Sheet1", &[&["id", "amount"], &["1", "10"], &["2", "20"]]),
+                ("Notes", &[&["note"], &["a"]]),
+            ],
+        );
+        write(
+            &b,
+            &[
+                ("Sheet1", &[&["id", "amount"], &["3", "30"]]),
+                ("Extra", &[&["x"], &["y"]]),
+            ],
+        );
+
+        let rows = WorkbookMerger::new()
+            .add_files([&a, &b])
+            .source_column("source_file")
+            .merge(&out)
+            .unwrap();
+        assert_eq!(rows, 8);
+
+        let reader = StreamingReader::open(&out).unwrap();
+        assert_eq!(reader.sheet_names(), ["Sheet1", "Notes", "Extra"]);
+        assert_eq!(
+            read(&out, "Sheet1"),
+            [
+                vec!["source_file", "id", "amount"],
+                vec!["day1.xlsx", "1", "10"],
+                vec!["day1.xlsx", "2", "20"],
+                vec!["day2.xlsx", "3", "30"],
+            ]
+        );
+        assert_eq!(
+            read(&out, "Extra"),
+            [["source_file", "x"], ["day2.xlsx", "y"]]
+        );
+
+        // Without a header every row is data
+        WorkbookMerger::new()
+            .add_file(&a)
+            .add_file(&b)
+            .has_header(false)
+            .sheet("Sheet1")
+            .merge(&out)
+            .unwrap();
+        assert_eq!(read(&out, "Sheet1").len(), 5);
+    }
+
+    #[test]
+    fn test_merge_errors() {
+        let dir = TempDir::new().unwrap();
+        let (a, b, out) = (
+            dir.path().join("a.xlsx"),
+            dir.path().join("b.xlsx"),
+            dir.path().join("merged.xlsx"),
+        );
+        write(&a, &[("Sheet1", &[&["id", "amount"], &["1", "10"]])]);
+        write(&b, &[("Sheet1", &[&["id", "total"], &["2", "20"]])]);
+
+        let err = WorkbookMerger::new()
+            .add_files([&a, &b])
+            .merge(&out)
+            .unwrap_err();
+        assert!(err.to_string().contains("b.xlsx"), "{}", err);
+        assert!(WorkbookMerger::new().merge(&out).is_err());
+        assert!(WorkbookMerger::new()
+            .add_file(&a)
+            .sheet("Missing")
+            .merge(&out)
+            .is_err());
+    }
+}
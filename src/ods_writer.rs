@@ -0,0 +1,482 @@
+//! OpenDocument Spreadsheet (`.ods`) writing with streaming support
+//!
+//! [`OdsWriter`] mirrors the row API of
+//! [`UltraLowMemoryWorkbook`](crate::fast_writer::UltraLowMemoryWorkbook):
+//! rows are serialized straight into `content.xml` inside the ZIP, so memory
+//! use does not grow with the number of rows. Every sheet lives in that one
+//! entry, which is why all cell styles are declared up front.
+
+use crate::convert::serial_to_datetime;
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::pipeline::ZipSink;
+use crate::fast_writer::xml_writer::push_escaped;
+use crate::types::{CellStyle, CellValue, StyledCell};
+use std::fmt::Display;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+const CONTENT_HEADER: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" xmlns:number="urn:oasis:names:tc:opendocument:xmlns:datastyle:1.0" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:msoxl="http://schemas.microsoft.com/office/excel/formula" office:version="1.3">
+<office:automatic-styles>
+<number:number-style style:name="N2"><number:number number:decimal-places="0" number:min-integer-digits="1" number:grouping="true"/></number:number-style>
+<number:number-style style:name="N3"><number:number number:decimal-places="2" number:min-integer-digits="1" number:grouping="true"/></number:number-style>
+<number:currency-style style:name="N4"><number:currency-symbol>$</number:currency-symbol><number:number number:decimal-places="2" number:min-integer-digits="1" number:grouping="true"/></number:currency-style>
+<number:percentage-style style:name="N5"><number:number number:decimal-places="2" number:min-integer-digits="1"/><number:text>%</number:text></number:percentage-style>
+<number:date-style style:name="N6"><number:month number:style="long"/><number:text>/</number:text><number:day number:style="long"/><number:text>/</number:text><number:year number:style="long"/></number:date-style>
+<number:date-style style:name="N7"><number:month number:style="long"/><number:text>/</number:text><number:day number:style="long"/><number:text>/</number:text><number:year number:style="long"/><number:text> </number:text><number:hours number:style="long"/><number:text>:</number:text><number:minutes number:style="long"/><number:text>:</number:text><number:seconds number:style="long"/></number:date-style>
+<number:date-style style:name="N14"><number:month number:style="long"/><number:text>/</number:text><number:day number:style="long"/><number:text>/</number:text><number:year number:style="long"/><number:text> </number:text><number:hours number:style="long"/><number:text>:</number:text><number:minutes number:style="long"/></number:date-style>
+<style:style style:name="ce1" style:family="table-cell"><style:text-properties fo:font-weight="bold"/></style:style>
+<style:style style:name="ce2" style:family="table-cell" style:data-style-name="N2"/>
+<style:style style:name="ce3" style:family="table-cell" style:data-style-name="N3"/>
+<style:style style:name="ce4" style:family="table-cell" style:data-style-name="N4"/>
+<style:style style:name="ce5" style:family="table-cell" style:data-style-name="N5"/>
+<style:style style:name="ce6" style:family="table-cell" style:data-style-name="N6"/>
+<style:style style:name="ce7" style:family="table-cell" style:data-style-name="N7"/>
+<style:style style:name="ce8" style:family="table-cell"><style:text-properties fo:font-weight="bold"/></style:style>
+<style:style style:name="ce9" style:family="table-cell"><style:text-properties fo:font-style="italic"/></style:style>
+<style:style style:name="ce10" style:family="table-cell"><style:table-cell-properties fo:background-color="#ffff00"/></style:style>
+<style:style style:name="ce11" style:family="table-cell"><style:table-cell-properties fo:background-color="#00ff00"/></style:style>
+<style:style style:name="ce12" style:family="table-cell"><style:table-cell-properties fo:background-color="#ff0000"/></style:style>
+<style:style style:name="ce13" style:family="table-cell"><style:table-cell-properties fo:border="0.06pt solid #000000"/></style:style>
+<style:style style:name="ce14" style:family="table-cell" style:data-style-name="N14"/>
+<style:style style:name="ce15" style:family="table-cell"><style:text-properties fo:color="#0563c1" style:text-underline-style="solid" style:text-underline-width="auto" style:text-underline-color="font-color"/></style:style>
+</office:automatic-styles>
+<office:body>
+<office:spreadsheet>"##;
+
+const MIMETYPE: &[u8] = b"application/vnd.oasis.opendocument.spreadsheet";
+
+const CONTENT_FOOTER: &str = "</office:spreadsheet>\n</office:body>\n</office:document-content>";
+
+const STYLES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-styles xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" office:version="1.3">
+<office:styles>
+<style:default-style style:family="table-cell"><style:text-properties style:font-name="Calibri" fo:font-size="11pt"/></style:default-style>
+</office:styles>
+</office:document-styles>"#;
+
+const MANIFEST_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.3">
+<manifest:file-entry manifest:full-path="/" manifest:version="1.3" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/>
+<manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+<manifest:file-entry manifest:full-path="styles.xml" manifest:media-type="text/xml"/>
+<manifest:file-entry manifest:full-path="meta.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>"#;
+
+/// Streaming `.ods` writer
+///
+/// Cells keep their types: numbers, booleans and dates are written as typed
+/// ODF values, [`CellStyle`] presets map to equivalent ODF styles, and
+/// formulas are stored in Excel syntax (`msoxl:` namespace), which
+/// LibreOffice and Excel both evaluate.
+///
+/// # Examples
+///
+/// ```no_run
+/// use excelstream::ods_writer::OdsWriter;
+/// use excelstream::types::CellValue;
+///
+/// let mut writer = OdsWriter::new("report.ods")?;
+/// writer.add_worksheet("Sales")?;
+/// writer.write_header_bold(["Region", "Revenue"])?;
+/// writer.write_row_typed(&[CellValue::String("EU".to_string()), CellValue::Float(1250.5)])?;
+/// writer.close()?;
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+pub struct OdsWriter {
+    zip: Option<ZipSink<BufWriter<File>>>,
+    sheet_names: Vec<String>,
+    in_table: bool,
+    row_count: u64,
+    buffer: Vec<u8>,
+    scratch: String,
+}
+
+impl OdsWriter {
+    /// Create a new ODS file with default compression (level 6)
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_compression(path, 6)
+    }
+
+    /// Create a new ODS file with a compression level (0-9)
+    pub fn with_compression<P: AsRef<Path>>(path: P, compression_level: u32) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| {
+            ExcelError::WriteError(format!("Failed to create {}: {}", path.display(), e))
+        })?;
+        let mut zip = ZipSink::new(BufWriter::new(file), compression_level);
+
+        // ODF requires `mimetype` as the first entry, stored uncompressed
+        // without an extra field, so the type can be read at a fixed offset
+        zip.add_stored_entry("mimetype", MIMETYPE)?;
+
+        zip.start_entry("content.xml")?;
+        zip.write_data(CONTENT_HEADER.as_bytes())?;
+
+        Ok(OdsWriter {
+            zip: Some(zip),
+            sheet_names: Vec::new(),
+            in_table: false,
+            row_count: 0,
+            buffer: Vec::with_capacity(4096),
+            scratch: String::new(),
+        })
+    }
+
+    /// Start a new sheet; later rows go to it
+    pub fn add_worksheet(&mut self, name: &str) -> Result<()> {
+        if name.is_empty() {
+            return Err(ExcelError::InvalidState(
+                "Sheet name must not be empty".to_string(),
+            ));
+        }
+        if self
+            .sheet_names
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(name))
+        {
+            return Err(ExcelError::InvalidState(format!(
+                "Sheet '{}' already exists",
+                name
+            )));
+        }
+
+        self.buffer.clear();
+        if self.in_table {
+            self.buffer.extend_from_slice(b"</table:table>\n");
+        }
+        self.buffer.extend_from_slice(b"<table:table table:name=\"");
+        push_escaped(&mut self.buffer, name);
+        self.buffer
+            .extend_from_slice(b"\">\n<table:table-column/>\n");
+        self.flush_buffer()?;
+
+        self.sheet_names.push(name.to_string());
+        self.in_table = true;
+        Ok(())
+    }
+
+    /// Write a row of text cells
+    pub fn write_row<I, S>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.start_row()?;
+        for value in values {
+            self.push_string(value.as_ref(), CellStyle::Default);
+        }
+        self.end_row()
+    }
+
+    /// Write a row of `Display` values as text cells
+    pub fn write_row_display<I>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.start_row()?;
+        for value in values {
+            self.scratch.clear();
+            let _ = write!(self.scratch, "{}", value);
+            let text = std::mem::take(&mut self.scratch);
+            self.push_string(&text, CellStyle::Default);
+            self.scratch = text;
+        }
+        self.end_row()
+    }
+
+    /// Write a bold header row
+    pub fn write_header_bold<I, S>(&mut self, headers: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.start_row()?;
+        for header in headers {
+            self.push_string(header.as_ref(), CellStyle::HeaderBold);
+        }
+        self.end_row()
+    }
+
+    /// Write a row of typed cells with the default style
+    pub fn write_row_typed(&mut self, values: &[CellValue]) -> Result<()> {
+        self.start_row()?;
+        for value in values {
+            self.push_cell(value, CellStyle::Default);
+        }
+        self.end_row()
+    }
+
+    /// Write a row of styled cells
+    pub fn write_row_styled(&mut self, cells: &[StyledCell]) -> Result<()> {
+        self.start_row()?;
+        for cell in cells {
+            self.push_cell(&cell.value, cell.effective_style());
+        }
+        self.end_row()
+    }
+
+    /// Number of rows written across all sheets
+    pub fn row_count(&self) -> u64 {
+        self.row_count
+    }
+
+    /// Finish `content.xml`, write the remaining parts and close the file
+    pub fn close(mut self) -> Result<()> {
+        if self.sheet_names.is_empty() {
+            self.add_worksheet("Sheet1")?;
+        }
+
+        let mut zip = self
+            .zip
+            .take()
+            .ok_or_else(|| ExcelError::InvalidState("Writer already closed".to_string()))?;
+        zip.write_data(b"</table:table>\n")?;
+        zip.write_data(CONTENT_FOOTER.as_bytes())?;
+
+        zip.start_entry("styles.xml")?;
+        zip.write_data(STYLES_XML.as_bytes())?;
+
+        zip.start_entry("meta.xml")?;
+        let meta = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-meta xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0" office:version="1.3">
+<office:meta><meta:generator>excelstream/{}</meta:generator><meta:creation-date>{}</meta:creation-date></office:meta>
+</office:document-meta>"#,
+            env!("CARGO_PKG_VERSION"),
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S")
+        );
+        zip.write_data(meta.as_bytes())?;
+
+        zip.start_entry("META-INF/manifest.xml")?;
+        zip.write_data(MANIFEST_XML.as_bytes())?;
+
+        zip.finish()?;
+        Ok(())
+    }
+
+    fn start_row(&mut self) -> Result<()> {
+        if !self.in_table {
+            return Err(ExcelError::WriteError("No worksheet started".to_string()));
+        }
+        self.buffer.clear();
+        self.buffer.extend_from_slice(b"<table:table-row>");
+        Ok(())
+    }
+
+    fn end_row(&mut self) -> Result<()> {
+        self.buffer.extend_from_slice(b"</table:table-row>\n");
+        self.row_count += 1;
+        self.flush_buffer()
+    }
+
+    fn flush_buffer(&mut self) -> Result<()> {
+        let zip = self
+            .zip
+            .as_mut()
+            .ok_or_else(|| ExcelError::InvalidState("Writer already closed".to_string()))?;
+        zip.write_data(&self.buffer)?;
+        Ok(())
+    }
+
+    /// `<table:table-cell` with the style attribute of `style`
+    fn open_cell(&mut self, style: CellStyle) {
+        self.buffer.extend_from_slice(b"<table:table-cell");
+        if style != CellStyle::Default {
+            self.scratch.clear();
+            let _ = write!(self.scratch, " table:style-name=\"ce{}\"", style.index());
+            self.buffer.extend_from_slice(self.scratch.as_bytes());
+        }
+    }
+
+    fn push_string(&mut self, text: &str, style: CellStyle) {
+        self.open_cell(style);
+        self.buffer
+            .extend_from_slice(b" office:value-type=\"string\"><text:p>");
+        push_escaped(&mut self.buffer, text);
+        self.buffer
+            .extend_from_slice(b"</text:p></table:table-cell>");
+    }
+
+    /// Numeric cell; `value` is the lexical form written to `office:value`
+    fn push_number(&mut self, value: &str, style: CellStyle) {
+        self.open_cell(style);
+        let value_type: &[u8] = match style {
+            CellStyle::NumberPercentage => b"percentage",
+            CellStyle::NumberCurrency => b"currency",
+            _ => b"float",
+        };
+        self.buffer.extend_from_slice(b" office:value-type=\"");
+        self.buffer.extend_from_slice(value_type);
+        self.buffer.extend_from_slice(b"\" office:value=\"");
+        self.buffer.extend_from_slice(value.as_bytes());
+        self.buffer.extend_from_slice(b"\"><text:p>");
+        self.buffer.extend_from_slice(value.as_bytes());
+        self.buffer
+            .extend_from_slice(b"</text:p></table:table-cell>");
+    }
+
+    fn push_cell(&mut self, value: &CellValue, style: CellStyle) {
+        match value {
+            CellValue::Empty => {
+                self.open_cell(style);
+                self.buffer.extend_from_slice(b"/>");
+            }
+            CellValue::String(s) | CellValue::BigInt(s) | CellValue::Error(s) => {
+                self.push_string(s, style)
+            }
+            CellValue::Int(i) => {
+                let mut num = itoa::Buffer::new();
+                self.push_number(num.format(*i), style);
+            }
+            CellValue::Float(f) if f.is_finite() => self.push_number(&f.to_string(), style),
+            CellValue::Float(_) => {
+                self.open_cell(style);
+                self.buffer.extend_from_slice(b"/>");
+            }
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => self.push_number(&d.to_string(), style),
+            CellValue::Bool(b) => {
+                self.open_cell(style);
+                self.buffer.extend_from_slice(if *b {
+                    b" office:value-type=\"boolean\" office:boolean-value=\"true\"><text:p>TRUE</text:p></table:table-cell>"
+                } else {
+                    b" office:value-type=\"boolean\" office:boolean-value=\"false\"><text:p>FALSE</text:p></table:table-cell>"
+                });
+            }
+            CellValue::DateTime(serial) => match serial_to_datetime(*serial) {
+                Some(datetime) => {
+                    // Unstyled dates would show as plain values; default to a date format
+                    let style = if style == CellStyle::Default {
+                        CellStyle::DateDefault
+                    } else {
+                        style
+                    };
+                    self.open_cell(style);
+                    self.scratch.clear();
+                    let _ = write!(
+                        self.scratch,
+                        " office:value-type=\"date\" office:date-value=\"{}\"><text:p>{}</text:p></table:table-cell>",
+                        datetime.format("%Y-%m-%dT%H:%M:%S"),
+                        datetime.format("%Y-%m-%d %H:%M:%S")
+                    );
+                    self.buffer.extend_from_slice(self.scratch.as_bytes());
+                }
+                None => self.push_number(&serial.to_string(), style),
+            },
+            CellValue::Formula { expr, .. } => {
+                // ODF consumers recalculate on load, so the cached result is not stored
+                self.open_cell(style);
+                self.buffer.extend_from_slice(b" table:formula=\"msoxl:=");
+                push_escaped(&mut self.buffer, expr.trim_start_matches('='));
+                self.buffer.extend_from_slice(b"\"/>");
+            }
+            CellValue::Hyperlink { url, text } => {
+                self.open_cell(style);
+                self.buffer.extend_from_slice(
+                    b" office:value-type=\"string\"><text:p><text:a xlink:type=\"simple\" xlink:href=\"",
+                );
+                push_escaped(&mut self.buffer, url);
+                self.buffer.extend_from_slice(b"\">");
+                push_escaped(&mut self.buffer, text);
+                self.buffer
+                    .extend_from_slice(b"</text:a></text:p></table:table-cell>");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fast_writer::StreamingZipReader;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ods_package() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("out.ods");
+
+        let mut writer = OdsWriter::new(&path).unwrap();
+        writer.add_worksheet("Sales & Costs").unwrap();
+        writer.write_header_bold(["Item", "Qty", "When"]).unwrap();
+        writer
+            .write_row_styled(&[
+                StyledCell::new(
+                    CellValue::String("<Widget>".to_string()),
+                    CellStyle::Default,
+                ),
+                StyledCell::new(CellValue::Int(3), CellStyle::NumberInteger),
+                StyledCell::new(CellValue::DateTime(45352.5), CellStyle::DateTimestamp),
+            ])
+            .unwrap();
+        writer.add_worksheet("Totals").unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::formula("=SUM(1,2)"),
+                CellValue::Bool(true),
+                CellValue::Empty,
+                CellValue::hyperlink("https://example.com/?a=1&b=2", "site"),
+            ])
+            .unwrap();
+        assert!(writer.add_worksheet("totals").is_err());
+        assert_eq!(writer.row_count(), 3);
+        writer.close().unwrap();
+
+        let mut zip = StreamingZipReader::open(&path).unwrap();
+        assert_eq!(
+            zip.read_entry_by_name("mimetype").unwrap(),
+            b"application/vnd.oasis.opendocument.spreadsheet"
+        );
+        let manifest =
+            String::from_utf8(zip.read_entry_by_name("META-INF/manifest.xml").unwrap()).unwrap();
+        assert!(manifest.contains("content.xml"));
+
+        let content = String::from_utf8(zip.read_entry_by_name("content.xml").unwrap()).unwrap();
+        assert!(content.contains(r#"<table:table table:name="Sales &amp; Costs">"#));
+        assert!(content.contains("<text:p>&lt;Widget&gt;</text:p>"));
+        assert!(content.contains(
+            r#"<table:table-cell table:style-name="ce2" office:value-type="float" office:value="3">"#
+        ));
+        assert!(content.contains(r#"office:date-value="2024-03-01T12:00:00""#));
+        assert!(content.contains(r#"table:formula="msoxl:=SUM(1,2)""#));
+        assert!(content.contains(r#"office:boolean-value="true""#));
+        assert!(content.contains(r#"xlink:href="https://example.com/?a=1&amp;b=2""#));
+        assert_eq!(content.matches("</table:table>").count(), 2);
+        assert!(content.ends_with("</office:document-content>"));
+    }
+
+    #[test]
+    fn test_mimetype_is_first_and_stored() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("typed.ods");
+        let mut writer = OdsWriter::new(&path).unwrap();
+        writer.add_worksheet("Sheet1").unwrap();
+        writer.write_row(["a"]).unwrap();
+        writer.close().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        assert_eq!(u16::from_le_bytes([bytes[8], bytes[9]]), 0, "method");
+        assert_eq!(u16::from_le_bytes([bytes[28], bytes[29]]), 0, "extra field");
+        assert_eq!(&bytes[30..38], b"mimetype");
+        assert_eq!(&bytes[38..38 + MIMETYPE.len()], MIMETYPE);
+    }
+
+    #[test]
+    fn test_ods_without_sheets_gets_one() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("empty.ods");
+
+        let mut writer = OdsWriter::new(&path).unwrap();
+        assert!(writer.write_row(["no sheet yet"]).is_err());
+        writer.close().unwrap();
+
+        let mut zip = StreamingZipReader::open(&path).unwrap();
+        let content = String::from_utf8(zip.read_entry_by_name("content.xml").unwrap()).unwrap();
+        assert!(content.contains(r#"<table:table table:name="Sheet1">"#));
+    }
+}
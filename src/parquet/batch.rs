@@ -0,0 +1,261 @@
+//! Arrow `RecordBatch` → typed cell rows
+//!
+//! Each column is inspected once per batch and turned into a cell builder
+//! for its Arrow type, so rows are produced without formatting every value
+//! into a string first.
+
+use crate::error::{ExcelError, Result};
+use crate::types::{CellStyle, CellValue, StyledCell};
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+
+/// Excel serial number of 1970-01-01
+const UNIX_EPOCH_SERIAL: f64 = 25569.0;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+type CellFn<'a> = Box<dyn Fn(usize) -> StyledCell + 'a>;
+
+/// Call `write` with the cells of every row of `batch`, returning the row count
+///
+/// | Arrow type | Cell |
+/// |---|---|
+/// | `Int*`, `UInt*` | `Int` (`BigInt` beyond 2^53) |
+/// | `Float*`, `Decimal128` | `Float` (`Decimal` with the `decimal` feature) |
+/// | `Boolean` | `Bool` |
+/// | `Utf8`, `LargeUtf8`, `Utf8View` | `String` |
+/// | `Date32`, `Date64` | `DateTime` styled `DateDefault` |
+/// | `Timestamp` | `DateTime` styled `DateTimestamp`, in UTC |
+/// | anything else | `String`, formatted by Arrow |
+///
+/// Nulls and non-finite floats become empty cells.
+pub(crate) fn for_each_row<F>(batch: &RecordBatch, mut write: F) -> Result<usize>
+where
+    F: FnMut(&[StyledCell]) -> Result<()>,
+{
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|array| column_cells(array.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut row = Vec::with_capacity(columns.len());
+    for row_idx in 0..batch.num_rows() {
+        row.clear();
+        for (array, cell) in batch.columns().iter().zip(&columns) {
+            if array.is_null(row_idx) {
+                row.push(StyledCell::new(CellValue::Empty, CellStyle::Default));
+            } else {
+                row.push(cell(row_idx));
+            }
+        }
+        write(&row)?;
+    }
+    Ok(batch.num_rows())
+}
+
+fn plain(value: CellValue) -> StyledCell {
+    StyledCell::new(value, CellStyle::Default)
+}
+
+fn float(value: f64) -> StyledCell {
+    if value.is_finite() {
+        plain(CellValue::Float(value))
+    } else {
+        plain(CellValue::Empty)
+    }
+}
+
+fn serial(seconds: f64, style: CellStyle) -> StyledCell {
+    StyledCell::new(
+        CellValue::DateTime(seconds / SECONDS_PER_DAY + UNIX_EPOCH_SERIAL),
+        style,
+    )
+}
+
+/// Cell builder for one column
+fn column_cells(array: &dyn Array) -> Result<CellFn<'_>> {
+    macro_rules! integer {
+        ($t:ty) => {{
+            let values = array.as_primitive::<$t>();
+            Box::new(move |i| plain(CellValue::integer(values.value(i) as i128)))
+        }};
+    }
+    macro_rules! timestamp {
+        ($t:ty, $per_second:expr) => {{
+            let values = array.as_primitive::<$t>();
+            Box::new(move |i| {
+                serial(
+                    values.value(i) as f64 / $per_second,
+                    CellStyle::DateTimestamp,
+                )
+            })
+        }};
+    }
+
+    let cell: CellFn<'_> = match array.data_type() {
+        DataType::Null => Box::new(|_| plain(CellValue::Empty)),
+        DataType::Boolean => {
+            let values = array.as_boolean();
+            Box::new(move |i| plain(CellValue::Bool(values.value(i))))
+        }
+        DataType::Int8 => integer!(Int8Type),
+        DataType::Int16 => integer!(Int16Type),
+        DataType::Int32 => integer!(Int32Type),
+        DataType::Int64 => integer!(Int64Type),
+        DataType::UInt8 => integer!(UInt8Type),
+        DataType::UInt16 => integer!(UInt16Type),
+        DataType::UInt32 => integer!(UInt32Type),
+        DataType::UInt64 => integer!(UInt64Type),
+        DataType::Float16 => {
+            let values = array.as_primitive::<Float16Type>();
+            Box::new(move |i| float(values.value(i).to_f64()))
+        }
+        DataType::Float32 => {
+            let values = array.as_primitive::<Float32Type>();
+            Box::new(move |i| float(values.value(i) as f64))
+        }
+        DataType::Float64 => {
+            let values = array.as_primitive::<Float64Type>();
+            Box::new(move |i| float(values.value(i)))
+        }
+        DataType::Decimal128(_, scale) => {
+            let values = array.as_primitive::<Decimal128Type>();
+            let scale = *scale;
+            Box::new(move |i| decimal(values.value(i), scale))
+        }
+        DataType::Utf8 => {
+            let values = array.as_string::<i32>();
+            Box::new(move |i| plain(CellValue::String(values.value(i).to_string())))
+        }
+        DataType::LargeUtf8 => {
+            let values = array.as_string::<i64>();
+            Box::new(move |i| plain(CellValue::String(values.value(i).to_string())))
+        }
+        DataType::Utf8View => {
+            let values = array.as_string_view();
+            Box::new(move |i| plain(CellValue::String(values.value(i).to_string())))
+        }
+        DataType::Date32 => {
+            let values = array.as_primitive::<Date32Type>();
+            Box::new(move |i| {
+                serial(
+                    values.value(i) as f64 * SECONDS_PER_DAY,
+                    CellStyle::DateDefault,
+                )
+            })
+        }
+        DataType::Date64 => {
+            let values = array.as_primitive::<Date64Type>();
+            Box::new(move |i| serial(values.value(i) as f64 / 1e3, CellStyle::DateDefault))
+        }
+        DataType::Timestamp(TimeUnit::Second, _) => timestamp!(TimestampSecondType, 1.0),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => timestamp!(TimestampMillisecondType, 1e3),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => timestamp!(TimestampMicrosecondType, 1e6),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => timestamp!(TimestampNanosecondType, 1e9),
+        _ => {
+            let formatter = ArrayFormatter::try_new(array, &FormatOptions::default())
+                .map_err(|e| ExcelError::WriteError(format!("Unsupported Arrow column: {}", e)))?;
+            Box::new(move |i| plain(CellValue::String(formatter.value(i).to_string())))
+        }
+    };
+    Ok(cell)
+}
+
+#[cfg(feature = "decimal")]
+fn decimal(value: i128, scale: i8) -> StyledCell {
+    use rust_decimal::Decimal;
+
+    match u32::try_from(scale)
+        .ok()
+        .and_then(|scale| Decimal::try_from_i128_with_scale(value, scale).ok())
+    {
+        Some(d) => plain(CellValue::Decimal(d)),
+        None => float(value as f64 / 10f64.powi(scale as i32)),
+    }
+}
+
+#[cfg(not(feature = "decimal"))]
+fn decimal(value: i128, scale: i8) -> StyledCell {
+    float(value as f64 / 10f64.powi(scale as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{
+        ArrayRef, BooleanArray, Date32Array, Float64Array, Int64Array, StringArray,
+        TimestampMillisecondArray, UInt64Array,
+    };
+    use std::sync::Arc;
+
+    fn rows(batch: &RecordBatch) -> Vec<Vec<StyledCell>> {
+        let mut rows = Vec::new();
+        for_each_row(batch, |cells| {
+            rows.push(cells.to_vec());
+            Ok(())
+        })
+        .unwrap();
+        rows
+    }
+
+    #[test]
+    fn test_typed_columns() {
+        let batch = RecordBatch::try_from_iter([
+            (
+                "id",
+                Arc::new(Int64Array::from(vec![Some(1), None])) as ArrayRef,
+            ),
+            (
+                "big",
+                Arc::new(UInt64Array::from(vec![u64::MAX, 7])) as ArrayRef,
+            ),
+            (
+                "score",
+                Arc::new(Float64Array::from(vec![9.5, f64::NAN])) as ArrayRef,
+            ),
+            (
+                "ok",
+                Arc::new(BooleanArray::from(vec![true, false])) as ArrayRef,
+            ),
+            (
+                "name",
+                Arc::new(StringArray::from(vec!["a", "b"])) as ArrayRef,
+            ),
+            // 2024-03-01
+            (
+                "day",
+                Arc::new(Date32Array::from(vec![19783, 0])) as ArrayRef,
+            ),
+            // 2024-03-01 12:00:00
+            (
+                "at",
+                Arc::new(TimestampMillisecondArray::from(vec![1_709_294_400_000, 0])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        let rows = rows(&batch);
+        assert_eq!(rows.len(), 2);
+        let values: Vec<_> = rows[0].iter().map(|c| c.value.clone()).collect();
+        assert_eq!(
+            values,
+            vec![
+                CellValue::Int(1),
+                CellValue::BigInt(u64::MAX.to_string()),
+                CellValue::Float(9.5),
+                CellValue::Bool(true),
+                CellValue::String("a".to_string()),
+                CellValue::DateTime(45352.0),
+                CellValue::DateTime(45352.5),
+            ]
+        );
+        assert_eq!(rows[0][5].style, CellStyle::DateDefault);
+        assert_eq!(rows[0][6].style, CellStyle::DateTimestamp);
+
+        assert_eq!(rows[1][0].value, CellValue::Empty);
+        assert_eq!(rows[1][2].value, CellValue::Empty);
+        assert_eq!(rows[1][5].value, CellValue::DateTime(25569.0));
+    }
+}
@@ -1,13 +1,25 @@
 //! High-level converters for Parquet ↔ Excel
 
+use crate::convert::{cell_datetime, ColumnType, SheetSelector};
 use crate::error::Result;
+use crate::fast_writer::UltraLowMemoryWorkbook;
 use crate::parquet::reader::ParquetReader;
-use crate::{ExcelReader, ExcelWriter};
-use std::path::Path;
+use crate::types::CellValue;
+use crate::ExcelReader;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// High-level converter for Parquet → Excel
 ///
 /// This converter provides a simple one-step conversion from Parquet to Excel format.
+/// Columns keep their types: numbers, booleans and dates are written as typed
+/// cells rather than text.
+///
+/// The input may also be a directory holding a (Hive-partitioned) dataset such
+/// as `sales/dt=2024-01-01/part-0.parquet`. Its `.parquet` files are read in
+/// path order and must share one schema; files whose name starts with `.` or
+/// `_` (e.g. `_SUCCESS`) are ignored.
 ///
 /// # Example
 ///
@@ -16,18 +28,32 @@ use std::path::Path;
 ///
 /// let converter = ParquetToExcelConverter::new("data.parquet")?;
 /// converter.convert_to_excel("output.xlsx")?;
+///
+/// // Partitioned dataset, one sheet per `dt=...` directory
+/// ParquetToExcelConverter::new("sales/")?
+///     .partition_columns(true)
+///     .sheet_per_partition(true)
+///     .convert_to_excel("sales.xlsx")?;
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 pub struct ParquetToExcelConverter {
     parquet_path: String,
+    partition_columns: bool,
+    sheet_per_partition: bool,
+}
+
+/// A Parquet file of the input with its `key=value` partition values
+struct PartFile {
+    path: PathBuf,
+    partition: Vec<(String, String)>,
 }
 
 impl ParquetToExcelConverter {
-    /// Create a new converter for the given Parquet file
+    /// Create a new converter for the given Parquet file or dataset directory
     ///
     /// # Arguments
     ///
-    /// * `parquet_path` - Path to the input Parquet file
+    /// * `parquet_path` - Path to the input Parquet file or directory
     pub fn new<P: AsRef<Path>>(parquet_path: P) -> Result<Self> {
         let path_str = parquet_path
             .as_ref()
@@ -39,9 +65,28 @@ impl ParquetToExcelConverter {
 
         Ok(Self {
             parquet_path: path_str,
+            partition_columns: false,
+            sheet_per_partition: false,
         })
     }
 
+    /// Append the partition keys of a directory input as text columns
+    /// (default: false)
+    pub fn partition_columns(mut self, enabled: bool) -> Self {
+        self.partition_columns = enabled;
+        self
+    }
+
+    /// Write each partition directory to its own sheet instead of one
+    /// `Sheet1` (default: false)
+    ///
+    /// Sheets are named after the partition path, e.g. `dt=2024-01-01`,
+    /// truncated to Excel's 31 characters.
+    pub fn sheet_per_partition(mut self, enabled: bool) -> Self {
+        self.sheet_per_partition = enabled;
+        self
+    }
+
     /// Convert the Parquet file to Excel
     ///
     /// # Arguments
@@ -52,23 +97,7 @@ impl ParquetToExcelConverter {
     ///
     /// Number of rows converted (excluding header)
     pub fn convert_to_excel<P: AsRef<Path>>(&self, excel_path: P) -> Result<usize> {
-        let reader = ParquetReader::open(&self.parquet_path)?;
-        let mut writer = ExcelWriter::new(excel_path)?;
-
-        // Write headers
-        let headers = reader.column_names();
-        writer.write_header_bold(&headers)?;
-
-        // Stream rows
-        let mut row_count = 0;
-        for row in reader.rows()? {
-            let row_data = row?;
-            writer.write_row(&row_data)?;
-            row_count += 1;
-        }
-
-        writer.save()?;
-        Ok(row_count)
+        self.convert_with_progress(excel_path, |_, _| {})
     }
 
     /// Convert with progress callback
@@ -86,49 +115,231 @@ impl ParquetToExcelConverter {
         P: AsRef<Path>,
         F: FnMut(usize, usize),
     {
-        let reader = ParquetReader::open(&self.parquet_path)?;
-        let total_rows = reader.row_count();
-        let mut writer = ExcelWriter::new(excel_path)?;
+        use crate::types::{CellStyle, StyledCell};
 
-        // Write headers
-        let headers = reader.column_names();
-        writer.write_header_bold(&headers)?;
+        let files = self.part_files()?;
+        let readers = files
+            .iter()
+            .map(|file| ParquetReader::open(&file.path))
+            .collect::<Result<Vec<_>>>()?;
+        let first = readers.first().ok_or_else(|| {
+            crate::error::ExcelError::ReadError(format!(
+                "No Parquet files found in {}",
+                self.parquet_path
+            ))
+        })?;
+        for (reader, file) in readers.iter().zip(&files).skip(1) {
+            if reader.schema().fields() != first.schema().fields() {
+                return Err(crate::error::ExcelError::ReadError(format!(
+                    "{} has a different schema than {}",
+                    file.path.display(),
+                    files[0].path.display()
+                )));
+            }
+        }
+        let total_rows = readers.iter().map(ParquetReader::row_count).sum();
 
-        // Stream rows with progress
+        // Headers, with the partition keys of the first file appended
+        let mut headers = first.column_names();
+        if self.partition_columns {
+            headers.extend(files[0].partition.iter().map(|(key, _)| key.clone()));
+        }
+        let header_cells: Vec<StyledCell> = headers
+            .into_iter()
+            .map(|name| StyledCell::new(CellValue::String(name), CellStyle::HeaderBold))
+            .collect();
+
+        let mut workbook = UltraLowMemoryWorkbook::new(excel_path)?;
+        let mut current_sheet: Option<String> = None;
         let mut row_count = 0;
-        for (idx, row) in reader.rows()?.enumerate() {
-            let row_data = row?;
-            writer.write_row(&row_data)?;
-            row_count += 1;
-
-            // Report progress every 1000 rows
-            if (idx + 1) % 1000 == 0 || idx + 1 == total_rows {
-                callback(idx + 1, total_rows);
+        for (reader, file) in readers.iter().zip(&files) {
+            let sheet = if self.sheet_per_partition {
+                partition_sheet_name(&file.partition)
+            } else {
+                "Sheet1".to_string()
+            };
+            if current_sheet.as_ref() != Some(&sheet) {
+                workbook.add_worksheet(&sheet)?;
+                workbook.write_row_styled(&header_cells)?;
+                current_sheet = Some(sheet);
+            }
+
+            // Stream batches with progress
+            for batch in reader.batches()? {
+                let batch = batch?;
+                let batch = if self.partition_columns {
+                    with_partition_columns(batch, &file.partition)?
+                } else {
+                    batch
+                };
+                row_count += workbook.write_record_batch(&batch)?;
+                callback(row_count, total_rows);
             }
         }
 
-        writer.save()?;
+        workbook.close()?;
         Ok(row_count)
     }
+
+    /// Files to convert: the input itself, or the `.parquet` files below it
+    /// in path order
+    fn part_files(&self) -> Result<Vec<PartFile>> {
+        let root = Path::new(&self.parquet_path);
+        if !root.is_dir() {
+            return Ok(vec![PartFile {
+                path: root.to_path_buf(),
+                partition: Vec::new(),
+            }]);
+        }
+
+        let mut paths = Vec::new();
+        collect_parquet_files(root, &mut paths)?;
+        paths.sort();
+
+        Ok(paths
+            .into_iter()
+            .map(|path| {
+                let partition = path
+                    .parent()
+                    .and_then(|dir| dir.strip_prefix(root).ok())
+                    .map(|dir| {
+                        dir.components()
+                            .filter_map(|c| c.as_os_str().to_str()?.split_once('='))
+                            .map(|(key, value)| (key.to_string(), percent_decode(value)))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                PartFile { path, partition }
+            })
+            .collect())
+    }
+}
+
+fn collect_parquet_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name.starts_with('.') || name.starts_with('_') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_parquet_files(&path, paths)?;
+        } else if name.ends_with(".parquet") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Decode the `%XX` escapes Hive uses in partition values
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Sheet name for a partition: `key=value` pairs joined with spaces, with
+/// characters Excel rejects replaced and cut to 31 characters
+fn partition_sheet_name(partition: &[(String, String)]) -> String {
+    if partition.is_empty() {
+        return "Sheet1".to_string();
+    }
+    partition
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .map(|c| match c {
+            '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+            c => c,
+        })
+        .take(31)
+        .collect()
+}
+
+/// `batch` with one constant text column per partition key appended
+fn with_partition_columns(
+    batch: arrow::record_batch::RecordBatch,
+    partition: &[(String, String)],
+) -> Result<arrow::record_batch::RecordBatch> {
+    use arrow::array::{ArrayRef, StringArray};
+
+    if partition.is_empty() {
+        return Ok(batch);
+    }
+
+    let mut fields: Vec<Field> = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.as_ref().clone())
+        .collect();
+    let mut columns = batch.columns().to_vec();
+    for (key, value) in partition {
+        fields.push(Field::new(key, DataType::Utf8, false));
+        columns
+            .push(Arc::new(StringArray::from(vec![value.as_str(); batch.num_rows()])) as ArrayRef);
+    }
+    arrow::record_batch::RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|e| crate::error::ExcelError::WriteError(e.to_string()))
 }
 
 /// High-level converter for Excel → Parquet
 ///
-/// Note: This converter reads the entire Excel file into memory first,
-/// then writes to Parquet. For very large files, consider using
-/// streaming approaches.
+/// Column types are inferred from the first [`sample_rows`](Self::sample_rows)
+/// data rows: integers become `Int64`, decimals `Float64`, booleans
+/// `Boolean`, date-only cells `Date32` and dates with a time
+/// `Timestamp(Millisecond)`. Columns mixing incompatible values, or holding
+/// only text, are written as `Utf8`. Pass an explicit schema with
+/// [`with_schema`](Self::with_schema) to skip inference.
+///
+/// Rows are streamed in batches of 10K, so memory use stays flat apart from
+/// the sample window.
 ///
 /// # Example
 ///
 /// ```no_run
 /// use excelstream::parquet::ExcelToParquetConverter;
 ///
-/// let converter = ExcelToParquetConverter::new("data.xlsx")?;
+/// let converter = ExcelToParquetConverter::new("data.xlsx")?.sample_rows(5_000);
 /// converter.convert_to_parquet("output.parquet")?;
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 pub struct ExcelToParquetConverter {
     excel_path: String,
+    sample_rows: usize,
+    schema: Option<SchemaRef>,
+    sheets: Vec<SheetSelector>,
+}
+
+/// Result of converting one sheet with
+/// [`ExcelToParquetConverter::convert_to_dir`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetConversion {
+    /// Sheet name
+    pub sheet: String,
+    /// Parquet file written for the sheet
+    pub path: PathBuf,
+    /// Number of data rows written (excluding the header)
+    pub rows: usize,
 }
 
 impl ExcelToParquetConverter {
@@ -148,15 +359,73 @@ impl ExcelToParquetConverter {
 
         Ok(Self {
             excel_path: path_str,
+            sample_rows: 1000,
+            schema: None,
+            sheets: Vec::new(),
         })
     }
 
+    /// Number of data rows used to infer column types (default: 1000)
+    pub fn sample_rows(mut self, rows: usize) -> Self {
+        self.sample_rows = rows.max(1);
+        self
+    }
+
+    /// Use `schema` instead of inferring one
+    ///
+    /// Fields are matched to sheet columns by position. Supported types are
+    /// `Int64`, `Float64`, `Boolean`, `Date32`, `Timestamp(Millisecond)` and
+    /// `Utf8`. The schema applies to every converted sheet.
+    pub fn with_schema(mut self, schema: SchemaRef) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Select the sheet with this name
+    ///
+    /// May be called several times, together with
+    /// [`sheet_index`](Self::sheet_index), to pick the sheets
+    /// [`convert_to_dir`](Self::convert_to_dir) converts.
+    /// [`convert_to_parquet`](Self::convert_to_parquet) uses the first one.
+    pub fn sheet(mut self, name: &str) -> Self {
+        self.sheets.push(SheetSelector::Name(name.to_string()));
+        self
+    }
+
+    /// Select the sheet at this zero-based index (see [`sheet`](Self::sheet))
+    pub fn sheet_index(mut self, index: usize) -> Self {
+        self.sheets.push(SheetSelector::Index(index));
+        self
+    }
+
+    /// Infer the Parquet schema of the first selected sheet from its header
+    /// row and the sample window
+    pub fn infer_schema(&self) -> Result<SchemaRef> {
+        let mut reader = ExcelReader::open(&self.excel_path)?;
+        let sheet_name = self.selected_sheets(&reader)?.swap_remove(0);
+        let mut rows_iter = reader.rows(&sheet_name)?;
+        let headers = Self::read_headers(&mut rows_iter)?.ok_or_else(Self::no_data)?;
+
+        let mut sample = Vec::new();
+        for row in rows_iter.take(self.sample_rows) {
+            sample.push(row?.cells);
+        }
+        Ok(Self::infer(&headers, &sample))
+    }
+
     /// Convert the Excel file to Parquet with streaming (constant memory)
     ///
     /// This method:
-    /// 1. Reads the Excel file row-by-row
+    /// 1. Reads the header row and the sample window to build the schema
     /// 2. Processes data in batches (10K rows per batch)
-    /// 3. Writes to Parquet format with constant memory usage
+    /// 3. Writes typed Arrow columns to Parquet
+    ///
+    /// Only the first selected sheet (by default the first sheet) is
+    /// converted; see [`convert_to_dir`](Self::convert_to_dir) for the rest.
+    ///
+    /// A value that does not fit its column after the sample window (e.g.
+    /// text in an `Int64` column) fails the conversion; raise
+    /// [`sample_rows`](Self::sample_rows) or pass a schema in that case.
     ///
     /// # Arguments
     ///
@@ -166,72 +435,105 @@ impl ExcelToParquetConverter {
     ///
     /// Number of rows converted
     pub fn convert_to_parquet<P: AsRef<Path>>(&self, parquet_path: P) -> Result<usize> {
-        use arrow::datatypes::{DataType, Field, Schema};
-        use parquet::arrow::arrow_writer::ArrowWriter;
-        use parquet::file::properties::WriterProperties;
-        use std::fs::File;
-        use std::sync::Arc;
+        let mut reader = ExcelReader::open(&self.excel_path)?;
+        let sheet_name = self.selected_sheets(&reader)?.swap_remove(0);
+        self.convert_sheet(&mut reader, &sheet_name, parquet_path.as_ref())?
+            .ok_or_else(Self::no_data)
+    }
 
-        const BATCH_SIZE: usize = 10_000; // Process 10K rows at a time
+    /// Convert each selected sheet (all sheets when none is selected) to
+    /// `output_dir/{sheet}.parquet`
+    ///
+    /// The directory is created if needed. Characters that are not allowed
+    /// in file names are replaced with `_`. Sheets without a header row are
+    /// skipped and left out of the returned summary.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use excelstream::parquet::ExcelToParquetConverter;
+    ///
+    /// let converted = ExcelToParquetConverter::new("finance.xlsx")?
+    ///     .sheet("Q1")
+    ///     .sheet("Q2")
+    ///     .convert_to_dir("parquet/")?;
+    /// for sheet in &converted {
+    ///     println!("{}: {} rows -> {}", sheet.sheet, sheet.rows, sheet.path.display());
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn convert_to_dir<P: AsRef<Path>>(&self, output_dir: P) -> Result<Vec<SheetConversion>> {
+        let output_dir = output_dir.as_ref();
+        std::fs::create_dir_all(output_dir)?;
 
-        // Read Excel file
         let mut reader = ExcelReader::open(&self.excel_path)?;
-        let sheet_names = reader.sheet_names();
+        let sheets = if self.sheets.is_empty() {
+            reader.sheet_names()
+        } else {
+            self.selected_sheets(&reader)?
+        };
 
-        if sheet_names.is_empty() {
-            return Err(crate::error::ExcelError::ReadError(
-                "No sheets found in Excel file".to_string(),
-            ));
+        let mut converted = Vec::with_capacity(sheets.len());
+        for sheet in sheets {
+            let path = output_dir.join(format!("{}.parquet", file_stem(&sheet)));
+            if let Some(rows) = self.convert_sheet(&mut reader, &sheet, &path)? {
+                converted.push(SheetConversion { sheet, path, rows });
+            }
         }
+        Ok(converted)
+    }
+
+    /// Convert one sheet, returning `None` when it has no header row
+    fn convert_sheet(
+        &self,
+        reader: &mut ExcelReader,
+        sheet_name: &str,
+        parquet_path: &Path,
+    ) -> Result<Option<usize>> {
+        use parquet::arrow::arrow_writer::ArrowWriter;
+        use parquet::file::properties::WriterProperties;
+        use std::fs::File;
+
+        const BATCH_SIZE: usize = 10_000; // Process 10K rows at a time
 
-        // Use first sheet
-        let sheet_name = &sheet_names[0];
         let mut rows_iter = reader.rows(sheet_name)?;
+        let Some(headers) = Self::read_headers(&mut rows_iter)? else {
+            return Ok(None);
+        };
 
-        // Read first row (headers)
-        let headers = match rows_iter.next() {
-            Some(Ok(row)) => row.to_strings(),
-            Some(Err(e)) => return Err(e),
-            None => {
-                return Err(crate::error::ExcelError::ReadError(
-                    "No data found in Excel file".to_string(),
-                ))
+        // Buffer the sample window, then build the schema from it
+        let mut batch_buffer: Vec<Vec<CellValue>> = Vec::with_capacity(BATCH_SIZE);
+        for row in rows_iter.by_ref().take(self.sample_rows) {
+            batch_buffer.push(row?.cells);
+        }
+        let schema = match &self.schema {
+            Some(schema) => {
+                Self::check_schema(schema)?;
+                schema.clone()
             }
+            None => Self::infer(&headers, &batch_buffer),
         };
 
-        // Create schema (all strings for simplicity)
-        let fields: Vec<Field> = headers
-            .iter()
-            .map(|name| Field::new(name, DataType::Utf8, true))
-            .collect();
-        let schema = Arc::new(Schema::new(fields));
-        let num_columns = headers.len();
-
         // Create Parquet writer
         let file = File::create(parquet_path)?;
         let props = WriterProperties::builder().build();
         let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props))
             .map_err(|e| crate::error::ExcelError::WriteError(e.to_string()))?;
 
-        // Process rows in batches
+        // Process rows in batches; data starts on sheet row 2
         let mut total_rows = 0;
-        let mut batch_buffer: Vec<Vec<String>> = Vec::with_capacity(BATCH_SIZE);
-
         for row_result in rows_iter {
-            let row = row_result?;
-            batch_buffer.push(row.to_strings());
-
-            // When batch is full, write it and clear buffer
             if batch_buffer.len() >= BATCH_SIZE {
-                Self::write_batch(&mut writer, &schema, &batch_buffer, num_columns)?;
+                Self::write_batch(&mut writer, &schema, &batch_buffer, total_rows + 2)?;
                 total_rows += batch_buffer.len();
                 batch_buffer.clear(); // Free memory
             }
+            batch_buffer.push(row_result?.cells);
         }
 
         // Write remaining rows
         if !batch_buffer.is_empty() {
-            Self::write_batch(&mut writer, &schema, &batch_buffer, num_columns)?;
+            Self::write_batch(&mut writer, &schema, &batch_buffer, total_rows + 2)?;
             total_rows += batch_buffer.len();
         }
 
@@ -240,41 +542,169 @@ impl ExcelToParquetConverter {
             .close()
             .map_err(|e| crate::error::ExcelError::WriteError(e.to_string()))?;
 
-        Ok(total_rows)
+        Ok(Some(total_rows))
+    }
+
+    /// Names of the selected sheets, or of the first sheet when none is
+    /// selected; never empty
+    fn selected_sheets(&self, reader: &ExcelReader) -> Result<Vec<String>> {
+        let names = reader.sheet_names();
+        if names.is_empty() {
+            return Err(crate::error::ExcelError::ReadError(
+                "No sheets found in Excel file".to_string(),
+            ));
+        }
+        if self.sheets.is_empty() {
+            return Ok(vec![names[0].clone()]);
+        }
+
+        self.sheets
+            .iter()
+            .map(|selector| {
+                let (found, label) = match selector {
+                    SheetSelector::Index(index) => (names.get(*index), format!("#{}", index)),
+                    SheetSelector::Name(name) => {
+                        (names.iter().find(|n| *n == name), format!("'{}'", name))
+                    }
+                };
+                found.cloned().ok_or_else(|| {
+                    crate::error::ExcelError::ReadError(format!("Sheet {} not found", label))
+                })
+            })
+            .collect()
+    }
+
+    fn read_headers<I>(rows_iter: &mut I) -> Result<Option<Vec<String>>>
+    where
+        I: Iterator<Item = Result<crate::types::Row>>,
+    {
+        rows_iter
+            .next()
+            .transpose()
+            .map(|row| row.map(|row| row.to_strings()))
+    }
+
+    fn no_data() -> crate::error::ExcelError {
+        crate::error::ExcelError::ReadError("No data found in Excel file".to_string())
+    }
+
+    /// Schema with one nullable field per header, typed from `sample`
+    fn infer(headers: &[String], sample: &[Vec<CellValue>]) -> SchemaRef {
+        let fields: Vec<Field> = headers
+            .iter()
+            .enumerate()
+            .map(|(col_idx, name)| {
+                let column_type = sample
+                    .iter()
+                    .filter_map(|row| row.get(col_idx).and_then(ColumnType::of_cell))
+                    .reduce(ColumnType::merge)
+                    .unwrap_or(ColumnType::Text);
+                let data_type = match column_type {
+                    ColumnType::Int => DataType::Int64,
+                    ColumnType::Float => DataType::Float64,
+                    ColumnType::Bool => DataType::Boolean,
+                    ColumnType::Date => DataType::Date32,
+                    ColumnType::DateTime => DataType::Timestamp(TimeUnit::Millisecond, None),
+                    ColumnType::Text => DataType::Utf8,
+                };
+                Field::new(name, data_type, true)
+            })
+            .collect();
+        Arc::new(Schema::new(fields))
+    }
+
+    fn check_schema(schema: &Schema) -> Result<()> {
+        for field in schema.fields() {
+            match field.data_type() {
+                DataType::Int64
+                | DataType::Float64
+                | DataType::Boolean
+                | DataType::Date32
+                | DataType::Timestamp(TimeUnit::Millisecond, None)
+                | DataType::Utf8 => {}
+                other => {
+                    return Err(crate::error::ExcelError::InvalidState(format!(
+                        "Unsupported Parquet type {} for column '{}'",
+                        other,
+                        field.name()
+                    )))
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Helper method to write a batch of rows to Parquet
+    ///
+    /// `first_row` is the sheet row number of `rows[0]`, used in errors.
     fn write_batch(
         writer: &mut parquet::arrow::arrow_writer::ArrowWriter<std::fs::File>,
-        schema: &std::sync::Arc<arrow::datatypes::Schema>,
-        rows: &[Vec<String>],
-        num_columns: usize,
+        schema: &SchemaRef,
+        rows: &[Vec<CellValue>],
+        first_row: usize,
     ) -> Result<()> {
-        use arrow::array::{ArrayRef, StringArray};
+        use arrow::array::{
+            ArrayRef, BooleanArray, Date32Array, Float64Array, Int64Array, StringArray,
+            TimestampMillisecondArray,
+        };
         use arrow::record_batch::RecordBatch;
-        use std::sync::Arc;
 
         if rows.is_empty() {
             return Ok(());
         }
 
         // Convert rows to columnar format
-        let mut columns: Vec<ArrayRef> = Vec::with_capacity(num_columns);
-
-        for col_idx in 0..num_columns {
-            let col_data: Vec<Option<&str>> = rows
-                .iter()
-                .map(|row| {
-                    if col_idx < row.len() && !row[col_idx].is_empty() {
-                        Some(row[col_idx].as_str())
-                    } else {
-                        None
-                    }
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(schema.fields().len());
+
+        for (col_idx, field) in schema.fields().iter().enumerate() {
+            let array: std::result::Result<ArrayRef, usize> = match field.data_type() {
+                DataType::Int64 => column_values(rows, col_idx, |cell| match cell {
+                    CellValue::Int(i) => Some(*i),
+                    CellValue::Float(f) if f.fract() == 0.0 && f.abs() < 9.2e18 => Some(*f as i64),
+                    CellValue::String(s) | CellValue::BigInt(s) => s.trim().parse().ok(),
+                    _ => None,
                 })
-                .collect();
-
-            let array = StringArray::from(col_data);
-            columns.push(Arc::new(array) as ArrayRef);
+                .map(|values| Arc::new(Int64Array::from(values)) as ArrayRef),
+                DataType::Float64 => column_values(rows, col_idx, |cell| match cell {
+                    CellValue::Bool(_) | CellValue::DateTime(_) => None,
+                    cell => cell.as_f64(),
+                })
+                .map(|values| Arc::new(Float64Array::from(values)) as ArrayRef),
+                DataType::Boolean => column_values(rows, col_idx, |cell| match cell {
+                    CellValue::Bool(b) => Some(*b),
+                    CellValue::String(s) if s.eq_ignore_ascii_case("true") => Some(true),
+                    CellValue::String(s) if s.eq_ignore_ascii_case("false") => Some(false),
+                    _ => None,
+                })
+                .map(|values| Arc::new(BooleanArray::from(values)) as ArrayRef),
+                DataType::Date32 => column_values(rows, col_idx, |cell| {
+                    let days = cell_datetime(cell)?
+                        .and_utc()
+                        .timestamp()
+                        .div_euclid(86_400);
+                    i32::try_from(days).ok()
+                })
+                .map(|values| Arc::new(Date32Array::from(values)) as ArrayRef),
+                DataType::Timestamp(TimeUnit::Millisecond, None) => {
+                    column_values(rows, col_idx, |cell| {
+                        Some(cell_datetime(cell)?.and_utc().timestamp_millis())
+                    })
+                    .map(|values| Arc::new(TimestampMillisecondArray::from(values)) as ArrayRef)
+                }
+                _ => column_values(rows, col_idx, |cell| Some(cell.as_string()))
+                    .map(|values| Arc::new(StringArray::from(values)) as ArrayRef),
+            };
+
+            let array = array.map_err(|i| {
+                crate::error::ExcelError::WriteError(format!(
+                    "Row {}, column '{}': {:?} does not fit {}; increase sample_rows or pass an explicit schema",
+                    first_row + i,
+                    field.name(),
+                    rows[i].get(col_idx).unwrap_or(&CellValue::Empty),
+                    field.data_type()
+                ))
+            })?;
+            columns.push(array);
         }
 
         // Create and write record batch
@@ -305,3 +735,279 @@ impl ExcelToParquetConverter {
         Ok(row_count)
     }
 }
+
+/// Typed value of each cell in column `col_idx`
+///
+/// Empty cells become `None`; the index of the first cell `convert` rejects
+/// is returned as the error.
+fn column_values<T>(
+    rows: &[Vec<CellValue>],
+    col_idx: usize,
+    convert: impl Fn(&CellValue) -> Option<T>,
+) -> std::result::Result<Vec<Option<T>>, usize> {
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| match row.get(col_idx) {
+            None | Some(CellValue::Empty) => Ok(None),
+            Some(CellValue::String(s)) if s.is_empty() => Ok(None),
+            Some(cell) => convert(cell).map(Some).ok_or(i),
+        })
+        .collect()
+}
+
+/// `sheet` with characters that are not allowed in file names replaced
+fn file_stem(sheet: &str) -> String {
+    sheet
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CellStyle;
+    use crate::ExcelWriter;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use tempfile::TempDir;
+
+    fn write_sample(path: &Path, extra: Option<CellValue>) {
+        let mut writer = ExcelWriter::new(path).unwrap();
+        writer
+            .write_header_bold(["id", "price", "active", "day", "at", "note", "mixed"])
+            .unwrap();
+        for i in 0..3 {
+            writer
+                .write_row_styled(&[
+                    (CellValue::Int(i), CellStyle::Default),
+                    (CellValue::Float(i as f64 + 0.5), CellStyle::Default),
+                    (CellValue::Bool(i % 2 == 0), CellStyle::Default),
+                    (
+                        CellValue::DateTime(45352.0 + i as f64),
+                        CellStyle::DateDefault,
+                    ),
+                    // The reader only turns whole-day serials into dates, so
+                    // date-times arrive as ISO text
+                    (
+                        CellValue::String("2024-03-01 12:00:00".to_string()),
+                        CellStyle::Default,
+                    ),
+                    (CellValue::String(format!("n{}", i)), CellStyle::Default),
+                    (
+                        if i == 0 {
+                            CellValue::Int(1)
+                        } else {
+                            CellValue::String("x".to_string())
+                        },
+                        CellStyle::Default,
+                    ),
+                ])
+                .unwrap();
+        }
+        if let Some(value) = extra {
+            writer.write_row_typed(&[value]).unwrap();
+        }
+        writer.save().unwrap();
+    }
+
+    #[test]
+    fn test_excel_to_parquet_infers_types() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in.xlsx");
+        let output = dir.path().join("out.parquet");
+        write_sample(&input, None);
+
+        let converter = ExcelToParquetConverter::new(&input).unwrap();
+        assert_eq!(converter.convert_to_parquet(&output).unwrap(), 3);
+
+        let file = std::fs::File::open(&output).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        let types: Vec<_> = builder
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.data_type().clone())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                DataType::Int64,
+                DataType::Float64,
+                DataType::Boolean,
+                DataType::Date32,
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                DataType::Utf8,
+                DataType::Utf8,
+            ]
+        );
+
+        let batch = builder.build().unwrap().next().unwrap().unwrap();
+        let days = batch
+            .column(3)
+            .as_any()
+            .downcast_ref::<arrow::array::Date32Array>()
+            .unwrap();
+        assert_eq!(days.value(0), 19783); // 2024-03-01
+        let at = batch
+            .column(4)
+            .as_any()
+            .downcast_ref::<arrow::array::TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(at.value(0), 1_709_294_400_000); // 2024-03-01 12:00:00
+    }
+
+    #[test]
+    fn test_excel_to_parquet_rejects_values_outside_sample() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in.xlsx");
+        write_sample(&input, Some(CellValue::String("oops".to_string())));
+
+        let converter = ExcelToParquetConverter::new(&input).unwrap().sample_rows(3);
+        let err = converter
+            .convert_to_parquet(dir.path().join("out.parquet"))
+            .unwrap_err();
+        assert!(err.to_string().contains("Row 5, column 'id'"), "{}", err);
+
+        // An explicit schema keeps the column as text
+        let mut fields: Vec<Field> = converter
+            .infer_schema()
+            .unwrap()
+            .fields()
+            .iter()
+            .map(|f| f.as_ref().clone())
+            .collect();
+        fields[0] = Field::new("id", DataType::Utf8, true);
+        let rows = converter
+            .with_schema(Arc::new(Schema::new(fields)))
+            .convert_to_parquet(dir.path().join("out.parquet"))
+            .unwrap();
+        assert_eq!(rows, 4);
+    }
+
+    #[test]
+    fn test_excel_to_parquet_per_sheet() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("book.xlsx");
+        let mut writer = ExcelWriter::new(&input).unwrap();
+        writer.write_header_bold(["a"]).unwrap();
+        writer.write_row_typed(&[CellValue::Int(1)]).unwrap();
+        writer.add_sheet("Q1|Q2").unwrap();
+        writer.write_header_bold(["b", "c"]).unwrap();
+        writer
+            .write_row_typed(&[CellValue::Float(1.5), CellValue::Bool(true)])
+            .unwrap();
+        writer
+            .write_row_typed(&[CellValue::Float(2.5), CellValue::Bool(false)])
+            .unwrap();
+        writer.add_sheet("Empty").unwrap();
+        writer.save().unwrap();
+
+        let out = dir.path().join("parquet");
+        let converted = ExcelToParquetConverter::new(&input)
+            .unwrap()
+            .convert_to_dir(&out)
+            .unwrap();
+        assert_eq!(
+            converted,
+            vec![
+                SheetConversion {
+                    sheet: "Sheet1".to_string(),
+                    path: out.join("Sheet1.parquet"),
+                    rows: 1,
+                },
+                SheetConversion {
+                    sheet: "Q1|Q2".to_string(),
+                    path: out.join("Q1_Q2.parquet"),
+                    rows: 2,
+                },
+            ]
+        );
+        assert!(!out.join("Empty.parquet").exists());
+
+        let converter = ExcelToParquetConverter::new(&input).unwrap().sheet_index(1);
+        let rows = converter
+            .convert_to_parquet(dir.path().join("q.parquet"))
+            .unwrap();
+        assert_eq!(rows, 2);
+        assert_eq!(converter.infer_schema().unwrap().field(1).name(), "c");
+
+        let err = ExcelToParquetConverter::new(&input)
+            .unwrap()
+            .sheet("Missing")
+            .convert_to_dir(&out)
+            .unwrap_err();
+        assert!(err.to_string().contains("Sheet 'Missing' not found"));
+    }
+
+    fn write_part(path: &Path, ids: &[i64]) {
+        use arrow::array::{ArrayRef, Int64Array};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::arrow_writer::ArrowWriter;
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let batch = RecordBatch::try_from_iter([(
+            "id",
+            Arc::new(Int64Array::from(ids.to_vec())) as ArrayRef,
+        )])
+        .unwrap();
+        let file = std::fs::File::create(path).unwrap();
+        let mut writer = ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_partitioned_dataset_to_excel() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("sales");
+        write_part(&root.join("dt=2024-01-02/part-1.parquet"), &[4]);
+        write_part(&root.join("dt=2024-01-02/part-0.parquet"), &[2, 3]);
+        write_part(&root.join("dt=2024-01-01/part-0.parquet"), &[1]);
+        std::fs::write(root.join("_SUCCESS"), "").unwrap();
+
+        let output = dir.path().join("merged.xlsx");
+        let rows = ParquetToExcelConverter::new(&root)
+            .unwrap()
+            .partition_columns(true)
+            .convert_to_excel(&output)
+            .unwrap();
+        assert_eq!(rows, 4);
+        let mut reader = ExcelReader::open(&output).unwrap();
+        let merged: Vec<_> = reader
+            .rows("Sheet1")
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(merged[0], vec!["id", "dt"]);
+        assert_eq!(merged[1], vec!["1", "2024-01-01"]);
+        assert_eq!(merged[2], vec!["2", "2024-01-02"]);
+        assert_eq!(merged[4], vec!["4", "2024-01-02"]);
+
+        let output = dir.path().join("split.xlsx");
+        ParquetToExcelConverter::new(&root)
+            .unwrap()
+            .sheet_per_partition(true)
+            .convert_to_excel(&output)
+            .unwrap();
+        let mut reader = ExcelReader::open(&output).unwrap();
+        assert_eq!(reader.sheet_names(), vec!["dt=2024-01-01", "dt=2024-01-02"]);
+        assert_eq!(reader.rows("dt=2024-01-02").unwrap().count(), 4);
+    }
+
+    #[test]
+    fn test_partition_helpers() {
+        assert_eq!(percent_decode("2024-01-01 10%3A00"), "2024-01-01 10:00");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(
+            partition_sheet_name(&[
+                ("dt".to_string(), "2024-01-01 10:00".to_string()),
+                ("region".to_string(), "eu".to_string())
+            ]),
+            "dt=2024-01-01 10_00 region=eu"
+        );
+    }
+}
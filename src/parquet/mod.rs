@@ -44,6 +44,9 @@
 //! // Convert with schema inference
 //! let converter = ExcelToParquetConverter::new("data.xlsx")?;
 //! converter.convert_to_parquet("output.parquet")?;
+//!
+//! // One file per sheet: parquet/{sheet}.parquet
+//! let sheets = converter.convert_to_dir("parquet")?;
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
@@ -53,8 +56,11 @@ pub mod reader;
 #[cfg(feature = "parquet-support")]
 pub mod converter;
 
+#[cfg(feature = "parquet-support")]
+pub(crate) mod batch;
+
 #[cfg(feature = "parquet-support")]
 pub use reader::ParquetReader;
 
 #[cfg(feature = "parquet-support")]
-pub use converter::{ExcelToParquetConverter, ParquetToExcelConverter};
+pub use converter::{ExcelToParquetConverter, ParquetToExcelConverter, SheetConversion};
@@ -1,10 +1,14 @@
 //! Parquet file reader with streaming support
 
+use super::batch::for_each_row;
 use crate::error::{ExcelError, Result};
+use crate::row_io::RowSource;
+use crate::types::CellValue;
 use arrow::array::*;
 use arrow::datatypes::*;
 use arrow::record_batch::RecordBatch;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::collections::VecDeque;
 use std::fs::File;
 use std::path::Path;
 use std::sync::Arc;
@@ -111,6 +115,51 @@ impl ParquetReader {
             schema: self.schema.clone(),
         })
     }
+
+    /// Create an iterator over the file's record batches
+    ///
+    /// Batches keep Arrow's column types; pass them to
+    /// [`ExcelWriter::write_record_batch`](crate::ExcelWriter::write_record_batch)
+    /// to write typed cells.
+    pub fn batches(&self) -> Result<impl Iterator<Item = Result<RecordBatch>>> {
+        let file = File::open(&self.file_path)?;
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .map_err(|e| ExcelError::ReadError(format!("Failed to open Parquet file: {}", e)))?
+            .build()
+            .map_err(|e| ExcelError::ReadError(format!("Failed to build reader: {}", e)))?;
+
+        Ok(reader.map(|batch| {
+            batch.map_err(|e| ExcelError::ReadError(format!("Failed to read batch: {}", e)))
+        }))
+    }
+}
+
+/// The schema is [`column_names`](ParquetReader::column_names); cells keep
+/// their Arrow types, as in [`ExcelWriter::write_record_batch`](crate::ExcelWriter::write_record_batch)
+impl RowSource for ParquetReader {
+    fn schema(&mut self) -> Result<Option<Vec<String>>> {
+        Ok(Some(self.column_names()))
+    }
+
+    fn rows(&mut self) -> Result<impl Iterator<Item = Result<Vec<CellValue>>> + '_> {
+        let mut batches = self.batches()?;
+        // Rows of the current batch
+        let mut pending: VecDeque<Vec<CellValue>> = VecDeque::new();
+        Ok(std::iter::from_fn(move || loop {
+            if let Some(row) = pending.pop_front() {
+                return Some(Ok(row));
+            }
+            let added = batches.next()?.and_then(|batch| {
+                for_each_row(&batch, |cells| {
+                    pending.push_back(cells.iter().map(|cell| cell.value.clone()).collect());
+                    Ok(())
+                })
+            });
+            if let Err(e) = added {
+                return Some(Err(e));
+            }
+        }))
+    }
 }
 
 /// Iterator over Parquet rows converted to string vectors
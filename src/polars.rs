@@ -0,0 +1,261 @@
+//! Polars `DataFrame` ↔ Excel
+//!
+//! [`read_sheet_to_polars`] loads a sheet into a `DataFrame`, taking column
+//! names from the first row and inferring a type per column from every
+//! value below it. [`write_polars_df`] writes a `DataFrame` as typed cells,
+//! row by row, under a bold header.
+//!
+//! ```no_run
+//! use excelstream::polars::{read_sheet_to_polars, write_polars_df};
+//!
+//! let df = read_sheet_to_polars("sales.xlsx", "Sheet1")?;
+//! println!("{} rows, columns {:?}", df.height(), df.get_column_names());
+//!
+//! write_polars_df(&df, "copy.xlsx")?;
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+//!
+//! | Excel column | Polars type |
+//! |---|---|
+//! | whole numbers | `Int64` |
+//! | numbers | `Float64` |
+//! | booleans | `Boolean` |
+//! | dates | `Date` |
+//! | `YYYY-MM-DD HH:MM:SS` text | `Datetime(ms)` |
+//! | anything else, or mixed | `String` |
+//!
+//! Empty cells are nulls, both ways. Date-time cells with a time of day are
+//! read the way [`StreamingReader`](crate::streaming_reader::StreamingReader)
+//! returns them, as their serial number.
+
+use crate::convert::{cell_datetime, ColumnType};
+use crate::error::{ExcelError, Result};
+use crate::types::{CellStyle, CellValue};
+use crate::writer::ExcelWriter;
+use crate::ExcelReader;
+use polars::prelude::*;
+use std::path::Path;
+
+/// Excel serial number of 1970-01-01
+const UNIX_EPOCH_SERIAL: f64 = 25569.0;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Read `sheet` of the workbook at `path` into a `DataFrame`
+///
+/// The first row holds the column names; a sheet without rows is an error.
+/// Every row is held in memory while the columns are typed.
+pub fn read_sheet_to_polars<P: AsRef<Path>>(path: P, sheet: &str) -> Result<DataFrame> {
+    let mut reader = ExcelReader::open(path)?;
+    let mut rows = reader.rows(sheet)?;
+    let headers = match rows.next() {
+        Some(row) => row?.to_strings(),
+        None => {
+            return Err(ExcelError::ReadError(format!(
+                "Sheet '{}' has no header row",
+                sheet
+            )))
+        }
+    };
+
+    // Cells by column; short rows are padded with empty cells
+    let mut columns: Vec<Vec<CellValue>> = vec![Vec::new(); headers.len()];
+    for row in rows {
+        let mut cells = row?.cells.into_iter();
+        for column in columns.iter_mut() {
+            column.push(cells.next().unwrap_or(CellValue::Empty));
+        }
+    }
+
+    let columns = headers
+        .iter()
+        .zip(&columns)
+        .map(|(name, cells)| polars_column(name, cells))
+        .collect();
+    DataFrame::new(columns).map_err(|e| ExcelError::ReadError(e.to_string()))
+}
+
+/// Write `df` to a new workbook at `path`
+///
+/// The column names become a bold header row; see
+/// [`write_polars_sheet`] for the cell types.
+pub fn write_polars_df<P: AsRef<Path>>(df: &DataFrame, path: P) -> Result<()> {
+    let mut writer = ExcelWriter::new(path)?;
+    write_polars_sheet(&mut writer, df)?;
+    writer.save()
+}
+
+/// Write `df` to the current sheet of `writer`, header first
+///
+/// Integers, floats and booleans become typed cells, `Date` and `Datetime`
+/// values become date cells (`Datetime` in UTC), nulls and non-finite
+/// floats become empty cells, and any other type is written as text.
+pub fn write_polars_sheet(writer: &mut ExcelWriter, df: &DataFrame) -> Result<()> {
+    writer.write_header_bold(df.get_column_names().iter().map(|name| name.as_str()))?;
+
+    let columns = df.get_columns();
+    let mut row = Vec::with_capacity(columns.len());
+    for i in 0..df.height() {
+        row.clear();
+        for column in columns {
+            let value = column
+                .get(i)
+                .map_err(|e| ExcelError::WriteError(e.to_string()))?;
+            row.push(styled_cell(value));
+        }
+        writer.write_row_styled(&row)?;
+    }
+    Ok(())
+}
+
+fn styled_cell(value: AnyValue<'_>) -> (CellValue, CellStyle) {
+    let plain = |value| (value, CellStyle::Default);
+    match value {
+        AnyValue::Null => plain(CellValue::Empty),
+        AnyValue::Boolean(b) => plain(CellValue::Bool(b)),
+        AnyValue::UInt8(n) => plain(CellValue::Int(n as i64)),
+        AnyValue::UInt16(n) => plain(CellValue::Int(n as i64)),
+        AnyValue::UInt32(n) => plain(CellValue::Int(n as i64)),
+        AnyValue::UInt64(n) => plain(CellValue::integer(n as i128)),
+        AnyValue::Int8(n) => plain(CellValue::Int(n as i64)),
+        AnyValue::Int16(n) => plain(CellValue::Int(n as i64)),
+        AnyValue::Int32(n) => plain(CellValue::Int(n as i64)),
+        AnyValue::Int64(n) => plain(CellValue::integer(n as i128)),
+        AnyValue::Int128(n) => plain(CellValue::integer(n)),
+        AnyValue::Float32(f) if f.is_finite() => plain(CellValue::Float(f as f64)),
+        AnyValue::Float64(f) if f.is_finite() => plain(CellValue::Float(f)),
+        AnyValue::Float32(_) | AnyValue::Float64(_) => plain(CellValue::Empty),
+        AnyValue::String(s) => plain(CellValue::String(s.to_string())),
+        AnyValue::StringOwned(s) => plain(CellValue::String(s.to_string())),
+        AnyValue::Date(days) => (
+            CellValue::DateTime(days as f64 + UNIX_EPOCH_SERIAL),
+            CellStyle::DateDefault,
+        ),
+        AnyValue::Datetime(value, unit, _) | AnyValue::DatetimeOwned(value, unit, _) => {
+            let per_second = match unit {
+                TimeUnit::Nanoseconds => 1e9,
+                TimeUnit::Microseconds => 1e6,
+                TimeUnit::Milliseconds => 1e3,
+            };
+            let seconds = value as f64 / per_second;
+            (
+                CellValue::DateTime(seconds / SECONDS_PER_DAY + UNIX_EPOCH_SERIAL),
+                CellStyle::DateTimestamp,
+            )
+        }
+        other => plain(CellValue::String(other.to_string())),
+    }
+}
+
+/// Column typed from all of its non-empty cells
+fn polars_column(name: &str, cells: &[CellValue]) -> Column {
+    let column_type = cells
+        .iter()
+        .filter_map(ColumnType::of_cell)
+        .reduce(ColumnType::merge)
+        .unwrap_or(ColumnType::Text);
+    let name = PlSmallStr::from_str(name);
+
+    // Every cell fits the merged type, so only empty cells become null
+    let column = match column_type {
+        ColumnType::Int => Int64Chunked::from_iter_options(
+            name,
+            cells.iter().map(|cell| match cell {
+                CellValue::Int(i) => Some(*i),
+                _ => None,
+            }),
+        )
+        .into_column(),
+        ColumnType::Float => {
+            Float64Chunked::from_iter_options(name, cells.iter().map(CellValue::as_f64))
+                .into_column()
+        }
+        ColumnType::Bool => BooleanChunked::from_iter_options(
+            name,
+            cells.iter().map(|cell| match cell {
+                CellValue::Bool(b) => Some(*b),
+                _ => None,
+            }),
+        )
+        .into_column(),
+        ColumnType::Date => Int32Chunked::from_iter_options(
+            name,
+            cells.iter().map(|cell| {
+                let days = cell_datetime(cell)?
+                    .and_utc()
+                    .timestamp()
+                    .div_euclid(86_400);
+                i32::try_from(days).ok()
+            }),
+        )
+        .into_date()
+        .into_column(),
+        ColumnType::DateTime => Int64Chunked::from_iter_options(
+            name,
+            cells
+                .iter()
+                .map(|cell| Some(cell_datetime(cell)?.and_utc().timestamp_millis())),
+        )
+        .into_datetime(TimeUnit::Milliseconds, None)
+        .into_column(),
+        ColumnType::Text => StringChunked::from_iter_options(
+            name,
+            cells.iter().map(|cell| match cell {
+                CellValue::Empty => None,
+                CellValue::String(s) if s.is_empty() => None,
+                cell => Some(cell.as_string()),
+            }),
+        )
+        .into_column(),
+    };
+    column
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_polars_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("frame.xlsx");
+
+        let day = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let epoch_days = (day - chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()).num_days();
+        let at_ms = day
+            .and_hms_opt(12, 30, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp_millis();
+        let df = DataFrame::new(vec![
+            Column::new("id".into(), [Some(1i64), Some(2), None]),
+            Column::new("price".into(), [Some(1.5f64), None, Some(3.0)]),
+            Column::new("active".into(), [Some(true), Some(false), None]),
+            Column::new("note".into(), [Some("a"), None, Some("0012")]),
+            Int32Chunked::from_iter_options(
+                "day".into(),
+                [Some(epoch_days as i32), None, Some(epoch_days as i32 + 1)].into_iter(),
+            )
+            .into_date()
+            .into_column(),
+            Int64Chunked::from_iter_options("at".into(), [None, Some(at_ms), None].into_iter())
+                .into_datetime(TimeUnit::Milliseconds, None)
+                .into_column(),
+        ])
+        .unwrap();
+
+        write_polars_df(&df, &path).unwrap();
+        let read = read_sheet_to_polars(&path, "Sheet1").unwrap();
+        let (read_at, df_at) = (read.drop("at").unwrap(), df.drop("at").unwrap());
+        assert_eq!(read_at.dtypes(), df_at.dtypes());
+        assert!(read_at.equals_missing(&df_at), "{}\n{}", read, df);
+
+        // A time of day comes back as the date-time's serial number
+        match read.column("at").unwrap().get(1).unwrap() {
+            AnyValue::Float64(serial) => assert!((serial - (45352.0 + 12.5 / 24.0)).abs() < 1e-9),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert!(read_sheet_to_polars(&path, "Missing").is_err());
+    }
+}
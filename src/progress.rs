@@ -0,0 +1,191 @@
+//! Progress reporting and cancellation for long writes
+//!
+//! ```no_run
+//! use excelstream::{CancellationToken, ExcelWriter};
+//!
+//! let token = CancellationToken::new();
+//! let mut writer = ExcelWriter::new("big.xlsx").unwrap();
+//! writer.set_progress_callback(|rows, bytes| println!("{} rows, {} bytes", rows, bytes));
+//! writer.set_cancellation_token(token.clone());
+//!
+//! // From another thread, e.g. a "Cancel" button:
+//! // token.cancel();
+//!
+//! for i in 0..5_000_000 {
+//!     // Returns ExcelError::Cancelled once the token is cancelled
+//!     writer.write_row([i.to_string()]).unwrap();
+//! }
+//! writer.save().unwrap();
+//! ```
+
+use crate::error::{ExcelError, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Rows between two progress callbacks
+pub const PROGRESS_INTERVAL: u64 = 1_000;
+
+/// Shared flag that aborts a write from another thread or task
+///
+/// Clones share the same flag. Once cancelled, every further row write
+/// returns [`ExcelError::Cancelled`]; the partial output is not a valid
+/// workbook and should be discarded.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Callback receiving `(rows_written, bytes_written)`
+pub type ProgressCallback = Box<dyn FnMut(u64, u64) + Send>;
+
+/// Observer for write statistics, e.g. to export them as Prometheus metrics
+///
+/// Every method receives a running total and defaults to doing nothing.
+/// They are called together every [`PROGRESS_INTERVAL`] rows and once
+/// more when the file is finished, so the last values are the final ones.
+///
+/// ```no_run
+/// use excelstream::{ExcelWriter, WriterMetrics};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::sync::Arc;
+///
+/// struct Compressed(Arc<AtomicU64>);
+///
+/// impl WriterMetrics for Compressed {
+///     fn bytes_compressed(&mut self, bytes: u64) {
+///         self.0.store(bytes, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let gauge = Arc::new(AtomicU64::new(0));
+/// let mut writer = ExcelWriter::new("export.xlsx").unwrap();
+/// writer.set_metrics(Compressed(gauge.clone()));
+/// writer.write_row(["ID", "Name"]).unwrap();
+/// writer.save().unwrap();
+/// println!("{} bytes on disk", gauge.load(Ordering::Relaxed));
+/// ```
+pub trait WriterMetrics: Send {
+    /// Rows written, over all sheets
+    fn rows_written(&mut self, _rows: u64) {}
+
+    /// Row XML generated, before compression
+    fn bytes_written(&mut self, _bytes: u64) {}
+
+    /// Bytes of the output file written so far
+    fn bytes_compressed(&mut self, _bytes: u64) {}
+
+    /// Distinct strings in the shared string table
+    fn sst_unique_strings(&mut self, _count: u64) {}
+
+    /// Times buffered output was flushed to the file
+    fn flush_count(&mut self, _flushes: u64) {}
+}
+
+/// Row and byte counters shared by the writers
+///
+/// Bytes are counted as generated (row XML or CSV text before compression),
+/// so they grow steadily even while the compressor is buffering.
+#[derive(Default)]
+pub(crate) struct Progress {
+    callback: Option<ProgressCallback>,
+    token: Option<CancellationToken>,
+    rows: u64,
+    bytes: u64,
+}
+
+impl Progress {
+    pub(crate) fn set_callback(&mut self, callback: ProgressCallback) {
+        self.callback = Some(callback);
+    }
+
+    pub(crate) fn set_token(&mut self, token: CancellationToken) {
+        self.token = Some(token);
+    }
+
+    /// Fail with [`ExcelError::Cancelled`] if cancellation was requested
+    pub(crate) fn check(&self) -> Result<()> {
+        match &self.token {
+            Some(token) if token.is_cancelled() => Err(ExcelError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    pub(crate) fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    pub(crate) fn row_written(&mut self, bytes: usize) {
+        self.rows += 1;
+        self.bytes += bytes as u64;
+        if self.rows.is_multiple_of(PROGRESS_INTERVAL) {
+            self.notify();
+        }
+    }
+
+    /// Final callback with the totals, unless the last one already had them
+    pub(crate) fn finish(&mut self) {
+        if !self.rows.is_multiple_of(PROGRESS_INTERVAL) {
+            self.notify();
+        }
+    }
+
+    fn notify(&mut self) {
+        if let Some(callback) = &mut self.callback {
+            callback(self.rows, self.bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_progress_interval() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut progress = Progress::default();
+        let sink = calls.clone();
+        progress.set_callback(Box::new(move |rows, bytes| {
+            sink.lock().unwrap().push((rows, bytes))
+        }));
+
+        for _ in 0..2_500 {
+            progress.row_written(10);
+        }
+        progress.finish();
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![(1_000, 10_000), (2_000, 20_000), (2_500, 25_000)]
+        );
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let token = CancellationToken::new();
+        let mut progress = Progress::default();
+        assert!(progress.check().is_ok());
+
+        progress.set_token(token.clone());
+        assert!(progress.check().is_ok());
+        token.clone().cancel();
+        assert!(matches!(progress.check(), Err(ExcelError::Cancelled)));
+    }
+}
@@ -0,0 +1,1130 @@
+//! Serde integration: turn `Serialize` structs into rows and rows back into structs
+//!
+//! [`to_record`] flattens one struct (or map) into a header list and a row
+//! of styled cells. [`ExcelWriter::write_record`](crate::ExcelWriter::write_record)
+//! builds on it and writes the header automatically before the first record.
+//!
+//! On the read side, [`from_row`] fills a `Deserialize` type from a header
+//! row and a data row, and
+//! [`StreamingReader::rows_as`](crate::streaming_reader::StreamingReader::rows_as)
+//! applies it to every row of a sheet.
+//!
+//! Value mapping when writing:
+//!
+//! | Rust / serde value                  | Cell                                      |
+//! |-------------------------------------|-------------------------------------------|
+//! | `bool`                              | `Bool`                                    |
+//! | integers                            | `Int` (`BigInt` beyond 2^53, see [`CellValue::integer`]) |
+//! | `f32` / `f64`                       | `Float`                                   |
+//! | `String`, `&str`, `char`            | `String`                                  |
+//! | unit enum variants                  | `String` with the variant name            |
+//! | `None`, `()`                        | `Empty`                                   |
+//! | chrono `NaiveDate`                  | `DateTime` with a date format             |
+//! | chrono `NaiveDateTime`, `DateTime`  | `DateTime` with a date-time format        |
+//!
+//! chrono serializes dates as ISO-8601 strings, so strings in exactly those
+//! formats (`2024-01-15`, `2024-01-15T10:30:00`, RFC 3339) become dates.
+//! RFC 3339 timestamps keep their wall-clock time unless a
+//! [`TimezonePolicy`] says otherwise, see [`to_record_in`].
+//! Nested structs, sequences and maps inside a field are rejected; use
+//! `#[serde(flatten)]` to inline a nested struct.
+//!
+//! When reading, columns are matched to fields by header text (so
+//! `#[serde(rename)]` and `#[serde(alias)]` work), unknown columns are
+//! ignored, and cells are coerced to the field type:
+//!
+//! | Field type              | Accepted cells                                        |
+//! |-------------------------|-------------------------------------------------------|
+//! | integers                | whole numbers, numeric text (`" 42 "`)                |
+//! | `f32` / `f64`           | numbers, numeric text                                 |
+//! | `bool`                  | booleans, `0`/`1`, `true`/`false`/`yes`/`no` text     |
+//! | `String`                | anything; numbers use their shortest form             |
+//! | `Option<_>`             | `None` for empty cells, blank text and missing columns |
+//! | unit enum variants      | text with the variant name                            |
+//! | chrono `NaiveDate`      | date cells without a time part, ISO text              |
+//! | chrono `NaiveDateTime`  | date cells with a time part, ISO text                 |
+//!
+//! Date cells are handed to the field as ISO-8601 text, which is what
+//! chrono's `Deserialize` impls parse. A whole-day date becomes `2024-01-15`,
+//! so read midnight timestamps into a `NaiveDate` or a `String`.
+
+use crate::error::{ExcelError, Result};
+use crate::streaming_reader::RowStructIterator;
+use crate::types::{CellStyle, CellValue, TimezonePolicy};
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Impossible, Serialize};
+use std::marker::PhantomData;
+
+/// One serialized record: column names and the matching cells
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Record {
+    /// Field names, in declaration order
+    pub headers: Vec<String>,
+    /// Cell values with the style chosen for each
+    pub cells: Vec<(CellValue, CellStyle)>,
+}
+
+/// Serialize a struct or map into a [`Record`]
+///
+/// # Example
+///
+/// ```
+/// use excelstream::record::to_record;
+/// use excelstream::types::CellValue;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Order {
+///     id: u32,
+///     customer: String,
+///     discount: Option<f64>,
+/// }
+///
+/// let record = to_record(&Order { id: 7, customer: "Alice".into(), discount: None })?;
+/// assert_eq!(record.headers, ["id", "customer", "discount"]);
+/// assert_eq!(record.cells[0].0, CellValue::Int(7));
+/// assert_eq!(record.cells[2].0, CellValue::Empty);
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+pub fn to_record<T: Serialize + ?Sized>(value: &T) -> Result<Record> {
+    to_record_in(value, TimezonePolicy::WallTime)
+}
+
+/// [`to_record`], with RFC 3339 timestamps converted under `timezone`
+///
+/// ```
+/// use excelstream::record::to_record_in;
+/// use excelstream::types::{CellValue, TimezonePolicy};
+/// use std::collections::BTreeMap;
+///
+/// let event = BTreeMap::from([("at", "2024-01-15T19:00:00+07:00")]);
+/// let record = to_record_in(&event, TimezonePolicy::Utc)?;
+/// assert_eq!(record.cells[0].0, CellValue::DateTime(45306.5));
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+pub fn to_record_in<T: Serialize + ?Sized>(value: &T, timezone: TimezonePolicy) -> Result<Record> {
+    let mut record = Record::default();
+    value.serialize(RecordSerializer {
+        record: &mut record,
+        cells: CellSerializer { timezone },
+    })?;
+    Ok(record)
+}
+
+fn unsupported(what: &str) -> ExcelError {
+    ExcelError::NotSupported(format!(
+        "{} cannot be written as a record; use a struct or map (with #[serde(flatten)] for nested structs)",
+        what
+    ))
+}
+
+/// Top-level serializer: accepts a struct or a map and fills a Record
+struct RecordSerializer<'a> {
+    record: &'a mut Record,
+    cells: CellSerializer,
+}
+
+macro_rules! reject_top_level {
+    ($($method:ident($($arg:ty),*) => $what:expr;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<()> {
+                Err(unsupported($what))
+            }
+        )*
+    };
+}
+
+impl<'a> ser::Serializer for RecordSerializer<'a> {
+    type Ok = ();
+    type Error = ExcelError;
+    type SerializeSeq = Impossible<(), ExcelError>;
+    type SerializeTuple = Impossible<(), ExcelError>;
+    type SerializeTupleStruct = Impossible<(), ExcelError>;
+    type SerializeTupleVariant = Impossible<(), ExcelError>;
+    type SerializeMap = RecordMap<'a>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), ExcelError>;
+
+    reject_top_level! {
+        serialize_bool(bool) => "a bool";
+        serialize_i8(i8) => "an integer";
+        serialize_i16(i16) => "an integer";
+        serialize_i32(i32) => "an integer";
+        serialize_i64(i64) => "an integer";
+        serialize_u8(u8) => "an integer";
+        serialize_u16(u16) => "an integer";
+        serialize_u32(u32) => "an integer";
+        serialize_u64(u64) => "an integer";
+        serialize_f32(f32) => "a float";
+        serialize_f64(f64) => "a float";
+        serialize_char(char) => "a char";
+        serialize_str(&str) => "a string";
+        serialize_bytes(&[u8]) => "a byte array";
+        serialize_none() => "None";
+        serialize_unit() => "()";
+        serialize_unit_struct(&'static str) => "a unit struct";
+        serialize_unit_variant(&'static str, u32, &'static str) => "an enum variant";
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()> {
+        Err(unsupported("an enum variant"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(unsupported("an enum variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(RecordMap {
+            record: self.record,
+            cells: self.cells,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.record.headers.reserve(len);
+        self.record.cells.reserve(len);
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(unsupported("an enum variant"))
+    }
+}
+
+impl ser::SerializeStruct for RecordSerializer<'_> {
+    type Ok = ();
+    type Error = ExcelError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.record.headers.push(key.to_string());
+        self.record.cells.push(value.serialize(self.cells)?);
+        Ok(())
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        // Keep columns aligned when `skip_serializing_if` drops a field
+        self.record.headers.push(key.to_string());
+        self.record
+            .cells
+            .push((CellValue::Empty, CellStyle::Default));
+        Ok(())
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Map serializer (also used for structs containing `#[serde(flatten)]`)
+struct RecordMap<'a> {
+    record: &'a mut Record,
+    cells: CellSerializer,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for RecordMap<'_> {
+    type Ok = ();
+    type Error = ExcelError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        let (key, _) = key.serialize(self.cells)?;
+        self.key = Some(key.as_string());
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ExcelError::WriteError("map value without a key".to_string()))?;
+        self.record.headers.push(key);
+        self.record.cells.push(value.serialize(self.cells)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Field serializer: turns one scalar value into a styled cell
+#[derive(Clone, Copy, Default)]
+pub(crate) struct CellSerializer {
+    /// Applied to RFC 3339 timestamps
+    pub(crate) timezone: TimezonePolicy,
+}
+
+type Cell = (CellValue, CellStyle);
+
+fn plain(value: CellValue) -> Result<Cell> {
+    Ok((value, CellStyle::Default))
+}
+
+impl ser::Serializer for CellSerializer {
+    type Ok = Cell;
+    type Error = ExcelError;
+    type SerializeSeq = Impossible<Cell, ExcelError>;
+    type SerializeTuple = Impossible<Cell, ExcelError>;
+    type SerializeTupleStruct = Impossible<Cell, ExcelError>;
+    type SerializeTupleVariant = Impossible<Cell, ExcelError>;
+    type SerializeMap = Impossible<Cell, ExcelError>;
+    type SerializeStruct = Impossible<Cell, ExcelError>;
+    type SerializeStructVariant = Impossible<Cell, ExcelError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Cell> {
+        plain(CellValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Cell> {
+        plain(CellValue::Int(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Cell> {
+        plain(CellValue::Int(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Cell> {
+        plain(CellValue::Int(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Cell> {
+        plain(CellValue::integer(v.into()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Cell> {
+        plain(CellValue::integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Cell> {
+        plain(CellValue::Int(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Cell> {
+        plain(CellValue::Int(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Cell> {
+        plain(CellValue::Int(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Cell> {
+        plain(CellValue::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Cell> {
+        plain(CellValue::from(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Cell> {
+        plain(CellValue::Float(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Cell> {
+        plain(CellValue::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Cell> {
+        plain(CellValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Cell> {
+        Ok(parse_iso_date(v, self.timezone)
+            .unwrap_or_else(|| (CellValue::String(v.to_string()), CellStyle::Default)))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Cell> {
+        Err(unsupported("a byte array field"))
+    }
+
+    fn serialize_none(self) -> Result<Cell> {
+        plain(CellValue::Empty)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Cell> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Cell> {
+        plain(CellValue::Empty)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Cell> {
+        plain(CellValue::Empty)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Cell> {
+        plain(CellValue::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Cell> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Cell> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(unsupported("a sequence field"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(unsupported("a tuple field"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(unsupported("a tuple struct field"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(unsupported("an enum field with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(unsupported("a map field"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(unsupported("a nested struct field"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(unsupported("an enum field with data"))
+    }
+}
+
+/// Recognize the ISO-8601 strings chrono's serde support produces
+fn parse_iso_date(s: &str, timezone: TimezonePolicy) -> Option<Cell> {
+    // Cheap pre-check so ordinary strings skip the parsers
+    let bytes = s.as_bytes();
+    if bytes.len() < 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        let serial = excel_serial(date.and_hms_opt(0, 0, 0)?);
+        return Some((CellValue::DateTime(serial), CellStyle::DateDefault));
+    }
+
+    let datetime = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(s)
+                .ok()
+                .map(|dt| timezone.naive(&dt))
+        })?;
+    Some((
+        CellValue::DateTime(excel_serial(datetime)),
+        CellStyle::DateTimestamp,
+    ))
+}
+
+/// Day zero of Excel serial dates (1900 date system)
+fn excel_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1899, 12, 30)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("valid epoch")
+}
+
+/// Excel serial date (days since 1899-12-30, time as the fraction)
+fn excel_serial(datetime: NaiveDateTime) -> f64 {
+    let duration = datetime - excel_epoch();
+    duration.num_milliseconds() as f64 / 86_400_000.0
+}
+
+/// ISO-8601 text for an Excel serial date, rounded to the second
+///
+/// Whole days are formatted as a date only, so they parse as `NaiveDate`.
+fn serial_to_iso(serial: f64) -> Option<String> {
+    let seconds = (serial * 86_400.0).round();
+    if !seconds.is_finite() || seconds.abs() > i64::MAX as f64 {
+        return None;
+    }
+    let seconds = seconds as i64;
+    let datetime = excel_epoch().checked_add_signed(Duration::try_seconds(seconds)?)?;
+
+    let format = if seconds % 86_400 == 0 {
+        "%Y-%m-%d"
+    } else {
+        "%Y-%m-%dT%H:%M:%S"
+    };
+    Some(datetime.format(format).to_string())
+}
+
+/// Deserialize one data row into `T`
+///
+/// `headers[i]` names the column of `cells[i]`. Columns with an empty header
+/// are skipped and cells past the end of `cells` count as empty.
+/// Tuples and sequences are filled by position instead, ignoring `headers`.
+///
+/// # Example
+///
+/// ```
+/// use excelstream::record::from_row;
+/// use excelstream::types::CellValue;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Order {
+///     #[serde(rename = "Order ID")]
+///     id: u32,
+///     customer: String,
+///     discount: Option<f64>,
+/// }
+///
+/// let headers = ["Order ID".to_string(), "customer".to_string(), "discount".to_string()];
+/// let cells = [CellValue::String("7".into()), CellValue::String("Alice".into())];
+///
+/// let order: Order = from_row(&headers, &cells)?;
+/// assert_eq!(order.id, 7);
+/// assert_eq!(order.discount, None);
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+pub fn from_row<T: DeserializeOwned>(headers: &[String], cells: &[CellValue]) -> Result<T> {
+    T::deserialize(RowDeserializer { headers, cells })
+}
+
+/// Iterator returned by [`StreamingReader::rows_as`](crate::streaming_reader::StreamingReader::rows_as)
+///
+/// The first row of the sheet is taken as the header; every following
+/// non-blank row is deserialized with [`from_row`]. Errors name the row and
+/// column that failed.
+pub struct RecordIterator<'a, T> {
+    rows: RowStructIterator<'a>,
+    headers: Option<Vec<String>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> RecordIterator<'a, T> {
+    pub(crate) fn new(rows: RowStructIterator<'a>) -> Self {
+        Self {
+            rows,
+            headers: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Header row, once the first row has been read
+    pub fn headers(&self) -> Option<&[String]> {
+        self.headers.as_deref()
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for RecordIterator<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = match self.rows.next()? {
+                Ok(row) => row,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let Some(headers) = &self.headers else {
+                self.headers = Some(
+                    row.cells
+                        .iter()
+                        .map(|cell| cell.as_string().trim().to_string())
+                        .collect(),
+                );
+                continue;
+            };
+
+            if row.is_blank() {
+                continue;
+            }
+
+            return Some(
+                from_row(headers, &row.cells)
+                    .map_err(|e| with_context(e, &format!("row {}", row.index + 1))),
+            );
+        }
+    }
+}
+
+/// Prefix a deserialization error message with where it happened
+fn with_context(err: ExcelError, location: &str) -> ExcelError {
+    match err {
+        ExcelError::ReadError(msg) => ExcelError::ReadError(format!("{}: {}", location, msg)),
+        other => other,
+    }
+}
+
+/// Top-level deserializer: a row as a map of header -> cell
+struct RowDeserializer<'a> {
+    headers: &'a [String],
+    cells: &'a [CellValue],
+}
+
+impl<'de> de::Deserializer<'de> for RowDeserializer<'_> {
+    type Error = ExcelError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_map(RowMap {
+            columns: self.headers.iter().enumerate(),
+            cells: self.cells,
+            value: None,
+        })
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(RowSeq {
+            cells: self.cells.iter(),
+            col: 0,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+struct RowMap<'a> {
+    columns: std::iter::Enumerate<std::slice::Iter<'a, String>>,
+    cells: &'a [CellValue],
+    value: Option<(&'a str, &'a CellValue)>,
+}
+
+impl<'de> de::MapAccess<'de> for RowMap<'_> {
+    type Error = ExcelError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        for (col, header) in self.columns.by_ref() {
+            if header.is_empty() {
+                continue;
+            }
+            let cell = self.cells.get(col).unwrap_or(&CellValue::Empty);
+            self.value = Some((header, cell));
+            let key: de::value::StrDeserializer<'_, ExcelError> =
+                header.as_str().into_deserializer();
+            return seed.deserialize(key).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let (header, cell) = self
+            .value
+            .take()
+            .ok_or_else(|| ExcelError::InvalidState("value requested before key".to_string()))?;
+        seed.deserialize(CellDeserializer(cell))
+            .map_err(|e| with_context(e, &format!("column '{}'", header)))
+    }
+}
+
+struct RowSeq<'a> {
+    cells: std::slice::Iter<'a, CellValue>,
+    col: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for RowSeq<'_> {
+    type Error = ExcelError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        let Some(cell) = self.cells.next() else {
+            return Ok(None);
+        };
+        self.col += 1;
+        seed.deserialize(CellDeserializer(cell))
+            .map(Some)
+            .map_err(|e| with_context(e, &format!("column {}", self.col)))
+    }
+}
+
+/// Deserializer for a single cell, coercing it to the requested type
+struct CellDeserializer<'a>(&'a CellValue);
+
+impl CellDeserializer<'_> {
+    fn invalid(&self, expected: &str) -> ExcelError {
+        let found = match self.0 {
+            CellValue::Empty => "an empty cell".to_string(),
+            CellValue::String(s) => format!("text {:?}", s),
+            other => format!("{:?}", other),
+        };
+        ExcelError::ReadError(format!("expected {}, found {}", expected, found))
+    }
+
+    fn integer(&self) -> Result<i128> {
+        let value = match self.0 {
+            CellValue::Int(i) => Some(*i as i128),
+            CellValue::Float(f) | CellValue::DateTime(f) if f.fract() == 0.0 => {
+                // Saturates for out-of-range values, which then fail the range check
+                Some(*f as i128)
+            }
+            CellValue::String(s) | CellValue::BigInt(s) => {
+                let s = s.trim();
+                s.parse::<i128>().ok().or_else(|| {
+                    s.parse::<f64>()
+                        .ok()
+                        .filter(|f| f.fract() == 0.0)
+                        .map(|f| f as i128)
+                })
+            }
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) if d.fract().is_zero() => {
+                rust_decimal::prelude::ToPrimitive::to_i128(d)
+            }
+            _ => None,
+        };
+        value.ok_or_else(|| self.invalid("an integer"))
+    }
+
+    fn float(&self) -> Result<f64> {
+        let value = match self.0 {
+            CellValue::String(s) | CellValue::BigInt(s) => s.trim().parse().ok(),
+            other => other.as_f64(),
+        };
+        value.ok_or_else(|| self.invalid("a number"))
+    }
+}
+
+macro_rules! deserialize_signed {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                let value = self.integer()?;
+                match i64::try_from(value) {
+                    Ok(v) => visitor.visit_i64(v),
+                    Err(_) => visitor.visit_i128(value),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! deserialize_unsigned {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                let value = self.integer()?;
+                match u64::try_from(value) {
+                    Ok(v) => visitor.visit_u64(v),
+                    Err(_) if value > 0 => visitor.visit_u128(value as u128),
+                    // Negative: let the visitor report the out-of-range value
+                    Err(_) => match i64::try_from(value) {
+                        Ok(v) => visitor.visit_i64(v),
+                        Err(_) => visitor.visit_i128(value),
+                    },
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for CellDeserializer<'_> {
+    type Error = ExcelError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            CellValue::Empty => visitor.visit_unit(),
+            CellValue::String(s)
+            | CellValue::Formula { expr: s, .. }
+            | CellValue::BigInt(s)
+            | CellValue::Hyperlink { text: s, .. } => visitor.visit_str(s),
+            CellValue::Int(i) => visitor.visit_i64(*i),
+            CellValue::Float(f) | CellValue::DateTime(f) => visitor.visit_f64(*f),
+            CellValue::Bool(b) => visitor.visit_bool(*b),
+            CellValue::Error(e) => Err(ExcelError::ReadError(format!("cell contains {}", e))),
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => visitor.visit_str(&d.to_string()),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let value = match self.0 {
+            CellValue::String(s) => CellValue::String(s.trim().to_string()).as_bool(),
+            CellValue::Float(f) if *f == 0.0 || *f == 1.0 => Some(*f == 1.0),
+            CellValue::Int(i) if *i != 0 && *i != 1 => None,
+            other => other.as_bool(),
+        };
+        match value {
+            Some(b) => visitor.visit_bool(b),
+            None => Err(self.invalid("a boolean")),
+        }
+    }
+
+    deserialize_signed! { deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128 }
+    deserialize_unsigned! { deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128 }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f32(self.float()? as f32)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(self.float()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let text = self.0.as_string();
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(self.invalid("a single character")),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            CellValue::String(s)
+            | CellValue::Formula { expr: s, .. }
+            | CellValue::BigInt(s)
+            | CellValue::Hyperlink { text: s, .. } => visitor.visit_str(s),
+            CellValue::DateTime(serial) => match serial_to_iso(*serial) {
+                Some(iso) => visitor.visit_string(iso),
+                None => Err(self.invalid("a valid date")),
+            },
+            CellValue::Error(e) => Err(ExcelError::ReadError(format!("cell contains {}", e))),
+            other => visitor.visit_string(other.as_string()),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_bytes(self.0.as_string().as_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_byte_buf(self.0.as_string().into_bytes())
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.0.is_blank() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.0.is_blank() {
+            visitor.visit_unit()
+        } else {
+            Err(self.invalid("an empty cell"))
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.0 {
+            CellValue::String(s) => {
+                let variant: de::value::StrDeserializer<'_, ExcelError> =
+                    s.trim().into_deserializer();
+                visitor.visit_enum(variant)
+            }
+            _ => Err(self.invalid("text naming an enum variant")),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        seq tuple tuple_struct map struct identifier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    enum Status {
+        Active,
+    }
+
+    #[derive(Serialize)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(Serialize)]
+    struct Customer {
+        id: u64,
+        name: String,
+        vip: bool,
+        balance: f64,
+        note: Option<String>,
+        status: Status,
+        joined: String,
+        #[serde(flatten)]
+        address: Address,
+    }
+
+    #[test]
+    fn test_struct_to_record() {
+        let record = to_record(&Customer {
+            id: 9_007_199_254_740_993,
+            name: "Alice".to_string(),
+            vip: true,
+            balance: 12.5,
+            note: None,
+            status: Status::Active,
+            joined: "2024-01-15".to_string(),
+            address: Address {
+                city: "Hanoi".to_string(),
+            },
+        })
+        .unwrap();
+
+        assert_eq!(
+            record.headers,
+            ["id", "name", "vip", "balance", "note", "status", "joined", "city"]
+        );
+        let values: Vec<_> = record.cells.iter().map(|(v, _)| v.clone()).collect();
+        assert_eq!(values[0], CellValue::BigInt("9007199254740993".to_string()));
+        assert_eq!(values[1], CellValue::String("Alice".to_string()));
+        assert_eq!(values[2], CellValue::Bool(true));
+        assert_eq!(values[3], CellValue::Float(12.5));
+        assert_eq!(values[4], CellValue::Empty);
+        assert_eq!(values[5], CellValue::String("Active".to_string()));
+        assert_eq!(values[6], CellValue::DateTime(45306.0));
+        assert_eq!(record.cells[6].1, CellStyle::DateDefault);
+        assert_eq!(values[7], CellValue::String("Hanoi".to_string()));
+    }
+
+    #[test]
+    fn test_iso_datetime_strings() {
+        let wall = TimezonePolicy::WallTime;
+        let (value, style) = parse_iso_date("2024-01-15T12:00:00", wall).unwrap();
+        assert_eq!(value, CellValue::DateTime(45306.5));
+        assert_eq!(style, CellStyle::DateTimestamp);
+
+        let (value, _) = parse_iso_date("2024-01-15T12:00:00+07:00", wall).unwrap();
+        assert_eq!(value, CellValue::DateTime(45306.5));
+        let (value, _) = parse_iso_date("2024-01-15T19:00:00+07:00", TimezonePolicy::Utc).unwrap();
+        assert_eq!(value, CellValue::DateTime(45306.5));
+        // Timestamps without an offset are taken as already on the target clock
+        let (value, _) = parse_iso_date("2024-01-15T12:00:00", TimezonePolicy::Utc).unwrap();
+        assert_eq!(value, CellValue::DateTime(45306.5));
+
+        assert!(parse_iso_date("2024-13-45", wall).is_none());
+        assert!(parse_iso_date("not a date", wall).is_none());
+    }
+
+    #[test]
+    fn test_map_and_rejections() {
+        let mut map = BTreeMap::new();
+        map.insert("a", 1);
+        map.insert("b", 2);
+        let record = to_record(&map).unwrap();
+        assert_eq!(record.headers, ["a", "b"]);
+
+        assert!(to_record(&42).is_err());
+        assert!(to_record(&vec![1, 2]).is_err());
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Level {
+        Gold,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Member {
+        #[serde(rename = "Member ID")]
+        id: u32,
+        name: String,
+        active: bool,
+        score: f64,
+        level: Level,
+        joined: NaiveDate,
+        note: Option<String>,
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_row_to_struct_with_coercion() {
+        let headers = strings(&[
+            "Member ID",
+            "name",
+            "",
+            "active",
+            "score",
+            "level",
+            "joined",
+            "extra",
+        ]);
+        let cells = [
+            CellValue::Float(42.0),
+            CellValue::Int(1234),
+            CellValue::String("ignored".to_string()),
+            CellValue::String(" Yes ".to_string()),
+            CellValue::String("7.5".to_string()),
+            CellValue::String("Gold".to_string()),
+            CellValue::DateTime(45306.0),
+        ];
+
+        let member: Member = from_row(&headers, &cells).unwrap();
+        assert_eq!(
+            member,
+            Member {
+                id: 42,
+                name: "1234".to_string(),
+                active: true,
+                score: 7.5,
+                level: Level::Gold,
+                joined: NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+                note: None,
+            }
+        );
+
+        // Tuples are filled by position
+        let pair: (String, i64) = from_row(
+            &[],
+            &[
+                CellValue::String("Alice".to_string()),
+                CellValue::String(" 12 ".to_string()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(pair, ("Alice".to_string(), 12));
+    }
+
+    #[test]
+    fn test_row_errors_name_the_column() {
+        let headers = strings(&["Member ID"]);
+        let err = from_row::<Member>(&headers, &[CellValue::String("abc".to_string())])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("column 'Member ID'"), "{}", err);
+        assert!(err.contains("expected an integer"), "{}", err);
+
+        let err = from_row::<Member>(&headers, &[CellValue::Int(-1)]).unwrap_err();
+        assert!(err.to_string().contains("Member ID"), "{}", err);
+    }
+
+    #[test]
+    fn test_serial_to_iso() {
+        assert_eq!(serial_to_iso(45306.0).unwrap(), "2024-01-15");
+        assert_eq!(serial_to_iso(45306.5).unwrap(), "2024-01-15T12:00:00");
+        assert!(serial_to_iso(f64::NAN).is_none());
+    }
+}
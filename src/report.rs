@@ -0,0 +1,389 @@
+//! Size and checksum of a written file
+//!
+//! Writers can hash their output while it streams to disk, so audit
+//! pipelines don't have to read the finished file back to fingerprint it.
+//!
+//! ```no_run
+//! use excelstream::{Checksum, ExcelWriter};
+//!
+//! let mut writer = ExcelWriter::new("export.xlsx").unwrap();
+//! writer.set_checksum(Checksum::Sha256);
+//! writer.write_row(["ID", "Name"]).unwrap();
+//! let report = writer.save_with_report().unwrap();
+//! println!("{} bytes, sha256 {}", report.bytes_written, report.digest.unwrap());
+//! ```
+
+use crate::error::Result;
+use sha2::{Digest as _, Sha256};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+
+/// Checksum algorithm computed over the written file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    /// CRC-32 (IEEE), as used by ZIP and gzip
+    Crc32,
+    /// SHA-256
+    Sha256,
+}
+
+/// Checksum value of a written file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Digest {
+    Crc32(u32),
+    Sha256([u8; 32]),
+}
+
+impl Digest {
+    /// Lowercase hex representation (8 characters for CRC-32, 64 for SHA-256)
+    pub fn to_hex(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Digest::Crc32(crc) => write!(f, "{:08x}", crc),
+            Digest::Sha256(hash) => hash.iter().try_for_each(|b| write!(f, "{:02x}", b)),
+        }
+    }
+}
+
+/// Returned by `save_with_report()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteReport {
+    /// Size of the produced file in bytes
+    pub bytes_written: u64,
+    /// Checksum of the produced file, if one was requested
+    pub digest: Option<Digest>,
+}
+
+enum Hasher {
+    Crc32(crc32fast::Hasher),
+    Sha256(Box<Sha256>),
+}
+
+impl Hasher {
+    fn new(checksum: Checksum) -> Self {
+        match checksum {
+            Checksum::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+            Checksum::Sha256 => Hasher::Sha256(Box::default()),
+        }
+    }
+
+    fn checksum(&self) -> Checksum {
+        match self {
+            Hasher::Crc32(_) => Checksum::Crc32,
+            Hasher::Sha256(_) => Checksum::Sha256,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Crc32(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+        }
+    }
+
+    fn finish(self) -> Digest {
+        match self {
+            Hasher::Crc32(h) => Digest::Crc32(h.finalize()),
+            Hasher::Sha256(h) => Digest::Sha256(h.finalize().into()),
+        }
+    }
+}
+
+/// Shared setting for a [`HashingWriter`] that has already been handed to a
+/// ZIP writer
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChecksumSetting(Arc<AtomicU8>);
+
+impl ChecksumSetting {
+    pub(crate) fn set(&self, checksum: Option<Checksum>) {
+        let value = match checksum {
+            None => 0,
+            Some(Checksum::Crc32) => 1,
+            Some(Checksum::Sha256) => 2,
+        };
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    pub(crate) fn get(&self) -> Option<Checksum> {
+        match self.0.load(Ordering::Relaxed) {
+            1 => Some(Checksum::Crc32),
+            2 => Some(Checksum::Sha256),
+            _ => None,
+        }
+    }
+}
+
+/// Counters of a [`HashingWriter`] that has already been handed to a ZIP
+/// writer, possibly on another thread
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OutputStats {
+    bytes: Arc<AtomicU64>,
+    flushes: Arc<AtomicU64>,
+}
+
+impl OutputStats {
+    /// Size of the output so far
+    pub(crate) fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Times buffered bytes went to the file (or memory target)
+    pub(crate) fn flushes(&self) -> u64 {
+        self.flushes.load(Ordering::Relaxed)
+    }
+}
+
+/// Output file that counts and hashes bytes as they are written
+///
+/// Hashing only covers bytes appended in order. If the requested checksum
+/// changes after writing started, or earlier bytes are overwritten through
+/// `Seek`, the file is hashed from disk once on [`HashingWriter::finish`].
+pub(crate) struct HashingWriter {
+    inner: BufWriter<Target>,
+    setting: ChecksumSetting,
+    hasher: Option<Hasher>,
+    pos: u64,
+    hashed: u64,
+    len: u64,
+    stale: bool,
+    stats: OutputStats,
+}
+
+impl HashingWriter {
+    /// Create (or truncate) `path` for writing
+    pub(crate) fn create(path: &Path, setting: ChecksumSetting) -> Result<Self> {
+        // Readable too, for the rare case where the hash has to be redone
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(Self::with_target(Target::File(file), setting))
+    }
+
+    /// Write to a growable buffer instead of a file; see
+    /// [`into_bytes`](Self::into_bytes)
+    pub(crate) fn in_memory(setting: ChecksumSetting) -> Self {
+        Self::with_target(Target::Memory(io::Cursor::new(Vec::new())), setting)
+    }
+
+    fn with_target(target: Target, setting: ChecksumSetting) -> Self {
+        HashingWriter {
+            inner: BufWriter::with_capacity(64 * 1024, target),
+            setting,
+            hasher: None,
+            pos: 0,
+            hashed: 0,
+            len: 0,
+            stale: false,
+            stats: OutputStats::default(),
+        }
+    }
+
+    /// Counters that keep following this writer after it is moved
+    pub(crate) fn stats(&self) -> OutputStats {
+        self.stats.clone()
+    }
+
+    /// The bytes of an [`in_memory`](Self::in_memory) writer
+    pub(crate) fn into_bytes(self) -> Result<Vec<u8>> {
+        match self.inner.into_inner().map_err(|e| e.into_error())? {
+            Target::Memory(cursor) => Ok(cursor.into_inner()),
+            Target::File(_) => Err(crate::error::ExcelError::InvalidState(
+                "Output was written to a file, not to memory".to_string(),
+            )),
+        }
+    }
+
+    /// Flush the file and report its size and checksum
+    pub(crate) fn finish(mut self) -> Result<WriteReport> {
+        self.inner.flush()?;
+        let digest = match self.setting.get() {
+            None => None,
+            Some(checksum) => match self.hasher.take() {
+                Some(h) if h.checksum() == checksum && !self.stale && self.hashed == self.len => {
+                    Some(h.finish())
+                }
+                _ => Some(self.hash_from_disk(checksum)?),
+            },
+        };
+        Ok(WriteReport {
+            bytes_written: self.len,
+            digest,
+        })
+    }
+
+    fn hash_from_disk(&mut self, checksum: Checksum) -> Result<Digest> {
+        let target = self.inner.get_mut();
+        target.seek(SeekFrom::Start(0))?;
+        hash_reader(target, checksum)
+    }
+}
+
+/// Where a [`HashingWriter`] puts its bytes
+enum Target {
+    File(File),
+    Memory(io::Cursor<Vec<u8>>),
+}
+
+impl Read for Target {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Target::File(file) => file.read(buf),
+            Target::Memory(cursor) => cursor.read(buf),
+        }
+    }
+}
+
+impl Write for Target {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Target::File(file) => file.write(buf),
+            Target::Memory(cursor) => cursor.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Target::File(file) => file.flush(),
+            Target::Memory(_) => Ok(()),
+        }
+    }
+}
+
+impl Seek for Target {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Target::File(file) => file.seek(pos),
+            Target::Memory(cursor) => cursor.seek(pos),
+        }
+    }
+}
+
+/// Report for a file that was written by something that can't be wrapped in
+/// a [`HashingWriter`]; the checksum needs one extra read of the file
+pub(crate) fn read_back(path: &Path, checksum: Option<Checksum>) -> Result<WriteReport> {
+    let mut file = File::open(path)?;
+    let bytes_written = file.metadata()?.len();
+    let digest = match checksum {
+        Some(checksum) => Some(hash_reader(&mut file, checksum)?),
+        None => None,
+    };
+    Ok(WriteReport {
+        bytes_written,
+        digest,
+    })
+}
+
+fn hash_reader(reader: &mut impl Read, checksum: Checksum) -> Result<Digest> {
+    let mut hasher = Hasher::new(checksum);
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finish())
+}
+
+impl Write for HashingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let buffered = self.inner.buffer().len();
+        let n = self.inner.write(buf)?;
+        // The buffer didn't just grow by `n`: it was written out
+        if self.inner.buffer().len() != buffered + n {
+            self.stats.flushes.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(checksum) = self.setting.get() {
+            if self.hasher.as_ref().map(Hasher::checksum) != Some(checksum) {
+                self.hasher = Some(Hasher::new(checksum));
+                self.hashed = 0;
+            }
+            if self.pos == self.hashed {
+                if let Some(hasher) = &mut self.hasher {
+                    hasher.update(&buf[..n]);
+                }
+                self.hashed += n as u64;
+            } else {
+                self.stale = true;
+            }
+        }
+        self.pos += n as u64;
+        self.len = self.len.max(self.pos);
+        self.stats.bytes.store(self.len, Ordering::Relaxed);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.inner.buffer().is_empty() {
+            self.stats.flushes.fetch_add(1, Ordering::Relaxed);
+        }
+        self.inner.flush()
+    }
+}
+
+impl Seek for HashingWriter {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pos = self.inner.seek(pos)?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_hex() {
+        assert_eq!(Digest::Crc32(0xcbf43926).to_hex(), "cbf43926");
+        let digest = Digest::Sha256([0xab; 32]);
+        assert_eq!(digest.to_hex(), "ab".repeat(32));
+    }
+
+    #[test]
+    fn test_hashing_writer() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("out.bin");
+
+        let setting = ChecksumSetting::default();
+        setting.set(Some(Checksum::Crc32));
+        let mut writer = HashingWriter::create(&path, setting).unwrap();
+        writer.write_all(b"1234").unwrap();
+        writer.write_all(b"56789").unwrap();
+        let report = writer.finish().unwrap();
+        assert_eq!(report.bytes_written, 9);
+        assert_eq!(report.digest, Some(Digest::Crc32(0xcbf43926)));
+    }
+
+    #[test]
+    fn test_hashing_writer_rehashes_after_seek() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("out.bin");
+
+        let setting = ChecksumSetting::default();
+        let mut writer = HashingWriter::create(&path, setting.clone()).unwrap();
+        writer.write_all(b"xxxx").unwrap();
+        // Requested late, and the start is patched afterwards
+        setting.set(Some(Checksum::Sha256));
+        writer.write_all(b"56789").unwrap();
+        writer.seek(SeekFrom::Start(0)).unwrap();
+        writer.write_all(b"1234").unwrap();
+        let report = writer.finish().unwrap();
+
+        let expected: [u8; 32] = Sha256::digest(b"123456789").into();
+        assert_eq!(report.bytes_written, 9);
+        assert_eq!(report.digest, Some(Digest::Sha256(expected)));
+    }
+}
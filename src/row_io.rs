@@ -0,0 +1,239 @@
+//! Format-independent row sources and sinks
+//!
+//! [`RowSource`] is implemented by the readers ([`StreamingReader`],
+//! [`CsvReader`], and with their features `ParquetReader` and
+//! `JsonlReader`), [`RowSink`] by the writers. [`copy`] connects any source
+//! to any sink, so a conversion is a single call and a custom source (a
+//! database cursor, an API client) can feed every output format.
+//!
+//! ```no_run
+//! use excelstream::row_io::copy;
+//! use excelstream::{CsvReader, ExcelWriter};
+//!
+//! let mut reader = CsvReader::open("orders.csv")?.has_header(true);
+//! let rows = copy(&mut reader, ExcelWriter::new("orders.xlsx")?)?;
+//! println!("{} rows", rows);
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+//!
+//! [`StreamingReader`]: crate::streaming_reader::StreamingReader
+//! [`CsvReader`]: crate::csv_reader::CsvReader
+
+use crate::csv_writer::CsvWriter;
+use crate::error::Result;
+use crate::fast_writer::UltraLowMemoryWorkbook;
+use crate::types::{CellStyle, CellValue, StyledCell};
+use crate::writer::ExcelWriter;
+
+/// Something rows can be read from
+///
+/// # Example
+///
+/// A source over rows already in memory:
+///
+/// ```
+/// use excelstream::row_io::RowSource;
+/// use excelstream::types::CellValue;
+/// use excelstream::Result;
+///
+/// struct Memory(Vec<Vec<CellValue>>);
+///
+/// impl RowSource for Memory {
+///     fn schema(&mut self) -> Result<Option<Vec<String>>> {
+///         Ok(Some(vec!["id".to_string()]))
+///     }
+///
+///     fn rows(&mut self) -> Result<impl Iterator<Item = Result<Vec<CellValue>>> + '_> {
+///         Ok(self.0.drain(..).map(Ok))
+///     }
+/// }
+/// ```
+pub trait RowSource {
+    /// Column names, when the source has them apart from its rows
+    ///
+    /// A CSV header or a Parquet schema is returned here and is not part of
+    /// [`rows`](Self::rows). A worksheet has no separate header: its first
+    /// row is data, and this returns `None`.
+    fn schema(&mut self) -> Result<Option<Vec<String>>>;
+
+    /// The remaining rows
+    fn rows(&mut self) -> Result<impl Iterator<Item = Result<Vec<CellValue>>> + '_>;
+}
+
+/// Something rows can be written to
+pub trait RowSink {
+    /// Write the column names of the source
+    ///
+    /// Called at most once, before any row.
+    fn write_schema(&mut self, names: &[String]) -> Result<()>;
+
+    /// Write one row
+    fn write_cells(&mut self, cells: &[CellValue]) -> Result<()>;
+
+    /// Finish the output, e.g. save the file
+    fn finish(self) -> Result<()>
+    where
+        Self: Sized;
+}
+
+/// Write the schema and every row of `source` to `sink`, then finish the
+/// sink, returning the number of rows copied (not counting the schema)
+pub fn copy<S, K>(source: &mut S, mut sink: K) -> Result<u64>
+where
+    S: RowSource,
+    K: RowSink,
+{
+    if let Some(names) = source.schema()? {
+        sink.write_schema(&names)?;
+    }
+    let mut copied = 0;
+    for row in source.rows()? {
+        sink.write_cells(&row?)?;
+        copied += 1;
+    }
+    sink.finish()?;
+    Ok(copied)
+}
+
+/// The schema is written in bold; date cells get a date format
+impl RowSink for ExcelWriter {
+    fn write_schema(&mut self, names: &[String]) -> Result<()> {
+        self.write_header_bold(names)
+    }
+
+    fn write_cells(&mut self, cells: &[CellValue]) -> Result<()> {
+        let cells: Vec<(CellValue, CellStyle)> = cells
+            .iter()
+            .map(|value| (value.clone(), value.default_style()))
+            .collect();
+        self.write_row_styled(&cells)
+    }
+
+    fn finish(self) -> Result<()> {
+        self.save()
+    }
+}
+
+/// Rows go to the current worksheet, which must have been added; the schema
+/// is written in bold and date cells get a date format
+impl RowSink for UltraLowMemoryWorkbook {
+    fn write_schema(&mut self, names: &[String]) -> Result<()> {
+        let cells: Vec<StyledCell> = names
+            .iter()
+            .map(|name| StyledCell::new(CellValue::String(name.clone()), CellStyle::HeaderBold))
+            .collect();
+        self.write_row_styled(&cells)
+    }
+
+    fn write_cells(&mut self, cells: &[CellValue]) -> Result<()> {
+        let cells: Vec<StyledCell> = cells
+            .iter()
+            .map(|value| StyledCell::new(value.clone(), value.default_style()))
+            .collect();
+        self.write_row_styled(&cells)
+    }
+
+    fn finish(self) -> Result<()> {
+        self.close()
+    }
+}
+
+/// The schema is the first record; cells are written as by
+/// [`CsvWriter::write_row_typed`]
+impl RowSink for CsvWriter {
+    fn write_schema(&mut self, names: &[String]) -> Result<()> {
+        self.write_row(names)
+    }
+
+    fn write_cells(&mut self, cells: &[CellValue]) -> Result<()> {
+        self.write_row_typed(cells)
+    }
+
+    fn finish(self) -> Result<()> {
+        self.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::SheetSelector;
+    use crate::csv_reader::CsvReader;
+    use crate::streaming_reader::StreamingReader;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Source over generated rows, as a user would plug in
+    struct Counter {
+        rows: i64,
+    }
+
+    impl RowSource for Counter {
+        fn schema(&mut self) -> Result<Option<Vec<String>>> {
+            Ok(Some(vec!["n".to_string(), "square".to_string()]))
+        }
+
+        fn rows(&mut self) -> Result<impl Iterator<Item = Result<Vec<CellValue>>> + '_> {
+            Ok((1..=self.rows).map(|n| Ok(vec![CellValue::Int(n), CellValue::Int(n * n)])))
+        }
+    }
+
+    #[test]
+    fn test_copy_custom_source() {
+        let dir = TempDir::new().unwrap();
+        let xlsx = dir.path().join("squares.xlsx");
+        let csv = dir.path().join("squares.csv");
+
+        let copied = copy(&mut Counter { rows: 3 }, ExcelWriter::new(&xlsx).unwrap()).unwrap();
+        assert_eq!(copied, 3);
+        let copied = copy(&mut Counter { rows: 3 }, CsvWriter::new(&csv).unwrap()).unwrap();
+        assert_eq!(copied, 3);
+
+        // A worksheet has no schema: the header comes back as the first row
+        let mut reader = StreamingReader::open(&xlsx).unwrap();
+        assert_eq!(reader.schema().unwrap(), None);
+        let rows: Vec<Vec<CellValue>> = RowSource::rows(&mut reader)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0][0], CellValue::String("n".to_string()));
+        assert_eq!(rows[3], vec![CellValue::Int(3), CellValue::Int(9)]);
+
+        assert_eq!(
+            fs::read_to_string(&csv).unwrap().replace("\r\n", "\n"),
+            "n,square\n1,1\n2,4\n3,9\n"
+        );
+    }
+
+    #[test]
+    fn test_copy_csv_to_xlsx_and_back() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in.csv");
+        let xlsx = dir.path().join("out.xlsx");
+        let output = dir.path().join("out.csv");
+        fs::write(&input, "name,city\nAlice,Paris\nBob,\n").unwrap();
+
+        let mut reader = CsvReader::open(&input).unwrap().has_header(true);
+        assert_eq!(
+            reader.schema().unwrap(),
+            Some(vec!["name".to_string(), "city".to_string()])
+        );
+        let mut workbook = UltraLowMemoryWorkbook::new(&xlsx).unwrap();
+        workbook.add_worksheet("People").unwrap();
+        assert_eq!(copy(&mut reader, workbook).unwrap(), 2);
+
+        let mut reader = StreamingReader::open(&xlsx).unwrap();
+        let mut source = reader
+            .sheet_source(SheetSelector::Name("People".to_string()))
+            .unwrap();
+        assert_eq!(
+            copy(&mut source, CsvWriter::new(&output).unwrap()).unwrap(),
+            3
+        );
+        assert_eq!(
+            fs::read_to_string(&output).unwrap().replace("\r\n", "\n"),
+            "name,city\nAlice,Paris\nBob,\n"
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! Excel's rules for worksheet names
+//!
+//! Excel refuses, or "repairs" on open, a workbook with a sheet name that is
+//! empty, longer than 31 characters, contains one of `[ ] : * ? / \`, starts
+//! or ends with an apostrophe, is `History`, or matches another sheet's name
+//! ignoring case. Writers check every sheet name when the sheet is added and,
+//! depending on their [`SheetNamePolicy`], return
+//! [`ExcelError::InvalidSheetName`] or fix the name.
+//!
+//! ```
+//! use excelstream::sheet_name::{resolve, sanitize};
+//! use excelstream::SheetNamePolicy;
+//!
+//! assert_eq!(sanitize("Q1/Q2 [draft]"), "Q1_Q2 _draft_");
+//!
+//! let existing = vec!["Report".to_string()];
+//! assert!(resolve("report", &existing, SheetNamePolicy::Strict).is_err());
+//! assert_eq!(
+//!     resolve("report", &existing, SheetNamePolicy::Sanitize).unwrap(),
+//!     "report (2)"
+//! );
+//! ```
+
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::xml_writer::is_xml_char;
+use crate::types::SheetNamePolicy;
+
+/// Longest sheet name Excel accepts, in UTF-16 code units
+pub const MAX_SHEET_NAME_LEN: usize = 31;
+
+/// Most rows a sheet can hold
+pub const MAX_SHEET_ROWS: u32 = 1_048_576;
+
+/// Most columns a sheet can hold, A to XFD
+pub const MAX_SHEET_COLUMNS: u32 = 16_384;
+
+/// Characters Excel doesn't allow in sheet names
+const INVALID_CHARS: [char; 7] = ['[', ']', ':', '*', '?', '/', '\\'];
+
+/// Name Excel keeps for its change history sheet
+const RESERVED_NAME: &str = "History";
+
+/// Check `name` against Excel's rules, other than uniqueness
+pub fn validate(name: &str) -> Result<()> {
+    let invalid = |reason: String| {
+        Err(ExcelError::InvalidSheetName {
+            name: name.to_string(),
+            reason,
+        })
+    };
+
+    if name.is_empty() {
+        return invalid("the name is empty".to_string());
+    }
+    let len = utf16_len(name);
+    if len > MAX_SHEET_NAME_LEN {
+        return invalid(format!(
+            "{} characters, at most {} are allowed",
+            len, MAX_SHEET_NAME_LEN
+        ));
+    }
+    if let Some(c) = name.chars().find(|&c| is_invalid_char(c)) {
+        return invalid(format!("{:?} is not allowed", c));
+    }
+    if name.starts_with('\'') || name.ends_with('\'') {
+        return invalid("the name can't start or end with an apostrophe".to_string());
+    }
+    if name.eq_ignore_ascii_case(RESERVED_NAME) {
+        return invalid("the name is reserved by Excel".to_string());
+    }
+    Ok(())
+}
+
+/// `name` made valid: invalid and control characters become `_`, leading
+/// and trailing apostrophes are dropped and the result is cut to 31
+/// characters without splitting a character
+///
+/// An empty name becomes `Sheet`.
+pub fn sanitize(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| if is_invalid_char(c) { '_' } else { c })
+        .collect();
+    let mut name = truncate(replaced.trim_matches('\''), MAX_SHEET_NAME_LEN);
+    // Cutting can leave an apostrophe at the end
+    while name.ends_with('\'') {
+        name.pop();
+    }
+
+    if name.is_empty() {
+        "Sheet".to_string()
+    } else if name.eq_ignore_ascii_case(RESERVED_NAME) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// Whether Excel treats `a` and `b` as the same sheet name
+///
+/// Comparison ignores case, including outside ASCII (`Ärger` and `ärger`
+/// clash).
+pub fn same_name(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+/// Name to give a new sheet called `name` in a workbook that already has
+/// the sheets `existing`
+///
+/// With [`SheetNamePolicy::Strict`] this is `name` itself, or an error if
+/// it breaks a rule or is taken. With [`SheetNamePolicy::Sanitize`] the
+/// name is [`sanitize`]d and, if taken, suffixed with ` (2)`, ` (3)`, ...
+/// the way Excel names copied sheets.
+pub fn resolve(name: &str, existing: &[String], policy: SheetNamePolicy) -> Result<String> {
+    let taken = |candidate: &str| existing.iter().any(|other| same_name(other, candidate));
+
+    match policy {
+        SheetNamePolicy::Strict => {
+            validate(name)?;
+            if taken(name) {
+                return Err(ExcelError::InvalidSheetName {
+                    name: name.to_string(),
+                    reason: "a sheet with this name already exists".to_string(),
+                });
+            }
+            Ok(name.to_string())
+        }
+        SheetNamePolicy::Sanitize => {
+            let base = sanitize(name);
+            if !taken(&base) {
+                return Ok(base);
+            }
+            // existing.len() + 2 suffixes can't all be taken
+            for n in 2..existing.len() + 2 {
+                let suffix = format!(" ({})", n);
+                let stem = truncate(&base, MAX_SHEET_NAME_LEN - suffix.len());
+                let candidate = format!("{}{}", stem.trim_end_matches('\''), suffix);
+                if !taken(&candidate) {
+                    return Ok(candidate);
+                }
+            }
+            unreachable!("one of {} suffixes is free", existing.len())
+        }
+    }
+}
+
+/// Name of shard `number` (2, 3, ...) of the sheet `base`: `Data_2`
+fn shard_name(base: &str, number: u32) -> String {
+    let suffix = format!("_{}", number);
+    let stem = truncate(base, MAX_SHEET_NAME_LEN - suffix.len());
+    format!("{}{}", stem, suffix)
+}
+
+/// Where a writer with `auto_shard_rows` stands in rolling rows over to
+/// new sheets
+///
+/// `H` is the sheet's first row as the writer saved it, repeated at the
+/// top of every shard.
+#[derive(Debug)]
+pub(crate) struct Shards<H = Vec<u8>> {
+    /// Rows per sheet; `None` fails once a sheet is full instead
+    rows: Option<u32>,
+    /// Row 1 of the current sheet, repeated at the top of the next shard
+    header: Option<H>,
+    /// Sheet being sharded and the number of its last shard
+    base: Option<(String, u32)>,
+}
+
+/// Sheet to continue on, from [`Shards::next`]
+pub(crate) struct NextShard<H> {
+    /// Name of the new sheet, not used by any other sheet
+    pub(crate) name: String,
+    base: String,
+    number: u32,
+    header: Option<H>,
+}
+
+impl<H: Clone> Shards<H> {
+    pub(crate) fn new(rows: Option<u32>) -> Self {
+        Shards {
+            rows,
+            header: None,
+            base: None,
+        }
+    }
+
+    /// Roll over after `rows` rows per sheet, see [`check_shard_rows`]
+    pub(crate) fn set_rows(&mut self, rows: u32) -> Result<()> {
+        self.rows = Some(check_shard_rows(rows)?);
+        Ok(())
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.rows.is_some()
+    }
+
+    /// Rows a sheet can hold
+    pub(crate) fn limit(&self) -> u32 {
+        self.rows.unwrap_or(MAX_SHEET_ROWS)
+    }
+
+    /// Whether a sheet holding `rows` rows takes no more
+    pub(crate) fn is_full(&self, rows: u32) -> bool {
+        rows >= self.limit()
+    }
+
+    /// Forget the sheet being sharded, once another sheet is started
+    pub(crate) fn reset(&mut self) {
+        self.header = None;
+        self.base = None;
+    }
+
+    /// Keep `header()` as the header of the next shards if `row` is row 1
+    pub(crate) fn keep_header(&mut self, row: u32, header: impl FnOnce() -> H) {
+        if self.rows.is_some() && row == 1 {
+            self.header = Some(header());
+        }
+    }
+
+    /// The sheet to continue on now that `sheet` is full
+    ///
+    /// Shards are named after the sheet sharding started on: `Data_2`,
+    /// `Data_3`, ... Numbers whose name one of the `existing` sheets
+    /// already has are skipped. Fails with [`sheet_full`] when the writer
+    /// doesn't shard.
+    pub(crate) fn next(&mut self, sheet: &str, existing: &[String]) -> Result<NextShard<H>> {
+        if self.rows.is_none() {
+            return Err(sheet_full(sheet));
+        }
+        let (base, mut number) = self.base.take().unwrap_or((sheet.to_string(), 1));
+        // existing.len() + 1 numbers can't all be taken
+        let name = loop {
+            number += 1;
+            let name = shard_name(&base, number);
+            if !existing.iter().any(|other| same_name(other, &name)) {
+                break name;
+            }
+        };
+        Ok(NextShard {
+            name,
+            base,
+            number,
+            header: self.header.take(),
+        })
+    }
+
+    /// Carry on sharding on the sheet just started for `next`; returns the
+    /// header to write as its row 1
+    pub(crate) fn started(&mut self, next: NextShard<H>) -> Option<H> {
+        self.base = Some((next.base, next.number));
+        self.header = next.header.clone();
+        next.header
+    }
+}
+
+/// `max_rows` if it is a usable `auto_shard_rows` limit: room for the
+/// repeated header and at least one row, within [`MAX_SHEET_ROWS`]
+pub(crate) fn check_shard_rows(max_rows: u32) -> Result<u32> {
+    if (2..=MAX_SHEET_ROWS).contains(&max_rows) {
+        Ok(max_rows)
+    } else {
+        Err(ExcelError::WriteError(format!(
+            "auto_shard_rows must be between 2 and {}, not {}",
+            MAX_SHEET_ROWS, max_rows
+        )))
+    }
+}
+
+/// Fails with [`ExcelError::TooManyColumns`] unless `count` columns fit on
+/// a sheet
+pub(crate) fn check_columns(count: usize) -> Result<()> {
+    if count > MAX_SHEET_COLUMNS as usize {
+        return Err(ExcelError::TooManyColumns {
+            column: count as u64,
+        });
+    }
+    Ok(())
+}
+
+/// Error for a row that doesn't fit on the sheet `name` any more
+pub(crate) fn sheet_full(name: &str) -> ExcelError {
+    ExcelError::WriteError(format!(
+        "Sheet '{}' is full: Excel allows {} rows per sheet (see auto_shard_rows)",
+        name, MAX_SHEET_ROWS
+    ))
+}
+
+/// Excel's forbidden characters, control characters and ones XML can't hold
+fn is_invalid_char(c: char) -> bool {
+    INVALID_CHARS.contains(&c) || c.is_control() || !is_xml_char(c)
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.chars().map(char::len_utf16).sum()
+}
+
+/// Longest prefix of `s` that is at most `max` UTF-16 code units long
+fn truncate(s: &str, max: usize) -> String {
+    let mut len = 0;
+    s.chars()
+        .take_while(|c| {
+            len += c.len_utf16();
+            len <= max
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(validate("Sales 2024").is_ok());
+        assert!(validate("Données – été").is_ok());
+        assert!(validate(&"a".repeat(31)).is_ok());
+        // 16 emoji are 32 UTF-16 code units
+        assert!(validate(&"😀".repeat(15)).is_ok());
+        assert!(validate(&"😀".repeat(16)).is_err());
+
+        for bad in [
+            "", "a/b", "[x]", "x:y", "*", "q?", "a\\b", "'quoted'", "tab\t", "\u{fffe}", "HISTORY",
+        ] {
+            let err = validate(bad).unwrap_err();
+            assert!(
+                matches!(err, ExcelError::InvalidSheetName { .. }),
+                "{}: {}",
+                bad,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("Q1/Q2 [draft]"), "Q1_Q2 _draft_");
+        assert_eq!(sanitize("'quoted'"), "quoted");
+        assert_eq!(sanitize(""), "Sheet");
+        assert_eq!(sanitize("''"), "Sheet");
+        assert_eq!(sanitize("history"), "history_");
+        assert_eq!(sanitize(&"x".repeat(40)), "x".repeat(31));
+        // Never splits a surrogate pair
+        assert_eq!(
+            sanitize(&format!("a{}", "😀".repeat(20))),
+            format!("a{}", "😀".repeat(15))
+        );
+        for name in ["a:b", &"é".repeat(50), "'", "x\n"] {
+            assert!(validate(&sanitize(name)).is_ok(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let existing = vec!["Sheet1".to_string(), "Ärger".to_string()];
+        assert_eq!(
+            resolve("Data", &existing, SheetNamePolicy::Strict).unwrap(),
+            "Data"
+        );
+        assert!(resolve("sheet1", &existing, SheetNamePolicy::Strict).is_err());
+        assert!(resolve("ärger", &existing, SheetNamePolicy::Strict).is_err());
+        assert!(resolve("a/b", &existing, SheetNamePolicy::Strict).is_err());
+
+        assert_eq!(
+            resolve("SHEET1", &existing, SheetNamePolicy::Sanitize).unwrap(),
+            "SHEET1 (2)"
+        );
+        let mut existing = vec!["x".repeat(31)];
+        let second = resolve(&"x".repeat(40), &existing, SheetNamePolicy::Sanitize).unwrap();
+        assert_eq!(second, format!("{} (2)", "x".repeat(27)));
+        existing.push(second);
+        let third = resolve(&"x".repeat(31), &existing, SheetNamePolicy::Sanitize).unwrap();
+        assert_eq!(third, format!("{} (3)", "x".repeat(27)));
+    }
+
+    #[test]
+    fn test_shard_name() {
+        assert_eq!(shard_name("Data", 2), "Data_2");
+        assert_eq!(
+            shard_name(&"x".repeat(31), 12),
+            format!("{}_12", "x".repeat(28))
+        );
+        assert!(check_shard_rows(1).is_err());
+        assert!(check_shard_rows(MAX_SHEET_ROWS + 1).is_err());
+        assert_eq!(check_shard_rows(1000).unwrap(), 1000);
+    }
+
+    #[test]
+    fn test_next_shard_skips_taken_names() {
+        let mut shards: Shards = Shards::new(Some(10));
+        shards.keep_header(1, || b"<row/>".to_vec());
+        let mut existing = vec!["Data".to_string(), "data_2".to_string()];
+
+        let next = shards.next("Data", &existing).unwrap();
+        assert_eq!(next.name, "Data_3");
+        existing.push(next.name.clone());
+        assert_eq!(shards.started(next), Some(b"<row/>".to_vec()));
+
+        existing.push("Data_4".to_string());
+        let next = shards.next("Data_3", &existing).unwrap();
+        assert_eq!(next.name, "Data_5");
+
+        let mut unlimited: Shards = Shards::new(None);
+        assert!(!unlimited.is_full(MAX_SHEET_ROWS - 1));
+        assert!(unlimited.is_full(MAX_SHEET_ROWS));
+        assert!(unlimited.next("Data", &existing).is_err());
+    }
+}
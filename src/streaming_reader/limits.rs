@@ -0,0 +1,287 @@
+//! Limits on what a package may make the reader decompress and keep
+//!
+//! Parts are streamed through [`LimitedReader`], which counts what is
+//! actually decompressed, and [`PrefixStripper`], which lets the parsers
+//! read SpreadsheetML bound to a namespace prefix.
+
+use super::MAX_COL;
+use crate::error::ExcelError;
+use std::io::Read;
+
+/// Limits that stop a hostile file (a zip bomb, a shared string table with
+/// millions of entries, a row with millions of cells) from taking unbounded
+/// memory or time
+///
+/// Going past a limit fails with [`ExcelError::LimitExceeded`]. The defaults
+/// fit nearly any file Excel writes while keeping the parts held in memory
+/// to a few hundred MiB; services reading untrusted uploads should
+/// lower them to what they expect.
+///
+/// ```no_run
+/// use excelstream::streaming_reader::{ReadLimits, ReadOptions, StreamingReader};
+///
+/// let options = ReadOptions {
+///     limits: ReadLimits {
+///         max_entry_size: 200 * 1024 * 1024,
+///         max_shared_strings: 1_000_000,
+///         ..ReadLimits::default()
+///     },
+///     ..ReadOptions::default()
+/// };
+/// let mut reader = StreamingReader::open_with_options("upload.xlsx", options)?;
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadLimits {
+    /// Largest uncompressed size of a part that is read whole into memory
+    /// (shared strings, workbook, relationships, styles), in bytes
+    /// (default: 256 MiB)
+    pub max_entry_size: u64,
+    /// Largest uncompressed size of a worksheet streamed row by row, in
+    /// bytes (default: 4 GiB)
+    pub max_sheet_size: u64,
+    /// Largest ratio of a part's uncompressed to compressed size (default:
+    /// 100)
+    ///
+    /// Only checked once a part has produced more than 1 MiB, so small,
+    /// highly repetitive parts don't trip it.
+    pub max_compression_ratio: u64,
+    /// Most entries in the shared string table (default: 16,777,216)
+    pub max_shared_strings: usize,
+    /// Most cells in one row, counting the empty cells before a cell
+    /// (default: 16,384, Excel's column count)
+    pub max_cells_per_row: usize,
+}
+
+impl Default for ReadLimits {
+    fn default() -> Self {
+        ReadLimits {
+            max_entry_size: 256 << 20,
+            max_sheet_size: 4 << 30,
+            max_compression_ratio: 100,
+            max_shared_strings: 1 << 24,
+            max_cells_per_row: MAX_COL as usize + 1,
+        }
+    }
+}
+
+impl ReadLimits {
+    /// No limits, for files from a trusted source
+    pub fn unlimited() -> Self {
+        ReadLimits {
+            max_entry_size: u64::MAX,
+            max_sheet_size: u64::MAX,
+            max_compression_ratio: u64::MAX,
+            max_shared_strings: usize::MAX,
+            max_cells_per_row: usize::MAX,
+        }
+    }
+}
+
+/// Bytes a part may produce before its compression ratio is checked
+const RATIO_GRACE_BYTES: u64 = 1 << 20;
+
+/// Decompressed part that fails with [`ExcelError::LimitExceeded`] once it
+/// produces more than [`ReadLimits`] allow
+pub(super) struct LimitedReader<R> {
+    pub(super) inner: R,
+    pub(super) part: String,
+    pub(super) compressed_size: u64,
+    pub(super) limits: ReadLimits,
+    /// `ReadLimits` field bounding the part's size, and its value
+    pub(super) size_limit: (&'static str, u64),
+    pub(super) read: u64,
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        let limit = if self.read > self.size_limit.1 {
+            Some(self.size_limit)
+        } else if self.read > RATIO_GRACE_BYTES
+            && self.read / self.compressed_size.max(1) > self.limits.max_compression_ratio
+        {
+            Some(("max_compression_ratio", self.limits.max_compression_ratio))
+        } else {
+            None
+        };
+        match limit {
+            Some((limit, max)) => Err(std::io::Error::other(ExcelError::LimitExceeded {
+                part: self.part.clone(),
+                limit: limit.to_string(),
+                max,
+            })),
+            None => Ok(n),
+        }
+    }
+}
+
+/// Part reader that drops the namespace prefix of the root element from
+/// the elements that use it, so `<x:row>` reads as `<row>`
+///
+/// Some generators bind the SpreadsheetML namespace to a prefix instead of
+/// making it the default; the parsers only look for unprefixed names.
+pub(super) struct PrefixStripper<R> {
+    inner: R,
+    /// `None` until the root element has been seen
+    prefix: Option<Vec<u8>>,
+    /// Read but not yet returned, or not yet complete enough to rewrite
+    input: Vec<u8>,
+    output: Vec<u8>,
+    output_pos: usize,
+    eof: bool,
+}
+
+impl<R: Read> PrefixStripper<R> {
+    pub(super) fn new(inner: R) -> Self {
+        PrefixStripper {
+            inner,
+            prefix: None,
+            input: Vec::new(),
+            output: Vec::new(),
+            output_pos: 0,
+            eof: false,
+        }
+    }
+
+    /// Prefix of the root element, once its name is complete in `input`
+    fn root_prefix(input: &[u8]) -> Option<Vec<u8>> {
+        let mut pos = 0;
+        loop {
+            let start = pos + input[pos..].iter().position(|&b| b == b'<')?;
+            match input.get(start + 1)? {
+                // Declaration, comment or doctype before the root
+                b'?' | b'!' => {
+                    pos = start + 1 + input[start + 1..].iter().position(|&b| b == b'>')?;
+                }
+                _ => {
+                    let name = &input[start + 1..];
+                    let end = name
+                        .iter()
+                        .position(|&b| b.is_ascii_whitespace() || b == b'>' || b == b'/')?;
+                    return Some(match name[..end].iter().position(|&b| b == b':') {
+                        Some(colon) => name[..colon].to_vec(),
+                        None => Vec::new(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Move `input` to `output`, rewriting prefixed tags; with `all` false a
+    /// tail that may be the start of a tag split across reads is kept
+    fn rewrite(&mut self, all: bool) {
+        let prefix = self.prefix.as_deref().unwrap_or_default();
+        let keep = if all || prefix.is_empty() {
+            0
+        } else {
+            let tail = self.input.len().saturating_sub(prefix.len() + 2);
+            match self.input[tail..].iter().rposition(|&b| b == b'<') {
+                Some(at) => self.input.len() - tail - at,
+                None => 0,
+            }
+        };
+        let end = self.input.len() - keep;
+
+        self.output.clear();
+        self.output_pos = 0;
+        if prefix.is_empty() {
+            self.output.extend_from_slice(&self.input[..end]);
+        } else {
+            let mut pos = 0;
+            while pos < end {
+                let byte = self.input[pos];
+                self.output.push(byte);
+                pos += 1;
+                if byte != b'<' {
+                    continue;
+                }
+                let name_start = if self.input.get(pos) == Some(&b'/') {
+                    self.output.push(b'/');
+                    pos + 1
+                } else {
+                    pos
+                };
+                let rest = &self.input[name_start..end];
+                if rest.starts_with(prefix) && rest.get(prefix.len()) == Some(&b':') {
+                    pos = name_start + prefix.len() + 1;
+                } else {
+                    pos = name_start;
+                }
+            }
+        }
+        self.input.drain(..end);
+    }
+}
+
+impl<R: Read> Read for PrefixStripper<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.output_pos == self.output.len() {
+            if self.eof && self.input.is_empty() {
+                return Ok(0);
+            }
+            // Once known to be unprefixed, skip the copies
+            if self.prefix.as_ref().is_some_and(Vec::is_empty) && self.input.is_empty() {
+                return self.inner.read(buf);
+            }
+            if !self.eof {
+                let mut chunk = [0u8; 32 * 1024];
+                let n = self.inner.read(&mut chunk)?;
+                self.eof = n == 0;
+                self.input.extend_from_slice(&chunk[..n]);
+            }
+            if self.prefix.is_none() {
+                self.prefix = Self::root_prefix(&self.input);
+                // Not XML as far as can be told: pass it through
+                if self.prefix.is_none() && self.input.len() > 64 * 1024 {
+                    self.prefix = Some(Vec::new());
+                }
+                if self.prefix.is_none() && !self.eof {
+                    continue;
+                }
+            }
+            self.rewrite(self.eof);
+        }
+        let n = buf.len().min(self.output.len() - self.output_pos);
+        buf[..n].copy_from_slice(&self.output[self.output_pos..self.output_pos + n]);
+        self.output_pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_stripper_split_reads() {
+        /// Hands out `n` bytes per read
+        struct Trickle<'a>(&'a [u8], usize);
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = self.1.min(buf.len()).min(self.0.len());
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+
+        let xml = r#"<?xml version="1.0"?><!-- x:no --><ss:sst xmlns:ss="ns"><ss:si><ss:t>a&lt;ss:b</ss:t></ss:si><ssx:t/><a:b/></ss:sst>"#;
+        let expected = r#"<?xml version="1.0"?><!-- x:no --><sst xmlns:ss="ns"><si><t>a&lt;ss:b</t></si><ssx:t/><a:b/></sst>"#;
+        for n in [1, 2, 3, 7, 4096] {
+            let mut out = String::new();
+            PrefixStripper::new(Trickle(xml.as_bytes(), n))
+                .read_to_string(&mut out)
+                .unwrap();
+            assert_eq!(out, expected, "{} byte reads", n);
+        }
+
+        let plain = "<worksheet><sheetData/></worksheet>";
+        let mut out = String::new();
+        PrefixStripper::new(Trickle(plain.as_bytes(), 3))
+            .read_to_string(&mut out)
+            .unwrap();
+        assert_eq!(out, plain);
+    }
+}
@@ -0,0 +1,400 @@
+//! Locating the parts of a package: the workbook, its relationships, the
+//! shared string table and the worksheets, plus the small XML helpers used
+//! to read them
+
+use super::limits::{LimitedReader, PrefixStripper};
+use super::{ReadLimits, StreamingReader};
+use crate::archive::Archive;
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::ZipEntry;
+use crate::trace;
+use crate::validate::resolve_target;
+use std::io::Read;
+
+pub(super) const WORKBOOK_PART: &str = "xl/workbook.xml";
+const WORKBOOK_RELS_PART: &str = "xl/_rels/workbook.xml.rels";
+
+/// Whether the package holds a VBA project, usually `xl/vbaProject.bin`
+pub(crate) fn has_vba_project(entries: &[ZipEntry]) -> bool {
+    entries
+        .iter()
+        .any(|entry| entry.name.ends_with("/vbaProject.bin"))
+}
+
+/// Stream the part `name` of `archive`, within `limits`; the part is
+/// bounded by `max_entry_size`, as it is usually read whole
+pub(super) fn open_part<'a>(
+    archive: &'a mut Archive,
+    name: &str,
+    limits: &ReadLimits,
+) -> Result<Box<dyn Read + 'a>> {
+    open_part_within(
+        archive,
+        name,
+        limits,
+        ("max_entry_size", limits.max_entry_size),
+    )
+}
+
+/// Stream the worksheet `name` of `archive`, bounded by `max_sheet_size`
+pub(super) fn open_sheet_part<'a>(
+    archive: &'a mut Archive,
+    name: &str,
+    limits: &ReadLimits,
+) -> Result<Box<dyn Read + 'a>> {
+    open_part_within(
+        archive,
+        name,
+        limits,
+        ("max_sheet_size", limits.max_sheet_size),
+    )
+}
+
+fn open_part_within<'a>(
+    archive: &'a mut Archive,
+    name: &str,
+    limits: &ReadLimits,
+    size_limit: (&'static str, u64),
+) -> Result<Box<dyn Read + 'a>> {
+    let entry = archive
+        .entries()
+        .iter()
+        .find(|entry| entry.name == name)
+        .cloned()
+        .ok_or_else(|| ExcelError::ZipEntryMissing {
+            path: name.to_string(),
+        })?;
+    // The sizes in the archive can lie, so they are only trusted to fail
+    // early; LimitedReader counts what is actually decompressed
+    if entry.uncompressed_size > size_limit.1 {
+        return Err(ExcelError::LimitExceeded {
+            part: name.to_string(),
+            limit: size_limit.0.to_string(),
+            max: size_limit.1,
+        });
+    }
+    trace::debug!(
+        part = name,
+        compressed = entry.compressed_size,
+        uncompressed = entry.uncompressed_size,
+        "opening zip entry"
+    );
+    let inner = archive.read_entry_streaming(&entry)?;
+    Ok(Box::new(PrefixStripper::new(LimitedReader {
+        inner,
+        part: name.to_string(),
+        compressed_size: entry.compressed_size,
+        limits: *limits,
+        size_limit,
+        read: 0,
+    })))
+}
+
+/// The whole part `name` of `archive`, within `limits`
+pub(crate) fn read_part(archive: &mut Archive, name: &str, limits: &ReadLimits) -> Result<Vec<u8>> {
+    let _span = trace::span!("read_zip_entry", part = name);
+    let mut data = Vec::new();
+    open_part(archive, name, limits)?.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Some tools write a byte order mark before the XML declaration
+pub(super) const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Text of an XML part, without a leading BOM
+pub(crate) fn xml_text(data: &[u8]) -> String {
+    let data = data.strip_prefix(UTF8_BOM).unwrap_or(data);
+    String::from_utf8_lossy(data).into_owned()
+}
+
+/// Start tags (`<name ...>` or `<name .../>`) of an element in document order
+pub(super) fn element_tags<'a>(xml: &'a str, name: &str) -> impl Iterator<Item = &'a str> {
+    let open = format!("<{}", name);
+    let mut pos = 0;
+    std::iter::from_fn(move || loop {
+        let start = pos + xml[pos..].find(&open)?;
+        let end = start + xml[start..].find('>')? + 1;
+        pos = end;
+        // Skip longer names sharing the prefix, e.g. <sheets> for <sheet
+        let next = xml[start + open.len()..].chars().next();
+        if matches!(next, Some(c) if c.is_whitespace() || c == '/' || c == '>') {
+            return Some(&xml[start..end]);
+        }
+    })
+}
+
+/// Value of attribute `name` in a start tag
+pub(crate) fn attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{}=\"", name);
+    let mut offset = 0;
+    while let Some(i) = tag[offset..].find(&pattern) {
+        let at = offset + i;
+        if tag[..at].ends_with(char::is_whitespace) {
+            let value = &tag[at + pattern.len()..];
+            return value.find('"').map(|end| &value[..end]);
+        }
+        offset = at + pattern.len();
+    }
+    None
+}
+
+// Decode XML entities (&lt; &gt; &amp; &quot; &apos;) and character
+// references (&#10; &#x1F600;) in one pass, so "&amp;lt;" stays "&lt;"
+pub(crate) fn decode_xml_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        decoded.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let c = entity.and_then(|entity| match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let code = match entity.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => entity.strip_prefix('#')?.parse().ok(),
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+        match (c, entity) {
+            (Some(c), Some(entity)) => {
+                decoded.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            // Not an entity: keep the ampersand as is
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Text of every `<si>` item of a sharedStrings.xml part, in order
+pub(super) fn parse_shared_strings(xml_data: &str, limits: &ReadLimits) -> Result<Vec<String>> {
+    let mut sst = Vec::new();
+
+    // Parse all <si> tags (multiple per line in compact XML)
+    let mut pos = 0;
+    while let Some(si_start) = xml_data[pos..].find("<si") {
+        let si_start = pos + si_start;
+        if let Some(si_end) = xml_data[si_start..].find("</si>") {
+            let si_end = si_start + si_end + 5; // Include "</si>"
+            let si_block = &xml_data[si_start..si_end];
+            if sst.len() == limits.max_shared_strings {
+                return Err(ExcelError::LimitExceeded {
+                    part: "xl/sharedStrings.xml".to_string(),
+                    limit: "max_shared_strings".to_string(),
+                    max: limits.max_shared_strings as u64,
+                });
+            }
+            sst.push(parse_shared_string_item(si_block));
+
+            pos = si_end;
+        } else {
+            break;
+        }
+    }
+
+    Ok(sst)
+}
+
+pub(crate) fn parse_shared_string_item(si_block: &str) -> String {
+    let mut text = String::new();
+    let mut pos = 0;
+
+    while let Some(t_start) = si_block[pos..].find("<t") {
+        let t_start = pos + t_start;
+        let Some(t_open_end) = si_block[t_start..].find('>') else {
+            break;
+        };
+        let value_start = t_start + t_open_end + 1;
+
+        let Some(t_close) = si_block[value_start..].find("</t>") else {
+            break;
+        };
+        let value_end = value_start + t_close;
+
+        text.push_str(&decode_xml_entities(&si_block[value_start..value_end]));
+        pos = value_end + 4;
+    }
+
+    text
+}
+
+impl StreamingReader {
+    /// Load Shared Strings Table
+    ///
+    /// This MUST be loaded fully because cells reference strings by index.
+    /// For files with millions of unique strings, this can still be large.
+    pub(crate) fn load_shared_strings(
+        archive: &mut Archive,
+        limits: &ReadLimits,
+    ) -> Result<Vec<String>> {
+        let _span = trace::span!("load_shared_strings");
+        let path = Self::shared_strings_path(archive, limits);
+        match read_part(archive, &path, limits) {
+            Ok(data) => parse_shared_strings(&xml_text(&data), limits),
+            Err(e @ ExcelError::LimitExceeded { .. }) => Err(e),
+            Err(_) => Ok(Vec::new()), // No SST = all cells are inline
+        }
+    }
+
+    /// Part name of the shared string table: usually xl/sharedStrings.xml,
+    /// but the workbook's relationships say where it is
+    pub(super) fn shared_strings_path(archive: &mut Archive, limits: &ReadLimits) -> String {
+        let rels = read_part(archive, WORKBOOK_RELS_PART, limits)
+            .map(|data| xml_text(&data))
+            .unwrap_or_default();
+        let path = element_tags(&rels, "Relationship")
+            .find(|tag| attr(tag, "Type").is_some_and(|t| t.ends_with("/sharedStrings")))
+            .and_then(|tag| attr(tag, "Target"))
+            .map(|target| resolve_target(WORKBOOK_PART, &decode_xml_entities(target)))
+            .unwrap_or_else(|| "xl/sharedStrings.xml".to_string());
+        path
+    }
+
+    /// Load sheet names and paths from workbook.xml
+    ///
+    /// Parses workbook.xml to get sheet names and their corresponding worksheet paths.
+    /// Supports Unicode sheet names.
+    pub(crate) fn load_sheet_info(
+        archive: &mut Archive,
+        limits: &ReadLimits,
+    ) -> Result<(Vec<String>, Vec<String>)> {
+        let mut sheet_names = Vec::new();
+        let mut sheet_ids = Vec::new();
+
+        // Load workbook.xml
+        let xml_data = xml_text(&read_part(archive, WORKBOOK_PART, limits)?);
+
+        // Parse <sheet> tags to get names and rIds
+        // Example: <sheet name="Sheet1" sheetId="1" r:id="rId1"/>
+        let mut pos = 0;
+        while let Some(sheet_start) = xml_data[pos..].find("<sheet ") {
+            let sheet_start = pos + sheet_start;
+            if let Some(sheet_end) = xml_data[sheet_start..].find("/>") {
+                let sheet_end = sheet_start + sheet_end + 2;
+                let sheet_tag = &xml_data[sheet_start..sheet_end];
+
+                // Extract name attribute
+                if let Some(name_start) = sheet_tag.find("name=\"") {
+                    let name_start = name_start + 6;
+                    if let Some(name_end) = sheet_tag[name_start..].find("\"") {
+                        let name = &sheet_tag[name_start..name_start + name_end];
+                        sheet_names.push(decode_xml_entities(name));
+                    }
+                }
+
+                // Extract r:id attribute (the relationships prefix varies)
+                if let Some(rid_start) = sheet_tag.find(":id=\"") {
+                    let rid_start = rid_start + 5;
+                    if let Some(rid_end) = sheet_tag[rid_start..].find("\"") {
+                        let rid = &sheet_tag[rid_start..rid_start + rid_end];
+                        sheet_ids.push(rid.to_string());
+                    }
+                }
+
+                pos = sheet_end;
+            } else {
+                break;
+            }
+        }
+        // Now load workbook.xml.rels to map rIds to worksheet paths
+        let mut sheet_paths = Vec::new();
+
+        let rels_data = xml_text(&read_part(archive, WORKBOOK_RELS_PART, limits)?);
+
+        // Map rIds to worksheet paths; targets are relative to xl/ or, with
+        // a leading '/', absolute
+        for rid in &sheet_ids {
+            let target = element_tags(&rels_data, "Relationship")
+                .find(|tag| attr(tag, "Id") == Some(rid.as_str()))
+                .and_then(|tag| attr(tag, "Target"));
+            if let Some(target) = target {
+                sheet_paths.push(resolve_target(WORKBOOK_PART, &decode_xml_entities(target)));
+            }
+        }
+
+        if sheet_names.len() != sheet_paths.len() {
+            return Err(ExcelError::ReadError(format!(
+                "Mismatch between sheet names ({}) and paths ({})",
+                sheet_names.len(),
+                sheet_paths.len()
+            )));
+        }
+
+        Ok((sheet_names, sheet_paths))
+    }
+
+    pub(super) fn estimate_sst_size(sst: &[String]) -> usize {
+        sst.iter().map(|s| s.len() + 24).sum() // 24 bytes per String overhead
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_sst_size() {
+        let sst = vec!["hello".to_string(), "world".to_string()];
+        let size = StreamingReader::estimate_sst_size(&sst);
+        assert!(size > 10); // At least the string bytes
+    }
+
+    #[test]
+    fn test_decode_xml_entities() {
+        assert_eq!(decode_xml_entities("A &amp; B"), "A & B");
+        // Decoded once: an escaped entity stays an entity
+        assert_eq!(decode_xml_entities("&amp;quot;&amp;lt;"), "&quot;&lt;");
+        assert_eq!(decode_xml_entities("&#65;&#x1F600;&#xD800;"), "A😀&#xD800;");
+        assert_eq!(decode_xml_entities("R&D; a & b &"), "R&D; a & b &");
+        for text in ["&amp;", "<'\"&#65;'>", "Q1 & Q2; [draft]"] {
+            let escaped = crate::fast_writer::xml_writer::escape(text);
+            assert_eq!(decode_xml_entities(&escaped), text);
+        }
+    }
+
+    #[test]
+    fn test_parse_shared_string_text_with_attributes() {
+        let xml = r#"<si><t xml:space="preserve">ID бизнес-аккаунта</t></si>"#;
+
+        assert_eq!(parse_shared_string_item(xml), "ID бизнес-аккаунта");
+    }
+
+    #[test]
+    fn test_parse_shared_string_rich_text_runs() {
+        let xml = r#"<si><r><t>ID </t></r><r><t>бизнес-аккаунта</t></r></si>"#;
+
+        assert_eq!(parse_shared_string_item(xml), "ID бизнес-аккаунта");
+    }
+
+    #[test]
+    fn test_parse_shared_string_preserves_empty_items() {
+        let xml = r#"<si></si>"#;
+
+        assert_eq!(parse_shared_string_item(xml), "");
+    }
+
+    #[test]
+    fn test_parse_shared_string_xml_entities() {
+        let xml = r#"<si><t>A&amp;B &lt;tag&gt; &quot;quoted&quot; &apos;single&apos;</t></si>"#;
+
+        assert_eq!(
+            parse_shared_string_item(xml),
+            "A&B <tag> \"quoted\" 'single'"
+        );
+    }
+}
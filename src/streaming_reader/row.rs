@@ -0,0 +1,828 @@
+//! Parsing the `<row>` elements of a worksheet into cell values
+
+use super::parts::{attr, decode_xml_entities, parse_shared_string_item};
+use super::{DetailedCell, ReadWarning, RowIterator, MAX_COL};
+use crate::error::{ExcelError, Result};
+use crate::types::{CellValue, MAX_EXACT_EXCEL_INT};
+
+/// Parse Excel date serial number to ISO date or datetime string
+/// Excel stores dates as floating point numbers representing days since 1900-01-01
+/// Examples:
+///   - 45217.0 = "2023-10-18" (date only)
+///   - 45217.5 = "2023-10-18 12:00:00" (date with time at noon)
+///
+/// Note: Excel has a bug where it treats 1900 as a leap year (it's not).
+/// This means dates from March 1, 1900 onwards are off by 1 day in Excel's count.
+///
+/// Performance: O(1) using 400-year cycle math (no loops for distant dates)
+fn parse_excel_date(serial: f64) -> String {
+    // Handle invalid dates
+    if !(1.0..=2958465.999).contains(&serial) {
+        // 2958465 = December 31, 9999
+        return serial.to_string();
+    }
+
+    // Split into date and time parts
+    let date_part = serial.floor();
+    let time_part = serial.fract();
+
+    // Excel epoch: January 1, 1900 = serial 1.0
+    // Account for Excel's leap year bug
+    let days_since_1900 = if date_part >= 60.0 {
+        (date_part - 2.0) as i64 // -2: -1 for bug, -1 for zero-based
+    } else {
+        (date_part - 1.0) as i64
+    };
+
+    // Calculate year from days since 1900
+    // Optimized: Estimate then iterate (much faster than pure iteration)
+    let mut year = 1900i64;
+    let mut remaining_days = days_since_1900;
+
+    // Quick estimate to get close (average 365.25 days/year)
+    // Then iterate the last few years
+    let est_years = (remaining_days / 365).min(500); // Cap at 500 for safety
+    if est_years > 0 {
+        year += est_years;
+
+        // Count exact days for estimated years
+        let mut days_counted = 0i64;
+        for y in 1900..(1900 + est_years) {
+            days_counted += if is_leap_year(y) { 366 } else { 365 };
+        }
+        remaining_days -= days_counted;
+
+        // Adjust if we overshot
+        while remaining_days < 0 {
+            year -= 1;
+            remaining_days += if is_leap_year(year) { 366 } else { 365 };
+        }
+
+        // Adjust if we undershot
+        while remaining_days >= 365 {
+            let days_in_year = if is_leap_year(year) { 366 } else { 365 };
+            if remaining_days < days_in_year {
+                break;
+            }
+            remaining_days -= days_in_year;
+            year += 1;
+        }
+    }
+
+    // Calculate month and day from remaining days
+    const DAYS_IN_MONTHS_LEAP: [i32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    const DAYS_IN_MONTHS_COMMON: [i32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+    let days_in_months = if is_leap_year(year) {
+        &DAYS_IN_MONTHS_LEAP
+    } else {
+        &DAYS_IN_MONTHS_COMMON
+    };
+
+    let mut month = 1;
+    let mut day = remaining_days as i32 + 1;
+
+    for (m, &days) in days_in_months.iter().enumerate() {
+        if day <= days {
+            month = m + 1;
+            break;
+        }
+        day -= days;
+    }
+
+    // Format with time if fractional part exists (>0.0001 to avoid float errors)
+    if time_part > 0.0001 {
+        // Time stored as fraction: 0.5 = 12:00:00, 0.25 = 06:00:00
+        let total_seconds = (time_part * 86400.0).round() as i64;
+        let hours = total_seconds / 3600;
+        let minutes = (total_seconds % 3600) / 60;
+        let seconds = total_seconds % 60;
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            year, month, day, hours, minutes, seconds
+        )
+    } else {
+        // Date only
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
+}
+
+/// A formula found while parsing a row
+#[derive(Debug, Default)]
+pub(super) struct FormulaCell {
+    pub(super) col: usize,
+    /// Formula text, empty for cells that reuse a shared formula
+    pub(super) expr: String,
+    /// `si` of a shared formula
+    pub(super) shared_index: Option<u32>,
+}
+
+/// What [`RowIterator::parse_row`] needs besides the row XML
+pub(super) struct ParseContext<'a> {
+    /// Shared string table the `t="s"` cells index into
+    pub(super) sst: &'a [String],
+    /// Formulas of the row are collected here when set
+    pub(super) formulas: Option<&'a mut Vec<FormulaCell>>,
+    /// When set, only columns marked `true` get a value
+    pub(super) wanted: Option<&'a [bool]>,
+    /// When set, malformed cells become error cells noted here instead of
+    /// failing the row
+    pub(super) issues: Option<&'a mut Vec<ReadWarning>>,
+    /// Most cells the row may have
+    pub(super) max_cells: usize,
+}
+
+impl<'a> ParseContext<'a> {
+    /// Every column, no formulas, and malformed cells fail the row
+    pub(super) fn new(sst: &'a [String], max_cells: usize) -> Self {
+        ParseContext {
+            sst,
+            formulas: None,
+            wanted: None,
+            issues: None,
+            max_cells,
+        }
+    }
+}
+
+impl RowIterator<'_> {
+    /// Parse the cells of a `<row>`, as set up by `context`
+    pub(super) fn parse_row(row_xml: &str, context: ParseContext<'_>) -> Result<Vec<CellValue>> {
+        let ParseContext {
+            sst,
+            mut formulas,
+            wanted,
+            mut issues,
+            max_cells,
+        } = context;
+        let mut row_data = Vec::new();
+        let mut pos = 0;
+
+        while let Some(cell_start) = [row_xml[pos..].find("<c "), row_xml[pos..].find("<c>")]
+            .into_iter()
+            .flatten()
+            .min()
+        {
+            let cell_start = pos + cell_start;
+            if row_data.len() >= max_cells {
+                return Err(too_many_cells(row_xml, max_cells));
+            }
+
+            // Handle both self-closing <c ... /> and <c ...></c>; attributes
+            // are only looked up in the opening tag, not in a nested <f .../>
+            let Some(tag_len) = row_xml[cell_start..].find('>') else {
+                let col = row_data.len();
+                let error = malformed(row_xml, col, "unterminated <c> tag");
+                row_data.push(reject(&mut issues, error, row_xml, col)?);
+                break;
+            };
+            let tag_end = cell_start + tag_len + 1;
+            let open_tag = &row_xml[cell_start..tag_end];
+
+            // Extract cell reference (e.g., "A1", "B1", "AA1")
+            let col_idx = if let Some(r_start) = open_tag.find("r=\"") {
+                let r_start = r_start + 3;
+                if let Some(r_end) = open_tag[r_start..].find("\"") {
+                    let cell_ref = &open_tag[r_start..r_start + r_end];
+                    match parse_column_index(cell_ref) {
+                        Some(col) => col,
+                        None => {
+                            // Skip the cell; filling up to it could take any amount of memory
+                            let col = row_data.len();
+                            let message = format!("cell reference {:?} is out of range", cell_ref);
+                            let error = malformed(row_xml, col, &message);
+                            row_data.push(reject(&mut issues, error, row_xml, col)?);
+                            pos = tag_end;
+                            continue;
+                        }
+                    }
+                } else {
+                    row_data.len()
+                }
+            } else {
+                row_data.len()
+            };
+
+            let (cell_end, cell_xml) = if open_tag.ends_with("/>") {
+                (tag_end, open_tag)
+            } else if let Some(close_tag_pos) = row_xml[tag_end..].find("</c>") {
+                let end = tag_end + close_tag_pos + 4;
+                (end, &row_xml[cell_start..end])
+            } else {
+                let error = malformed(row_xml, col_idx, "<c> without </c>");
+                let cell = reject(&mut issues, error, row_xml, col_idx)?;
+                row_data.resize(col_idx.max(row_data.len()), CellValue::Empty);
+                row_data.push(cell);
+                break;
+            };
+
+            if let Some(wanted) = wanted {
+                match wanted.get(col_idx) {
+                    Some(true) => {}
+                    Some(false) => {
+                        pos = cell_end;
+                        continue;
+                    }
+                    // Past the last requested column
+                    None => break,
+                }
+            }
+
+            // Fill empty cells between last column and current column
+            if col_idx >= max_cells {
+                return Err(too_many_cells(row_xml, max_cells));
+            }
+            while row_data.len() < col_idx {
+                row_data.push(CellValue::Empty);
+            }
+
+            // Determine cell type
+            let cell_type = if let Some(t_start) = open_tag.find("t=\"") {
+                let t_start = t_start + 3;
+                if let Some(t_end) = open_tag[t_start..].find("\"") {
+                    &open_tag[t_start..t_start + t_end]
+                } else {
+                    ""
+                }
+            } else {
+                "" // No type means numeric
+            };
+
+            let is_shared_string = cell_type == "s";
+            let is_inline_str = cell_type == "inlineStr";
+            let is_boolean = cell_type == "b";
+            let is_error = cell_type == "e";
+            // Empty type means numeric or date
+
+            // Extract value
+            let cell_value = if !matches!(
+                cell_type,
+                "" | "n" | "s" | "str" | "inlineStr" | "b" | "e" | "d"
+            ) {
+                let error = ExcelError::UnsupportedCellType {
+                    t: cell_type.to_string(),
+                    cell: cell_reference(row_xml, col_idx),
+                };
+                reject(&mut issues, error, row_xml, col_idx)?
+            } else if is_inline_str {
+                // Inline string - <is> holds a plain <t> or rich text runs,
+                // the same as a shared string item
+                match cell_xml.find("<is>") {
+                    Some(is_start) => {
+                        CellValue::String(parse_shared_string_item(&cell_xml[is_start..]))
+                    }
+                    None => CellValue::Empty,
+                }
+            } else if let Some(v_start) = cell_xml.find("<v>") {
+                if let Some(v_end) = cell_xml[v_start..].find("</v>") {
+                    let val_str = &cell_xml[v_start + 3..v_start + v_end];
+
+                    if is_shared_string {
+                        // Lookup in SST
+                        if let Ok(idx) = val_str.parse::<usize>() {
+                            match sst.get(idx) {
+                                Some(value) => CellValue::String(decode_xml_entities(value)),
+                                None => {
+                                    let message = format!(
+                                        "shared string {} out of range ({} strings)",
+                                        idx,
+                                        sst.len()
+                                    );
+                                    let error = malformed(row_xml, col_idx, &message);
+                                    reject(&mut issues, error, row_xml, col_idx)?
+                                }
+                            }
+                        } else {
+                            CellValue::Empty
+                        }
+                    } else if is_boolean {
+                        // Boolean: 0 = false, 1 = true
+                        CellValue::Bool(val_str == "1")
+                    } else if is_error {
+                        // Error cell
+                        CellValue::Error(val_str.to_string())
+                    } else {
+                        // Numeric value (could be number or date)
+                        // Try to parse as number first
+                        if let Ok(num) = val_str.parse::<f64>() {
+                            // Check if this might be a date
+                            // Dates in Excel are typically between 1 (1900-01-01) and 2958465 (9999-12-31)
+                            // Also check for style attribute 's' which indicates formatting
+                            let has_style = open_tag.contains("s=\"");
+
+                            // If it looks like a date serial number and has a style, try parsing as date
+                            if has_style && (1.0..=2958465.0).contains(&num) && num.fract() < 0.0001
+                            {
+                                // Likely a date - return as string in ISO format
+                                CellValue::String(parse_excel_date(num))
+                            } else if let Ok(int) = val_str.parse::<i64>() {
+                                // Exact integer, no round trip through f64
+                                CellValue::Int(int)
+                            } else if is_integer_literal(val_str) {
+                                // Too large for i64: keep every digit
+                                CellValue::BigInt(val_str.to_string())
+                            } else if num.fract() == 0.0
+                                && (i64::MIN as f64..=i64::MAX as f64).contains(&num)
+                            {
+                                // Integer
+                                CellValue::Int(num as i64)
+                            } else {
+                                // Float
+                                CellValue::Float(num)
+                            }
+                        } else {
+                            // Can't parse as number, treat as string
+                            CellValue::String(decode_xml_entities(val_str))
+                        }
+                    }
+                } else {
+                    CellValue::Empty
+                }
+            } else {
+                CellValue::Empty
+            };
+
+            if let Some(formulas) = formulas.as_deref_mut() {
+                if let Some(formula) = parse_formula(cell_xml) {
+                    formulas.push(FormulaCell {
+                        col: row_data.len(),
+                        ..formula
+                    });
+                }
+            }
+
+            row_data.push(cell_value);
+            pos = cell_end;
+        }
+
+        Ok(row_data)
+    }
+}
+
+// Read the <f> element of a cell, if any (column left for the caller)
+fn parse_formula(cell_xml: &str) -> Option<FormulaCell> {
+    let f_start = cell_xml.find("<f")?;
+    let tag_end = f_start + cell_xml[f_start..].find('>')?;
+    let open_tag = &cell_xml[f_start..tag_end];
+    if !open_tag[2..].starts_with([' ', '/']) && tag_end != f_start + 2 {
+        return None; // Not an <f> element
+    }
+    let attribute = |name: &str| {
+        let start = open_tag.find(name)? + name.len();
+        let end = open_tag[start..].find('"')?;
+        Some(&open_tag[start..start + end])
+    };
+    let shared_index = match attribute(" t=\"") {
+        Some("shared") => attribute(" si=\"").and_then(|si| si.parse().ok()),
+        _ => None,
+    };
+    let expr = if open_tag.ends_with('/') {
+        String::new()
+    } else {
+        let body = &cell_xml[tag_end + 1..];
+        decode_xml_entities(&body[..body.find("</f>")?])
+    };
+    if expr.is_empty() && shared_index.is_none() {
+        return None;
+    }
+    Some(FormulaCell {
+        col: 0,
+        expr,
+        shared_index,
+    })
+}
+
+// Raw attributes, value and formula of the cells of a <row>, by column; the
+// values themselves are left empty
+pub(super) fn parse_cell_details(row_xml: &str, details: &mut Vec<DetailedCell>) {
+    details.clear();
+    let mut pos = 0;
+    while let Some(cell_start) = [row_xml[pos..].find("<c "), row_xml[pos..].find("<c>")]
+        .into_iter()
+        .flatten()
+        .min()
+    {
+        let cell_start = pos + cell_start;
+        let Some(tag_len) = row_xml[cell_start..].find('>') else {
+            break;
+        };
+        let tag_end = cell_start + tag_len + 1;
+        let open_tag = &row_xml[cell_start..tag_end];
+        let cell_xml = if open_tag.ends_with("/>") {
+            open_tag
+        } else {
+            match row_xml[tag_end..].find("</c>") {
+                Some(close) => &row_xml[cell_start..tag_end + close + 4],
+                None => break,
+            }
+        };
+        pos = cell_start + cell_xml.len();
+
+        let col = match attr(open_tag, "r") {
+            Some(cell_ref) => match parse_column_index(cell_ref) {
+                Some(col) => col,
+                None => continue,
+            },
+            None => details.len(),
+        };
+        let raw_value = cell_xml.find("<v>").and_then(|start| {
+            let value = &cell_xml[start + 3..];
+            value
+                .find("</v>")
+                .map(|end| decode_xml_entities(&value[..end]))
+        });
+        let detail = DetailedCell {
+            value: CellValue::Empty,
+            raw_value,
+            type_attr: attr(open_tag, "t").map(str::to_string),
+            style_index: attr(open_tag, "s").and_then(|s| s.parse().ok()),
+            formula: parse_formula(cell_xml).map(|formula| formula.expr),
+        };
+        if col >= details.len() {
+            details.resize_with(col, DetailedCell::empty);
+            details.push(detail);
+        } else {
+            details[col] = detail;
+        }
+    }
+}
+
+/// `XmlParse` error for a cell of `row_xml`; the sheet is filled in by the
+/// row iterator
+fn malformed(row_xml: &str, col: usize, message: &str) -> ExcelError {
+    ExcelError::XmlParse {
+        sheet: String::new(),
+        row: parse_row_number(row_xml),
+        col: Some(col as u32),
+        message: message.to_string(),
+    }
+}
+
+/// `LimitExceeded` for a row with more than `max_cells` cells; the sheet is
+/// filled in by the row iterator
+fn too_many_cells(row_xml: &str, max_cells: usize) -> ExcelError {
+    let part = match parse_row_number(row_xml) {
+        Some(row) => format!("row {}", row),
+        None => "row".to_string(),
+    };
+    ExcelError::LimitExceeded {
+        part,
+        limit: "max_cells_per_row".to_string(),
+        max: max_cells as u64,
+    }
+}
+
+/// `error` in strict mode; in lenient mode an error cell, with the problem
+/// added to `issues`
+fn reject(
+    issues: &mut Option<&mut Vec<ReadWarning>>,
+    error: ExcelError,
+    row_xml: &str,
+    col: usize,
+) -> Result<CellValue> {
+    let Some(issues) = issues else {
+        return Err(error);
+    };
+    let message = match error {
+        ExcelError::XmlParse { message, .. } => message,
+        other => other.to_string(),
+    };
+    issues.push(ReadWarning {
+        sheet: String::new(),
+        row: parse_row_number(row_xml),
+        col: Some(col as u32),
+        message,
+    });
+    Ok(CellValue::Error("#VALUE!".to_string()))
+}
+
+/// A1-style reference of a cell in `row_xml`
+fn cell_reference(row_xml: &str, col: usize) -> String {
+    let letters = crate::types::Cell::col_to_letter(col as u32);
+    match parse_row_number(row_xml) {
+        Some(row) => format!("{}{}", letters, row),
+        None => letters,
+    }
+}
+
+// Row number from the r attribute of a <row> element
+pub(super) fn parse_row_number(row_xml: &str) -> Option<u32> {
+    let tag = &row_xml[..row_xml.find('>')?];
+    let start = tag.find(" r=\"")? + 4;
+    let end = tag[start..].find('"')?;
+    tag[start..start + end].parse().ok()
+}
+
+// Turn whole numbers that f64 can't hold exactly into BigInt text
+pub(super) fn big_integers_to_text(mut cells: Vec<CellValue>) -> Vec<CellValue> {
+    const EXACT: f64 = MAX_EXACT_EXCEL_INT as f64;
+    for cell in &mut cells {
+        match *cell {
+            CellValue::Int(i) if i.unsigned_abs() > MAX_EXACT_EXCEL_INT as u64 => {
+                *cell = CellValue::BigInt(i.to_string());
+            }
+            CellValue::Float(f) if f.fract() == 0.0 && f.abs() > EXACT => {
+                *cell = CellValue::BigInt(format!("{:.0}", f));
+            }
+            _ => {}
+        }
+    }
+    cells
+}
+
+// Turn floats into the decimal of their shortest text
+#[cfg(feature = "decimal")]
+pub(super) fn floats_to_decimal(mut cells: Vec<CellValue>) -> Vec<CellValue> {
+    for cell in &mut cells {
+        if let CellValue::Float(_) = cell {
+            if let Some(d) = cell.as_decimal() {
+                *cell = CellValue::Decimal(d);
+            }
+        }
+    }
+    cells
+}
+
+// Check whether a <v> value is a plain integer (optional sign, digits only)
+fn is_integer_literal(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+// Parse column index from cell reference (e.g., "A1" -> 0, "B1" -> 1, "AA1" -> 26);
+// None past the last sheet column
+fn parse_column_index(cell_ref: &str) -> Option<usize> {
+    let mut col_idx = 0usize;
+    for ch in cell_ref.chars() {
+        if ch.is_ascii_alphabetic() {
+            col_idx = col_idx * 26 + (ch.to_ascii_uppercase() as usize - 'A' as usize + 1);
+            if col_idx > MAX_COL as usize + 1 {
+                return None;
+            }
+        } else {
+            break;
+        }
+    }
+    Some(col_idx.saturating_sub(1)) // Convert to 0-based index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming_reader::Projection;
+
+    #[test]
+    fn test_parse_row_resolves_shared_string() {
+        let sst = vec!["ID бизнес-аккаунта".to_string()];
+        let row_xml = r#"<row r="1"><c r="A1" t="s"><v>0</v></c></row>"#;
+
+        let row = RowIterator::parse_row(row_xml, ParseContext::new(&sst, usize::MAX)).unwrap();
+
+        assert_eq!(
+            row,
+            vec![CellValue::String("ID бизнес-аккаунта".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_row_keeps_big_integers() {
+        let row_xml = r#"<row r="1"><c r="A1"><v>9007199254740993</v></c><c r="B1"><v>12345678901234567890</v></c></row>"#;
+
+        let row = RowIterator::parse_row(row_xml, ParseContext::new(&[], usize::MAX)).unwrap();
+
+        assert_eq!(
+            row,
+            vec![
+                CellValue::Int(9007199254740993),
+                CellValue::BigInt("12345678901234567890".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_row_errors() {
+        let sst = vec!["a".to_string()];
+
+        let err = RowIterator::parse_row(
+            r#"<row r="4"><c r="A4" t="s"><v>3</v></c></row>"#,
+            ParseContext::new(&sst, usize::MAX),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ExcelError::XmlParse {
+                row: Some(4),
+                col: Some(0),
+                ..
+            }
+        ));
+
+        let err = RowIterator::parse_row(
+            r#"<row r="2"><c r="C2" t="x"><v>1</v></c></row>"#,
+            ParseContext::new(&sst, usize::MAX),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ExcelError::UnsupportedCellType { ref t, ref cell } if t == "x" && cell == "C2"
+        ));
+
+        let err = RowIterator::parse_row(
+            r#"<row r="9"><c r="B9"><v>1</v></row>"#,
+            ParseContext::new(&sst, usize::MAX),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ExcelError::XmlParse {
+                row: Some(9),
+                col: Some(1),
+                ..
+            }
+        ));
+
+        // Past column XFD, including references that overflow
+        for cell_ref in ["XFE1", &"Z".repeat(40)] {
+            let row_xml = format!(r#"<row r="1"><c r="{}"><v>1</v></c></row>"#, cell_ref);
+            let err =
+                RowIterator::parse_row(&row_xml, ParseContext::new(&sst, usize::MAX)).unwrap_err();
+            assert!(err.to_string().contains("out of range"), "{}", err);
+        }
+        let row = RowIterator::parse_row(
+            r#"<row r="1"><c r="XFD1"><v>1</v></c></row>"#,
+            ParseContext::new(&sst, usize::MAX),
+        )
+        .unwrap();
+        assert_eq!(row.len(), 16_384);
+    }
+
+    #[test]
+    fn test_parse_row_lenient() {
+        let sst = vec!["a".to_string()];
+        let row_xml = r#"<row r="3"><c r="A3" t="s"><v>5</v></c><c r="B3" t="x"><v>1</v></c><c r="C3" t="s"><v>0</v></c><c r="D3"><v>2</v></row>"#;
+        let mut issues = Vec::new();
+
+        let row = RowIterator::parse_row(
+            row_xml,
+            ParseContext {
+                issues: Some(&mut issues),
+                ..ParseContext::new(&sst, usize::MAX)
+            },
+        )
+        .unwrap();
+
+        let bad = CellValue::Error("#VALUE!".to_string());
+        assert_eq!(
+            row,
+            vec![
+                bad.clone(),
+                bad.clone(),
+                CellValue::String("a".to_string()),
+                bad
+            ]
+        );
+        let cols: Vec<_> = issues.iter().map(|w| (w.row, w.col)).collect();
+        assert_eq!(
+            cols,
+            [(Some(3), Some(0)), (Some(3), Some(1)), (Some(3), Some(3))]
+        );
+        assert_eq!(issues[1].message, "Unsupported cell type 'x' in cell B3");
+    }
+
+    #[test]
+    fn test_parse_row_projection() {
+        let sst = vec!["a".to_string(), "b".to_string()];
+        let row_xml = r#"<row r="2"><c r="A2" t="s"><v>0</v></c><c r="B2"><v>7</v></c><c r="D2" t="s"><v>1</v></c><c r="E2"><v>9</v></c></row>"#;
+        let projection = Projection::new(&[3, 0, 2]);
+
+        let row = RowIterator::parse_row(
+            row_xml,
+            ParseContext {
+                wanted: Some(&projection.wanted),
+                ..ParseContext::new(&sst, usize::MAX)
+            },
+        )
+        .unwrap();
+
+        // Parsing stops after column D; B is skipped
+        assert_eq!(
+            row,
+            vec![
+                CellValue::String("a".to_string()),
+                CellValue::Empty,
+                CellValue::Empty,
+                CellValue::String("b".to_string()),
+            ]
+        );
+        assert_eq!(
+            projection.select(row),
+            vec![
+                CellValue::String("b".to_string()),
+                CellValue::String("a".to_string()),
+                CellValue::Empty,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_excel_date() {
+        // Test January 1, 2022 (known: 44562)
+        let date = parse_excel_date(44562.0);
+        assert_eq!(date, "2022-01-01", "Serial 44562 should be 2022-01-01");
+
+        // Test January 1, 1970 (Unix epoch, known: 25569)
+        let date = parse_excel_date(25569.0);
+        assert_eq!(date, "1970-01-01", "Serial 25569 should be 1970-01-01");
+
+        // Test January 1, 2000 (known: 36526)
+        let date = parse_excel_date(36526.0);
+        assert_eq!(date, "2000-01-01", "Serial 36526 should be 2000-01-01");
+
+        // Test December 31, 2020 (known: 44196)
+        let date = parse_excel_date(44196.0);
+        assert_eq!(date, "2020-12-31", "Serial 44196 should be 2020-12-31");
+
+        // Test leap year: February 29, 2020 (known: 43890)
+        let date = parse_excel_date(43890.0);
+        assert_eq!(date, "2020-02-29", "Serial 43890 should be 2020-02-29");
+
+        // Test October 18, 2023 (actual value for 45217 from online converter)
+        let date = parse_excel_date(45217.0);
+        assert_eq!(date, "2023-10-18", "Serial 45217 should be 2023-10-18");
+    }
+
+    #[test]
+    fn test_parse_excel_datetime() {
+        // Test with time component: noon (0.5 = 12:00:00)
+        let datetime = parse_excel_date(44562.5);
+        assert_eq!(
+            datetime, "2022-01-01 12:00:00",
+            "Serial 44562.5 should be 2022-01-01 12:00:00"
+        );
+
+        // Test with time: 6:00 AM (0.25 = 06:00:00)
+        let datetime = parse_excel_date(44562.25);
+        assert_eq!(
+            datetime, "2022-01-01 06:00:00",
+            "Serial 44562.25 should be 2022-01-01 06:00:00"
+        );
+
+        // Test with time: 6:00 PM (0.75 = 18:00:00)
+        let datetime = parse_excel_date(44562.75);
+        assert_eq!(
+            datetime, "2022-01-01 18:00:00",
+            "Serial 44562.75 should be 2022-01-01 18:00:00"
+        );
+
+        // Test with specific time: 14:30:00 (14.5 hours / 24 = 0.6041666...)
+        let datetime = parse_excel_date(44562.0 + (14.5 / 24.0));
+        assert_eq!(
+            datetime, "2022-01-01 14:30:00",
+            "Serial with 14:30 should parse correctly"
+        );
+
+        // Test midnight (0.0 = 00:00:00) - should return date only
+        let datetime = parse_excel_date(44562.0);
+        assert_eq!(
+            datetime, "2022-01-01",
+            "Serial 44562.0 should be date only (midnight)"
+        );
+
+        // Test near-midnight (0.00001 < threshold) - should return date only
+        let datetime = parse_excel_date(44562.00005);
+        assert_eq!(
+            datetime, "2022-01-01",
+            "Serial with tiny fraction should be date only"
+        );
+    }
+
+    #[test]
+    fn test_is_leap_year() {
+        assert!(is_leap_year(2024)); // Divisible by 4
+        assert!(!is_leap_year(2023)); // Not divisible by 4
+        assert!(!is_leap_year(1900)); // Divisible by 100 but not 400
+        assert!(is_leap_year(2000)); // Divisible by 400
+    }
+
+    #[test]
+    fn test_parse_excel_date_edge_cases() {
+        // Test year 2100 (next century) - Jan 1, 2100 = serial 73049 + 1 = 73050
+        // Actually: 73049 days from 1900 = Jan 1, 2100, so serial is 73049 + 2 = 73051
+        let next_century = parse_excel_date(73051.0);
+        assert_eq!(next_century, "2100-01-01", "Should handle next century");
+
+        // Test year 2000 transition (Y2K)
+        let y2k = parse_excel_date(36526.0);
+        assert_eq!(y2k, "2000-01-01", "Y2K transition");
+
+        // Test near Excel's leap year bug boundary
+        let feb28_1900 = parse_excel_date(59.0); // Feb 28, 1900
+        let mar1_1900 = parse_excel_date(61.0); // Mar 1, 1900
+        assert_eq!(feb28_1900, "1900-02-28", "Feb 28, 1900");
+        assert_eq!(mar1_1900, "1900-03-01", "Mar 1, 1900");
+    }
+}
@@ -0,0 +1,515 @@
+//! Group-by summaries written as a sheet next to the detail data
+//!
+//! Feed every detail row to a [`SummarySheetBuilder`] while writing it, then
+//! add the summary sheet at the end. Groups are aggregated in a hash map;
+//! past [`max_groups_in_memory`](SummarySheetBuilder::max_groups_in_memory)
+//! distinct groups the partial results are sorted and spilled to a
+//! temporary file, and the runs are merged when the sheet is written, so
+//! memory stays bounded however many groups there are.
+//!
+//! ```no_run
+//! use excelstream::{ExcelWriter, SummarySheetBuilder};
+//!
+//! let mut writer = ExcelWriter::new("sales.xlsx")?;
+//! let header = ["Region", "Product", "Amount"];
+//! let mut summary = SummarySheetBuilder::new("Summary")
+//!     .headers(header)
+//!     .group_by(0)
+//!     .count()
+//!     .sum(2)
+//!     .average(2);
+//!
+//! writer.write_header_bold(header)?;
+//! for row in [["North", "Tea", "120"], ["South", "Tea", "80"], ["North", "Coffee", "200"]] {
+//!     writer.write_row(row)?;
+//!     summary.add_row(row)?;
+//! }
+//!
+//! // Region | Count | Sum of Amount | Average of Amount, plus a total row
+//! summary.write(&mut writer)?;
+//! writer.save()?;
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::spill::{SpillBuffer, SpillReader, DEFAULT_SPILL_THRESHOLD};
+use crate::types::{Cell, CellStyle, CellValue};
+use crate::writer::ExcelWriter;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+/// Distinct groups kept in memory before partial results are spilled
+pub const DEFAULT_MAX_GROUPS: usize = 100_000;
+
+/// Column of the summary sheet after the group columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Measure {
+    Count,
+    /// Index into the accumulated value columns
+    Sum(usize),
+    Average(usize),
+}
+
+/// Row count plus (sum, count of numeric cells) per value column
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Accumulator {
+    rows: u64,
+    values: Vec<(f64, u64)>,
+}
+
+impl Accumulator {
+    fn merge(&mut self, other: &Accumulator) {
+        self.rows += other.rows;
+        if self.values.len() < other.values.len() {
+            self.values.resize(other.values.len(), (0.0, 0));
+        }
+        for (value, (sum, count)) in self.values.iter_mut().zip(&other.values) {
+            value.0 += sum;
+            value.1 += count;
+        }
+    }
+}
+
+/// Group-by counts, sums and averages over a stream of rows
+///
+/// See the [module documentation](self) for an example. Groups are written
+/// sorted by their values as text, followed by a bold total row.
+pub struct SummarySheetBuilder {
+    sheet_name: String,
+    headers: Vec<String>,
+    group_by: Vec<usize>,
+    /// Detail columns that are summed or averaged
+    value_columns: Vec<usize>,
+    measures: Vec<Measure>,
+    total_row: bool,
+    max_groups: usize,
+    groups: HashMap<Vec<String>, Accumulator>,
+    /// Sorted partial results spilled when `groups` grew too large
+    runs: Vec<SpillBuffer>,
+    totals: Accumulator,
+}
+
+impl SummarySheetBuilder {
+    /// Summary written to a new sheet named `sheet_name`
+    pub fn new(sheet_name: &str) -> Self {
+        SummarySheetBuilder {
+            sheet_name: sheet_name.to_string(),
+            headers: Vec::new(),
+            group_by: Vec::new(),
+            value_columns: Vec::new(),
+            measures: Vec::new(),
+            total_row: true,
+            max_groups: DEFAULT_MAX_GROUPS,
+            groups: HashMap::new(),
+            runs: Vec::new(),
+            totals: Accumulator::default(),
+        }
+    }
+
+    /// Names of the detail columns, used in the summary's header row
+    /// (default: column letters)
+    pub fn headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.headers = headers
+            .into_iter()
+            .map(|h| h.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    /// Group by detail column `col` (0-based); call again for more columns
+    pub fn group_by(mut self, col: usize) -> Self {
+        self.group_by.push(col);
+        self
+    }
+
+    /// Add a column with the number of rows in each group
+    pub fn count(mut self) -> Self {
+        self.measures.push(Measure::Count);
+        self
+    }
+
+    /// Add a column with the sum of detail column `col`
+    ///
+    /// Cells that aren't numbers are skipped.
+    pub fn sum(mut self, col: usize) -> Self {
+        let index = self.value_index(col);
+        self.measures.push(Measure::Sum(index));
+        self
+    }
+
+    /// Add a column with the average of the numeric cells of detail
+    /// column `col`
+    pub fn average(mut self, col: usize) -> Self {
+        let index = self.value_index(col);
+        self.measures.push(Measure::Average(index));
+        self
+    }
+
+    /// Write a total row under the groups (default: true)
+    pub fn total_row(mut self, enabled: bool) -> Self {
+        self.total_row = enabled;
+        self
+    }
+
+    /// Distinct groups kept in memory before spilling to a temporary file
+    /// (default: [`DEFAULT_MAX_GROUPS`])
+    pub fn max_groups_in_memory(mut self, max: usize) -> Self {
+        self.max_groups = max.max(1);
+        self
+    }
+
+    fn value_index(&mut self, col: usize) -> usize {
+        match self.value_columns.iter().position(|&c| c == col) {
+            Some(index) => index,
+            None => {
+                self.value_columns.push(col);
+                self.value_columns.len() - 1
+            }
+        }
+    }
+
+    /// Add a detail row of strings; numbers are parsed from the text
+    pub fn add_row<I, S>(&mut self, row: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let cells: Vec<CellValue> = row
+            .into_iter()
+            .map(|s| CellValue::String(s.as_ref().to_string()))
+            .collect();
+        self.add_row_typed(&cells)
+    }
+
+    /// Add a detail row of typed values
+    pub fn add_row_typed(&mut self, row: &[CellValue]) -> Result<()> {
+        let key: Vec<String> = self
+            .group_by
+            .iter()
+            .map(|&col| row.get(col).map(CellValue::as_string).unwrap_or_default())
+            .collect();
+
+        let mut acc = Accumulator {
+            rows: 1,
+            values: vec![(0.0, 0); self.value_columns.len()],
+        };
+        for (value, &col) in acc.values.iter_mut().zip(&self.value_columns) {
+            if let Some(number) = row
+                .get(col)
+                .and_then(CellValue::as_f64)
+                .filter(|n| n.is_finite())
+            {
+                *value = (number, 1);
+            }
+        }
+        self.totals.merge(&acc);
+
+        if let Some(group) = self.groups.get_mut(&key) {
+            group.merge(&acc);
+            return Ok(());
+        }
+        if self.groups.len() >= self.max_groups {
+            self.spill_groups()?;
+        }
+        self.groups.insert(key, acc);
+        Ok(())
+    }
+
+    /// Write the in-memory groups, sorted, as a new run
+    fn spill_groups(&mut self) -> Result<()> {
+        let mut run = SpillBuffer::new(DEFAULT_SPILL_THRESHOLD);
+        for (key, acc) in Self::sorted(std::mem::take(&mut self.groups)) {
+            write_entry(&mut run, &key, &acc)?;
+        }
+        self.runs.push(run);
+        Ok(())
+    }
+
+    fn sorted(groups: HashMap<Vec<String>, Accumulator>) -> Vec<(Vec<String>, Accumulator)> {
+        let mut groups: Vec<_> = groups.into_iter().collect();
+        groups.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        groups
+    }
+
+    /// Add the summary sheet to `writer` and write the header, one row per
+    /// group and the total row
+    pub fn write(mut self, writer: &mut ExcelWriter) -> Result<()> {
+        if self.group_by.is_empty() && self.measures.is_empty() {
+            return Err(ExcelError::InvalidState(
+                "Summary has no group or measure columns".to_string(),
+            ));
+        }
+        writer.add_sheet(&self.sheet_name)?;
+        writer.autofit_columns()?;
+        writer.write_header_bold(self.header_row())?;
+
+        let in_memory = Self::sorted(std::mem::take(&mut self.groups));
+        if self.runs.is_empty() {
+            for (key, acc) in &in_memory {
+                writer.write_row_styled(&self.summary_row(key, acc))?;
+            }
+        } else {
+            let mut sources: Vec<GroupSource> = vec![Box::new(in_memory.into_iter().map(Ok))];
+            for run in std::mem::take(&mut self.runs) {
+                sources.push(Box::new(RunReader::new(run.into_reader()?)));
+            }
+            let mut merge = Merge::new(sources)?;
+            while let Some((key, acc)) = merge.next_group()? {
+                writer.write_row_styled(&self.summary_row(&key, &acc))?;
+            }
+        }
+
+        if self.total_row {
+            let mut row = self.summary_row(&[], &self.totals);
+            row.splice(
+                0..0,
+                (0..self.group_by.len()).map(|i| {
+                    let text = if i == 0 { "Total" } else { "" };
+                    (CellValue::String(text.to_string()), CellStyle::TextBold)
+                }),
+            );
+            for cell in row.iter_mut() {
+                if cell.1 == CellStyle::Default {
+                    cell.1 = CellStyle::TextBold;
+                }
+            }
+            writer.write_row_styled(&row)?;
+        }
+        Ok(())
+    }
+
+    fn column_name(&self, col: usize) -> String {
+        match self.headers.get(col) {
+            Some(name) => name.clone(),
+            None => Cell::col_to_letter(col as u32),
+        }
+    }
+
+    fn header_row(&self) -> Vec<String> {
+        let groups = self.group_by.iter().map(|&col| self.column_name(col));
+        let measures = self.measures.iter().map(|measure| match *measure {
+            Measure::Count => "Count".to_string(),
+            Measure::Sum(i) => format!("Sum of {}", self.column_name(self.value_columns[i])),
+            Measure::Average(i) => {
+                format!("Average of {}", self.column_name(self.value_columns[i]))
+            }
+        });
+        groups.chain(measures).collect()
+    }
+
+    fn summary_row(&self, key: &[String], acc: &Accumulator) -> Vec<(CellValue, CellStyle)> {
+        let value = |i: usize| acc.values.get(i).copied().unwrap_or((0.0, 0));
+        let groups = key
+            .iter()
+            .map(|k| (CellValue::String(k.clone()), CellStyle::Default));
+        let measures = self.measures.iter().map(|measure| match *measure {
+            Measure::Count => (CellValue::Int(acc.rows as i64), CellStyle::NumberInteger),
+            Measure::Sum(i) => (CellValue::Float(value(i).0), CellStyle::NumberDecimal),
+            Measure::Average(i) => match value(i) {
+                (_, 0) => (CellValue::Empty, CellStyle::Default),
+                (sum, count) => (
+                    CellValue::Float(sum / count as f64),
+                    CellStyle::NumberDecimal,
+                ),
+            },
+        });
+        groups.chain(measures).collect()
+    }
+}
+
+/// Spilled run entry: key count, then each key as length and bytes, the
+/// row count and each value column's sum and numeric count (all LE)
+fn write_entry(out: &mut impl Write, key: &[String], acc: &Accumulator) -> io::Result<()> {
+    out.write_all(&(key.len() as u32).to_le_bytes())?;
+    for part in key {
+        out.write_all(&(part.len() as u32).to_le_bytes())?;
+        out.write_all(part.as_bytes())?;
+    }
+    out.write_all(&acc.rows.to_le_bytes())?;
+    out.write_all(&(acc.values.len() as u32).to_le_bytes())?;
+    for (sum, count) in &acc.values {
+        out.write_all(&sum.to_le_bytes())?;
+        out.write_all(&count.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Sorted (key, accumulator) entries from memory or a spilled run
+type GroupSource = Box<dyn Iterator<Item = Result<(Vec<String>, Accumulator)>>>;
+
+/// Sequential reader of one spilled run
+struct RunReader {
+    reader: SpillReader,
+}
+
+impl RunReader {
+    fn new(reader: SpillReader) -> Self {
+        RunReader { reader }
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        let mut bytes = [0u8; 4];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_u64(&mut self) -> io::Result<u64> {
+        let mut bytes = [0u8; 8];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn read_entry(&mut self) -> io::Result<Option<(Vec<String>, Accumulator)>> {
+        let parts = match self.read_u32() {
+            Ok(parts) => parts,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut key = Vec::with_capacity(parts as usize);
+        for _ in 0..parts {
+            let mut bytes = vec![0u8; self.read_u32()? as usize];
+            self.reader.read_exact(&mut bytes)?;
+            key.push(String::from_utf8(bytes).map_err(io::Error::other)?);
+        }
+        let rows = self.read_u64()?;
+        let mut values = Vec::new();
+        for _ in 0..self.read_u32()? {
+            let sum = f64::from_bits(self.read_u64()?);
+            values.push((sum, self.read_u64()?));
+        }
+        Ok(Some((key, Accumulator { rows, values })))
+    }
+}
+
+impl Iterator for RunReader {
+    type Item = Result<(Vec<String>, Accumulator)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_entry().map_err(ExcelError::from).transpose()
+    }
+}
+
+/// K-way merge of sorted runs, combining entries with equal keys
+struct Merge {
+    sources: Vec<GroupSource>,
+    heads: Vec<Option<(Vec<String>, Accumulator)>>,
+}
+
+impl Merge {
+    fn new(mut sources: Vec<GroupSource>) -> Result<Self> {
+        let heads = sources
+            .iter_mut()
+            .map(|source| source.next().transpose())
+            .collect::<Result<_>>()?;
+        Ok(Merge { sources, heads })
+    }
+
+    fn next_group(&mut self) -> Result<Option<(Vec<String>, Accumulator)>> {
+        let Some(key) = self
+            .heads
+            .iter()
+            .flatten()
+            .map(|(key, _)| key)
+            .min()
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        let mut total = Accumulator::default();
+        for (head, source) in self.heads.iter_mut().zip(&mut self.sources) {
+            if head.as_ref().is_some_and(|(k, _)| *k == key) {
+                if let Some((_, acc)) = head.take() {
+                    total.merge(&acc);
+                }
+                *head = source.next().transpose()?;
+            }
+        }
+        Ok(Some((key, total)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fast_writer::StreamingZipReader;
+
+    fn summary_rows(max_groups: usize) -> Vec<Vec<String>> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("summary.xlsx");
+        let mut writer = ExcelWriter::new(&path).unwrap();
+        let mut summary = SummarySheetBuilder::new("Summary")
+            .headers(["Region", "Product", "Amount"])
+            .group_by(0)
+            .count()
+            .sum(2)
+            .average(2)
+            .max_groups_in_memory(max_groups);
+        for (region, amount) in [
+            ("North", "10"),
+            ("South", "5"),
+            ("East", "n/a"),
+            ("North", "20"),
+            ("West", "1.5"),
+            ("South", "7"),
+            ("North", "30"),
+        ] {
+            let row = [region, "Tea", amount];
+            writer.write_row(row).unwrap();
+            summary.add_row(row).unwrap();
+        }
+        summary.write(&mut writer).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(&path).unwrap();
+        let xml = zip.read_entry_by_name("xl/worksheets/sheet2.xml").unwrap();
+        let xml = String::from_utf8(xml).unwrap();
+
+        // Read the XML directly: the reader takes styled whole numbers for
+        // date serials
+        let row_xml = xml.split("<sheetData>").nth(1).unwrap();
+        row_xml
+            .split("<row ")
+            .skip(1)
+            .map(|row| {
+                row.split("<c ")
+                    .skip(1)
+                    .map(|cell| {
+                        let value = cell
+                            .split_once("<t>")
+                            .or_else(|| cell.split_once("<v>"))
+                            .map_or("", |(_, rest)| rest);
+                        value.split('<').next().unwrap().to_string()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_summary_sheet() {
+        let expected = vec![
+            vec!["Region", "Count", "Sum of Amount", "Average of Amount"],
+            vec!["East", "1", "0", ""],
+            vec!["North", "3", "60", "20"],
+            vec!["South", "2", "12", "6"],
+            vec!["West", "1", "1.5", "1.5"],
+            vec!["Total", "7", "73.5", "12.25"],
+        ];
+        assert_eq!(summary_rows(DEFAULT_MAX_GROUPS), expected);
+        // Spilling after every new group merges to the same result
+        assert_eq!(summary_rows(1), expected);
+    }
+
+    #[test]
+    fn test_summary_needs_columns() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut writer = ExcelWriter::new(dir.path().join("empty.xlsx")).unwrap();
+        assert!(SummarySheetBuilder::new("Summary")
+            .write(&mut writer)
+            .is_err());
+    }
+}
@@ -0,0 +1,1482 @@
+//! Fill a workbook designed in Excel with data
+//!
+//! A designer lays out the report in Excel (fonts, borders, number
+//! formats, column widths, logos) and types placeholders where the data
+//! goes. [`TemplateEngine`] copies the workbook, replacing them:
+//!
+//! - `{{customer.name}}` takes a value from the context, following field
+//!   names through nested structs and maps, and indexes into sequences
+//!   (`{{contacts.0.email}}`).
+//! - A row with `{{row.…}}` placeholders is the repeating row. It is
+//!   written once per item of the row iterator, `{{row.sku}}` taking the
+//!   item's `sku` field (`{{row}}` for items that are plain values).
+//!
+//! ```no_run
+//! use excelstream::template::TemplateEngine;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Customer { name: String }
+//! #[derive(Serialize)]
+//! struct Invoice { number: u32, customer: Customer }
+//! #[derive(Serialize)]
+//! struct Line { sku: String, qty: u32, price: f64 }
+//!
+//! let invoice = Invoice { number: 1042, customer: Customer { name: "Acme".into() } };
+//! let lines = vec![
+//!     Line { sku: "A-1".into(), qty: 2, price: 9.5 },
+//!     Line { sku: "B-7".into(), qty: 1, price: 120.0 },
+//! ];
+//!
+//! TemplateEngine::open("invoice_template.xlsx")?
+//!     .render_with_rows("invoice_1042.xlsx", &invoice, lines)?;
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+//!
+//! A cell holding nothing but a placeholder takes the value's type and
+//! keeps the cell's style, so numbers stay numbers in their number format.
+//! Give date cells a date format in the template; a date in a cell without
+//! a style is written as ISO text. Placeholders inside longer text are
+//! replaced by the value's text. A placeholder without a value is an error.
+//!
+//! Rows below the repeating row move down, with their merged cells,
+//! conditional formats and data validations, and formula references to
+//! moved rows follow them. A range ending on the repeating row, like the
+//! `SUM(D5:D5)` of a total, grows to cover every item. Formulas are
+//! recalculated when Excel opens the file. Defined names, charts and
+//! pivot tables are copied unchanged.
+//!
+//! Templates saved as `.xltx`/`.xltm` render into ordinary workbooks. The
+//! VBA project of a macro-enabled template is copied too, so render it to
+//! an `.xlsm` file, or drop the macros with
+//! [`keep_macros(false)`](TemplateEngine::keep_macros).
+//!
+//! Requires the `serde` feature.
+
+use crate::archive::Archive;
+use crate::convert::{format_iso, serial_to_datetime};
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::spill::{SpillBuffer, DEFAULT_SPILL_THRESHOLD};
+use crate::fast_writer::StreamingZipWriter;
+use crate::formula::{cell_position, map_row_references};
+use crate::record::CellSerializer;
+use crate::streaming_reader::parts::{
+    attr, decode_xml_entities, has_vba_project, parse_shared_string_item, read_part, xml_text,
+};
+use crate::streaming_reader::{ReadLimits, StreamingReader};
+use crate::types::{Cell, CellStyle, CellValue};
+use serde::ser::{self, Impossible, Serialize, Serializer};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Path prefix of the repeating row's placeholders
+const ROW: &str = "row";
+const CALC_CHAIN: &str = "xl/calcChain.xml";
+
+/// Content types of the workbook part: (template, workbook) pairs, plain
+/// and macro-enabled
+const WORKBOOK_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml";
+const TEMPLATE_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.template.main+xml";
+const MACRO_WORKBOOK_TYPE: &str = "application/vnd.ms-excel.sheet.macroEnabled.main+xml";
+const MACRO_TEMPLATE_TYPE: &str = "application/vnd.ms-excel.template.macroEnabled.main+xml";
+
+/// Copies a template workbook with its placeholders filled in
+///
+/// See the [module documentation](self) for the placeholder syntax.
+#[derive(Debug, Clone)]
+pub struct TemplateEngine {
+    path: PathBuf,
+    compression_level: u32,
+    /// The template has a VBA project
+    macros: bool,
+    keep_macros: bool,
+}
+
+impl TemplateEngine {
+    /// Template at `path`; fails if it isn't a readable workbook
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if !path.exists() {
+            return Err(ExcelError::FileNotFound(path.display().to_string()));
+        }
+        let mut archive = Archive::open(&path)?;
+        StreamingReader::load_sheet_info(&mut archive, &ReadLimits::default())?;
+        Ok(TemplateEngine {
+            path,
+            compression_level: 6,
+            macros: has_vba_project(archive.entries()),
+            keep_macros: true,
+        })
+    }
+
+    /// Whether the template carries VBA macros
+    pub fn has_macros(&self) -> bool {
+        self.macros
+    }
+
+    /// Copy the template's macros into the output (default: true)
+    ///
+    /// Excel won't open an `.xlsx` file holding macros, so rendering a
+    /// template with macros to one fails while they are kept. Without
+    /// them the output is a plain workbook.
+    pub fn keep_macros(mut self, keep: bool) -> Self {
+        self.keep_macros = keep;
+        self
+    }
+
+    /// XLSX compression level 0-9 (default: 6)
+    pub fn compression_level(mut self, level: u32) -> Self {
+        self.compression_level = level;
+        self
+    }
+
+    /// Write the template to `output` with placeholders taken from
+    /// `context`, a struct or map
+    ///
+    /// A repeating row in the template is removed.
+    pub fn render<P, C>(&self, output: P, context: &C) -> Result<()>
+    where
+        P: AsRef<Path>,
+        C: Serialize + ?Sized,
+    {
+        self.render_with_rows(output, context, std::iter::empty::<()>())
+            .map(|_| ())
+    }
+
+    /// Write the template to `output`, repeating its repeating row for every
+    /// item of `rows`; returns the number of items written
+    ///
+    /// Items are rendered as they come and buffered in a temporary file
+    /// when there are many, unless a formula in the repeating row refers to
+    /// rows below it: then all items are collected first.
+    pub fn render_with_rows<P, C, I>(&self, output: P, context: &C, rows: I) -> Result<u64>
+    where
+        P: AsRef<Path>,
+        C: Serialize + ?Sized,
+        I: IntoIterator,
+        I::Item: Serialize,
+    {
+        let strip_macros = self.macros && !self.keep_macros;
+        let xlsx = output
+            .as_ref()
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("xlsx"));
+        if self.macros && self.keep_macros && xlsx {
+            return Err(ExcelError::WriteError(format!(
+                "Template has macros, which an .xlsx file can't hold; write '{}' as .xlsm or call keep_macros(false)",
+                output.as_ref().display()
+            )));
+        }
+
+        let context = Values::of(context, "")?;
+        let mut archive = Archive::open(&self.path)?;
+        let limits = ReadLimits::default();
+        let (names, paths) = StreamingReader::load_sheet_info(&mut archive, &limits)?;
+        let strings = StreamingReader::load_shared_strings(&mut archive, &limits)?;
+
+        let mut sheets = HashMap::new();
+        let mut repeating: Option<&str> = None;
+        for (name, path) in names.iter().zip(&paths) {
+            let xml = xml_text(&read_part(&mut archive, path, &limits)?);
+            let sheet = Sheet::parse(name, xml, &strings)?;
+            if sheet.repeat.is_some() {
+                if let Some(first) = repeating {
+                    return Err(ExcelError::InvalidFormat(format!(
+                        "Template has repeating rows on sheets '{}' and '{}'; only one is supported",
+                        first, name
+                    )));
+                }
+                repeating = Some(name);
+            }
+            sheets.insert(path.as_str(), sheet);
+        }
+
+        let mut rows = rows.into_iter();
+        if repeating.is_none() && rows.next().is_some() {
+            return Err(ExcelError::InvalidFormat(
+                "Template has no repeating row ({{row.…}} placeholders) for the rows".to_string(),
+            ));
+        }
+        let mut items = rows.map(|item| Values::of(&item, ROW));
+
+        let mut zip =
+            StreamingZipWriter::with_compression(output.as_ref(), self.compression_level)?;
+        let has_calc_chain = archive.entries().iter().any(|e| e.name == CALC_CHAIN);
+        let mut written = 0;
+        for entry in archive.entries().to_vec() {
+            let name = entry.name.as_str();
+            // Cell positions change, so Excel's calculation order is rebuilt
+            if name.ends_with('/') || name == CALC_CHAIN || (strip_macros && is_vba_part(name)) {
+                continue;
+            }
+            zip.start_entry(name)?;
+            if let Some(sheet) = sheets.get(name) {
+                let mut sink = |bytes: &[u8]| -> Result<()> { Ok(zip.write_data(bytes)?) };
+                written += sheet.render(&context, &mut items, &mut sink)?;
+            } else if name == "xl/workbook.xml" {
+                let xml = xml_text(&read_part(&mut archive, &entry.name, &limits)?);
+                zip.write_data(recalculate_on_open(&xml).as_bytes())?;
+            } else if name == "[Content_Types].xml" {
+                let xml = xml_text(&read_part(&mut archive, &entry.name, &limits)?);
+                let xml = output_content_types(&xml, has_calc_chain, strip_macros);
+                zip.write_data(xml.as_bytes())?;
+            } else if name == "xl/_rels/workbook.xml.rels" && (has_calc_chain || strip_macros) {
+                let mut xml = xml_text(&read_part(&mut archive, &entry.name, &limits)?);
+                if has_calc_chain {
+                    xml = without_tag_containing(&xml, "calcChain.xml");
+                }
+                if strip_macros {
+                    xml = without_tags_containing(&xml, "relationships/vbaProject\"");
+                }
+                zip.write_data(xml.as_bytes())?;
+            } else {
+                let mut reader = archive.read_entry_streaming(&entry)?;
+                let mut chunk = vec![0u8; 64 * 1024];
+                loop {
+                    let n = reader.read(&mut chunk)?;
+                    if n == 0 {
+                        break;
+                    }
+                    zip.write_data(&chunk[..n])?;
+                }
+            }
+        }
+        zip.finish()?;
+        Ok(written)
+    }
+}
+
+/// Placeholder values by path, e.g. `customer.name`
+#[derive(Debug, Default)]
+struct Values(HashMap<String, CellValue>);
+
+impl Values {
+    /// Values of `value`, with paths starting at `prefix`
+    fn of<T: Serialize + ?Sized>(value: &T, prefix: &str) -> Result<Values> {
+        let mut values = Values::default();
+        value.serialize(ValueSerializer {
+            path: prefix.to_string(),
+            values: &mut values,
+        })?;
+        Ok(values)
+    }
+}
+
+/// Worksheet of the template, split around its rows
+struct Sheet {
+    name: String,
+    /// XML up to and including `<sheetData>`, without `<dimension>`
+    head: String,
+    rows: Vec<TemplateRow>,
+    /// XML from `</sheetData>` on
+    tail: String,
+    /// Index in `rows` of the repeating row
+    repeat: Option<usize>,
+}
+
+struct TemplateRow {
+    /// 1-based
+    number: u32,
+    /// Attributes of `<row>` other than `r`
+    attrs: String,
+    cells: Vec<TemplateCell>,
+}
+
+struct TemplateCell {
+    /// 0-based
+    col: u32,
+    /// Attributes other than `r` and `t`
+    attrs: String,
+    styled: bool,
+    content: Content,
+}
+
+enum Content {
+    /// Copied with only its formula adjusted: the `t` attribute and the XML
+    /// inside `<c>`
+    Raw { t: Option<String>, inner: String },
+    /// Text with placeholders
+    Text(Vec<Part>),
+}
+
+#[derive(Debug, PartialEq)]
+enum Part {
+    Literal(String),
+    Placeholder(String),
+}
+
+impl TemplateRow {
+    fn is_repeating(&self) -> bool {
+        self.cells.iter().any(|cell| match &cell.content {
+            Content::Text(parts) => parts.iter().any(|part| match part {
+                Part::Placeholder(path) => is_row_path(path),
+                Part::Literal(_) => false,
+            }),
+            Content::Raw { .. } => false,
+        })
+    }
+
+    /// Whether a formula in the row refers to a row below it
+    fn refers_below(&self) -> bool {
+        let probe = Shift {
+            row: self.number,
+            count: 2,
+        };
+        self.cells.iter().any(|cell| match &cell.content {
+            Content::Raw { inner, .. } => shift_cell_xml(inner, probe, Some(0)) != *inner,
+            Content::Text(_) => false,
+        })
+    }
+}
+
+fn is_row_path(path: &str) -> bool {
+    path == ROW
+        || path
+            .strip_prefix(ROW)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+impl Sheet {
+    fn parse(name: &str, xml: String, strings: &[String]) -> Result<Sheet> {
+        let xml = without_tag_containing(&xml, "<dimension ");
+        let (head, body, tail) = match (xml.find("<sheetData>"), xml.find("</sheetData>")) {
+            (Some(open), Some(close)) => {
+                let body_start = open + "<sheetData>".len();
+                (
+                    xml[..body_start].to_string(),
+                    &xml[body_start..close],
+                    xml[close..].to_string(),
+                )
+            }
+            // `<sheetData/>`: no rows
+            _ => (xml.clone(), "", String::new()),
+        };
+
+        let mut rows: Vec<TemplateRow> = Vec::new();
+        let mut pos = 0;
+        while let Some((start, end)) = find_element(body, "row", pos) {
+            let (tag, inner) = split_element(&body[start..end]);
+            let number = attr(tag, "r")
+                .and_then(|r| r.parse().ok())
+                .unwrap_or_else(|| rows.last().map_or(1, |row| row.number + 1));
+            let mut cells = Vec::new();
+            let mut cell_pos = 0;
+            while let Some((cell_start, cell_end)) = find_element(inner, "c", cell_pos) {
+                let (cell_tag, cell_inner) = split_element(&inner[cell_start..cell_end]);
+                let col = cells.last().map_or(0, |cell: &TemplateCell| cell.col + 1);
+                cells.push(TemplateCell::parse(cell_tag, cell_inner, col, strings));
+                cell_pos = cell_end;
+            }
+            rows.push(TemplateRow {
+                number,
+                attrs: other_attrs(tag, &["r"]),
+                cells,
+            });
+            pos = end;
+        }
+
+        let mut repeat = None;
+        for (i, row) in rows.iter().enumerate() {
+            if row.is_repeating() {
+                if let Some(first) = repeat.map(|first: usize| rows[first].number) {
+                    return Err(ExcelError::InvalidFormat(format!(
+                        "Sheet '{}' has repeating rows {} and {}; only one is supported",
+                        name, first, row.number
+                    )));
+                }
+                repeat = Some(i);
+            }
+        }
+
+        Ok(Sheet {
+            name: name.to_string(),
+            head,
+            rows,
+            tail,
+            repeat,
+        })
+    }
+
+    /// Write the filled sheet to `sink`; returns the number of items written
+    fn render(
+        &self,
+        context: &Values,
+        items: &mut dyn Iterator<Item = Result<Values>>,
+        sink: &mut dyn FnMut(&[u8]) -> Result<()>,
+    ) -> Result<u64> {
+        let mut xml = String::new();
+        let Some(repeat) = self.repeat else {
+            sink(self.head.as_bytes())?;
+            for row in &self.rows {
+                xml.clear();
+                self.push_row(&mut xml, row, row.number, Shift::NONE, context, None)?;
+                sink(xml.as_bytes())?;
+            }
+            return sink(self.tail.as_bytes()).map(|_| 0);
+        };
+
+        let template = &self.rows[repeat];
+        let collected = if template.refers_below() {
+            Some(items.collect::<Result<Vec<_>>>()?)
+        } else {
+            None
+        };
+        // Only formulas referring below the repeating row need the count
+        let mut shift = Shift {
+            row: template.number,
+            count: collected.as_ref().map_or(1, |items| items.len() as u32),
+        };
+        let mut item_rows = SpillBuffer::new(DEFAULT_SPILL_THRESHOLD);
+        let mut count = 0u32;
+        let mut render_item = |item: &Values, count: &mut u32| -> Result<()> {
+            xml.clear();
+            let number = template.number + *count;
+            let offset = Some((*count, item));
+            self.push_row(&mut xml, template, number, shift, context, offset)?;
+            item_rows.write_all(xml.as_bytes())?;
+            *count += 1;
+            Ok(())
+        };
+        match &collected {
+            Some(items) => {
+                for item in items {
+                    render_item(item, &mut count)?;
+                }
+            }
+            None => {
+                for item in items {
+                    render_item(&item?, &mut count)?;
+                }
+            }
+        }
+        shift.count = count;
+
+        sink(self.head.as_bytes())?;
+        for row in &self.rows[..repeat] {
+            xml.clear();
+            self.push_row(&mut xml, row, row.number, shift, context, None)?;
+            sink(xml.as_bytes())?;
+        }
+        item_rows.drain_into(&mut *sink)?;
+        for row in &self.rows[repeat + 1..] {
+            xml.clear();
+            self.push_row(
+                &mut xml,
+                row,
+                shift.row_number(row.number),
+                shift,
+                context,
+                None,
+            )?;
+            sink(xml.as_bytes())?;
+        }
+        sink(shift_tail(&self.tail, shift).as_bytes())?;
+        Ok(count as u64)
+    }
+
+    /// `<row>` for `row` written at `number`; `item` is the item index and
+    /// values for the repeating row
+    fn push_row(
+        &self,
+        xml: &mut String,
+        row: &TemplateRow,
+        number: u32,
+        shift: Shift,
+        context: &Values,
+        item: Option<(u32, &Values)>,
+    ) -> Result<()> {
+        xml.push_str("<row r=\"");
+        xml.push_str(itoa::Buffer::new().format(number));
+        xml.push('"');
+        xml.push_str(&row.attrs);
+        if row.cells.is_empty() {
+            xml.push_str("/>");
+            return Ok(());
+        }
+        xml.push('>');
+        for cell in &row.cells {
+            let reference = format!("{}{}", Cell::col_to_letter(cell.col), number);
+            match &cell.content {
+                Content::Raw { t, inner } => {
+                    push_cell_start(xml, &reference, &cell.attrs, t.as_deref());
+                    if inner.is_empty() {
+                        xml.push_str("/>");
+                    } else {
+                        xml.push('>');
+                        xml.push_str(&shift_cell_xml(inner, shift, item.map(|(i, _)| i)));
+                        xml.push_str("</c>");
+                    }
+                }
+                Content::Text(parts) => {
+                    let lookup = |path: &str| -> Result<&CellValue> {
+                        let values = match item {
+                            Some((_, values)) if is_row_path(path) => values,
+                            _ => context,
+                        };
+                        values.0.get(path).ok_or_else(|| {
+                            ExcelError::InvalidFormat(format!(
+                                "No value for placeholder {{{{{}}}}} in {}!{}{}",
+                                path,
+                                self.name,
+                                Cell::col_to_letter(cell.col),
+                                row.number
+                            ))
+                        })
+                    };
+                    if let [Part::Placeholder(path)] = parts.as_slice() {
+                        push_value(xml, &reference, cell, lookup(path)?);
+                    } else {
+                        let mut text = String::new();
+                        for part in parts {
+                            match part {
+                                Part::Literal(literal) => text.push_str(literal),
+                                Part::Placeholder(path) => {
+                                    text.push_str(&lookup(path)?.as_string())
+                                }
+                            }
+                        }
+                        push_text(xml, &reference, &cell.attrs, &text);
+                    }
+                }
+            }
+        }
+        xml.push_str("</row>");
+        Ok(())
+    }
+}
+
+impl TemplateCell {
+    fn parse(tag: &str, inner: &str, next_col: u32, strings: &[String]) -> TemplateCell {
+        let col = attr(tag, "r")
+            .and_then(cell_position)
+            .map_or(next_col, |(_, col)| col);
+        let t = attr(tag, "t");
+        let text = match t {
+            Some("s") => value_text(inner)
+                .and_then(|index| index.trim().parse::<usize>().ok())
+                .and_then(|index| strings.get(index).cloned()),
+            Some("inlineStr") => Some(parse_shared_string_item(inner)),
+            _ => None,
+        };
+        let content = match text.as_deref().and_then(parse_parts) {
+            Some(parts) => Content::Text(parts),
+            None => Content::Raw {
+                t: t.map(str::to_string),
+                inner: inner.to_string(),
+            },
+        };
+        TemplateCell {
+            col,
+            attrs: other_attrs(tag, &["r", "t"]),
+            styled: attr(tag, "s").is_some_and(|s| s != "0"),
+            content,
+        }
+    }
+}
+
+/// Text split at `{{path}}` placeholders; `None` without placeholders
+fn parse_parts(text: &str) -> Option<Vec<Part>> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find("{{") {
+        let Some(len) = rest[open + 2..].find("}}") else {
+            break;
+        };
+        let path = rest[open + 2..open + 2 + len].trim();
+        if path.is_empty() {
+            break;
+        }
+        if open > 0 {
+            parts.push(Part::Literal(rest[..open].to_string()));
+        }
+        parts.push(Part::Placeholder(path.to_string()));
+        rest = &rest[open + 2 + len + 2..];
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Literal(rest.to_string()));
+    }
+    Some(parts)
+}
+
+/// Text of the `<v>` element in a cell
+fn value_text(inner: &str) -> Option<&str> {
+    let start = inner.find("<v>")? + 3;
+    let end = start + inner[start..].find("</v>")?;
+    Some(&inner[start..end])
+}
+
+/// Byte range of the next `<name ...>...</name>` or `<name .../>` element
+/// starting at or after `pos`
+fn find_element(xml: &str, name: &str, pos: usize) -> Option<(usize, usize)> {
+    let open = format!("<{}", name);
+    let mut from = pos;
+    loop {
+        let start = from + xml[from..].find(&open)?;
+        let after = &xml[start + open.len()..];
+        from = start + open.len();
+        if !after.starts_with(|c: char| c.is_whitespace() || c == '/' || c == '>') {
+            continue;
+        }
+        let tag_end = start + xml[start..].find('>')? + 1;
+        if xml[..tag_end].ends_with("/>") {
+            return Some((start, tag_end));
+        }
+        let close = format!("</{}>", name);
+        let end = tag_end + xml[tag_end..].find(&close)? + close.len();
+        return Some((start, end));
+    }
+}
+
+/// Start tag and content of an element found by [`find_element`]
+fn split_element(element: &str) -> (&str, &str) {
+    let tag_end = element.find('>').map_or(element.len(), |i| i + 1);
+    let tag = &element[..tag_end];
+    if tag.ends_with("/>") {
+        return (tag, "");
+    }
+    let inner_end = element.rfind("</").unwrap_or(element.len()).max(tag_end);
+    (tag, &element[tag_end..inner_end])
+}
+
+/// Attributes of a start tag except `skip`, each as ` name="value"`
+fn other_attrs(tag: &str, skip: &[&str]) -> String {
+    let mut attrs = String::new();
+    let body = tag.trim_end_matches('>').trim_end_matches('/');
+    let mut rest = body.find(char::is_whitespace).map_or("", |i| &body[i..]);
+    loop {
+        rest = rest.trim_start();
+        let Some(eq) = rest.find('=') else {
+            break;
+        };
+        let name = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some(len) = value[1..].find(quote) else {
+            break;
+        };
+        if !skip.contains(&name) {
+            attrs.push(' ');
+            attrs.push_str(name);
+            attrs.push_str("=\"");
+            attrs.push_str(&value[1..1 + len].replace('"', "&quot;"));
+            attrs.push('"');
+        }
+        rest = &value[len + 2..];
+    }
+    attrs
+}
+
+fn push_cell_start(xml: &mut String, reference: &str, attrs: &str, t: Option<&str>) {
+    xml.push_str("<c r=\"");
+    xml.push_str(reference);
+    xml.push('"');
+    xml.push_str(attrs);
+    if let Some(t) = t {
+        xml.push_str(" t=\"");
+        xml.push_str(t);
+        xml.push('"');
+    }
+}
+
+/// Cell holding `value` as its own type
+fn push_value(xml: &mut String, reference: &str, cell: &TemplateCell, value: &CellValue) {
+    let number = |xml: &mut String, text: &str| {
+        push_cell_start(xml, reference, &cell.attrs, None);
+        xml.push_str("><v>");
+        xml.push_str(text);
+        xml.push_str("</v></c>");
+    };
+    match value {
+        CellValue::Empty => {
+            push_cell_start(xml, reference, &cell.attrs, None);
+            xml.push_str("/>");
+        }
+        CellValue::Int(i) => number(xml, itoa::Buffer::new().format(*i)),
+        CellValue::Float(f) if f.is_finite() => number(xml, &f.to_string()),
+        #[cfg(feature = "decimal")]
+        CellValue::Decimal(d) => number(xml, &d.to_string()),
+        CellValue::DateTime(serial) if cell.styled => number(xml, &serial.to_string()),
+        CellValue::DateTime(serial) => match serial_to_datetime(*serial) {
+            Some(datetime) => push_text(xml, reference, &cell.attrs, &format_iso(datetime)),
+            None => number(xml, &serial.to_string()),
+        },
+        CellValue::Bool(b) => {
+            push_cell_start(xml, reference, &cell.attrs, Some("b"));
+            xml.push_str(if *b { "><v>1</v></c>" } else { "><v>0</v></c>" });
+        }
+        other => push_text(xml, reference, &cell.attrs, &other.as_string()),
+    }
+}
+
+fn push_text(xml: &mut String, reference: &str, attrs: &str, text: &str) {
+    push_cell_start(xml, reference, attrs, Some("inlineStr"));
+    if text.starts_with(char::is_whitespace) || text.ends_with(char::is_whitespace) {
+        xml.push_str("><is><t xml:space=\"preserve\">");
+    } else {
+        xml.push_str("><is><t>");
+    }
+    push_escaped(xml, text);
+    xml.push_str("</t></is></c>");
+}
+
+fn push_escaped(xml: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            '"' => xml.push_str("&quot;"),
+            c if !crate::fast_writer::xml_writer::is_xml_char(c) => {}
+            c => xml.push(c),
+        }
+    }
+}
+
+/// Where rows move when the repeating row is written `count` times
+#[derive(Debug, Clone, Copy)]
+struct Shift {
+    /// 1-based number of the repeating row
+    row: u32,
+    count: u32,
+}
+
+impl Shift {
+    /// No repeating row: nothing moves
+    const NONE: Shift = Shift {
+        row: u32::MAX,
+        count: 1,
+    };
+
+    /// New number of a template row other than the repeating row
+    fn row_number(self, row: u32) -> u32 {
+        if row > self.row {
+            (row as i64 + self.count as i64 - 1) as u32
+        } else {
+            row
+        }
+    }
+
+    /// New row of a reference to `row`; `item` is set inside the repeating
+    /// row, `range_end` for the second half of a range
+    fn reference(self, row: u32, item: Option<u32>, range_end: bool) -> u32 {
+        if row != self.row {
+            return self.row_number(row);
+        }
+        match item {
+            Some(item) => row + item,
+            None if range_end && self.count > 0 => row + self.count - 1,
+            None => row,
+        }
+    }
+}
+
+/// `text` (a formula or a list of ranges) with its cell references moved
+fn shift_refs(text: &str, shift: Shift, item: Option<u32>) -> String {
+    map_row_references(text, |row, range_end| {
+        shift.reference(row + 1, item, range_end) - 1
+    })
+}
+
+/// Cell content with the references of its formula moved
+fn shift_cell_xml(inner: &str, shift: Shift, item: Option<u32>) -> String {
+    let Some((start, end)) = find_element(inner, "f", 0) else {
+        return inner.to_string();
+    };
+    let (tag, formula) = split_element(&inner[start..end]);
+    let tag = match attr(tag, "ref") {
+        Some(range) => tag.replacen(
+            &format!("ref=\"{}\"", range),
+            &format!("ref=\"{}\"", shift_refs(range, shift, item)),
+            1,
+        ),
+        None => tag.to_string(),
+    };
+    let mut out = String::with_capacity(inner.len() + 8);
+    out.push_str(&inner[..start]);
+    out.push_str(&tag);
+    if !formula.is_empty() || !tag.ends_with("/>") {
+        push_escaped(
+            &mut out,
+            &shift_refs(&decode_xml_entities(formula), shift, item),
+        );
+        out.push_str("</f>");
+    }
+    out.push_str(&inner[end..]);
+    out
+}
+
+/// Sheet XML after the rows, with merged cells, conditional formats, data
+/// validations and the like following moved rows
+fn shift_tail(tail: &str, shift: Shift) -> String {
+    let mut out = String::with_capacity(tail.len());
+    let mut merged = 0;
+    let mut pos = 0;
+    while let Some(start) = tail[pos..].find('<').map(|i| pos + i) {
+        let Some(end) = tail[start..].find('>').map(|i| start + i + 1) else {
+            break;
+        };
+        out.push_str(&tail[pos..start]);
+        let tag = &tail[start..end];
+        pos = end;
+
+        if tag.starts_with("<mergeCell ") {
+            let range = attr(tag, "ref").unwrap_or("");
+            let on_repeat_row = range
+                .split(':')
+                .all(|r| cell_position(r).is_some_and(|(row, _)| row + 1 == shift.row));
+            // A merge within the repeating row is repeated with it
+            let items: Vec<Option<u32>> = if on_repeat_row {
+                (0..shift.count).map(Some).collect()
+            } else {
+                vec![None]
+            };
+            for item in items {
+                let moved = shift_refs(range, shift, item);
+                out.push_str(&tag.replacen(
+                    &format!("ref=\"{}\"", range),
+                    &format!("ref=\"{}\"", moved),
+                    1,
+                ));
+                merged += 1;
+            }
+            continue;
+        }
+
+        let mut tag = tag.to_string();
+        for name in ["ref", "sqref"] {
+            if let Some(value) = attr(&tag, name) {
+                let moved = shift_refs(value, shift, None);
+                tag = tag.replacen(
+                    &format!(" {}=\"{}\"", name, value),
+                    &format!(" {}=\"{}\"", name, moved),
+                    1,
+                );
+            }
+        }
+        out.push_str(&tag);
+    }
+    out.push_str(&tail[pos..]);
+
+    match out.find("<mergeCells").and_then(|start| {
+        let end = start + out[start..].find('>')?;
+        let count = attr(&out[start..=end], "count")?;
+        Some((start, end, count.to_string()))
+    }) {
+        Some((start, end, count)) => {
+            let tag = out[start..=end].replacen(
+                &format!("count=\"{}\"", count),
+                &format!("count=\"{}\"", merged),
+                1,
+            );
+            out.replace_range(start..=end, &tag);
+            out
+        }
+        None => out,
+    }
+}
+
+/// `xml` with the first element tag containing `needle` removed
+fn without_tag_containing(xml: &str, needle: &str) -> String {
+    let Some(at) = xml.find(needle) else {
+        return xml.to_string();
+    };
+    let start = xml[..=at].rfind('<').unwrap_or(at);
+    let end = xml[at..].find("/>").map_or(xml.len(), |i| at + i + 2);
+    format!("{}{}", &xml[..start], &xml[end..])
+}
+
+fn without_tags_containing(xml: &str, needle: &str) -> String {
+    let mut xml = xml.to_string();
+    while xml.contains(needle) {
+        xml = without_tag_containing(&xml, needle);
+    }
+    xml
+}
+
+/// A VBA project part, its signatures or relationships
+fn is_vba_part(name: &str) -> bool {
+    let file = name.rsplit('/').next().unwrap_or(name);
+    file.starts_with("vbaProject") || file.starts_with("vbaData")
+}
+
+/// `[Content_Types].xml` of the output: a workbook rather than a template,
+/// without the parts that are left out
+fn output_content_types(xml: &str, has_calc_chain: bool, strip_macros: bool) -> String {
+    let mut xml = xml
+        .replace(TEMPLATE_TYPE, WORKBOOK_TYPE)
+        .replace(MACRO_TEMPLATE_TYPE, MACRO_WORKBOOK_TYPE);
+    if has_calc_chain {
+        xml = without_tag_containing(&xml, "calcChain.xml");
+    }
+    if strip_macros {
+        xml = xml.replace(MACRO_WORKBOOK_TYPE, WORKBOOK_TYPE);
+        // vbaProject, vbaProjectSignature and vbaData parts
+        xml = without_tags_containing(&xml, "vnd.ms-office.vba");
+        xml = without_tags_containing(&xml, "vbaData");
+    }
+    xml
+}
+
+/// workbook.xml telling Excel to recalculate every formula on open
+fn recalculate_on_open(xml: &str) -> String {
+    if let Some(start) = xml.find("<calcPr") {
+        let tag_end = start + xml[start..].find('>').unwrap_or(0);
+        if attr(&xml[start..=tag_end], "fullCalcOnLoad").is_some() {
+            return xml.to_string();
+        }
+        let at = start + "<calcPr".len();
+        return format!("{} fullCalcOnLoad=\"1\"{}", &xml[..at], &xml[at..]);
+    }
+    // calcPr follows definedNames, or sheets when there are none
+    let after = xml
+        .find("</definedNames>")
+        .map(|i| i + "</definedNames>".len())
+        .or_else(|| xml.find("</sheets>").map(|i| i + "</sheets>".len()));
+    match after {
+        Some(at) => format!("{}<calcPr fullCalcOnLoad=\"1\"/>{}", &xml[..at], &xml[at..]),
+        None => xml.to_string(),
+    }
+}
+
+fn context_error(what: &str) -> ExcelError {
+    ExcelError::NotSupported(format!("{} cannot be a template value", what))
+}
+
+/// Flattens a value into [`Values`], one entry per scalar
+struct ValueSerializer<'a> {
+    path: String,
+    values: &'a mut Values,
+}
+
+impl ValueSerializer<'_> {
+    fn insert(self, (value, _): (CellValue, CellStyle)) -> Result<()> {
+        if self.path.is_empty() {
+            return Err(ExcelError::NotSupported(
+                "The template context must be a struct or map".to_string(),
+            ));
+        }
+        self.values.0.insert(self.path, value);
+        Ok(())
+    }
+}
+
+macro_rules! scalars {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<()> {
+                let cell = CellSerializer::default().$method(value)?;
+                self.insert(cell)
+            }
+        )*
+    };
+}
+
+impl<'a> Serializer for ValueSerializer<'a> {
+    type Ok = ();
+    type Error = ExcelError;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Impossible<(), ExcelError>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Impossible<(), ExcelError>;
+
+    scalars! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.insert((CellValue::Empty, CellStyle::Default))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        let cell =
+            CellSerializer::default().serialize_unit_variant(name, variant_index, variant)?;
+        self.insert(cell)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>> {
+        Ok(Compound::new(self))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>> {
+        Ok(Compound::new(self))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>> {
+        Ok(Compound::new(self))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(context_error("an enum variant with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>> {
+        Ok(Compound::new(self))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>> {
+        Ok(Compound::new(self))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(context_error("an enum variant with data"))
+    }
+}
+
+/// Struct, map or sequence whose entries get paths below its own
+struct Compound<'a> {
+    path: String,
+    values: &'a mut Values,
+    index: usize,
+    key: Option<String>,
+}
+
+impl<'a> Compound<'a> {
+    fn new(parent: ValueSerializer<'a>) -> Self {
+        Compound {
+            path: parent.path,
+            values: parent.values,
+            index: 0,
+            key: None,
+        }
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> Result<()> {
+        let path = if self.path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.path, name)
+        };
+        value.serialize(ValueSerializer {
+            path,
+            values: &mut *self.values,
+        })
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let index = self.index;
+        self.index += 1;
+        self.field(itoa::Buffer::new().format(index), value)
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = ExcelError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = ExcelError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = ExcelError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = ExcelError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        let (key, _) = key.serialize(CellSerializer::default())?;
+        self.key = Some(key.as_string());
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ExcelError::WriteError("map value without a key".to_string()))?;
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = ExcelError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        // A skipped `Option` still fills its placeholders, with nothing
+        self.field(key, &())
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fast_writer::StreamingZipReader;
+    use crate::ExcelWriter;
+    use serde::Serialize;
+    use tempfile::TempDir;
+
+    #[derive(Serialize)]
+    struct Customer {
+        name: String,
+    }
+
+    #[derive(Serialize)]
+    struct Invoice {
+        number: u32,
+        customer: Customer,
+        tags: Vec<&'static str>,
+    }
+
+    #[derive(Serialize)]
+    struct Line {
+        sku: &'static str,
+        qty: u32,
+        price: f64,
+    }
+
+    fn sheet_xml(path: &Path) -> String {
+        let mut zip = StreamingZipReader::open(path).unwrap();
+        xml_text(&zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap())
+    }
+
+    fn template(path: &Path) {
+        let mut writer = ExcelWriter::new(path).unwrap();
+        writer
+            .write_row(["Invoice {{number}} for {{ customer.name }}"])
+            .unwrap();
+        writer.write_row(["{{number}}", "{{tags.1}}"]).unwrap();
+        writer
+            .write_header_bold(["SKU", "Qty", "Price", "Total"])
+            .unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::String("{{row.sku}}".into()),
+                CellValue::String("{{row.qty}}".into()),
+                CellValue::String("{{row.price}}".into()),
+                CellValue::Formula {
+                    expr: "B4*C4".into(),
+                    cached: None,
+                },
+            ])
+            .unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::String("Total".into()),
+                CellValue::Empty,
+                CellValue::Empty,
+                CellValue::Formula {
+                    expr: "SUM(D4:D4)".into(),
+                    cached: None,
+                },
+            ])
+            .unwrap();
+        writer.save().unwrap();
+    }
+
+    #[test]
+    fn test_render_template() {
+        let dir = TempDir::new().unwrap();
+        let (path, out) = (dir.path().join("t.xlsx"), dir.path().join("out.xlsx"));
+        template(&path);
+
+        let invoice = Invoice {
+            number: 1042,
+            customer: Customer {
+                name: "Acme & Co".into(),
+            },
+            tags: vec!["a", "b"],
+        };
+        let lines = [
+            Line {
+                sku: "A-1",
+                qty: 2,
+                price: 9.5,
+            },
+            Line {
+                sku: "B-7",
+                qty: 1,
+                price: 120.0,
+            },
+            Line {
+                sku: "C-3",
+                qty: 4,
+                price: 0.25,
+            },
+        ];
+        let written = TemplateEngine::open(&path)
+            .unwrap()
+            .render_with_rows(&out, &invoice, &lines)
+            .unwrap();
+        assert_eq!(written, 3);
+
+        let mut reader = StreamingReader::open(&out).unwrap();
+        let rows: Vec<Vec<String>> = reader
+            .rows("Sheet1")
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(rows[0], ["Invoice 1042 for Acme & Co"]);
+        assert_eq!(rows[1], ["1042", "b"]);
+        assert_eq!(rows[3][..3], ["A-1", "2", "9.5"]);
+        assert_eq!(rows[5][..3], ["C-3", "4", "0.25"]);
+
+        // Formulas follow their rows and the total covers every line
+        let xml = sheet_xml(&out);
+        assert!(xml.contains("<f>B5*C5</f>"), "{}", xml);
+        assert!(xml.contains("<f>SUM(D4:D6)</f>"), "{}", xml);
+        assert!(xml.contains("<row r=\"7\""), "{}", xml);
+        assert!(!xml.contains("{{"), "{}", xml);
+        let report = crate::validate::validate_package(&out).unwrap();
+        assert!(report.is_valid(), "{}", report);
+
+        // Without rows the repeating row is dropped
+        TemplateEngine::open(&path)
+            .unwrap()
+            .render(&out, &invoice)
+            .unwrap();
+        let xml = sheet_xml(&out);
+        assert!(xml.contains("<row r=\"4\""), "{}", xml);
+        assert!(!xml.contains("<row r=\"5\""), "{}", xml);
+    }
+
+    /// Copy of the workbook at `path` turned into a macro-enabled template
+    fn add_macros(path: &Path, out: &Path) {
+        let mut archive = StreamingZipReader::open(path).unwrap();
+        let mut zip = StreamingZipWriter::new(out).unwrap();
+        for entry in archive.entries().to_vec() {
+            let mut data = archive.read_entry(&entry).unwrap();
+            if entry.name == "[Content_Types].xml" {
+                data = xml_text(&data)
+                    .replace(WORKBOOK_TYPE, MACRO_TEMPLATE_TYPE)
+                    .replace(
+                        "<Default ",
+                        r#"<Default Extension="bin" ContentType="application/vnd.ms-office.vbaProject"/><Default "#,
+                    )
+                    .into_bytes();
+            } else if entry.name == "xl/_rels/workbook.xml.rels" {
+                data = xml_text(&data)
+                    .replace(
+                        "</Relationships>",
+                        r#"<Relationship Id="rId99" Type="http://schemas.microsoft.com/office/2006/relationships/vbaProject" Target="vbaProject.bin"/></Relationships>"#,
+                    )
+                    .into_bytes();
+            }
+            zip.start_entry(&entry.name).unwrap();
+            zip.write_data(&data).unwrap();
+        }
+        zip.start_entry("xl/vbaProject.bin").unwrap();
+        zip.write_data(b"\xD0\xCF\x11\xE0 not really OLE").unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[derive(Serialize)]
+    struct Greeting {
+        name: &'static str,
+    }
+
+    #[test]
+    fn test_macro_enabled_template() {
+        let dir = TempDir::new().unwrap();
+        let plain = dir.path().join("plain.xlsx");
+        let mut writer = ExcelWriter::new(&plain).unwrap();
+        writer.write_row(["Hello {{name}}"]).unwrap();
+        writer.save().unwrap();
+        let path = dir.path().join("t.xltm");
+        add_macros(&plain, &path);
+
+        assert!(!StreamingReader::open(&plain).unwrap().has_macros());
+        let mut reader = StreamingReader::open(&path).unwrap();
+        assert!(reader.has_macros());
+        let cells = reader.rows("Sheet1").unwrap().next().unwrap().unwrap();
+        assert_eq!(cells.to_strings(), ["Hello {{name}}"]);
+
+        let engine = TemplateEngine::open(&path).unwrap();
+        assert!(engine.has_macros());
+        let context = Greeting { name: "Ana" };
+        let err = engine
+            .render(dir.path().join("out.xlsx"), &context)
+            .unwrap_err();
+        assert!(err.to_string().contains("keep_macros(false)"), "{}", err);
+
+        // Kept: the VBA project is copied and the package is an .xlsm workbook
+        let xlsm = dir.path().join("out.xlsm");
+        engine.render(&xlsm, &context).unwrap();
+        let mut zip = StreamingZipReader::open(&xlsm).unwrap();
+        assert!(zip.read_entry_by_name("xl/vbaProject.bin").is_ok());
+        let types = xml_text(&zip.read_entry_by_name("[Content_Types].xml").unwrap());
+        assert!(types.contains(MACRO_WORKBOOK_TYPE));
+        assert!(!types.contains(MACRO_TEMPLATE_TYPE));
+        let mut reader = StreamingReader::open(&xlsm).unwrap();
+        assert!(reader.has_macros());
+        let cells = reader.rows("Sheet1").unwrap().next().unwrap().unwrap();
+        assert_eq!(cells.to_strings(), ["Hello Ana"]);
+
+        // Dropped: a plain workbook without any trace of the project
+        let xlsx = dir.path().join("plain_out.xlsx");
+        engine.keep_macros(false).render(&xlsx, &context).unwrap();
+        let mut zip = StreamingZipReader::open(&xlsx).unwrap();
+        assert!(zip.entries().iter().all(|e| !is_vba_part(&e.name)));
+        let types = xml_text(&zip.read_entry_by_name("[Content_Types].xml").unwrap());
+        assert!(types.contains(WORKBOOK_TYPE));
+        assert!(!types.contains("vba") && !types.contains("macroEnabled"));
+        let rels = xml_text(
+            &zip.read_entry_by_name("xl/_rels/workbook.xml.rels")
+                .unwrap(),
+        );
+        assert!(!rels.contains("vbaProject"));
+        assert!(!StreamingReader::open(&xlsx).unwrap().has_macros());
+    }
+
+    #[test]
+    fn test_missing_placeholder_value() {
+        let dir = TempDir::new().unwrap();
+        let (path, out) = (dir.path().join("t.xlsx"), dir.path().join("out.xlsx"));
+        template(&path);
+
+        let mut context = HashMap::new();
+        context.insert("number", 1);
+        let err = TemplateEngine::open(&path)
+            .unwrap()
+            .render(&out, &context)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("{{customer.name}} in Sheet1!A1"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_shift_refs() {
+        let shift = Shift { row: 5, count: 3 };
+        assert_eq!(
+            shift_refs("SUM(D5:D5)+$E$9", shift, None),
+            "SUM(D5:D7)+$E$11"
+        );
+        assert_eq!(shift_refs("B5*C5/D8", shift, Some(1)), "B6*C6/D10");
+        assert_eq!(
+            shift_refs("Other!A9&\"A9\"&LOG10(A1)", shift, None),
+            "Other!A9&\"A9\"&LOG10(A1)"
+        );
+        assert_eq!(shift_refs("A6:C6 E2", shift, None), "A8:C8 E2");
+        assert_eq!(
+            parse_parts("{{a}} and {{ b.c }}!"),
+            Some(vec![
+                Part::Placeholder("a".into()),
+                Part::Literal(" and ".into()),
+                Part::Placeholder("b.c".into()),
+                Part::Literal("!".into()),
+            ])
+        );
+        assert_eq!(parse_parts("no {{ }} here"), None);
+    }
+}
@@ -0,0 +1,44 @@
+//! Diagnostics through `tracing` (feature `tracing`)
+//!
+//! Without the feature the macros expand to nothing, except [`warning!`],
+//! which falls back to stderr. Their arguments are not evaluated then, so
+//! keep side effects out of them.
+
+/// Enter a debug-level span until the returned guard is dropped
+macro_rules! span {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        let guard = tracing::debug_span!($($arg)*).entered();
+        #[cfg(not(feature = "tracing"))]
+        let guard = $crate::trace::NoSpan;
+        guard
+    }};
+}
+
+/// Debug-level event
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+/// Warning event from a plain format string; printed to stderr without
+/// the `tracing` feature
+#[allow(unused_macros)] // Only the cloud writers warn so far
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::warn!($($arg)*);
+        #[cfg(not(feature = "tracing"))]
+        eprintln!("warning: {}", format_args!($($arg)*));
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use warning;
+pub(crate) use {debug, span};
+
+/// Stands in for an entered span without the `tracing` feature
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct NoSpan;
@@ -0,0 +1,307 @@
+//! Conversions between [`CellValue`] and Rust values
+
+use super::{CellValue, DateSystem};
+
+#[cfg(feature = "decimal")]
+use rust_decimal::prelude::ToPrimitive;
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
+#[cfg(feature = "decimal")]
+use std::str::FromStr;
+
+impl CellValue {
+    /// Convert cell value to string
+    pub fn as_string(&self) -> String {
+        match self {
+            CellValue::Empty => String::new(),
+            CellValue::String(s) => s.clone(),
+            CellValue::Int(i) => i.to_string(),
+            CellValue::Float(f) => f.to_string(),
+            CellValue::Bool(b) => b.to_string(),
+            CellValue::DateTime(d) => d.to_string(),
+            CellValue::Error(e) => format!("ERROR: {}", e),
+            CellValue::Formula { expr, .. } => expr.clone(),
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => d.to_string(),
+            CellValue::BigInt(s) => s.clone(),
+            CellValue::Hyperlink { text, .. } => text.clone(),
+        }
+    }
+
+    /// Try to convert to integer
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            CellValue::Int(i) => Some(*i),
+            CellValue::Float(f) => Some(*f as i64),
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => d.trunc().to_i64(),
+            CellValue::String(s) | CellValue::BigInt(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Try to convert to float
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            CellValue::Float(f) => Some(*f),
+            CellValue::Int(i) => Some(*i as f64),
+            CellValue::DateTime(d) => Some(*d),
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => d.to_f64(),
+            CellValue::String(s) | CellValue::BigInt(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Try to convert to an exact decimal
+    ///
+    /// Numbers read back from a sheet arrive as `Float`; they are converted
+    /// through their shortest round-trip text (so `1234.56` stays `1234.56`
+    /// rather than `1234.5599999999999`).
+    #[cfg(feature = "decimal")]
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            CellValue::Decimal(d) => Some(*d),
+            CellValue::Int(i) => Some(Decimal::from(*i)),
+            CellValue::Float(f) if f.is_finite() => Decimal::from_str(&f.to_string()).ok(),
+            CellValue::String(s) | CellValue::BigInt(s) => Decimal::from_str(s.trim()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Try to convert to boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            CellValue::Bool(b) => Some(*b),
+            CellValue::Int(i) => Some(*i != 0),
+            CellValue::String(s) => match s.to_lowercase().as_str() {
+                "true" | "yes" | "1" => Some(true),
+                "false" | "no" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for CellValue {
+    fn from(s: &str) -> Self {
+        CellValue::String(s.to_string())
+    }
+}
+
+impl From<String> for CellValue {
+    fn from(s: String) -> Self {
+        CellValue::String(s)
+    }
+}
+
+impl From<i64> for CellValue {
+    fn from(i: i64) -> Self {
+        CellValue::Int(i)
+    }
+}
+
+impl From<u64> for CellValue {
+    fn from(i: u64) -> Self {
+        CellValue::integer(i as i128)
+    }
+}
+
+impl From<i128> for CellValue {
+    fn from(i: i128) -> Self {
+        CellValue::integer(i)
+    }
+}
+
+impl From<u128> for CellValue {
+    fn from(i: u128) -> Self {
+        match i128::try_from(i) {
+            Ok(i) => CellValue::integer(i),
+            Err(_) => CellValue::BigInt(i.to_string()),
+        }
+    }
+}
+
+impl From<f64> for CellValue {
+    fn from(f: f64) -> Self {
+        CellValue::Float(f)
+    }
+}
+
+impl From<bool> for CellValue {
+    fn from(b: bool) -> Self {
+        CellValue::Bool(b)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl From<Decimal> for CellValue {
+    fn from(d: Decimal) -> Self {
+        CellValue::Decimal(d)
+    }
+}
+
+impl From<chrono::NaiveDate> for CellValue {
+    fn from(date: chrono::NaiveDate) -> Self {
+        CellValue::from(date.and_time(chrono::NaiveTime::MIN))
+    }
+}
+
+impl From<chrono::NaiveDateTime> for CellValue {
+    fn from(datetime: chrono::NaiveDateTime) -> Self {
+        CellValue::DateTime(DateSystem::V1900.serial(datetime))
+    }
+}
+
+/// The UTC date and time; see [`DateSystem`] for the serial it becomes
+impl From<chrono::DateTime<chrono::Utc>> for CellValue {
+    fn from(datetime: chrono::DateTime<chrono::Utc>) -> Self {
+        CellValue::from(datetime.naive_utc())
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::Date> for CellValue {
+    fn from(date: time::Date) -> Self {
+        CellValue::from(time::PrimitiveDateTime::new(date, time::Time::MIDNIGHT))
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::PrimitiveDateTime> for CellValue {
+    fn from(datetime: time::PrimitiveDateTime) -> Self {
+        // Excel has no dates before 1900, so the first day of the epoch
+        // stands in for the years chrono can't hold
+        let date = chrono::NaiveDate::from_ymd_opt(
+            datetime.year(),
+            u8::from(datetime.month()) as u32,
+            datetime.day() as u32,
+        )
+        .unwrap_or(chrono::NaiveDate::MIN);
+        let time = chrono::NaiveTime::from_hms_nano_opt(
+            datetime.hour() as u32,
+            datetime.minute() as u32,
+            datetime.second() as u32,
+            datetime.nanosecond(),
+        )
+        .unwrap_or(chrono::NaiveTime::MIN);
+        CellValue::from(date.and_time(time))
+    }
+}
+
+/// The UTC date and time; see [`DateSystem`] for the serial it becomes
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for CellValue {
+    fn from(datetime: time::OffsetDateTime) -> Self {
+        let utc = datetime.to_offset(time::UtcOffset::UTC);
+        CellValue::from(time::PrimitiveDateTime::new(utc.date(), utc.time()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MAX_EXACT_EXCEL_INT;
+
+    #[test]
+    fn test_cell_value_conversions() {
+        let val = CellValue::Int(42);
+        assert_eq!(val.as_i64(), Some(42));
+        assert_eq!(val.as_f64(), Some(42.0));
+
+        let val = CellValue::String("true".to_string());
+        assert_eq!(val.as_bool(), Some(true));
+    }
+
+    #[test]
+    fn test_date_conversions() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            CellValue::from(date(2024, 1, 15)),
+            CellValue::DateTime(45306.0)
+        );
+        assert_eq!(
+            CellValue::from(date(2024, 1, 15).and_hms_opt(18, 0, 0).unwrap()),
+            CellValue::DateTime(45306.75)
+        );
+        assert_eq!(
+            CellValue::from(Utc.with_ymd_and_hms(1970, 1, 1, 6, 0, 0).unwrap()),
+            CellValue::DateTime(25569.25)
+        );
+
+        // Excel counts the nonexistent 1900-02-29 as serial 60
+        let v1900 = DateSystem::V1900;
+        let midnight = |d: NaiveDate| d.and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(v1900.serial(midnight(date(1900, 1, 1))), 1.0);
+        assert_eq!(v1900.serial(midnight(date(1900, 2, 28))), 59.0);
+        assert_eq!(v1900.serial(midnight(date(1900, 3, 1))), 61.0);
+        assert_eq!(v1900.datetime(59.0), Some(midnight(date(1900, 2, 28))));
+        assert_eq!(v1900.datetime(61.0), Some(midnight(date(1900, 3, 1))));
+        assert_eq!(
+            v1900.datetime(2_958_465.0),
+            Some(midnight(date(9999, 12, 31)))
+        );
+        assert_eq!(v1900.datetime(2_958_466.0), None);
+        assert_eq!(v1900.datetime(-1.0), None);
+
+        let v1904 = DateSystem::V1904;
+        assert_eq!(v1904.serial(midnight(date(1904, 1, 1))), 0.0);
+        assert_eq!(v1904.serial(midnight(date(2024, 1, 15))), 43844.0);
+        assert_eq!(v1904.datetime(0.5), date(1904, 1, 1).and_hms_opt(12, 0, 0));
+        assert_eq!(v1904.rebase(45306.0), 43844.0);
+
+        #[cfg(feature = "time")]
+        {
+            let day = time::Date::from_calendar_date(2024, time::Month::January, 15).unwrap();
+            assert_eq!(CellValue::from(day), CellValue::DateTime(45306.0));
+            let evening = day
+                .with_hms(20, 0, 0)
+                .unwrap()
+                .assume_offset(time::UtcOffset::from_hms(2, 0, 0).unwrap());
+            assert_eq!(CellValue::from(evening), CellValue::DateTime(45306.75));
+        }
+    }
+
+    #[test]
+    fn test_big_integer_conversions() {
+        assert_eq!(CellValue::from(42u64), CellValue::Int(42));
+        assert_eq!(
+            CellValue::from(MAX_EXACT_EXCEL_INT as u64),
+            CellValue::Int(MAX_EXACT_EXCEL_INT)
+        );
+
+        let id = 12345678901234567890u64;
+        let val = CellValue::from(id);
+        assert_eq!(val, CellValue::BigInt("12345678901234567890".to_string()));
+        assert_eq!(val.as_string(), "12345678901234567890");
+        assert_eq!(val.as_i64(), None);
+
+        let val = CellValue::from(u128::MAX);
+        assert_eq!(val.as_string(), u128::MAX.to_string());
+
+        let val = CellValue::integer(-(MAX_EXACT_EXCEL_INT as i128) - 1);
+        assert_eq!(val.as_string(), "-9007199254740992");
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_decimal_conversions() {
+        use crate::types::{CellStyle, StyledCell};
+
+        let d = Decimal::from_str("1234.56").unwrap();
+        let val = CellValue::from(d);
+        assert_eq!(val.as_string(), "1234.56");
+        assert_eq!(val.as_decimal(), Some(d));
+        assert_eq!(val.as_i64(), Some(1234));
+
+        // Values read back as floats keep their exact lexical form
+        assert_eq!(CellValue::Float(1234.56).as_decimal(), Some(d));
+        assert_eq!(CellValue::String(" 1234.56 ".into()).as_decimal(), Some(d));
+
+        let styled = StyledCell::default_style(val);
+        assert_eq!(styled.effective_style(), CellStyle::NumberDecimal);
+    }
+}
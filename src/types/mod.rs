@@ -0,0 +1,1979 @@
+//! Type definitions for Excel data
+
+use std::fmt;
+
+mod conversions;
+
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
+
+/// Cell style presets for formatting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellStyle {
+    /// Default style - no formatting
+    Default = 0,
+    /// Bold text for headers
+    HeaderBold = 1,
+    /// Integer format with thousand separator (#,##0)
+    NumberInteger = 2,
+    /// Decimal format with 2 places (#,##0.00)
+    NumberDecimal = 3,
+    /// Currency format ($#,##0.00)
+    NumberCurrency = 4,
+    /// Percentage format (0.00%)
+    NumberPercentage = 5,
+    /// Date format (MM/DD/YYYY)
+    DateDefault = 6,
+    /// DateTime format (MM/DD/YYYY HH:MM:SS)
+    DateTimestamp = 7,
+    /// Bold text for emphasis
+    TextBold = 8,
+    /// Italic text for notes
+    TextItalic = 9,
+    /// Yellow background highlight
+    HighlightYellow = 10,
+    /// Green background highlight
+    HighlightGreen = 11,
+    /// Red background highlight
+    HighlightRed = 12,
+    /// Thin borders on all sides
+    BorderThin = 13,
+    /// DateTime format without seconds (MM/DD/YYYY HH:MM)
+    DateTimeShort = 14,
+    /// Blue underlined text, applied to hyperlink cells by default
+    Hyperlink = 15,
+}
+
+impl CellStyle {
+    /// Get the style index for XML
+    pub fn index(&self) -> u32 {
+        *self as u32
+    }
+
+    /// This style as written for `value`, see [`StyledCell::effective_style`]
+    pub(crate) fn effective_for(self, value: &CellValue) -> CellStyle {
+        #[cfg(feature = "decimal")]
+        if matches!(value, CellValue::Decimal(_)) && self == CellStyle::Default {
+            return CellStyle::NumberDecimal;
+        }
+        if matches!(value, CellValue::Hyperlink { .. }) && self == CellStyle::Default {
+            return CellStyle::Hyperlink;
+        }
+        self
+    }
+}
+
+/// Styled cell value (combines value with formatting)
+#[derive(Debug, Clone)]
+pub struct StyledCell {
+    /// The cell value
+    pub value: CellValue,
+    /// The cell style
+    pub style: CellStyle,
+}
+
+impl StyledCell {
+    /// Create a new styled cell
+    pub fn new(value: CellValue, style: CellStyle) -> Self {
+        StyledCell { value, style }
+    }
+
+    /// Create a cell with default style
+    pub fn default_style(value: CellValue) -> Self {
+        StyledCell {
+            value,
+            style: CellStyle::Default,
+        }
+    }
+
+    /// Style index that is actually written for this cell
+    ///
+    /// Decimal values without an explicit style get the `#,##0.00` number
+    /// format instead of General, and hyperlinks get [`CellStyle::Hyperlink`].
+    pub fn effective_style(&self) -> CellStyle {
+        self.style.effective_for(&self.value)
+    }
+}
+
+impl From<CellValue> for StyledCell {
+    fn from(value: CellValue) -> Self {
+        StyledCell::default_style(value)
+    }
+}
+
+/// Represents a single cell value in an Excel worksheet
+///
+/// The enum is `#[non_exhaustive]`: variants are added with new cell types
+/// and behind cargo features (`Decimal` with `decimal`), so a `match` on it
+/// needs a `_` arm.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum CellValue {
+    /// Empty cell
+    Empty,
+    /// String value
+    String(String),
+    /// Integer value
+    Int(i64),
+    /// Float value
+    Float(f64),
+    /// Boolean value
+    Bool(bool),
+    /// DateTime value (Excel serial date number)
+    DateTime(f64),
+    /// Error value
+    Error(String),
+    /// Formula, with the result shown until the workbook is recalculated
+    ///
+    /// Build it with [`CellValue::formula`] or [`CellValue::formula_with_result`].
+    Formula {
+        /// Excel formula syntax, with or without the leading `=`
+        /// (e.g. `"=SUM(A1:A10)"`)
+        expr: String,
+        /// Result stored with the formula, for viewers that don't calculate
+        cached: Option<Box<CellValue>>,
+    },
+    /// Exact decimal value (e.g. money), written without going through f64
+    #[cfg(feature = "decimal")]
+    Decimal(Decimal),
+    /// Integer too large to survive Excel's f64 numbers (e.g. 20-digit IDs)
+    ///
+    /// Holds the decimal digits and is written as text so no digit is lost.
+    BigInt(String),
+    /// Clickable link showing `text`
+    ///
+    /// `url` is an external address (`https://...`, `mailto:...`) or, when it
+    /// starts with `#`, a location in the workbook such as `#'Sheet 2'!A1`.
+    /// Writers without hyperlink support write `text` only.
+    Hyperlink {
+        /// Link target
+        url: String,
+        /// Text shown in the cell
+        text: String,
+    },
+}
+
+/// Largest integer magnitude Excel can store exactly (2^53 - 1)
+pub const MAX_EXACT_EXCEL_INT: i64 = (1 << 53) - 1;
+
+impl CellValue {
+    /// Build a formula cell without a cached result
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use excelstream::types::CellValue;
+    ///
+    /// let total = CellValue::formula("=SUM(B2:B10)");
+    /// assert_eq!(total.as_string(), "=SUM(B2:B10)");
+    /// ```
+    pub fn formula(expr: impl Into<String>) -> Self {
+        CellValue::Formula {
+            expr: expr.into(),
+            cached: None,
+        }
+    }
+
+    /// Build a formula cell that also stores its current result
+    ///
+    /// Excel recalculates the formula, but viewers and libraries that don't
+    /// evaluate formulas show `result` instead of an empty cell.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use excelstream::types::CellValue;
+    ///
+    /// let total = CellValue::formula_with_result("=A2+B2", CellValue::Int(30));
+    /// ```
+    pub fn formula_with_result(expr: impl Into<String>, result: CellValue) -> Self {
+        CellValue::Formula {
+            expr: expr.into(),
+            cached: Some(Box::new(result)),
+        }
+    }
+
+    /// `t` attribute and `<v>` text of this value as a formula's cached
+    /// result in SpreadsheetML, or `None` if it can't be cached
+    pub(crate) fn formula_result(&self) -> Option<(&'static str, String)> {
+        match self {
+            CellValue::Empty | CellValue::Formula { .. } => None,
+            CellValue::Int(i) => Some(("", i.to_string())),
+            CellValue::Float(f) | CellValue::DateTime(f) if f.is_finite() => {
+                Some(("", crate::fast_writer::number::f64_text(*f)))
+            }
+            CellValue::Float(_) | CellValue::DateTime(_) => None,
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => Some(("", d.to_string())),
+            CellValue::Bool(b) => Some((" t=\"b\"", if *b { "1" } else { "0" }.to_string())),
+            CellValue::Error(e) => Some((" t=\"e\"", e.clone())),
+            CellValue::String(s) | CellValue::BigInt(s) | CellValue::Hyperlink { text: s, .. } => {
+                Some((" t=\"str\"", s.clone()))
+            }
+        }
+    }
+
+    /// Build a hyperlink cell
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use excelstream::types::CellValue;
+    ///
+    /// let link = CellValue::hyperlink("https://example.com/audit/42", "Audit #42");
+    /// assert_eq!(link.as_string(), "Audit #42");
+    /// ```
+    pub fn hyperlink(url: impl Into<String>, text: impl Into<String>) -> Self {
+        CellValue::Hyperlink {
+            url: url.into(),
+            text: text.into(),
+        }
+    }
+
+    /// Build an integer cell, falling back to `BigInt` when Excel can't hold it exactly
+    ///
+    /// Values above [`MAX_EXACT_EXCEL_INT`] in magnitude would be rounded once
+    /// Excel loads them as doubles, so they are kept as text instead.
+    pub fn integer(value: i128) -> Self {
+        if (-(MAX_EXACT_EXCEL_INT as i128)..=MAX_EXACT_EXCEL_INT as i128).contains(&value) {
+            CellValue::Int(value as i64)
+        } else {
+            CellValue::BigInt(value.to_string())
+        }
+    }
+
+    /// Style for a cell written without one: dates get a date format, so they
+    /// don't show as serial numbers
+    pub(crate) fn default_style(&self) -> CellStyle {
+        match self {
+            CellValue::DateTime(serial) if serial.fract() == 0.0 => CellStyle::DateDefault,
+            CellValue::DateTime(_) => CellStyle::DateTimestamp,
+            _ => CellStyle::Default,
+        }
+    }
+
+    /// Check if cell is empty
+    pub fn is_empty(&self) -> bool {
+        matches!(self, CellValue::Empty)
+    }
+
+    /// Whether the cell shows nothing: `Empty`, or a string of only
+    /// whitespace
+    ///
+    /// Every blank-row and blank-cell option of the readers uses this test.
+    pub fn is_blank(&self) -> bool {
+        match self {
+            CellValue::Empty => true,
+            CellValue::String(s) => s.trim().is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Same output as `as_string()`, without the intermediate allocation
+        match self {
+            CellValue::Empty => Ok(()),
+            CellValue::String(s) => f.write_str(s),
+            CellValue::Int(i) => write!(f, "{}", i),
+            CellValue::Float(v) => write!(f, "{}", v),
+            CellValue::Bool(b) => write!(f, "{}", b),
+            CellValue::DateTime(d) => write!(f, "{}", d),
+            CellValue::Error(e) => write!(f, "ERROR: {}", e),
+            CellValue::Formula { expr, .. } => f.write_str(expr),
+            #[cfg(feature = "decimal")]
+            CellValue::Decimal(d) => write!(f, "{}", d),
+            CellValue::BigInt(s) => f.write_str(s),
+            CellValue::Hyperlink { text, .. } => f.write_str(text),
+        }
+    }
+}
+
+/// Cell value that borrows its text instead of owning it
+///
+/// Row writers such as
+/// [`ExcelWriter::write_row_refs`](crate::ExcelWriter::write_row_refs)
+/// take these, so strings already held by the caller go straight into the
+/// XML without a `String` copy per cell. Anything beyond plain text,
+/// numbers, booleans and dates can be passed as a [`CellRef::Value`].
+///
+/// ```
+/// use excelstream::types::{CellRef, CellValue};
+///
+/// let name = String::from("Alice");
+/// let row = [CellRef::from(name.as_str()), CellRef::from(30), CellRef::Empty];
+/// assert_eq!(row[0].into_owned(), CellValue::String("Alice".to_string()));
+///
+/// let link = CellValue::hyperlink("https://example.com", "Home");
+/// assert_eq!(CellRef::from(&link), CellRef::Value(&link));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellRef<'a> {
+    /// Empty cell
+    Empty,
+    /// Borrowed string value
+    String(&'a str),
+    /// Integer value
+    Int(i64),
+    /// Float value
+    Float(f64),
+    /// Boolean value
+    Bool(bool),
+    /// DateTime value (Excel serial date number)
+    DateTime(f64),
+    /// Any other value, by reference
+    Value(&'a CellValue),
+}
+
+impl CellRef<'_> {
+    /// The same value as an owned [`CellValue`]
+    pub fn into_owned(self) -> CellValue {
+        match self {
+            CellRef::Empty => CellValue::Empty,
+            CellRef::String(s) => CellValue::String(s.to_string()),
+            CellRef::Int(i) => CellValue::Int(i),
+            CellRef::Float(f) => CellValue::Float(f),
+            CellRef::Bool(b) => CellValue::Bool(b),
+            CellRef::DateTime(dt) => CellValue::DateTime(dt),
+            CellRef::Value(value) => value.clone(),
+        }
+    }
+}
+
+impl<'a> From<&'a CellValue> for CellRef<'a> {
+    fn from(value: &'a CellValue) -> Self {
+        match value {
+            CellValue::Empty => CellRef::Empty,
+            CellValue::String(s) => CellRef::String(s),
+            CellValue::Int(i) => CellRef::Int(*i),
+            CellValue::Float(f) => CellRef::Float(*f),
+            CellValue::Bool(b) => CellRef::Bool(*b),
+            CellValue::DateTime(dt) => CellRef::DateTime(*dt),
+            value => CellRef::Value(value),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for CellRef<'a> {
+    fn from(s: &'a str) -> Self {
+        CellRef::String(s)
+    }
+}
+
+impl<'a> From<&'a String> for CellRef<'a> {
+    fn from(s: &'a String) -> Self {
+        CellRef::String(s)
+    }
+}
+
+impl From<i64> for CellRef<'_> {
+    fn from(i: i64) -> Self {
+        CellRef::Int(i)
+    }
+}
+
+impl From<f64> for CellRef<'_> {
+    fn from(f: f64) -> Self {
+        CellRef::Float(f)
+    }
+}
+
+impl From<bool> for CellRef<'_> {
+    fn from(b: bool) -> Self {
+        CellRef::Bool(b)
+    }
+}
+
+impl<'a, T> From<Option<T>> for CellRef<'a>
+where
+    T: Into<CellRef<'a>>,
+{
+    fn from(value: Option<T>) -> Self {
+        value.map_or(CellRef::Empty, Into::into)
+    }
+}
+
+/// One column of a [`ExcelWriter::write_columns`](crate::ExcelWriter::write_columns)
+/// batch, borrowed from the producer
+///
+/// ```
+/// use excelstream::types::{CellRef, ColumnSlice};
+///
+/// let names = ["Alice", "Bob"];
+/// let column = ColumnSlice::Str(&names);
+/// assert_eq!(column.len(), 2);
+/// assert_eq!(column.get(1), Some(CellRef::String("Bob")));
+/// assert_eq!(column.get(2), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnSlice<'a> {
+    /// Integers
+    Int(&'a [i64]),
+    /// Floats
+    Float(&'a [f64]),
+    /// Booleans
+    Bool(&'a [bool]),
+    /// Excel serial dates, written with a date format
+    DateTime(&'a [f64]),
+    /// Owned strings
+    String(&'a [String]),
+    /// Borrowed strings
+    Str(&'a [&'a str]),
+    /// Values of any type, e.g. a column with empty cells
+    Values(&'a [CellValue]),
+}
+
+impl<'a> ColumnSlice<'a> {
+    /// Number of values in the column
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnSlice::Int(values) => values.len(),
+            ColumnSlice::Float(values) | ColumnSlice::DateTime(values) => values.len(),
+            ColumnSlice::Bool(values) => values.len(),
+            ColumnSlice::String(values) => values.len(),
+            ColumnSlice::Str(values) => values.len(),
+            ColumnSlice::Values(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The cell at 0-based `row`, or `None` past the end of the column
+    pub fn get(&self, row: usize) -> Option<CellRef<'a>> {
+        Some(match *self {
+            ColumnSlice::Int(values) => CellRef::Int(*values.get(row)?),
+            ColumnSlice::Float(values) => CellRef::Float(*values.get(row)?),
+            ColumnSlice::Bool(values) => CellRef::Bool(*values.get(row)?),
+            ColumnSlice::DateTime(values) => CellRef::DateTime(*values.get(row)?),
+            ColumnSlice::String(values) => CellRef::String(values.get(row)?),
+            ColumnSlice::Str(values) => CellRef::String(values.get(row)?),
+            ColumnSlice::Values(values) => CellRef::from(values.get(row)?),
+        })
+    }
+}
+
+/// Day zero of the serial numbers a workbook stores dates as
+///
+/// [`CellValue::DateTime`] always holds a serial in the 1900 system, which
+/// is what the `From` conversions for chrono (and, with the `time` feature,
+/// `time`) types produce. A workbook written with
+/// [`ExcelWriter::set_date_system`](crate::ExcelWriter::set_date_system)
+/// set to `V1904` stores those dates re-based, so the same values work
+/// with either system.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use excelstream::types::{CellValue, DateSystem};
+///
+/// let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+/// assert_eq!(CellValue::from(date), CellValue::DateTime(45306.0));
+///
+/// let noon = date.and_hms_opt(12, 0, 0).unwrap();
+/// assert_eq!(DateSystem::V1904.serial(noon), 43844.5);
+/// assert_eq!(DateSystem::V1904.datetime(43844.5), Some(noon));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateSystem {
+    /// Serial 1 is 1900-01-01 (the default). Like Excel, serial 60 is
+    /// 1900-02-29, a day that never existed.
+    #[default]
+    V1900,
+    /// Serial 0 is 1904-01-01, as in workbooks from early Mac Excel
+    V1904,
+}
+
+impl DateSystem {
+    /// Days from the 1900 system's day zero to the 1904 system's
+    const DAYS_1900_TO_1904: f64 = 1462.0;
+    /// Serial of 1900-03-01, the first day the 1900 system counts correctly
+    const MARCH_1900: f64 = 61.0;
+    /// Serial of 9999-12-31 in the 1900 system, the last day Excel shows
+    const MAX_1900: f64 = 2_958_465.0;
+
+    /// Serial number of `datetime`, the time of day as the fraction
+    pub fn serial(self, datetime: chrono::NaiveDateTime) -> f64 {
+        let days = (datetime - Self::day_zero()).num_milliseconds() as f64 / 86_400_000.0;
+        match self {
+            // Before the phantom 1900-02-29 Excel's count is one day lower
+            DateSystem::V1900 if (1.0..Self::MARCH_1900).contains(&days) => days - 1.0,
+            DateSystem::V1900 => days,
+            DateSystem::V1904 => days - Self::DAYS_1900_TO_1904,
+        }
+    }
+
+    /// Date and time of `serial`, to the millisecond, or `None` outside
+    /// the dates Excel shows (serial 60 of the 1900 system, 1900-02-29,
+    /// reads as 1900-03-01)
+    pub fn datetime(self, serial: f64) -> Option<chrono::NaiveDateTime> {
+        let days = match self {
+            DateSystem::V1900 if (1.0..Self::MARCH_1900).contains(&serial) => serial + 1.0,
+            DateSystem::V1900 => serial,
+            DateSystem::V1904 => serial + Self::DAYS_1900_TO_1904,
+        };
+        if !(0.0..Self::MAX_1900 + 1.0).contains(&days) {
+            return None;
+        }
+        let millis = (days * 86_400_000.0).round() as i64;
+        Self::day_zero().checked_add_signed(chrono::Duration::try_milliseconds(millis)?)
+    }
+
+    /// `serial` from the 1900 system as a serial in this system
+    pub(crate) fn rebase(self, serial: f64) -> f64 {
+        match self {
+            DateSystem::V1900 => serial,
+            DateSystem::V1904 => serial - Self::DAYS_1900_TO_1904,
+        }
+    }
+
+    /// 1899-12-30, from which both systems count once past February 1900
+    fn day_zero() -> chrono::NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(1899, 12, 30)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .expect("valid epoch")
+    }
+}
+
+/// How a timestamp with a time zone becomes a date cell
+///
+/// Excel dates carry no time zone, so a `DateTime<Tz>` has to be reduced to
+/// a wall-clock time first. Exporting timestamps from several zones with
+/// `WallTime` mixes clocks in one column; `Utc` or `Offset` put them all on
+/// the same clock.
+///
+/// ```
+/// use chrono::{FixedOffset, TimeZone};
+/// use excelstream::types::{CellValue, TimezonePolicy};
+///
+/// let hanoi = FixedOffset::east_opt(7 * 3600).unwrap();
+/// let opened = hanoi.with_ymd_and_hms(2024, 1, 15, 19, 0, 0).unwrap();
+///
+/// assert_eq!(TimezonePolicy::WallTime.cell(&opened), CellValue::DateTime(45306.0 + 19.0 / 24.0));
+/// assert_eq!(TimezonePolicy::Utc.cell(&opened), CellValue::DateTime(45306.5));
+/// assert_eq!(TimezonePolicy::Utc.to_string(), "UTC");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimezonePolicy {
+    /// Keep the time each timestamp shows in its own zone (the default)
+    #[default]
+    WallTime,
+    /// Convert to UTC
+    Utc,
+    /// Convert to a fixed offset from UTC
+    Offset(chrono::FixedOffset),
+}
+
+impl TimezonePolicy {
+    /// Wall-clock time of `datetime` under this policy
+    pub fn naive<Tz: chrono::TimeZone>(
+        self,
+        datetime: &chrono::DateTime<Tz>,
+    ) -> chrono::NaiveDateTime {
+        match self {
+            TimezonePolicy::WallTime => datetime.naive_local(),
+            TimezonePolicy::Utc => datetime.naive_utc(),
+            TimezonePolicy::Offset(offset) => datetime.with_timezone(&offset).naive_local(),
+        }
+    }
+
+    /// Date cell for `datetime` under this policy
+    pub fn cell<Tz: chrono::TimeZone>(self, datetime: &chrono::DateTime<Tz>) -> CellValue {
+        CellValue::from(self.naive(datetime))
+    }
+}
+
+/// `UTC`, the offset as `UTC+07:00`, or `wall time`
+impl fmt::Display for TimezonePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimezonePolicy::WallTime => f.write_str("wall time"),
+            TimezonePolicy::Utc => f.write_str("UTC"),
+            TimezonePolicy::Offset(offset) => write!(f, "UTC{}", offset),
+        }
+    }
+}
+
+/// Represents a cell with its position
+#[derive(Debug, Clone)]
+pub struct Cell {
+    /// Row index (0-based)
+    pub row: u32,
+    /// Column index (0-based)
+    pub col: u32,
+    /// Cell value
+    pub value: CellValue,
+}
+
+impl Cell {
+    /// Create a new cell
+    pub fn new(row: u32, col: u32, value: CellValue) -> Self {
+        Cell { row, col, value }
+    }
+
+    /// Get Excel-style cell reference (e.g., "A1", "B2")
+    pub fn reference(&self) -> String {
+        format!("{}{}", Self::col_to_letter(self.col), self.row + 1)
+    }
+
+    /// Convert column index to Excel letter (0 -> A, 25 -> Z, 26 -> AA)
+    pub(crate) fn col_to_letter(col: u32) -> String {
+        let mut result = String::new();
+        let mut col = col + 1;
+
+        while col > 0 {
+            col -= 1;
+            result.insert(0, (b'A' + (col % 26) as u8) as char);
+            col /= 26;
+        }
+
+        result
+    }
+}
+
+/// Whether every cell of a row is blank, see [`CellValue::is_blank`]
+pub(crate) fn is_blank_row(cells: &[CellValue]) -> bool {
+    cells.iter().all(CellValue::is_blank)
+}
+
+/// Represents a row of cells
+#[derive(Debug, Clone)]
+pub struct Row {
+    /// Row index (0-based)
+    pub index: u32,
+    /// Cells in this row
+    pub cells: Vec<CellValue>,
+}
+
+impl Row {
+    /// Create a new row
+    pub fn new(index: u32, cells: Vec<CellValue>) -> Self {
+        Row { index, cells }
+    }
+
+    /// Get cell at column index
+    pub fn get(&self, col: usize) -> Option<&CellValue> {
+        self.cells.get(col)
+    }
+
+    /// Get number of cells
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Check if row is empty
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty() || self.cells.iter().all(|c| c.is_empty())
+    }
+
+    /// Whether every cell is blank, see [`CellValue::is_blank`]
+    pub fn is_blank(&self) -> bool {
+        is_blank_row(&self.cells)
+    }
+
+    /// Convert row to vector of strings
+    pub fn to_strings(&self) -> Vec<String> {
+        self.cells.iter().map(|c| c.as_string()).collect()
+    }
+}
+
+/// Worksheet protection options
+#[derive(Debug, Clone)]
+pub struct ProtectionOptions {
+    /// Password hash (optional) - use set_password() to hash
+    pub password_hash: Option<String>,
+    /// Allow selecting locked cells (default: true)
+    pub select_locked_cells: bool,
+    /// Allow selecting unlocked cells (default: true)
+    pub select_unlocked_cells: bool,
+    /// Allow formatting cells (default: false)
+    pub format_cells: bool,
+    /// Allow formatting columns (default: false)
+    pub format_columns: bool,
+    /// Allow formatting rows (default: false)
+    pub format_rows: bool,
+    /// Allow inserting columns (default: false)
+    pub insert_columns: bool,
+    /// Allow inserting rows (default: false)
+    pub insert_rows: bool,
+    /// Allow deleting columns (default: false)
+    pub delete_columns: bool,
+    /// Allow deleting rows (default: false)
+    pub delete_rows: bool,
+    /// Allow sorting (default: false)
+    pub sort: bool,
+    /// Allow auto filter (default: false)
+    pub auto_filter: bool,
+}
+
+impl Default for ProtectionOptions {
+    fn default() -> Self {
+        ProtectionOptions {
+            password_hash: None,
+            select_locked_cells: true,
+            select_unlocked_cells: true,
+            format_cells: false,
+            format_columns: false,
+            format_rows: false,
+            insert_columns: false,
+            insert_rows: false,
+            delete_columns: false,
+            delete_rows: false,
+            sort: false,
+            auto_filter: false,
+        }
+    }
+}
+
+impl ProtectionOptions {
+    /// Create new protection with default settings (most restrictive)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set password for protection (hashed with Excel's algorithm)
+    pub fn with_password(mut self, password: &str) -> Self {
+        self.password_hash = Some(Self::hash_password(password));
+        self
+    }
+
+    /// Allow users to select locked cells
+    pub fn allow_select_locked_cells(mut self, allow: bool) -> Self {
+        self.select_locked_cells = allow;
+        self
+    }
+
+    /// Allow users to select unlocked cells
+    pub fn allow_select_unlocked_cells(mut self, allow: bool) -> Self {
+        self.select_unlocked_cells = allow;
+        self
+    }
+
+    /// Allow users to format cells
+    pub fn allow_format_cells(mut self, allow: bool) -> Self {
+        self.format_cells = allow;
+        self
+    }
+
+    /// Allow users to format columns
+    pub fn allow_format_columns(mut self, allow: bool) -> Self {
+        self.format_columns = allow;
+        self
+    }
+
+    /// Allow users to format rows
+    pub fn allow_format_rows(mut self, allow: bool) -> Self {
+        self.format_rows = allow;
+        self
+    }
+
+    /// Allow users to insert columns
+    pub fn allow_insert_columns(mut self, allow: bool) -> Self {
+        self.insert_columns = allow;
+        self
+    }
+
+    /// Allow users to insert rows
+    pub fn allow_insert_rows(mut self, allow: bool) -> Self {
+        self.insert_rows = allow;
+        self
+    }
+
+    /// Allow users to delete columns
+    pub fn allow_delete_columns(mut self, allow: bool) -> Self {
+        self.delete_columns = allow;
+        self
+    }
+
+    /// Allow users to delete rows
+    pub fn allow_delete_rows(mut self, allow: bool) -> Self {
+        self.delete_rows = allow;
+        self
+    }
+
+    /// Allow users to sort
+    pub fn allow_sort(mut self, allow: bool) -> Self {
+        self.sort = allow;
+        self
+    }
+
+    /// Allow users to use auto filter
+    pub fn allow_auto_filter(mut self, allow: bool) -> Self {
+        self.auto_filter = allow;
+        self
+    }
+
+    /// Hash password using Excel's algorithm (simple XOR-based)
+    fn hash_password(password: &str) -> String {
+        let mut hash: u16 = 0;
+        for ch in password.chars().rev() {
+            let val = (ch as u16).rotate_left(1);
+            hash ^= val;
+        }
+        hash ^= password.len() as u16;
+        hash ^= 0xCE4B;
+        format!("{:04X}", hash)
+    }
+}
+
+/// Comparison used by data validations and `cellIs` conditional formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonOperator {
+    Between,
+    NotBetween,
+    Equal,
+    NotEqual,
+    GreaterThan,
+    LessThan,
+    GreaterThanOrEqual,
+    LessThanOrEqual,
+}
+
+impl ComparisonOperator {
+    /// Value of the `operator` attribute
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ComparisonOperator::Between => "between",
+            ComparisonOperator::NotBetween => "notBetween",
+            ComparisonOperator::Equal => "equal",
+            ComparisonOperator::NotEqual => "notEqual",
+            ComparisonOperator::GreaterThan => "greaterThan",
+            ComparisonOperator::LessThan => "lessThan",
+            ComparisonOperator::GreaterThanOrEqual => "greaterThanOrEqual",
+            ComparisonOperator::LessThanOrEqual => "lessThanOrEqual",
+        }
+    }
+
+    fn takes_two_values(self) -> bool {
+        matches!(
+            self,
+            ComparisonOperator::Between | ComparisonOperator::NotBetween
+        )
+    }
+}
+
+/// What Excel does when a cell fails validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationErrorStyle {
+    /// Reject the value (default)
+    #[default]
+    Stop,
+    /// Warn and let the user choose to keep the value
+    Warning,
+    /// Inform the user and keep the value
+    Information,
+}
+
+/// Data validation rule for a cell range
+///
+/// Pass to [`crate::ExcelWriter::add_data_validation`] to constrain what
+/// users can type into a range, e.g. a dropdown list of allowed values.
+///
+/// # Example
+/// ```
+/// use excelstream::DataValidation;
+///
+/// let status = DataValidation::list(["Open", "In Progress", "Done"])
+///     .input_message("Status", "Pick a status from the list")
+///     .error_message("Invalid status", "Choose one of the listed values");
+/// let quantity = DataValidation::whole_number(1, 1000).allow_blank(false);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataValidation {
+    /// Validation type (`list`, `whole`, `decimal`, `textLength`, `custom`)
+    pub(crate) kind: &'static str,
+    pub(crate) operator: Option<ComparisonOperator>,
+    /// First formula, already in the form Excel stores it
+    pub(crate) formula1: String,
+    pub(crate) formula2: Option<String>,
+    /// Values of an inline list, checked when the rule is added
+    pub(crate) list_values: Option<Vec<String>>,
+    pub(crate) allow_blank: bool,
+    pub(crate) show_dropdown: bool,
+    pub(crate) input: Option<(String, String)>,
+    pub(crate) error: Option<(String, String)>,
+    pub(crate) error_style: ValidationErrorStyle,
+}
+
+impl DataValidation {
+    fn new(kind: &'static str, formula1: String) -> Self {
+        DataValidation {
+            kind,
+            operator: None,
+            formula1,
+            formula2: None,
+            list_values: None,
+            allow_blank: true,
+            show_dropdown: true,
+            input: None,
+            error: None,
+            error_style: ValidationErrorStyle::Stop,
+        }
+    }
+
+    fn compare(
+        kind: &'static str,
+        operator: ComparisonOperator,
+        value: String,
+        max: Option<String>,
+    ) -> Self {
+        let mut validation = Self::new(kind, value);
+        validation.operator = Some(operator);
+        validation.formula2 = max.filter(|_| operator.takes_two_values());
+        validation
+    }
+
+    /// Dropdown with a fixed list of values
+    ///
+    /// Values may not contain commas and the joined list must fit in 255
+    /// characters; use [`Self::list_range`] for longer lists.
+    pub fn list<I, S>(values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let values: Vec<String> = values.into_iter().map(Into::into).collect();
+        let formula = format!("\"{}\"", values.join(","));
+        let mut validation = Self::new("list", formula);
+        validation.list_values = Some(values);
+        validation
+    }
+
+    /// Dropdown with the values of a cell range, e.g. `"Lists!$A$1:$A$20"`
+    pub fn list_range(range: &str) -> Self {
+        Self::new("list", range.trim_start_matches('=').to_string())
+    }
+
+    /// Whole number between `min` and `max` (inclusive)
+    pub fn whole_number(min: i64, max: i64) -> Self {
+        Self::compare(
+            "whole",
+            ComparisonOperator::Between,
+            min.to_string(),
+            Some(max.to_string()),
+        )
+    }
+
+    /// Whole number compared against a single value
+    ///
+    /// For `Between` / `NotBetween` use [`Self::whole_number`].
+    pub fn whole_number_where(operator: ComparisonOperator, value: i64) -> Self {
+        Self::compare("whole", operator, value.to_string(), None)
+    }
+
+    /// Decimal number between `min` and `max` (inclusive)
+    pub fn decimal(min: f64, max: f64) -> Self {
+        Self::compare(
+            "decimal",
+            ComparisonOperator::Between,
+            min.to_string(),
+            Some(max.to_string()),
+        )
+    }
+
+    /// Decimal number compared against a single value
+    pub fn decimal_where(operator: ComparisonOperator, value: f64) -> Self {
+        Self::compare("decimal", operator, value.to_string(), None)
+    }
+
+    /// Text with a length between `min` and `max` characters
+    pub fn text_length(min: u32, max: u32) -> Self {
+        Self::compare(
+            "textLength",
+            ComparisonOperator::Between,
+            min.to_string(),
+            Some(max.to_string()),
+        )
+    }
+
+    /// Custom formula that must evaluate to TRUE, e.g. `"ISNUMBER(A2)"`
+    ///
+    /// Relative references are relative to the top-left cell of the range.
+    pub fn custom(formula: &str) -> Self {
+        Self::new("custom", formula.trim_start_matches('=').to_string())
+    }
+
+    /// Allow empty cells (default: true)
+    pub fn allow_blank(mut self, allow: bool) -> Self {
+        self.allow_blank = allow;
+        self
+    }
+
+    /// Hide the in-cell dropdown arrow of list validations
+    pub fn hide_dropdown(mut self) -> Self {
+        self.show_dropdown = false;
+        self
+    }
+
+    /// Prompt shown when a cell in the range is selected
+    pub fn input_message(mut self, title: &str, message: &str) -> Self {
+        self.input = Some((title.to_string(), message.to_string()));
+        self
+    }
+
+    /// Message shown when an invalid value is entered
+    pub fn error_message(mut self, title: &str, message: &str) -> Self {
+        self.error = Some((title.to_string(), message.to_string()));
+        self
+    }
+
+    /// How strictly invalid values are rejected (default: Stop)
+    pub fn error_style(mut self, style: ValidationErrorStyle) -> Self {
+        self.error_style = style;
+        self
+    }
+}
+
+/// Differential format applied by a conditional formatting rule
+///
+/// Colors are hex RGB (`"9C0006"`, `"#9C0006"`) or ARGB (`"FF9C0006"`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConditionalStyle {
+    pub(crate) font_color: Option<String>,
+    pub(crate) fill_color: Option<String>,
+    pub(crate) bold: bool,
+    pub(crate) italic: bool,
+}
+
+impl ConditionalStyle {
+    /// Create an empty style
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Excel's "Light Red Fill with Dark Red Text"
+    pub fn red() -> Self {
+        Self::new().fill_color("FFC7CE").font_color("9C0006")
+    }
+
+    /// Excel's "Green Fill with Dark Green Text"
+    pub fn green() -> Self {
+        Self::new().fill_color("C6EFCE").font_color("006100")
+    }
+
+    /// Excel's "Yellow Fill with Dark Yellow Text"
+    pub fn yellow() -> Self {
+        Self::new().fill_color("FFEB9C").font_color("9C5700")
+    }
+
+    /// Set the font color
+    pub fn font_color(mut self, color: &str) -> Self {
+        self.font_color = Some(color.to_string());
+        self
+    }
+
+    /// Set the background fill color
+    pub fn fill_color(mut self, color: &str) -> Self {
+        self.fill_color = Some(color.to_string());
+        self
+    }
+
+    /// Make the text bold
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Make the text italic
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+}
+
+/// Icon set used by [`ConditionalFormat::icon_set`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconSet {
+    ThreeArrows,
+    ThreeTrafficLights,
+    ThreeSymbols,
+    ThreeFlags,
+    FourArrows,
+    FourRating,
+    FiveArrows,
+    FiveRating,
+}
+
+impl IconSet {
+    /// Value of the `iconSet` attribute
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            IconSet::ThreeArrows => "3Arrows",
+            IconSet::ThreeTrafficLights => "3TrafficLights1",
+            IconSet::ThreeSymbols => "3Symbols",
+            IconSet::ThreeFlags => "3Flags",
+            IconSet::FourArrows => "4Arrows",
+            IconSet::FourRating => "4Rating",
+            IconSet::FiveArrows => "5Arrows",
+            IconSet::FiveRating => "5Rating",
+        }
+    }
+
+    /// Number of icons in the set
+    pub(crate) fn icon_count(self) -> u32 {
+        match self {
+            IconSet::ThreeArrows
+            | IconSet::ThreeTrafficLights
+            | IconSet::ThreeSymbols
+            | IconSet::ThreeFlags => 3,
+            IconSet::FourArrows | IconSet::FourRating => 4,
+            IconSet::FiveArrows | IconSet::FiveRating => 5,
+        }
+    }
+}
+
+/// Conditional formatting rule for a cell range
+///
+/// Pass to [`crate::ExcelWriter::add_conditional_format`]. Comparison values
+/// and expressions are Excel formulas, so text must be quoted (`"\"Done\""`).
+///
+/// # Example
+/// ```
+/// use excelstream::{ComparisonOperator, ConditionalFormat, ConditionalStyle};
+///
+/// let negative =
+///     ConditionalFormat::cell_is(ComparisonOperator::LessThan, 0, ConditionalStyle::red());
+/// let heatmap = ConditionalFormat::color_scale("F8696B", "63BE7B");
+/// let bars = ConditionalFormat::data_bar("638EC6");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionalFormat {
+    /// Style cells whose value compares true against one or two formulas
+    CellIs {
+        operator: ComparisonOperator,
+        value: String,
+        max: Option<String>,
+        style: ConditionalStyle,
+    },
+    /// Style cells for which a formula evaluates to TRUE
+    Expression {
+        formula: String,
+        style: ConditionalStyle,
+    },
+    /// Color gradient from the lowest to the highest value
+    ColorScale {
+        min_color: String,
+        mid_color: Option<String>,
+        max_color: String,
+    },
+    /// In-cell bar proportional to the value
+    DataBar { color: String },
+    /// Icon per value band, splitting the range into equal percentiles
+    IconSet {
+        icons: IconSet,
+        reverse: bool,
+        show_value: bool,
+    },
+}
+
+impl ConditionalFormat {
+    /// Compare each cell against a single value
+    ///
+    /// For `Between` / `NotBetween` use [`Self::cell_between`].
+    pub fn cell_is(
+        operator: ComparisonOperator,
+        value: impl fmt::Display,
+        style: ConditionalStyle,
+    ) -> Self {
+        ConditionalFormat::CellIs {
+            operator,
+            value: value.to_string(),
+            max: None,
+            style,
+        }
+    }
+
+    /// Style cells with a value between `min` and `max` (inclusive)
+    pub fn cell_between(
+        min: impl fmt::Display,
+        max: impl fmt::Display,
+        style: ConditionalStyle,
+    ) -> Self {
+        ConditionalFormat::CellIs {
+            operator: ComparisonOperator::Between,
+            value: min.to_string(),
+            max: Some(max.to_string()),
+            style,
+        }
+    }
+
+    /// Style cells for which `formula` is TRUE, e.g. `"$C2>$D2"`
+    ///
+    /// Relative references are relative to the top-left cell of the range.
+    pub fn expression(formula: &str, style: ConditionalStyle) -> Self {
+        ConditionalFormat::Expression {
+            formula: formula.trim_start_matches('=').to_string(),
+            style,
+        }
+    }
+
+    /// Two-color scale from `min_color` (lowest) to `max_color` (highest)
+    pub fn color_scale(min_color: &str, max_color: &str) -> Self {
+        ConditionalFormat::ColorScale {
+            min_color: min_color.to_string(),
+            mid_color: None,
+            max_color: max_color.to_string(),
+        }
+    }
+
+    /// Three-color scale with `mid_color` at the 50th percentile
+    pub fn color_scale3(min_color: &str, mid_color: &str, max_color: &str) -> Self {
+        ConditionalFormat::ColorScale {
+            min_color: min_color.to_string(),
+            mid_color: Some(mid_color.to_string()),
+            max_color: max_color.to_string(),
+        }
+    }
+
+    /// Data bars in the given color
+    pub fn data_bar(color: &str) -> Self {
+        ConditionalFormat::DataBar {
+            color: color.to_string(),
+        }
+    }
+
+    /// Icon set, lowest band first
+    pub fn icon_set(icons: IconSet) -> Self {
+        ConditionalFormat::IconSet {
+            icons,
+            reverse: false,
+            show_value: true,
+        }
+    }
+
+    /// Reverse the icon order of an icon set rule (no effect on other rules)
+    pub fn reverse_icons(mut self) -> Self {
+        if let ConditionalFormat::IconSet { reverse, .. } = &mut self {
+            *reverse = true;
+        }
+        self
+    }
+
+    /// Show only the icons of an icon set rule, hiding cell values
+    pub fn icons_only(mut self) -> Self {
+        if let ConditionalFormat::IconSet { show_value, .. } = &mut self {
+            *show_value = false;
+        }
+        self
+    }
+
+    /// Style applied by `cellIs` and expression rules
+    pub(crate) fn style(&self) -> Option<&ConditionalStyle> {
+        match self {
+            ConditionalFormat::CellIs { style, .. }
+            | ConditionalFormat::Expression { style, .. } => Some(style),
+            _ => None,
+        }
+    }
+
+    /// Every color used by the rule, for validation
+    pub(crate) fn colors(&self) -> Vec<&str> {
+        let mut colors = Vec::new();
+        match self {
+            ConditionalFormat::CellIs { style, .. }
+            | ConditionalFormat::Expression { style, .. } => {
+                colors.extend(style.font_color.as_deref());
+                colors.extend(style.fill_color.as_deref());
+            }
+            ConditionalFormat::ColorScale {
+                min_color,
+                mid_color,
+                max_color,
+            } => {
+                colors.push(min_color.as_str());
+                colors.extend(mid_color.as_deref());
+                colors.push(max_color.as_str());
+            }
+            ConditionalFormat::DataBar { color } => colors.push(color.as_str()),
+            ConditionalFormat::IconSet { .. } => {}
+        }
+        colors
+    }
+}
+
+/// Kind of chart created by [`crate::ExcelWriter::add_chart`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartType {
+    /// Horizontal bars
+    Bar,
+    /// Vertical bars
+    Column,
+    /// Lines with markers
+    Line,
+    /// Pie of the first series
+    Pie,
+}
+
+/// Title, axis labels and size of a chart
+///
+/// # Example
+/// ```
+/// use excelstream::ChartOptions;
+///
+/// let options = ChartOptions::new()
+///     .title("Monthly revenue")
+///     .x_axis_title("Month")
+///     .y_axis_title("USD")
+///     .size(10, 20);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChartOptions {
+    /// Chart title (default: none)
+    pub title: Option<String>,
+    /// Category axis title (default: none)
+    pub x_axis_title: Option<String>,
+    /// Value axis title (default: none)
+    pub y_axis_title: Option<String>,
+    /// Width in columns (default: 8)
+    pub width_cols: u32,
+    /// Height in rows (default: 15)
+    pub height_rows: u32,
+    /// Show the legend (default: true)
+    pub legend: bool,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        ChartOptions {
+            title: None,
+            x_axis_title: None,
+            y_axis_title: None,
+            width_cols: 8,
+            height_rows: 15,
+            legend: true,
+        }
+    }
+}
+
+impl ChartOptions {
+    /// Create options with default size and no titles
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the chart title
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Set the category (X) axis title; ignored for pie charts
+    pub fn x_axis_title(mut self, title: &str) -> Self {
+        self.x_axis_title = Some(title.to_string());
+        self
+    }
+
+    /// Set the value (Y) axis title; ignored for pie charts
+    pub fn y_axis_title(mut self, title: &str) -> Self {
+        self.y_axis_title = Some(title.to_string());
+        self
+    }
+
+    /// Set the size of the chart in columns and rows
+    pub fn size(mut self, width_cols: u32, height_rows: u32) -> Self {
+        self.width_cols = width_cols.max(1);
+        self.height_rows = height_rows.max(1);
+        self
+    }
+
+    /// Show or hide the legend
+    pub fn legend(mut self, show: bool) -> Self {
+        self.legend = show;
+        self
+    }
+}
+
+/// Name, style and columns of an Excel table (ListObject)
+///
+/// Used with [`crate::ExcelWriter::add_table`]. The first row of the table
+/// range is its header row; `column_names` must repeat the header cells'
+/// text, since Excel repairs tables whose column names differ from their
+/// headers. Without names, columns are called `Column1`, `Column2`, ...
+///
+/// With `totals_row`, the last row of the range is the totals row: the
+/// first column is labelled "Total" and the others are summed. The cells
+/// of that row are written like any other row, typically as formulas such
+/// as `SUBTOTAL(109,Sales[Amount])`.
+///
+/// # Example
+/// ```
+/// use excelstream::TableOptions;
+///
+/// let options = TableOptions::new()
+///     .name("Sales")
+///     .style("TableStyleMedium9")
+///     .column_names(["Region", "Amount"])
+///     .totals_row(true);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableOptions {
+    /// Table name used in structured references (default: `TableN`)
+    pub name: Option<String>,
+    /// Built-in table style (default: `TableStyleMedium2`)
+    pub style: String,
+    /// Last row of the range is a totals row (default: false)
+    pub totals_row: bool,
+    /// Header names, one per column (default: `Column1`, `Column2`, ...)
+    pub column_names: Vec<String>,
+    /// Shade alternate rows (default: true)
+    pub banded_rows: bool,
+    /// Shade alternate columns (default: false)
+    pub banded_columns: bool,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        TableOptions {
+            name: None,
+            style: "TableStyleMedium2".to_string(),
+            totals_row: false,
+            column_names: Vec::new(),
+            banded_rows: true,
+            banded_columns: false,
+        }
+    }
+}
+
+impl TableOptions {
+    /// Create options with the default style and generated names
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the table name
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Set the table style, e.g. `"TableStyleLight1"`
+    pub fn style(mut self, style: &str) -> Self {
+        self.style = style.to_string();
+        self
+    }
+
+    /// Treat the last row of the range as a totals row
+    pub fn totals_row(mut self, totals_row: bool) -> Self {
+        self.totals_row = totals_row;
+        self
+    }
+
+    /// Set the column names, matching the header row
+    pub fn column_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.column_names = names.into_iter().map(|n| n.as_ref().to_string()).collect();
+        self
+    }
+
+    /// Shade alternate rows and/or columns
+    pub fn banding(mut self, rows: bool, columns: bool) -> Self {
+        self.banded_rows = rows;
+        self.banded_columns = columns;
+        self
+    }
+}
+
+/// Page orientation for printing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+/// Common paper sizes, with their `paperSize` codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaperSize {
+    Letter = 1,
+    Legal = 5,
+    A3 = 8,
+    A4 = 9,
+    A5 = 11,
+}
+
+/// Print setup for a worksheet
+///
+/// Applied with [`crate::ExcelWriter::set_page_setup`]. Print area and
+/// repeated rows are stored as the sheet's `_xlnm.Print_Area` and
+/// `_xlnm.Print_Titles` defined names.
+///
+/// Headers and footers use Excel's codes: `&L`, `&C` and `&R` start the
+/// left, center and right sections, `&P` is the page number, `&N` the page
+/// count, `&D` / `&T` the print date and time, `&A` the sheet name and `&F`
+/// the file name. A literal ampersand is written `&&`.
+///
+/// # Example
+/// ```
+/// use excelstream::{Orientation, PageSetup, PaperSize};
+///
+/// let setup = PageSetup::new()
+///     .orientation(Orientation::Landscape)
+///     .paper_size(PaperSize::A4)
+///     .margins(0.5, 0.5, 0.75, 0.75)
+///     .repeat_rows(1, 1)
+///     .header("&LQuarterly report&R&D")
+///     .footer("&CPage &P of &N");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageSetup {
+    /// Page orientation (default: portrait)
+    pub orientation: Orientation,
+    /// Paper size (default: printer default)
+    pub paper_size: Option<PaperSize>,
+    /// Left, right, top and bottom margins in inches (default: 0.7, 0.7, 0.75, 0.75)
+    pub margins: [f64; 4],
+    /// Header and footer margins in inches (default: 0.3, 0.3)
+    pub header_footer_margins: [f64; 2],
+    /// Range to print, e.g. `"A1:F200"` (default: used range)
+    pub print_area: Option<String>,
+    /// First and last row (1-based) repeated at the top of each page
+    pub repeat_rows: Option<(u32, u32)>,
+    /// Text printed at the top of each page (default: none)
+    pub header: Option<String>,
+    /// Text printed at the bottom of each page (default: none)
+    pub footer: Option<String>,
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        PageSetup {
+            orientation: Orientation::Portrait,
+            paper_size: None,
+            margins: [0.7, 0.7, 0.75, 0.75],
+            header_footer_margins: [0.3, 0.3],
+            print_area: None,
+            repeat_rows: None,
+            header: None,
+            footer: None,
+        }
+    }
+}
+
+impl PageSetup {
+    /// Create a page setup with Excel's defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the page orientation
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Set the paper size
+    pub fn paper_size(mut self, size: PaperSize) -> Self {
+        self.paper_size = Some(size);
+        self
+    }
+
+    /// Set the page margins in inches
+    pub fn margins(mut self, left: f64, right: f64, top: f64, bottom: f64) -> Self {
+        self.margins = [left, right, top, bottom];
+        self
+    }
+
+    /// Set the header and footer margins in inches
+    pub fn header_footer_margins(mut self, header: f64, footer: f64) -> Self {
+        self.header_footer_margins = [header, footer];
+        self
+    }
+
+    /// Print only `range`, e.g. `"A1:F200"`
+    pub fn print_area(mut self, range: &str) -> Self {
+        self.print_area = Some(range.to_string());
+        self
+    }
+
+    /// Repeat rows `first..=last` (1-based) at the top of every printed page
+    pub fn repeat_rows(mut self, first: u32, last: u32) -> Self {
+        self.repeat_rows = Some((first.min(last), first.max(last)));
+        self
+    }
+
+    /// Print `text` at the top of every page, e.g. `"&CSales&R&D"`
+    pub fn header(mut self, text: &str) -> Self {
+        self.header = Some(text.to_string());
+        self
+    }
+
+    /// Print `text` at the bottom of every page, e.g. `"&CPage &P of &N"`
+    pub fn footer(mut self, text: &str) -> Self {
+        self.footer = Some(text.to_string());
+        self
+    }
+}
+
+/// Where outline summary rows and columns sit relative to their groups
+///
+/// Excel puts the summary (total) row below a row group and the summary
+/// column to the right of a column group, which is the default here.
+///
+/// ```
+/// use excelstream::types::OutlineSettings;
+///
+/// // Totals first, details underneath
+/// let settings = OutlineSettings { summary_below: false, ..Default::default() };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutlineSettings {
+    /// Summary rows follow their detail rows (default: true)
+    pub summary_below: bool,
+    /// Summary columns follow their detail columns (default: true)
+    pub summary_right: bool,
+}
+
+impl Default for OutlineSettings {
+    fn default() -> Self {
+        OutlineSettings {
+            summary_below: true,
+            summary_right: true,
+        }
+    }
+}
+
+/// Bounds for column widths estimated from cell content
+///
+/// Widths are in Excel's column units, roughly one character of the
+/// default font each. Long text is capped at `max_width` instead of
+/// stretching a column across the screen.
+///
+/// ```
+/// use excelstream::types::AutofitOptions;
+///
+/// // Never narrower than Excel's default column, never wider than 40
+/// let options = AutofitOptions { min_width: 8.43, max_width: 40.0 };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutofitOptions {
+    /// Narrowest width given to a column with content (default: 0)
+    pub min_width: f64,
+    /// Widest width given to any column (default: 60)
+    pub max_width: f64,
+}
+
+impl Default for AutofitOptions {
+    fn default() -> Self {
+        AutofitOptions {
+            min_width: 0.0,
+            max_width: 60.0,
+        }
+    }
+}
+
+/// Speed / size trade-off for compressed output, shared by the XLSX and
+/// CSV writers
+///
+/// Each profile maps to a compression level suited to the format: XLSX
+/// parts are always deflated (Excel reads nothing else), while compressed
+/// CSV uses Zstd unless the file is a `.gz`. `Smallest` usually saves only a
+/// few percent over `Balanced` at a much higher CPU cost.
+///
+/// ```no_run
+/// use excelstream::{CompressionProfile, ExcelWriter};
+///
+/// let writer =
+///     ExcelWriter::with_compression_profile("archive.xlsx", CompressionProfile::Smallest)?;
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompressionProfile {
+    /// Least CPU per row (deflate 1, zstd 1)
+    Fastest,
+    /// Default trade-off (deflate 6, zstd 3)
+    #[default]
+    Balanced,
+    /// Smallest files for archiving (deflate 9, zstd 19)
+    Smallest,
+}
+
+impl CompressionProfile {
+    /// Deflate level (0-9) for XLSX parts and `.csv.gz` files
+    pub fn deflate_level(self) -> u32 {
+        match self {
+            CompressionProfile::Fastest => 1,
+            CompressionProfile::Balanced => 6,
+            CompressionProfile::Smallest => 9,
+        }
+    }
+
+    /// Zstd level (1-21) for compressed CSV
+    pub fn zstd_level(self) -> u32 {
+        match self {
+            CompressionProfile::Fastest => 1,
+            CompressionProfile::Balanced => 3,
+            CompressionProfile::Smallest => 19,
+        }
+    }
+}
+
+/// How string cells are stored in the workbook
+///
+/// Inline strings keep memory flat no matter how many distinct values are
+/// written. A shared string table makes files with many repeated values
+/// smaller, but the writer has to keep every distinct string until close.
+///
+/// ```no_run
+/// use excelstream::{ExcelWriter, StringMode};
+///
+/// let mut writer = ExcelWriter::new("status.xlsx")?;
+/// writer.set_string_mode(StringMode::SharedHashed { memory_limit: 64 * 1024 * 1024 })?;
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StringMode {
+    /// Every string is written into its cell (`t="inlineStr"`)
+    #[default]
+    Inline,
+    /// Every distinct string goes into the shared string table once
+    SharedDeduped,
+    /// Share the first `max_unique` distinct strings; new strings after
+    /// that are written inline
+    SharedThresholded { max_unique: usize },
+    /// Deduplicate by hash while the lookup index fits in about
+    /// `memory_limit` bytes
+    ///
+    /// The strings themselves are kept in a temporary file rather than in
+    /// memory. Once the budget is used up, or in the rare case two strings
+    /// share a hash, new strings are written inline, so a file with millions
+    /// of distinct values can't exhaust memory.
+    SharedHashed { memory_limit: usize },
+}
+
+/// What a writer does with a sheet name Excel wouldn't accept
+///
+/// See [`sheet_name`](crate::sheet_name) for the rules.
+///
+/// ```no_run
+/// use excelstream::{ExcelWriter, SheetNamePolicy};
+///
+/// let mut writer = ExcelWriter::new("regions.xlsx")?;
+/// writer.set_sheet_name_policy(SheetNamePolicy::Sanitize);
+/// writer.add_sheet("North/East")?; // written as "North_East"
+/// writer.add_sheet("north_east")?; // written as "north_east (2)"
+/// # Ok::<(), excelstream::ExcelError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SheetNamePolicy {
+    /// Fail with [`ExcelError::InvalidSheetName`](crate::ExcelError::InvalidSheetName)
+    #[default]
+    Strict,
+    /// Replace invalid characters, cut long names and number duplicates
+    Sanitize,
+}
+
+/// Value of a custom document property
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Text(String),
+    Int(i64),
+    Number(f64),
+    Bool(bool),
+    DateTime(chrono::DateTime<chrono::Utc>),
+}
+
+impl From<&str> for PropertyValue {
+    fn from(value: &str) -> Self {
+        PropertyValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for PropertyValue {
+    fn from(value: String) -> Self {
+        PropertyValue::Text(value)
+    }
+}
+
+impl From<i64> for PropertyValue {
+    fn from(value: i64) -> Self {
+        PropertyValue::Int(value)
+    }
+}
+
+impl From<i32> for PropertyValue {
+    fn from(value: i32) -> Self {
+        PropertyValue::Int(value as i64)
+    }
+}
+
+impl From<f64> for PropertyValue {
+    fn from(value: f64) -> Self {
+        PropertyValue::Number(value)
+    }
+}
+
+impl From<bool> for PropertyValue {
+    fn from(value: bool) -> Self {
+        PropertyValue::Bool(value)
+    }
+}
+
+impl From<chrono::DateTime<chrono::Utc>> for PropertyValue {
+    fn from(value: chrono::DateTime<chrono::Utc>) -> Self {
+        PropertyValue::DateTime(value)
+    }
+}
+
+/// Workbook metadata shown under *File > Info > Properties*
+///
+/// Applied with [`crate::ExcelWriter::set_properties`] and written to
+/// `docProps/core.xml`, `docProps/app.xml` and, when custom properties are
+/// set, `docProps/custom.xml`. Unset timestamps default to the time the
+/// workbook is saved.
+///
+/// # Example
+/// ```
+/// use excelstream::DocumentProperties;
+///
+/// let props = DocumentProperties::new()
+///     .title("Quarterly report")
+///     .author("Finance team")
+///     .company("Acme Corp")
+///     .custom("Department", "Sales")
+///     .custom("Revision", 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentProperties {
+    /// Document title
+    pub title: Option<String>,
+    /// Document subject
+    pub subject: Option<String>,
+    /// Document creator (default: `ExcelStream`)
+    pub author: Option<String>,
+    /// Manager (`app.xml`)
+    pub manager: Option<String>,
+    /// Company (`app.xml`)
+    pub company: Option<String>,
+    /// Category
+    pub category: Option<String>,
+    /// Keywords, conventionally comma-separated
+    pub keywords: Option<String>,
+    /// Comments, stored as the description
+    pub comments: Option<String>,
+    /// Creation time (default: save time)
+    pub created: Option<chrono::DateTime<chrono::Utc>>,
+    /// Last modification time (default: creation time)
+    pub modified: Option<chrono::DateTime<chrono::Utc>>,
+    /// Custom properties in insertion order; a repeated name replaces the value
+    pub custom: Vec<(String, PropertyValue)>,
+}
+
+impl DocumentProperties {
+    /// Create empty properties
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the title
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Set the subject
+    pub fn subject(mut self, subject: &str) -> Self {
+        self.subject = Some(subject.to_string());
+        self
+    }
+
+    /// Set the author
+    pub fn author(mut self, author: &str) -> Self {
+        self.author = Some(author.to_string());
+        self
+    }
+
+    /// Set the manager
+    pub fn manager(mut self, manager: &str) -> Self {
+        self.manager = Some(manager.to_string());
+        self
+    }
+
+    /// Set the company
+    pub fn company(mut self, company: &str) -> Self {
+        self.company = Some(company.to_string());
+        self
+    }
+
+    /// Set the category
+    pub fn category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    /// Set the keywords, e.g. `"sales, 2024"`
+    pub fn keywords(mut self, keywords: &str) -> Self {
+        self.keywords = Some(keywords.to_string());
+        self
+    }
+
+    /// Set the comments (description)
+    pub fn comments(mut self, comments: &str) -> Self {
+        self.comments = Some(comments.to_string());
+        self
+    }
+
+    /// Set the creation time
+    pub fn created(mut self, created: chrono::DateTime<chrono::Utc>) -> Self {
+        self.created = Some(created);
+        self
+    }
+
+    /// Set the last modification time (default: the creation time)
+    pub fn modified(mut self, modified: chrono::DateTime<chrono::Utc>) -> Self {
+        self.modified = Some(modified);
+        self
+    }
+
+    /// Add a custom property
+    pub fn custom(mut self, name: &str, value: impl Into<PropertyValue>) -> Self {
+        let value = value.into();
+        match self.custom.iter_mut().find(|(n, _)| n == name) {
+            Some(existing) => existing.1 = value,
+            None => self.custom.push((name.to_string(), value)),
+        }
+        self
+    }
+}
+
+/// Normalize a hex color to the ARGB form used in styles (`"FF9C0006"`)
+pub(crate) fn argb_color(color: &str) -> Option<String> {
+    let hex = color.trim_start_matches('#');
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hex.len() {
+        6 => Some(format!("FF{}", hex.to_ascii_uppercase())),
+        8 => Some(hex.to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_profile_levels() {
+        assert_eq!(CompressionProfile::default(), CompressionProfile::Balanced);
+        assert_eq!(CompressionProfile::Balanced.deflate_level(), 6);
+        assert!(
+            CompressionProfile::Fastest.zstd_level() < CompressionProfile::Smallest.zstd_level()
+        );
+    }
+
+    #[test]
+    fn test_cell_reference() {
+        let cell = Cell::new(0, 0, CellValue::Empty);
+        assert_eq!(cell.reference(), "A1");
+
+        let cell = Cell::new(0, 25, CellValue::Empty);
+        assert_eq!(cell.reference(), "Z1");
+
+        let cell = Cell::new(0, 26, CellValue::Empty);
+        assert_eq!(cell.reference(), "AA1");
+    }
+}
@@ -0,0 +1,660 @@
+//! Structural checks on XLSX packages
+//!
+//! [`validate_package`] looks for the problems that make Excel show its
+//! "We found a problem with some content" repair dialog: parts without a
+//! content type, relationships pointing at missing parts, sheets that
+//! aren't wired up in the workbook, shared string indexes past the end of
+//! the table and rows or cells out of order. Worksheets are scanned tag by
+//! tag, so memory stays flat for large files.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use excelstream::validate::validate_package;
+//!
+//! let report = validate_package("export.xlsx")?;
+//! if !report.is_valid() {
+//!     eprintln!("{}", report);
+//!     std::process::exit(1);
+//! }
+//! # Ok::<(), excelstream::ExcelError>(())
+//! ```
+
+use crate::error::{ExcelError, Result};
+use crate::fast_writer::StreamingZipReader;
+use crate::types::Cell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Issues reported per part before the rest are summarized
+const MAX_ISSUES_PER_PART: usize = 100;
+/// Last row and column Excel supports
+const MAX_ROWS: u32 = 1_048_576;
+const MAX_COLS: u32 = 16_384;
+
+/// Relationship types (last segment of the type URI) whose targets need
+/// their own content type override
+const TYPED_PARTS: &[&str] = &[
+    "officeDocument",
+    "worksheet",
+    "chartsheet",
+    "styles",
+    "sharedStrings",
+    "theme",
+    "table",
+    "drawing",
+    "chart",
+    "comments",
+];
+
+/// How serious a [`ValidationIssue`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Excel repairs or refuses the file
+    Error,
+    /// Tolerated by Excel, but likely a bug in whatever wrote the file
+    Warning,
+}
+
+/// One problem found in a package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Part the problem is in, e.g. `xl/worksheets/sheet1.xml`
+    pub part: String,
+    /// Cell reference or row within the part, when there is one
+    pub location: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}", severity, self.part)?;
+        if let Some(location) = &self.location {
+            write!(f, " {}", location)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Result of [`validate_package`]
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// True if nothing would make Excel repair the file
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    fn error(&mut self, part: &str, location: Option<String>, message: impl Into<String>) {
+        self.push(Severity::Error, part, location, message.into());
+    }
+
+    fn warning(&mut self, part: &str, message: impl Into<String>) {
+        self.push(Severity::Warning, part, None, message.into());
+    }
+
+    fn push(&mut self, severity: Severity, part: &str, location: Option<String>, message: String) {
+        self.issues.push(ValidationIssue {
+            severity,
+            part: part.to_string(),
+            location,
+            message,
+        });
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.issues.is_empty() {
+            return write!(f, "no issues found");
+        }
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check the structure of the XLSX package at `path`
+///
+/// Only fails if the file can't be read as a ZIP archive; everything else
+/// is reported in the returned [`ValidationReport`].
+pub fn validate_package<P: AsRef<Path>>(path: P) -> Result<ValidationReport> {
+    let mut zip = StreamingZipReader::open(path.as_ref())?;
+    let parts: HashSet<String> = zip
+        .entries()
+        .iter()
+        .map(|entry| entry.name.trim_start_matches('/').to_string())
+        .filter(|name| !name.ends_with('/'))
+        .collect();
+    let mut report = ValidationReport::default();
+
+    let overrides = check_content_types(&mut zip, &parts, &mut report)?;
+
+    // Relationships of every part, by source part
+    let mut relationships = HashMap::new();
+    let mut rels_parts: Vec<&String> = parts.iter().filter(|p| p.ends_with(".rels")).collect();
+    rels_parts.sort();
+    for rels_part in rels_parts {
+        let Some(source) = rels_source(rels_part) else {
+            report.error(rels_part, None, "relationships part outside a _rels folder");
+            continue;
+        };
+        let xml = read_text(&mut zip, rels_part)?;
+        let rels = parse_relationships(&xml, &source, rels_part, &parts, &mut report);
+        for rel in rels.iter().filter(|rel| !rel.external) {
+            let kind = rel.kind();
+            if TYPED_PARTS.contains(&kind)
+                && parts.contains(&rel.target)
+                && !overrides.contains(&rel.target)
+            {
+                report.error(
+                    "[Content_Types].xml",
+                    None,
+                    format!("{} part {} has no content type override", kind, rel.target),
+                );
+            }
+        }
+        relationships.insert(source, rels);
+    }
+
+    let Some(workbook) = relationships
+        .get("")
+        .and_then(|rels| find_kind(rels, "officeDocument"))
+        .map(|rel| rel.target.clone())
+    else {
+        report.error("_rels/.rels", None, "no officeDocument relationship");
+        return Ok(report);
+    };
+    if !parts.contains(&workbook) {
+        return Ok(report);
+    }
+
+    let workbook_rels = relationships.remove(&workbook).unwrap_or_default();
+    let sheets = check_workbook(&mut zip, &workbook, &workbook_rels, &parts, &mut report)?;
+
+    let sst_count = match find_kind(&workbook_rels, "sharedStrings") {
+        Some(rel) if parts.contains(&rel.target) => {
+            Some(check_shared_strings(&mut zip, &rel.target, &mut report)?)
+        }
+        _ => None,
+    };
+
+    for sheet in sheets {
+        check_worksheet(&mut zip, &sheet, sst_count, &mut report)?;
+    }
+
+    Ok(report)
+}
+
+struct Relationship {
+    id: String,
+    rel_type: String,
+    /// Part name for internal targets, the raw target for external ones
+    target: String,
+    external: bool,
+}
+
+impl Relationship {
+    /// `worksheet` for `http://schemas.openxmlformats.org/.../worksheet`
+    fn kind(&self) -> &str {
+        self.rel_type.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+fn find_kind<'a>(rels: &'a [Relationship], kind: &str) -> Option<&'a Relationship> {
+    rels.iter().find(|rel| rel.kind() == kind)
+}
+
+/// Check `[Content_Types].xml`; returns the parts that have an override
+fn check_content_types(
+    zip: &mut StreamingZipReader,
+    parts: &HashSet<String>,
+    report: &mut ValidationReport,
+) -> Result<HashSet<String>> {
+    const PART: &str = "[Content_Types].xml";
+    let mut overrides = HashSet::new();
+    if !parts.contains(PART) {
+        report.error(PART, None, "package has no content types part");
+        return Ok(overrides);
+    }
+
+    let xml = read_text(zip, PART)?;
+    let defaults: HashSet<String> = elements(&xml, "Default")
+        .filter_map(|tag| attr(tag, "Extension"))
+        .map(|ext| ext.to_ascii_lowercase())
+        .collect();
+    for tag in elements(&xml, "Override") {
+        let Some(name) = attr(tag, "PartName") else {
+            report.error(PART, None, "Override without PartName");
+            continue;
+        };
+        let name = name.trim_start_matches('/').to_string();
+        if !parts.contains(&name) {
+            report.error(PART, None, format!("override for missing part {}", name));
+        }
+        overrides.insert(name);
+    }
+
+    let mut untyped: Vec<&String> = parts
+        .iter()
+        .filter(|part| *part != PART && !overrides.contains(*part))
+        .filter(|part| {
+            let ext = part.rsplit_once('.').map_or("", |(_, ext)| ext);
+            !defaults.contains(&ext.to_ascii_lowercase())
+        })
+        .collect();
+    untyped.sort();
+    for part in untyped {
+        report.error(part, None, "part has no content type");
+    }
+    Ok(overrides)
+}
+
+/// Source part of a relationships part: `xl/_rels/workbook.xml.rels` belongs
+/// to `xl/workbook.xml`, `_rels/.rels` to the package (`""`)
+fn rels_source(rels_part: &str) -> Option<String> {
+    let (dir, file) = rels_part.rsplit_once('/')?;
+    let file = file.strip_suffix(".rels")?;
+    let parent = match dir.strip_suffix("_rels")? {
+        "" => "",
+        parent => parent.strip_suffix('/')?,
+    };
+    Some(match (parent, file) {
+        (_, "") => parent.to_string(),
+        ("", file) => file.to_string(),
+        (parent, file) => format!("{}/{}", parent, file),
+    })
+}
+
+/// Part name of `target` relative to the folder of `source`
+pub(crate) fn resolve_target(source: &str, target: &str) -> String {
+    let mut segments: Vec<&str> = match target.strip_prefix('/') {
+        Some(_) => Vec::new(),
+        None => match source.rsplit_once('/') {
+            Some((dir, _)) => dir.split('/').collect(),
+            None => Vec::new(),
+        },
+    };
+    for segment in target.trim_start_matches('/').split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn parse_relationships(
+    xml: &str,
+    source: &str,
+    rels_part: &str,
+    parts: &HashSet<String>,
+    report: &mut ValidationReport,
+) -> Vec<Relationship> {
+    let mut rels = Vec::new();
+    let mut ids = HashSet::new();
+    for tag in elements(xml, "Relationship") {
+        let (Some(id), Some(kind), Some(target)) =
+            (attr(tag, "Id"), attr(tag, "Type"), attr(tag, "Target"))
+        else {
+            report.error(rels_part, None, "Relationship without Id, Type or Target");
+            continue;
+        };
+        if !ids.insert(id) {
+            report.error(rels_part, None, format!("duplicate relationship id {}", id));
+        }
+        let external = attr(tag, "TargetMode") == Some("External");
+        let target = if external {
+            target.to_string()
+        } else {
+            let part = resolve_target(source, &decode_entities(target));
+            if !parts.contains(&part) {
+                report.error(
+                    rels_part,
+                    None,
+                    format!("relationship {} points to missing part {}", id, part),
+                );
+            }
+            part
+        };
+        rels.push(Relationship {
+            id: id.to_string(),
+            rel_type: kind.to_string(),
+            target,
+            external,
+        });
+    }
+    rels
+}
+
+/// Check the `<sheets>` of the workbook; returns the worksheet parts
+fn check_workbook(
+    zip: &mut StreamingZipReader,
+    workbook: &str,
+    rels: &[Relationship],
+    parts: &HashSet<String>,
+    report: &mut ValidationReport,
+) -> Result<Vec<String>> {
+    let xml = read_text(zip, workbook)?;
+    let mut names = HashSet::new();
+    let mut sheet_ids = HashSet::new();
+    let mut sheets = Vec::new();
+
+    for tag in elements(&xml, "sheet") {
+        let name = attr(tag, "name").map(decode_entities).unwrap_or_default();
+        if name.is_empty() {
+            report.error(workbook, None, "sheet without a name");
+        } else if !names.insert(name.to_lowercase()) {
+            report.error(workbook, None, format!("duplicate sheet name '{}'", name));
+        }
+        match attr(tag, "sheetId") {
+            Some(id) if !sheet_ids.insert(id) => {
+                report.error(workbook, None, format!("duplicate sheetId {}", id))
+            }
+            Some(_) => {}
+            None => report.error(workbook, None, format!("sheet '{}' has no sheetId", name)),
+        }
+
+        let Some(rid) = attr(tag, "r:id") else {
+            report.error(workbook, None, format!("sheet '{}' has no r:id", name));
+            continue;
+        };
+        match rels.iter().find(|rel| rel.id == rid) {
+            None => report.error(
+                workbook,
+                None,
+                format!("sheet '{}' refers to unknown relationship {}", name, rid),
+            ),
+            Some(rel) if rel.kind() == "worksheet" && parts.contains(&rel.target) => {
+                sheets.push(rel.target.clone())
+            }
+            // Chartsheets, dialogsheets and missing targets (reported above)
+            Some(_) => {}
+        }
+    }
+
+    if names.is_empty() {
+        report.error(workbook, None, "workbook has no sheets");
+    }
+    Ok(sheets)
+}
+
+/// Number of strings in the shared string table
+fn check_shared_strings(
+    zip: &mut StreamingZipReader,
+    part: &str,
+    report: &mut ValidationReport,
+) -> Result<u32> {
+    let xml = read_text(zip, part)?;
+    let count = elements(&xml, "si").count() as u32;
+    if let Some(tag) = elements(&xml, "sst").next() {
+        if let Some(unique) = attr(tag, "uniqueCount").and_then(|v| v.parse::<u32>().ok()) {
+            if unique != count {
+                report.warning(
+                    part,
+                    format!(
+                        "uniqueCount is {} but the table has {} strings",
+                        unique, count
+                    ),
+                );
+            }
+        }
+    }
+    Ok(count)
+}
+
+/// Row order, cell order and shared string indexes of a worksheet
+fn check_worksheet(
+    zip: &mut StreamingZipReader,
+    part: &str,
+    sst_count: Option<u32>,
+    report: &mut ValidationReport,
+) -> Result<()> {
+    let reader = zip.read_entry_streaming_by_name(part)?;
+    let mut reader = BufReader::with_capacity(64 * 1024, reader);
+    let mut chunk = Vec::new();
+    let mut issues = 0usize;
+    let mut error = |report: &mut ValidationReport, location: String, message: String| {
+        issues += 1;
+        if issues <= MAX_ISSUES_PER_PART {
+            report.error(part, Some(location), message);
+        }
+    };
+
+    let mut last_row = 0u32;
+    let mut row = 0u32;
+    let mut last_col: Option<u32> = None;
+    // Reference of the shared string cell whose <v> is being read
+    let mut shared_cell: Option<String> = None;
+
+    loop {
+        chunk.clear();
+        if reader.read_until(b'>', &mut chunk)? == 0 {
+            break;
+        }
+        let piece = String::from_utf8_lossy(&chunk);
+        let Some(tag_start) = piece.rfind('<') else {
+            continue;
+        };
+        let (text, tag) = piece.split_at(tag_start);
+
+        if tag == "</v>" {
+            if let (Some(cell), Some(count)) = (shared_cell.take(), sst_count) {
+                match text.trim().parse::<u32>() {
+                    Ok(index) if index < count => {}
+                    Ok(index) => error(
+                        report,
+                        cell,
+                        format!("shared string {} out of range ({} strings)", index, count),
+                    ),
+                    Err(_) => error(
+                        report,
+                        cell,
+                        format!("invalid shared string index '{}'", text),
+                    ),
+                }
+            }
+        } else if is_tag(tag, "row") {
+            row = match attr(tag, "r").map(str::parse::<u32>) {
+                Some(Ok(r)) => r,
+                Some(Err(_)) => {
+                    error(
+                        report,
+                        format!("row {}", last_row + 1),
+                        "invalid row number".into(),
+                    );
+                    last_row + 1
+                }
+                None => last_row + 1,
+            };
+            if row <= last_row {
+                error(
+                    report,
+                    format!("row {}", row),
+                    format!("row {} comes after row {}", row, last_row),
+                );
+            } else if row > MAX_ROWS {
+                error(
+                    report,
+                    format!("row {}", row),
+                    "row past the last Excel row".into(),
+                );
+            }
+            last_row = last_row.max(row);
+            last_col = None;
+        } else if is_tag(tag, "c") {
+            let next_col = last_col.map_or(0, |c| c + 1);
+            let col = match attr(tag, "r") {
+                Some(reference) => {
+                    let letters = reference.trim_end_matches(|c: char| c.is_ascii_digit());
+                    let digits = &reference[letters.len()..];
+                    let col = column_index(letters);
+                    if col.is_none() || digits.parse::<u32>() != Ok(row) {
+                        error(
+                            report,
+                            reference.to_string(),
+                            format!("cell reference {} is not on row {}", reference, row),
+                        );
+                    }
+                    col.unwrap_or(next_col)
+                }
+                None => next_col,
+            };
+            let reference = format!("{}{}", Cell::col_to_letter(col), row);
+            if last_col.is_some_and(|last| col <= last) {
+                error(
+                    report,
+                    reference.clone(),
+                    "cell is out of column order".into(),
+                );
+            } else if col >= MAX_COLS {
+                error(
+                    report,
+                    reference.clone(),
+                    "cell past the last Excel column".into(),
+                );
+            }
+            last_col = Some(col);
+            shared_cell = (attr(tag, "t") == Some("s")).then_some(reference);
+        } else if tag == "</c>" {
+            shared_cell = None;
+        }
+    }
+
+    if issues > MAX_ISSUES_PER_PART {
+        report.error(
+            part,
+            None,
+            format!("{} more issues not listed", issues - MAX_ISSUES_PER_PART),
+        );
+    }
+    Ok(())
+}
+
+fn read_text(zip: &mut StreamingZipReader, part: &str) -> Result<String> {
+    let data = zip.read_entry_by_name(part)?;
+    String::from_utf8(data)
+        .map_err(|_| ExcelError::InvalidFormat(format!("{} is not valid UTF-8", part)))
+}
+
+/// Whether `tag` opens (or is) a `<name>` element, not one starting with it
+fn is_tag(tag: &str, name: &str) -> bool {
+    tag.strip_prefix('<')
+        .and_then(|rest| rest.strip_prefix(name))
+        .is_some_and(|rest| rest.starts_with([' ', '>', '/', '\t', '\r', '\n']))
+}
+
+/// Opening tags of `<name>` elements in `xml`
+fn elements<'a>(xml: &'a str, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    let mut rest = xml;
+    std::iter::from_fn(move || loop {
+        let start = rest.find('<')?;
+        let end = start + rest[start..].find('>')? + 1;
+        let tag = &rest[start..end];
+        rest = &rest[end..];
+        if is_tag(tag, name) {
+            return Some(tag);
+        }
+    })
+}
+
+fn attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{}=\"", name);
+    let mut offset = 0;
+    while let Some(i) = tag[offset..].find(&pattern) {
+        let at = offset + i;
+        if tag[..at].ends_with(char::is_whitespace) {
+            let value = &tag[at + pattern.len()..];
+            return value.find('"').map(|end| &value[..end]);
+        }
+        offset = at + pattern.len();
+    }
+    None
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 0-based column of `letters` ("A" = 0), if valid
+fn column_index(letters: &str) -> Option<u32> {
+    if letters.is_empty() || letters.len() > 3 {
+        return None;
+    }
+    letters
+        .bytes()
+        .try_fold(0u32, |acc, b| {
+            b.is_ascii_uppercase()
+                .then(|| acc * 26 + (b - b'A') as u32 + 1)
+        })
+        .map(|n| n - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_targets() {
+        assert_eq!(rels_source("_rels/.rels").as_deref(), Some(""));
+        assert_eq!(
+            rels_source("xl/worksheets/_rels/sheet1.xml.rels").as_deref(),
+            Some("xl/worksheets/sheet1.xml")
+        );
+        assert_eq!(rels_source("xl/workbook.xml.rels"), None);
+
+        assert_eq!(resolve_target("", "xl/workbook.xml"), "xl/workbook.xml");
+        assert_eq!(
+            resolve_target("xl/worksheets/sheet1.xml", "../tables/table1.xml"),
+            "xl/tables/table1.xml"
+        );
+        assert_eq!(
+            resolve_target("xl/workbook.xml", "/xl/styles.xml"),
+            "xl/styles.xml"
+        );
+    }
+
+    #[test]
+    fn test_scanning_helpers() {
+        let xml = r#"<rows><row r="1"><c r="A1"/></row><rowBreaks count="1"/></rows>"#;
+        let rows: Vec<_> = elements(xml, "row").collect();
+        assert_eq!(rows, [r#"<row r="1">"#]);
+        assert_eq!(attr(r#"<c r="B2" t="s">"#, "t"), Some("s"));
+        assert_eq!(column_index("A"), Some(0));
+        assert_eq!(column_index("XFD"), Some(16_383));
+        assert_eq!(column_index("a1"), None);
+    }
+}
@@ -0,0 +1,493 @@
+//! Column widths, column and row styles, and row heights of the current
+//! worksheet
+
+use super::ExcelWriter;
+use crate::error::Result;
+use crate::types::CellStyle;
+
+impl ExcelWriter {
+    /// Set column width for the current worksheet
+    ///
+    /// Width is in Excel units (default is 8.43).
+    /// One unit is approximately the width of one character in the default font.
+    ///
+    /// **IMPORTANT:** Must be called BEFORE writing any rows, unless the
+    /// sheet was prepared with [`defer_column_widths`](Self::defer_column_widths)
+    /// or [`autofit_columns`](Self::autofit_columns); then widths can be set
+    /// until the sheet is finished and override estimated widths.
+    ///
+    /// # Arguments
+    /// * `col` - Column index (0-based: 0=A, 1=B, 2=C, etc.)
+    /// * `width` - Column width in Excel units (typically 8-50)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    ///
+    /// // Set column widths BEFORE writing rows
+    /// writer.set_column_width(0, 20.0).unwrap();  // Column A = 20 units wide
+    /// writer.set_column_width(1, 15.0).unwrap();  // Column B = 15 units wide
+    /// writer.set_column_width(2, 30.0).unwrap();  // Column C = 30 units wide
+    ///
+    /// // Now write rows
+    /// writer.write_header_bold(&["Name", "Age", "Email"]).unwrap();
+    /// writer.write_row(&["Alice", "30", "alice@example.com"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_column_width(&mut self, col: u32, width: f64) -> Result<()> {
+        self.inner.set_column_width(col, width)
+    }
+
+    /// Give every cell of column `col` (0-based) written without a style
+    /// the style `style`
+    ///
+    /// Applies to rows of the current sheet written after the call, by
+    /// [`write_row`](Self::write_row), [`write_row_typed`](Self::write_row_typed)
+    /// and the other `write_*` methods. Cells with an explicit style other
+    /// than [`CellStyle::Default`] keep it. A column style takes precedence
+    /// over [`set_row_banding`](Self::set_row_banding); setting
+    /// `CellStyle::Default` removes it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::types::{CellStyle, CellValue};
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("prices.xlsx").unwrap();
+    /// writer.write_header_bold(["Item", "Price"]).unwrap();
+    /// writer.set_column_style(1, CellStyle::NumberCurrency).unwrap();
+    /// writer
+    ///     .write_row_typed(&[CellValue::String("Coffee".into()), CellValue::Float(3.5)])
+    ///     .unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_column_style(&mut self, col: u32, style: CellStyle) -> Result<()> {
+        self.inner.set_column_style(col, style)
+    }
+
+    /// Round floats written to column `col` (0-based) to at most `decimals`
+    /// places, or write them in full again with `None`
+    ///
+    /// Applies to rows of the current sheet written after the call. Floats
+    /// are otherwise written with the shortest text that reads back as the
+    /// same value, so `0.1 + 0.2` is stored as `0.30000000000000004`; with
+    /// 2 decimals it is stored as `0.3`. This changes the stored value, not
+    /// how many decimals Excel shows (see [`CellStyle::NumberDecimal`]).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::types::CellValue;
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("rates.xlsx").unwrap();
+    /// writer.set_column_decimals(1, Some(4)).unwrap();
+    /// writer
+    ///     .write_row_typed(&[CellValue::String("EUR".into()), CellValue::Float(1.0 / 3.0)])
+    ///     .unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_column_decimals(&mut self, col: u32, decimals: Option<u8>) -> Result<()> {
+        self.inner.set_column_decimals(col, decimals)
+    }
+
+    /// Write numbers in column `col` (0-based) as text, or as numbers again
+    /// with `false`
+    ///
+    /// Excel keeps only 15 significant digits of a number, so an ID like
+    /// `915240563020386304` would be shown and saved as `915240563020386000`.
+    /// In a text column every digit is kept, and Excel's "number stored as
+    /// text" warning is turned off. Applies to rows of the current sheet
+    /// written after the call.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::types::CellValue;
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("tweets.xlsx").unwrap();
+    /// writer.set_column_as_text(0, true).unwrap();
+    /// writer
+    ///     .write_row_typed(&[CellValue::Int(915240563020386304), CellValue::Int(12)])
+    ///     .unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_column_as_text(&mut self, col: u32, as_text: bool) -> Result<()> {
+        self.inner.set_column_as_text(col, as_text)
+    }
+
+    /// Alternate the style of unstyled cells between even and odd rows
+    ///
+    /// Rows are counted as Excel numbers them, so with a header in row 1
+    /// the first data row is even. Like column styles, banding only fills
+    /// cells written without a style of their own (a bold header stays
+    /// bold) and applies to the current sheet until it is cleared with
+    /// [`clear_row_banding`](Self::clear_row_banding) or the next sheet is
+    /// added. [`set_column_style`](Self::set_column_style) wins over it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::types::CellStyle;
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("banded.xlsx").unwrap();
+    /// writer.write_header_bold(["Name", "Team"]).unwrap();
+    /// writer.set_row_banding(CellStyle::HighlightGreen, CellStyle::Default);
+    /// writer.write_row(["Alice", "Red"]).unwrap();
+    /// writer.write_row(["Bob", "Blue"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_row_banding(&mut self, even: CellStyle, odd: CellStyle) {
+        self.inner.set_row_banding(even, odd)
+    }
+
+    /// Stop banding rows of the current sheet
+    pub fn clear_row_banding(&mut self) {
+        self.inner.clear_row_banding()
+    }
+
+    /// Allow [`set_column_width`](Self::set_column_width) after rows have
+    /// been written to the current sheet
+    ///
+    /// Useful when widths are computed while iterating the data. The
+    /// sheet's rows are held back until it is finished (or the next sheet is
+    /// added), spilling to a temporary file past 1 MB, because the widths
+    /// must precede the cell data in the file. Must be called before writing
+    /// rows.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("names.xlsx").unwrap();
+    /// writer.defer_column_widths().unwrap();
+    /// let mut longest = 0;
+    /// for name in ["Ann", "Bartholomew", "Cy"] {
+    ///     longest = longest.max(name.len());
+    ///     writer.write_row([name]).unwrap();
+    /// }
+    /// writer.set_column_width(0, longest as f64 + 2.0).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn defer_column_widths(&mut self) -> Result<()> {
+        self.inner.defer_column_widths()
+    }
+
+    /// Size the current sheet's columns to fit their content
+    ///
+    /// Widths are estimated from each cell's text (characters × a font
+    /// width factor, wide CJK characters counting double) and capped at 60
+    /// units. Rows are held back as with
+    /// [`defer_column_widths`](Self::defer_column_widths). Must be called
+    /// before writing rows.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("fitted.xlsx").unwrap();
+    /// writer.autofit_columns().unwrap();
+    /// writer.write_header_bold(["Name", "Email"]).unwrap();
+    /// writer.write_row(["Alice", "alice@example.com"]).unwrap();
+    /// // Explicit widths still win
+    /// writer.set_column_width(0, 30.0).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn autofit_columns(&mut self) -> Result<()> {
+        self.autofit_columns_with(crate::types::AutofitOptions::default())
+    }
+
+    /// Size the current sheet's columns to fit their content, within the
+    /// given minimum and maximum widths
+    pub fn autofit_columns_with(&mut self, options: crate::types::AutofitOptions) -> Result<()> {
+        self.inner.autofit_columns(options)
+    }
+
+    /// Set height for the next row to be written
+    ///
+    /// Height is in points (1 point = 1/72 inch).
+    /// Default row height is 15 points.
+    ///
+    /// This setting is consumed by the next write_row call.
+    /// To set height for multiple rows, call this before each write_row.
+    ///
+    /// # Arguments
+    /// * `height` - Row height in points (typically 10-50)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    ///
+    /// // Set height for header row
+    /// writer.set_next_row_height(25.0).unwrap();
+    /// writer.write_header_bold(&["Name", "Age", "Email"]).unwrap();
+    ///
+    /// // Regular row (default height)
+    /// writer.write_row(&["Alice", "30", "alice@example.com"]).unwrap();
+    ///
+    /// // Tall row
+    /// writer.set_next_row_height(40.0).unwrap();
+    /// writer.write_row(&["Bob", "25", "bob@example.com"]).unwrap();
+    ///
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_next_row_height(&mut self, height: f64) -> Result<()> {
+        self.inner.set_next_row_height(height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CellValue;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_row_banding_and_column_styles() {
+        use crate::fast_writer::StreamingZipReader;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_row_banding(CellStyle::HighlightGreen, CellStyle::Default);
+        writer
+            .set_column_style(2, CellStyle::NumberCurrency)
+            .unwrap();
+        writer.write_header_bold(["Name", "Note", "Price"]).unwrap();
+        writer.write_row(["Coffee", "", "3.5"]).unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::String("Tea".to_string()),
+                CellValue::Empty,
+                CellValue::Float(2.0),
+            ])
+            .unwrap();
+        writer
+            .write_row_styled(&[
+                (CellValue::String("Cake".to_string()), CellStyle::TextBold),
+                (CellValue::Empty, CellStyle::Default),
+                (CellValue::Float(4.25), CellStyle::Default),
+            ])
+            .unwrap();
+        writer.clear_row_banding();
+        writer.set_column_style(2, CellStyle::Default).unwrap();
+        writer.write_row(["Total", "", "9.75"]).unwrap();
+        assert!(writer
+            .set_column_style(16_384, CellStyle::TextBold)
+            .is_err());
+
+        writer.add_sheet("Plain").unwrap();
+        writer.write_row(["x"]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        // The explicit header style wins over the column style
+        assert!(sheet.contains(r#"<c r="C1" s="1" t="inlineStr">"#));
+        assert!(sheet.contains(r#"<c r="A2" s="11" t="inlineStr">"#));
+        assert!(sheet.contains(r#"<c r="B2" s="11"/>"#));
+        assert!(sheet.contains(r#"<c r="C2" s="4" t="inlineStr">"#));
+        assert!(sheet.contains(r#"<c r="A3" t="inlineStr">"#));
+        assert!(sheet.contains(r#"<c r="C3" s="4" t="n">"#));
+        assert!(sheet.contains(r#"<c r="A4" s="8" t="inlineStr">"#));
+        assert!(sheet.contains(r#"<c r="B4" s="11"/>"#));
+        assert!(sheet.contains(r#"<c r="C4" s="4" t="n">"#));
+        assert!(sheet.contains(r#"<row r="5"><c r="A5" t="inlineStr">"#));
+        assert!(sheet.contains(r#"<c r="C5" t="inlineStr">"#));
+
+        let plain =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet2.xml").unwrap()).unwrap();
+        assert!(plain.contains(r#"<c r="A1" t="inlineStr">"#));
+    }
+
+    #[test]
+    fn test_column_widths_and_autofit() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::AutofitOptions;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_column_width(1, 20.0).unwrap();
+        writer.write_row(["Fixed", "Width"]).unwrap();
+        assert!(writer.set_column_width(0, 10.0).is_err());
+
+        writer.add_sheet("Fitted").unwrap();
+        writer
+            .autofit_columns_with(AutofitOptions {
+                min_width: 5.0,
+                max_width: 30.0,
+            })
+            .unwrap();
+        writer.write_header_bold(["Id", "Name", "Note"]).unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::Int(1234567),
+                CellValue::String("東京".to_string()),
+                CellValue::String("x".repeat(100)),
+            ])
+            .unwrap();
+        writer.write_row(["", "", "", "", "Explicit"]).unwrap();
+        // Allowed after rows on an auto-fitted sheet, and wins over the estimate
+        writer.set_column_width(4, 12.5).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let fixed =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(fixed.contains(
+            r#"<cols><col min="2" max="2" width="20" customWidth="1"/></cols><sheetData>"#
+        ));
+
+        // 7 digits → 8.7; "Name" (4) beats "東京" (2 wide chars) → 5.4;
+        // 100 chars capped at 30; nothing in column D
+        let fitted =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet2.xml").unwrap()).unwrap();
+        assert!(fitted.contains(
+            r#"<cols><col min="1" max="1" width="8.7" customWidth="1"/><col min="2" max="2" width="5.4" customWidth="1"/><col min="3" max="3" width="30" customWidth="1"/><col min="5" max="5" width="12.5" customWidth="1"/></cols><sheetData><row r="1">"#
+        ));
+
+        let mut reader = crate::ExcelReader::open(temp.path()).unwrap();
+        let rows: Vec<Vec<String>> = reader
+            .rows("Fitted")
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1][1], "東京");
+    }
+
+    #[test]
+    fn test_deferred_column_widths() {
+        use crate::fast_writer::StreamingZipReader;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.defer_column_widths().unwrap();
+        writer.start_group().unwrap();
+        writer.write_row(["Detail", "1"]).unwrap();
+        writer.end_group(true).unwrap();
+        writer.write_row(["Total", "1"]).unwrap();
+        writer.set_column_width(0, 14.0).unwrap();
+        writer.set_column_width(0, 16.0).unwrap();
+        let err = writer.set_column_width(1, 300.0).unwrap_err();
+        assert!(err.to_string().contains("Invalid width"));
+
+        writer.add_sheet("Streamed").unwrap();
+        writer.write_row(["Data"]).unwrap();
+        let err = writer.set_column_width(0, 10.0).unwrap_err();
+        assert!(err.to_string().contains("defer_column_widths"));
+        assert!(writer.defer_column_widths().is_err());
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(
+            r#"<cols><col min="1" max="1" width="16" customWidth="1"/></cols><sheetData><row r="1" hidden="1" outlineLevel="1">"#
+        ));
+        assert!(sheet.contains(r#"<row r="2" collapsed="1">"#));
+        let streamed =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet2.xml").unwrap()).unwrap();
+        assert!(!streamed.contains("<cols>"));
+    }
+
+    #[test]
+    fn test_float_text_and_column_decimals() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_column_decimals(1, Some(2)).unwrap();
+        assert!(writer.set_column_decimals(16_384, Some(2)).is_err());
+        writer
+            .write_row_typed(&[
+                CellValue::Float(1e-7),
+                CellValue::Float(0.1 + 0.2),
+                CellValue::Float(f64::NAN),
+                CellValue::Float(1e21),
+            ])
+            .unwrap();
+        writer.set_column_decimals(1, None).unwrap();
+        writer
+            .write_row_typed(&[CellValue::Empty, CellValue::Float(0.1 + 0.2)])
+            .unwrap();
+        writer.save().unwrap();
+
+        let mut zip = crate::fast_writer::StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(r#"<c r="A1" t="n"><v>1E-7</v></c>"#));
+        assert!(sheet.contains(r#"<c r="B1" t="n"><v>0.3</v></c>"#));
+        assert!(sheet.contains(r#"<c r="C1" t="e"><v>#NUM!</v></c>"#));
+        assert!(sheet.contains(r#"<c r="D1" t="n"><v>1E21</v></c>"#));
+        assert!(sheet.contains(r#"<c r="B2" t="n"><v>0.30000000000000004</v></c>"#));
+
+        let mut reader = crate::streaming_reader::StreamingReader::open(temp.path()).unwrap();
+        let rows: Vec<_> = reader
+            .rows("Sheet1")
+            .unwrap()
+            .map(|row| row.unwrap().cells)
+            .collect();
+        assert_eq!(rows[0][0], CellValue::Float(1e-7));
+        assert_eq!(rows[1][1], CellValue::Float(0.1 + 0.2));
+    }
+
+    #[test]
+    fn test_column_as_text() {
+        use crate::streaming_reader::{ReadOptions, StreamingReader};
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_column_as_text(0, true).unwrap();
+        writer.set_column_decimals(1, Some(1)).unwrap();
+        writer.set_column_as_text(1, true).unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::Int(915240563020386304),
+                CellValue::Float(2.25),
+                CellValue::Int(915240563020386304),
+                CellValue::Float(1e20),
+            ])
+            .unwrap();
+        writer.save().unwrap();
+
+        let mut zip = crate::fast_writer::StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(r#"<c r="A1" t="inlineStr"><is><t>915240563020386304</t></is></c>"#));
+        assert!(sheet.contains(r#"<c r="B1" t="inlineStr"><is><t>2.3</t></is></c>"#));
+        assert!(sheet.contains(r#"<c r="C1" t="n"><v>915240563020386304</v></c>"#));
+        assert!(sheet.contains("<ignoredErrors>"));
+
+        // Numbers past 2^53 read back as text only when asked for
+        let mut reader = StreamingReader::open(temp.path()).unwrap();
+        let row = reader.rows("Sheet1").unwrap().next().unwrap().unwrap();
+        assert_eq!(row.cells[2], CellValue::Int(915240563020386304));
+        assert_eq!(row.cells[3], CellValue::Float(1e20));
+
+        let options = ReadOptions {
+            big_integers_as_text: true,
+            ..ReadOptions::default()
+        };
+        let mut reader = StreamingReader::open_with_options(temp.path(), options).unwrap();
+        let row = reader.rows("Sheet1").unwrap().next().unwrap().unwrap();
+        assert_eq!(
+            row.cells,
+            vec![
+                CellValue::String("915240563020386304".to_string()),
+                CellValue::String("2.3".to_string()),
+                CellValue::BigInt("915240563020386304".to_string()),
+                CellValue::BigInt("100000000000000000000".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,3192 @@
+//! Excel file writing with streaming support
+//!
+//! **Breaking Change in v0.2.0:** ExcelWriter now uses streaming with constant memory usage.
+//! Data is written directly to disk as you call write_row(), not kept in memory.
+
+use crate::error::Result;
+use crate::fast_writer::UltraLowMemoryWorkbook;
+use crate::types::{CellRef, CellStyle, CellValue, ColumnSlice};
+use std::borrow::Borrow;
+use std::fmt::Display;
+use std::path::Path;
+
+mod format;
+mod names;
+
+/// Excel file writer with streaming capabilities
+///
+/// **V0.2.0 Breaking Change:** Now uses streaming underneath.
+/// Data is written directly to disk with constant memory usage.
+///
+/// Writes Excel files row by row, streaming data directly to a ZIP file.
+/// Each row is turned into XML and handed to the compressor as soon as it is
+/// written; no cell data is kept once `write_row*` returns, so memory usage
+/// does not grow with the number of rows.
+///
+/// What is kept until the sheet or workbook is finished is metadata that
+/// Excel requires after the cell data: hyperlink targets (at most 65,530 per
+/// sheet), data validations, conditional formats, charts and defined names.
+///
+/// # Examples
+///
+/// ```no_run
+/// use excelstream::writer::ExcelWriter;
+///
+/// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+///
+/// // Write millions of rows with constant memory usage
+/// for i in 0..1_000_000 {
+///     writer.write_row(&["Name", "Age", "Email"]).unwrap();
+/// }
+///
+/// writer.save().unwrap();
+/// ```
+pub struct ExcelWriter {
+    inner: UltraLowMemoryWorkbook,
+    current_sheet_name: String,
+    current_row: u32,
+    #[cfg(feature = "encryption")]
+    encryption: Option<crate::crypto::PendingEncryption>,
+}
+
+impl ExcelWriter {
+    /// Create a new Excel writer with streaming support
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_row(&["Name", "Age"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut inner = UltraLowMemoryWorkbook::new(path)?;
+        inner.add_worksheet("Sheet1")?;
+
+        Ok(ExcelWriter {
+            inner,
+            current_sheet_name: "Sheet1".to_string(),
+            current_row: 0,
+            #[cfg(feature = "encryption")]
+            encryption: None,
+        })
+    }
+
+    /// Create a new Excel writer with custom compression level
+    ///
+    /// # Arguments
+    /// * `path` - Output file path
+    /// * `compression_level` - Compression level from 0 to 9
+    ///   - 0: No compression (fastest, largest files)
+    ///   - 1: Fast compression (very fast, ~31MB for 1M rows) - good for development
+    ///   - 6: Balanced compression (~18MB for 1M rows) - **recommended for production**
+    ///   - 9: Maximum compression (~18MB for 1M rows, slowest)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// // Fast compression for development
+    /// let mut writer = ExcelWriter::with_compression("output.xlsx", 1).unwrap();
+    /// writer.write_row(&["Name", "Age"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn with_compression<P: AsRef<Path>>(path: P, compression_level: u32) -> Result<Self> {
+        let mut inner = UltraLowMemoryWorkbook::with_compression(path, compression_level)?;
+        inner.add_worksheet("Sheet1")?;
+
+        Ok(ExcelWriter {
+            inner,
+            current_sheet_name: "Sheet1".to_string(),
+            current_row: 0,
+            #[cfg(feature = "encryption")]
+            encryption: None,
+        })
+    }
+
+    /// Create a new Excel writer using a
+    /// [`CompressionProfile`](crate::types::CompressionProfile)
+    ///
+    /// Same as [`with_compression`](#method.with_compression) with the
+    /// profile's deflate level.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::{CompressionProfile, ExcelWriter};
+    ///
+    /// let writer = ExcelWriter::with_compression_profile("out.xlsx", CompressionProfile::Fastest)
+    ///     .unwrap();
+    /// ```
+    pub fn with_compression_profile<P: AsRef<Path>>(
+        path: P,
+        profile: crate::types::CompressionProfile,
+    ) -> Result<Self> {
+        Self::with_compression(path, profile.deflate_level())
+    }
+
+    /// Create a writer whose file can only be opened with `password`
+    ///
+    /// The workbook is streamed as usual to a temporary file next to `path`,
+    /// and [`save`](#method.save) encrypts it into `path` with ECMA-376 Agile
+    /// Encryption (AES-256, SHA-512), the scheme Excel uses for
+    /// *File > Protect Workbook > Encrypt with Password*. The temporary file
+    /// is removed afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::with_password("secret.xlsx", "s3cr3t").unwrap();
+    /// writer.write_row(&["Name", "Salary"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    #[cfg(feature = "encryption")]
+    pub fn with_password<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
+        let (pending, plain) = crate::crypto::PendingEncryption::new(path.as_ref(), password)?;
+        let mut writer = Self::new(&plain)?;
+        writer.encryption = Some(pending);
+        Ok(writer)
+    }
+
+    /// Set compression level for the output file
+    ///
+    /// # Arguments
+    /// * `level` - Compression level from 0 to 9 (see [`with_compression`](#method.with_compression))
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.set_compression_level(1); // Fast compression
+    /// writer.write_row(&["Name", "Age"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_compression_level(&mut self, level: u32) {
+        self.inner.set_compression_level(level);
+    }
+
+    /// Get current compression level
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// println!("Compression level: {}", writer.compression_level());
+    /// ```
+    pub fn compression_level(&self) -> u32 {
+        self.inner.compression_level()
+    }
+
+    /// Deflate on `workers` threads while rows are being generated
+    ///
+    /// Deflate usually dominates the cost of large exports. With workers,
+    /// each part is cut into chunks of a few hundred KB that are compressed
+    /// in parallel and written back in order, so building the XML of the
+    /// next rows overlaps with compressing the previous ones. At most two
+    /// chunks per worker are in flight before `write_row` waits. 0, the
+    /// default, compresses on the calling thread.
+    ///
+    /// Can be changed at any time; data written so far is compressed and
+    /// written out first.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("large.xlsx").unwrap();
+    /// writer.set_compression_workers(4).unwrap();
+    /// for i in 0..1_000_000 {
+    ///     writer.write_row([i.to_string(), format!("Row {}", i)]).unwrap();
+    /// }
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_compression_workers(&mut self, workers: usize) -> Result<()> {
+        self.inner.set_compression_workers(workers)
+    }
+
+    /// Number of compression worker threads, 0 when compressing inline
+    pub fn compression_workers(&self) -> usize {
+        self.inner.compression_workers()
+    }
+
+    /// Choose between inline strings and a shared string table
+    ///
+    /// Strings are written inline by default, which keeps memory flat for
+    /// exports of mostly unique values (ids, emails, free text). Columns with
+    /// a few repeated values (status, country, category) give smaller files
+    /// with [`StringMode::SharedDeduped`](crate::types::StringMode), at the
+    /// cost of keeping every distinct string in memory until
+    /// [`save`](#method.save). `SharedThresholded` caps that memory: once
+    /// `max_unique` strings are shared, new ones are written inline.
+    /// `SharedHashed` bounds memory by bytes instead and keeps the string
+    /// text in a temporary file, for exports with millions of distinct
+    /// values where repeats should still be shared.
+    ///
+    /// Takes effect for cells written after the call. Fails when switching
+    /// between `SharedHashed` and the other shared modes after a string was
+    /// shared.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::{ExcelWriter, StringMode};
+    ///
+    /// let mut writer = ExcelWriter::new("orders.xlsx").unwrap();
+    /// writer
+    ///     .set_string_mode(StringMode::SharedThresholded { max_unique: 50_000 })
+    ///     .unwrap();
+    /// writer.write_row(["Order", "Status"]).unwrap();
+    /// writer.write_row(["1001", "Shipped"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_string_mode(&mut self, mode: crate::types::StringMode) -> Result<()> {
+        self.inner.set_string_mode(mode)
+    }
+
+    ///
+    /// Data is written directly to the ZIP file and flushed periodically.
+    /// Memory usage remains constant regardless of how many rows you write.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_row(&["Alice", "30", "New York"]).unwrap();
+    /// writer.write_row(&["Bob", "25", "San Francisco"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_row<I, S>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.inner.write_row(data)?;
+        self.row_written();
+        Ok(())
+    }
+
+    /// Write a row of any `Display` values (numbers, dates, custom types...)
+    ///
+    /// Every value is written as an inline string, exactly like [`write_row`](Self::write_row),
+    /// but values are formatted into a reused buffer instead of requiring the
+    /// caller to build a `Vec<String>` for each row.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// for i in 0..3u32 {
+    ///     writer.write_row_display([i, i * 10, i * 100]).unwrap();
+    /// }
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_row_display<I>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.inner.write_row_display(data)?;
+        self.row_written();
+        Ok(())
+    }
+
+    /// Write a `Serialize` struct as a row
+    ///
+    /// Columns follow the struct's field order. The first record written to
+    /// a sheet also writes a bold header row with the field names. See
+    /// [`record`](crate::record) for how values are mapped to cells.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Employee {
+    ///     name: String,
+    ///     age: u32,
+    ///     salary: Option<f64>,
+    /// }
+    ///
+    /// let mut writer = ExcelWriter::new("employees.xlsx").unwrap();
+    /// writer.write_record(&Employee { name: "Alice".into(), age: 30, salary: Some(5000.0) }).unwrap();
+    /// writer.write_record(&Employee { name: "Bob".into(), age: 25, salary: None }).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn write_record<T: serde::Serialize + ?Sized>(&mut self, record: &T) -> Result<()> {
+        let record = crate::record::to_record_in(record, self.inner.timezone_policy())?;
+        if self.current_row == 0 {
+            self.write_header_bold(&record.headers)?;
+        }
+        self.write_row_styled(&record.cells)
+    }
+
+    /// Write every item of an iterator with [`write_record`](Self::write_record)
+    #[cfg(feature = "serde")]
+    pub fn write_records<I>(&mut self, records: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: serde::Serialize,
+    {
+        for record in records {
+            self.write_record(&record)?;
+        }
+        Ok(())
+    }
+
+    /// Write multiple rows at once (batch operation)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    ///
+    /// let rows = vec![
+    ///     vec!["Alice", "30", "NYC"],
+    ///     vec!["Bob", "25", "SF"],
+    ///     vec!["Carol", "35", "LA"],
+    /// ];
+    ///
+    /// writer.write_rows_batch(&rows).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_rows_batch<I, R, S>(&mut self, rows: I) -> Result<()>
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for row_data in rows {
+            self.write_row(row_data)?;
+        }
+        Ok(())
+    }
+
+    /// Write multiple typed rows at once (batch operation)
+    pub fn write_rows_typed_batch(&mut self, rows: &[Vec<CellValue>]) -> Result<()> {
+        for row_cells in rows {
+            self.write_row_typed(row_cells)?;
+        }
+        Ok(())
+    }
+
+    /// Write a row with typed cell values
+    ///
+    /// [`CellValue::Hyperlink`] cells become clickable links. Date cells,
+    /// such as chrono dates and timestamps converted with `into()`, get a
+    /// date format.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use chrono::NaiveDate;
+    /// use excelstream::writer::ExcelWriter;
+    /// use excelstream::types::CellValue;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_row_typed(&[
+    ///     CellValue::String("Alice".to_string()),
+    ///     CellValue::Int(30),
+    ///     CellValue::Float(1234.56),
+    ///     NaiveDate::from_ymd_opt(2024, 1, 15).unwrap().into(),
+    ///     CellValue::hyperlink("https://example.com/users/alice", "Profile"),
+    /// ]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
+        self.write_row_typed_iter(cells)
+    }
+
+    /// Write a row of typed values from an iterator, without collecting it
+    ///
+    /// Takes owned `CellValue`s or references to them, so rows can be
+    /// produced on the fly or borrowed from existing records. Cells are
+    /// styled like [`write_row_typed`](Self::write_row_typed) does; nothing
+    /// is allocated per row.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    /// use excelstream::types::CellValue;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// for i in 0..1000 {
+    ///     writer
+    ///         .write_row_typed_iter((0..10).map(|col| CellValue::Int(i * 10 + col)))
+    ///         .unwrap();
+    /// }
+    ///
+    /// let totals = vec![CellValue::String("Total".to_string()), CellValue::Int(49995)];
+    /// writer.write_row_typed_iter(&totals).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_row_typed_iter<I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CellValue>,
+    {
+        self.inner.write_row_typed_iter(cells)?;
+        self.row_written();
+        Ok(())
+    }
+
+    /// Write a row of borrowed cells, passing strings through without
+    /// copying them
+    ///
+    /// For exports whose data is already in memory: `&str` and `&String`
+    /// cells are escaped into the row straight from the caller's data
+    /// instead of being cloned into a [`CellValue::String`] first. Numbers,
+    /// booleans and dates are passed by value, and any other value by
+    /// reference through [`CellRef::Value`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::types::CellRef;
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// struct User {
+    ///     name: String,
+    ///     email: Option<String>,
+    ///     age: i64,
+    /// }
+    ///
+    /// let users = vec![User { name: "Alice".into(), email: None, age: 30 }];
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_row_refs(["Name", "Email", "Age"]).unwrap();
+    /// for user in &users {
+    ///     writer
+    ///         .write_row_refs([
+    ///             CellRef::from(&user.name),
+    ///             CellRef::from(user.email.as_deref()),
+    ///             CellRef::from(user.age),
+    ///         ])
+    ///         .unwrap();
+    /// }
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_row_refs<'a, I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<CellRef<'a>>,
+    {
+        self.inner.write_row_refs(cells)?;
+        self.row_written();
+        Ok(())
+    }
+
+    /// Write a batch of columns, returning the number of rows written
+    ///
+    /// For producers that hand out data column by column (Arrow, Polars,
+    /// database drivers): row `i` of the sheet gets value `i` of every
+    /// column, read straight from the slices while the row is encoded, so
+    /// the batch is never transposed into row vectors. All columns must
+    /// have the same length.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::types::ColumnSlice;
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let ids = vec![1, 2, 3];
+    /// let names = vec!["Alice".to_string(), "Bob".to_string(), "Carol".to_string()];
+    /// let scores = vec![91.5, 78.0, 88.25];
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_header_bold(["id", "name", "score"]).unwrap();
+    /// let rows = writer
+    ///     .write_columns(&[
+    ///         ColumnSlice::Int(&ids),
+    ///         ColumnSlice::String(&names),
+    ///         ColumnSlice::Float(&scores),
+    ///     ])
+    ///     .unwrap();
+    /// assert_eq!(rows, 3);
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_columns(&mut self, columns: &[ColumnSlice]) -> Result<usize> {
+        let written = self.inner.write_columns(columns);
+        self.row_written();
+        written
+    }
+
+    /// Write a row with styled cells
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    /// use excelstream::types::{CellValue, CellStyle};
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_row_styled(&[
+    ///     (CellValue::String("Total".to_string()), CellStyle::HeaderBold),
+    ///     (CellValue::Float(1234.56), CellStyle::NumberCurrency),
+    ///     (CellValue::Int(95), CellStyle::NumberPercentage),
+    /// ]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_row_styled(&mut self, cells: &[(CellValue, CellStyle)]) -> Result<()> {
+        use crate::types::StyledCell;
+
+        let styled_cells: Vec<StyledCell> = cells
+            .iter()
+            .map(|(value, style)| StyledCell::new(value.clone(), *style))
+            .collect();
+
+        self.inner.write_row_styled(&styled_cells)?;
+        self.row_written();
+        Ok(())
+    }
+
+    /// Write every row of an Arrow `RecordBatch`, returning the number of rows
+    ///
+    /// Columns are mapped by their Arrow type: integers and floats become
+    /// numbers, booleans stay booleans, dates and timestamps become Excel
+    /// dates with a date format, and strings are written as text. Nulls are
+    /// left empty. No header is written; use the schema's field names with
+    /// [`write_header_bold`](Self::write_header_bold) for that.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use arrow::array::{Float64Array, Int64Array, StringArray};
+    /// use arrow::record_batch::RecordBatch;
+    /// use excelstream::writer::ExcelWriter;
+    /// use std::sync::Arc;
+    ///
+    /// let batch = RecordBatch::try_from_iter([
+    ///     ("id", Arc::new(Int64Array::from(vec![1, 2])) as _),
+    ///     ("name", Arc::new(StringArray::from(vec!["Alice", "Bob"])) as _),
+    ///     ("score", Arc::new(Float64Array::from(vec![9.5, 7.25])) as _),
+    /// ])
+    /// .unwrap();
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_header_bold(["id", "name", "score"]).unwrap();
+    /// writer.write_record_batch(&batch).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    #[cfg(feature = "parquet-support")]
+    pub fn write_record_batch(
+        &mut self,
+        batch: &arrow::record_batch::RecordBatch,
+    ) -> Result<usize> {
+        let rows = self.inner.write_record_batch(batch)?;
+        self.row_written();
+        Ok(rows)
+    }
+
+    /// Write a row with all cells using the same style
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    /// use excelstream::types::{CellValue, CellStyle};
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_row_with_style(&[
+    ///     CellValue::Int(100),
+    ///     CellValue::Int(200),
+    ///     CellValue::Int(300),
+    /// ], CellStyle::NumberInteger).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_row_with_style(&mut self, values: &[CellValue], style: CellStyle) -> Result<()> {
+        let cells: Vec<_> = values.iter().map(|v| (v.clone(), style)).collect();
+        self.write_row_styled(&cells)
+    }
+
+    /// Write a row of typed values at 0-based `row`, leaving the rows
+    /// in between empty
+    ///
+    /// For sparse grids whose rows must keep their numbers. Rows still go
+    /// top to bottom: `row` has to be below every row written so far.
+    /// Writing continues after it, so a following
+    /// [`write_row`](Self::write_row) lands on `row + 1`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    /// use excelstream::types::CellValue;
+    ///
+    /// let mut writer = ExcelWriter::new("grid.xlsx").unwrap();
+    /// writer.write_row_at(0, &[CellValue::String("Sensor".into())]).unwrap();
+    /// writer.write_row_at(99, &[CellValue::Float(21.5)]).unwrap();
+    /// writer.write_row_at(4999, &[CellValue::Float(22.25)]).unwrap();
+    /// // Rejected: row 100 was already passed
+    /// assert!(writer.write_row_at(99, &[CellValue::Float(0.0)]).is_err());
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_row_at(&mut self, row: u32, values: &[CellValue]) -> Result<()> {
+        let styled_cells: Vec<_> = values
+            .iter()
+            .map(|cell| crate::types::StyledCell::new(cell.clone(), cell.default_style()))
+            .collect();
+        self.inner.write_row_at(row, &styled_cells)?;
+        self.row_written();
+        Ok(())
+    }
+
+    /// Set one cell of the current sheet, at 0-based `row` and `col`
+    ///
+    /// For small sheets such as summaries, whose cells are easier to place
+    /// than to produce row by row. Cells can be set in any order (setting
+    /// a cell again replaces it) and are held in memory until the sheet is
+    /// finished by [`add_sheet`](Self::add_sheet) or [`save`](Self::save),
+    /// then written sorted.
+    ///
+    /// Rows streamed before the first `write_cell` stay as they are, and
+    /// cells can only go below them. Once a cell is set, streaming more rows
+    /// to the sheet is an error; large data belongs on its own sheet.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    /// use excelstream::types::{CellValue, CellStyle};
+    ///
+    /// let mut writer = ExcelWriter::new("report.xlsx").unwrap();
+    /// writer.write_header_bold(["Region", "Total"]).unwrap();
+    /// writer.write_cell(3, 0, CellValue::String("Total".into()), CellStyle::TextBold).unwrap();
+    /// writer.write_cell(1, 1, CellValue::Float(1200.5), CellStyle::NumberCurrency).unwrap();
+    /// writer.write_cell(1, 0, CellValue::String("North".into()), CellStyle::Default).unwrap();
+    /// writer.write_cell(3, 1, CellValue::formula("=SUM(B2:B3)"), CellStyle::NumberCurrency).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_cell(
+        &mut self,
+        row: u32,
+        col: u32,
+        value: CellValue,
+        style: CellStyle,
+    ) -> Result<()> {
+        self.inner
+            .write_cell(row, col, crate::types::StyledCell::new(value, style))
+    }
+
+    /// Write header row with bold formatting
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_header_bold(&["ID", "Name", "Email"]).unwrap();
+    /// writer.write_row(&["1", "Alice", "alice@example.com"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_header_bold<I, S>(&mut self, headers: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        use crate::types::CellStyle;
+
+        let cells: Vec<_> = headers
+            .into_iter()
+            .map(|h| {
+                (
+                    CellValue::String(h.as_ref().to_string()),
+                    CellStyle::HeaderBold,
+                )
+            })
+            .collect();
+        self.write_row_styled(&cells)
+    }
+
+    /// Write header row (without bold - for backward compatibility)
+    ///
+    /// **Note:** For bold headers, use `write_header_bold()` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_header(&["ID", "Name", "Email"]).unwrap();
+    /// writer.write_row(&["1", "Alice", "alice@example.com"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn write_header<I, S>(&mut self, headers: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.write_row(headers)
+    }
+
+    /// Choose what [`add_sheet`](Self::add_sheet) and
+    /// [`worksheet`](Self::worksheet) do with names Excel wouldn't accept
+    ///
+    /// By default ([`SheetNamePolicy::Strict`](crate::types::SheetNamePolicy::Strict))
+    /// they fail with [`ExcelError::InvalidSheetName`](crate::ExcelError::InvalidSheetName);
+    /// with `Sanitize` the name is fixed and a duplicate gets a ` (2)`
+    /// suffix. Use [`current_sheet_name`](Self::current_sheet_name) to get
+    /// the name a sheet ended up with.
+    pub fn set_sheet_name_policy(&mut self, policy: crate::types::SheetNamePolicy) {
+        self.inner.set_sheet_name_policy(policy)
+    }
+
+    /// Store dates in the 1904 date system of early Mac Excel, or back in
+    /// the default 1900 system
+    ///
+    /// [`CellValue::DateTime`] values keep their 1900-system serials, as
+    /// produced by the chrono conversions; they are re-based as they are
+    /// written, so the same dates show either way. Must be called before
+    /// the first row.
+    ///
+    /// ```no_run
+    /// use chrono::NaiveDate;
+    /// use excelstream::types::{CellValue, DateSystem};
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("mac.xlsx")?;
+    /// writer.set_date_system(DateSystem::V1904)?;
+    /// writer.write_row_typed(&[NaiveDate::from_ymd_opt(2024, 1, 15).unwrap().into()])?;
+    /// writer.save()?;
+    /// # Ok::<(), excelstream::ExcelError>(())
+    /// ```
+    pub fn set_date_system(&mut self, system: crate::types::DateSystem) -> Result<()> {
+        self.inner.set_date_system(system)
+    }
+
+    /// Choose how timestamps with a time zone become date cells
+    ///
+    /// Applies to [`timestamp`](Self::timestamp) and to RFC 3339 strings
+    #[cfg_attr(
+        feature = "serde",
+        doc = "written with [`write_record`](Self::write_record). The choice is"
+    )]
+    #[cfg_attr(
+        not(feature = "serde"),
+        doc = "written with `write_record`. The choice is"
+    )]
+    /// recorded in the custom document property `Timezone` (e.g. `UTC`),
+    /// so readers of the file can tell which clock the dates are on.
+    ///
+    /// ```no_run
+    /// use chrono::{FixedOffset, TimeZone, Utc};
+    /// use excelstream::types::TimezonePolicy;
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("logins.xlsx")?;
+    /// writer.set_timezone_policy(TimezonePolicy::Utc);
+    ///
+    /// let paris = FixedOffset::east_opt(3600).unwrap();
+    /// let hanoi = FixedOffset::east_opt(7 * 3600).unwrap();
+    /// let first = paris.with_ymd_and_hms(2024, 1, 15, 9, 0, 0).unwrap();
+    /// let second = hanoi.with_ymd_and_hms(2024, 1, 15, 15, 0, 0).unwrap();
+    /// // Both are 08:00 UTC
+    /// writer.write_row_typed(&[writer.timestamp(&first), writer.timestamp(&second)])?;
+    /// writer.save()?;
+    /// # Ok::<(), excelstream::ExcelError>(())
+    /// ```
+    pub fn set_timezone_policy(&mut self, policy: crate::types::TimezonePolicy) {
+        self.inner.set_timezone_policy(policy)
+    }
+
+    /// Date cell for `datetime` under the
+    /// [timezone policy](Self::set_timezone_policy)
+    pub fn timestamp<Tz: chrono::TimeZone>(&self, datetime: &chrono::DateTime<Tz>) -> CellValue {
+        self.inner.timezone_policy().cell(datetime)
+    }
+
+    /// Name of the sheet rows are currently written to
+    pub fn current_sheet_name(&self) -> &str {
+        &self.current_sheet_name
+    }
+
+    /// Continue on a new sheet once a sheet holds `max_rows_per_sheet` rows
+    ///
+    /// Rows past the limit go to `Data_2`, `Data_3`, ... for a sheet named
+    /// `Data`, each starting with a copy of the sheet's first row as its
+    /// header, so data larger than Excel's 1 048 576 rows per sheet still
+    /// opens. Without this, writing past that limit fails.
+    /// [`current_sheet_name`](Self::current_sheet_name) and
+    /// [`current_row`](Self::current_row) follow the rollover.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("events.xlsx").unwrap();
+    /// writer.set_auto_shard_rows(1_048_576).unwrap();
+    /// writer.write_header_bold(["id", "event"]).unwrap();
+    /// for id in 0..3_000_000u32 {
+    ///     writer.write_row_display([id.to_string(), "login".to_string()]).unwrap();
+    /// }
+    /// // Sheet1, Sheet1_2 and Sheet1_3
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_auto_shard_rows(&mut self, max_rows_per_sheet: u32) -> Result<()> {
+        self.inner.set_auto_shard_rows(max_rows_per_sheet)
+    }
+
+    /// Pick up the row number after a write, and the sheet if the workbook
+    /// rolled over to a new shard
+    fn row_written(&mut self) {
+        let row = self.inner.current_row();
+        if row <= self.current_row {
+            if let Some(name) = self.inner.current_sheet_name() {
+                self.current_sheet_name = name.to_string();
+            }
+        }
+        self.current_row = row;
+    }
+
+    /// Add a new sheet and switch to it
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_row(&["Data on Sheet1"]).unwrap();
+    ///
+    /// writer.add_sheet("Sheet2").unwrap();
+    /// writer.write_row(&["Data on Sheet2"]).unwrap();
+    ///
+    /// writer.save().unwrap();
+    /// ```
+    pub fn add_sheet(&mut self, name: &str) -> Result<()> {
+        self.inner.add_worksheet(name)?;
+        self.current_sheet_name = self.inner.current_sheet_name().unwrap_or(name).to_string();
+        self.current_row = 0;
+        Ok(())
+    }
+
+    /// Hide or unhide a sheet
+    ///
+    /// Hidden sheets still count for formulas and can be unhidden in
+    /// Excel. At least one sheet must stay visible, and the active sheet
+    /// can't be hidden.
+    pub fn set_sheet_hidden(&mut self, name: &str, hidden: bool) -> Result<()> {
+        self.inner.set_sheet_hidden(name, hidden)
+    }
+
+    /// Color a sheet's tab, e.g. `"FF0000"` or `"#3366FF"`
+    ///
+    /// Must be called before writing rows to that sheet (a buffered sheet
+    /// from [`worksheet`](Self::worksheet) can be colored any time before saving).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("tabs.xlsx").unwrap();
+    /// writer.set_tab_color("Sheet1", "#2E7D32").unwrap();
+    /// writer.write_row(["Data"]).unwrap();
+    ///
+    /// writer.add_sheet("Summary").unwrap();
+    /// writer.write_row(["Total"]).unwrap();
+    /// // Summary becomes the first tab and opens first
+    /// writer.move_sheet("Summary", 0).unwrap();
+    /// writer.set_active_sheet("Summary").unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_tab_color(&mut self, name: &str, color: &str) -> Result<()> {
+        self.inner.set_tab_color(name, color)
+    }
+
+    /// Move a sheet to tab position `position` (0-based)
+    ///
+    /// Only the tab order changes; sheets can be moved at any time before
+    /// saving, including ones already written.
+    pub fn move_sheet(&mut self, name: &str, position: usize) -> Result<()> {
+        self.inner.move_sheet(name, position)
+    }
+
+    /// Choose the sheet shown when the workbook is opened (default: the
+    /// first visible tab)
+    pub fn set_active_sheet(&mut self, name: &str) -> Result<()> {
+        self.inner.set_active_sheet(name)
+    }
+
+    /// Start a collapsible row group
+    ///
+    /// Rows written until the matching [`end_group`](#method.end_group) get
+    /// one more outline level; groups nest up to 7 levels. Rows inside a
+    /// group are held back (spilling to a temporary file past 1 MB) until the
+    /// outermost group ends, because collapsing hides rows already written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("statement.xlsx").unwrap();
+    /// writer.write_header_bold(["Account", "Amount"]).unwrap();
+    ///
+    /// writer.start_group().unwrap();
+    /// writer.write_row(["Salaries", "120000"]).unwrap();
+    /// writer.write_row(["Rent", "30000"]).unwrap();
+    /// writer.end_group(true).unwrap(); // collapsed
+    /// writer.write_row(["Operating expenses", "150000"]).unwrap();
+    ///
+    /// writer.save().unwrap();
+    /// ```
+    pub fn start_group(&mut self) -> Result<()> {
+        self.inner.start_row_group()
+    }
+
+    /// End the innermost row group, optionally collapsed
+    ///
+    /// With summary rows below the details (the default), the next row
+    /// written is the summary row that carries the group's +/- button.
+    pub fn end_group(&mut self, collapsed: bool) -> Result<()> {
+        self.inner.end_row_group(collapsed)
+    }
+
+    /// Group columns `first..=last` (0-based) under an outline button
+    ///
+    /// Overlapping groups nest. Must be called before writing rows to the
+    /// current sheet.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("budget.xlsx").unwrap();
+    /// // Monthly detail in B:M, collapsed behind the yearly total in N
+    /// writer.group_columns(1, 12, true).unwrap();
+    /// writer.write_row(["Item", "Jan", "Feb"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn group_columns(&mut self, first: u32, last: u32, collapsed: bool) -> Result<()> {
+        self.inner.group_columns(first, last, collapsed)
+    }
+
+    /// Choose whether summary rows sit below and summary columns to the
+    /// right of their groups
+    ///
+    /// Must be called before writing rows to the current sheet.
+    pub fn set_outline_settings(&mut self, settings: crate::types::OutlineSettings) -> Result<()> {
+        self.inner.set_outline_settings(settings)
+    }
+
+    /// Add a worksheet that can be written to while another sheet is in progress
+    ///
+    /// Rows written through the returned handle are buffered (spilling to a
+    /// temporary file once they pass 1 MB) and copied into the workbook on
+    /// save, so the sheet being streamed is not interrupted. The new sheet
+    /// keeps its place in the tab order. Typical use is an "Errors" sheet
+    /// collecting rejected rows alongside the main export.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("import.xlsx").unwrap();
+    /// let errors = writer.worksheet("Errors").unwrap();
+    /// writer.sheet(errors).write_header_bold(["Line", "Reason"]).unwrap();
+    ///
+    /// for (line, value) in ["10", "x", "30"].iter().enumerate() {
+    ///     match value.parse::<i64>() {
+    ///         Ok(_) => writer.write_row([value]).unwrap(),
+    ///         Err(e) => writer
+    ///             .sheet(errors)
+    ///             .write_row([(line + 1).to_string(), e.to_string()])
+    ///             .unwrap(),
+    ///     }
+    /// }
+    /// writer.save().unwrap();
+    /// ```
+    pub fn worksheet(&mut self, name: &str) -> Result<WorksheetHandle> {
+        let index = self.inner.add_buffered_worksheet(name)?;
+        Ok(WorksheetHandle { index })
+    }
+
+    /// Writer for a sheet added with [`worksheet`](#method.worksheet)
+    pub fn sheet(&mut self, handle: WorksheetHandle) -> SheetWriter<'_> {
+        SheetWriter {
+            inner: &mut self.inner,
+            index: handle.index,
+        }
+    }
+
+    /// Protect the current worksheet with options
+    ///
+    /// Protects the worksheet from editing. Users can still view and select cells
+    /// unless restricted. Call this after `add_sheet()` and before writing data.
+    ///
+    /// # Arguments
+    /// * `options` - Protection settings including password and permissions
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::{ExcelWriter, ProtectionOptions};
+    ///
+    /// let mut writer = ExcelWriter::new("protected.xlsx").unwrap();
+    ///
+    /// // Default sheet is already created, protect it
+    /// let protection = ProtectionOptions::new()
+    ///     .with_password("secret123")
+    ///     .allow_select_locked_cells(true)
+    ///     .allow_select_unlocked_cells(true);
+    ///
+    /// writer.protect_sheet(protection).unwrap();
+    /// writer.write_row(&["Protected", "Data"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn protect_sheet(&mut self, options: crate::types::ProtectionOptions) -> Result<()> {
+        self.inner.protect_sheet(options)
+    }
+
+    /// Set document properties (title, author, company, custom key/value
+    /// pairs) shown under File > Info in Excel
+    ///
+    /// Written to `docProps/core.xml`, `app.xml` and, when custom properties
+    /// are present, `custom.xml`. Can be called at any time before `save()`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::{DocumentProperties, ExcelWriter};
+    ///
+    /// let mut writer = ExcelWriter::new("report.xlsx").unwrap();
+    /// writer
+    ///     .set_properties(
+    ///         DocumentProperties::new()
+    ///             .title("Quarterly Report")
+    ///             .author("Finance Team")
+    ///             .company("Acme Corp")
+    ///             .custom("Department", "Sales")
+    ///             .custom("Approved", true),
+    ///     )
+    ///     .unwrap();
+    /// writer.write_row(["Region", "Revenue"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_properties(&mut self, properties: crate::types::DocumentProperties) -> Result<()> {
+        self.inner.set_properties(properties)
+    }
+
+    /// Use one formula for a whole block of the current worksheet
+    ///
+    /// `formula` is written for the top-left cell of `range`, and Excel
+    /// shifts its relative references for the other cells, like a fill-down.
+    /// Cells of the block written as empty (`""` or `CellValue::Empty`) get
+    /// the formula; the formula text is stored only once, which keeps large
+    /// files noticeably smaller. Must be called before the block's first row
+    /// is written.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("totals.xlsx").unwrap();
+    /// writer.write_header_bold(["Net", "Tax", "Gross"]).unwrap();
+    /// writer.add_shared_formula("C2:C1001", "=A2+B2").unwrap();
+    /// for i in 0..1000 {
+    ///     writer.write_row([i.to_string(), (i / 5).to_string(), String::new()]).unwrap();
+    /// }
+    /// writer.save().unwrap();
+    /// ```
+    pub fn add_shared_formula(&mut self, range: &str, formula: &str) -> Result<()> {
+        self.inner.add_shared_formula(range, formula)
+    }
+
+    /// Format `range` of the current worksheet as an Excel table
+    ///
+    /// The table gets a header row with filter buttons, banded styling and a
+    /// name usable in structured references such as `Sales[Amount]`. The
+    /// first row of `range` is the header row, and `options.column_names`
+    /// should repeat its cells. The range may include rows that haven't been
+    /// written yet; the table part is written when the sheet is finished.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::{ExcelWriter, TableOptions};
+    ///
+    /// let mut writer = ExcelWriter::new("sales.xlsx").unwrap();
+    /// writer
+    ///     .add_table(
+    ///         "A1:B3",
+    ///         TableOptions::new().name("Sales").column_names(["Region", "Amount"]),
+    ///     )
+    ///     .unwrap();
+    /// writer.write_row(["Region", "Amount"]).unwrap();
+    /// writer.write_row(["North", "1200"]).unwrap();
+    /// writer.write_row(["South", "900"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn add_table(&mut self, range: &str, options: crate::types::TableOptions) -> Result<()> {
+        self.inner.add_table(range, options)
+    }
+
+    /// Add a chart to the current worksheet
+    ///
+    /// `data_range` is a block whose first column holds the categories, whose
+    /// first row holds the series names and whose other columns each become a
+    /// series. It may point at another sheet (`"Data!A1:C13"`); without a
+    /// sheet name it refers to the current one. The chart's top-left corner
+    /// is placed at `anchor_cell`.
+    ///
+    /// Charts only reference their data, so Excel draws them from the cell
+    /// values when the file is opened.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::{ChartOptions, ChartType, ExcelWriter};
+    ///
+    /// let mut writer = ExcelWriter::new("report.xlsx").unwrap();
+    /// writer.write_header_bold(["Month", "Revenue", "Cost"]).unwrap();
+    /// writer.write_row(["Jan", "1200", "800"]).unwrap();
+    /// writer.write_row(["Feb", "1500", "900"]).unwrap();
+    /// writer
+    ///     .add_chart(
+    ///         ChartType::Column,
+    ///         "A1:C3",
+    ///         "E2",
+    ///         ChartOptions::new().title("Revenue vs cost").y_axis_title("USD"),
+    ///     )
+    ///     .unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn add_chart(
+        &mut self,
+        chart_type: crate::types::ChartType,
+        data_range: &str,
+        anchor_cell: &str,
+        options: crate::types::ChartOptions,
+    ) -> Result<()> {
+        self.inner
+            .add_chart(chart_type, data_range, anchor_cell, options)
+    }
+
+    /// Add a conditional formatting rule to a range of the current worksheet
+    ///
+    /// Supports `cellIs` comparisons and formula expressions with a
+    /// [`ConditionalStyle`](crate::ConditionalStyle), color scales, data bars
+    /// and icon sets. When several rules cover the same cell, the one added
+    /// first takes priority.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::{ComparisonOperator, ConditionalFormat, ConditionalStyle, ExcelWriter};
+    ///
+    /// let mut writer = ExcelWriter::new("kpis.xlsx").unwrap();
+    /// writer.write_header_bold(["Region", "Growth", "Revenue"]).unwrap();
+    /// writer
+    ///     .add_conditional_format(
+    ///         "B2:B100",
+    ///         ConditionalFormat::cell_is(ComparisonOperator::LessThan, 0, ConditionalStyle::red()),
+    ///     )
+    ///     .unwrap();
+    /// writer
+    ///     .add_conditional_format(
+    ///         "B2:B100",
+    ///         ConditionalFormat::cell_is(
+    ///             ComparisonOperator::GreaterThanOrEqual,
+    ///             0,
+    ///             ConditionalStyle::green(),
+    ///         ),
+    ///     )
+    ///     .unwrap();
+    /// writer
+    ///     .add_conditional_format("C2:C100", ConditionalFormat::data_bar("638EC6"))
+    ///     .unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn add_conditional_format(
+        &mut self,
+        range: &str,
+        format: crate::types::ConditionalFormat,
+    ) -> Result<()> {
+        self.inner.add_conditional_format(range, format)
+    }
+
+    /// Add a data validation rule to a range of the current worksheet
+    ///
+    /// Constrains what users can enter in `range` (e.g. `"C2:C500"`), most
+    /// commonly with a dropdown list. Several ranges can be given separated
+    /// by spaces. Rules apply to the current sheet and may be added before
+    /// or after its rows are written.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::{DataValidation, ExcelWriter};
+    ///
+    /// let mut writer = ExcelWriter::new("template.xlsx").unwrap();
+    /// writer.write_header_bold(["Task", "Status", "Hours"]).unwrap();
+    /// writer
+    ///     .add_data_validation("B2:B1000", DataValidation::list(["Open", "Done"]))
+    ///     .unwrap();
+    /// writer
+    ///     .add_data_validation("C2:C1000", DataValidation::decimal(0.0, 24.0))
+    ///     .unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn add_data_validation(
+        &mut self,
+        range: &str,
+        validation: crate::types::DataValidation,
+    ) -> Result<()> {
+        self.inner.add_data_validation(range, validation)
+    }
+
+    /// Set flush interval (rows between disk flushes)
+    ///
+    /// Default is 1000 rows. Lower values use less memory but slower.
+    /// Higher values are faster but use more memory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.set_flush_interval(500); // Flush every 500 rows
+    /// ```
+    pub fn set_flush_interval(&mut self, interval: u32) {
+        self.inner.set_flush_interval(interval);
+    }
+
+    /// Set maximum buffer size before forcing a flush
+    ///
+    /// Default is 1MB. This ensures memory usage stays bounded.
+    pub fn set_max_buffer_size(&mut self, size: usize) {
+        self.inner.set_max_buffer_size(size);
+    }
+
+    /// Report progress while writing
+    ///
+    /// `callback` receives `(rows_written, bytes_written)` every
+    /// [`PROGRESS_INTERVAL`](crate::progress::PROGRESS_INTERVAL) rows and
+    /// once more from `save()`. Bytes are counted before compression.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("export.xlsx").unwrap();
+    /// writer.set_progress_callback(|rows, _bytes| eprintln!("{} rows written", rows));
+    /// for i in 0..100_000 {
+    ///     writer.write_row([i.to_string()]).unwrap();
+    /// }
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(u64, u64) + Send + 'static,
+    {
+        self.inner.set_progress_callback(Box::new(callback));
+    }
+
+    /// Report rows, bytes, shared strings and flushes to `metrics`
+    ///
+    /// See [`WriterMetrics`](crate::progress::WriterMetrics) for when its
+    /// methods are called.
+    pub fn set_metrics<M>(&mut self, metrics: M)
+    where
+        M: crate::progress::WriterMetrics + 'static,
+    {
+        self.inner.set_metrics(Box::new(metrics));
+    }
+
+    /// Abort the export when `token` is cancelled
+    ///
+    /// Row writes and `save()` then return
+    /// [`ExcelError::Cancelled`](crate::error::ExcelError::Cancelled). The
+    /// file on disk is left incomplete.
+    pub fn set_cancellation_token(&mut self, token: crate::progress::CancellationToken) {
+        self.inner.set_cancellation_token(token);
+    }
+
+    /// Save and finalize the workbook
+    ///
+    /// This closes the ZIP file and ensures all data is written to disk.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use excelstream::writer::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("output.xlsx").unwrap();
+    /// writer.write_row(&["Data"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn save(self) -> Result<()> {
+        self.save_with_report().map(|_| ())
+    }
+
+    /// Compute a checksum of the output file while it is written
+    ///
+    /// The digest is returned by [`save_with_report`](Self::save_with_report).
+    /// For password-protected workbooks it covers the encrypted file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::{Checksum, ExcelWriter};
+    ///
+    /// let mut writer = ExcelWriter::new("audit.xlsx").unwrap();
+    /// writer.set_checksum(Checksum::Sha256);
+    /// writer.write_row(["ID", "Amount"]).unwrap();
+    /// let report = writer.save_with_report().unwrap();
+    /// println!("{} bytes, sha256 {}", report.bytes_written, report.digest.unwrap());
+    /// ```
+    pub fn set_checksum(&mut self, checksum: crate::report::Checksum) {
+        #[cfg(feature = "encryption")]
+        if let Some(pending) = &mut self.encryption {
+            pending.set_checksum(checksum);
+            return;
+        }
+        self.inner.set_checksum(checksum);
+    }
+
+    /// Save the workbook and report the file size and checksum
+    ///
+    /// Same as [`save`](Self::save), but returns a [`WriteReport`](crate::report::WriteReport).
+    /// `digest` is `None` unless [`set_checksum`](Self::set_checksum) was called.
+    pub fn save_with_report(self) -> Result<crate::report::WriteReport> {
+        #[cfg(feature = "encryption")]
+        if let Some(pending) = self.encryption {
+            return match self.inner.close() {
+                Ok(()) => pending.finish(),
+                Err(e) => {
+                    pending.discard();
+                    Err(e)
+                }
+            };
+        }
+
+        self.inner.close_with_report()
+    }
+
+    /// Writer whose workbook is built in memory, for
+    /// [`ZipBundleWriter`](crate::bundle::ZipBundleWriter)
+    pub(crate) fn in_memory(compression_level: u32) -> Result<Self> {
+        let mut inner = UltraLowMemoryWorkbook::in_memory(compression_level)?;
+        inner.add_worksheet("Sheet1")?;
+
+        Ok(ExcelWriter {
+            inner,
+            current_sheet_name: "Sheet1".to_string(),
+            current_row: 0,
+            #[cfg(feature = "encryption")]
+            encryption: None,
+        })
+    }
+
+    /// Finish an [`in_memory`](Self::in_memory) workbook and return the file
+    pub(crate) fn into_bytes(self) -> Result<Vec<u8>> {
+        self.inner.close_into_bytes()
+    }
+
+    /// Get current row number (0-based)
+    pub fn current_row(&self) -> u32 {
+        self.current_row
+    }
+}
+
+/// Identifies a worksheet added with [`ExcelWriter::worksheet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorksheetHandle {
+    index: usize,
+}
+
+/// Row writer for a buffered worksheet, returned by [`ExcelWriter::sheet`]
+///
+/// Hyperlink cells are not supported on buffered worksheets.
+pub struct SheetWriter<'a> {
+    inner: &'a mut UltraLowMemoryWorkbook,
+    index: usize,
+}
+
+impl SheetWriter<'_> {
+    /// Write a row of strings
+    pub fn write_row<I, S>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.inner
+            .with_buffered_worksheet(self.index, |wb| wb.write_row(data))
+    }
+
+    /// Write a row of `Display` values as inline strings
+    pub fn write_row_display<I>(&mut self, data: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.inner
+            .with_buffered_worksheet(self.index, |wb| wb.write_row_display(data))
+    }
+
+    /// Write a row with typed cell values
+    pub fn write_row_typed(&mut self, cells: &[CellValue]) -> Result<()> {
+        self.write_row_typed_iter(cells)
+    }
+
+    /// Write a row of typed values from an iterator of owned or borrowed
+    /// cells
+    pub fn write_row_typed_iter<I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CellValue>,
+    {
+        self.inner
+            .with_buffered_worksheet(self.index, |wb| wb.write_row_typed_iter(cells))
+    }
+
+    /// Write a row of borrowed cells, see
+    /// [`ExcelWriter::write_row_refs`]
+    pub fn write_row_refs<'a, I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Into<CellRef<'a>>,
+    {
+        self.inner
+            .with_buffered_worksheet(self.index, |wb| wb.write_row_refs(cells))
+    }
+
+    /// Write a batch of equally long columns, see
+    /// [`ExcelWriter::write_columns`]
+    pub fn write_columns(&mut self, columns: &[ColumnSlice]) -> Result<usize> {
+        self.inner
+            .with_buffered_worksheet(self.index, |wb| wb.write_columns(columns))
+    }
+
+    /// Write a row with styled cells
+    pub fn write_row_styled(&mut self, cells: &[(CellValue, CellStyle)]) -> Result<()> {
+        let styled_cells: Vec<_> = cells
+            .iter()
+            .map(|(value, style)| crate::types::StyledCell::new(value.clone(), *style))
+            .collect();
+        self.inner
+            .with_buffered_worksheet(self.index, |wb| wb.write_row_styled(&styled_cells))
+    }
+
+    /// Write a header row in bold
+    pub fn write_header_bold<I, S>(&mut self, headers: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let cells: Vec<_> = headers
+            .into_iter()
+            .map(|h| {
+                (
+                    CellValue::String(h.as_ref().to_string()),
+                    CellStyle::HeaderBold,
+                )
+            })
+            .collect();
+        self.write_row_styled(&cells)
+    }
+}
+
+/// Builder for creating configured Excel writers
+pub struct ExcelWriterBuilder {
+    path: String,
+    default_sheet_name: Option<String>,
+    flush_interval: Option<u32>,
+    max_buffer_size: Option<usize>,
+    sheet_name_policy: crate::types::SheetNamePolicy,
+}
+
+impl ExcelWriterBuilder {
+    /// Create a new builder
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        ExcelWriterBuilder {
+            path: path.as_ref().to_string_lossy().to_string(),
+            default_sheet_name: None,
+            flush_interval: None,
+            max_buffer_size: None,
+            sheet_name_policy: crate::types::SheetNamePolicy::Strict,
+        }
+    }
+
+    /// Set the default sheet name
+    pub fn with_sheet_name(mut self, name: &str) -> Self {
+        self.default_sheet_name = Some(name.to_string());
+        self
+    }
+
+    /// Set how invalid or duplicate sheet names are handled, see
+    /// [`ExcelWriter::set_sheet_name_policy`]
+    pub fn with_sheet_name_policy(mut self, policy: crate::types::SheetNamePolicy) -> Self {
+        self.sheet_name_policy = policy;
+        self
+    }
+
+    /// Set flush interval (rows between disk flushes)
+    pub fn with_flush_interval(mut self, interval: u32) -> Self {
+        self.flush_interval = Some(interval);
+        self
+    }
+
+    /// Set maximum buffer size
+    pub fn with_max_buffer_size(mut self, size: usize) -> Self {
+        self.max_buffer_size = Some(size);
+        self
+    }
+
+    /// Build the writer
+    pub fn build(self) -> Result<ExcelWriter> {
+        let mut inner = UltraLowMemoryWorkbook::new(&self.path)?;
+        inner.set_sheet_name_policy(self.sheet_name_policy);
+
+        let sheet_name = self
+            .default_sheet_name
+            .unwrap_or_else(|| "Sheet1".to_string());
+        inner.add_worksheet(&sheet_name)?;
+
+        let mut writer = ExcelWriter {
+            current_sheet_name: inner.current_sheet_name().unwrap_or("Sheet1").to_string(),
+            inner,
+            current_row: 0,
+            #[cfg(feature = "encryption")]
+            encryption: None,
+        };
+
+        if let Some(interval) = self.flush_interval {
+            writer.set_flush_interval(interval);
+        }
+
+        if let Some(size) = self.max_buffer_size {
+            writer.set_max_buffer_size(size);
+        }
+
+        Ok(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_writer_creation() {
+        let temp = NamedTempFile::new().unwrap();
+        let writer = ExcelWriter::new(temp.path());
+        assert!(writer.is_ok());
+
+        // Should be able to save immediately
+        let writer = writer.unwrap();
+        assert!(writer.save().is_ok());
+    }
+
+    #[test]
+    fn test_write_row_display() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+
+        assert!(writer.write_row_display([1, 2, 3]).is_ok());
+        assert!(writer.write_row_display([1.5, 2.5]).is_ok());
+        assert_eq!(writer.current_row(), 2);
+
+        assert!(writer.save().is_ok());
+    }
+
+    #[test]
+    fn test_write_row() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+
+        assert!(writer.write_row(["A", "B", "C"]).is_ok());
+        assert!(writer.write_row(["1", "2", "3"]).is_ok());
+        assert_eq!(writer.current_row(), 2);
+
+        assert!(writer.save().is_ok());
+    }
+
+    #[test]
+    fn test_write_row_typed() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+
+        use crate::types::CellValue;
+        let row = vec![
+            CellValue::String("Text".to_string()),
+            CellValue::Int(42),
+            CellValue::Float(1234.56),
+            CellValue::Bool(true),
+        ];
+
+        assert!(writer.write_row_typed(&row).is_ok());
+        assert_eq!(writer.current_row(), 1);
+
+        assert!(writer.save().is_ok());
+    }
+
+    #[test]
+    fn test_builder() {
+        let temp = NamedTempFile::new().unwrap();
+        let writer = ExcelWriterBuilder::new(temp.path())
+            .with_sheet_name("CustomSheet")
+            .with_flush_interval(500)
+            .with_max_buffer_size(512 * 1024)
+            .build();
+
+        assert!(writer.is_ok());
+        let writer = writer.unwrap();
+        assert_eq!(writer.current_sheet_name, "CustomSheet");
+        assert!(writer.save().is_ok());
+    }
+
+    #[test]
+    fn test_add_sheet() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+
+        writer.write_row(["Sheet1 Data"]).unwrap();
+        assert_eq!(writer.current_row(), 1);
+
+        writer.add_sheet("Sheet2").unwrap();
+        assert_eq!(writer.current_row(), 0);
+        assert_eq!(writer.current_sheet_name, "Sheet2");
+
+        writer.write_row(["Sheet2 Data"]).unwrap();
+        assert_eq!(writer.current_row(), 1);
+
+        assert!(writer.save().is_ok());
+    }
+
+    #[test]
+    fn test_write_header() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+
+        writer.write_header(["ID", "Name", "Email"]).unwrap();
+        writer
+            .write_row(["1", "Alice", "alice@example.com"])
+            .unwrap();
+
+        assert_eq!(writer.current_row(), 2);
+        assert!(writer.save().is_ok());
+    }
+
+    #[test]
+    fn test_batch_write() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+
+        let data = vec![
+            vec!["A1", "B1", "C1"],
+            vec!["A2", "B2", "C2"],
+            vec!["A3", "B3", "C3"],
+        ];
+
+        writer.write_rows_batch(&data).unwrap();
+        assert_eq!(writer.current_row(), 3);
+
+        assert!(writer.save().is_ok());
+    }
+
+    #[test]
+    fn test_sheet_name_policy() {
+        use crate::types::SheetNamePolicy;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        let err = writer.add_sheet("sheet1").unwrap_err();
+        assert!(matches!(err, crate::ExcelError::InvalidSheetName { .. }));
+        assert!(writer.add_sheet("Q1/Q2").is_err());
+        assert!(writer.worksheet("SHEET1").is_err());
+        assert_eq!(writer.current_sheet_name(), "Sheet1");
+
+        writer.set_sheet_name_policy(SheetNamePolicy::Sanitize);
+        writer.add_sheet("Q1/Q2").unwrap();
+        assert_eq!(writer.current_sheet_name(), "Q1_Q2");
+        writer.add_sheet("q1_q2").unwrap();
+        assert_eq!(writer.current_sheet_name(), "q1_q2 (2)");
+        writer.worksheet("sheet1").unwrap();
+        writer.save().unwrap();
+
+        let reader = crate::streaming_reader::StreamingReader::open(temp.path()).unwrap();
+        assert_eq!(
+            reader.sheet_names(),
+            ["Sheet1", "Q1_Q2", "q1_q2 (2)", "sheet1 (2)"]
+        );
+
+        let builder_err = ExcelWriterBuilder::new(temp.path())
+            .with_sheet_name("Report: 2024")
+            .build();
+        assert!(builder_err.is_err());
+        let writer = ExcelWriterBuilder::new(temp.path())
+            .with_sheet_name("Report: 2024")
+            .with_sheet_name_policy(SheetNamePolicy::Sanitize)
+            .build()
+            .unwrap();
+        assert_eq!(writer.current_sheet_name(), "Report_ 2024");
+    }
+
+    /// Panics unless `xml` is well-formed: balanced tags, quoted attributes
+    /// without `<`, only known entities and no characters XML forbids
+    fn assert_well_formed(part: &str, xml: &str) {
+        let fail = |what: &str, at: usize| -> ! {
+            panic!("{}: {} at byte {}: {:?}", part, what, at, &xml[at..])
+        };
+        let entity_ok = |rest: &str| {
+            let Some(end) = rest.find(';') else {
+                return false;
+            };
+            matches!(&rest[1..end], "lt" | "gt" | "amp" | "quot" | "apos")
+                || rest[1..end].starts_with('#')
+        };
+        if let Some(at) = xml.find(|c| !crate::fast_writer::xml_writer::is_xml_char(c)) {
+            fail("forbidden character", at);
+        }
+
+        let mut open: Vec<&str> = Vec::new();
+        let mut i = 0;
+        while let Some(lt) = xml[i..].find('<').map(|at| i + at) {
+            if let Some(amp) = xml[i..lt].find('&') {
+                if !entity_ok(&xml[i + amp..]) {
+                    fail("bad entity", i + amp);
+                }
+            }
+            if xml[lt..].starts_with("<?") {
+                i = lt + xml[lt..].find("?>").unwrap_or_else(|| fail("open <?", lt)) + 2;
+                continue;
+            }
+            // Find the end of the tag, skipping over quoted attribute values
+            let mut quoted = false;
+            let mut end = None;
+            for (at, c) in xml[lt + 1..].char_indices() {
+                let at = lt + 1 + at;
+                match c {
+                    '"' => quoted = !quoted,
+                    '<' => fail("'<' in a tag", at),
+                    '&' if quoted && !entity_ok(&xml[at..]) => fail("bad entity", at),
+                    '>' if !quoted => {
+                        end = Some(at);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            let end = end.unwrap_or_else(|| fail("unterminated tag", lt));
+            let tag = &xml[lt + 1..end];
+            let name = tag
+                .trim_start_matches('/')
+                .split([' ', '\n', '/'])
+                .next()
+                .unwrap();
+            if tag.starts_with('/') {
+                if open.pop() != Some(name) {
+                    fail("mismatched end tag", lt);
+                }
+            } else if !tag.ends_with('/') {
+                open.push(name);
+            }
+            i = end + 1;
+        }
+        assert!(open.is_empty(), "{}: unclosed {:?}", part, open);
+    }
+
+    #[test]
+    fn test_hostile_names_and_properties() {
+        use crate::types::{DocumentProperties, SheetNamePolicy};
+
+        const PIECES: &[&str] = &[
+            "&", "<", ">", "\"", "'", ";", "&amp;", "&lt;", "&#65;", "]]>", "<!--", "=", "a", "Q1",
+            " ", "é", "Ä", "日本", "😀", "\t", "\n", "\u{1}", "\u{b}", "\u{fffe}", "/", "[", "*",
+            "?",
+        ];
+        // xorshift64*, fixed seed so failures reproduce
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = move |bound: usize| {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            (state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 33) as usize % bound
+        };
+        let mut hostile = |max_pieces: usize| -> String {
+            (0..1 + next(max_pieces))
+                .map(|_| PIECES[next(PIECES.len())])
+                .collect()
+        };
+
+        for _ in 0..40 {
+            let temp = NamedTempFile::new().unwrap();
+            let mut writer = ExcelWriter::new(temp.path()).unwrap();
+            writer.set_sheet_name_policy(SheetNamePolicy::Sanitize);
+            let mut names = vec!["Sheet1".to_string()];
+            for _ in 0..3 {
+                writer.add_sheet(&hostile(12)).unwrap();
+                writer.write_row([hostile(6), hostile(6)]).unwrap();
+                names.push(writer.current_sheet_name().to_string());
+            }
+            writer
+                .set_properties(
+                    DocumentProperties::new()
+                        .title(&hostile(8))
+                        .author(&hostile(8))
+                        .company(&hostile(8))
+                        .custom(&format!("p{}", hostile(4)), hostile(8)),
+                )
+                .unwrap();
+            writer.save().unwrap();
+
+            let mut zip = crate::fast_writer::StreamingZipReader::open(temp.path()).unwrap();
+            for part in [
+                "xl/workbook.xml",
+                "xl/worksheets/sheet2.xml",
+                "docProps/core.xml",
+                "docProps/app.xml",
+                "docProps/custom.xml",
+            ] {
+                let xml = String::from_utf8(zip.read_entry_by_name(part).unwrap()).unwrap();
+                assert_well_formed(part, &xml);
+            }
+
+            let report = crate::validate::validate_package(temp.path()).unwrap();
+            assert!(report.is_valid(), "{}", report);
+            let reader = crate::streaming_reader::StreamingReader::open(temp.path()).unwrap();
+            assert_eq!(reader.sheet_names(), names);
+        }
+    }
+
+    #[test]
+    fn test_formula_support() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+
+        use crate::types::CellValue;
+
+        // Write header
+        writer.write_header(["Value 1", "Value 2", "Sum"]).unwrap();
+
+        // Write data with formula
+        writer
+            .write_row_typed(&[
+                CellValue::Int(10),
+                CellValue::Int(20),
+                CellValue::formula("=A2+B2"),
+            ])
+            .unwrap();
+
+        writer
+            .write_row_typed(&[
+                CellValue::Int(15),
+                CellValue::Int(25),
+                CellValue::formula("=A3+B3"),
+            ])
+            .unwrap();
+
+        // Add a summary row with SUM formula
+        writer
+            .write_row_typed(&[
+                CellValue::String("Total".to_string()),
+                CellValue::Empty,
+                CellValue::formula("=SUM(C2:C3)"),
+            ])
+            .unwrap();
+
+        assert_eq!(writer.current_row(), 4);
+        assert!(writer.save().is_ok());
+    }
+
+    #[test]
+    fn test_hyperlinks() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::CellValue;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_header(["Finding", "Evidence"]).unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::String("Open port".to_string()),
+                CellValue::hyperlink("https://example.com/scan?id=1&full=true", "Scan 1"),
+            ])
+            .unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::String("See summary".to_string()),
+                CellValue::hyperlink("#'Summary'!A1", "Summary"),
+            ])
+            .unwrap();
+        writer.add_sheet("Summary").unwrap();
+        writer.write_row(["No links here"]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(
+            r#"<hyperlinks><hyperlink ref="B2" r:id="rId1"/><hyperlink ref="B3" location="&apos;Summary&apos;!A1"/></hyperlinks>"#
+        ));
+        assert!(sheet.contains(r#"<c r="B2" s="15" t="inlineStr"><is><t>Scan 1</t></is></c>"#));
+
+        let rels = String::from_utf8(
+            zip.read_entry_by_name("xl/worksheets/_rels/sheet1.xml.rels")
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(rels.contains(
+            r#"Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/scan?id=1&amp;full=true" TargetMode="External""#
+        ));
+        assert!(zip
+            .read_entry_by_name("xl/worksheets/_rels/sheet2.xml.rels")
+            .is_err());
+    }
+
+    #[test]
+    fn test_hyperlink_too_long() {
+        use crate::types::CellValue;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        let url = format!("https://example.com/{}", "a".repeat(2100));
+        assert!(writer
+            .write_row_typed(&[CellValue::hyperlink(url, "too long")])
+            .is_err());
+    }
+
+    #[test]
+    fn test_failed_row_drops_its_hyperlinks() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::CellValue;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        let url = format!("https://example.com/{}", "a".repeat(2100));
+        assert!(writer
+            .write_row_typed(&[
+                CellValue::hyperlink("https://example.com/ok", "ok"),
+                CellValue::hyperlink(url, "too long"),
+            ])
+            .is_err());
+        writer.write_row(["plain"]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(r#"<c r="A1" t="inlineStr"><is><t>plain</t></is></c>"#));
+        assert!(!sheet.contains("<hyperlinks>"));
+        assert!(zip
+            .read_entry_by_name("xl/worksheets/_rels/sheet1.xml.rels")
+            .is_err());
+    }
+
+    #[test]
+    fn test_outline_groups() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::OutlineSettings;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.group_columns(1, 3, true).unwrap();
+        writer.group_columns(2, 2, false).unwrap();
+        writer.write_row(["Statement"]).unwrap();
+        assert!(writer.group_columns(5, 6, false).is_err());
+        assert!(writer.end_group(false).is_err());
+
+        writer.start_group().unwrap();
+        writer.write_row(["Salaries"]).unwrap();
+        writer.start_group().unwrap();
+        writer.write_row(["Bonus"]).unwrap();
+        writer.end_group(true).unwrap();
+        writer.write_row(["Salaries total"]).unwrap();
+        writer.end_group(false).unwrap();
+        writer.write_row(["Total"]).unwrap();
+
+        writer.add_sheet("Above").unwrap();
+        writer
+            .set_outline_settings(OutlineSettings {
+                summary_below: false,
+                ..Default::default()
+            })
+            .unwrap();
+        writer.write_row(["Total"]).unwrap();
+        writer.start_group().unwrap();
+        writer.write_row(["Detail"]).unwrap();
+        writer.end_group(true).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(
+            r#"<sheetFormatPr defaultRowHeight="15" outlineLevelCol="2"/><cols><col min="2" max="2" width="9.140625" hidden="1" outlineLevel="1"/><col min="3" max="3" width="9.140625" hidden="1" outlineLevel="2"/><col min="4" max="4" width="9.140625" hidden="1" outlineLevel="1"/><col min="5" max="5" width="9.140625" collapsed="1"/></cols><sheetData>"#
+        ));
+        assert!(sheet.contains(r#"<row r="1"><c r="A1""#));
+        assert!(sheet.contains(r#"<row r="2" outlineLevel="1"><c"#));
+        assert!(sheet.contains(r#"<row r="3" hidden="1" outlineLevel="2"><c"#));
+        assert!(sheet.contains(r#"<row r="4" outlineLevel="1" collapsed="1"><c"#));
+        assert!(sheet.contains(r#"<row r="5"><c"#));
+
+        let above =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet2.xml").unwrap()).unwrap();
+        assert!(above.contains(r#"<sheetPr><outlinePr summaryBelow="0"/></sheetPr><sheetData>"#));
+        assert!(above.contains(r#"<row r="2" hidden="1" outlineLevel="1"><c"#));
+    }
+
+    #[test]
+    fn test_formula_results_and_shared_formulas() {
+        use crate::fast_writer::StreamingZipReader;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_row(["Net", "Tax", "Gross"]).unwrap();
+        writer.add_shared_formula("C2:C4", "=A2+B2").unwrap();
+        assert!(writer.add_shared_formula("C4:D5", "=1").is_err());
+        assert!(writer.add_shared_formula("A1:A1", "=1").is_err());
+        writer.write_row(["10", "2", ""]).unwrap();
+        writer
+            .write_row_typed(&[CellValue::Int(20), CellValue::Int(4), CellValue::Empty])
+            .unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::Int(30),
+                CellValue::Int(6),
+                CellValue::formula_with_result("=A4*1.2", CellValue::Float(36.5)),
+                CellValue::formula_with_result("=IF(A4>1,\"big\",\"small\")", "big".into()),
+                CellValue::formula_with_result("=A4>1", CellValue::Bool(true)),
+            ])
+            .unwrap();
+        writer.write_row(["x", "y", ""]).unwrap();
+        writer.add_shared_formula("B6:C7", "=A6").unwrap();
+        writer.write_row(["5", "", ""]).unwrap();
+        writer.write_row(["6", "", ""]).unwrap();
+        // The top-left cell must be left empty for the formula
+        writer.add_shared_formula("B8:B9", "=A8").unwrap();
+        writer.write_row(["7", "manual"]).unwrap();
+        assert!(writer.write_row(["8", ""]).is_err());
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(r#"<c r="C2"><f t="shared" ref="C2:C4" si="0">A2+B2</f></c>"#));
+        assert!(sheet.contains(r#"<c r="C3"><f t="shared" si="0"/></c>"#));
+        assert!(sheet.contains(r#"<c r="C4"><f>A4*1.2</f><v>36.5</v></c>"#));
+        assert!(sheet.contains(
+            r#"<c r="D4" t="str"><f>IF(A4&gt;1,&quot;big&quot;,&quot;small&quot;)</f><v>big</v></c>"#
+        ));
+        assert!(sheet.contains(r#"<c r="E4" t="b"><f>A4&gt;1</f><v>1</v></c>"#));
+        assert!(sheet.contains(r#"<c r="B6"><f t="shared" ref="B6:C7" si="1">A6</f></c>"#));
+        assert!(sheet.contains(r#"<c r="C7"><f t="shared" si="1"/></c>"#));
+        assert!(sheet.contains(r#"<c r="B8" t="inlineStr"><is><t>manual</t>"#));
+    }
+
+    #[test]
+    fn test_tables() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::TableOptions;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer
+            .add_table(
+                "A1:B4",
+                TableOptions::new()
+                    .name("Sales")
+                    .column_names(["Region", "A & B"])
+                    .totals_row(true),
+            )
+            .unwrap();
+        assert!(writer
+            .add_table("B2:C5", TableOptions::new().name("Other"))
+            .is_err());
+        assert!(writer
+            .add_table("D1:E3", TableOptions::new().name("sales"))
+            .is_err());
+        assert!(writer
+            .add_table("D1:E3", TableOptions::new().column_names(["Only one"]))
+            .is_err());
+        assert!(writer.add_table("D1:E1", TableOptions::new()).is_err());
+        writer.add_table("D1:E3", TableOptions::new()).unwrap();
+        writer.write_row(["Region", "A & B"]).unwrap();
+        writer.add_sheet("Second").unwrap();
+        writer
+            .add_table("A1:A2", TableOptions::new().style("TableStyleLight1"))
+            .unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.ends_with(
+            r#"<tableParts count="2"><tablePart r:id="rId1"/><tablePart r:id="rId2"/></tableParts></worksheet>"#
+        ));
+        let rels = String::from_utf8(
+            zip.read_entry_by_name("xl/worksheets/_rels/sheet1.xml.rels")
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(rels.contains(r#"Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/table" Target="../tables/table2.xml""#));
+
+        let sales =
+            String::from_utf8(zip.read_entry_by_name("xl/tables/table1.xml").unwrap()).unwrap();
+        assert!(sales.contains(
+            r#"id="1" name="Sales" displayName="Sales" ref="A1:B4" totalsRowCount="1"><autoFilter ref="A1:B3"/>"#
+        ));
+        assert!(sales.contains(r#"<tableColumn id="1" name="Region" totalsRowLabel="Total"/><tableColumn id="2" name="A &amp; B" totalsRowFunction="sum"/>"#));
+        assert!(sales.contains(r#"<tableStyleInfo name="TableStyleMedium2" showFirstColumn="0" showLastColumn="0" showRowStripes="1" showColumnStripes="0"/>"#));
+
+        let generated =
+            String::from_utf8(zip.read_entry_by_name("xl/tables/table2.xml").unwrap()).unwrap();
+        assert!(generated.contains(r#"name="Table2""#));
+        assert!(generated.contains(r#"<tableColumn id="2" name="Column2"/>"#));
+        let second =
+            String::from_utf8(zip.read_entry_by_name("xl/tables/table3.xml").unwrap()).unwrap();
+        assert!(second.contains(r#"id="3" name="Table3""#));
+
+        let content_types =
+            String::from_utf8(zip.read_entry_by_name("[Content_Types].xml").unwrap()).unwrap();
+        assert!(content_types.contains(r#"<Override PartName="/xl/tables/table3.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.table+xml"/>"#));
+    }
+
+    #[test]
+    fn test_buffered_worksheet() {
+        use crate::fast_writer::StreamingZipReader;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        let errors = writer.worksheet("Errors").unwrap();
+        assert!(writer.worksheet("errors").is_err());
+
+        writer.write_row(["Value"]).unwrap();
+        writer
+            .sheet(errors)
+            .write_header_bold(["Line", "Reason"])
+            .unwrap();
+        writer.write_row(["10"]).unwrap();
+        writer
+            .sheet(errors)
+            .write_row_typed(&[CellValue::Int(3), CellValue::String("a < b".to_string())])
+            .unwrap();
+        writer.write_row(["30"]).unwrap();
+        assert!(writer
+            .sheet(errors)
+            .write_row_typed(&[CellValue::hyperlink("https://example.com", "x")])
+            .is_err());
+        writer.add_sheet("Summary").unwrap();
+        writer.write_row(["Total", "40"]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let main =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(main.contains(r#"<row r="3"><c r="A3" t="inlineStr"><is><t>30</t>"#));
+        let errors =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet2.xml").unwrap()).unwrap();
+        assert!(errors.contains(r#"<row r="1"><c r="A1" s="#));
+        assert!(errors.contains(r#"<row r="2"><c r="A2" t="n"><v>3</v></c>"#));
+        assert!(errors.contains("a &lt; b"));
+        assert!(errors.ends_with("</sheetData></worksheet>"));
+        let summary =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet3.xml").unwrap()).unwrap();
+        assert!(summary.contains("<t>Total</t>"));
+
+        let workbook =
+            String::from_utf8(zip.read_entry_by_name("xl/workbook.xml").unwrap()).unwrap();
+        let order: Vec<_> = ["Sheet1", "Errors", "Summary"]
+            .iter()
+            .map(|name| workbook.find(&format!("name=\"{}\"", name)).unwrap())
+            .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_write_cell() {
+        use crate::fast_writer::StreamingZipReader;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_header_bold(["Region", "Total"]).unwrap();
+        writer
+            .write_cell(3, 1, CellValue::formula("=SUM(B2:B3)"), CellStyle::Default)
+            .unwrap();
+        writer
+            .write_cell(1, 1, CellValue::Int(5), CellStyle::Default)
+            .unwrap();
+        writer
+            .write_cell(
+                3,
+                0,
+                CellValue::String("Total".to_string()),
+                CellStyle::TextBold,
+            )
+            .unwrap();
+        writer
+            .write_cell(1, 1, CellValue::Int(7), CellStyle::Default)
+            .unwrap();
+        assert!(writer
+            .write_cell(0, 3, CellValue::Int(1), CellStyle::Default)
+            .is_err());
+        assert!(writer
+            .write_cell(1, 16_384, CellValue::Int(1), CellStyle::Default)
+            .is_err());
+        assert!(writer.write_row(["North", "1"]).is_err());
+        writer.add_sheet("Data").unwrap();
+        writer.write_row(["streamed"]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let summary =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        let rows = &summary[summary.find(r#"<row r="2">"#).unwrap()..];
+        assert!(rows.starts_with(
+            r#"<row r="2"><c r="A2"/><c r="B2" t="n"><v>7</v></c></row><row r="4"><c r="A4" s=""#
+        ));
+        assert!(rows.contains("<f>SUM(B2:B3)</f>"));
+        assert!(rows.ends_with("</row></sheetData></worksheet>"));
+
+        let mut reader = crate::streaming_reader::StreamingReader::open(temp.path()).unwrap();
+        let rows: Vec<_> = reader
+            .rows("Sheet1")
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(rows[0], vec!["Region", "Total"]);
+        assert_eq!(rows.last().unwrap()[0], "Total");
+        let data: Vec<_> = reader.rows("Data").unwrap().collect();
+        assert_eq!(data.len(), 1);
+    }
+
+    #[test]
+    fn test_data_validation() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::{ComparisonOperator, DataValidation, ValidationErrorStyle};
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_header(["Task", "Status", "Hours"]).unwrap();
+        writer
+            .add_data_validation(
+                "B2:B100",
+                DataValidation::list(["Open", "In Progress", "Done"])
+                    .input_message("Status", "Pick one")
+                    .error_message("Invalid", "Use the <list>"),
+            )
+            .unwrap();
+        writer.write_row(["Write docs", "Open", "2"]).unwrap();
+        writer
+            .add_data_validation(
+                "$C$2:$C$100 E2",
+                DataValidation::decimal_where(ComparisonOperator::GreaterThan, 0.0)
+                    .error_style(ValidationErrorStyle::Warning)
+                    .allow_blank(false),
+            )
+            .unwrap();
+        writer
+            .add_data_validation("D2:D100", DataValidation::list_range("=Lists!$A$1:$A$5"))
+            .unwrap();
+        writer.add_sheet("Lists").unwrap();
+        writer.write_row(["No rules here"]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(
+            r#"<dataValidations count="3"><dataValidation type="list" allowBlank="1" showInputMessage="1" showErrorMessage="1" errorTitle="Invalid" error="Use the &lt;list&gt;" promptTitle="Status" prompt="Pick one" sqref="B2:B100"><formula1>&quot;Open,In Progress,Done&quot;</formula1></dataValidation>"#
+        ));
+        assert!(sheet.contains(
+            r#"<dataValidation type="decimal" errorStyle="warning" operator="greaterThan" showInputMessage="1" showErrorMessage="1" sqref="C2:C100 E2"><formula1>0</formula1></dataValidation>"#
+        ));
+        assert!(sheet.contains(r#"sqref="D2:D100"><formula1>Lists!$A$1:$A$5</formula1>"#));
+        assert!(sheet.find("</sheetData>").unwrap() < sheet.find("<dataValidations").unwrap());
+
+        let lists =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet2.xml").unwrap()).unwrap();
+        assert!(!lists.contains("<dataValidations"));
+    }
+
+    #[test]
+    fn test_conditional_formatting() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::{
+            ComparisonOperator, ConditionalFormat, ConditionalStyle, DataValidation, IconSet,
+        };
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer
+            .write_header(["Region", "Growth", "Revenue"])
+            .unwrap();
+        writer.write_row(["North", "-0.05", "1200"]).unwrap();
+        let red = ConditionalStyle::red();
+        writer
+            .add_conditional_format(
+                "B2:B100",
+                ConditionalFormat::cell_is(ComparisonOperator::LessThan, 0, red.clone()),
+            )
+            .unwrap();
+        writer
+            .add_conditional_format("C2:C100", ConditionalFormat::data_bar("#638ec6"))
+            .unwrap();
+        writer
+            .add_conditional_format(
+                "C2:C100",
+                ConditionalFormat::icon_set(IconSet::ThreeArrows).icons_only(),
+            )
+            .unwrap();
+        writer
+            .add_data_validation("A2:A100", DataValidation::list(["North", "South"]))
+            .unwrap();
+
+        writer.add_sheet("Trend").unwrap();
+        writer
+            .add_conditional_format(
+                "A1:A10",
+                ConditionalFormat::color_scale3("F8696B", "FFEB84", "63BE7B"),
+            )
+            .unwrap();
+        writer
+            .add_conditional_format("B1:B10", ConditionalFormat::cell_between(1, 5, red))
+            .unwrap();
+        writer
+            .add_conditional_format(
+                "C1:C10",
+                ConditionalFormat::expression("=$C1>$B1", ConditionalStyle::new().bold()),
+            )
+            .unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(
+            r#"<conditionalFormatting sqref="B2:B100"><cfRule type="cellIs" dxfId="0" priority="1" operator="lessThan"><formula>0</formula></cfRule></conditionalFormatting>"#
+        ));
+        assert!(sheet.contains(
+            r#"<cfRule type="dataBar" priority="2"><dataBar><cfvo type="min"/><cfvo type="max"/><color rgb="FF638EC6"/></dataBar></cfRule>"#
+        ));
+        assert!(sheet.contains(
+            r#"<iconSet iconSet="3Arrows" showValue="0"><cfvo type="percent" val="0"/><cfvo type="percent" val="33"/><cfvo type="percent" val="66"/></iconSet>"#
+        ));
+        assert!(
+            sheet.find("<conditionalFormatting").unwrap() < sheet.find("<dataValidations").unwrap()
+        );
+
+        let trend =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet2.xml").unwrap()).unwrap();
+        assert!(trend.contains(
+            r#"<colorScale><cfvo type="min"/><cfvo type="percentile" val="50"/><cfvo type="max"/><color rgb="FFF8696B"/><color rgb="FFFFEB84"/><color rgb="FF63BE7B"/></colorScale>"#
+        ));
+        // The red style is shared with sheet 1
+        assert!(trend.contains(
+            r#"<cfRule type="cellIs" dxfId="0" priority="2" operator="between"><formula>1</formula><formula>5</formula></cfRule>"#
+        ));
+        assert!(trend.contains(
+            r#"<cfRule type="expression" dxfId="1" priority="3"><formula>$C1&gt;$B1</formula></cfRule>"#
+        ));
+
+        let styles = String::from_utf8(zip.read_entry_by_name("xl/styles.xml").unwrap()).unwrap();
+        assert!(styles.contains(
+            r#"<dxfs count="2"><dxf><font><color rgb="FF9C0006"/></font><fill><patternFill><bgColor rgb="FFFFC7CE"/></patternFill></fill></dxf><dxf><font><b/></font></dxf></dxfs>"#
+        ));
+        assert!(styles.trim_end().ends_with("</styleSheet>"));
+    }
+
+    #[test]
+    fn test_conditional_format_rejects_bad_color() {
+        use crate::types::ConditionalFormat;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        assert!(writer
+            .add_conditional_format("A1:A10", ConditionalFormat::data_bar("blue"))
+            .is_err());
+        assert!(writer
+            .add_conditional_format("A1:A10", ConditionalFormat::color_scale("F8696B", "12345"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_charts() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::{CellValue, ChartOptions, ChartType};
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_header(["Month", "Revenue", "Cost"]).unwrap();
+        writer.write_row(["Jan", "1200", "800"]).unwrap();
+        writer.write_row(["Feb", "1500", "900"]).unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::String("Docs".to_string()),
+                CellValue::hyperlink("https://example.com", "Docs"),
+            ])
+            .unwrap();
+        writer
+            .add_chart(
+                ChartType::Column,
+                "A1:C3",
+                "E2",
+                ChartOptions::new()
+                    .title("Revenue & cost")
+                    .x_axis_title("Month")
+                    .y_axis_title("USD"),
+            )
+            .unwrap();
+        writer.add_sheet("Summary Q1").unwrap();
+        writer
+            .add_chart(
+                ChartType::Pie,
+                "Sheet1!$A$1:$B$3",
+                "B2",
+                ChartOptions::new().size(6, 10),
+            )
+            .unwrap();
+        writer
+            .add_chart(ChartType::Line, "Sheet1!A1:C3", "J2", ChartOptions::new())
+            .unwrap();
+        writer.add_sheet("Plain").unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let mut entry =
+            |name: &str| String::from_utf8(zip.read_entry_by_name(name).unwrap()).unwrap();
+
+        // The drawing relationship follows the external hyperlink
+        let sheet = entry("xl/worksheets/sheet1.xml");
+        assert!(sheet.ends_with(r#"<drawing r:id="rId2"/></worksheet>"#));
+        let rels = entry("xl/worksheets/_rels/sheet1.xml.rels");
+        assert!(rels.contains(r#"Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/drawing" Target="../drawings/drawing1.xml""#));
+
+        let chart1 = entry("xl/charts/chart1.xml");
+        assert!(chart1.contains(r#"<c:barDir val="col"/>"#));
+        assert!(chart1.contains("<a:t>Revenue &amp; cost</a:t>"));
+        assert!(chart1.contains(
+            "<c:tx><c:strRef><c:f>Sheet1!$C$1</c:f></c:strRef></c:tx><c:cat><c:strRef><c:f>Sheet1!$A$2:$A$3</c:f></c:strRef></c:cat><c:val><c:numRef><c:f>Sheet1!$C$2:$C$3</c:f></c:numRef></c:val>"
+        ));
+        assert!(chart1.contains("<a:t>USD</a:t>"));
+
+        let sheet2 = entry("xl/worksheets/sheet2.xml");
+        assert!(sheet2.contains(r#"<drawing r:id="rId1"/>"#));
+        let drawing = entry("xl/drawings/drawing2.xml");
+        assert!(drawing.contains(
+            "<xdr:from><xdr:col>1</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>1</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from><xdr:to><xdr:col>7</xdr:col>"
+        ));
+        let drawing_rels = entry("xl/drawings/_rels/drawing2.xml.rels");
+        assert!(drawing_rels.contains(r#"Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart" Target="../charts/chart2.xml""#));
+        assert!(drawing_rels.contains(r#"Target="../charts/chart3.xml""#));
+
+        let pie = entry("xl/charts/chart2.xml");
+        assert_eq!(pie.matches("<c:ser>").count(), 1);
+        assert!(!pie.contains("<c:catAx>"));
+        assert!(entry("xl/charts/chart3.xml").contains("<c:lineChart>"));
+
+        assert!(!entry("xl/worksheets/sheet3.xml").contains("<drawing"));
+        let content_types = entry("[Content_Types].xml");
+        assert!(content_types.contains(r#"PartName="/xl/charts/chart3.xml""#));
+        assert!(content_types.contains(r#"PartName="/xl/drawings/drawing2.xml""#));
+        assert!(!content_types.contains("drawing3"));
+    }
+
+    #[test]
+    fn test_chart_rejects_bad_ranges() {
+        use crate::types::{ChartOptions, ChartType};
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        let chart = |writer: &mut ExcelWriter, range: &str, anchor: &str| {
+            writer.add_chart(ChartType::Bar, range, anchor, ChartOptions::new())
+        };
+        assert!(chart(&mut writer, "A1:A10", "C1").is_err());
+        assert!(chart(&mut writer, "A1:D1", "C1").is_err());
+        assert!(chart(&mut writer, "A1", "C1").is_err());
+        assert!(chart(&mut writer, "A1:B5", "not a cell").is_err());
+        assert!(chart(&mut writer, "'It''s data'!B5:A1", "c1").is_ok());
+    }
+
+    #[test]
+    fn test_date_system_1904() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::DateSystem;
+        use chrono::NaiveDate;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_date_system(DateSystem::V1904).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        writer
+            .write_row_typed(&[date.into(), date.and_hms_opt(12, 0, 0).unwrap().into()])
+            .unwrap();
+        assert!(writer.set_date_system(DateSystem::V1900).is_err());
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let workbook =
+            String::from_utf8(zip.read_entry_by_name("xl/workbook.xml").unwrap()).unwrap();
+        assert!(workbook.contains(r#"<workbookPr date1904="1"/>"#));
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains("<v>43844</v>"));
+        assert!(sheet.contains("<v>43844.5</v>"));
+        // Both cells get a date format instead of showing the serial
+        assert!(!sheet.contains(r#"<c r="A1"><v>"#));
+    }
+
+    #[test]
+    fn test_write_row_at() {
+        use crate::fast_writer::StreamingZipReader;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_row_at(0, &[CellValue::from("id")]).unwrap();
+        writer.write_row_at(99, &[CellValue::Int(100)]).unwrap();
+        writer.write_row(["next"]).unwrap();
+        assert_eq!(writer.current_row(), 101);
+        assert!(writer.write_row_at(50, &[CellValue::Int(0)]).is_err());
+        assert!(writer
+            .write_row_at(2_000_000, &[CellValue::Int(0)])
+            .is_err());
+        // A failed row leaves the position alone
+        assert!(writer
+            .write_row_at(4_999, &vec![CellValue::Int(0); 16_385])
+            .is_err());
+        assert_eq!(writer.current_row(), 101);
+        writer.write_row_at(4_999, &[CellValue::Int(5000)]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(
+            r#"</row><row r="100"><c r="A100" t="n"><v>100</v></c></row><row r="101"><c r="A101""#
+        ));
+        assert!(sheet.contains(r#"<row r="5000"><c r="A5000" t="n"><v>5000</v>"#));
+    }
+
+    #[test]
+    fn test_write_row_typed_iter() {
+        use crate::fast_writer::StreamingZipReader;
+        use chrono::NaiveDate;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer
+            .write_row_typed_iter((1..=3).map(CellValue::Int))
+            .unwrap();
+        let record = vec![
+            CellValue::from("due"),
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap().into(),
+        ];
+        writer.write_row_typed_iter(&record).unwrap();
+        writer.write_row_typed_iter(record.iter().rev()).unwrap();
+        // A row past the last column is dropped as a whole
+        let wide = std::iter::repeat_n(CellValue::Int(0), 16_385);
+        assert!(matches!(
+            writer.write_row_typed_iter(wide),
+            Err(crate::error::ExcelError::TooManyColumns { .. })
+        ));
+        assert_eq!(writer.current_row(), 3);
+        writer.write_row_typed(&record).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(r#"<c r="C1" t="n"><v>3</v></c></row>"#));
+        assert!(sheet.contains(r#"<c r="B2" s="6" t="n"><v>45306</v></c>"#));
+        assert!(sheet.contains(r#"<row r="3"><c r="A3" s="6" t="n"><v>45306</v></c>"#));
+        assert!(sheet.contains(r#"<row r="4"><c r="A4""#));
+        assert!(!sheet.contains(r#"<row r="5""#));
+    }
+
+    #[test]
+    fn test_write_row_refs() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::CellRef;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        let name = String::from("Tom & Jerry");
+        let link = CellValue::hyperlink("https://example.com", "Home");
+        writer
+            .write_row_refs([
+                CellRef::from(&name),
+                CellRef::from(None::<&str>),
+                CellRef::Int(7),
+                CellRef::DateTime(45306.0),
+                CellRef::from(&link),
+            ])
+            .unwrap();
+        writer.write_row_refs(["a", "b"]).unwrap();
+        let wide = vec![CellValue::Int(0); 16_385];
+        assert!(writer.write_row_refs(&wide).is_err());
+        assert_eq!(writer.current_row(), 2);
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(
+            r#"<c r="A1" t="inlineStr"><is><t>Tom &amp; Jerry</t></is></c><c r="B1"/><c r="C1" t="n"><v>7</v></c><c r="D1" s="6" t="n"><v>45306</v></c>"#
+        ));
+        assert!(sheet.contains(r#"<hyperlink ref="E1""#));
+        assert!(sheet.contains(r#"<row r="2"><c r="A2" t="inlineStr"><is><t>a</t>"#));
+        assert!(!sheet.contains(r#"<row r="3""#));
+    }
+
+    #[test]
+    fn test_write_columns() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::ColumnSlice;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_header(["id", "name", "due", "note"]).unwrap();
+        let ids = [1, 2];
+        let names = ["Alice", "Bob"];
+        let due = [45306.0, 45307.0];
+        let notes = [CellValue::Empty, CellValue::from("late")];
+        let columns = [
+            ColumnSlice::Int(&ids),
+            ColumnSlice::Str(&names),
+            ColumnSlice::DateTime(&due),
+            ColumnSlice::Values(&notes),
+        ];
+        assert_eq!(writer.write_columns(&columns).unwrap(), 2);
+        assert_eq!(writer.current_row(), 3);
+        // Ragged batches are rejected before anything is written
+        let err = writer
+            .write_columns(&[ColumnSlice::Int(&ids), ColumnSlice::Str(&names[..1])])
+            .unwrap_err();
+        assert!(err.to_string().contains("Column 2 has 1 values"));
+        assert_eq!(writer.write_columns(&[]).unwrap(), 0);
+        assert_eq!(writer.current_row(), 3);
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(
+            r#"<row r="2"><c r="A2" t="n"><v>1</v></c><c r="B2" t="inlineStr"><is><t>Alice</t></is></c><c r="C2" s="6" t="n"><v>45306</v></c><c r="D2"/></row>"#
+        ));
+        assert!(sheet.contains(r#"<c r="D3" t="inlineStr"><is><t>late</t></is></c></row>"#));
+        assert!(!sheet.contains(r#"<row r="4""#));
+    }
+
+    #[test]
+    fn test_timezone_policy() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::{DocumentProperties, TimezonePolicy};
+        use chrono::{FixedOffset, TimeZone};
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        let hanoi = FixedOffset::east_opt(7 * 3600).unwrap();
+        writer.set_timezone_policy(TimezonePolicy::Offset(hanoi));
+        // Properties set afterwards keep the recorded policy
+        writer
+            .set_properties(DocumentProperties::new().custom("Source", "crm"))
+            .unwrap();
+
+        let paris = FixedOffset::east_opt(3600).unwrap();
+        let login = paris.with_ymd_and_hms(2024, 1, 15, 6, 0, 0).unwrap();
+        assert_eq!(writer.timestamp(&login), CellValue::DateTime(45306.5));
+        writer.write_row_typed(&[writer.timestamp(&login)]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let custom =
+            String::from_utf8(zip.read_entry_by_name("docProps/custom.xml").unwrap()).unwrap();
+        assert!(custom.contains(r#"name="Source""#));
+        assert!(custom.contains(r#"name="Timezone"><vt:lpwstr>UTC+07:00</vt:lpwstr>"#));
+    }
+
+    #[test]
+    fn test_sheet_visibility_tab_color_and_order() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::PageSetup;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_tab_color("sheet1", "#ff0000").unwrap();
+        writer.write_row(["Data"]).unwrap();
+        assert!(writer.set_tab_color("Sheet1", "00FF00").is_err());
+        assert!(writer.set_sheet_hidden("Sheet1", true).is_err());
+
+        writer.add_sheet("Lookup").unwrap();
+        writer
+            .set_page_setup(PageSetup::new().repeat_rows(1, 1))
+            .unwrap();
+        writer.write_row(["Code"]).unwrap();
+        writer.set_sheet_hidden("Lookup", true).unwrap();
+        assert!(writer.set_active_sheet("Lookup").is_err());
+        assert!(writer.set_tab_color("Nope", "FF0000").is_err());
+
+        writer.worksheet("Summary").unwrap();
+        writer.set_tab_color("Summary", "3366FF").unwrap();
+        assert!(writer.set_tab_color("Summary", "blue").is_err());
+        writer.move_sheet("Summary", 0).unwrap();
+        assert!(writer.move_sheet("Summary", 3).is_err());
+        writer.set_active_sheet("Sheet1").unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let workbook =
+            String::from_utf8(zip.read_entry_by_name("xl/workbook.xml").unwrap()).unwrap();
+        assert!(workbook.contains(
+            r#"<bookViews><workbookView activeTab="1"/></bookViews>
+<sheets>
+<sheet name="Summary" sheetId="3" r:id="rId3"/>
+<sheet name="Sheet1" sheetId="1" r:id="rId1"/>
+<sheet name="Lookup" sheetId="2" state="hidden" r:id="rId2"/>
+</sheets>"#
+        ));
+        // Names scoped to Lookup follow it to its new tab position
+        assert!(workbook.contains(r#"name="_xlnm.Print_Titles" localSheetId="2">"#));
+
+        let sheet1 =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet1.contains(r#"<sheetPr><tabColor rgb="FFFF0000"/></sheetPr><sheetData>"#));
+        let summary =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet3.xml").unwrap()).unwrap();
+        assert!(summary.contains(r#"<sheetPr><tabColor rgb="FF3366FF"/></sheetPr><sheetData>"#));
+
+        let reader = crate::ExcelReader::open(temp.path()).unwrap();
+        assert_eq!(reader.sheet_names(), ["Summary", "Sheet1", "Lookup"]);
+    }
+
+    #[test]
+    fn test_data_validation_rejects_bad_input() {
+        use crate::types::DataValidation;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        let rule = || DataValidation::whole_number(1, 10);
+        assert!(writer.add_data_validation("B2:", rule()).is_err());
+        assert!(writer.add_data_validation("XFE1", rule()).is_err());
+        assert!(writer.add_data_validation("A0", rule()).is_err());
+        assert!(writer.add_data_validation("", rule()).is_err());
+        assert!(writer.add_data_validation("a1:b2", rule()).is_ok());
+
+        assert!(writer
+            .add_data_validation("A1", DataValidation::list(["1,000", "2,000"]))
+            .is_err());
+        let long = (0..100).map(|i| format!("Item{}", i));
+        assert!(writer
+            .add_data_validation("A1", DataValidation::list(long))
+            .is_err());
+    }
+
+    #[test]
+    fn test_document_properties() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::DocumentProperties;
+        use chrono::TimeZone;
+
+        let created = chrono::Utc.with_ymd_and_hms(2024, 3, 1, 9, 30, 0).unwrap();
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer
+            .set_properties(
+                DocumentProperties::new()
+                    .title("Q1 <Sales>")
+                    .author("Jane & Co")
+                    .company("Acme")
+                    .created(created)
+                    .custom("Revision", 3)
+                    .custom("Big", 5_000_000_000i64)
+                    .custom("Approved", true)
+                    .custom("Revision", 4),
+            )
+            .unwrap();
+        assert!(writer
+            .set_properties(DocumentProperties::new().custom(" ", 1))
+            .is_err());
+        writer.write_row(["A"]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let mut entry = |name| String::from_utf8(zip.read_entry_by_name(name).unwrap()).unwrap();
+        let core = entry("docProps/core.xml");
+        assert!(core.contains("<dc:title>Q1 &lt;Sales&gt;</dc:title>"));
+        assert!(core.contains("<dc:creator>Jane &amp; Co</dc:creator>"));
+        assert!(core.contains(
+            r#"<dcterms:created xsi:type="dcterms:W3CDTF">2024-03-01T09:30:00Z</dcterms:created>"#
+        ));
+        assert!(core.contains(
+            r#"<dcterms:modified xsi:type="dcterms:W3CDTF">2024-03-01T09:30:00Z</dcterms:modified>"#
+        ));
+        assert!(entry("docProps/app.xml").contains("<Company>Acme</Company>"));
+
+        let custom = entry("docProps/custom.xml");
+        assert!(custom.contains(r#"pid="2" name="Revision"><vt:i4>4</vt:i4>"#));
+        assert!(custom.contains(r#"pid="3" name="Big"><vt:r8>5000000000</vt:r8>"#));
+        assert!(custom.contains(r#"pid="4" name="Approved"><vt:bool>true</vt:bool>"#));
+        assert!(entry("[Content_Types].xml").contains("/docProps/custom.xml"));
+        assert!(entry("_rels/.rels").contains(r#"Target="docProps/custom.xml""#));
+    }
+
+    #[test]
+    fn test_default_properties_have_no_custom_part() {
+        use crate::fast_writer::StreamingZipReader;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_row(["A"]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let core = String::from_utf8(zip.read_entry_by_name("docProps/core.xml").unwrap()).unwrap();
+        assert!(core.contains("<dc:creator>ExcelStream</dc:creator>"));
+        assert!(zip.read_entry_by_name("docProps/custom.xml").is_err());
+    }
+
+    #[test]
+    fn test_save_with_report() {
+        use crate::report::{Checksum, Digest};
+        use sha2::Digest as _;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_checksum(Checksum::Sha256);
+        for i in 0..1000 {
+            writer
+                .write_row([i.to_string(), format!("Row {}", i)])
+                .unwrap();
+        }
+        let report = writer.save_with_report().unwrap();
+
+        let bytes = std::fs::read(temp.path()).unwrap();
+        assert_eq!(report.bytes_written, bytes.len() as u64);
+        let expected: [u8; 32] = sha2::Sha256::digest(&bytes).into();
+        assert_eq!(report.digest, Some(Digest::Sha256(expected)));
+
+        // Size is always reported
+        let writer = ExcelWriter::new(temp.path()).unwrap();
+        let report = writer.save_with_report().unwrap();
+        assert_eq!(
+            report.bytes_written,
+            temp.as_file().metadata().unwrap().len()
+        );
+        assert_eq!(report.digest, None);
+    }
+
+    #[test]
+    fn test_compression_workers() {
+        let write = |workers: usize| {
+            let temp = NamedTempFile::new().unwrap();
+            let mut writer = ExcelWriter::new(temp.path()).unwrap();
+            writer.set_compression_workers(workers).unwrap();
+            assert_eq!(writer.compression_workers(), workers);
+            for i in 0..20_000 {
+                writer
+                    .write_row([i.to_string(), format!("Row {}", i)])
+                    .unwrap();
+            }
+            writer.add_sheet("Second").unwrap();
+            writer.write_row(["after"]).unwrap();
+            // Switching mid-sheet keeps everything written so far
+            writer.set_compression_workers(0).unwrap();
+            writer.write_row(["inline"]).unwrap();
+            writer.set_compression_workers(2).unwrap();
+            writer.write_row(["parallel again"]).unwrap();
+            writer.save().unwrap();
+
+            let mut zip = crate::fast_writer::StreamingZipReader::open(temp.path()).unwrap();
+            ["xl/worksheets/sheet1.xml", "xl/worksheets/sheet2.xml"]
+                .map(|name| zip.read_entry_by_name(name).unwrap())
+        };
+
+        let inline = write(0);
+        for workers in [1, 4] {
+            assert_eq!(write(workers), inline);
+        }
+        let second = String::from_utf8_lossy(&inline[1]);
+        assert!(second.contains("inline") && second.contains("parallel again"));
+    }
+
+    #[test]
+    fn test_string_modes() {
+        use crate::types::StringMode;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_row(["inline"]).unwrap();
+        writer
+            .set_string_mode(StringMode::SharedThresholded { max_unique: 2 })
+            .unwrap();
+        writer.write_row(["a", "b", "c", "a"]).unwrap();
+        writer
+            .write_row_typed(&[CellValue::String("b".to_string()), CellValue::Int(7)])
+            .unwrap();
+        writer.save().unwrap();
+
+        let mut zip = crate::fast_writer::StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert_eq!(sheet.matches("t=\"s\"").count(), 4);
+        assert_eq!(sheet.matches("t=\"inlineStr\"").count(), 2);
+        let sst =
+            String::from_utf8(zip.read_entry_by_name("xl/sharedStrings.xml").unwrap()).unwrap();
+        assert!(sst.contains("count=\"4\" uniqueCount=\"2\""));
+
+        let mut reader = crate::ExcelReader::open(temp.path()).unwrap();
+        let rows: Vec<Vec<String>> = reader
+            .rows_by_index(0)
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(rows[0], ["inline"]);
+        assert_eq!(rows[1], ["a", "b", "c", "a"]);
+        assert_eq!(rows[2], ["b", "7"]);
+    }
+
+    #[test]
+    fn test_hashed_string_mode() {
+        use crate::types::StringMode;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer
+            .set_string_mode(StringMode::SharedHashed {
+                memory_limit: 1 << 20,
+            })
+            .unwrap();
+        for i in 0..3_000 {
+            writer
+                .write_row([format!("id-{}", i), ["open", "closed"][i % 2].to_string()])
+                .unwrap();
+        }
+        // The hashed table can't be swapped for the regular one mid-file
+        assert!(writer.set_string_mode(StringMode::SharedDeduped).is_err());
+        writer.save().unwrap();
+
+        let mut zip = crate::fast_writer::StreamingZipReader::open(temp.path()).unwrap();
+        let sst =
+            String::from_utf8(zip.read_entry_by_name("xl/sharedStrings.xml").unwrap()).unwrap();
+        assert!(sst.contains("count=\"6000\" uniqueCount=\"3002\""));
+
+        let mut reader = crate::ExcelReader::open(temp.path()).unwrap();
+        let rows: Vec<Vec<String>> = reader
+            .rows_by_index(0)
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(rows.len(), 3_000);
+        assert_eq!(rows[2_999], ["id-2999", "closed"]);
+    }
+
+    #[test]
+    fn test_progress_callback() {
+        use std::sync::{Arc, Mutex};
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        let sink = calls.clone();
+        writer.set_progress_callback(move |rows, bytes| sink.lock().unwrap().push((rows, bytes)));
+        for i in 0..2_500 {
+            writer.write_row([i.to_string()]).unwrap();
+        }
+        writer.save().unwrap();
+
+        let calls = calls.lock().unwrap();
+        let rows: Vec<u64> = calls.iter().map(|&(rows, _)| rows).collect();
+        assert_eq!(rows, vec![1_000, 2_000, 2_500]);
+        assert!(calls.windows(2).all(|w| w[0].1 < w[1].1));
+    }
+
+    #[test]
+    fn test_writer_metrics() {
+        use crate::progress::WriterMetrics;
+        use crate::types::StringMode;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default)]
+        struct Totals {
+            calls: u32,
+            rows: u64,
+            bytes: u64,
+            compressed: u64,
+            strings: u64,
+            flushes: u64,
+        }
+        struct Recorder(Arc<Mutex<Totals>>);
+        impl WriterMetrics for Recorder {
+            fn rows_written(&mut self, rows: u64) {
+                let mut totals = self.0.lock().unwrap();
+                totals.calls += 1;
+                totals.rows = rows;
+            }
+            fn bytes_written(&mut self, bytes: u64) {
+                self.0.lock().unwrap().bytes = bytes;
+            }
+            fn bytes_compressed(&mut self, bytes: u64) {
+                self.0.lock().unwrap().compressed = bytes;
+            }
+            fn sst_unique_strings(&mut self, count: u64) {
+                self.0.lock().unwrap().strings = count;
+            }
+            fn flush_count(&mut self, flushes: u64) {
+                self.0.lock().unwrap().flushes = flushes;
+            }
+        }
+
+        let totals = Arc::new(Mutex::new(Totals::default()));
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_string_mode(StringMode::SharedDeduped).unwrap();
+        writer.set_metrics(Recorder(totals.clone()));
+        for i in 0..2_500 {
+            writer
+                .write_row([format!("k{}", i % 7), i.to_string()])
+                .unwrap();
+        }
+        writer.save().unwrap();
+
+        let totals = totals.lock().unwrap();
+        assert_eq!(totals.calls, 3);
+        assert_eq!(totals.rows, 2_500);
+        assert!(totals.bytes > totals.compressed);
+        assert_eq!(
+            totals.compressed,
+            std::fs::metadata(temp.path()).unwrap().len()
+        );
+        assert_eq!(totals.strings, 7 + 2_500);
+        assert!(totals.flushes > 0);
+    }
+
+    #[test]
+    fn test_auto_shard_rows() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        assert!(writer.set_auto_shard_rows(1).is_err());
+        writer.set_auto_shard_rows(3).unwrap();
+        writer.add_sheet("Data").unwrap();
+        writer.set_column_width(0, 20.0).unwrap();
+        writer.write_header_bold(["id", "name"]).unwrap();
+        for id in 0..5 {
+            writer
+                .write_row_typed(&[CellValue::Int(id), CellValue::from("x")])
+                .unwrap();
+        }
+        assert_eq!(writer.current_sheet_name(), "Data_3");
+        assert_eq!(writer.current_row(), 2);
+        writer.add_sheet("Other").unwrap();
+        writer.write_row(["fits"]).unwrap();
+        writer.save().unwrap();
+
+        let mut reader = crate::streaming_reader::StreamingReader::open(temp.path()).unwrap();
+        assert_eq!(
+            reader.sheet_names(),
+            vec!["Sheet1", "Data", "Data_2", "Data_3", "Other"]
+        );
+        let rows = |reader: &mut crate::streaming_reader::StreamingReader,
+                    sheet: &str|
+         -> Vec<Vec<String>> {
+            reader
+                .rows(sheet)
+                .unwrap()
+                .map(|row| row.unwrap().to_strings())
+                .collect()
+        };
+        assert_eq!(
+            rows(&mut reader, "Data"),
+            vec![vec!["id", "name"], vec!["0", "x"], vec!["1", "x"]]
+        );
+        assert_eq!(
+            rows(&mut reader, "Data_3"),
+            vec![vec!["id", "name"], vec!["4", "x"]]
+        );
+
+        let mut zip = crate::fast_writer::StreamingZipReader::open(temp.path()).unwrap();
+        let xml = zip.read_entry_by_name("xl/worksheets/sheet4.xml").unwrap();
+        let xml = String::from_utf8(xml).unwrap();
+        assert!(xml.contains(r#"<col min="1" max="1" width="20""#));
+        assert!(xml.contains(r#"<c r="A1" s="1""#));
+    }
+
+    #[test]
+    fn test_too_many_columns() {
+        use crate::error::ExcelError;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        let too_wide =
+            |err: ExcelError| matches!(err, ExcelError::TooManyColumns { column: 16_385 });
+        assert!(too_wide(writer.set_column_width(16_384, 10.0).unwrap_err()));
+        assert!(too_wide(
+            writer
+                .write_row_typed(&vec![CellValue::Int(1); 16_385])
+                .unwrap_err()
+        ));
+        assert!(too_wide(writer.write_row(vec!["x"; 16_385]).unwrap_err()));
+        assert!(too_wide(writer.write_row_display(0..16_385).unwrap_err()));
+        assert_eq!(writer.current_row(), 0);
+
+        writer.write_row(vec!["x"; 16_384]).unwrap();
+        writer.write_row(["next"]).unwrap();
+        writer.save().unwrap();
+
+        let mut reader = crate::streaming_reader::StreamingReader::open(temp.path()).unwrap();
+        let rows: Vec<Vec<String>> = reader
+            .rows("Sheet1")
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].len(), 16_384);
+        assert_eq!(rows[1], vec!["next"]);
+    }
+
+    #[test]
+    fn test_cancellation_token() {
+        use crate::error::ExcelError;
+        use crate::progress::CancellationToken;
+
+        let token = CancellationToken::new();
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_cancellation_token(token.clone());
+        writer.write_row(["before"]).unwrap();
+
+        token.cancel();
+        let err = writer.write_row(["after"]).unwrap_err();
+        assert!(matches!(err, ExcelError::Cancelled));
+        assert!(writer.write_row_typed(&[CellValue::Int(1)]).is_err());
+        assert!(matches!(writer.save(), Err(ExcelError::Cancelled)));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_with_password() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("secret.xlsx");
+        let mut writer = ExcelWriter::with_password(&path, "s3cr3t").unwrap();
+        writer.write_row(["Name", "Salary"]).unwrap();
+        writer.save().unwrap();
+
+        // A compound file, not a ZIP, and the staging copy is gone
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..4], &[0xD0, 0xCF, 0x11, 0xE0]);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        assert!(ExcelWriter::with_password(&path, "").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Defined names, and the print settings Excel stores next to them: print
+//! area and print titles are workbook names, set with the rest of the page
+//! setup
+
+use super::ExcelWriter;
+use crate::error::Result;
+
+impl ExcelWriter {
+    /// Set print setup (orientation, paper, margins, print area, repeated
+    /// header rows) for the current worksheet
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::{ExcelWriter, Orientation, PageSetup};
+    ///
+    /// let mut writer = ExcelWriter::new("printable.xlsx").unwrap();
+    /// writer
+    ///     .set_page_setup(
+    ///         PageSetup::new()
+    ///             .orientation(Orientation::Landscape)
+    ///             .print_area("A1:F500")
+    ///             .repeat_rows(1, 1),
+    ///     )
+    ///     .unwrap();
+    /// writer.write_header_bold(["ID", "Name", "Amount"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_page_setup(&mut self, setup: crate::types::PageSetup) -> Result<()> {
+        self.inner.set_page_setup(setup)
+    }
+
+    /// Print `text` at the top of every page of the current worksheet
+    ///
+    /// `&L`, `&C` and `&R` start the left, center and right sections; `&P`
+    /// is the page number and `&N` the page count (see
+    /// [`PageSetup`](crate::types::PageSetup) for the other codes). Other
+    /// page settings are kept, but a later
+    /// [`set_page_setup`](Self::set_page_setup) replaces the header.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::ExcelWriter;
+    ///
+    /// let mut writer = ExcelWriter::new("printable.xlsx").unwrap();
+    /// writer.set_header("&LSales report&R&D").unwrap();
+    /// writer.set_footer("&CPage &P of &N").unwrap();
+    /// writer.set_print_title_rows("1:1").unwrap();
+    /// writer.write_header_bold(["ID", "Name", "Amount"]).unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn set_header(&mut self, text: &str) -> Result<()> {
+        self.inner.set_header(text)
+    }
+
+    /// Print `text` at the bottom of every page of the current worksheet,
+    /// with the same codes as [`set_header`](Self::set_header)
+    pub fn set_footer(&mut self, text: &str) -> Result<()> {
+        self.inner.set_footer(text)
+    }
+
+    /// Repeat rows such as `"1:1"` at the top of every printed page of the
+    /// current worksheet
+    pub fn set_print_title_rows(&mut self, rows: &str) -> Result<()> {
+        self.inner.set_print_title_rows(rows)
+    }
+
+    /// Define a workbook-level name usable in formulas
+    ///
+    /// `refers_to` is a reference or formula such as `"Sheet1!$B$1"` or
+    /// `"Rates!$A$2:$A$20"`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use excelstream::{CellValue, ExcelWriter};
+    ///
+    /// let mut writer = ExcelWriter::new("invoice.xlsx").unwrap();
+    /// writer.write_row(["Tax rate", "0.08"]).unwrap();
+    /// writer.define_name("TaxRate", "Sheet1!$B$1").unwrap();
+    /// writer
+    ///     .write_row_typed(&[
+    ///         CellValue::String("Tax".to_string()),
+    ///         CellValue::formula("=100*TaxRate"),
+    ///     ])
+    ///     .unwrap();
+    /// writer.save().unwrap();
+    /// ```
+    pub fn define_name(&mut self, name: &str, refers_to: &str) -> Result<()> {
+        self.inner.define_name(name, refers_to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_defined_names_and_page_setup() {
+        use crate::fast_writer::StreamingZipReader;
+        use crate::types::{Orientation, PageSetup, PaperSize};
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_row(["Tax rate", "0.08"]).unwrap();
+        writer.define_name("TaxRate", "=Sheet1!$B$1").unwrap();
+        assert!(writer.define_name("taxrate", "Sheet1!$B$2").is_err());
+        assert!(writer.define_name("B1", "Sheet1!$B$2").is_err());
+        assert!(writer.define_name("1st", "Sheet1!$B$2").is_err());
+        assert!(writer.define_name("Has space", "Sheet1!$B$2").is_err());
+
+        writer.add_sheet("Q1 Report").unwrap();
+        writer
+            .set_page_setup(
+                PageSetup::new()
+                    .orientation(Orientation::Landscape)
+                    .paper_size(PaperSize::A4)
+                    .margins(0.5, 0.5, 1.0, 1.0)
+                    .print_area("a1:f$200")
+                    .repeat_rows(1, 2),
+            )
+            .unwrap();
+        assert!(writer
+            .set_page_setup(PageSetup::new().print_area("A1:"))
+            .is_err());
+        writer.write_row(["Header"]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let workbook =
+            String::from_utf8(zip.read_entry_by_name("xl/workbook.xml").unwrap()).unwrap();
+        assert!(workbook.contains(r#"<definedName name="TaxRate">Sheet1!$B$1</definedName>"#));
+        assert!(workbook.contains(
+            r#"<definedName name="_xlnm.Print_Area" localSheetId="1">&apos;Q1 Report&apos;!$A$1:$F$200</definedName>"#
+        ));
+        assert!(workbook.contains(
+            r#"<definedName name="_xlnm.Print_Titles" localSheetId="1">&apos;Q1 Report&apos;!$1:$2</definedName>"#
+        ));
+        assert!(workbook.find("</sheets>").unwrap() < workbook.find("<definedNames>").unwrap());
+
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet2.xml").unwrap()).unwrap();
+        assert!(sheet.contains(
+            r#"<pageMargins left="0.5" right="0.5" top="1" bottom="1" header="0.3" footer="0.3"/><pageSetup paperSize="9" orientation="landscape"/>"#
+        ));
+        let sheet1 =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(!sheet1.contains("<pageSetup"));
+    }
+
+    #[test]
+    fn test_header_footer_and_print_titles() {
+        use crate::fast_writer::StreamingZipReader;
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_header("&LR&D <Q1>&RPrinted &D").unwrap();
+        writer.set_footer("&CPage &P of &N").unwrap();
+        writer.set_print_title_rows("$1:$2").unwrap();
+        assert!(writer.set_print_title_rows("3:1").is_err());
+        assert!(writer.set_print_title_rows("A:B").is_err());
+        assert!(writer.set_footer(&"x".repeat(256)).is_err());
+        writer.write_row(["Header"]).unwrap();
+        writer.save().unwrap();
+
+        let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+        let sheet =
+            String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        assert!(sheet.contains(
+            r#"<pageSetup orientation="portrait"/><headerFooter><oddHeader>&amp;LR&amp;D &lt;Q1&gt;&amp;RPrinted &amp;D</oddHeader><oddFooter>&amp;CPage &amp;P of &amp;N</oddFooter></headerFooter></worksheet>"#
+        ));
+        let workbook =
+            String::from_utf8(zip.read_entry_by_name("xl/workbook.xml").unwrap()).unwrap();
+        assert!(workbook.contains(
+            r#"<definedName name="_xlnm.Print_Titles" localSheetId="0">Sheet1!$1:$2</definedName>"#
+        ));
+    }
+}
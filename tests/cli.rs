@@ -0,0 +1,74 @@
+//! Runs the `excelstream` binary (built with the `cli` feature)
+
+use excelstream::ExcelReader;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn excelstream() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_excelstream"))
+}
+
+#[test]
+fn test_convert_csv_to_xlsx() {
+    let dir = TempDir::new().unwrap();
+    let csv = dir.path().join("people.csv");
+    let xlsx = dir.path().join("people.xlsx");
+    std::fs::write(&csv, "Name,Age\nAlice,30\nBob,25\n").unwrap();
+
+    let output = excelstream()
+        .arg("convert")
+        .arg(&csv)
+        .arg(&xlsx)
+        .args(["--sheet", "People"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let mut reader = ExcelReader::open(&xlsx).unwrap();
+    let rows: Vec<Vec<String>> = reader
+        .rows("People")
+        .unwrap()
+        .map(|row| row.unwrap().to_strings())
+        .collect();
+    assert_eq!(rows[0], vec!["Name", "Age"]);
+    assert_eq!(rows[2][0], "Bob");
+
+    let output = excelstream()
+        .arg("head")
+        .arg(&xlsx)
+        .args(["--rows", "2"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Name,Age\nAlice,30\n"
+    );
+}
+
+#[test]
+fn test_bad_arguments_exit_with_error() {
+    // Rejected by the argument parser
+    let output = excelstream()
+        .args(["head", "file.xlsx", "--rows", "many"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+
+    // Parsed, but the output format is unknown
+    let dir = TempDir::new().unwrap();
+    let csv = dir.path().join("data.csv");
+    std::fs::write(&csv, "a,b\n").unwrap();
+    let output = excelstream()
+        .arg("convert")
+        .arg(&csv)
+        .arg(dir.path().join("data.txt"))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("data.txt"));
+}
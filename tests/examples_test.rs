@@ -0,0 +1,264 @@
+//! Runs the example workloads from `examples/scenarios` with small datasets
+//!
+//! Cloud scenarios need a local emulator, so they are `#[ignore]`d and run
+//! with `cargo test --all-features --test examples_test -- --ignored` (the
+//! `emulators` CI job starts both):
+//!
+//! - S3 (e.g. MinIO): `EXCELSTREAM_S3_ENDPOINT`, `EXCELSTREAM_S3_BUCKET` and
+//!   the usual `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY`
+//! - GCS (e.g. fake-gcs-server): `STORAGE_EMULATOR_HOST` and
+//!   `EXCELSTREAM_GCS_BUCKET`
+//!
+//! The buckets must already exist.
+
+#[path = "../examples/scenarios/mod.rs"]
+mod scenarios;
+
+use excelstream::{CompressionMethod, CsvReader, ExcelReader};
+use s_zip::StreamingZipReader;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+/// Row count used for the scaled-down runs
+const ROWS: usize = 1000;
+
+fn read_sheet(path: &Path, sheet: &str) -> Vec<Vec<String>> {
+    let mut reader = ExcelReader::open(path).unwrap();
+    reader
+        .rows(sheet)
+        .unwrap()
+        .map(|row| row.unwrap().to_strings())
+        .collect()
+}
+
+/// Emulator setting for the cloud scenarios
+#[cfg(any(feature = "cloud-s3", feature = "cloud-gcs"))]
+fn emulator_env(name: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| panic!("{} is not set; see the module docs", name))
+}
+
+fn read_entry(path: &Path, name: &str) -> String {
+    let mut zip = StreamingZipReader::open(path).unwrap();
+    String::from_utf8(zip.read_entry_by_name(name).unwrap()).unwrap()
+}
+
+#[test]
+fn test_protection_example() {
+    let temp = NamedTempFile::new().unwrap();
+    scenarios::protection::write_protection_demo(temp.path()).unwrap();
+
+    let reader = ExcelReader::open(temp.path()).unwrap();
+    assert_eq!(reader.sheet_names(), scenarios::protection::SHEETS);
+
+    // Sheets 1-4 are protected, sheet 5 is not
+    for i in 1..=4 {
+        let xml = read_entry(temp.path(), &format!("xl/worksheets/sheet{}.xml", i));
+        assert!(xml.contains("<sheetProtection"), "sheet{} unprotected", i);
+    }
+    let xml = read_entry(temp.path(), "xl/worksheets/sheet5.xml");
+    assert!(!xml.contains("<sheetProtection"));
+
+    let rows = read_sheet(temp.path(), "Data Entry");
+    assert_eq!(rows[0], vec!["Name", "Email", "Phone"]);
+    assert_eq!(rows[1], vec!["Alice", "alice@example.com", "555-0001"]);
+}
+
+#[test]
+fn test_formatting_example() {
+    let temp = NamedTempFile::new().unwrap();
+    scenarios::formatting::write_style_showcase(temp.path()).unwrap();
+
+    let rows = read_sheet(temp.path(), "Sheet1");
+    assert_eq!(rows[0], vec!["Style", "Example Value", "Description"]);
+    assert_eq!(
+        rows[1],
+        vec!["Default", "Plain text", "No special formatting"]
+    );
+    assert_eq!(rows[2][0], "NumberInteger");
+    assert_eq!(rows.last().unwrap()[0], "Profit");
+
+    let xml = read_entry(temp.path(), "xl/worksheets/sheet1.xml");
+    assert!(xml.contains(" s=\""), "no styled cells written");
+}
+
+#[test]
+fn test_multi_sheet_example() {
+    let temp = NamedTempFile::new().unwrap();
+    scenarios::multi_sheet::write_multi_sheet(temp.path()).unwrap();
+
+    let reader = ExcelReader::open(temp.path()).unwrap();
+    assert_eq!(reader.sheet_names(), scenarios::multi_sheet::SHEETS);
+
+    for sheet in scenarios::multi_sheet::SHEETS {
+        assert_eq!(read_sheet(temp.path(), sheet).len(), 4, "sheet {}", sheet);
+    }
+    assert_eq!(read_sheet(temp.path(), "Employees")[3][1], "Carol");
+}
+
+#[test]
+fn test_csv_products_example() {
+    let temp = tempfile::Builder::new()
+        .suffix(".csv.zst")
+        .tempfile()
+        .unwrap();
+    let written =
+        scenarios::csv::write_products(temp.path(), CompressionMethod::Zstd, 3, ROWS).unwrap();
+    assert_eq!(written, ROWS as u64 + 1);
+
+    let mut reader = CsvReader::open(temp.path()).unwrap();
+    let rows: Vec<_> = reader.rows().collect::<Result<_, _>>().unwrap();
+    assert_eq!(rows.len(), ROWS + 1);
+    assert_eq!(rows[0], vec!["ID", "Product", "Price", "Quantity"]);
+    assert_eq!(rows[ROWS], vec!["999", "Product_999", "89.01", "50"]);
+}
+
+#[test]
+fn test_csv_edge_cases_example() {
+    let temp = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+    let written = scenarios::csv::write_edge_cases(temp.path()).unwrap();
+    assert_eq!(written, 6);
+
+    let content = std::fs::read_to_string(temp.path()).unwrap();
+    assert!(content.contains("\"a,b,c\""));
+    assert!(content.contains(r#""Say ""Hello""""#));
+    assert!(content.contains("\"Line 1\nLine 2\nLine 3\""));
+}
+
+#[cfg(feature = "cloud-http")]
+mod http {
+    use super::*;
+    use excelstream::cloud::{HttpExcelStreamWriter, HttpExcelWriter};
+    use scenarios::users::{user_row, HEADER};
+    use std::io::Write;
+
+    fn check_users(bytes: &[u8]) {
+        let mut temp = NamedTempFile::new().unwrap();
+        temp.write_all(bytes).unwrap();
+
+        let rows = read_sheet(temp.path(), "Sheet1");
+        assert_eq!(rows.len(), ROWS + 1);
+        assert_eq!(rows[0], HEADER);
+        assert_eq!(rows[ROWS][1], format!("User_{}", ROWS));
+    }
+
+    #[test]
+    fn test_http_large_dataset_example() {
+        let mut writer = HttpExcelWriter::with_compression(6);
+        writer.write_header_bold(HEADER).unwrap();
+        for id in 1..=ROWS as i64 {
+            writer.write_row_typed(&user_row(id)).unwrap();
+        }
+
+        check_users(&writer.finish().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_http_stream_large_dataset_example() {
+        let (mut writer, mut stream) = HttpExcelStreamWriter::new();
+        let collect = tokio::spawn(async move {
+            let mut bytes = Vec::new();
+            while let Some(chunk) = stream.next_chunk().await {
+                bytes.extend_from_slice(&chunk.unwrap());
+            }
+            bytes
+        });
+
+        writer.write_header_bold(HEADER).await.unwrap();
+        for id in 1..=ROWS as i64 {
+            writer.write_row_typed(&user_row(id)).await.unwrap();
+        }
+        writer.finish().await.unwrap();
+
+        check_users(&collect.await.unwrap());
+    }
+}
+
+#[cfg(feature = "cloud-s3")]
+mod s3 {
+    use super::*;
+    use excelstream::cloud::{S3ExcelReader, S3ExcelWriter};
+    use scenarios::sales::{sales_row, write_to_s3, HEADER};
+
+    #[tokio::test]
+    #[ignore = "needs an S3 emulator (EXCELSTREAM_S3_ENDPOINT, EXCELSTREAM_S3_BUCKET)"]
+    async fn test_s3_streaming_example() {
+        let endpoint = emulator_env("EXCELSTREAM_S3_ENDPOINT");
+        let bucket = emulator_env("EXCELSTREAM_S3_BUCKET");
+
+        let sdk_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_sdk_s3::config::Region::new("us-east-1"))
+            .load()
+            .await;
+        let s3_config = aws_sdk_s3::config::Builder::from(&sdk_config)
+            .endpoint_url(endpoint)
+            .force_path_style(true)
+            .build();
+        let client = aws_sdk_s3::Client::from_conf(s3_config);
+        let key = "examples-test/sales.xlsx";
+
+        let mut writer = S3ExcelWriter::builder()
+            .bucket(&bucket)
+            .key(key)
+            .build_with_client(client.clone())
+            .await
+            .unwrap();
+        write_to_s3(&mut writer, ROWS).await.unwrap();
+        writer.save().await.unwrap();
+
+        let mut reader = S3ExcelReader::from_s3_client(client, &bucket, key)
+            .await
+            .unwrap();
+        let rows: Vec<_> = reader
+            .rows_by_index(0)
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(rows.len(), ROWS + 1);
+        assert_eq!(rows[0], HEADER);
+        assert_eq!(rows[ROWS][..2], sales_row(ROWS - 1)[..2]);
+    }
+}
+
+#[cfg(feature = "cloud-gcs")]
+mod gcs {
+    use super::*;
+    use excelstream::cloud::{GCSExcelReader, GCSExcelWriter};
+    use google_cloud_storage::client::{Client, ClientConfig};
+    use scenarios::sales::{sales_row, write_to_gcs, HEADER};
+
+    #[tokio::test]
+    #[ignore = "needs a GCS emulator (STORAGE_EMULATOR_HOST, EXCELSTREAM_GCS_BUCKET)"]
+    async fn test_gcs_streaming_example() {
+        let host = emulator_env("STORAGE_EMULATOR_HOST");
+        let bucket = emulator_env("EXCELSTREAM_GCS_BUCKET");
+
+        let config = ClientConfig {
+            storage_endpoint: host,
+            ..ClientConfig::default()
+        }
+        .anonymous();
+        let client = Client::new(config);
+        let object = "examples-test/sales.xlsx";
+
+        let mut writer = GCSExcelWriter::builder()
+            .bucket(&bucket)
+            .object(object)
+            .build_with_client(client.clone())
+            .await
+            .unwrap();
+        write_to_gcs(&mut writer, ROWS).await.unwrap();
+        writer.save().await.unwrap();
+
+        let mut reader = GCSExcelReader::from_gcs_client(client, &bucket, object)
+            .await
+            .unwrap();
+        let rows: Vec<_> = reader
+            .rows_by_index(0)
+            .unwrap()
+            .map(|row| row.unwrap().to_strings())
+            .collect();
+        assert_eq!(rows.len(), ROWS + 1);
+        assert_eq!(rows[0], HEADER);
+        assert_eq!(rows[ROWS][..2], sales_row(ROWS - 1)[..2]);
+    }
+}
@@ -0,0 +1,143 @@
+"""Rebuild the producer fixtures in this directory.
+
+Each workbook is modelled on the package layout of the named producer's
+exports (part names, namespace prefixes, relationship targets and
+attributes), trimmed to a few cells. Replace a file with a genuine export
+whenever one showing the same layout is at hand. Run from the repository
+root:
+
+    python3 tests/fixtures/make_fixtures.py
+"""
+
+import zipfile
+from pathlib import Path
+
+HERE = Path(__file__).parent
+
+MAIN = "http://schemas.openxmlformats.org/spreadsheetml/2006/main"
+REL = "http://schemas.openxmlformats.org/officeDocument/2006/relationships"
+PKG_REL = "http://schemas.openxmlformats.org/package/2006/relationships"
+CT = "http://schemas.openxmlformats.org/package/2006/content-types"
+SHEET_CT = "application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"
+WORKBOOK_CT = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"
+STYLES_CT = "application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"
+SST_CT = "application/vnd.openxmlformats-officedocument.spreadsheetml.sharedStrings+xml"
+RELS_CT = "application/vnd.openxmlformats-package.relationships+xml"
+
+MINIMAL_STYLES = (
+    f'<styleSheet xmlns="{MAIN}"><fonts count="1"><font><sz val="10"/><name val="Arial"/></font></fonts>'
+    '<fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills>'
+    '<borders count="1"><border/></borders><cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>'
+    '<cellXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/></cellXfs>'
+    '<cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles></styleSheet>'
+)
+
+
+def write(name, parts):
+    with zipfile.ZipFile(HERE / name, "w", zipfile.ZIP_DEFLATED) as zf:
+        for part, xml in parts:
+            info = zipfile.ZipInfo(part, date_time=(1980, 1, 1, 0, 0, 0))
+            info.compress_type = zipfile.ZIP_DEFLATED
+            zf.writestr(info, xml)
+
+
+def google_sheets():
+    # Inline strings only, no sharedStrings.xml and no <dimension>
+    decl = '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>'
+    write(
+        "google_sheets.xlsx",
+        [
+            ("[Content_Types].xml", decl + f'<Types xmlns="{CT}"><Default ContentType="{RELS_CT}" Extension="rels"/>'
+             f'<Default ContentType="application/xml" Extension="xml"/>'
+             f'<Override ContentType="{STYLES_CT}" PartName="/xl/styles.xml"/>'
+             f'<Override ContentType="{SHEET_CT}" PartName="/xl/worksheets/sheet1.xml"/>'
+             f'<Override ContentType="{WORKBOOK_CT}" PartName="/xl/workbook.xml"/></Types>'),
+            ("_rels/.rels", decl + f'<Relationships xmlns="{PKG_REL}"><Relationship Id="rId1" '
+             f'Type="{REL}/officeDocument" Target="xl/workbook.xml"/></Relationships>'),
+            ("xl/_rels/workbook.xml.rels", decl + f'<Relationships xmlns="{PKG_REL}">'
+             f'<Relationship Id="rId1" Type="{REL}/styles" Target="styles.xml"/>'
+             f'<Relationship Id="rId3" Type="{REL}/worksheet" Target="worksheets/sheet1.xml"/></Relationships>'),
+            ("xl/styles.xml", decl + MINIMAL_STYLES),
+            ("xl/workbook.xml", decl + f'<workbook xmlns="{MAIN}" xmlns:r="{REL}"><workbookPr/>'
+             '<sheets><sheet state="visible" name="Data" sheetId="1" r:id="rId3"/></sheets><definedNames/>'
+             '<calcPr/></workbook>'),
+            ("xl/worksheets/sheet1.xml", decl + f'<worksheet xmlns="{MAIN}" xmlns:r="{REL}">'
+             '<sheetPr><outlinePr summaryBelow="0" summaryRight="0"/></sheetPr>'
+             '<sheetViews><sheetView workbookViewId="0"/></sheetViews>'
+             '<sheetFormatPr customHeight="1" defaultColWidth="12.63" defaultRowHeight="15.75"/>'
+             '<sheetData>'
+             '<row r="1"><c r="A1" s="0" t="inlineStr"><is><t>Name</t></is></c>'
+             '<c r="B1" s="0" t="inlineStr"><is><t xml:space="preserve">Note </t></is></c></row>'
+             '<row r="2"><c r="A2" s="0" t="inlineStr"><is><r><t>Al</t></r><r><rPr><b/></rPr><t>ice</t></r></is></c>'
+             '<c r="B2" s="0" t="inlineStr"><is><t>a &amp; b</t></is></c><c r="C2"><v>42</v></c></row>'
+             '</sheetData></worksheet>'),
+        ],
+    )
+
+
+def poi():
+    # SpreadsheetML bound to a prefix, absolute part names in the rels,
+    # shared strings under a non-default name and cells without references
+    decl = '<?xml version="1.0" encoding="utf-8"?>'
+    write(
+        "poi_prefixed.xlsx",
+        [
+            ("[Content_Types].xml", decl + f'<Types xmlns="{CT}"><Default Extension="xml" ContentType="application/xml"/>'
+             f'<Default Extension="rels" ContentType="{RELS_CT}"/>'
+             f'<Override PartName="/xl/workbook.xml" ContentType="{WORKBOOK_CT}"/>'
+             f'<Override PartName="/xl/strings.xml" ContentType="{SST_CT}"/>'
+             f'<Override PartName="/xl/worksheets/sheet1.xml" ContentType="{SHEET_CT}"/></Types>'),
+            ("_rels/.rels", decl + f'<Relationships xmlns="{PKG_REL}"><Relationship Type="{REL}/officeDocument" '
+             'Target="/xl/workbook.xml" Id="R0c3e"/></Relationships>'),
+            ("xl/workbook.xml", decl + f'<x:workbook xmlns:x="{MAIN}" xmlns:rel="{REL}"><x:sheets>'
+             '<x:sheet name="Report &amp; Notes" sheetId="1" rel:id="R5f2"/></x:sheets></x:workbook>'),
+            ("xl/_rels/workbook.xml.rels", decl + f'<Relationships xmlns="{PKG_REL}">'
+             f'<Relationship Type="{REL}/sharedStrings" Target="/xl/strings.xml" Id="R9a1"/>'
+             f'<Relationship Type="{REL}/worksheet" Target="/xl/worksheets/sheet1.xml" Id="R5f2"/></Relationships>'),
+            ("xl/strings.xml", decl + f'<x:sst xmlns:x="{MAIN}" count="1" uniqueCount="1">'
+             '<x:si><x:t>Region</x:t></x:si></x:sst>'),
+            ("xl/worksheets/sheet1.xml", decl + f'<x:worksheet xmlns:x="{MAIN}"><x:sheetData>'
+             '<x:row r="1"><x:c t="s"><x:v>0</x:v></x:c><x:c t="inlineStr"><x:is><x:t>Total</x:t></x:is></x:c></x:row>'
+             '<x:row r="2"><x:c t="inlineStr"><x:is><x:t>North</x:t></x:is></x:c><x:c><x:v>1.5</x:v></x:c>'
+             '<x:c t="b"><x:v>1</x:v></x:c></x:row></x:sheetData></x:worksheet>'),
+        ],
+    )
+
+
+def libreoffice():
+    # Relationship target that leaves xl/ through "../" and "./" segments
+    decl = '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    write(
+        "libreoffice_relative.xlsx",
+        [
+            ("_rels/.rels", decl + f'<Relationships xmlns="{PKG_REL}"><Relationship Id="rId1" '
+             f'Type="{REL}/officeDocument" Target="xl/workbook.xml"/></Relationships>'),
+            ("[Content_Types].xml", decl + f'<Types xmlns="{CT}"><Default Extension="xml" ContentType="application/xml"/>'
+             f'<Default Extension="rels" ContentType="{RELS_CT}"/>'
+             f'<Override PartName="/xl/workbook.xml" ContentType="{WORKBOOK_CT}"/>'
+             f'<Override PartName="/xl/styles.xml" ContentType="{STYLES_CT}"/>'
+             f'<Override PartName="/sheets/first.xml" ContentType="{SHEET_CT}"/></Types>'),
+            ("xl/_rels/workbook.xml.rels", decl + f'<Relationships xmlns="{PKG_REL}">'
+             f'<Relationship Id="rId1" Type="{REL}/styles" Target="styles.xml"/>'
+             f'<Relationship Id="rId2" Type="{REL}/worksheet" Target="../sheets/./first.xml"/></Relationships>'),
+            ("xl/workbook.xml", decl + f'<workbook xmlns="{MAIN}" xmlns:r="{REL}">'
+             '<fileVersion appName="Calc"/><workbookPr backupFile="false" showObjects="all" date1904="false"/>'
+             '<bookViews><workbookView activeTab="0"/></bookViews>'
+             '<sheets><sheet name="Sheet1" sheetId="1" state="visible" r:id="rId2"/></sheets>'
+             '<calcPr iterateCount="100" refMode="A1" iterate="false" iterateDelta="0.001"/></workbook>'),
+            ("xl/styles.xml", decl + MINIMAL_STYLES),
+            ("sheets/first.xml", decl + f'<worksheet xmlns="{MAIN}" xmlns:r="{REL}">'
+             '<sheetPr filterMode="false"><pageSetUpPr fitToPage="false"/></sheetPr><dimension ref="B1"/>'
+             '<sheetFormatPr defaultColWidth="11.53515625" defaultRowHeight="12.8" zeroHeight="false" outlineLevelRow="0" outlineLevelCol="0"/>'
+             '<sheetData><row r="1" customFormat="false" ht="12.8" hidden="false" customHeight="false" outlineLevel="0" collapsed="false">'
+             '<c r="B1" s="0" t="inlineStr"><is><t>x</t></is></c></row></sheetData>'
+             '<pageMargins left="0.7875" right="0.7875" top="1.05277777777778" bottom="1.05277777777778" header="0.7875" footer="0.7875"/>'
+             '</worksheet>'),
+        ],
+    )
+
+
+if __name__ == "__main__":
+    google_sheets()
+    poi()
+    libreoffice()
@@ -477,3 +477,696 @@ fn test_multiple_sheets_with_column_widths() {
         assert!(sheets.len() >= 2);
     }
 }
+
+#[cfg(feature = "decimal")]
+#[test]
+fn test_decimal_roundtrip() {
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let temp = NamedTempFile::new().unwrap();
+    let path = temp.path().to_string_lossy().to_string();
+
+    let values = ["1234.56", "0.10", "-98765.4321"];
+
+    {
+        let mut writer = ExcelWriter::new(&path).unwrap();
+        let cells: Vec<CellValue> = values
+            .iter()
+            .map(|v| CellValue::Decimal(Decimal::from_str(v).unwrap()))
+            .collect();
+        writer.write_row_typed(&cells).unwrap();
+        writer.save().unwrap();
+    }
+
+    {
+        let mut reader = ExcelReader::open(&path).unwrap();
+        let rows: Vec<_> = reader
+            .rows_by_index(0)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(
+                rows[0].get(i).unwrap().as_decimal(),
+                Some(Decimal::from_str(v).unwrap())
+            );
+        }
+    }
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn test_decimal_currency_column_and_exact_read() {
+    use excelstream::fast_writer::StreamingZipReader;
+    use excelstream::streaming_reader::{ReadOptions, StreamingReader};
+    use excelstream::types::CellStyle;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let temp = NamedTempFile::new().unwrap();
+    let price = Decimal::from_str("19.99").unwrap();
+
+    {
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer
+            .set_column_style(1, CellStyle::NumberCurrency)
+            .unwrap();
+        writer
+            .write_row_typed(&[CellValue::Decimal(price), CellValue::Decimal(price)])
+            .unwrap();
+        writer.save().unwrap();
+    }
+
+    // Decimals default to #,##0.00 unless the column has a style of its own
+    let mut zip = StreamingZipReader::open(temp.path()).unwrap();
+    let sheet =
+        String::from_utf8(zip.read_entry_by_name("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+    assert!(sheet.contains(r#"<c r="A1" s="3" t="n"><v>19.99</v></c>"#));
+    assert!(sheet.contains(r#"<c r="B1" s="4" t="n"><v>19.99</v></c>"#));
+
+    let mut reader = StreamingReader::open(temp.path()).unwrap();
+    let row = reader.rows("Sheet1").unwrap().next().unwrap().unwrap();
+    assert_eq!(row.cells[0], CellValue::Float(19.99));
+
+    let options = ReadOptions {
+        numbers_as_decimal: true,
+        ..ReadOptions::default()
+    };
+    let mut reader = StreamingReader::open_with_options(temp.path(), options).unwrap();
+    let row = reader.rows("Sheet1").unwrap().next().unwrap().unwrap();
+    assert_eq!(
+        row.cells,
+        vec![CellValue::Decimal(price), CellValue::Decimal(price)]
+    );
+}
+
+#[test]
+fn test_big_integer_roundtrip() {
+    let temp = NamedTempFile::new().unwrap();
+    let path = temp.path().to_string_lossy().to_string();
+
+    let ids = [u64::MAX, 12345678901234567890, 9007199254740993];
+
+    {
+        let mut writer = ExcelWriter::new(&path).unwrap();
+        for id in ids {
+            writer
+                .write_row_typed(&[CellValue::from(id), CellValue::Int(1)])
+                .unwrap();
+        }
+        writer.save().unwrap();
+    }
+
+    {
+        let mut reader = ExcelReader::open(&path).unwrap();
+        let rows: Vec<_> = reader
+            .rows_by_index(0)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(rows.len(), ids.len());
+        for (row, id) in rows.iter().zip(ids) {
+            // Every digit survives, not a 15-digit approximation
+            assert_eq!(row.get(0).unwrap().as_string(), id.to_string());
+            assert_eq!(row.get(1).unwrap().as_i64(), Some(1));
+        }
+    }
+}
+
+#[test]
+fn test_evaluate_formulas_on_read() {
+    let temp = NamedTempFile::new().unwrap();
+    let path = temp.path().to_string_lossy().to_string();
+
+    {
+        let mut writer = ExcelWriter::new(&path).unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::Int(10),
+                CellValue::Int(2),
+                CellValue::formula("=A1*B1"),
+            ])
+            .unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::Int(5),
+                CellValue::Int(4),
+                CellValue::formula_with_result("=A2*B2", CellValue::Int(0)),
+            ])
+            .unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::formula("=SUM(A1:A2)"),
+                CellValue::formula("=AVERAGE(B1:B2)"),
+                CellValue::formula("=IF(C1>C2,\"first\",\"second\")"),
+            ])
+            .unwrap();
+        writer.save().unwrap();
+    }
+
+    let read = |evaluate: bool| -> Vec<Vec<CellValue>> {
+        let mut reader = ExcelReader::open(&path).unwrap();
+        reader.evaluate_formulas(evaluate);
+        reader
+            .rows_by_index(0)
+            .unwrap()
+            .map(|row| row.unwrap().cells)
+            .collect()
+    };
+
+    // Without evaluation the stored results come back, stale or missing
+    let stored = read(false);
+    assert_eq!(stored[0][2], CellValue::Empty);
+    assert_eq!(stored[1][2], CellValue::Int(0));
+
+    let computed = read(true);
+    assert_eq!(computed[0][2], CellValue::Int(20));
+    assert_eq!(computed[1][2], CellValue::Int(20));
+    assert_eq!(
+        computed[2],
+        vec![
+            CellValue::Int(15),
+            CellValue::Int(3),
+            CellValue::String("second".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_rows_projected() {
+    let temp = NamedTempFile::new().unwrap();
+    let path = temp.path().to_string_lossy().to_string();
+
+    {
+        let mut writer = ExcelWriter::new(&path).unwrap();
+        writer
+            .write_header(["ID", "Name", "Notes", "Amount", "Date"])
+            .unwrap();
+        writer
+            .write_row(["1", "Alice", "a", "10.5", "2024-01-01"])
+            .unwrap();
+        writer
+            .write_row(["2", "Bob", "b", "20", "2024-01-02"])
+            .unwrap();
+        writer.save().unwrap();
+    }
+
+    let mut reader = ExcelReader::open(&path).unwrap();
+    let rows: Vec<Vec<String>> = reader
+        .rows_projected("Sheet1", &["Amount", "ID"])
+        .unwrap()
+        .map(|row| row.unwrap().to_strings())
+        .collect();
+    assert_eq!(
+        rows,
+        vec![vec!["Amount", "ID"], vec!["10.5", "1"], vec!["20", "2"],]
+    );
+
+    let names: Vec<Vec<String>> = reader
+        .rows_projected_by_index("Sheet1", &[1, 7])
+        .unwrap()
+        .map(|row| row.unwrap().to_strings())
+        .collect();
+    assert_eq!(names[2], vec!["Bob", ""]);
+
+    let err = reader.rows_projected("Sheet1", &["Missing"]).err().unwrap();
+    assert!(err.to_string().contains("Missing"));
+}
+
+#[test]
+fn test_rows_filtered() {
+    let temp = NamedTempFile::new().unwrap();
+    let path = temp.path().to_string_lossy().to_string();
+
+    {
+        let mut writer = ExcelWriter::new(&path).unwrap();
+        writer.write_header(["ID", "Status"]).unwrap();
+        for i in 1..=10 {
+            let status = if i % 3 == 0 { "FLAGGED" } else { "ok" };
+            writer.write_row([i.to_string().as_str(), status]).unwrap();
+            if i == 4 {
+                writer.write_row(["", ""]).unwrap();
+            }
+        }
+        writer.save().unwrap();
+    }
+
+    let flagged =
+        |cells: &[CellValue]| matches!(cells.get(1), Some(CellValue::String(s)) if s == "FLAGGED");
+    let mut reader = ExcelReader::open(&path).unwrap();
+
+    let rows: Vec<_> = reader
+        .rows_filtered("Sheet1", flagged)
+        .unwrap()
+        .map(|row| row.unwrap())
+        .collect();
+    // Indexes are sheet positions: header, rows 1-4, the blank row, rows 5-10
+    let indexes: Vec<u32> = rows.iter().map(|row| row.index).collect();
+    assert_eq!(indexes, vec![3, 7, 10]);
+    assert_eq!(rows[1].to_strings(), vec!["6", "FLAGGED"]);
+
+    let limited = reader
+        .rows_filtered("Sheet1", flagged)
+        .unwrap()
+        .limit(2)
+        .count();
+    assert_eq!(limited, 2);
+
+    let mut seen = 0;
+    let non_blank = reader
+        .rows_filtered("Sheet1", |_| {
+            seen += 1;
+            true
+        })
+        .unwrap()
+        .skip_blank_rows()
+        .count();
+    assert_eq!(non_blank, 11);
+    assert_eq!(seen, 11);
+}
+
+#[test]
+fn test_rows_filtered_sparse_sheet() {
+    use excelstream::fast_writer::StreamingZipWriter;
+
+    // Only sheet rows 1 and 10 are present in the XML
+    let temp = NamedTempFile::new().unwrap();
+    {
+        let mut zip = StreamingZipWriter::with_compression(temp.path(), 6).unwrap();
+        let parts = [
+            (
+                "xl/workbook.xml",
+                r#"<workbook><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#,
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData><row r="1"><c r="A1"><v>1</v></c></row><row r="10"><c r="A10"><v>10</v></c></row></sheetData></worksheet>"#,
+            ),
+        ];
+        for (name, xml) in parts {
+            zip.start_entry(name).unwrap();
+            zip.write_data(xml.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    let mut reader = ExcelReader::open(temp.path()).unwrap();
+    let indexes: Vec<u32> = reader
+        .rows_filtered("Sheet1", |_| true)
+        .unwrap()
+        .map(|row| row.unwrap().index)
+        .collect();
+    assert_eq!(indexes, vec![0, 9]);
+}
+
+#[test]
+fn test_read_sheets_parallel() {
+    let temp = NamedTempFile::new().unwrap();
+    let path = temp.path().to_string_lossy().to_string();
+
+    {
+        let mut writer = ExcelWriter::new(&path).unwrap();
+        for sheet in 1..=4 {
+            if sheet > 1 {
+                writer.add_sheet(&format!("Sheet{}", sheet)).unwrap();
+            }
+            for i in 0..sheet * 10 {
+                writer
+                    .write_row(["shared".to_string(), i.to_string()])
+                    .unwrap();
+            }
+        }
+        writer.save().unwrap();
+    }
+
+    let reader = ExcelReader::open(&path).unwrap();
+    let counts = reader
+        .read_sheets_parallel(&["Sheet4", "Sheet1", "Sheet3"], |name, rows| {
+            let mut count = 0;
+            for row in rows {
+                assert_eq!(row?.to_strings()[0], "shared", "{}", name);
+                count += 1;
+            }
+            Ok(count)
+        })
+        .unwrap();
+    assert_eq!(counts, vec![40, 10, 30]);
+
+    let err = reader
+        .read_sheets_parallel(&["Sheet1", "Nope"], |_, rows| Ok(rows.count()))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("Nope"));
+}
+
+#[test]
+fn test_read_from_bytes() {
+    use excelstream::streaming_reader::{ReadLimits, ReadOptions, StreamingReader};
+
+    let temp = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.write_header(["Name", "Note"]).unwrap();
+        writer.write_row(["Alice", "a & b"]).unwrap();
+        writer.add_sheet("Second").unwrap();
+        writer.write_row(["x"]).unwrap();
+        writer.save().unwrap();
+    }
+    let data = std::fs::read(temp.path()).unwrap();
+
+    let mut reader = StreamingReader::from_bytes(data.clone()).unwrap();
+    assert_eq!(reader.sheet_names(), vec!["Sheet1", "Second"]);
+    let rows: Vec<Vec<String>> = reader
+        .rows("Sheet1")
+        .unwrap()
+        .map(|row| row.unwrap().to_strings())
+        .collect();
+    assert_eq!(rows, vec![vec!["Name", "Note"], vec!["Alice", "a & b"]]);
+
+    let counts = reader
+        .read_sheets_parallel(&["Second", "Sheet1"], |_, rows| Ok(rows.count()))
+        .unwrap();
+    assert_eq!(counts, vec![1, 2]);
+
+    // The limits apply as for files
+    let options = ReadOptions {
+        limits: ReadLimits {
+            max_entry_size: 16,
+            ..ReadLimits::default()
+        },
+        ..ReadOptions::default()
+    };
+    assert!(StreamingReader::from_bytes_with_options(data.clone(), options).is_err());
+
+    let err = StreamingReader::from_bytes(data[..data.len() / 2].to_vec())
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("Failed to open ZIP"), "{}", err);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_write_record_with_header() {
+    #[derive(serde::Serialize)]
+    struct Product {
+        sku: String,
+        qty: u32,
+        price: Option<f64>,
+    }
+
+    let temp = NamedTempFile::new().unwrap();
+    let path = temp.path().to_string_lossy().to_string();
+
+    {
+        let mut writer = ExcelWriter::new(&path).unwrap();
+        writer
+            .write_records([
+                Product {
+                    sku: "A-1".to_string(),
+                    qty: 3,
+                    price: Some(9.5),
+                },
+                Product {
+                    sku: "B-2".to_string(),
+                    qty: 0,
+                    price: None,
+                },
+            ])
+            .unwrap();
+        writer.save().unwrap();
+    }
+
+    {
+        let mut reader = ExcelReader::open(&path).unwrap();
+        let rows: Vec<_> = reader
+            .rows_by_index(0)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].to_strings(), vec!["sku", "qty", "price"]);
+        assert_eq!(rows[1].to_strings(), vec!["A-1", "3", "9.5"]);
+        assert!(rows[2].get(2).is_none_or(|c| c.is_empty()));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_rows_as_roundtrip() {
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct Product {
+        #[serde(rename = "SKU")]
+        sku: String,
+        qty: u32,
+        price: Option<f64>,
+    }
+
+    let products = vec![
+        Product {
+            sku: "A-1".to_string(),
+            qty: 3,
+            price: Some(9.5),
+        },
+        Product {
+            sku: "B-2".to_string(),
+            qty: 0,
+            price: None,
+        },
+    ];
+
+    let temp = NamedTempFile::new().unwrap();
+    let path = temp.path().to_string_lossy().to_string();
+
+    {
+        let mut writer = ExcelWriter::new(&path).unwrap();
+        writer.write_records(&products).unwrap();
+        writer.save().unwrap();
+    }
+
+    let mut reader = ExcelReader::open(&path).unwrap();
+    let read: Vec<Product> = reader
+        .rows_as("Sheet1")
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(read, products);
+}
+
+#[test]
+fn test_validate_package() {
+    use excelstream::fast_writer::StreamingZipWriter;
+    use excelstream::validate::{validate_package, Severity};
+    use excelstream::StringMode;
+
+    let temp = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ExcelWriter::new(temp.path()).unwrap();
+        writer.set_string_mode(StringMode::SharedDeduped).unwrap();
+        writer.write_header_bold(["Name", "Link"]).unwrap();
+        writer
+            .write_row_typed(&[
+                CellValue::String("Docs".to_string()),
+                CellValue::Hyperlink {
+                    url: "https://example.com".to_string(),
+                    text: "example".to_string(),
+                },
+            ])
+            .unwrap();
+        writer.add_sheet("Second").unwrap();
+        writer.write_row(["Name", "x"]).unwrap();
+        writer.save().unwrap();
+    }
+    let report = validate_package(temp.path()).unwrap();
+    assert!(report.is_valid(), "{}", report);
+
+    // Hand-made package with a dangling relationship, rows out of order and
+    // a shared string index past the table
+    let broken = NamedTempFile::new().unwrap();
+    {
+        let mut zip = StreamingZipWriter::with_compression(broken.path(), 6).unwrap();
+        let parts = [
+            (
+                "[Content_Types].xml",
+                r#"<Types><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/><Override PartName="/xl/sharedStrings.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sharedStrings+xml"/></Types>"#,
+            ),
+            (
+                "_rels/.rels",
+                r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#,
+            ),
+            (
+                "xl/workbook.xml",
+                r#"<workbook><sheets><sheet name="Data" sheetId="1" r:id="rId1"/><sheet name="Gone" sheetId="2" r:id="rId9"/></sheets></workbook>"#,
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/sharedStrings" Target="sharedStrings.xml"/><Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#,
+            ),
+            (
+                "xl/sharedStrings.xml",
+                r#"<sst count="1" uniqueCount="1"><si><t>a</t></si></sst>"#,
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData><row r="2"><c r="A2" t="s"><v>0</v></c><c r="B2" t="s"><v>4</v></c></row><row r="1"><c r="A1"><v>1</v></c></row></sheetData></worksheet>"#,
+            ),
+        ];
+        for (name, xml) in parts {
+            zip.start_entry(name).unwrap();
+            zip.write_data(xml.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    let report = validate_package(broken.path()).unwrap();
+    assert!(!report.is_valid());
+    let messages: Vec<String> = report.errors().map(|issue| issue.to_string()).collect();
+    assert!(
+        messages.contains(
+            &"error: xl/_rels/workbook.xml.rels: relationship rId3 points to missing part xl/styles.xml"
+                .to_string()
+        ),
+        "{}",
+        report
+    );
+    assert!(messages
+        .iter()
+        .any(|m| m.contains("sheet 'Gone' refers to unknown relationship rId9")));
+    assert!(messages.contains(
+        &"error: xl/worksheets/sheet1.xml B2: shared string 4 out of range (1 strings)".to_string()
+    ));
+    assert!(messages
+        .contains(&"error: xl/worksheets/sheet1.xml row 1: row 1 comes after row 2".to_string()));
+    assert_eq!(report.warnings().count(), 0);
+    assert!(report
+        .issues
+        .iter()
+        .all(|issue| issue.severity == Severity::Error));
+}
+
+#[test]
+fn test_read_parts_with_bom_and_multibyte_text() {
+    use excelstream::fast_writer::StreamingZipWriter;
+
+    // Long enough to cross many read chunks, so some multi-byte characters
+    // are split between two of them
+    let text = |i: usize| format!("Tiếng Việt 日本語 ✓ {}", i);
+    let mut sheet =
+        String::from("\u{feff}<?xml version=\"1.0\" encoding=\"UTF-8\"?><worksheet><sheetData>");
+    for i in 1..=5000 {
+        sheet.push_str(&format!(
+            r#"<row r="{i}"><c r="A{i}" t="inlineStr"><is><t>{}</t></is></c><c r="B{i}" t="s"><v>0</v></c></row>"#,
+            text(i)
+        ));
+    }
+    sheet.push_str("</sheetData></worksheet>");
+
+    let temp = NamedTempFile::new().unwrap();
+    {
+        let mut zip = StreamingZipWriter::with_compression(temp.path(), 6).unwrap();
+        let parts = [
+            (
+                "xl/workbook.xml",
+                "\u{feff}<workbook><sheets><sheet name=\"Dữ liệu\" sheetId=\"1\" r:id=\"rId1\"/></sheets></workbook>".to_string(),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                "\u{feff}<Relationships><Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/></Relationships>".to_string(),
+            ),
+            (
+                "xl/sharedStrings.xml",
+                "\u{feff}<sst count=\"1\" uniqueCount=\"1\"><si><t>Ấn</t></si></sst>".to_string(),
+            ),
+            ("xl/worksheets/sheet1.xml", sheet),
+        ];
+        for (name, xml) in parts {
+            zip.start_entry(name).unwrap();
+            zip.write_data(xml.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    let mut reader = ExcelReader::open(temp.path()).unwrap();
+    assert_eq!(reader.sheet_names(), vec!["Dữ liệu"]);
+    let mut count = 0;
+    for (i, row) in reader.rows("Dữ liệu").unwrap().enumerate() {
+        assert_eq!(
+            row.unwrap().to_strings(),
+            vec![text(i + 1), "Ấn".to_string()]
+        );
+        count += 1;
+    }
+    assert_eq!(count, 5000);
+}
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+fn test_read_google_sheets_export() {
+    // Inline strings only, no sharedStrings.xml and no <dimension>
+    let mut reader = ExcelReader::open(fixture("google_sheets.xlsx")).unwrap();
+    assert_eq!(reader.sheet_names(), vec!["Data"]);
+    assert_eq!(reader.sheet_metadata().unwrap()[0].dimension, None);
+    assert_eq!(reader.dimensions("Data").unwrap(), (2, 3));
+
+    let rows: Vec<Vec<String>> = reader
+        .rows("Data")
+        .unwrap()
+        .map(|r| r.unwrap().to_strings())
+        .collect();
+    assert_eq!(
+        rows,
+        vec![vec!["Name", "Note "], vec!["Alice", "a & b", "42"]]
+    );
+}
+
+#[test]
+fn test_read_poi_export() {
+    // Prefixed SpreadsheetML, absolute rels targets and cells without
+    // references
+    let mut reader = ExcelReader::open(fixture("poi_prefixed.xlsx")).unwrap();
+    assert_eq!(reader.sheet_names(), vec!["Report & Notes"]);
+
+    let rows: Vec<Vec<CellValue>> = reader
+        .rows("Report & Notes")
+        .unwrap()
+        .map(|r| r.unwrap().cells)
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            vec![
+                CellValue::String("Region".to_string()),
+                CellValue::String("Total".to_string())
+            ],
+            vec![
+                CellValue::String("North".to_string()),
+                CellValue::Float(1.5),
+                CellValue::Bool(true)
+            ],
+        ]
+    );
+}
+
+#[test]
+fn test_read_libreoffice_export() {
+    // Worksheet reached through a "../" relationship target
+    let mut reader = ExcelReader::open(fixture("libreoffice_relative.xlsx")).unwrap();
+    let rows: Vec<Vec<String>> = reader
+        .rows("Sheet1")
+        .unwrap()
+        .map(|r| r.unwrap().to_strings())
+        .collect();
+    assert_eq!(rows, vec![vec!["", "x"]]);
+}
@@ -11,6 +11,13 @@ use wasm_bindgen::prelude::*;
 mod local_csv;
 use local_csv::CsvParser;
 
+// Whole .xlsx files are read by excelstream's StreamingReader, built
+// without its native (zstd) dependencies.
+use excelstream::streaming_reader::StreamingReader;
+// Generated .xlsx files are built by HttpExcelWriter's in-memory workbook
+use excelstream::cloud::HttpExcelWriter;
+use excelstream::types::CellValue;
+
 thread_local! {
     static PARSER: RefCell<Option<CsvParser>> = RefCell::new(None);
     static CALLBACK: RefCell<Option<Function>> = RefCell::new(None);
@@ -37,7 +44,7 @@ pub fn feed_line(line: &str) {
             let fields = parser.parse_line(line);
             CALLBACK.with(|c| {
                 if let Some(cb) = &*c.borrow() {
-                    let jsv = to_value(&fields).unwrap_or_else(|_| JsValue::NULL);
+                    let jsv = to_value(&fields).unwrap_or(JsValue::NULL);
                     let _ = cb.call1(&JsValue::NULL, &jsv);
                 }
             });
@@ -45,6 +52,41 @@ pub fn feed_line(line: &str) {
     });
 }
 
+/// Call the registered callback for each record, outside the PARSER borrow
+/// so the callback may feed more data
+fn emit_records(records: Vec<Vec<String>>) {
+    CALLBACK.with(|c| {
+        if let Some(cb) = &*c.borrow() {
+            for fields in records {
+                let jsv = to_value(&fields).unwrap_or(JsValue::NULL);
+                let _ = cb.call1(&JsValue::NULL, &jsv);
+            }
+        }
+    });
+}
+
+/// Feed raw bytes (any chunk size) to the streaming parser. Records can span
+/// chunks and quoted fields may contain newlines; the callback is called for
+/// each record completed by this chunk.
+#[wasm_bindgen]
+pub fn feed_chunk(bytes: &[u8]) {
+    let mut records = Vec::new();
+    PARSER.with(|p| {
+        if let Some(parser) = p.borrow_mut().as_mut() {
+            parser.feed(bytes, |fields| records.push(fields));
+        }
+    });
+    emit_records(records);
+}
+
+/// Emit the last record if the input didn't end with a newline and reset the
+/// parser for new input
+#[wasm_bindgen]
+pub fn flush() {
+    let record = PARSER.with(|p| p.borrow_mut().as_mut().and_then(|parser| parser.finish()));
+    emit_records(record.into_iter().collect());
+}
+
 #[wasm_bindgen]
 pub fn parse_csv_full(contents: &str) -> JsValue {
     // Simple convenience: parse full CSV string into array of arrays
@@ -163,3 +205,125 @@ pub fn parse_sheet_xml(xml: &str) -> JsValue {
     }
     to_value(&rows).unwrap_or_else(|_| JsValue::NULL)
 }
+
+// --- XLSX straight from the file bytes (unzip and SST lookup inside WASM) ---
+
+/// Workbook opened from the raw bytes of an .xlsx file
+#[wasm_bindgen]
+pub struct XlsxReader {
+    reader: StreamingReader,
+}
+
+fn js_error(e: excelstream::ExcelError) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+#[wasm_bindgen]
+impl XlsxReader {
+    /// `data` is an ArrayBuffer or Uint8Array holding the whole file
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &JsValue) -> Result<XlsxReader, JsError> {
+        let bytes = js_sys::Uint8Array::new(data).to_vec();
+        let reader = StreamingReader::from_bytes(bytes).map_err(js_error)?;
+        Ok(XlsxReader { reader })
+    }
+
+    pub fn sheet_names(&self) -> JsValue {
+        to_value(&self.reader.sheet_names()).unwrap_or(JsValue::NULL)
+    }
+
+    /// Call `callback(fields, index)` for each row of `sheet` as it is
+    /// decompressed, `index` being the 0-based row in the sheet; returning
+    /// `false` from the callback stops early. Returns the number of rows
+    /// passed to the callback.
+    pub fn stream_rows(&mut self, sheet: &str, callback: &Function) -> Result<u32, JsValue> {
+        let mut count = 0u32;
+        for row in self.reader.rows(sheet).map_err(js_error)? {
+            let row = row.map_err(js_error)?;
+            let jsv = to_value(&row.to_strings()).unwrap_or(JsValue::NULL);
+            // What the callback throws is re-thrown
+            let result = callback.call2(&JsValue::NULL, &jsv, &JsValue::from(row.index))?;
+            count += 1;
+            if result.as_bool() == Some(false) {
+                break;
+            }
+        }
+        Ok(count)
+    }
+
+    /// All rows of `sheet` as an array of string arrays
+    pub fn read_sheet(&mut self, sheet: &str) -> Result<JsValue, JsError> {
+        let rows = self
+            .reader
+            .rows(sheet)
+            .map_err(js_error)?
+            .map(|row| row.map(|row| row.to_strings()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(js_error)?;
+        Ok(to_value(&rows).unwrap_or(JsValue::NULL))
+    }
+}
+
+// --- XLSX generation (download built client-side) ---
+
+thread_local! {
+    static WORKBOOK: RefCell<Option<HttpExcelWriter>> = const { RefCell::new(None) };
+}
+
+fn with_workbook<T>(
+    f: impl FnOnce(&mut HttpExcelWriter) -> excelstream::Result<T>,
+) -> Result<T, JsError> {
+    WORKBOOK.with(|w| match w.borrow_mut().as_mut() {
+        Some(workbook) => f(workbook).map_err(js_error),
+        None => Err(JsError::new("No workbook; call create_workbook() first")),
+    })
+}
+
+/// Start a new workbook, dropping any unfinished one
+#[wasm_bindgen]
+pub fn create_workbook() {
+    WORKBOOK.with(|w| {
+        *w.borrow_mut() = Some(HttpExcelWriter::new());
+    });
+}
+
+/// Start a new worksheet; following rows go to it
+#[wasm_bindgen]
+pub fn add_worksheet(name: &str) -> Result<(), JsError> {
+    with_workbook(|workbook| workbook.add_worksheet(name))
+}
+
+/// Write one row of strings, numbers, booleans and null/undefined (empty)
+#[wasm_bindgen]
+pub fn write_row(values: &JsValue) -> Result<(), JsError> {
+    if !js_sys::Array::is_array(values) {
+        return Err(JsError::new("write_row expects an array"));
+    }
+    let cells: Vec<CellValue> = js_sys::Array::from(values)
+        .iter()
+        .map(|value| {
+            if let Some(s) = value.as_string() {
+                CellValue::String(s)
+            } else if let Some(n) = value.as_f64() {
+                CellValue::Float(n)
+            } else if let Some(b) = value.as_bool() {
+                CellValue::Bool(b)
+            } else if value.is_null() || value.is_undefined() {
+                CellValue::Empty
+            } else {
+                CellValue::String(String::from(js_sys::JsString::from(value)))
+            }
+        })
+        .collect();
+    with_workbook(|workbook| workbook.write_row_typed(&cells))
+}
+
+/// Close the workbook and return the .xlsx file bytes
+#[wasm_bindgen]
+pub fn finish() -> Result<js_sys::Uint8Array, JsError> {
+    let workbook = WORKBOOK
+        .with(|w| w.borrow_mut().take())
+        .ok_or_else(|| JsError::new("No workbook; call create_workbook() first"))?;
+    let bytes = workbook.finish().map_err(js_error)?;
+    Ok(js_sys::Uint8Array::from(bytes.as_slice()))
+}
@@ -1,8 +1,22 @@
 //! Minimal CSV parser copied for WASM adapter to avoid heavy native deps.
 /// CSV parser for reading CSV data (simplified, line-based)
+///
+/// `parse_line` handles one line at a time; `feed` / `finish` keep state
+/// between calls so records can span chunks and quoted fields can hold
+/// newlines.
 pub struct CsvParser {
     delimiter: u8,
     quote_char: u8,
+    // Incremental state for `feed`
+    fields: Vec<String>,
+    field: Vec<u8>,
+    in_quotes: bool,
+    /// Last byte closed a quoted section; another quote is an escaped quote
+    quote_closed: bool,
+    /// Bytes of the current record seen (blank lines are skipped)
+    in_record: bool,
+    /// Last record ended with \r; skip a following \n
+    skip_lf: bool,
 }
 
 impl CsvParser {
@@ -10,9 +24,78 @@ impl CsvParser {
         Self {
             delimiter,
             quote_char,
+            fields: Vec::new(),
+            field: Vec::new(),
+            in_quotes: false,
+            quote_closed: false,
+            in_record: false,
+            skip_lf: false,
         }
     }
 
+    /// Parse the next chunk of input, calling `on_record` for every record
+    /// completed by it. A partial record at the end is kept for the next call.
+    pub fn feed<F: FnMut(Vec<String>)>(&mut self, chunk: &[u8], mut on_record: F) {
+        for &byte in chunk {
+            if std::mem::take(&mut self.skip_lf) && byte == b'\n' {
+                continue;
+            }
+            let quote_closed = std::mem::take(&mut self.quote_closed);
+            if self.in_quotes {
+                if byte == self.quote_char {
+                    self.in_quotes = false;
+                    self.quote_closed = true;
+                } else {
+                    self.field.push(byte);
+                }
+                continue;
+            }
+            match byte {
+                b'\n' | b'\r' => {
+                    if self.in_record {
+                        on_record(self.take_record());
+                    }
+                    self.skip_lf = byte == b'\r';
+                }
+                _ if byte == self.quote_char => {
+                    if quote_closed {
+                        self.field.push(byte);
+                    }
+                    self.in_quotes = true;
+                    self.in_record = true;
+                }
+                _ if byte == self.delimiter => {
+                    let field = String::from_utf8_lossy(&self.field).into_owned();
+                    self.fields.push(field);
+                    self.field.clear();
+                    self.in_record = true;
+                }
+                _ => {
+                    self.field.push(byte);
+                    self.in_record = true;
+                }
+            }
+        }
+    }
+
+    /// Return the last record if the input didn't end with a newline, and
+    /// reset the parser for new input
+    pub fn finish(&mut self) -> Option<Vec<String>> {
+        let record = self.in_record.then(|| self.take_record());
+        self.in_quotes = false;
+        self.quote_closed = false;
+        self.skip_lf = false;
+        record
+    }
+
+    fn take_record(&mut self) -> Vec<String> {
+        let field = String::from_utf8_lossy(&self.field).into_owned();
+        self.fields.push(field);
+        self.field.clear();
+        self.in_record = false;
+        std::mem::take(&mut self.fields)
+    }
+
     pub fn parse_line(&self, line: &str) -> Vec<String> {
         let mut fields = Vec::new();
         let mut current = String::new();
@@ -42,3 +125,32 @@ impl CsvParser {
         fields
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_records_across_chunks() {
+        let input = "name,note\r\n\"Alice\",\"line 1\r\nline \"\"2\"\"\"\r\n\r\nBob,caf\u{e9}";
+        // Every split point, including inside \r\n, "" and multi-byte chars
+        for split in 0..=input.len() {
+            let (first, second) = input.as_bytes().split_at(split);
+            let mut parser = CsvParser::new(b',', b'"');
+            let mut records = Vec::new();
+            parser.feed(first, |r| records.push(r));
+            parser.feed(second, |r| records.push(r));
+            records.extend(parser.finish());
+            assert_eq!(
+                records,
+                vec![
+                    vec!["name", "note"],
+                    vec!["Alice", "line 1\r\nline \"2\""],
+                    vec!["Bob", "caf\u{e9}"],
+                ],
+                "split at {}",
+                split
+            );
+        }
+    }
+}